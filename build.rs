@@ -0,0 +1,29 @@
+//! Build script: records the resolved moonblokz-radio-lib version (and git revision)
+//! from Cargo.lock for the provenance embedded in exported files.
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let lock = std::fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let version = radio_lib_version(&lock).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=MOONBLOKZ_RADIO_LIB_VERSION={}", version);
+}
+
+/// Version of the moonblokz-radio-lib package in a Cargo.lock, e.g. `0.1.0 (1a2b3c4d5e6f)`.
+fn radio_lib_version(lock: &str) -> Option<String> {
+    let package = lock
+        .split("[[package]]")
+        .find(|package| package.contains("name = \"moonblokz-radio-lib\""))?;
+    let field = |key: &str| {
+        package
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(|value| value.trim().trim_matches('"').to_string())
+    };
+    let version = field("version = ")?;
+    let revision = field("source = ")
+        .and_then(|source| source.rsplit_once('#').map(|(_, rev)| rev.to_string()));
+    Some(match revision {
+        Some(rev) => format!("{} ({})", version, &rev[..rev.len().min(12)]),
+        None => version,
+    })
+}
@@ -0,0 +1,274 @@
+//! Parse JSON-lines (structured) telemetry into `LogEvent` data.
+//!
+//! Firmware with structured logging writes one JSON object per line:
+//!
+//! ```text
+//! {"timestamp":"2025-10-23T18:00:01Z","level":"INFO","node_id":3094,"event":"packet_sent","type":6,"sequence":30940779,"length":215,"packet":1,"packet_count":10}
+//! ```
+//!
+//! Common fields:
+//! - `timestamp` (or `ts`): RFC 3339 string or Unix epoch milliseconds
+//! - `node_id` (or `node`): reporting node
+//! - `level`: log level (defaults to INFO)
+//! - `message` (or `msg`): free text shown in the Log Stream tab
+//! - `event`: telemetry event name or its textual code (`packet_sent` / `TM1`, ...)
+//!
+//! Event fields use the names of the textual format (`sender`, `type`, `sequence`,
+//! `length`, `packet`, `packet_count`, `link_quality`, `probe_version`, `node_version`).
+
+use super::types::{LogEvent, RawLogLine};
+use crate::simulation::types::LogLevel;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+type JsonObject = Map<String, Value>;
+
+/// Fields not repeated in the Log Stream content of event lines.
+const COMMON_FIELDS: [&str; 8] = [
+    "timestamp",
+    "ts",
+    "level",
+    "node_id",
+    "node",
+    "target",
+    "message",
+    "msg",
+];
+
+/// Check whether a log line is a JSON object (structured telemetry).
+pub fn is_json_line(line: &str) -> bool {
+    line.trim_start().starts_with('{')
+}
+
+/// Parse a JSON log line and extract timestamp and event.
+///
+/// # Parameters
+///
+/// * `line` - A single JSON object line
+///
+/// # Returns
+///
+/// `Some((timestamp, event))` for telemetry events, `None` for plain log records
+/// and unparseable lines.
+pub fn parse_json_log_line(line: &str) -> Option<(DateTime<Utc>, LogEvent)> {
+    let object = parse_object(line)?;
+    let timestamp = parse_timestamp(&object)?;
+    let node_id = parse_node_id(&object)?;
+    let event = object.get("event")?.as_str()?;
+
+    let event = match event {
+        "packet_sent" | "TM1" => LogEvent::SendPacket {
+            node_id,
+            message_type: field_u8(&object, "type")?,
+            sequence: field_u32(&object, "sequence"),
+            packet_index: field_u8(&object, "packet").unwrap_or(1),
+            packet_count: field_u8(&object, "packet_count").unwrap_or(1),
+            length: field_usize(&object, "length").unwrap_or(0),
+        },
+        "packet_received" | "TM2" => LogEvent::ReceivePacket {
+            node_id,
+            sender_id: field_u32(&object, "sender")?,
+            message_type: field_u8(&object, "type")?,
+            sequence: field_u32(&object, "sequence"),
+            packet_index: field_u8(&object, "packet").unwrap_or(1),
+            packet_count: field_u8(&object, "packet_count").unwrap_or(1),
+            length: field_usize(&object, "length").unwrap_or(0),
+            link_quality: field_u8(&object, "link_quality").unwrap_or(0),
+        },
+        "measurement_started" | "TM3" => LogEvent::StartMeasurement {
+            node_id,
+            sequence: field_u32(&object, "sequence")?,
+        },
+        "message_received" | "TM4" => LogEvent::ReceivedFullMessage {
+            node_id,
+            sender_id: field_u32(&object, "sender")?,
+            message_type: field_u8(&object, "type")?,
+            sequence: field_u32(&object, "sequence")?,
+            length: field_usize(&object, "length").unwrap_or(0),
+        },
+        "crc_mismatch" | "TM5" => LogEvent::PacketCrcError {
+            node_id,
+            link_quality: field_u8(&object, "link_quality").unwrap_or(0),
+        },
+        "add_block_received" | "TM6" => LogEvent::AddBlockReceived {
+            node_id,
+            sender_id: field_u32(&object, "sender")?,
+            sequence: field_u32(&object, "sequence")?,
+            length: field_usize(&object, "length").unwrap_or(0),
+        },
+        "add_block_sent" | "TM7" => LogEvent::AddBlockSent {
+            node_id,
+            sender_id: field_u32(&object, "sender")?,
+            sequence: field_u32(&object, "sequence")?,
+            length: field_usize(&object, "length").unwrap_or(0),
+        },
+        "version" | "TM8" => LogEvent::VersionInfo {
+            node_id,
+            probe_version: field_u8(&object, "probe_version")?,
+            node_version: field_u8(&object, "node_version")?,
+        },
+        _ => return None,
+    };
+
+    Some((timestamp, event))
+}
+
+/// Parse a JSON log line for the Log Stream tab.
+///
+/// The content is the `message` field; event records without a message are shown
+/// as `event: field: value, ...` like the textual telemetry lines.
+///
+/// # Parameters
+///
+/// * `line` - A single JSON object line
+///
+/// # Returns
+///
+/// `Some((node_id, raw_log_line))` if the object has a timestamp and a node ID.
+pub fn parse_json_raw_log_line(line: &str) -> Option<(u32, RawLogLine)> {
+    let object = parse_object(line)?;
+    let timestamp = parse_timestamp(&object)?;
+    let node_id = parse_node_id(&object)?;
+
+    let level = match field_str(&object, &["level"])
+        .unwrap_or("INFO")
+        .to_uppercase()
+        .as_str()
+    {
+        "ERROR" => LogLevel::Error,
+        "WARN" | "WARNING" => LogLevel::Warn,
+        "DEBUG" => LogLevel::Debug,
+        "TRACE" => LogLevel::Trace,
+        _ => LogLevel::Info,
+    };
+
+    let content = match field_str(&object, &["message", "msg"]) {
+        Some(message) => message.to_string(),
+        None => {
+            let fields: Vec<String> = object
+                .iter()
+                .filter(|(key, _)| {
+                    key.as_str() != "event" && !COMMON_FIELDS.contains(&key.as_str())
+                })
+                .map(|(key, value)| match value {
+                    Value::String(s) => format!("{}: {}", key, s),
+                    other => format!("{}: {}", key, other),
+                })
+                .collect();
+            match field_str(&object, &["event"]) {
+                Some(event) => format!("{}: {}", event, fields.join(", ")),
+                None => fields.join(", "),
+            }
+        }
+    };
+
+    Some((
+        node_id,
+        RawLogLine {
+            timestamp,
+            content,
+            level,
+        },
+    ))
+}
+
+/// Parse a line as a JSON object.
+fn parse_object(line: &str) -> Option<JsonObject> {
+    match serde_json::from_str(line.trim()) {
+        Ok(Value::Object(object)) => Some(object),
+        _ => None,
+    }
+}
+
+/// Extract the timestamp (RFC 3339 string or Unix epoch milliseconds).
+fn parse_timestamp(object: &JsonObject) -> Option<DateTime<Utc>> {
+    match object.get("timestamp").or_else(|| object.get("ts"))? {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc)),
+        Value::Number(n) => DateTime::from_timestamp_millis(n.as_i64()?),
+        _ => None,
+    }
+}
+
+/// Extract the reporting node ID.
+fn parse_node_id(object: &JsonObject) -> Option<u32> {
+    field_u32(object, "node_id").or_else(|| field_u32(object, "node"))
+}
+
+/// Extract the first present string field of `names`.
+fn field_str<'a>(object: &'a JsonObject, names: &[&str]) -> Option<&'a str> {
+    names
+        .iter()
+        .find_map(|name| object.get(*name).and_then(Value::as_str))
+}
+
+/// Extract an unsigned integer field (numbers or numeric strings).
+fn field_u64(object: &JsonObject, name: &str) -> Option<u64> {
+    match object.get(name)? {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Extract a u8 field value.
+fn field_u8(object: &JsonObject, name: &str) -> Option<u8> {
+    field_u64(object, name)?.try_into().ok()
+}
+
+/// Extract a u32 field value.
+fn field_u32(object: &JsonObject, name: &str) -> Option<u32> {
+    field_u64(object, name)?.try_into().ok()
+}
+
+/// Extract a usize field value.
+fn field_usize(object: &JsonObject, name: &str) -> Option<usize> {
+    field_u64(object, name)?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_packet_received() {
+        let line = r#"{"timestamp":"2025-10-23T18:00:01Z","level":"INFO","node_id":3094,"event":"packet_received","sender":3093,"type":6,"sequence":30940779,"length":215,"packet":1,"packet_count":10,"link_quality":26}"#;
+        let (_, event) = parse_json_log_line(line).expect("event expected");
+        if let LogEvent::ReceivePacket {
+            node_id,
+            sender_id,
+            message_type,
+            packet_count,
+            link_quality,
+            ..
+        } = event
+        {
+            assert_eq!(node_id, 3094);
+            assert_eq!(sender_id, 3093);
+            assert_eq!(message_type, 6);
+            assert_eq!(packet_count, 10);
+            assert_eq!(link_quality, 26);
+        } else {
+            panic!("Expected ReceivePacket event");
+        }
+
+        let (node_id, raw) = parse_json_raw_log_line(line).expect("raw line expected");
+        assert_eq!(node_id, 3094);
+        assert!(raw.content.starts_with("packet_received: "));
+        assert!(raw.content.contains("sender: 3093"));
+    }
+
+    #[test]
+    fn test_parse_json_plain_record() {
+        let line = r#"{"ts":1761242400000,"level":"warn","node":7,"msg":"Queue full"}"#;
+        assert!(is_json_line(line));
+        assert!(parse_json_log_line(line).is_none());
+
+        let (node_id, raw) = parse_json_raw_log_line(line).expect("raw line expected");
+        assert_eq!(node_id, 7);
+        assert_eq!(raw.content, "Queue full");
+        assert_eq!(raw.level, LogLevel::Warn);
+        assert_eq!(raw.timestamp.timestamp_millis(), 1761242400000);
+    }
+}
@@ -3,10 +3,19 @@
 //! Provides async-compatible log file reading with two modes:
 //! - Real-time tracking: Tail-follow semantics (starts at end, polls for new lines)
 //! - Log visualization: Sequential reading from start
+//!
+//! In log visualization mode the loader also reads gzip-compressed files (`.gz`)
+//! and whole logrotate sets: opening any member of `node.log`, `node.log.1`,
+//! `node.log.2.gz`, ... reads all members oldest first (highest rotation number
+//! first, the current file last), as if they were one log. A member present both
+//! plain and compressed (`node.log` and `node.log.gz`) is read once.
 
 use embassy_time::{Duration, Timer};
+use flate2::read::MultiGzDecoder;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 
 use super::types::AnalyzerMode;
 
@@ -18,12 +27,106 @@ const POLL_INTERVAL_MS: u64 = 50;
 
 /// Log file loader with mode-aware reading behavior.
 pub struct LogLoader {
-    reader: BufReader<File>,
+    reader: Box<dyn BufRead>,
+    /// Remaining files of a rotated set, oldest first (log visualization only).
+    pending_files: VecDeque<PathBuf>,
     mode: AnalyzerMode,
     eof_reached: bool,
     line_buffer: String,
 }
 
+/// Split a log file name into its base name and rotation number.
+///
+/// `node.log` → (`node.log`, 0), `node.log.3` and `node.log.3.gz` → (`node.log`, 3),
+/// `node.log.gz` → (`node.log`, 0).
+fn split_rotation(file_name: &str) -> (&str, u32) {
+    let name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    match name.rsplit_once('.') {
+        Some((base, suffix))
+            if !suffix.is_empty() && suffix.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            (base, suffix.parse().unwrap_or(0))
+        }
+        _ => (name, 0),
+    }
+}
+
+/// Order the members of the rotated set of `file_name` chronologically.
+///
+/// # Parameters
+///
+/// * `file_name` - Name of the selected log file
+/// * `candidates` - Names of the files in the same directory
+///
+/// # Returns
+///
+/// The members of the set (including `file_name`), oldest first. Of a plain and a
+/// `.gz` file with the same rotation number only one is read: `file_name` if it is
+/// one of them, otherwise the plain file.
+fn rotation_set(file_name: &str, candidates: &[String]) -> Vec<String> {
+    let (base, _) = split_rotation(file_name);
+    let mut members: Vec<(u32, &String)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let (candidate_base, rotation) = split_rotation(candidate);
+            (candidate_base == base).then_some((rotation, candidate))
+        })
+        .collect();
+    // Higher rotation numbers are older; a plain file sorts after its `.gz` twin
+    members.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(b.1.ends_with(".gz").cmp(&a.1.ends_with(".gz")))
+    });
+    let mut set: Vec<String> = Vec::new();
+    let mut last_rotation = None;
+    for (rotation, name) in members {
+        if last_rotation == Some(rotation) {
+            // Twin of the previous member: the selected file wins, then the plain one
+            if set.last().is_some_and(|kept| kept == file_name) {
+                continue;
+            }
+            set.pop();
+        }
+        last_rotation = Some(rotation);
+        set.push(name.clone());
+    }
+    set
+}
+
+/// Find the files of the rotated set containing `path`, oldest first.
+fn find_rotated_files(path: &Path) -> std::io::Result<VecDeque<PathBuf>> {
+    let (Some(directory), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Ok(VecDeque::from([path.to_path_buf()]));
+    };
+    let directory = if directory.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        directory
+    };
+    let candidates: Vec<String> = std::fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map_or(false, |t| t.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    Ok(rotation_set(&file_name.to_string_lossy(), &candidates)
+        .into_iter()
+        .map(|name| directory.join(name))
+        .collect())
+}
+
+/// Open a log file for sequential reading, decompressing `.gz` files.
+fn open_reader(path: &Path) -> std::io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    if path.extension().is_some_and(|extension| extension == "gz") {
+        Ok(Box::new(BufReader::with_capacity(
+            BUFFER_SIZE,
+            MultiGzDecoder::new(file),
+        )))
+    } else {
+        Ok(Box::new(BufReader::with_capacity(BUFFER_SIZE, file)))
+    }
+}
+
 impl LogLoader {
     /// Create a new log loader.
     ///
@@ -36,23 +139,66 @@ impl LogLoader {
     ///
     /// `Ok(LogLoader)` if file opens successfully, `Err` otherwise.
     pub fn new(path: &str, mode: AnalyzerMode) -> Result<Self, std::io::Error> {
-        let mut file = File::open(path)?;
-
-        // In real-time mode, seek to end to only read new lines
-        if mode == AnalyzerMode::RealtimeTracking {
-            file.seek(SeekFrom::End(0))?;
-        }
-
-        let reader = BufReader::with_capacity(BUFFER_SIZE, file);
+        let (reader, pending_files): (Box<dyn BufRead>, VecDeque<PathBuf>) = match mode {
+            AnalyzerMode::RealtimeTracking => {
+                if path.ends_with(".gz") {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "real-time tracking requires an uncompressed log file",
+                    ));
+                }
+                // In real-time mode, seek to end to only read new lines
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::End(0))?;
+                (
+                    Box::new(BufReader::with_capacity(BUFFER_SIZE, file)),
+                    VecDeque::new(),
+                )
+            }
+            AnalyzerMode::LogVisualization => {
+                let mut files = find_rotated_files(Path::new(path))?;
+                if files.len() > 1 {
+                    log::info!(
+                        "Reading rotated log set: {}",
+                        files
+                            .iter()
+                            .map(|f| f.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+                let first = files.pop_front().unwrap_or_else(|| PathBuf::from(path));
+                (open_reader(&first)?, files)
+            }
+        };
 
         Ok(Self {
             reader,
+            pending_files,
             mode,
             eof_reached: false,
             line_buffer: String::with_capacity(512),
         })
     }
 
+    /// Continue with the next file of a rotated set.
+    ///
+    /// # Returns
+    ///
+    /// `true` if another file was opened, `false` when the set is exhausted.
+    fn open_next_file(&mut self) -> bool {
+        while let Some(path) = self.pending_files.pop_front() {
+            match open_reader(&path) {
+                Ok(reader) => {
+                    self.reader = reader;
+                    return true;
+                }
+                Err(e) => log::warn!("Skipping log file {}: {}", path.display(), e),
+            }
+        }
+        false
+    }
+
     /// Read the next line from the log file.
     ///
     /// In `RealtimeTracking` mode, this will poll for new lines if at EOF.
@@ -70,6 +216,9 @@ impl LogLoader {
                     // EOF reached
                     match self.mode {
                         AnalyzerMode::LogVisualization => {
+                            if self.open_next_file() {
+                                continue;
+                            }
                             self.eof_reached = true;
                             return None;
                         }
@@ -92,6 +241,10 @@ impl LogLoader {
                     log::warn!("Error reading log file: {}", e);
                     match self.mode {
                         AnalyzerMode::LogVisualization => {
+                            // A corrupt member of a rotated set does not end the playback
+                            if self.open_next_file() {
+                                continue;
+                            }
                             self.eof_reached = true;
                             return None;
                         }
@@ -115,3 +268,40 @@ impl LogLoader {
         self.mode
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_set_is_ordered_oldest_first() {
+        let candidates: Vec<String> = [
+            "node.log",
+            "node.log.gz",
+            "node.log.1",
+            "node.log.10.gz",
+            "node.log.2.gz",
+            "other.log.1.gz",
+            "node.log.bak",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            rotation_set("node.log.2.gz", &candidates),
+            vec!["node.log.10.gz", "node.log.2.gz", "node.log.1", "node.log"]
+        );
+        // Rotation 0 exists plain and compressed: only one of them is read
+        assert_eq!(
+            rotation_set("node.log.gz", &candidates),
+            vec![
+                "node.log.10.gz",
+                "node.log.2.gz",
+                "node.log.1",
+                "node.log.gz"
+            ]
+        );
+        assert_eq!(split_rotation("node.log.gz"), ("node.log", 0));
+    }
+}
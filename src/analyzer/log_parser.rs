@@ -9,7 +9,11 @@
 //! - *TM6*: AddBlock message fully received
 //! - *TM7*: AddBlock message sent
 //! - *TM8*: Version information
+//!
+//! Lines starting with `{` are structured (JSON-lines) telemetry and are handed to
+//! `json_log_parser`, so textual, structured and mixed logs are detected per line.
 
+use super::json_log_parser::{is_json_line, parse_json_log_line, parse_json_raw_log_line};
 use super::types::{LogEvent, RawLogLine};
 use crate::simulation::types::LogLevel;
 use chrono::{DateTime, Utc};
@@ -40,6 +44,9 @@ use chrono::{DateTime, Utc};
 /// 2025-10-23T18:00:05Z [3094] *TM4* Routing message to incoming queue: sender: 3093, type: 6, length: 2000, sequence: 321312
 /// ```
 pub fn parse_log_line(line: &str) -> Option<(DateTime<Utc>, LogEvent)> {
+    if is_json_line(line) {
+        return parse_json_log_line(line);
+    }
     // Extract timestamp from the start of the line
     let timestamp = parse_timestamp(line)?;
     // Find the node ID pattern [xxxx]
@@ -81,6 +88,9 @@ pub fn parse_log_line(line: &str) -> Option<(DateTime<Utc>, LogEvent)> {
 ///
 /// `Some((node_id, raw_log_line))` if the line contains a node ID, `None` otherwise.
 pub fn parse_raw_log_line(line: &str) -> Option<(u32, RawLogLine)> {
+    if is_json_line(line) {
+        return parse_json_raw_log_line(line);
+    }
     let timestamp = parse_timestamp(line)?;
     let (node_id, bracket_end) = extract_node_id_with_position(line)?;
 
@@ -3,11 +3,15 @@
 //! Provides functionality for:
 //! - Real-time tracking of live log streams
 //! - Log file visualization with time-synchronized playback
+//! - Textual (`*TMn*`) and structured JSON-lines telemetry formats
+//! - Monitoring several sites (scene and live log pairs) at once for the sites dashboard
 //!
 //! The analyzer communicates with the UI using the same channels as the simulation module.
 
+pub mod json_log_parser;
 pub mod log_loader;
 pub mod log_parser;
+pub mod sites;
 pub mod task;
 pub mod types;
 
@@ -0,0 +1,372 @@
+//! Multi-site monitoring for real-time tracking.
+//!
+//! A sites file lists several deployments, each with its own scene and live log:
+//!
+//! ```json
+//! { "sites": [ { "name": "North", "scene": "north/scene.json", "log": "north/node.log" } ] }
+//! ```
+//!
+//! Relative paths are resolved against the directory of the sites file. The
+//! [`SiteMonitor`] tails every site's log on a background thread and keeps a
+//! [`SiteSummary`] per site (nodes alive, traffic rate, alerts) for the sites
+//! dashboard; the full map view of a site is opened by restarting the analyzer
+//! on that site's scene and log.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::common::scene::{SceneMode, load_scene};
+use crate::simulation::types::LogLevel;
+
+use super::log_parser::{parse_log_line, parse_raw_log_line};
+use super::types::LogEvent;
+
+/// A node is counted as alive if it logged a line within this window (seconds).
+const ALIVE_WINDOW_SECS: i64 = 600;
+
+/// Window of the packet rate shown on the dashboard (seconds).
+const TRAFFIC_WINDOW_SECS: i64 = 60;
+
+/// Interval between two polls of the site logs (milliseconds).
+const POLL_INTERVAL_MS: u64 = 500;
+
+/// One site of a sites file.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SiteConfig {
+    /// Display name of the site.
+    pub name: String,
+    /// Path to the site's scene file.
+    pub scene: String,
+    /// Path to the site's live log file.
+    pub log: String,
+}
+
+#[derive(Deserialize)]
+struct SitesFile {
+    sites: Vec<SiteConfig>,
+}
+
+/// Load a sites file and resolve the scene and log paths of every site.
+///
+/// # Parameters
+///
+/// * `path` - Path to the sites JSON file
+///
+/// # Returns
+///
+/// The configured sites, or an error if the file is unreadable, malformed or lists no site.
+pub fn load_sites(path: &str) -> anyhow::Result<Vec<SiteConfig>> {
+    let text = std::fs::read_to_string(path)?;
+    let file: SitesFile = serde_json::from_str(&text)?;
+    if file.sites.is_empty() {
+        anyhow::bail!("Sites file lists no site");
+    }
+    let base = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let resolve = |p: &str| -> String {
+        let p = Path::new(p);
+        if p.is_absolute() {
+            p.to_string_lossy().into_owned()
+        } else {
+            base.join(p).to_string_lossy().into_owned()
+        }
+    };
+    Ok(file
+        .sites
+        .into_iter()
+        .map(|site| SiteConfig {
+            scene: resolve(&site.scene),
+            log: resolve(&site.log),
+            name: site.name,
+        })
+        .collect())
+}
+
+/// Dashboard summary of one site.
+#[derive(Clone, Debug, Default)]
+pub struct SiteSummary {
+    /// Display name of the site.
+    pub name: String,
+    /// Number of nodes in the site's scene.
+    pub node_count: usize,
+    /// Nodes that logged a line within the alive window.
+    pub nodes_alive: usize,
+    /// Packets transmitted within the last traffic window.
+    pub packets_per_minute: usize,
+    /// Error lines logged since monitoring started.
+    pub error_lines: u64,
+    /// Current alerts of the site.
+    pub alerts: Vec<String>,
+    /// Timestamp of the latest log line, if any.
+    pub last_line: Option<DateTime<Utc>>,
+}
+
+/// Activity of one site accumulated from its log lines.
+#[derive(Default)]
+pub struct SiteActivity {
+    /// Latest log timestamp of every node that logged anything.
+    last_seen: HashMap<u32, DateTime<Utc>>,
+    /// Timestamps of recent packet transmissions (trimmed to the traffic window).
+    transmissions: VecDeque<DateTime<Utc>>,
+    /// Timestamps of recent error lines (trimmed to the alive window).
+    recent_errors: VecDeque<DateTime<Utc>>,
+    error_lines: u64,
+    last_line: Option<DateTime<Utc>>,
+}
+
+impl SiteActivity {
+    /// Record one log line of the site.
+    pub fn record_line(&mut self, line: &str) {
+        if let Some((node_id, raw)) = parse_raw_log_line(line) {
+            let seen = self.last_seen.entry(node_id).or_insert(raw.timestamp);
+            *seen = (*seen).max(raw.timestamp);
+            self.last_line = Some(
+                self.last_line
+                    .map_or(raw.timestamp, |t| t.max(raw.timestamp)),
+            );
+            if matches!(raw.level, LogLevel::Error) {
+                self.error_lines += 1;
+                self.recent_errors.push_back(raw.timestamp);
+            }
+        }
+        if let Some((timestamp, LogEvent::SendPacket { .. })) = parse_log_line(line) {
+            self.transmissions.push_back(timestamp);
+        }
+    }
+
+    /// Drop samples that fell out of their windows.
+    fn trim(&mut self, now: DateTime<Utc>) {
+        let traffic_start = now - ChronoDuration::seconds(TRAFFIC_WINDOW_SECS);
+        while self
+            .transmissions
+            .front()
+            .is_some_and(|t| *t < traffic_start)
+        {
+            self.transmissions.pop_front();
+        }
+        let alive_start = now - ChronoDuration::seconds(ALIVE_WINDOW_SECS);
+        while self.recent_errors.front().is_some_and(|t| *t < alive_start) {
+            self.recent_errors.pop_front();
+        }
+    }
+
+    /// Summarize the site's activity at `now`.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - Display name of the site
+    /// * `node_ids` - Node ids of the site's scene
+    /// * `now` - Current wall-clock time
+    pub fn summary(&mut self, name: &str, node_ids: &[u32], now: DateTime<Utc>) -> SiteSummary {
+        self.trim(now);
+        let alive_start = now - ChronoDuration::seconds(ALIVE_WINDOW_SECS);
+        let silent: Vec<u32> = node_ids
+            .iter()
+            .copied()
+            .filter(|id| !self.last_seen.get(id).is_some_and(|t| *t >= alive_start))
+            .collect();
+
+        let mut alerts = Vec::new();
+        if self.last_line.is_none_or(|t| t < alive_start) {
+            alerts.push(format!("No log lines for {} min", ALIVE_WINDOW_SECS / 60));
+        } else if !silent.is_empty() {
+            let listed: Vec<String> = silent.iter().take(5).map(|id| id.to_string()).collect();
+            let more = if silent.len() > 5 { ", …" } else { "" };
+            alerts.push(format!(
+                "{} node(s) silent: {}{}",
+                silent.len(),
+                listed.join(", "),
+                more
+            ));
+        }
+        if !self.recent_errors.is_empty() {
+            alerts.push(format!(
+                "{} error line(s) in the last {} min",
+                self.recent_errors.len(),
+                ALIVE_WINDOW_SECS / 60
+            ));
+        }
+
+        SiteSummary {
+            name: name.to_string(),
+            node_count: node_ids.len(),
+            nodes_alive: node_ids.len() - silent.len(),
+            packets_per_minute: self.transmissions.len() * 60 / TRAFFIC_WINDOW_SECS as usize,
+            error_lines: self.error_lines,
+            alerts,
+            last_line: self.last_line,
+        }
+    }
+}
+
+/// Tail state of one monitored site.
+struct MonitoredSite {
+    config: SiteConfig,
+    node_ids: Vec<u32>,
+    reader: Option<BufReader<File>>,
+    activity: SiteActivity,
+    /// Why the site's scene could not be loaded, if it could not.
+    scene_error: Option<String>,
+    /// Why the site's log could not be opened, if it could not (retried every poll).
+    log_error: Option<String>,
+}
+
+impl MonitoredSite {
+    fn open(config: SiteConfig) -> Self {
+        let (node_ids, scene_error) = match load_scene(&config.scene, SceneMode::Analyzer) {
+            Ok(scene) => (scene.nodes.iter().map(|n| n.node_id).collect(), None),
+            Err(e) => (Vec::new(), Some(format!("Scene unreadable: {}", e))),
+        };
+        let mut site = Self {
+            config,
+            node_ids,
+            reader: None,
+            activity: SiteActivity::default(),
+            scene_error,
+            log_error: None,
+        };
+        site.reopen();
+        site
+    }
+
+    /// Open the log at its end, like real-time tracking does.
+    fn reopen(&mut self) {
+        match File::open(&self.config.log).and_then(|mut f| f.seek(SeekFrom::End(0)).map(|_| f)) {
+            Ok(file) => {
+                self.reader = Some(BufReader::new(file));
+                self.log_error = None;
+            }
+            Err(e) => self.log_error = Some(format!("Log unreadable: {}", e)),
+        }
+    }
+
+    /// Read every complete line appended since the last poll.
+    fn poll(&mut self) {
+        if self.reader.is_none() {
+            self.reopen();
+        }
+        let Some(reader) = self.reader.as_mut() else {
+            return;
+        };
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) if !line.ends_with('\n') => {
+                    // Incomplete line: rewind and read it again once it is finished
+                    let _ = reader.seek_relative(-(line.len() as i64));
+                    break;
+                }
+                Ok(_) => self.activity.record_line(line.trim_end()),
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn summary(&mut self, now: DateTime<Utc>) -> SiteSummary {
+        let mut summary = self
+            .activity
+            .summary(&self.config.name, &self.node_ids, now);
+        let errors = self.scene_error.iter().chain(self.log_error.iter());
+        summary.alerts.splice(0..0, errors.cloned());
+        summary
+    }
+}
+
+/// Background monitor of all sites of a sites file.
+pub struct SiteMonitor {
+    summaries: Arc<Mutex<Vec<SiteSummary>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SiteMonitor {
+    /// Start tailing the logs of `sites` on a background thread.
+    pub fn start(sites: Vec<SiteConfig>) -> Self {
+        let summaries = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let summaries = Arc::clone(&summaries);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                let mut sites: Vec<MonitoredSite> =
+                    sites.into_iter().map(MonitoredSite::open).collect();
+                while !stop.load(Ordering::Relaxed) {
+                    let now = Utc::now();
+                    let latest: Vec<SiteSummary> = sites
+                        .iter_mut()
+                        .map(|site| {
+                            site.poll();
+                            site.summary(now)
+                        })
+                        .collect();
+                    if let Ok(mut guard) = summaries.lock() {
+                        *guard = latest;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS));
+                }
+            })
+        };
+        Self {
+            summaries,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Latest summary of every site, in sites file order.
+    pub fn summaries(&self) -> Vec<SiteSummary> {
+        self.summaries.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+impl Drop for SiteMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_counts_alive_nodes_traffic_and_errors() {
+        let mut activity = SiteActivity::default();
+        activity.record_line("2025-10-23T17:59:50Z [1] *TM1* Packet transmitted: type: 6, sequence: 1, length: 215, packet: 1/1");
+        activity.record_line("2025-10-23T18:00:30Z [1] *TM1* Packet transmitted: type: 6, sequence: 2, length: 215, packet: 1/1");
+        activity.record_line("2025-10-23T17:40:00Z [2] *TM1* Packet transmitted: type: 6, sequence: 3, length: 215, packet: 1/1");
+        activity.record_line("2025-10-23T18:00:40Z ERROR [1] Radio init failed");
+
+        let now = DateTime::parse_from_rfc3339("2025-10-23T18:01:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let summary = activity.summary("North", &[1, 2, 3], now);
+        assert_eq!(summary.node_count, 3);
+        assert_eq!(summary.nodes_alive, 1);
+        assert_eq!(summary.packets_per_minute, 1);
+        assert_eq!(summary.error_lines, 1);
+        assert!(
+            summary
+                .alerts
+                .iter()
+                .any(|a| a.starts_with("2 node(s) silent: 2, 3"))
+        );
+        assert!(
+            summary
+                .alerts
+                .iter()
+                .any(|a| a.starts_with("1 error line(s)"))
+        );
+    }
+}
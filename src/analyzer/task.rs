@@ -4,37 +4,61 @@
 //! - Scene loading and UI initialization
 //! - Log file reading with mode-appropriate behavior
 //! - Time-synchronized event dispatching with adaptive delay recovery
+//! - Playback speed control through the virtual time driver (speed slider, auto speed)
 //! - UI command handling
 //! - Remote control commands to Telemetry Hub (real-time mode only)
 //!
 //! The main loop uses a two-phase select approach:
-//! 1. Wait for log line OR UI command
-//! 2. If log line needs delay, wait for remaining time OR UI command
+//! 1. Wait for log line OR UI command (OR the activity tick that reports the nodes
+//!    that logged since the last report in one message)
+//! 2. If log line needs delay, wait for remaining time OR UI command (the event
+//!    stays in flight: it is processed when the wait ends, after a pause once
+//!    playback resumes)
 //!
 //! This provides stable delay visualization while allowing the system to
 //! gradually catch up when the average network latency is better than spikes.
+//!
+//! Playback timing uses `embassy_time::Instant`, i.e. the virtual clock of
+//! `time_driver`, the same clock the simulation runs on: the speed slider and the
+//! auto speed scale log playback exactly like a simulation. Real-time tracking
+//! follows a live log, so it pins the speed to 100%.
 
 use chrono::{DateTime, Utc};
-use embassy_futures::select::{Either, select};
-use embassy_time::{Duration, Timer};
+use embassy_futures::select::{Either, Either3, select, select3};
+use embassy_time::{Duration, Instant, Timer};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::Instant;
 
 use crate::common::connection_matrix::ConnectionMatrixParser;
-use crate::common::scene::{Scene, SceneMode, load_scene};
+use crate::common::measurement_ids;
+use crate::common::provenance;
+use crate::common::scene::{
+    Scene, SceneMode, effective_distance_warnings, load_scene, read_scene_value,
+};
 use crate::control::{ControlCommand, ControlConfig, TelemetryClient};
+use crate::simulation::network::{
+    AUTO_SPEED_MAX_PERCENT, AUTO_SPEED_MIN_PERCENT, adjust_auto_speed,
+};
 use crate::simulation::types::{FullMessage, LogLine, NodeMessage};
-use crate::ui::{NodeInfo, NodeUIState, UICommand, UIRefreshState};
-use crate::{UICommandQueueReceiver, UIRefreshQueueSender};
+use crate::ui::{
+    MeasurementKind, MeasurementReachStatus, NodeInfo, NodeStateSnapshot, NodeUIState, UICommand,
+    UIRefreshState,
+};
+use crate::{UICommandQueueReceiver, UIRefreshQueueSender, session};
 
 use super::log_loader::LogLoader;
 use super::log_parser::{parse_log_line, parse_raw_log_line};
-use super::types::{AnalyzerMode, AnalyzerState, LogEvent, NodePacketRecord};
+use super::types::{
+    AnalyzerMode, AnalyzerState, LogEvent, MeasurementReach, NodePacketRecord, SiteExit,
+};
 
 /// Size of the sliding window for calculating average delay.
 const DELAY_HISTORY_SIZE: usize = 100;
 
+/// Interval of the node activity reports: the nodes that logged within a tick are
+/// sent to the UI in one message.
+const ACTIVITY_TICK_MS: u64 = 250;
+
 /// Tracks the delay between log timestamp and processing time for adaptive catch-up.
 struct DelayTracker {
     /// Recent delay samples (real_processing_time - log_timestamp) in milliseconds
@@ -83,6 +107,9 @@ impl DelayTracker {
 /// * `log_path` - Path to the log file
 /// * `ui_refresh_tx` - Channel for sending UI updates
 /// * `ui_command_rx` - Channel for receiving UI commands
+///
+/// Opening another site from the sites dashboard (`UICommand::OpenSite`) restarts
+/// the analyzer on that site's scene and log; `UICommand::EndSession` ends the task.
 #[embassy_executor::task]
 pub async fn analyzer_task(
     mode: AnalyzerMode,
@@ -91,6 +118,54 @@ pub async fn analyzer_task(
     ui_refresh_tx: UIRefreshQueueSender,
     ui_command_rx: UICommandQueueReceiver,
 ) {
+    // Releases the mode dispatcher when the session ends
+    let _session = session::SessionGuard;
+    let mut site = (scene_path, log_path);
+    loop {
+        let exit = match run_analyzer(mode, site.0, site.1, ui_refresh_tx, ui_command_rx).await {
+            Some(exit) => exit,
+            None => wait_for_site(ui_command_rx).await,
+        };
+        site = match exit {
+            SiteExit::OpenSite(scene_path, log_path) => (scene_path, log_path),
+            SiteExit::EndSession => {
+                log::info!("Analyzer session ended");
+                return;
+            }
+        };
+    }
+}
+
+/// Wait until the UI opens a site or ends the session, ignoring every other command.
+///
+/// Used after the scene or log of a session failed to load, so a multi-site
+/// session can still switch to another site.
+async fn wait_for_site(ui_command_rx: UICommandQueueReceiver) -> SiteExit {
+    loop {
+        match ui_command_rx.receive().await {
+            UICommand::OpenSite {
+                scene_path,
+                log_path,
+            } => return SiteExit::OpenSite(scene_path, log_path),
+            UICommand::EndSession => return SiteExit::EndSession,
+            _ => {}
+        }
+    }
+}
+
+/// Run the analyzer on one scene and log.
+///
+/// # Returns
+///
+/// Why the analyzer left the site (another site to open, or the end of the
+/// session), or `None` if the scene or log could not be loaded.
+async fn run_analyzer(
+    mode: AnalyzerMode,
+    scene_path: String,
+    log_path: String,
+    ui_refresh_tx: UIRefreshQueueSender,
+    ui_command_rx: UICommandQueueReceiver,
+) -> Option<SiteExit> {
     log::info!("Analyzer task started in {:?} mode", mode);
     log::info!("Scene: {}, Log: {}", scene_path, log_path);
 
@@ -98,15 +173,32 @@ pub async fn analyzer_task(
     let scene = match load_scene(&scene_path, SceneMode::Analyzer) {
         Ok(s) => s,
         Err(e) => {
-            let _ = ui_refresh_tx
-                .send(UIRefreshState::Alert(format!(
-                    "Failed to load scene: {}",
-                    e
-                )))
-                .await;
-            return;
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Failed to load scene: {}",
+                e
+            )));
+            return None;
         }
     };
+    if let Ok(value) = read_scene_value(&scene_path) {
+        provenance::record_scene(&scene_path, &value);
+    }
+    let distance_warnings = effective_distance_warnings(&scene);
+    if !distance_warnings.is_empty() {
+        for warning in &distance_warnings {
+            log::warn!("{}", warning);
+        }
+        const SHOWN_WARNINGS: usize = 5;
+        let mut message = format!(
+            "{} node(s) have an effective_distance inconsistent with their radio_strength; the scene may be stale:\n{}",
+            distance_warnings.len(),
+            distance_warnings[..distance_warnings.len().min(SHOWN_WARNINGS)].join("\n")
+        );
+        if distance_warnings.len() > SHOWN_WARNINGS {
+            message.push_str("\n… (see the log for the rest)");
+        }
+        ui_refresh_tx.send(UIRefreshState::Alert(message));
+    }
 
     // Build node effective distances map for radio message visualization
     let node_effective_distances: HashMap<u32, u32> = scene
@@ -145,9 +237,7 @@ pub async fn analyzer_task(
 
     // Notify UI of control availability
     let control_available = telemetry_client.is_some();
-    let _ = ui_refresh_tx
-        .send(UIRefreshState::ControlAvailable(control_available))
-        .await;
+    ui_refresh_tx.send(UIRefreshState::ControlAvailable(control_available));
 
     // Initialize UI with scene data
     initialize_scene_ui(&scene, &ui_refresh_tx).await;
@@ -157,29 +247,34 @@ pub async fn analyzer_task(
         AnalyzerMode::RealtimeTracking => crate::ui::OperatingMode::RealtimeTracking,
         AnalyzerMode::LogVisualization => crate::ui::OperatingMode::LogVisualization,
     };
-    let _ = ui_refresh_tx
-        .send(UIRefreshState::ModeChanged(operating_mode))
-        .await;
+    ui_refresh_tx.send(UIRefreshState::ModeChanged(operating_mode));
+    if mode == AnalyzerMode::RealtimeTracking {
+        // A live log cannot be played faster or slower than it is written
+        crate::time_driver::set_simulation_speed_percent(100);
+        ui_refresh_tx.send(UIRefreshState::SimulationSpeedChanged(100));
+    }
 
     // Open log file
     let mut log_loader = match LogLoader::new(&log_path, mode) {
         Ok(l) => l,
         Err(e) => {
-            let _ = ui_refresh_tx
-                .send(UIRefreshState::Alert(format!(
-                    "Failed to open log file: {}",
-                    e
-                )))
-                .await;
-            return;
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Failed to open log file: {}",
+                e
+            )));
+            return None;
         }
     };
 
+    // Measurement IDs of this session follow the AddBlock sequences of earlier ones
+    measurement_ids::reset_from_clock();
+
     // Initialize analyzer state
     let mut state = AnalyzerState::new();
     let mut total_sent = 0u64;
     let mut total_received = 0u64;
     let mut delay_tracker = DelayTracker::new();
+    let mut upcounter = 0;
     let mut connection_matrix_parser = ConnectionMatrixParser::new();
 
     // Timing state
@@ -188,9 +283,39 @@ pub async fn analyzer_task(
 
     // Main processing loop
     loop {
-        // Phase 1: Wait for log line OR UI command
-        match select(log_loader.next_line(), ui_command_rx.receive()).await {
-            Either::First(line_result) => {
+        // While playback is paused only UI commands are handled (log visualization)
+        if state.playback_paused {
+            let cmd = ui_command_rx.receive().await;
+            handle_ui_command(cmd, &mut state, &ui_refresh_tx, &telemetry_client);
+            if let Some(exit) = state.exit.take() {
+                return Some(exit);
+            }
+            if !state.playback_paused {
+                // Resume the log timeline from now instead of catching up the pause
+                last_process_time = Some(Instant::now());
+            }
+            continue;
+        }
+
+        // Phase 1: Wait for log line OR UI command (OR the activity tick while node
+        // activity is waiting to be reported)
+        let activity_pending = !state.pending_activity.is_empty();
+        let activity_tick = async move {
+            if activity_pending {
+                Timer::after(Duration::from_millis(ACTIVITY_TICK_MS)).await;
+            } else {
+                core::future::pending::<()>().await;
+            }
+        };
+        match select3(
+            log_loader.next_line(),
+            ui_command_rx.receive(),
+            activity_tick,
+        )
+        .await
+        {
+            Either3::Third(()) => publish_activity(&mut state, &ui_refresh_tx),
+            Either3::First(line_result) => {
                 match line_result {
                     Some(line) => {
                         // First, try to capture the raw log line for Log Stream tab
@@ -202,11 +327,15 @@ pub async fn analyzer_task(
                                 log_timestamp,
                                 &raw_log.content,
                             ) {
-                                let _ = ui_refresh_tx
-                                    .send(UIRefreshState::ConnectionMatrixUpdated(matrix))
-                                    .await;
+                                ui_refresh_tx.send(UIRefreshState::ConnectionMatrixUpdated(matrix));
                             }
                             state.add_log_line(node_id, raw_log);
+                            state.pending_activity.insert(node_id, log_timestamp);
+                            if state.last_activity_publish.map_or(true, |t| {
+                                t.elapsed() >= std::time::Duration::from_millis(ACTIVITY_TICK_MS)
+                            }) {
+                                publish_activity(&mut state, &ui_refresh_tx);
+                            }
                         }
 
                         // Then, parse for structured events (Radio Stream tab)
@@ -238,14 +367,25 @@ pub async fn analyzer_task(
                                 let log_line_diff = timestamp.signed_duration_since(prev_ts);
                                 let mut log_line_diff_ms = log_line_diff.num_milliseconds().max(0);
 
-                                // Calculate time spent since we processed the last message
-                                let real_elapsed = prev_process.elapsed();
-                                let real_elapsed_ms = real_elapsed.as_millis() as i64;
+                                // Calculate virtual time spent since we processed the last message
+                                let elapsed_ms = prev_process.elapsed().as_millis() as i64;
 
                                 // Calculate current delay (how far we are behind the log timeline)
-                                let current_delay = real_elapsed_ms - log_line_diff_ms;
+                                let current_delay = elapsed_ms - log_line_diff_ms;
                                 let average_delay = delay_tracker.add_sample(current_delay);
 
+                                if state.auto_speed_enabled
+                                    && mode == AnalyzerMode::LogVisualization
+                                {
+                                    adjust_auto_speed(
+                                        Duration::from_millis(current_delay.max(0) as u64),
+                                        &mut upcounter,
+                                        AUTO_SPEED_MIN_PERCENT,
+                                        AUTO_SPEED_MAX_PERCENT,
+                                        &ui_refresh_tx,
+                                    );
+                                }
+
                                 // Adaptive catch-up: if average delay is less than current delay,
                                 // speed up by multiplying wait time by 0.9
                                 if average_delay < current_delay && log_line_diff_ms > 0 {
@@ -253,48 +393,70 @@ pub async fn analyzer_task(
                                 }
 
                                 // Calculate remaining wait time
-                                let remaining_wait_ms = log_line_diff_ms - real_elapsed_ms;
+                                let remaining_wait_ms = log_line_diff_ms - elapsed_ms;
 
                                 if remaining_wait_ms > 0 && mode == AnalyzerMode::LogVisualization {
                                     // Phase 2: Wait for remaining time OR UI command
-                                    let wait_duration =
-                                        Duration::from_millis(remaining_wait_ms as u64);
-                                    match select(
-                                        Timer::after(wait_duration),
-                                        ui_command_rx.receive(),
-                                    )
-                                    .await
-                                    {
-                                        Either::First(_) => {
-                                            // Timer expired - process the event
-                                            last_log_timestamp = Some(timestamp);
-                                            last_process_time = Some(Instant::now());
-
-                                            process_event(
-                                                &event,
-                                                timestamp,
-                                                &mut state,
-                                                &ui_refresh_tx,
-                                                &mut total_sent,
-                                                &mut total_received,
-                                                &node_effective_distances,
-                                            )
-                                            .await;
-
-                                            state.last_processed_timestamp = Some(timestamp);
-                                        }
-                                        Either::Second(cmd) => {
-                                            // UI command arrived during wait - handle it
-                                            // The event is lost in this case (spec says start next iteration)
-                                            handle_ui_command(
-                                                cmd,
-                                                &state,
-                                                &ui_refresh_tx,
-                                                &telemetry_client,
-                                            );
-                                            continue;
+                                    let mut deadline = Instant::now()
+                                        + Duration::from_millis(remaining_wait_ms as u64);
+                                    loop {
+                                        match select(Timer::at(deadline), ui_command_rx.receive())
+                                            .await
+                                        {
+                                            Either::First(_) => break,
+                                            Either::Second(cmd) => {
+                                                // UI command arrived during wait - handle it,
+                                                // the event stays in flight
+                                                handle_ui_command(
+                                                    cmd,
+                                                    &mut state,
+                                                    &ui_refresh_tx,
+                                                    &telemetry_client,
+                                                );
+                                                if let Some(exit) = state.exit.take() {
+                                                    return Some(exit);
+                                                }
+                                                if !state.playback_paused {
+                                                    continue;
+                                                }
+                                                // Paused: the rest of the wait continues
+                                                // after the resume
+                                                let remaining = deadline
+                                                    .checked_duration_since(Instant::now())
+                                                    .unwrap_or(Duration::from_ticks(0));
+                                                while state.playback_paused {
+                                                    let cmd = ui_command_rx.receive().await;
+                                                    handle_ui_command(
+                                                        cmd,
+                                                        &mut state,
+                                                        &ui_refresh_tx,
+                                                        &telemetry_client,
+                                                    );
+                                                    if let Some(exit) = state.exit.take() {
+                                                        return Some(exit);
+                                                    }
+                                                }
+                                                deadline = Instant::now() + remaining;
+                                            }
                                         }
                                     }
+
+                                    // Timer expired - process the event
+                                    last_log_timestamp = Some(timestamp);
+                                    last_process_time = Some(Instant::now());
+
+                                    process_event(
+                                        &event,
+                                        timestamp,
+                                        &mut state,
+                                        &ui_refresh_tx,
+                                        &mut total_sent,
+                                        &mut total_received,
+                                        &node_effective_distances,
+                                    )
+                                    .await;
+
+                                    state.last_processed_timestamp = Some(timestamp);
                                 } else {
                                     // No wait needed or in real-time mode - process immediately
                                     last_log_timestamp = Some(timestamp);
@@ -319,40 +481,92 @@ pub async fn analyzer_task(
                     }
                     None => {
                         // EOF reached in log visualization mode
-                        let _ = ui_refresh_tx.send(UIRefreshState::VisualizationEnded).await;
+                        ui_refresh_tx.send(UIRefreshState::VisualizationEnded);
                         log::info!("Log visualization ended (EOF reached)");
 
                         // After EOF, just keep responding to UI commands
                         loop {
                             let cmd = ui_command_rx.receive().await;
-                            handle_ui_command(cmd, &state, &ui_refresh_tx, &telemetry_client);
+                            handle_ui_command(cmd, &mut state, &ui_refresh_tx, &telemetry_client);
+                            if let Some(exit) = state.exit.take() {
+                                return Some(exit);
+                            }
                         }
                     }
                 }
             }
-            Either::Second(cmd) => {
+            Either3::Second(cmd) => {
                 // UI command received - handle it and continue to next iteration
-                handle_ui_command(cmd, &state, &ui_refresh_tx, &telemetry_client);
+                handle_ui_command(cmd, &mut state, &ui_refresh_tx, &telemetry_client);
+                if let Some(exit) = state.exit.take() {
+                    return Some(exit);
+                }
             }
         }
     }
 }
 
+/// Report the nodes that logged since the last report in one `NodeActivity` message.
+///
+/// If the message is shed, the activity stays pending and goes out with the next report.
+///
+/// # Parameters
+///
+/// * `state` - Analyzer state holding the pending node activity
+/// * `ui_refresh_tx` - Channel for UI updates
+fn publish_activity(state: &mut AnalyzerState, ui_refresh_tx: &UIRefreshQueueSender) {
+    state.last_activity_publish = Some(std::time::Instant::now());
+    if state.pending_activity.is_empty() {
+        return;
+    }
+    let activity = state
+        .pending_activity
+        .iter()
+        .map(|(node_id, timestamp)| (*node_id, *timestamp))
+        .collect();
+    if ui_refresh_tx
+        .try_send(UIRefreshState::NodeActivity(activity))
+        .is_ok()
+    {
+        state.pending_activity.clear();
+    }
+}
+
 /// Handle a UI command.
 fn handle_ui_command(
     cmd: UICommand,
-    state: &AnalyzerState,
+    state: &mut AnalyzerState,
     ui_refresh_tx: &UIRefreshQueueSender,
     telemetry_client: &Option<Arc<TelemetryClient>>,
 ) {
     match cmd {
         UICommand::RequestNodeInfo(node_id) => {
             // Build NodeInfo from packet history
-            let node_info = build_node_info(node_id, state);
+            let node_info = build_node_info(node_id, state, None);
             let _ = ui_refresh_tx
                 .try_send(UIRefreshState::NodeInfo(node_info))
                 .ok();
         }
+        UICommand::SetPlaybackPaused(paused) => {
+            state.playback_paused = paused;
+            log::info!("Log playback {}", if paused { "paused" } else { "resumed" });
+        }
+        UICommand::SetAutoSpeed(enabled) => {
+            state.auto_speed_enabled = enabled;
+        }
+        UICommand::RequestNodeStateAt(node_id, time) => {
+            let Some(until) = DateTime::from_timestamp_millis(time.as_millis() as i64) else {
+                return;
+            };
+            let node_info = build_node_info(node_id, state, Some(until));
+            let _ = ui_refresh_tx
+                .try_send(UIRefreshState::NodeInfo(node_info))
+                .ok();
+            let snapshot = build_node_state_at(node_id, state, until);
+            let _ = ui_refresh_tx
+                .try_send(UIRefreshState::NodeStateAt(snapshot))
+                .ok();
+        }
         UICommand::SendControlCommand(control_cmd) => {
             if let Some(client) = telemetry_client {
                 match client.send_command(&control_cmd) {
@@ -363,7 +577,10 @@ fn handle_ui_command(
                 log::warn!("Control command received but no telemetry client available");
             }
         }
-        UICommand::StartMeasurement(node_id, sequence) => {
+        UICommand::StartMeasurement(_, _, MeasurementKind::Transaction) => {
+            log::warn!("Transaction measurements are only available in Simulation mode");
+        }
+        UICommand::StartMeasurement(node_id, sequence, MeasurementKind::Block) => {
             // Send start_measurement command to the Telemetry Hub
             if let Some(client) = telemetry_client {
                 let control_cmd = ControlCommand::StartMeasurement { node_id, sequence };
@@ -393,6 +610,16 @@ fn handle_ui_command(
                 log::warn!("Connection matrix request received but no telemetry client available");
             }
         }
+        UICommand::OpenSite {
+            scene_path,
+            log_path,
+        } => {
+            log::info!("Opening site: {}, {}", scene_path, log_path);
+            state.exit = Some(SiteExit::OpenSite(scene_path, log_path));
+        }
+        UICommand::EndSession => {
+            state.exit = Some(SiteExit::EndSession);
+        }
         _ => {
             // Ignore other commands in analyzer mode
         }
@@ -405,9 +632,7 @@ async fn initialize_scene_ui(scene: &Scene, ui_refresh_tx: &UIRefreshQueueSender
         scene.link_quality_weak_threshold,
         scene.link_quality_excellent_threshold,
     ) {
-        let _ = ui_refresh_tx
-            .send(UIRefreshState::PoorAndExcellentLimits(weak, excellent))
-            .await;
+        ui_refresh_tx.send(UIRefreshState::PoorAndExcellentLimits(weak, excellent));
     }
 
     // Publish nodes with effective distances
@@ -418,39 +643,32 @@ async fn initialize_scene_ui(scene: &Scene, ui_refresh_tx: &UIRefreshQueueSender
             node_id: n.node_id,
             position: (&n.position).into(),
             radio_strength: n.effective_distance.unwrap_or(100),
+            tx_power: None,
+            is_gateway: false,
+            disabled: false,
         })
         .collect();
 
-    let _ = ui_refresh_tx
-        .send(UIRefreshState::NodesUpdated(node_states))
-        .await;
+    ui_refresh_tx.send(UIRefreshState::NodesUpdated(node_states));
 
     // Publish obstacles (using From trait for conversion)
     let obstacles: Vec<crate::simulation::Obstacle> =
         scene.obstacles.iter().map(|o| o.into()).collect();
 
-    let _ = ui_refresh_tx
-        .send(UIRefreshState::ObstaclesUpdated(obstacles))
-        .await;
+    ui_refresh_tx.send(UIRefreshState::ObstaclesUpdated(obstacles));
+    ui_refresh_tx.send(UIRefreshState::AnnotationsUpdated(
+        scene.annotations.clone(),
+    ));
 
     // Publish scene dimensions
-    let _ = ui_refresh_tx
-        .send(UIRefreshState::SceneDimensionsUpdated(
-            (&scene.world_top_left).into(),
-            (&scene.world_bottom_right).into(),
-            scene.width,
-            scene.height,
-        ))
-        .await;
+    ui_refresh_tx.send(UIRefreshState::SceneDimensionsUpdated(scene.transform));
 
     // Background image if present
     if let Some(ref bg_image) = scene.background_image {
         log::info!("Background image specified: {:?}", bg_image);
-        let _ = ui_refresh_tx
-            .send(UIRefreshState::BackgroundImageUpdated(Some(
-                bg_image.clone(),
-            )))
-            .await;
+        ui_refresh_tx.send(UIRefreshState::BackgroundImageUpdated(Some(
+            bg_image.clone(),
+        )));
     }
 }
 
@@ -469,6 +687,7 @@ async fn process_event(
             node_id,
             message_type,
             sequence,
+            length,
             ..
         } => {
             *total_sent += 1;
@@ -482,14 +701,14 @@ async fn process_event(
                 },
             );
 
-            if let Some(active_id) = state.active_measurement_id {
-                if let Some(seq) = sequence {
-                    if active_id == *seq {
-                        let _ = ui_refresh_tx
-                            .try_send(UIRefreshState::SendMessageInMeasurement(*seq))
-                            .ok();
-                    }
-                }
+            // Packets of any measurement seen in the log count towards its statistics
+            if let Some(seq) = sequence.filter(|seq| state.measurement_reach.contains_key(seq)) {
+                let _ = ui_refresh_tx
+                    .try_send(UIRefreshState::SendMessageInMeasurement(
+                        seq,
+                        convert_to_embassy_instant(timestamp),
+                    ))
+                    .ok();
             }
 
             // Notify UI of transmission using the node's effective distance
@@ -502,6 +721,7 @@ async fn process_event(
                     *node_id,
                     *message_type,
                     effective_distance,
+                    *length,
                 ))
                 .ok();
 
@@ -514,9 +734,32 @@ async fn process_event(
                 ))
                 .ok();
         }
-        LogEvent::ReceivePacket { node_id, .. } => {
+        LogEvent::ReceivePacket {
+            node_id,
+            sender_id,
+            message_type,
+            link_quality,
+            ..
+        } => {
             *total_received += 1;
 
+            let _ = ui_refresh_tx
+                .try_send(UIRefreshState::PacketDelivered(
+                    *sender_id,
+                    *node_id,
+                    *message_type,
+                ))
+                .ok();
+            if *message_type == moonblokz_radio_lib::MessageType::Echo as u8 {
+                let _ = ui_refresh_tx
+                    .try_send(UIRefreshState::LinkQualityObserved(
+                        *sender_id,
+                        *node_id,
+                        *link_quality,
+                    ))
+                    .ok();
+            }
+
             // Store in history
             state.add_packet_record(
                 *node_id,
@@ -536,7 +779,21 @@ async fn process_event(
                 .ok();
         }
         LogEvent::StartMeasurement { node_id, sequence } => {
-            state.active_measurement_id = Some(*sequence);
+            state.measurement_reach.insert(
+                *sequence,
+                MeasurementReach {
+                    origin_node_id: *node_id,
+                    start: timestamp,
+                    reached: HashMap::from([(*node_id, timestamp)]),
+                },
+            );
+            let _ = ui_refresh_tx
+                .try_send(UIRefreshState::MeasurementStarted(
+                    *node_id,
+                    *sequence,
+                    convert_to_embassy_instant(timestamp),
+                ))
+                .ok();
             log::info!(
                 "Measurement started by node {} with sequence {}",
                 node_id,
@@ -545,26 +802,37 @@ async fn process_event(
         }
         LogEvent::ReceivedFullMessage {
             node_id,
+            sender_id,
             message_type,
             sequence,
             ..
-        } => {}
+        } => {
+            // Firmware without *TM6* lines reports measurement blocks as full AddBlock messages
+            if *message_type == moonblokz_radio_lib::MessageType::AddBlock as u8 {
+                record_measurement_reach(
+                    state,
+                    ui_refresh_tx,
+                    *node_id,
+                    *sender_id,
+                    *sequence,
+                    timestamp,
+                );
+            }
+        }
         LogEvent::AddBlockReceived {
             node_id,
             sender_id,
             sequence,
             length,
         } => {
-            // If this is an AddBlock message (type 6), it might be part of a measurement
-            if let Some(active_id) = state.active_measurement_id {
-                if active_id == *sequence {
-                    let _ = ui_refresh_tx
-                        .try_send(UIRefreshState::NodeReachedInMeasurement(
-                            *node_id, *sequence,
-                        ))
-                        .ok();
-                }
-            }
+            record_measurement_reach(
+                state,
+                ui_refresh_tx,
+                *node_id,
+                *sender_id,
+                *sequence,
+                timestamp,
+            );
 
             // Store as FullMessage for Message Stream tab
             state.add_full_message(
@@ -632,6 +900,19 @@ async fn process_event(
         }
     }
 
+    // Publish per-node statistics for the node table at most once per second
+    if state
+        .last_statistics_publish
+        .map_or(true, |t| t.elapsed() >= std::time::Duration::from_secs(1))
+    {
+        state.last_statistics_publish = Some(std::time::Instant::now());
+        let _ = ui_refresh_tx
+            .try_send(UIRefreshState::NodeStatisticsUpdated(
+                state.node_statistics.clone(),
+            ))
+            .ok();
+    }
+
     // Send timestamp update for UI display (as embassy_time::Instant from Unix epoch)
     let _ = ui_refresh_tx
         .try_send(UIRefreshState::TimeUpdated(convert_to_embassy_instant(
@@ -640,6 +921,42 @@ async fn process_event(
         .ok();
 }
 
+/// Record the first reception of a measurement block by a node and report it to the
+/// measurement panel (blocks of unknown measurements are ignored).
+///
+/// # Parameters
+///
+/// * `state` - Analyzer state holding the measurement reach history
+/// * `ui_refresh_tx` - Channel for UI updates
+/// * `node_id` - Receiving node
+/// * `sender_id` - Node the block was received from (its first relayer)
+/// * `sequence` - AddBlock sequence (measurement ID)
+/// * `timestamp` - Log timestamp of the reception
+fn record_measurement_reach(
+    state: &mut AnalyzerState,
+    ui_refresh_tx: &UIRefreshQueueSender,
+    node_id: u32,
+    sender_id: u32,
+    sequence: u32,
+    timestamp: DateTime<Utc>,
+) {
+    let Some(reach) = state.measurement_reach.get_mut(&sequence) else {
+        return;
+    };
+    if reach.reached.contains_key(&node_id) {
+        return;
+    }
+    reach.reached.insert(node_id, timestamp);
+    let _ = ui_refresh_tx
+        .try_send(UIRefreshState::NodeReachedInMeasurement(
+            node_id,
+            sequence,
+            Some(sender_id),
+            convert_to_embassy_instant(timestamp),
+        ))
+        .ok();
+}
+
 /// Build NodeInfo from the analyzer's packet history for a given node.
 ///
 /// Converts stored `NodePacketRecord` entries into `NodeMessage` format
@@ -649,15 +966,17 @@ async fn process_event(
 ///
 /// * `node_id` - The node to build info for
 /// * `state` - Analyzer state containing packet histories
+/// * `until` - Only include entries up to this log timestamp (None = everything)
 ///
 /// # Returns
 ///
 /// A `NodeInfo` struct with the node's message history.
-fn build_node_info(node_id: u32, state: &AnalyzerState) -> NodeInfo {
+fn build_node_info(node_id: u32, state: &AnalyzerState, until: Option<DateTime<Utc>>) -> NodeInfo {
     // Build radio packets from packet history
     let messages = if let Some(history) = state.node_packet_histories.get(&node_id) {
+        let end = entries_until(history, until, |record| record.timestamp);
         history
-            .iter()
+            .range(..end)
             .filter_map(|record| {
                 // Convert DateTime<Utc> to embassy_time::Instant based on absolute timestamp
                 let timestamp = convert_to_embassy_instant(record.timestamp);
@@ -679,7 +998,10 @@ fn build_node_info(node_id: u32, state: &AnalyzerState) -> NodeInfo {
                         sender_node: node_id, // Self-sent
                         link_quality: 0,
                         collision: false,
+                        missed_while_transmitting: false,
+                        missed_while_sleeping: false,
                         sequence: *sequence,
+                        packet: None,
                     }),
                     LogEvent::ReceivePacket {
                         sender_id,
@@ -699,7 +1021,10 @@ fn build_node_info(node_id: u32, state: &AnalyzerState) -> NodeInfo {
                         sender_node: *sender_id,
                         link_quality: *link_quality,
                         collision: false,
+                        missed_while_transmitting: false,
+                        missed_while_sleeping: false,
                         sequence: *sequence,
+                        packet: None,
                     }),
                     LogEvent::PacketCrcError { link_quality, .. } => Some(NodeMessage {
                         timestamp,
@@ -710,7 +1035,10 @@ fn build_node_info(node_id: u32, state: &AnalyzerState) -> NodeInfo {
                         sender_node: 0, // Unknown sender
                         link_quality: *link_quality,
                         collision: true, // Mark as collision for red background
+                        missed_while_transmitting: false,
+                        missed_while_sleeping: false,
                         sequence: None,
+                        packet: None,
                     }),
                     _ => None, // Skip other event types
                 }
@@ -722,8 +1050,9 @@ fn build_node_info(node_id: u32, state: &AnalyzerState) -> NodeInfo {
 
     // Build log lines from raw log history
     let log_lines = if let Some(history) = state.node_log_histories.get(&node_id) {
+        let end = entries_until(history, until, |raw_log| raw_log.timestamp);
         history
-            .iter()
+            .range(..end)
             .map(|raw_log| LogLine {
                 timestamp: convert_to_embassy_instant(raw_log.timestamp),
                 content: raw_log.content.clone(),
@@ -736,7 +1065,9 @@ fn build_node_info(node_id: u32, state: &AnalyzerState) -> NodeInfo {
 
     // Build full messages from TM6/TM7 event history
     let full_messages = if let Some(history) = state.node_full_messages.get(&node_id) {
-        history.iter().cloned().collect()
+        let until = until.map(convert_to_embassy_instant);
+        let end = entries_until(history, until, |message| message.timestamp);
+        history.range(..end).cloned().collect()
     } else {
         Vec::new()
     };
@@ -758,6 +1089,72 @@ fn build_node_info(node_id: u32, state: &AnalyzerState) -> NodeInfo {
     }
 }
 
+/// Number of leading history entries with a timestamp up to `until` (all if None).
+///
+/// Histories are appended in log order, so the cut-off is found with a binary search.
+fn entries_until<T, K: PartialOrd>(
+    history: &VecDeque<T>,
+    until: Option<K>,
+    timestamp: impl Fn(&T) -> K,
+) -> usize {
+    match until {
+        Some(until) => history.partition_point(|entry| timestamp(entry) <= until),
+        None => history.len(),
+    }
+}
+
+/// Reconstruct a node's packet counters and measurement reach status at a log timestamp.
+///
+/// # Parameters
+///
+/// * `node_id` - The node to inspect
+/// * `state` - Analyzer state containing the timelines and measurement reach history
+/// * `until` - Inspected log timestamp (inclusive)
+///
+/// # Returns
+///
+/// The node state as of `until`.
+fn build_node_state_at(
+    node_id: u32,
+    state: &AnalyzerState,
+    until: DateTime<Utc>,
+) -> NodeStateSnapshot {
+    let counters = state
+        .node_timelines
+        .get(&node_id)
+        .and_then(|timeline| timeline.at(until));
+
+    let mut measurements: Vec<MeasurementReachStatus> = state
+        .measurement_reach
+        .iter()
+        .filter(|(_, reach)| reach.start <= until)
+        .map(|(measurement_id, reach)| MeasurementReachStatus {
+            measurement_id: *measurement_id,
+            origin_node_id: reach.origin_node_id,
+            reached_after: reach
+                .reached
+                .get(&node_id)
+                .filter(|reached| **reached <= until)
+                .map(|reached| {
+                    let elapsed_ms = reached
+                        .signed_duration_since(reach.start)
+                        .num_milliseconds();
+                    embassy_time::Duration::from_millis(elapsed_ms.max(0) as u64)
+                }),
+        })
+        .collect();
+    measurements.sort_by_key(|status| state.measurement_reach[&status.measurement_id].start);
+
+    NodeStateSnapshot {
+        node_id,
+        time: convert_to_embassy_instant(until),
+        tx_packets: counters.map_or(0, |c| c.tx_packets),
+        rx_packets: counters.map_or(0, |c| c.rx_packets),
+        crc_errors: counters.map_or(0, |c| c.crc_errors),
+        measurements,
+    }
+}
+
 /// Convert a DateTime<Utc> timestamp to an embassy_time::Instant.
 ///
 /// Creates an instant based on the absolute timestamp by converting
@@ -767,3 +1164,61 @@ fn convert_to_embassy_instant(timestamp: DateTime<Utc>) -> embassy_time::Instant
     let timestamp_ms = timestamp.timestamp_millis().max(0) as u64;
     embassy_time::Instant::from_millis(timestamp_ms)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UIRefreshQueue;
+
+    #[test]
+    fn test_time_travel_reconstructs_node_state_around_an_event() {
+        let log = [
+            "2025-10-23T18:00:00Z [1] *TM3* Start measurement: sequence: 7",
+            "2025-10-23T18:00:01Z [1] *TM1* Packet transmitted: type: 6, sequence: 7, length: 215, packet: 1/1",
+            "2025-10-23T18:00:02Z [2] *TM2* Packet received: sender: 1, type: 6, sequence: 7, length: 215, packet: 1/1, link quality: 26",
+            "2025-10-23T18:00:02Z [2] *TM6* Received AddBlock message: sender: 1, sequence: 7, length: 215",
+        ];
+        let queue: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+        let tx = queue.sender();
+        let mut state = AnalyzerState::new();
+        let (mut sent, mut received) = (0, 0);
+        for line in log {
+            let (timestamp, event) = parse_log_line(line).unwrap();
+            embassy_futures::block_on(process_event(
+                &event,
+                timestamp,
+                &mut state,
+                &tx,
+                &mut sent,
+                &mut received,
+                &HashMap::new(),
+            ));
+        }
+
+        // Just before node 2 hears node 1, nothing has reached it yet
+        let before: DateTime<Utc> = "2025-10-23T18:00:01.500Z".parse().unwrap();
+        let snapshot = build_node_state_at(2, &state, before);
+        assert_eq!(snapshot.rx_packets, 0);
+        assert_eq!(snapshot.measurements.len(), 1);
+        assert_eq!(snapshot.measurements[0].reached_after, None);
+        assert!(
+            build_node_info(2, &state, Some(before))
+                .radio_packets
+                .is_empty()
+        );
+        assert_eq!(build_node_state_at(1, &state, before).tx_packets, 1);
+
+        // At the reception the packet and the measurement reach show up
+        let after: DateTime<Utc> = "2025-10-23T18:00:02Z".parse().unwrap();
+        let snapshot = build_node_state_at(2, &state, after);
+        assert_eq!(snapshot.rx_packets, 1);
+        assert_eq!(
+            snapshot.measurements[0].reached_after,
+            Some(embassy_time::Duration::from_secs(2))
+        );
+        assert_eq!(
+            build_node_info(2, &state, Some(after)).radio_packets.len(),
+            1
+        );
+    }
+}
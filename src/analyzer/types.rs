@@ -1,8 +1,9 @@
 //! Type definitions specific to the analyzer module.
 
 use chrono::{DateTime, Utc};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
+use crate::common::node_statistics::NodeStatistics;
 use crate::simulation::types::{FullMessage, LogLevel};
 
 /// Analyzer operating mode.
@@ -80,6 +81,84 @@ pub struct NodePacketRecord {
     pub event: LogEvent,
 }
 
+/// Maximum number of timeline entries kept per node.
+const MAX_TIMELINE_ENTRIES: usize = 10_000;
+
+/// Cumulative packet counters of a node after one of its packet events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineEntry {
+    pub timestamp: DateTime<Utc>,
+    pub tx_packets: u64,
+    pub rx_packets: u64,
+    pub crc_errors: u64,
+}
+
+/// Time-indexed cumulative packet counters of a node (log visualization time travel).
+///
+/// Unlike the bounded packet history, the timeline covers the whole log, so the
+/// counters at any timestamp can be found with a binary search. It keeps one
+/// compact entry per packet event up to `MAX_TIMELINE_ENTRIES`; beyond that every
+/// second entry is dropped, so a long log is covered at a coarser resolution and a
+/// lookup between two kept entries returns the counters of the earlier one.
+#[derive(Debug, Clone, Default)]
+pub struct NodeTimeline {
+    entries: Vec<TimelineEntry>,
+}
+
+impl NodeTimeline {
+    /// Append the counters after a packet event.
+    ///
+    /// Log lines are expected in timestamp order; an out-of-order timestamp is
+    /// clamped to the previous one to keep the entries sorted.
+    pub fn record(&mut self, timestamp: DateTime<Utc>, event: &LogEvent) {
+        let mut entry = self.entries.last().copied().unwrap_or(TimelineEntry {
+            timestamp,
+            tx_packets: 0,
+            rx_packets: 0,
+            crc_errors: 0,
+        });
+        entry.timestamp = entry.timestamp.max(timestamp);
+        match event {
+            LogEvent::SendPacket { .. } => entry.tx_packets += 1,
+            LogEvent::ReceivePacket { .. } => entry.rx_packets += 1,
+            LogEvent::PacketCrcError { .. } => entry.crc_errors += 1,
+            _ => return,
+        }
+        self.entries.push(entry);
+        if self.entries.len() > MAX_TIMELINE_ENTRIES {
+            self.thin();
+        }
+    }
+
+    /// Drop every second entry, keeping the latest one (the base of the next entry).
+    fn thin(&mut self) {
+        let last = self.entries.len() - 1;
+        let mut index = 0;
+        self.entries.retain(|_| {
+            let keep = index % 2 == 1 || index == last;
+            index += 1;
+            keep
+        });
+    }
+
+    /// Counters as of `timestamp` (inclusive); None if no packet event happened yet.
+    pub fn at(&self, timestamp: DateTime<Utc>) -> Option<TimelineEntry> {
+        let index = self.entries.partition_point(|e| e.timestamp <= timestamp);
+        index.checked_sub(1).map(|i| self.entries[i])
+    }
+}
+
+/// Reach history of a measurement seen in the log (TM3 start, TM6 receptions).
+#[derive(Debug, Clone)]
+pub struct MeasurementReach {
+    /// Node that started the measurement.
+    pub origin_node_id: u32,
+    /// Log timestamp of the measurement start.
+    pub start: DateTime<Utc>,
+    /// First AddBlock reception time per node.
+    pub reached: HashMap<u32, DateTime<Utc>>,
+}
+
 /// Raw log line record for the Log Stream tab.
 #[derive(Debug, Clone)]
 pub struct RawLogLine {
@@ -98,8 +177,6 @@ pub struct AnalyzerState {
     pub reference_timestamp: Option<DateTime<Utc>>,
     /// Real-time instant when reference_timestamp was set.
     pub reference_instant: Option<std::time::Instant>,
-    /// Currently active measurement ID (from *TM3*).
-    pub active_measurement_id: Option<u32>,
     /// Per-node packet history for RequestNodeInfo responses.
     pub node_packet_histories: HashMap<u32, VecDeque<NodePacketRecord>>,
     /// Per-node raw log line history for the Log Stream tab.
@@ -110,6 +187,35 @@ pub struct AnalyzerState {
     pub last_processed_timestamp: Option<DateTime<Utc>>,
     /// Per-node version info from TM8: (probe_version, node_version).
     pub node_versions: HashMap<u32, (u8, u8)>,
+    /// Cumulative per-node radio statistics for the node table.
+    pub node_statistics: HashMap<u32, NodeStatistics>,
+    /// Senders each node received packets from (its neighbor count).
+    pub heard_senders: HashMap<u32, HashSet<u32>>,
+    /// Real-time instant when node statistics were last published to the UI.
+    pub last_statistics_publish: Option<std::time::Instant>,
+    /// Latest log timestamp of the nodes that logged since the last activity report.
+    pub pending_activity: HashMap<u32, embassy_time::Instant>,
+    /// Real-time instant when node activity was last reported to the UI.
+    pub last_activity_publish: Option<std::time::Instant>,
+    /// Per-node time-indexed packet counters (time travel inspection).
+    pub node_timelines: HashMap<u32, NodeTimeline>,
+    /// Reach history of every measurement seen in the log, keyed by sequence.
+    pub measurement_reach: HashMap<u32, MeasurementReach>,
+    /// Whether log playback is paused (log visualization only).
+    pub playback_paused: bool,
+    /// Whether the playback speed follows the processing delay (log visualization only).
+    pub auto_speed_enabled: bool,
+    /// Set when the UI asked to leave the current site (open another one or end the session).
+    pub exit: Option<SiteExit>,
+}
+
+/// Why the analyzer leaves the current site.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SiteExit {
+    /// Open another site: scene and log paths (Real-time Tracking only).
+    OpenSite(String, String),
+    /// The user returned to the mode selector.
+    EndSession,
 }
 
 impl AnalyzerState {
@@ -117,17 +223,49 @@ impl AnalyzerState {
         Self {
             reference_timestamp: None,
             reference_instant: None,
-            active_measurement_id: None,
             node_packet_histories: HashMap::new(),
             node_log_histories: HashMap::new(),
             node_full_messages: HashMap::new(),
             last_processed_timestamp: None,
             node_versions: HashMap::new(),
+            node_statistics: HashMap::new(),
+            heard_senders: HashMap::new(),
+            last_statistics_publish: None,
+            pending_activity: HashMap::new(),
+            last_activity_publish: None,
+            node_timelines: HashMap::new(),
+            measurement_reach: HashMap::new(),
+            playback_paused: false,
+            auto_speed_enabled: false,
+            exit: None,
         }
     }
 
-    /// Add a packet record to a node's history.
+    /// Add a packet record to a node's history and update the node's statistics.
     pub fn add_packet_record(&mut self, node_id: u32, record: NodePacketRecord) {
+        let statistics = self.node_statistics.entry(node_id).or_default();
+        match &record.event {
+            LogEvent::SendPacket { .. } => statistics.record_tx(),
+            LogEvent::ReceivePacket {
+                sender_id,
+                link_quality,
+                ..
+            } => {
+                let new_neighbor = self
+                    .heard_senders
+                    .entry(node_id)
+                    .or_default()
+                    .insert(*sender_id);
+                statistics.record_rx(*link_quality, new_neighbor)
+            }
+            LogEvent::PacketCrcError { .. } => statistics.record_collision(),
+            _ => {}
+        }
+        self.node_timelines
+            .entry(node_id)
+            .or_default()
+            .record(record.timestamp, &record.event);
+
         const MAX_HISTORY: usize = 1000;
         let history = self
             .node_packet_histories
@@ -171,3 +309,64 @@ impl Default for AnalyzerState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_timeline_counters_at_timestamp() {
+        let t = |secs| Utc.timestamp_opt(secs, 0).unwrap();
+        let send = LogEvent::SendPacket {
+            node_id: 1,
+            message_type: 2,
+            sequence: None,
+            packet_index: 0,
+            packet_count: 1,
+            length: 10,
+        };
+        let crc = LogEvent::PacketCrcError {
+            node_id: 1,
+            link_quality: 0,
+        };
+
+        let mut timeline = NodeTimeline::default();
+        timeline.record(t(10), &send);
+        timeline.record(t(20), &crc);
+        timeline.record(t(30), &send);
+
+        assert_eq!(timeline.at(t(5)), None);
+        let at_20 = timeline.at(t(20)).unwrap();
+        assert_eq!((at_20.tx_packets, at_20.crc_errors), (1, 1));
+        assert_eq!(timeline.at(t(25)), Some(at_20));
+        assert_eq!(timeline.at(t(99)).unwrap().tx_packets, 2);
+    }
+
+    #[test]
+    fn test_timeline_storage_is_bounded() {
+        let t = |secs| Utc.timestamp_opt(secs, 0).unwrap();
+        let send = LogEvent::SendPacket {
+            node_id: 1,
+            message_type: 2,
+            sequence: None,
+            packet_index: 0,
+            packet_count: 1,
+            length: 10,
+        };
+
+        let mut timeline = NodeTimeline::default();
+        let events = 3 * MAX_TIMELINE_ENTRIES as i64;
+        for secs in 1..=events {
+            timeline.record(t(secs), &send);
+        }
+
+        assert!(timeline.entries.len() <= MAX_TIMELINE_ENTRIES);
+        // The latest counters stay exact, earlier ones are sampled
+        assert_eq!(timeline.at(t(events)).unwrap().tx_packets, events as u64);
+        let sampled = timeline.at(t(events / 2)).unwrap();
+        assert!(sampled.tx_packets <= events as u64 / 2);
+        assert!(sampled.tx_packets + 16 >= events as u64 / 2);
+        assert_eq!(timeline.at(t(0)), None);
+    }
+}
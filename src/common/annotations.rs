@@ -0,0 +1,143 @@
+//! # Scene Annotations
+//!
+//! Text labels, arrows and colored regions shown on the map, so results can be
+//! presented without post-editing screenshots ("hospital roof", "dead zone").
+//! Annotations are listed in the scene's `annotations` array in world units, are
+//! purely visual and do not affect the simulation or the analysis. Both scene
+//! types share this definition; the UI can also draw new ones and export them in
+//! the same format for pasting into the scene.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::scene::Point;
+
+/// A map annotation in world units.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    /// Text anchored at its center.
+    Label {
+        position: Point,
+        text: String,
+        /// `#rrggbb` or `#rrggbbaa` (white if missing).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+    },
+    /// Arrow from `from` to `to`, with optional text at its tail.
+    Arrow {
+        from: Point,
+        to: Point,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        /// `#rrggbb` or `#rrggbbaa` (white if missing).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+    },
+    /// Translucent rectangle spanned by two opposite corners, with optional text
+    /// in its top-left corner.
+    Region {
+        corner1: Point,
+        corner2: Point,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        /// `#rrggbb` or `#rrggbbaa` (white if missing); the fill uses a fraction of its alpha.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+    },
+}
+
+impl Annotation {
+    /// Color of the annotation, if set.
+    pub fn color(&self) -> Option<&str> {
+        match self {
+            Annotation::Label { color, .. }
+            | Annotation::Arrow { color, .. }
+            | Annotation::Region { color, .. } => color.as_deref(),
+        }
+    }
+
+    /// Text of the annotation, if any.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Annotation::Label { text, .. } => Some(text),
+            Annotation::Arrow { text, .. } | Annotation::Region { text, .. } => text.as_deref(),
+        }
+    }
+}
+
+/// Parse a `#rrggbb` or `#rrggbbaa` color into RGBA components.
+///
+/// # Returns
+///
+/// The components (alpha 255 if not given), or `None` if the string is not a valid color.
+pub fn parse_color(color: &str) -> Option<[u8; 4]> {
+    let hex = color.strip_prefix('#')?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+    let mut rgba = [255u8; 4];
+    for (index, component) in rgba.iter_mut().enumerate().take(hex.len() / 2) {
+        *component = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok()?;
+    }
+    Some(rgba)
+}
+
+/// Format RGBA components as `#rrggbb`, or `#rrggbbaa` when not opaque.
+pub fn format_color(rgba: [u8; 4]) -> String {
+    let [r, g, b, a] = rgba;
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+/// Validate the annotations of a scene (colors must parse).
+///
+/// # Returns
+///
+/// `Ok(())` if all annotations are valid, `Err(String)` describing the first invalid one otherwise.
+pub fn validate_annotations(annotations: &[Annotation]) -> Result<(), String> {
+    for (idx, annotation) in annotations.iter().enumerate() {
+        if let Some(color) = annotation.color() {
+            if parse_color(color).is_none() {
+                return Err(format!(
+                    "Annotation {} has invalid color '{}' (expected #rrggbb or #rrggbbaa)",
+                    idx, color
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_round_trip_and_scene_entries_parse() {
+        assert_eq!(parse_color("#ff8000"), Some([255, 128, 0, 255]));
+        assert_eq!(parse_color("#ff800040"), Some([255, 128, 0, 64]));
+        assert_eq!(parse_color("ff8000"), None);
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("#gg8000"), None);
+        assert_eq!(format_color([255, 128, 0, 255]), "#ff8000");
+        assert_eq!(format_color([255, 128, 0, 64]), "#ff800040");
+
+        let annotations: Vec<Annotation> = serde_json::from_str(
+            r##"[
+                { "type": "label", "position": { "x": 10, "y": 20 }, "text": "hospital roof" },
+                { "type": "region", "corner1": { "x": 0, "y": 0 }, "corner2": { "x": 5, "y": 5 },
+                  "text": "dead zone", "color": "#ff000080" },
+                { "type": "arrow", "from": { "x": 1, "y": 1 }, "to": { "x": 2, "y": 2 }, "color": "red" }
+            ]"##,
+        )
+        .unwrap();
+        assert_eq!(annotations[0].text(), Some("hospital roof"));
+        assert_eq!(annotations[1].color(), Some("#ff000080"));
+        assert!(validate_annotations(&annotations[..2]).is_ok());
+        assert!(validate_annotations(&annotations).is_err());
+    }
+}
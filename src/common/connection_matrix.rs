@@ -1,8 +1,17 @@
 //! Connection matrix parsing utilities shared between simulation and analyzer.
+//!
+//! Also defines the link matrix file used to export the matrices of all nodes and
+//! to import them as the initial state of a later simulation run:
+//! `{ "matrices": [ { "node_id": 1, "node_ids": [1, 2], "values": [[0, 40], [38, 0]] } ] }`,
+//! plus the `provenance` of the exporting run (ignored on import).
 
+use anyhow::{Result, bail};
 use embassy_time::Instant;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::provenance::Provenance;
+
 /// Decoded connection matrix for a requester node.
 #[derive(Debug, Clone)]
 pub struct ConnectionMatrix {
@@ -115,6 +124,81 @@ impl ConnectionMatrixParser {
     }
 }
 
+/// One node's matrix in a link matrix file.
+#[derive(Serialize, Deserialize)]
+struct LinkMatrixEntry {
+    node_id: u32,
+    node_ids: Vec<u32>,
+    values: Vec<Vec<u8>>,
+}
+
+/// Link matrix file contents.
+#[derive(Serialize, Deserialize)]
+struct LinkMatrixFile {
+    matrices: Vec<LinkMatrixEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    provenance: Option<Provenance>,
+}
+
+/// Serialize connection matrices into a link matrix file (ordered by node ID).
+pub fn link_matrices_to_json<'a>(
+    matrices: impl IntoIterator<Item = &'a ConnectionMatrix>,
+    provenance: Option<Provenance>,
+) -> Result<String> {
+    let mut matrices: Vec<LinkMatrixEntry> = matrices
+        .into_iter()
+        .map(|matrix| LinkMatrixEntry {
+            node_id: matrix.requester_node_id,
+            node_ids: matrix.node_ids.clone(),
+            values: matrix.values.clone(),
+        })
+        .collect();
+    matrices.sort_by_key(|entry| entry.node_id);
+    Ok(serde_json::to_string_pretty(&LinkMatrixFile {
+        matrices,
+        provenance,
+    })?)
+}
+
+/// Parse a link matrix file.
+///
+/// # Returns
+///
+/// The matrices (timestamped at zero), or an error if the file is invalid or a
+/// matrix is not square or holds values above 63.
+pub fn parse_link_matrices(text: &str) -> Result<Vec<ConnectionMatrix>> {
+    let file: LinkMatrixFile = serde_json::from_str(text)?;
+    file.matrices
+        .into_iter()
+        .map(|entry| {
+            let node_count = entry.node_ids.len();
+            if entry.values.len() != node_count
+                || entry.values.iter().any(|row| row.len() != node_count)
+            {
+                bail!(
+                    "matrix of node {} is not {}x{}",
+                    entry.node_id,
+                    node_count,
+                    node_count
+                );
+            }
+            if entry.values.iter().flatten().any(|value| *value > 63) {
+                bail!(
+                    "matrix of node {} has link qualities above 63",
+                    entry.node_id
+                );
+            }
+            Ok(ConnectionMatrix {
+                requester_node_id: entry.node_id,
+                node_count,
+                timestamp: Instant::from_ticks(0),
+                node_ids: entry.node_ids,
+                values: entry.values,
+            })
+        })
+        .collect()
+}
+
 fn parse_start_line(content: &str) -> Option<usize> {
     let marker = "Logging Connection Matrix, node_count:";
     let idx = content.find(marker)?;
@@ -162,4 +246,24 @@ mod tests {
         let v = decode_chunk("Aa0-_").unwrap();
         assert_eq!(v, vec![0, 26, 52, 62, 63]);
     }
+
+    #[test]
+    fn link_matrix_file_round_trip() {
+        let matrix = ConnectionMatrix {
+            requester_node_id: 7,
+            node_count: 2,
+            timestamp: Instant::from_ticks(0),
+            node_ids: vec![7, 9],
+            values: vec![vec![0, 40], vec![38, 0]],
+        };
+        let json = link_matrices_to_json([&matrix], None).unwrap();
+        let parsed = parse_link_matrices(&json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].requester_node_id, 7);
+        assert_eq!(parsed[0].node_ids, vec![7, 9]);
+        assert_eq!(parsed[0].values, matrix.values);
+
+        let ragged = r#"{"matrices":[{"node_id":1,"node_ids":[1,2],"values":[[0,1]]}]}"#;
+        assert!(parse_link_matrices(ragged).is_err());
+    }
 }
@@ -0,0 +1,155 @@
+//! LoRa regional presets.
+//!
+//! A scene can name a regional preset instead of filling raw modulation numbers by
+//! hand: `"lora_preset": { "region": "eu868", "spreading_factor": 9 }`. The preset
+//! fills `lora_parameters` (bandwidth, spreading factor, coding rate 4/5, 8 preamble
+//! symbols, CRC on, low data rate optimization where the symbol time reaches 16 ms)
+//! and the region's `regulatory_limits` (maximum TX power and allowed spreading
+//! factors). Fields the scene sets itself in `lora_parameters` or `regulatory_limits`
+//! override the preset's values. The expansion happens when the scene JSON is read
+//! (see `scene::read_scene_value`), so every consumer sees the filled parameters.
+
+use serde_json::{Value, json};
+
+/// A regional LoRa channel plan.
+#[derive(Debug, Clone, Copy)]
+pub struct LoraPreset {
+    /// Name used in scene files.
+    pub region: &'static str,
+    pub description: &'static str,
+    /// Channel bandwidth in Hz.
+    pub bandwidth: u32,
+    /// Spreading factors allowed in the region.
+    pub min_spreading_factor: u8,
+    pub max_spreading_factor: u8,
+    /// Spreading factor used when the scene does not choose one.
+    pub default_spreading_factor: u8,
+    /// Maximum transmit power in dBm.
+    pub max_tx_power_dbm: f32,
+}
+
+/// Presets selectable with `lora_preset.region`.
+pub static LORA_PRESETS: [LoraPreset; 3] = [
+    LoraPreset {
+        region: "eu868",
+        description: "EU 863-870 MHz, 125 kHz, SF7-12, 14 dBm",
+        bandwidth: 125_000,
+        min_spreading_factor: 7,
+        max_spreading_factor: 12,
+        default_spreading_factor: 9,
+        max_tx_power_dbm: 14.0,
+    },
+    LoraPreset {
+        region: "us915",
+        description: "US 902-928 MHz, 125 kHz, SF7-10, 30 dBm",
+        bandwidth: 125_000,
+        min_spreading_factor: 7,
+        max_spreading_factor: 10,
+        default_spreading_factor: 7,
+        max_tx_power_dbm: 30.0,
+    },
+    LoraPreset {
+        region: "ism2400",
+        description: "2.4 GHz LoRa, 812.5 kHz, SF5-12, 10 dBm",
+        bandwidth: 812_500,
+        min_spreading_factor: 5,
+        max_spreading_factor: 12,
+        default_spreading_factor: 7,
+        max_tx_power_dbm: 10.0,
+    },
+];
+
+/// Find a preset by region name (case-insensitive).
+pub fn find_preset(region: &str) -> Option<&'static LoraPreset> {
+    LORA_PRESETS
+        .iter()
+        .find(|preset| preset.region.eq_ignore_ascii_case(region))
+}
+
+/// Expand a `lora_preset` scene entry into `lora_parameters` and `regulatory_limits`.
+///
+/// # Parameters
+///
+/// * `preset` - The `lora_preset` value: `region` and optional `spreading_factor`
+///
+/// # Returns
+///
+/// A JSON object with the preset's `lora_parameters` and `regulatory_limits`, or an
+/// error for an unknown region or a spreading factor the region does not allow.
+pub fn expand_preset(preset: &Value) -> Result<Value, String> {
+    let region = preset
+        .get("region")
+        .and_then(Value::as_str)
+        .ok_or("lora_preset must name a region")?;
+    let Some(found) = find_preset(region) else {
+        let known: Vec<String> = LORA_PRESETS
+            .iter()
+            .map(|preset| format!("{} ({})", preset.region, preset.description))
+            .collect();
+        return Err(format!(
+            "Unknown lora_preset region '{}' (known: {})",
+            region,
+            known.join(", ")
+        ));
+    };
+    let spreading_factor = match preset.get("spreading_factor") {
+        Some(value) => value
+            .as_u64()
+            .and_then(|sf| u8::try_from(sf).ok())
+            .ok_or("lora_preset spreading_factor must be an integer")?,
+        None => found.default_spreading_factor,
+    };
+    if !(found.min_spreading_factor..=found.max_spreading_factor).contains(&spreading_factor) {
+        return Err(format!(
+            "lora_preset {} allows SF{}-{}, not SF{}",
+            found.region, found.min_spreading_factor, found.max_spreading_factor, spreading_factor
+        ));
+    }
+
+    let symbol_time = f64::from(1u32 << spreading_factor) / f64::from(found.bandwidth);
+    Ok(json!({
+        "lora_parameters": {
+            "bandwidth": found.bandwidth,
+            "spreading_factor": spreading_factor,
+            "coding_rate": 1,
+            "preamble_symbols": 8.0,
+            "crc_enabled": true,
+            "low_data_rate_optimization": symbol_time >= 0.016,
+        },
+        "regulatory_limits": {
+            "region": found.region,
+            "max_tx_power_dbm": found.max_tx_power_dbm,
+            "min_spreading_factor": found.min_spreading_factor,
+            "max_spreading_factor": found.max_spreading_factor,
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_fill_parameters_and_reject_foreign_spreading_factors() {
+        let expanded =
+            expand_preset(&json!({ "region": "EU868", "spreading_factor": 12 })).unwrap();
+        assert_eq!(expanded["lora_parameters"]["bandwidth"], 125_000);
+        assert_eq!(expanded["lora_parameters"]["spreading_factor"], 12);
+        // SF12 at 125 kHz: 32.8 ms symbols need low data rate optimization
+        assert_eq!(
+            expanded["lora_parameters"]["low_data_rate_optimization"],
+            true
+        );
+        assert_eq!(expanded["regulatory_limits"]["max_tx_power_dbm"], 14.0);
+
+        let expanded = expand_preset(&json!({ "region": "us915" })).unwrap();
+        assert_eq!(expanded["lora_parameters"]["spreading_factor"], 7);
+        assert_eq!(
+            expanded["lora_parameters"]["low_data_rate_optimization"],
+            false
+        );
+
+        assert!(expand_preset(&json!({ "region": "us915", "spreading_factor": 12 })).is_err());
+        assert!(expand_preset(&json!({ "region": "as923" })).is_err());
+    }
+}
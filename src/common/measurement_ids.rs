@@ -0,0 +1,58 @@
+//! Measurement ID allocation.
+//!
+//! Measurements started from the UI and from scene scripts draw their IDs from one
+//! allocator, so two measurements of a session never share an ID. IDs are handed
+//! out in increasing order. Every session restarts the sequence with `reset`:
+//! simulation sessions at 1, so reruns of a scene (determinism audit) use the same
+//! IDs, the analyzer modes at the current Unix time in seconds, so measurements of a
+//! new session do not reuse the AddBlock sequences of earlier sessions in the log.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Next measurement ID to hand out.
+static NEXT_MEASUREMENT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Restart the ID sequence of a new session.
+///
+/// # Parameters
+///
+/// * `first` - First ID of the session (0 is skipped)
+pub fn reset(first: u32) {
+    NEXT_MEASUREMENT_ID.store(first.max(1), Ordering::Relaxed);
+}
+
+/// Restart the ID sequence at the current Unix time in seconds (analyzer modes).
+pub fn reset_from_clock() {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(1, |elapsed| elapsed.as_secs() as u32);
+    reset(now);
+}
+
+/// Allocate the ID of a new measurement (never 0).
+pub fn next() -> u32 {
+    loop {
+        let id = NEXT_MEASUREMENT_ID.fetch_add(1, Ordering::Relaxed);
+        if id != 0 {
+            return id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_increase_and_skip_zero() {
+        // The sequence wraps around without handing out 0
+        reset(u32::MAX);
+        assert_ne!(next(), 0);
+        assert_ne!(next(), 0);
+
+        reset(5);
+        let first = next();
+        assert!(first >= 5);
+        assert!(next() > first);
+    }
+}
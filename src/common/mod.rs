@@ -1,4 +1,14 @@
 //! Common utilities shared between simulation and analyzer modules.
 
+pub mod annotations;
 pub mod connection_matrix;
+pub mod lora_presets;
+pub mod measurement_ids;
+pub mod node_import;
+pub mod node_statistics;
+pub mod obstacle_generator;
+pub mod provenance;
 pub mod scene;
+pub mod scene_diff;
+pub mod scene_schema;
+pub mod units;
@@ -0,0 +1,242 @@
+//! Node import from CSV inventories.
+//!
+//! Node inventories are often maintained in spreadsheets. Instead of copying them
+//! into the scene by hand, a scene can list CSV exports in `node_imports`; every row
+//! becomes a node, with bulk attributes for all rows of the file and for the rows
+//! carrying a tag:
+//!
+//! ```json
+//! "node_imports": [{
+//!     "path": "inventory.csv",
+//!     "attributes": { "radio_strength": 14.0 },
+//!     "tag_attributes": { "gateway": { "role": "gateway" } }
+//! }]
+//! ```
+//!
+//! The CSV needs a header row with the columns `id`, `x` and `y` (world units) and
+//! optionally `tx_power` (dBm, overrides the attributes' `radio_strength`) and `tags`
+//! (separated by `;`). A node is built from the file's `attributes`, then the
+//! `tag_attributes` of its tags in the order of the row, then the row itself.
+//!
+//! The expansion happens when the scene JSON is read (see `scene::read_scene_value`):
+//! an imported node whose `node_id` already exists in `nodes` updates the existing
+//! node field by field, any other is appended, so an inventory can be merged into a
+//! hand-written scene.
+
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A CSV file to import.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeImport {
+    /// CSV path, relative to the scene file.
+    path: String,
+    /// Node fields applied to every row.
+    #[serde(default)]
+    attributes: Map<String, Value>,
+    /// Node fields applied to the rows carrying the tag.
+    #[serde(default)]
+    tag_attributes: HashMap<String, Map<String, Value>>,
+}
+
+/// A node row of the inventory.
+#[derive(Debug, Clone, PartialEq)]
+struct InventoryRow {
+    node_id: u32,
+    x: f64,
+    y: f64,
+    tx_power: Option<f32>,
+    tags: Vec<String>,
+}
+
+/// Import the nodes of the `node_imports` CSV files into the scene's `nodes`.
+///
+/// # Parameters
+///
+/// * `imports` - The scene's `node_imports` value
+/// * `scene` - The scene JSON the nodes are merged into
+/// * `base_dir` - Directory the CSV paths are relative to (the scene file's)
+///
+/// # Returns
+///
+/// An error naming the file and line for an invalid import, an unreadable file or
+/// an invalid row.
+pub fn import_nodes(imports: &Value, scene: &mut Value, base_dir: &Path) -> Result<(), String> {
+    let imports: Vec<NodeImport> = serde_json::from_value(imports.clone())
+        .map_err(|e| format!("Invalid node_imports: {}", e))?;
+    let Some(object) = scene.as_object_mut() else {
+        return Ok(());
+    };
+    let nodes = object
+        .entry("nodes")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let Some(nodes) = nodes.as_array_mut() else {
+        return Err("'nodes' must be an array".to_string());
+    };
+    for import in &imports {
+        let path = base_dir.join(&import.path);
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read node inventory {}: {}", path.display(), e))?;
+        let rows = parse_inventory(&text)
+            .map_err(|e| format!("Invalid node inventory {}: {}", path.display(), e))?;
+        for row in &rows {
+            merge_node(nodes, node_fields(import, row));
+        }
+    }
+    Ok(())
+}
+
+/// Parse the rows of a CSV inventory (header row first, columns in any order).
+fn parse_inventory(text: &str) -> Result<Vec<InventoryRow>, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let Some((_, header)) = lines.next() else {
+        return Err("file is empty".to_string());
+    };
+    let columns: Vec<String> = header
+        .split(',')
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| columns.iter().position(|column| column == name);
+    let (Some(id_column), Some(x_column), Some(y_column)) =
+        (column("id"), column("x"), column("y"))
+    else {
+        return Err("header must contain the columns id, x and y".to_string());
+    };
+    let tx_power_column = column("tx_power");
+    let tags_column = column("tags");
+
+    let mut rows = Vec::new();
+    for (index, line) in lines {
+        let line_number = index + 1;
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |column: usize| fields.get(column).copied().unwrap_or("");
+        let number = |column: usize, name: &str| {
+            field(column)
+                .parse::<f64>()
+                .map_err(|_| format!("line {}: invalid {} '{}'", line_number, name, field(column)))
+        };
+        let node_id = field(id_column)
+            .parse::<u32>()
+            .map_err(|_| format!("line {}: invalid id '{}'", line_number, field(id_column)))?;
+        let tx_power = match tx_power_column {
+            Some(column) if !field(column).is_empty() => Some(number(column, "tx_power")? as f32),
+            _ => None,
+        };
+        let tags = tags_column
+            .map(field)
+            .unwrap_or("")
+            .split(';')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+        rows.push(InventoryRow {
+            node_id,
+            x: number(x_column, "x")?,
+            y: number(y_column, "y")?,
+            tx_power,
+            tags,
+        });
+    }
+    Ok(rows)
+}
+
+/// Node fields of a row: the file's attributes, the tag attributes, then the row.
+fn node_fields(import: &NodeImport, row: &InventoryRow) -> Map<String, Value> {
+    let mut fields = import.attributes.clone();
+    for tag in &row.tags {
+        if let Some(attributes) = import.tag_attributes.get(tag) {
+            fields.extend(attributes.clone());
+        }
+    }
+    fields.insert("node_id".to_string(), json!(row.node_id));
+    fields.insert("position".to_string(), json!({ "x": row.x, "y": row.y }));
+    if let Some(tx_power) = row.tx_power {
+        fields.insert("radio_strength".to_string(), json!(tx_power));
+    }
+    if !row.tags.is_empty() {
+        fields.insert("tags".to_string(), json!(row.tags));
+    }
+    fields
+}
+
+/// Update the node with the same `node_id` field by field, or append a new node.
+fn merge_node(nodes: &mut Vec<Value>, fields: Map<String, Value>) {
+    let existing = nodes
+        .iter_mut()
+        .filter_map(Value::as_object_mut)
+        .find(|node| node.get("node_id") == fields.get("node_id"));
+    match existing {
+        Some(node) => node.extend(fields),
+        None => nodes.push(Value::Object(fields)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_merge_into_nodes_with_bulk_attributes() {
+        let rows = parse_inventory(
+            "ID, X, Y, tx_power, tags\n\
+             1, 10, 20, , rooftop\n\
+             \n\
+             7, 5.5, 6, 20, gateway; rooftop\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rows[1],
+            InventoryRow {
+                node_id: 7,
+                x: 5.5,
+                y: 6.0,
+                tx_power: Some(20.0),
+                tags: vec!["gateway".to_string(), "rooftop".to_string()],
+            }
+        );
+        assert_eq!(rows[0].tx_power, None);
+        assert_eq!(
+            parse_inventory("id,x,y\n3,1,oops").unwrap_err(),
+            "line 2: invalid y 'oops'"
+        );
+        assert!(parse_inventory("id,x\n3,1").is_err());
+
+        let import: NodeImport = serde_json::from_value(json!({
+            "path": "inventory.csv",
+            "attributes": { "radio_strength": 14.0, "start_time": 5 },
+            "tag_attributes": {
+                "gateway": { "role": "gateway", "start_time": 0 },
+                "rooftop": { "radio_strength": 17.0 }
+            }
+        }))
+        .unwrap();
+        let mut nodes = vec![json!({
+            "node_id": 1,
+            "position": { "x": 0.0, "y": 0.0 },
+            "radio_strength": 10.0,
+            "history_policy": "full"
+        })];
+        for row in &rows {
+            merge_node(&mut nodes, node_fields(&import, row));
+        }
+
+        assert_eq!(nodes.len(), 2);
+        // The existing node is updated, keeping the fields the import does not set
+        assert_eq!(nodes[0]["radio_strength"], json!(17.0));
+        assert_eq!(nodes[0]["position"], json!({ "x": 10.0, "y": 20.0 }));
+        assert_eq!(nodes[0]["history_policy"], json!("full"));
+        assert_eq!(nodes[0]["tags"], json!(["rooftop"]));
+        // The row's tx_power wins over the attributes
+        assert_eq!(nodes[1]["radio_strength"], json!(20.0));
+        assert_eq!(nodes[1]["role"], json!("gateway"));
+        assert_eq!(nodes[1]["start_time"], json!(0));
+    }
+}
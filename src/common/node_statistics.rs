@@ -0,0 +1,173 @@
+//! Cumulative per-node radio statistics.
+//!
+//! Shared by the simulation and the analyzer: both backends update one
+//! `NodeStatistics` per node as packets are sent, received or lost to collisions
+//! (or to the node's own transmissions or its sleeping receiver),
+//! and periodically publish a snapshot to the UI for the node table.
+//!
+//! In Simulation mode the node's channel activity detections (CAD) are counted as
+//! well: requests, and answers reporting a busy or a free channel. CAD gates every
+//! transmission of the MAC layer, so a node that mostly sees a busy channel defers
+//! its relays.
+//!
+//! In Simulation mode the received packets of flooded messages (AddBlock and
+//! AddTransaction parts) are also split into new information and duplicates of
+//! packets the node already received or sent, the efficiency of flood relaying.
+//!
+//! The statistics are plain counters: the sets behind the distinct counts (senders
+//! heard, flooded packets known) are kept by the backends, so the snapshot published
+//! every second copies only scalars.
+
+/// Cumulative radio statistics of a single node.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeStatistics {
+    /// Packets transmitted by the node.
+    pub tx_packets: u64,
+    /// Packets successfully received by the node.
+    pub rx_packets: u64,
+    /// Packets lost to collisions (or CRC errors in analyzer modes) at the node.
+    pub collisions: u64,
+    /// Packets missed because the node was transmitting at the same time (half-duplex).
+    pub missed_while_transmitting: u64,
+    /// Packets missed because the node's receiver was asleep (duty cycling, Simulation mode).
+    pub missed_while_sleeping: u64,
+    /// Sum of the link quality of all received packets.
+    pub link_quality_sum: u64,
+    /// Number of distinct nodes this node has received packets from.
+    pub neighbors: usize,
+    /// Measurement blocks relayed (Simulation mode).
+    pub relays: u64,
+    /// Sum of the relay delays in milliseconds (block received → first packet sent).
+    pub relay_delay_sum_ms: u64,
+    /// Longest relay delay in milliseconds.
+    pub relay_delay_max_ms: u64,
+    /// Estimated memory of the node's histories and queues in bytes (Simulation mode).
+    pub memory_bytes: u64,
+    /// Received flooded packets that were new to the node (Simulation mode).
+    pub flood_new_packets: u64,
+    /// Received flooded packets the node already knew (Simulation mode).
+    pub flood_duplicate_packets: u64,
+    /// Received packets delivered truncated by an injected fault (Simulation mode).
+    pub truncated_packets: u64,
+    /// Received packets delivered with flipped bits by an injected fault (Simulation mode).
+    pub corrupted_packets: u64,
+    /// Channel activity detections requested by the node (Simulation mode).
+    pub cad_requests: u64,
+    /// CAD answers reporting channel activity.
+    pub cad_busy: u64,
+    /// CAD answers reporting a free channel.
+    pub cad_free: u64,
+}
+
+impl NodeStatistics {
+    /// Record a transmitted packet.
+    pub fn record_tx(&mut self) {
+        self.tx_packets += 1;
+    }
+
+    /// Record a successfully received packet; `new_neighbor` if its sender was not
+    /// heard before.
+    pub fn record_rx(&mut self, link_quality: u8, new_neighbor: bool) {
+        self.rx_packets += 1;
+        self.link_quality_sum += link_quality as u64;
+        if new_neighbor {
+            self.neighbors += 1;
+        }
+    }
+
+    /// Record a packet lost to a collision.
+    pub fn record_collision(&mut self) {
+        self.collisions += 1;
+    }
+
+    /// Record a packet missed because the node was transmitting.
+    pub fn record_missed_while_transmitting(&mut self) {
+        self.missed_while_transmitting += 1;
+    }
+
+    /// Record a packet missed because the node's receiver was asleep.
+    pub fn record_missed_while_sleeping(&mut self) {
+        self.missed_while_sleeping += 1;
+    }
+
+    /// Record the delay between receiving a measurement block and starting to relay it
+    /// (random TX delay, message spacing and channel access together).
+    pub fn record_relay(&mut self, delay_ms: u64) {
+        self.relays += 1;
+        self.relay_delay_sum_ms += delay_ms;
+        self.relay_delay_max_ms = self.relay_delay_max_ms.max(delay_ms);
+    }
+
+    /// Record a channel activity detection requested by the node.
+    pub fn record_cad_request(&mut self) {
+        self.cad_requests += 1;
+    }
+
+    /// Record the answer of a channel activity detection.
+    pub fn record_cad_result(&mut self, busy: bool) {
+        if busy {
+            self.cad_busy += 1;
+        } else {
+            self.cad_free += 1;
+        }
+    }
+
+    /// Percentage of answered CAD requests that found the channel busy (None if no
+    /// request was answered).
+    pub fn cad_busy_ratio(&self) -> Option<f64> {
+        let answered = self.cad_busy + self.cad_free;
+        if answered == 0 {
+            return None;
+        }
+        Some(self.cad_busy as f64 * 100.0 / answered as f64)
+    }
+
+    /// Record a received packet delivered with an injected fault.
+    pub fn record_faulty_packet(&mut self, truncated: bool) {
+        if truncated {
+            self.truncated_packets += 1;
+        } else {
+            self.corrupted_packets += 1;
+        }
+    }
+
+    /// Record a received packet of a flooded message, new or a duplicate.
+    pub fn record_flood_packet(&mut self, duplicate: bool) {
+        if duplicate {
+            self.flood_duplicate_packets += 1;
+        } else {
+            self.flood_new_packets += 1;
+        }
+    }
+
+    /// Percentage of received flooded packets that were duplicates (None if none
+    /// was received).
+    pub fn duplicate_ratio(&self) -> Option<f64> {
+        duplicate_ratio(self.flood_new_packets, self.flood_duplicate_packets)
+    }
+
+    /// Average relay delay in milliseconds (None if nothing was relayed).
+    pub fn average_relay_delay(&self) -> Option<f64> {
+        if self.relays == 0 {
+            return None;
+        }
+        Some(self.relay_delay_sum_ms as f64 / self.relays as f64)
+    }
+
+    /// Average link quality of received packets (None if nothing was received).
+    pub fn average_link_quality(&self) -> Option<f64> {
+        if self.rx_packets == 0 {
+            return None;
+        }
+        Some(self.link_quality_sum as f64 / self.rx_packets as f64)
+    }
+}
+
+/// Percentage of duplicates among `new + duplicates` flooded packets (None if zero).
+pub fn duplicate_ratio(new: u64, duplicates: u64) -> Option<f64> {
+    let total = new + duplicates;
+    if total == 0 {
+        return None;
+    }
+    Some(duplicates as f64 * 100.0 / total as f64)
+}
@@ -0,0 +1,340 @@
+//! Procedural obstacle generation.
+//!
+//! Instead of drawing hundreds of rectangles by hand, a scene can describe an
+//! urban-like environment with `obstacle_generator`: an optional street grid that
+//! splits the area into blocks, and random rectangular buildings filling every block
+//! (or the whole area) up to a ground coverage density, with sizes drawn from a
+//! uniform or normal distribution. The buildings are drawn from a generator seeded
+//! with the generator's `seed` (or the scene `seed`, or 0), so the same scene always
+//! produces the same obstacles, in the simulator and in the analyzer modes alike.
+//!
+//! The expansion happens when the scene JSON is read (see `scene::read_scene_value`):
+//! the generated rectangles are appended to the scene's `obstacles`, so hand-drawn
+//! obstacles can be combined with generated ones. All lengths are in world units.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Largest number of obstacles a generator may produce.
+const MAX_GENERATED_OBSTACLES: usize = 20_000;
+
+/// Consecutive failed placements after which a block counts as full.
+const MAX_PLACEMENT_ATTEMPTS: u32 = 100;
+
+/// Smallest building side drawn from a normal distribution without a `min`.
+const DEFAULT_MIN_SIZE: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Corner {
+    x: f64,
+    y: f64,
+}
+
+/// Rectangular area in world coordinates.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Area {
+    #[serde(rename = "top-left-position")]
+    top_left: Corner,
+    #[serde(rename = "bottom-right-position")]
+    bottom_right: Corner,
+}
+
+/// Streets splitting the area into square blocks.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct StreetGrid {
+    /// Side of a block between streets.
+    block_size: f64,
+    street_width: f64,
+}
+
+/// Distribution of building side lengths.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+enum SizeDistribution {
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    Normal {
+        mean: f64,
+        std_dev: f64,
+        #[serde(default)]
+        min: Option<f64>,
+    },
+}
+
+/// Random buildings of a block.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Buildings {
+    /// Share of each block's ground covered by buildings (0..=1).
+    density: f64,
+    size: SizeDistribution,
+    /// Minimum gap between two buildings.
+    #[serde(default)]
+    spacing: f64,
+}
+
+/// The scene's `obstacle_generator` entry.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObstacleGenerator {
+    #[serde(default)]
+    seed: Option<u64>,
+    /// Area to fill (the whole world by default).
+    #[serde(default)]
+    area: Option<Area>,
+    #[serde(default)]
+    street_grid: Option<StreetGrid>,
+    buildings: Buildings,
+}
+
+/// Axis-aligned rectangle (`x0 < x1`, `y0 < y1`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Footprint {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+}
+
+impl Footprint {
+    fn from_area(area: &Area) -> Self {
+        Self {
+            x0: area.top_left.x.min(area.bottom_right.x),
+            y0: area.top_left.y.min(area.bottom_right.y),
+            x1: area.top_left.x.max(area.bottom_right.x),
+            y1: area.top_left.y.max(area.bottom_right.y),
+        }
+    }
+
+    fn width(&self) -> f64 {
+        self.x1 - self.x0
+    }
+
+    fn height(&self) -> f64 {
+        self.y1 - self.y0
+    }
+
+    /// Whether the rectangles come closer than `gap` to each other.
+    fn overlaps(&self, other: &Footprint, gap: f64) -> bool {
+        self.x0 < other.x1 + gap
+            && other.x0 < self.x1 + gap
+            && self.y0 < other.y1 + gap
+            && other.y0 < self.y1 + gap
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "type": "rectangle",
+            "top-left-position": { "x": self.x0, "y": self.y0 },
+            "bottom-right-position": { "x": self.x1, "y": self.y1 },
+        })
+    }
+}
+
+impl SizeDistribution {
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            SizeDistribution::Uniform { min, max } if min > 0.0 && max >= min => Ok(()),
+            SizeDistribution::Uniform { .. } => {
+                Err("obstacle_generator uniform size needs 0 < min <= max".into())
+            }
+            SizeDistribution::Normal { mean, std_dev, .. } if mean > 0.0 && std_dev >= 0.0 => {
+                Ok(())
+            }
+            SizeDistribution::Normal { .. } => {
+                Err("obstacle_generator normal size needs mean > 0 and std_dev >= 0".into())
+            }
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        match *self {
+            SizeDistribution::Uniform { min, max } => rng.gen_range(min..=max),
+            SizeDistribution::Normal { mean, std_dev, min } => Normal::new(mean, std_dev)
+                .map_or(mean, |normal| normal.sample(rng))
+                .max(min.unwrap_or(DEFAULT_MIN_SIZE)),
+        }
+    }
+}
+
+impl ObstacleGenerator {
+    /// Blocks to fill: the street grid's blocks clipped to the area, or the area.
+    fn blocks(&self, area: Footprint) -> Result<Vec<Footprint>, String> {
+        let Some(grid) = self.street_grid else {
+            return Ok(vec![area]);
+        };
+        if grid.block_size <= 0.0 || grid.street_width < 0.0 {
+            return Err(
+                "obstacle_generator street_grid needs block_size > 0 and street_width >= 0".into(),
+            );
+        }
+        let pitch = grid.block_size + grid.street_width;
+        let mut blocks = Vec::new();
+        let mut y0 = area.y0;
+        while y0 < area.y1 {
+            let mut x0 = area.x0;
+            while x0 < area.x1 {
+                blocks.push(Footprint {
+                    x0,
+                    y0,
+                    x1: (x0 + grid.block_size).min(area.x1),
+                    y1: (y0 + grid.block_size).min(area.y1),
+                });
+                x0 += pitch;
+            }
+            y0 += pitch;
+        }
+        Ok(blocks)
+    }
+
+    /// Fill every block with buildings up to the coverage density.
+    fn generate(&self, area: Footprint, seed: u64) -> Result<Vec<Footprint>, String> {
+        let buildings = self.buildings;
+        if !(buildings.density > 0.0 && buildings.density <= 1.0) {
+            return Err("obstacle_generator buildings.density must be in (0, 1]".into());
+        }
+        buildings.size.validate()?;
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut generated = Vec::new();
+        for block in self.blocks(area)? {
+            let target = buildings.density * block.width() * block.height();
+            let first = generated.len();
+            let mut covered = 0.0;
+            let mut failures = 0;
+            while covered < target && failures < MAX_PLACEMENT_ATTEMPTS {
+                let width = buildings.size.sample(&mut rng);
+                let height = buildings.size.sample(&mut rng);
+                if width > block.width() || height > block.height() {
+                    failures += 1;
+                    continue;
+                }
+                let x0 = rng.gen_range(block.x0..=block.x1 - width);
+                let y0 = rng.gen_range(block.y0..=block.y1 - height);
+                let candidate = Footprint {
+                    x0,
+                    y0,
+                    x1: x0 + width,
+                    y1: y0 + height,
+                };
+                if generated[first..]
+                    .iter()
+                    .any(|placed: &Footprint| placed.overlaps(&candidate, buildings.spacing))
+                {
+                    failures += 1;
+                    continue;
+                }
+                generated.push(candidate);
+                if generated.len() > MAX_GENERATED_OBSTACLES {
+                    return Err(format!(
+                        "obstacle_generator produces more than {} obstacles; lower the density or raise the building size",
+                        MAX_GENERATED_OBSTACLES
+                    ));
+                }
+                covered += width * height;
+                failures = 0;
+            }
+        }
+        Ok(generated)
+    }
+}
+
+/// Generate the obstacles of an `obstacle_generator` scene entry.
+///
+/// # Parameters
+///
+/// * `generator` - The `obstacle_generator` value
+/// * `scene` - The rest of the scene (world corners and `seed`)
+///
+/// # Returns
+///
+/// The generated rectangle obstacles as scene JSON, or an error for an invalid
+/// generator or one producing more than `MAX_GENERATED_OBSTACLES` obstacles.
+pub fn generate_obstacles(generator: &Value, scene: &Value) -> Result<Vec<Value>, String> {
+    let generator: ObstacleGenerator = serde_json::from_value(generator.clone())
+        .map_err(|e| format!("Invalid obstacle_generator: {}", e))?;
+    let area = match generator.area {
+        Some(area) => area,
+        None => {
+            let corner = |key: &str| {
+                scene
+                    .get(key)
+                    .and_then(|value| serde_json::from_value::<Corner>(value.clone()).ok())
+            };
+            let (Some(top_left), Some(bottom_right)) =
+                (corner("world_top_left"), corner("world_bottom_right"))
+            else {
+                return Err("obstacle_generator needs an area or the scene's world corners".into());
+            };
+            Area {
+                top_left,
+                bottom_right,
+            }
+        }
+    };
+    let seed = generator
+        .seed
+        .or_else(|| scene.get("seed").and_then(Value::as_u64))
+        .unwrap_or(0);
+    let obstacles = generator.generate(Footprint::from_area(&area), seed)?;
+    log::info!(
+        "obstacle_generator: {} buildings generated (seed {})",
+        obstacles.len(),
+        seed
+    );
+    Ok(obstacles.into_iter().map(Footprint::to_json).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_is_reproducible_and_stays_off_the_streets() {
+        let scene = json!({
+            "world_top_left": { "x": 0, "y": 0 },
+            "world_bottom_right": { "x": 1000, "y": 1000 },
+            "seed": 7,
+        });
+        let generator = json!({
+            "street_grid": { "block_size": 180, "street_width": 20 },
+            "buildings": {
+                "density": 0.3,
+                "size": { "distribution": "uniform", "min": 10, "max": 40 },
+                "spacing": 2,
+            },
+        });
+        let first = generate_obstacles(&generator, &scene).unwrap();
+        assert!(first.len() > 25);
+        assert_eq!(first, generate_obstacles(&generator, &scene).unwrap());
+
+        let parsed: ObstacleGenerator = serde_json::from_value(generator.clone()).unwrap();
+        let area = Footprint {
+            x0: 0.0,
+            y0: 0.0,
+            x1: 1000.0,
+            y1: 1000.0,
+        };
+        let blocks = parsed.blocks(area).unwrap();
+        assert_eq!(blocks.len(), 25);
+        for building in parsed.generate(area, 7).unwrap() {
+            // Inside a block, never on a street
+            assert!(blocks.iter().any(|block| building.x0 >= block.x0
+                && building.x1 <= block.x1
+                && building.y0 >= block.y0
+                && building.y1 <= block.y1));
+        }
+
+        // Another seed gives another city
+        let mut other_scene = scene.clone();
+        other_scene["seed"] = json!(8);
+        assert_ne!(first, generate_obstacles(&generator, &other_scene).unwrap());
+
+        let invalid = json!({ "buildings": { "density": 1.5, "size": { "distribution": "uniform", "min": 1, "max": 2 } } });
+        assert!(generate_obstacles(&invalid, &scene).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! # Run Provenance
+//!
+//! Configuration a result was produced with, embedded in every exported file so the
+//! file stays interpretable months later: simulator and moonblokz-radio-lib versions,
+//! the scene (path, hash and full resolved JSON), the random seed, the simulation
+//! speed (or the event-driven clock) and the scene's speed profile.
+//!
+//! The running session records its scene and seed here (one session per process);
+//! exports take a snapshot with `current`. JSON exports (run summary, link matrix
+//! file) carry it as a `provenance` field, other files get a
+//! `<file>.provenance.json` sidecar, and crash dumps a one-line summary without the
+//! scene.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Simulator (crate) version.
+const SIMULATOR_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// moonblokz-radio-lib version from Cargo.lock, with the git revision (see build.rs).
+const RADIO_LIB_VERSION: &str = env!("MOONBLOKZ_RADIO_LIB_VERSION");
+
+/// Scene and seed of the running session.
+struct SessionRecord {
+    scene_path: Option<String>,
+    scene: Option<Value>,
+    seed: Option<u64>,
+}
+
+static SESSION: Mutex<SessionRecord> = Mutex::new(SessionRecord {
+    scene_path: None,
+    scene: None,
+    seed: None,
+});
+
+/// Provenance of an exported file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Provenance {
+    pub simulator_version: String,
+    pub radio_lib_version: String,
+    pub scene_path: Option<String>,
+    /// FNV-1a 64-bit hash (hex) of the canonical (key-sorted) resolved scene JSON.
+    pub scene_hash: Option<String>,
+    /// The resolved scene (includes merged); omitted in one-line summaries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scene: Option<Value>,
+    /// Random seed of the simulation (None in the analyzer modes).
+    pub seed: Option<u64>,
+    /// Simulation speed in percent at export time.
+    pub speed_percent: u32,
+    /// The virtual clock jumped from event to event (headless `run --event-driven`);
+    /// `speed_percent` did not apply.
+    #[serde(default)]
+    pub event_driven: bool,
+    /// The scene's `speed_profile`, if any.
+    pub speed_profile: Option<Value>,
+    /// Local time of the export (RFC 3339).
+    pub exported_at: String,
+}
+
+impl Provenance {
+    /// The same provenance without the full scene (the hash identifies it).
+    pub fn without_scene(self) -> Self {
+        Self {
+            scene: None,
+            ..self
+        }
+    }
+}
+
+/// Record the scene of the running session (resolved JSON, includes merged).
+pub fn record_scene(scene_path: &str, scene: &Value) {
+    let mut session = SESSION.lock().unwrap_or_else(|e| e.into_inner());
+    session.scene_path = Some(scene_path.to_string());
+    session.scene = Some(scene.clone());
+}
+
+/// Record the random seed of the running simulation.
+pub fn record_seed(seed: u64) {
+    SESSION.lock().unwrap_or_else(|e| e.into_inner()).seed = Some(seed);
+}
+
+/// FNV-1a 64-bit hash of the canonical JSON text of a scene, as hex.
+fn scene_hash(scene: &Value) -> String {
+    let hash = serde_json::to_string(scene)
+        .unwrap_or_default()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// Snapshot of the provenance of the running session.
+pub fn current() -> Provenance {
+    let session = SESSION.lock().unwrap_or_else(|e| e.into_inner());
+    Provenance {
+        simulator_version: SIMULATOR_VERSION.to_string(),
+        radio_lib_version: RADIO_LIB_VERSION.to_string(),
+        scene_path: session.scene_path.clone(),
+        scene_hash: session.scene.as_ref().map(scene_hash),
+        scene: session.scene.clone(),
+        seed: session.seed,
+        speed_percent: crate::time_driver::get_simulation_speed_percent(),
+        event_driven: crate::time_driver::is_event_driven(),
+        speed_profile: session
+            .scene
+            .as_ref()
+            .and_then(|scene| scene.get("speed_profile"))
+            .cloned(),
+        exported_at: chrono::Local::now().to_rfc3339(),
+    }
+}
+
+/// Write the provenance of the running session next to an exported file
+/// (`<file>.provenance.json`).
+///
+/// # Returns
+///
+/// The path of the sidecar file, or an error message.
+pub fn write_sidecar(exported_path: &Path) -> Result<String, String> {
+    let mut sidecar = exported_path.as_os_str().to_owned();
+    sidecar.push(".provenance.json");
+    let json = serde_json::to_string_pretty(&current()).map_err(|e| e.to_string())?;
+    std::fs::write(&sidecar, json)
+        .map_err(|e| format!("Failed to write {}: {}", Path::new(&sidecar).display(), e))?;
+    Ok(Path::new(&sidecar).display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scene_hash_ignores_key_order_and_detects_changes() {
+        let scene = json!({ "nodes": [{ "node_id": 1 }], "seed": 7 });
+        let reordered: Value =
+            serde_json::from_str(r#"{"seed":7,"nodes":[{"node_id":1}]}"#).unwrap();
+        let changed = json!({ "nodes": [{ "node_id": 2 }], "seed": 7 });
+        assert_eq!(scene_hash(&scene), scene_hash(&reordered));
+        assert_ne!(scene_hash(&scene), scene_hash(&changed));
+        assert_eq!(scene_hash(&scene).len(), 16);
+    }
+}
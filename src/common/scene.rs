@@ -3,11 +3,23 @@
 //! Contains all data structures for scene configuration and provides
 //! functions for loading and validating scenes for both simulation
 //! and analyzer modes.
+//!
+//! Scene files can include other JSON files (shared radio parameters, obstacle
+//! libraries, node clusters) with an `includes` list; see [`read_scene_value`].
 
 use anyhow::Context;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashSet;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::annotations::{Annotation, validate_annotations};
+use super::lora_presets::expand_preset;
+use super::node_import::import_nodes;
+use super::obstacle_generator::generate_obstacles;
+use super::units::WorldTransform;
 
 /// Scene loading mode determines which fields are required.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,7 +51,7 @@ impl std::fmt::Display for SceneLoadError {
 impl std::error::Error for SceneLoadError {}
 
 /// Parameters defining the radio channel propagation model.
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct PathLossParameters {
     /// Path loss exponent (n). 2.0 for free space, 2.7-3.5 for urban.
     pub path_loss_exponent: f32,
@@ -52,7 +64,7 @@ pub struct PathLossParameters {
 }
 
 /// LoRa modulation parameters.
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct LoraParameters {
     pub bandwidth: u32,
     pub spreading_factor: u8,
@@ -63,7 +75,7 @@ pub struct LoraParameters {
 }
 
 /// Radio module configuration for the simulated radio manager.
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct RadioModuleConfig {
     /// Inter-packet gap inside a single message (ms).
     pub delay_between_tx_packets: u16,
@@ -86,14 +98,14 @@ pub struct RadioModuleConfig {
 }
 
 /// Simple 2D point.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
 /// Rectangle position with two corners.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct RectPos {
     #[serde(rename = "top-left-position")]
     pub top_left: Point,
@@ -102,7 +114,7 @@ pub struct RectPos {
 }
 
 /// Circle position defined by its center.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct CirclePos {
     #[serde(rename = "center_position")]
     pub center: Point,
@@ -110,7 +122,7 @@ pub struct CirclePos {
 }
 
 /// Obstacles represented as tagged enum.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(tag = "type")]
 pub enum Obstacle {
     #[serde(rename = "rectangle")]
@@ -126,20 +138,24 @@ pub enum Obstacle {
 }
 
 /// Node structure with position and radio strength.
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct Node {
     pub node_id: u32,
     pub position: Point,
-    /// Radio strength in dBm (used for simulation).
+    /// Radio strength in dBm (used for simulation; in analyzer scenes checked against
+    /// `effective_distance`, see [`effective_distance_warnings`]).
     #[serde(default)]
-    pub radio_strength: f32,
+    pub radio_strength: Option<f32>,
     /// Pre-calculated effective radio range in meters (required for analyzer).
     #[serde(default)]
     pub effective_distance: Option<u32>,
+    /// Free-form labels, e.g. from a CSV import (see `node_import`).
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// Root structure representing the entire scene.
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct Scene {
     /// Path loss model parameters (required for simulation, optional for analyzer).
     #[serde(default)]
@@ -161,16 +177,18 @@ pub struct Scene {
     /// Bottom-right corner of the world coordinate system.
     #[serde(rename = "world_bottom_right")]
     pub world_bottom_right: Point,
-    /// Width of the world in meters.
-    pub width: f64,
-    /// Height of the world in meters.
-    pub height: f64,
-    /// Pre-calculated X-axis scaling factor.
-    #[serde(skip)]
-    pub scale_x: f64,
-    /// Pre-calculated Y-axis scaling factor.
+    /// Width of the world in meters (alternative to `meters_per_unit`).
+    #[serde(default)]
+    pub width: Option<f64>,
+    /// Height of the world in meters (alternative to `meters_per_unit`).
+    #[serde(default)]
+    pub height: Option<f64>,
+    /// Uniform scale in meters per world unit (alternative to `width`/`height`).
+    #[serde(default)]
+    pub meters_per_unit: Option<f64>,
+    /// World unit ↔ meter transform, built from the fields above when the scene is loaded.
     #[serde(skip)]
-    pub scale_y: f64,
+    pub transform: WorldTransform,
     /// Optional path to background image.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub background_image: Option<String>,
@@ -180,6 +198,9 @@ pub struct Scene {
     /// Optional link quality threshold for "excellent" (real-time tracking).
     #[serde(default)]
     pub link_quality_excellent_threshold: Option<u8>,
+    /// Labels, arrows and regions drawn on the map (see `annotations`).
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
 }
 
 /// Load and parse a scene from a file.
@@ -193,28 +214,29 @@ pub struct Scene {
 ///
 /// Parsed and validated Scene or an error.
 pub fn load_scene(path: &str, mode: SceneMode) -> Result<Scene, SceneLoadError> {
-    let data = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read file: {}", path))
-        .map_err(|e| SceneLoadError::FileReadError(e.to_string()))?;
+    let data = read_scene_value(path)?;
 
-    let mut scene: Scene = serde_json::from_str(&data)
+    let mut scene: Scene = serde_json::from_value(data)
         .context("Invalid JSON format")
         .map_err(|e| SceneLoadError::ParseError(e.to_string()))?;
 
     // If background_image is specified, prepend the scene file's directory
     if let Some(ref bg_image) = scene.background_image {
-        use std::path::Path;
         if let Some(parent_dir) = Path::new(path).parent() {
             let full_path = parent_dir.join(bg_image);
             scene.background_image = Some(full_path.to_string_lossy().to_string());
         }
     }
 
-    // Pre-calculate scaling factors
-    let world_width = scene.world_bottom_right.x - scene.world_top_left.x;
-    let world_height = scene.world_bottom_right.y - scene.world_top_left.y;
-    scene.scale_x = scene.width / world_width;
-    scene.scale_y = scene.height / world_height;
+    // Build the world unit ↔ meter transform
+    scene.transform = WorldTransform::from_scene(
+        (scene.world_top_left.x, scene.world_top_left.y),
+        (scene.world_bottom_right.x, scene.world_bottom_right.y),
+        scene.width,
+        scene.height,
+        scene.meters_per_unit,
+    )
+    .map_err(SceneLoadError::ValidationError)?;
 
     // Validate the scene
     validate_scene(&scene, mode).map_err(SceneLoadError::ValidationError)?;
@@ -222,6 +244,167 @@ pub fn load_scene(path: &str, mode: SceneMode) -> Result<Scene, SceneLoadError>
     Ok(scene)
 }
 
+/// Maximum nesting depth of scene includes.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Read a scene file as JSON with all `includes` resolved, the `obstacle_generator`
+/// and the `lora_preset` expanded.
+///
+/// `includes` is an optional list of JSON file paths, relative to the file that
+/// lists them. Included files may include further files. The included files are
+/// merged in list order, then the including file is merged on top:
+/// - objects are merged key by key (e.g. override a single `lora_parameters` field)
+/// - arrays are concatenated, included entries first (`nodes`, `obstacles`)
+/// - other values of the including file replace the included ones
+///
+/// A `lora_preset` is replaced by the preset's `lora_parameters` and
+/// `regulatory_limits`, with the scene's own fields merged on top (see `lora_presets`).
+/// An `obstacle_generator` appends its generated buildings to `obstacles` (see
+/// `obstacle_generator`). The `node_imports` CSV inventories are merged into `nodes`
+/// (see `node_import`).
+///
+/// # Parameters
+///
+/// * `path` - Path to the scene JSON file
+///
+/// # Returns
+///
+/// The merged scene JSON (without `includes` keys) or an error for unreadable
+/// files, invalid JSON, include cycles, too deep nesting, invalid presets, invalid
+/// obstacle generators and invalid node imports.
+pub fn read_scene_value(path: &str) -> Result<Value, SceneLoadError> {
+    let mut stack = Vec::new();
+    let mut value = read_with_includes(Path::new(path), &mut stack)?;
+    if let Some(imports) = value
+        .as_object_mut()
+        .and_then(|object| object.remove("node_imports"))
+    {
+        let base_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        import_nodes(&imports, &mut value, base_dir).map_err(SceneLoadError::ValidationError)?;
+    }
+    if let Some(generator) = value
+        .as_object_mut()
+        .and_then(|object| object.remove("obstacle_generator"))
+    {
+        let generated =
+            generate_obstacles(&generator, &value).map_err(SceneLoadError::ValidationError)?;
+        if let Some(object) = value.as_object_mut() {
+            let obstacles = object
+                .entry("obstacles")
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Some(obstacles) = obstacles.as_array_mut() {
+                obstacles.extend(generated);
+            }
+        }
+    }
+    let Some(preset) = value
+        .as_object_mut()
+        .and_then(|object| object.remove("lora_preset"))
+    else {
+        return Ok(value);
+    };
+    let mut expanded = expand_preset(&preset).map_err(SceneLoadError::ValidationError)?;
+    merge_json(&mut expanded, value);
+    Ok(expanded)
+}
+
+/// Read a JSON file and resolve its includes recursively.
+///
+/// `stack` holds the canonical paths of the files currently being included to
+/// detect cycles.
+fn read_with_includes(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Value, SceneLoadError> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))
+        .map_err(|e| SceneLoadError::FileReadError(e.to_string()))?;
+    let mut value: Value = serde_json::from_str(&data)
+        .with_context(|| format!("Invalid JSON format in {}", path.display()))
+        .map_err(|e| SceneLoadError::ParseError(e.to_string()))?;
+
+    let Some(object) = value.as_object_mut() else {
+        return Ok(value);
+    };
+    let Some(includes) = object.remove("includes") else {
+        return Ok(value);
+    };
+    let includes: Vec<String> = serde_json::from_value(includes).map_err(|e| {
+        SceneLoadError::ParseError(format!(
+            "'includes' in {} must be a list of file paths: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        return Err(SceneLoadError::ValidationError(format!(
+            "Include cycle detected at {}",
+            path.display()
+        )));
+    }
+    if stack.len() >= MAX_INCLUDE_DEPTH {
+        return Err(SceneLoadError::ValidationError(format!(
+            "Scene includes nested deeper than {} levels at {}",
+            MAX_INCLUDE_DEPTH,
+            path.display()
+        )));
+    }
+
+    stack.push(canonical);
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mut merged = Value::Object(serde_json::Map::new());
+    for include in &includes {
+        let included = read_with_includes(&base_dir.join(include), stack)?;
+        merge_json(&mut merged, included);
+    }
+    stack.pop();
+
+    merge_json(&mut merged, value);
+    Ok(merged)
+}
+
+/// Top-level scene arrays whose entries add up across includes.
+const CONCATENATED_ARRAYS: [&str; 2] = ["nodes", "obstacles"];
+
+/// Merge the scene `overlay` into `base`: the top-level `nodes` and `obstacles`
+/// arrays are concatenated, everything else is merged by `merge_value`.
+fn merge_json(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match (base.get_mut(&key), value) {
+                    (Some(Value::Array(existing)), Value::Array(added))
+                        if CONCATENATED_ARRAYS.contains(&key.as_str()) =>
+                    {
+                        existing.extend(added)
+                    }
+                    (Some(existing), value) => merge_value(existing, value),
+                    (None, value) => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => merge_value(base, overlay),
+    }
+}
+
+/// Merge `overlay` into `base` (objects by key, other values including arrays replaced).
+fn merge_value(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
 /// Validate scene configuration based on the mode.
 ///
 /// # Parameters
@@ -233,7 +416,6 @@ pub fn load_scene(path: &str, mode: SceneMode) -> Result<Scene, SceneLoadError>
 ///
 /// `Ok(())` if validation passes, `Err(String)` with error description otherwise.
 pub fn validate_scene(scene: &Scene, mode: SceneMode) -> Result<(), String> {
-    const MAX_WORLD_COORD: f64 = 10000.0;
     const MAX_NODES: usize = 10000;
     const MIN_RADIO_STRENGTH: f32 = -50.0;
     const MAX_RADIO_STRENGTH: f32 = 50.0;
@@ -261,10 +443,13 @@ pub fn validate_scene(scene: &Scene, mode: SceneMode) -> Result<(), String> {
     // Validate each node
     for node in &scene.nodes {
         // Check position bounds
-        if node.position.x > MAX_WORLD_COORD || node.position.y > MAX_WORLD_COORD {
+        if !scene.transform.contains(node.position.x, node.position.y) {
             return Err(format!(
-                "Node {} position ({}, {}) exceeds world bounds (0-{})",
-                node.node_id, node.position.x, node.position.y, MAX_WORLD_COORD
+                "Node {} position ({}, {}) is outside the world bounds {}",
+                node.node_id,
+                node.position.x,
+                node.position.y,
+                scene.transform.bounds_label()
             ));
         }
 
@@ -272,12 +457,11 @@ pub fn validate_scene(scene: &Scene, mode: SceneMode) -> Result<(), String> {
         match mode {
             SceneMode::Simulation => {
                 // Check radio strength is realistic
-                if node.radio_strength < MIN_RADIO_STRENGTH
-                    || node.radio_strength > MAX_RADIO_STRENGTH
-                {
+                let radio_strength = node.radio_strength.unwrap_or(0.0);
+                if !(MIN_RADIO_STRENGTH..=MAX_RADIO_STRENGTH).contains(&radio_strength) {
                     return Err(format!(
                         "Node {} radio_strength {} dBm outside realistic range ({} to {} dBm)",
-                        node.node_id, node.radio_strength, MIN_RADIO_STRENGTH, MAX_RADIO_STRENGTH
+                        node.node_id, radio_strength, MIN_RADIO_STRENGTH, MAX_RADIO_STRENGTH
                     ));
                 }
             }
@@ -354,21 +538,25 @@ pub fn validate_scene(scene: &Scene, mode: SceneMode) -> Result<(), String> {
     for (idx, obstacle) in scene.obstacles.iter().enumerate() {
         match obstacle {
             Obstacle::Rectangle { position } => {
-                if position.top_left.x > MAX_WORLD_COORD
-                    || position.top_left.y > MAX_WORLD_COORD
-                    || position.bottom_right.x > MAX_WORLD_COORD
-                    || position.bottom_right.y > MAX_WORLD_COORD
+                if !scene
+                    .transform
+                    .contains(position.top_left.x, position.top_left.y)
+                    || !scene
+                        .transform
+                        .contains(position.bottom_right.x, position.bottom_right.y)
                 {
                     return Err(format!(
-                        "Obstacle {} (rectangle) has coordinates exceeding world bounds (0-{})",
-                        idx, MAX_WORLD_COORD
+                        "Obstacle {} (rectangle) has coordinates outside the world bounds {}",
+                        idx,
+                        scene.transform.bounds_label()
                     ));
                 }
-                if position.top_left.x >= position.bottom_right.x
-                    || position.top_left.y >= position.bottom_right.y
+                // Corners may be given in either order (inverted axes), but must span an area
+                if position.top_left.x == position.bottom_right.x
+                    || position.top_left.y == position.bottom_right.y
                 {
                     return Err(format!(
-                        "Obstacle {} (rectangle) has invalid geometry: top-left ({}, {}) must be strictly less than bottom-right ({}, {})",
+                        "Obstacle {} (rectangle) has invalid geometry: top-left ({}, {}) and bottom-right ({}, {}) must differ on both axes",
                         idx,
                         position.top_left.x,
                         position.top_left.y,
@@ -378,30 +566,100 @@ pub fn validate_scene(scene: &Scene, mode: SceneMode) -> Result<(), String> {
                 }
             }
             Obstacle::Circle { position } => {
-                if position.center.x > MAX_WORLD_COORD || position.center.y > MAX_WORLD_COORD {
+                if !scene
+                    .transform
+                    .contains(position.center.x, position.center.y)
+                {
                     return Err(format!(
-                        "Obstacle {} (circle) center ({}, {}) exceeds world bounds (0-{})",
-                        idx, position.center.x, position.center.y, MAX_WORLD_COORD
+                        "Obstacle {} (circle) center ({}, {}) is outside the world bounds {}",
+                        idx,
+                        position.center.x,
+                        position.center.y,
+                        scene.transform.bounds_label()
                     ));
                 }
                 if position.radius == 0.0 {
                     return Err(format!("Obstacle {} (circle) has zero radius", idx));
                 }
-                let max_extent_x = position.center.x + position.radius;
-                let max_extent_y = position.center.y + position.radius;
-                if max_extent_x > MAX_WORLD_COORD || max_extent_y > MAX_WORLD_COORD {
+                // The radius is in meters; compare its extent in world units
+                let (radius_x, radius_y) = scene
+                    .transform
+                    .offset_to_units(position.radius, position.radius);
+                if !scene
+                    .transform
+                    .contains(position.center.x - radius_x, position.center.y - radius_y)
+                    || !scene
+                        .transform
+                        .contains(position.center.x + radius_x, position.center.y + radius_y)
+                {
                     return Err(format!(
-                        "Obstacle {} (circle) extends beyond world bounds (0-{})",
-                        idx, MAX_WORLD_COORD
+                        "Obstacle {} (circle) extends beyond the world bounds {}",
+                        idx,
+                        scene.transform.bounds_label()
                     ));
                 }
             }
         }
     }
 
+    validate_annotations(&scene.annotations)?;
+
     Ok(())
 }
 
+/// Relative deviation between a node's `effective_distance` and the range derived
+/// from its `radio_strength` above which the node is reported as inconsistent.
+pub const EFFECTIVE_DISTANCE_TOLERANCE: f64 = 0.1;
+
+/// Check the `effective_distance` of analyzer scene nodes against physics.
+///
+/// Recomputes each node's range from its `radio_strength` with the scene's
+/// `path_loss_parameters` and `lora_parameters` (log-distance model, as in a
+/// simulation scene without `propagation_model`). A stale scene, e.g. one whose
+/// TX powers or modulation were changed without regenerating the distances,
+/// deviates by more than [`EFFECTIVE_DISTANCE_TOLERANCE`]. Scenes without the
+/// physics parameters and nodes without `radio_strength` are not checked.
+///
+/// # Returns
+///
+/// One warning per inconsistent node.
+pub fn effective_distance_warnings(scene: &Scene) -> Vec<String> {
+    use crate::simulation::propagation::LogDistance;
+    use crate::simulation::signal_calculations::calculate_effective_distance;
+
+    let (Some(path_loss), Some(lora)) = (&scene.path_loss_parameters, &scene.lora_parameters)
+    else {
+        return Vec::new();
+    };
+    let path_loss = path_loss.into();
+    let lora = lora.into();
+    let mut warnings = Vec::new();
+    for node in &scene.nodes {
+        let (Some(radio_strength), Some(effective_distance)) =
+            (node.radio_strength, node.effective_distance)
+        else {
+            continue;
+        };
+        let expected =
+            calculate_effective_distance(radio_strength, &lora, &LogDistance, &path_loss) as f64;
+        if expected <= 0.0 {
+            continue;
+        }
+        let deviation = (effective_distance as f64 - expected) / expected;
+        if deviation.abs() > EFFECTIVE_DISTANCE_TOLERANCE {
+            warnings.push(format!(
+                "Node {} effective_distance {} m differs by {:+.0}% from the {:.0} m derived from radio_strength {} dBm",
+                node.node_id,
+                effective_distance,
+                deviation * 100.0,
+                expected,
+                radio_strength
+            ));
+        }
+    }
+    warnings
+}
+
 // ============================================================================
 // Conversion traits to simulation types
 // ============================================================================
@@ -418,6 +676,30 @@ impl From<&Point> for crate::simulation::types::Point {
     }
 }
 
+impl From<&PathLossParameters> for crate::simulation::signal_calculations::PathLossParameters {
+    fn from(p: &PathLossParameters) -> Self {
+        crate::simulation::signal_calculations::PathLossParameters {
+            path_loss_exponent: p.path_loss_exponent,
+            shadowing_sigma: p.shadowing_sigma,
+            path_loss_at_reference_distance: p.path_loss_at_reference_distance,
+            noise_floor: p.noise_floor,
+        }
+    }
+}
+
+impl From<&LoraParameters> for crate::simulation::signal_calculations::LoraParameters {
+    fn from(l: &LoraParameters) -> Self {
+        crate::simulation::signal_calculations::LoraParameters {
+            bandwidth: l.bandwidth,
+            spreading_factor: l.spreading_factor,
+            coding_rate: l.coding_rate,
+            preamble_symbols: l.preamble_symbols,
+            crc_enabled: l.crc_enabled,
+            low_data_rate_optimization: l.low_data_rate_optimization,
+        }
+    }
+}
+
 impl From<RectPos> for crate::simulation::types::RectPos {
     fn from(r: RectPos) -> Self {
         crate::simulation::types::RectPos {
@@ -441,9 +723,11 @@ impl From<Obstacle> for crate::simulation::types::Obstacle {
         match o {
             Obstacle::Rectangle { position } => crate::simulation::types::Obstacle::Rectangle {
                 position: position.into(),
+                one_way: None,
             },
             Obstacle::Circle { position } => crate::simulation::types::Obstacle::Circle {
                 position: position.into(),
+                one_way: None,
             },
         }
     }
@@ -457,13 +741,144 @@ impl From<&Obstacle> for crate::simulation::types::Obstacle {
                     top_left: (&position.top_left).into(),
                     bottom_right: (&position.bottom_right).into(),
                 },
+                one_way: None,
             },
             Obstacle::Circle { position } => crate::simulation::types::Obstacle::Circle {
                 position: crate::simulation::types::CirclePos {
                     center: (&position.center).into(),
                     radius: position.radius,
                 },
+                one_way: None,
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_json_replaces_nested_arrays() {
+        let mut base = json!({
+            "radio_module_config": { "scoring_matrix": [1, 2, 3, 4, 5], "relay_position_delay": 10 },
+            "obstacles": [{ "type": "rectangle" }]
+        });
+        merge_json(
+            &mut base,
+            json!({
+                "radio_module_config": { "scoring_matrix": [9, 8, 7, 6, 5] },
+                "obstacles": [{ "type": "circle" }]
+            }),
+        );
+        assert_eq!(
+            base,
+            json!({
+                "radio_module_config": { "scoring_matrix": [9, 8, 7, 6, 5], "relay_position_delay": 10 },
+                "obstacles": [{ "type": "rectangle" }, { "type": "circle" }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_json_overrides_fields_and_concatenates_arrays() {
+        let mut base = json!({
+            "lora_parameters": { "spreading_factor": 7, "bandwidth": 125000 },
+            "nodes": [{ "node_id": 1 }],
+            "width": 1000
+        });
+        merge_json(
+            &mut base,
+            json!({
+                "lora_parameters": { "spreading_factor": 9 },
+                "nodes": [{ "node_id": 2 }],
+                "width": 2000
+            }),
+        );
+        assert_eq!(
+            base,
+            json!({
+                "lora_parameters": { "spreading_factor": 9, "bandwidth": 125000 },
+                "nodes": [{ "node_id": 1 }, { "node_id": 2 }],
+                "width": 2000
+            })
+        );
+    }
+
+    #[test]
+    fn test_effective_distance_warnings_report_only_stale_nodes() {
+        let scene_with = |distances: [u32; 2], physics: bool| -> Scene {
+            let mut scene = json!({
+                "nodes": [
+                    { "node_id": 1, "position": { "x": 0, "y": 0 }, "radio_strength": 14.0, "effective_distance": distances[0] },
+                    { "node_id": 2, "position": { "x": 1, "y": 1 }, "radio_strength": 14.0, "effective_distance": distances[1] },
+                    { "node_id": 3, "position": { "x": 2, "y": 2 }, "effective_distance": 1 }
+                ],
+                "world_top_left": { "x": 0, "y": 0 },
+                "world_bottom_right": { "x": 10, "y": 10 }
+            });
+            if physics {
+                scene["path_loss_parameters"] = json!({
+                    "path_loss_exponent": 3.0, "shadowing_sigma": 0.0,
+                    "path_loss_at_reference_distance": 40.0, "noise_floor": -120.0
+                });
+                scene["lora_parameters"] = json!({
+                    "bandwidth": 125000, "spreading_factor": 9, "coding_rate": 1,
+                    "preamble_symbols": 8.0, "crc_enabled": true, "low_data_rate_optimization": false
+                });
+            }
+            serde_json::from_value(scene).unwrap()
+        };
+        let physics = scene_with([0, 0], true);
+        let expected = crate::simulation::signal_calculations::calculate_effective_distance(
+            14.0,
+            &physics.lora_parameters.as_ref().unwrap().into(),
+            &crate::simulation::propagation::LogDistance,
+            &physics.path_loss_parameters.as_ref().unwrap().into(),
+        ) as u32;
+
+        // Node 1 within the tolerance, node 2 stale, node 3 without radio_strength
+        let warnings = effective_distance_warnings(&scene_with([expected, expected * 2], true));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Node 2 "));
+        assert!(effective_distance_warnings(&scene_with([1, 1], false)).is_empty());
+    }
+
+    #[test]
+    fn test_read_scene_value_resolves_nested_includes_and_cycles() {
+        let dir = std::env::temp_dir().join(format!("scene_includes_{}", std::process::id()));
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(
+            dir.join("shared/radio.json"),
+            r#"{ "includes": ["walls.json"], "noise_floor": -120 }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("shared/walls.json"),
+            r#"{ "obstacles": [{ "type": "circle" }] }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("scene.json"),
+            r#"{ "includes": ["shared/radio.json"], "obstacles": [{ "type": "rectangle" }] }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("cycle.json"), r#"{ "includes": ["cycle.json"] }"#).unwrap();
+
+        let value = read_scene_value(dir.join("scene.json").to_str().unwrap()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "obstacles": [{ "type": "circle" }, { "type": "rectangle" }],
+                "noise_floor": -120
+            })
+        );
+        assert!(matches!(
+            read_scene_value(dir.join("cycle.json").to_str().unwrap()),
+            Err(SceneLoadError::ValidationError(_))
+        ));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
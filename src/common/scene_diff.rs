@@ -0,0 +1,346 @@
+//! Differences between two scene files.
+//!
+//! Both scenes are read with their includes resolved (see `scene::read_scene_value`)
+//! and compared on the JSON level, so simulation and analyzer scenes can be compared
+//! without validating either of them:
+//! - nodes are matched by `node_id` and reported as added, removed, moved or with
+//!   changed fields (`radio_strength`, `role`, `effective_distance`, ...)
+//! - obstacles are compared as a multiset: entries only in the first scene are
+//!   removed, entries only in the second scene are added
+//! - every other value is compared by its dotted path (e.g.
+//!   `lora_parameters.spreading_factor`); arrays such as `scoring_matrix` are
+//!   compared as a whole
+
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::scene::{SceneLoadError, read_scene_value};
+
+/// World position of a node (x, y in world units).
+pub type Position = (f64, f64);
+
+/// Change of a single node between the two scenes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeChange {
+    /// Node only present in the second scene.
+    Added { node_id: u32, position: Position },
+    /// Node only present in the first scene.
+    Removed { node_id: u32, position: Position },
+    /// Node present in both scenes at different positions.
+    Moved {
+        node_id: u32,
+        from: Position,
+        to: Position,
+    },
+    /// Node field (other than the position) with different values.
+    FieldChanged {
+        node_id: u32,
+        field: String,
+        before: String,
+        after: String,
+    },
+}
+
+/// Changed scene value: `None` means the value is missing in that scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterChange {
+    /// Dotted path of the value, e.g. `path_loss_parameters.path_loss_exponent`.
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// All differences between two scenes.
+#[derive(Debug, Clone, Default)]
+pub struct SceneDiff {
+    /// Node changes ordered by node ID.
+    pub nodes: Vec<NodeChange>,
+    /// Positions of nodes present in both scenes at the same position.
+    pub unchanged_nodes: Vec<(u32, Position)>,
+    /// Obstacles only present in the second scene (compact JSON).
+    pub obstacles_added: Vec<String>,
+    /// Obstacles only present in the first scene (compact JSON).
+    pub obstacles_removed: Vec<String>,
+    /// Changed values outside `nodes` and `obstacles`, ordered by path.
+    pub parameters: Vec<ParameterChange>,
+}
+
+impl SceneDiff {
+    /// Whether the scenes are identical.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+            && self.obstacles_added.is_empty()
+            && self.obstacles_removed.is_empty()
+            && self.parameters.is_empty()
+    }
+
+    /// Textual diff, one change per line (`+` added, `-` removed, `~` changed).
+    pub fn to_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for change in &self.parameters {
+            lines.push(match (&change.before, &change.after) {
+                (Some(before), Some(after)) => {
+                    format!("~ {}: {} -> {}", change.path, before, after)
+                }
+                (None, Some(after)) => format!("+ {}: {}", change.path, after),
+                (Some(before), None) => format!("- {}: {}", change.path, before),
+                (None, None) => continue,
+            });
+        }
+        for change in &self.nodes {
+            lines.push(match change {
+                NodeChange::Added { node_id, position } => {
+                    format!("+ node #{} at ({}, {})", node_id, position.0, position.1)
+                }
+                NodeChange::Removed { node_id, position } => {
+                    format!("- node #{} at ({}, {})", node_id, position.0, position.1)
+                }
+                NodeChange::Moved { node_id, from, to } => format!(
+                    "~ node #{} moved ({}, {}) -> ({}, {})",
+                    node_id, from.0, from.1, to.0, to.1
+                ),
+                NodeChange::FieldChanged {
+                    node_id,
+                    field,
+                    before,
+                    after,
+                } => format!("~ node #{} {}: {} -> {}", node_id, field, before, after),
+            });
+        }
+        for obstacle in &self.obstacles_removed {
+            lines.push(format!("- obstacle {}", obstacle));
+        }
+        for obstacle in &self.obstacles_added {
+            lines.push(format!("+ obstacle {}", obstacle));
+        }
+        lines
+    }
+}
+
+/// Compare two scene files (includes resolved).
+///
+/// # Parameters
+///
+/// * `before_path` - First (reference) scene file
+/// * `after_path` - Second scene file
+///
+/// # Returns
+///
+/// The differences or an error if either file cannot be read or parsed.
+pub fn diff_scene_files(before_path: &str, after_path: &str) -> Result<SceneDiff, SceneLoadError> {
+    let before = read_scene_value(before_path)?;
+    let after = read_scene_value(after_path)?;
+    Ok(diff_scene_values(&before, &after))
+}
+
+/// Compare two scenes given as JSON values.
+pub fn diff_scene_values(before: &Value, after: &Value) -> SceneDiff {
+    let mut diff = SceneDiff::default();
+    diff_nodes(before, after, &mut diff);
+    diff_obstacles(before, after, &mut diff);
+
+    let mut before_values = BTreeMap::new();
+    let mut after_values = BTreeMap::new();
+    flatten_parameters(before, &mut before_values);
+    flatten_parameters(after, &mut after_values);
+    let paths: BTreeSet<&String> = before_values.keys().chain(after_values.keys()).collect();
+    for path in paths {
+        let (old, new) = (before_values.get(path), after_values.get(path));
+        if old != new {
+            diff.parameters.push(ParameterChange {
+                path: path.clone(),
+                before: old.cloned(),
+                after: new.cloned(),
+            });
+        }
+    }
+    diff
+}
+
+/// Nodes of a scene keyed by node ID (entries without a numeric `node_id` are skipped).
+fn nodes_by_id(scene: &Value) -> BTreeMap<u32, &Map<String, Value>> {
+    scene
+        .get("nodes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|node| {
+            let object = node.as_object()?;
+            let node_id = object.get("node_id")?.as_u64()?.try_into().ok()?;
+            Some((node_id, object))
+        })
+        .collect()
+}
+
+/// Position of a node object ((0, 0) if missing).
+fn node_position(node: &Map<String, Value>) -> Position {
+    let coordinate = |axis: &str| {
+        node.get("position")
+            .and_then(|p| p.get(axis))
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0)
+    };
+    (coordinate("x"), coordinate("y"))
+}
+
+/// Compare the nodes of both scenes.
+fn diff_nodes(before: &Value, after: &Value, diff: &mut SceneDiff) {
+    let before_nodes = nodes_by_id(before);
+    let after_nodes = nodes_by_id(after);
+
+    for (&node_id, node) in &before_nodes {
+        let from = node_position(node);
+        let Some(other) = after_nodes.get(&node_id) else {
+            diff.nodes.push(NodeChange::Removed {
+                node_id,
+                position: from,
+            });
+            continue;
+        };
+
+        let to = node_position(other);
+        if from == to {
+            diff.unchanged_nodes.push((node_id, to));
+        } else {
+            diff.nodes.push(NodeChange::Moved { node_id, from, to });
+        }
+
+        let fields: BTreeSet<&String> = node
+            .keys()
+            .chain(other.keys())
+            .filter(|k| k.as_str() != "node_id" && k.as_str() != "position")
+            .collect();
+        for field in fields {
+            let (old, new) = (node.get(field), other.get(field));
+            if old != new {
+                diff.nodes.push(NodeChange::FieldChanged {
+                    node_id,
+                    field: field.clone(),
+                    before: old.map_or("-".to_string(), Value::to_string),
+                    after: new.map_or("-".to_string(), Value::to_string),
+                });
+            }
+        }
+    }
+
+    for (&node_id, node) in &after_nodes {
+        if !before_nodes.contains_key(&node_id) {
+            diff.nodes.push(NodeChange::Added {
+                node_id,
+                position: node_position(node),
+            });
+        }
+    }
+    diff.nodes.sort_by_key(|change| match change {
+        NodeChange::Added { node_id, .. }
+        | NodeChange::Removed { node_id, .. }
+        | NodeChange::Moved { node_id, .. }
+        | NodeChange::FieldChanged { node_id, .. } => *node_id,
+    });
+}
+
+/// Compare the obstacles of both scenes as multisets of compact JSON strings.
+fn diff_obstacles(before: &Value, after: &Value, diff: &mut SceneDiff) {
+    let obstacles = |scene: &Value| -> Vec<String> {
+        scene
+            .get("obstacles")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(Value::to_string)
+            .collect()
+    };
+
+    let mut remaining: HashMap<String, usize> = HashMap::new();
+    for obstacle in obstacles(before) {
+        *remaining.entry(obstacle).or_default() += 1;
+    }
+    for obstacle in obstacles(after) {
+        match remaining.get_mut(&obstacle) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => diff.obstacles_added.push(obstacle),
+        }
+    }
+    for obstacle in obstacles(before) {
+        if let Some(count) = remaining.get_mut(&obstacle) {
+            if *count > 0 {
+                *count -= 1;
+                diff.obstacles_removed.push(obstacle);
+            }
+        }
+    }
+}
+
+/// Flatten all values except `nodes` and `obstacles` into dotted paths.
+fn flatten_parameters(scene: &Value, values: &mut BTreeMap<String, String>) {
+    fn flatten(prefix: &str, value: &Value, values: &mut BTreeMap<String, String>) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    flatten(&path, value, values);
+                }
+            }
+            other => {
+                values.insert(prefix.to_string(), other.to_string());
+            }
+        }
+    }
+
+    if let Value::Object(object) = scene {
+        for (key, value) in object {
+            if key != "nodes" && key != "obstacles" {
+                flatten(key, value, values);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_reports_nodes_obstacles_and_parameters() {
+        let before = json!({
+            "lora_parameters": { "spreading_factor": 7, "bandwidth": 125000 },
+            "nodes": [
+                { "node_id": 1, "position": { "x": 10, "y": 10 }, "radio_strength": 14.0 },
+                { "node_id": 2, "position": { "x": 20, "y": 20 }, "radio_strength": 14.0 },
+                { "node_id": 3, "position": { "x": 30, "y": 30 }, "radio_strength": 14.0 }
+            ],
+            "obstacles": [{ "type": "circle", "radius": 5 }]
+        });
+        let after = json!({
+            "lora_parameters": { "spreading_factor": 9, "bandwidth": 125000 },
+            "reception_model": "packet_error_rate",
+            "nodes": [
+                { "node_id": 1, "position": { "x": 10, "y": 10 }, "radio_strength": 20.0 },
+                { "node_id": 2, "position": { "x": 25, "y": 20 }, "radio_strength": 14.0 },
+                { "node_id": 4, "position": { "x": 40, "y": 40 }, "radio_strength": 14.0 }
+            ],
+            "obstacles": [{ "type": "circle", "radius": 5 }, { "type": "circle", "radius": 5 }]
+        });
+
+        let diff = diff_scene_values(&before, &after);
+        assert_eq!(
+            diff.to_lines(),
+            vec![
+                "~ lora_parameters.spreading_factor: 7 -> 9",
+                "+ reception_model: \"packet_error_rate\"",
+                "~ node #1 radio_strength: 14.0 -> 20.0",
+                "~ node #2 moved (20, 20) -> (25, 20)",
+                "- node #3 at (30, 30)",
+                "+ node #4 at (40, 40)",
+                "+ obstacle {\"radius\":5,\"type\":\"circle\"}",
+            ]
+        );
+        assert_eq!(diff.unchanged_nodes, vec![(1, (10.0, 10.0))]);
+        assert!(diff_scene_values(&before, &before).is_empty());
+    }
+}
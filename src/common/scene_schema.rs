@@ -0,0 +1,140 @@
+//! JSON Schema of the scene format and schema validation of scene files.
+//!
+//! The schema is generated from the serde structs of the scene (`schemars`), so it
+//! follows the loader without a hand-maintained copy: the Simulation schema from
+//! `simulation::types::Scene`, the analyzer schema from `common::scene::Scene`.
+//! Unlike the loader, which ignores unknown keys, the schema rejects them, so a
+//! misspelled optional field (e.g. `stop_condition`) is reported instead of being
+//! silently left at its default.
+//!
+//! Validation runs on the scene as the loader sees it: with the `includes` merged and
+//! the `lora_preset` and `obstacle_generator` expanded (see [`read_scene_value`]).
+//! Every violation is reported with the JSON pointer of the offending value, e.g.
+//! `/nodes/12/radio_strength: "14" is not of type "number"`.
+
+use serde_json::Value;
+
+use super::scene::{SceneMode, read_scene_value};
+
+/// JSON Schema of the scene format of a mode.
+pub fn scene_schema(mode: SceneMode) -> Value {
+    let schema = match mode {
+        SceneMode::Simulation => schemars::schema_for!(crate::simulation::types::Scene),
+        SceneMode::Analyzer => schemars::schema_for!(super::scene::Scene),
+    };
+    let mut schema = serde_json::to_value(schema).unwrap_or(Value::Null);
+    deny_unknown_properties(&mut schema);
+    schema
+}
+
+/// Reject unknown keys in every object schema that lists its properties.
+///
+/// Objects combined from subschemas are left open, since each subschema only lists
+/// part of the keys.
+fn deny_unknown_properties(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            let combined = ["allOf", "anyOf", "oneOf", "$ref"]
+                .iter()
+                .any(|key| object.contains_key(*key));
+            if object.contains_key("properties")
+                && !combined
+                && !object.contains_key("additionalProperties")
+            {
+                object.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            object.values_mut().for_each(deny_unknown_properties);
+        }
+        Value::Array(items) => items.iter_mut().for_each(deny_unknown_properties),
+        _ => {}
+    }
+}
+
+/// Check a scene value against the schema of a mode.
+///
+/// # Returns
+///
+/// The violations as `<JSON pointer>: <message>` lines (empty if the scene is valid).
+pub fn validate_scene_value(value: &Value, mode: SceneMode) -> Vec<String> {
+    let schema = scene_schema(mode);
+    let compiled = match jsonschema::JSONSchema::compile(&schema) {
+        Ok(compiled) => compiled,
+        Err(err) => return vec![format!("Invalid scene schema: {}", err)],
+    };
+    let Err(errors) = compiled.validate(value) else {
+        return Vec::new();
+    };
+    errors
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            format!("{}: {}", if path.is_empty() { "/" } else { &path }, error)
+        })
+        .collect()
+}
+
+/// Read a scene file (includes and presets resolved) and check it against the schema.
+///
+/// # Parameters
+///
+/// * `path` - Path to the scene JSON file
+/// * `mode` - Scene format to check against
+///
+/// # Returns
+///
+/// The violations (empty if the scene is valid), or an error if the file cannot be
+/// read or its includes, preset or obstacle generator are invalid.
+pub fn validate_scene_file(path: &str, mode: SceneMode) -> Result<Vec<String>, String> {
+    let value = read_scene_value(path).map_err(|err| err.to_string())?;
+    Ok(validate_scene_value(&value, mode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_scenes_match_and_typos_are_located() {
+        let scenes = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+        for (directory, mode) in [
+            ("simulation", SceneMode::Simulation),
+            ("analyzer", SceneMode::Analyzer),
+        ] {
+            for entry in std::fs::read_dir(scenes.join(directory)).unwrap() {
+                let path = entry.unwrap().path();
+                let violations = validate_scene_file(path.to_str().unwrap(), mode).unwrap();
+                assert!(
+                    violations.is_empty(),
+                    "{}: {:?}",
+                    path.display(),
+                    violations
+                );
+            }
+        }
+
+        let path = scenes.join("simulation").join("basic.json");
+        let mut scene = read_scene_value(path.to_str().unwrap()).unwrap();
+        scene["nodes"][1]["radio_strength"] = Value::String("14".to_string());
+        scene["stop_condition"] = serde_json::json!({ "time_limit": 60 });
+        scene["obstacles"] = serde_json::json!([{ "type": "triangle" }]);
+        let violations = validate_scene_value(&scene, SceneMode::Simulation);
+        assert!(
+            violations
+                .iter()
+                .any(|v| v.starts_with("/nodes/1/radio_strength:")),
+            "{:?}",
+            violations
+        );
+        assert!(
+            violations
+                .iter()
+                .any(|v| v.starts_with("/:") && v.contains("stop_condition")),
+            "{:?}",
+            violations
+        );
+        assert!(
+            violations.iter().any(|v| v.starts_with("/obstacles/0")),
+            "{:?}",
+            violations
+        );
+    }
+}
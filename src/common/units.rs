@@ -0,0 +1,255 @@
+//! Scene units and coordinate transforms.
+//!
+//! The simulator works with three coordinate spaces:
+//! - **World units**: coordinates used in scene files (node positions, obstacles),
+//!   bounded by `world_top_left` / `world_bottom_right`. The world does not have to
+//!   start at (0, 0): projected GIS data (e.g. UTM eastings/northings) with negative
+//!   or very large coordinates and inverted axes is used as is
+//! - **Meters**: physical distances used by the radio model (ranges, path loss)
+//! - **Screen pixels**: map rendering (see `ui::map::MapTransform`)
+//!
+//! `WorldTransform` owns the world-unit ↔ meter conversion. It is built once when a
+//! scene is loaded, either from the world size in meters (`width`/`height`) or from
+//! a uniform `meters_per_unit` scale, and then shared by geometry, the radio model
+//! and the map so that all of them agree on distances.
+
+/// Conversion between world units and meters for a scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldTransform {
+    /// Top-left corner of the world in world units.
+    top_left: (f64, f64),
+    /// Bottom-right corner of the world in world units.
+    bottom_right: (f64, f64),
+    /// Meters per world unit along the X axis.
+    meters_per_unit_x: f64,
+    /// Meters per world unit along the Y axis.
+    meters_per_unit_y: f64,
+}
+
+impl Default for WorldTransform {
+    fn default() -> Self {
+        Self::from_meters_per_unit((0.0, 0.0), (100.0, 100.0), 1.0)
+    }
+}
+
+impl WorldTransform {
+    /// Create a transform from the world bounds and the world size in meters.
+    ///
+    /// The X and Y scales may differ (non-uniform scaling).
+    pub fn from_dimensions(
+        top_left: (f64, f64),
+        bottom_right: (f64, f64),
+        width_meters: f64,
+        height_meters: f64,
+    ) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+            meters_per_unit_x: width_meters / (bottom_right.0 - top_left.0).abs(),
+            meters_per_unit_y: height_meters / (bottom_right.1 - top_left.1).abs(),
+        }
+    }
+
+    /// Create a transform from the world bounds and a uniform meters-per-unit scale.
+    pub fn from_meters_per_unit(
+        top_left: (f64, f64),
+        bottom_right: (f64, f64),
+        meters_per_unit: f64,
+    ) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+            meters_per_unit_x: meters_per_unit,
+            meters_per_unit_y: meters_per_unit,
+        }
+    }
+
+    /// Create the transform from the scene file fields.
+    ///
+    /// Exactly one of `meters_per_unit` or the `width`/`height` pair must be given.
+    ///
+    /// # Returns
+    ///
+    /// The transform, or an error describing the invalid configuration.
+    pub fn from_scene(
+        top_left: (f64, f64),
+        bottom_right: (f64, f64),
+        width: Option<f64>,
+        height: Option<f64>,
+        meters_per_unit: Option<f64>,
+    ) -> Result<Self, String> {
+        if bottom_right.0 == top_left.0 || bottom_right.1 == top_left.1 {
+            return Err("world_top_left and world_bottom_right must span a non-empty area".into());
+        }
+        let transform = match (width, height, meters_per_unit) {
+            (None, None, Some(meters_per_unit)) => {
+                Self::from_meters_per_unit(top_left, bottom_right, meters_per_unit)
+            }
+            (Some(width), Some(height), None) => {
+                Self::from_dimensions(top_left, bottom_right, width, height)
+            }
+            (_, _, Some(_)) => {
+                return Err("specify either width/height or meters_per_unit, not both".into());
+            }
+            _ => {
+                return Err("scene must define both width and height, or meters_per_unit".into());
+            }
+        };
+        let (scale_x, scale_y) = transform.meters_per_unit();
+        if !(scale_x.is_finite() && scale_x > 0.0 && scale_y.is_finite() && scale_y > 0.0) {
+            return Err("world size in meters must be positive".into());
+        }
+        Ok(transform)
+    }
+
+    /// Top-left corner of the world in world units.
+    pub fn top_left(&self) -> (f64, f64) {
+        self.top_left
+    }
+
+    /// Bottom-right corner of the world in world units.
+    pub fn bottom_right(&self) -> (f64, f64) {
+        self.bottom_right
+    }
+
+    /// Axis-aligned extent of the world in world units as `(min_x, min_y, max_x, max_y)`,
+    /// independent of the axis directions.
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        (
+            self.top_left.0.min(self.bottom_right.0),
+            self.top_left.1.min(self.bottom_right.1),
+            self.top_left.0.max(self.bottom_right.0),
+            self.top_left.1.max(self.bottom_right.1),
+        )
+    }
+
+    /// Whether a world point lies inside the world bounds (inclusive).
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y)
+    }
+
+    /// The world bounds for messages, e.g. `x 0..10000, y -500..500`.
+    pub fn bounds_label(&self) -> String {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        format!("x {}..{}, y {}..{}", min_x, max_x, min_y, max_y)
+    }
+
+    /// Nearest point inside the world bounds.
+    pub fn clamp(&self, x: f64, y: f64) -> (f64, f64) {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        (x.clamp(min_x, max_x), y.clamp(min_y, max_y))
+    }
+
+    /// Meters per world unit along the X and Y axes.
+    pub fn meters_per_unit(&self) -> (f64, f64) {
+        (self.meters_per_unit_x, self.meters_per_unit_y)
+    }
+
+    /// Width of the world in meters.
+    pub fn width_meters(&self) -> f64 {
+        (self.bottom_right.0 - self.top_left.0).abs() * self.meters_per_unit_x
+    }
+
+    /// Height of the world in meters.
+    pub fn height_meters(&self) -> f64 {
+        (self.bottom_right.1 - self.top_left.1).abs() * self.meters_per_unit_y
+    }
+
+    /// Convert a world-unit offset to meters.
+    pub fn offset_to_meters(&self, dx: f64, dy: f64) -> (f64, f64) {
+        (dx * self.meters_per_unit_x, dy * self.meters_per_unit_y)
+    }
+
+    /// Convert an offset in meters to world units.
+    pub fn offset_to_units(&self, dx_meters: f64, dy_meters: f64) -> (f64, f64) {
+        (
+            dx_meters / self.meters_per_unit_x,
+            dy_meters / self.meters_per_unit_y,
+        )
+    }
+
+    /// Squared distance in meters² between two points given in world units.
+    pub fn distance2(&self, a: (f64, f64), b: (f64, f64)) -> f64 {
+        let (dx, dy) = self.offset_to_meters(a.0 - b.0, a.1 - b.1);
+        dx * dx + dy * dy
+    }
+
+    /// Position of a world point relative to the world bounds (0..1 on each axis,
+    /// 0 at the top-left corner). Works for inverted axes as well.
+    pub fn normalize(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            (x - self.top_left.0) / (self.bottom_right.0 - self.top_left.0),
+            (y - self.top_left.1) / (self.bottom_right.1 - self.top_left.1),
+        )
+    }
+
+    /// World point at a normalized position (inverse of `normalize`).
+    pub fn denormalize(&self, tx: f64, ty: f64) -> (f64, f64) {
+        (
+            self.top_left.0 + tx * (self.bottom_right.0 - self.top_left.0),
+            self.top_left.1 + ty * (self.bottom_right.1 - self.top_left.1),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dimensions_and_meters_per_unit_agree() {
+        let by_size =
+            WorldTransform::from_dimensions((0.0, 0.0), (10000.0, 5000.0), 2000.0, 1000.0);
+        let by_scale = WorldTransform::from_meters_per_unit((0.0, 0.0), (10000.0, 5000.0), 0.2);
+        assert_eq!(by_size, by_scale);
+        assert_eq!(by_size.width_meters(), 2000.0);
+        assert_eq!(by_size.height_meters(), 1000.0);
+        assert!((by_size.distance2((0.0, 0.0), (3000.0, 4000.0)) - 1_000_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn normalize_handles_inverted_axes() {
+        let transform = WorldTransform::from_meters_per_unit((0.0, 100.0), (100.0, 0.0), 1.0);
+        assert_eq!(transform.normalize(25.0, 25.0), (0.25, 0.75));
+        assert_eq!(transform.denormalize(0.25, 0.75), (25.0, 25.0));
+        assert_eq!(transform.height_meters(), 100.0);
+    }
+
+    #[test]
+    fn bounds_support_negative_and_projected_coordinates() {
+        // UTM-like northings decreasing downwards, eastings crossing zero
+        let transform = WorldTransform::from_meters_per_unit(
+            (-2500.0, 5_274_000.0),
+            (2500.0, 5_269_000.0),
+            1.0,
+        );
+        assert_eq!(
+            transform.bounds(),
+            (-2500.0, 5_269_000.0, 2500.0, 5_274_000.0)
+        );
+        assert!(transform.contains(-1000.0, 5_270_000.0));
+        assert!(!transform.contains(-1000.0, 5_268_999.0));
+        assert_eq!(
+            transform.clamp(-3000.0, 5_280_000.0),
+            (-2500.0, 5_274_000.0)
+        );
+        assert_eq!(transform.normalize(0.0, 5_271_500.0), (0.5, 0.5));
+        assert_eq!(transform.width_meters(), 5000.0);
+        assert_eq!(
+            transform.distance2((-1.0, 5_270_000.0), (2.0, 5_270_004.0)),
+            25.0
+        );
+    }
+
+    #[test]
+    fn scene_fields_must_be_unambiguous() {
+        let (tl, br) = ((0.0, 0.0), (100.0, 100.0));
+        assert!(WorldTransform::from_scene(tl, br, Some(10.0), Some(10.0), None).is_ok());
+        assert!(WorldTransform::from_scene(tl, br, None, None, Some(0.5)).is_ok());
+        assert!(WorldTransform::from_scene(tl, br, Some(10.0), Some(10.0), Some(0.5)).is_err());
+        assert!(WorldTransform::from_scene(tl, br, Some(10.0), None, None).is_err());
+        assert!(WorldTransform::from_scene(tl, br, None, None, Some(-1.0)).is_err());
+        assert!(WorldTransform::from_scene(tl, tl, Some(10.0), Some(10.0), None).is_err());
+    }
+}
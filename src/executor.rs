@@ -0,0 +1,131 @@
+//! Thread-mode Embassy executor that reports when it is idle.
+//!
+//! Works like the `executor-thread` executor of `embassy-executor`: `run` polls
+//! the ready tasks and blocks the thread until a task is woken (the `__pender`
+//! hook). In addition it tells the time driver when it blocks with no task ready
+//! and when a wake makes it busy again (`time_driver::executor_idle` /
+//! `time_driver::executor_busy`). The event-driven clock jumps to the next
+//! deadline only while every executor is idle, i.e. exactly when no task can run
+//! before a timer fires.
+
+use embassy_executor::{Spawner, raw};
+use std::marker::PhantomData;
+use std::sync::{Condvar, Mutex};
+
+use crate::time_driver;
+
+/// Wake-up state of an executor thread.
+#[derive(Default)]
+struct SignalState {
+    /// A task was woken since the last poll.
+    ready: bool,
+    /// The executor blocks with no task ready (reported to the time driver).
+    idle: bool,
+}
+
+/// Blocks the executor thread until a task is woken.
+#[derive(Default)]
+struct Signaler {
+    state: Mutex<SignalState>,
+    condvar: Condvar,
+}
+
+impl Signaler {
+    /// Wait until a task is woken; an executor that has to block reports itself idle.
+    fn wait(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if !state.ready {
+            state.idle = true;
+            time_driver::executor_idle();
+            while !state.ready {
+                state = self.condvar.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+        }
+        state.ready = false;
+    }
+
+    /// A task was woken: let the executor poll (busy again if it was idle).
+    fn signal(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.idle {
+            state.idle = false;
+            time_driver::executor_busy();
+        }
+        state.ready = true;
+        self.condvar.notify_one();
+    }
+}
+
+/// Pender of the raw executor: called by Embassy whenever a task is woken.
+#[unsafe(export_name = "__pender")]
+fn __pender(context: *mut ()) {
+    // SAFETY: the context is the leaked `Signaler` of the executor (see `Executor::new`)
+    let signaler = unsafe { &*(context as *const Signaler) };
+    signaler.signal();
+}
+
+/// Thread-mode executor with idle reporting.
+pub struct Executor {
+    inner: raw::Executor,
+    signaler: &'static Signaler,
+    /// The executor runs on the thread that created it.
+    not_send: PhantomData<*mut ()>,
+}
+
+impl Executor {
+    /// Create a new executor.
+    pub fn new() -> Self {
+        // INTENTIONAL LEAK: the pender context must outlive the executor's tasks
+        let signaler: &'static Signaler = Box::leak(Box::default());
+        Self {
+            inner: raw::Executor::new(signaler as *const Signaler as *mut ()),
+            signaler,
+            not_send: PhantomData,
+        }
+    }
+
+    /// Run the executor on the current thread, forever.
+    ///
+    /// # Parameters
+    ///
+    /// * `init` - Called once with the spawner, to spawn the first tasks
+    pub fn run(&'static mut self, init: impl FnOnce(Spawner)) -> ! {
+        time_driver::executor_busy();
+        init(self.inner.spawner());
+        loop {
+            // SAFETY: the executor is only polled from this thread
+            unsafe { self.inner.poll() };
+            self.signaler.wait();
+        }
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_waiting_executor_is_idle_until_signaled() {
+        let signaler: &'static Signaler = Box::leak(Box::default());
+        // A pending wake is consumed without blocking
+        signaler.signal();
+        signaler.wait();
+        assert!(!signaler.state.lock().unwrap().idle);
+
+        let waiter = std::thread::spawn(move || signaler.wait());
+        while !signaler.state.lock().unwrap().idle {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        signaler.signal();
+        waiter.join().unwrap();
+        let state = signaler.state.lock().unwrap();
+        assert!(!state.idle && !state.ready);
+    }
+}
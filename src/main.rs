@@ -25,9 +25,22 @@
 //! ## Communication Channels
 //!
 //! Two bounded channels coordinate between the UI and simulation:
-//! - `UIRefreshChannel`: Network → UI updates (node states, metrics, events)
+//! - `UIRefreshChannel`: Network → UI updates (node states, metrics, events), shedding
+//!   cosmetic updates first under load
 //! - `UICommandChannel`: UI → Network commands (load scene, select node, start measurement)
 //!
+//! ## Shutdown
+//!
+//! When the window closes, the UI thread sends `UICommand::Shutdown` and waits for the
+//! running simulation to flush its outputs (run summary, node processes) before the
+//! process exits (see `shutdown`).
+//!
+//! ## Sessions
+//!
+//! The mode dispatcher starts the task of the selected mode and waits until it ended,
+//! so "Change mode" (`UICommand::EndSession`) returns to the mode selector without
+//! restarting the process (see `session`).
+//!
 //! ## Design Rationale
 //!
 //! This lightweight multi-node simulation architecture avoids the overhead of VM-based
@@ -36,46 +49,40 @@
 //! controlled experiments with arbitrary topologies without deploying physical hardware.
 
 use eframe::egui;
-use embassy_executor::{Executor, Spawner};
+use embassy_executor::Spawner;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use env_logger::Builder;
 use log::{LevelFilter, error, info};
 use std::thread;
 
+use crate::executor::Executor;
+use crate::ui::launch_options::{
+    AuditArgs, CalibrateArgs, ConvertArgs, DiffArgs, Launch, PlanArgs, RunArgs, ToolCommand,
+};
+
 mod analyzer;
 mod common;
 mod control;
+mod executor;
+mod session;
+mod shutdown;
 mod simulation;
 mod time_driver;
 mod ui;
 
-/// Capacity of the UI refresh channel (network → UI).
-/// Large enough to handle bursts of node updates without blocking the simulation.
+/// Capacity of the UI refresh channel (network → UI) for non-critical updates.
+/// Large enough to handle bursts of node updates; critical updates are queued beyond it.
 pub const UI_REFRESH_CHANNEL_SIZE: usize = 500;
-/// Bounded channel for sending UI state updates from the network task to the UI.
-pub type UIRefreshQueue = embassy_sync::channel::Channel<
-    CriticalSectionRawMutex,
-    ui::UIRefreshState,
-    UI_REFRESH_CHANNEL_SIZE,
->;
-/// Receiver side of the UI refresh channel.
-pub type UIRefreshQueueReceiver = embassy_sync::channel::Receiver<
-    'static,
-    CriticalSectionRawMutex,
-    ui::UIRefreshState,
-    UI_REFRESH_CHANNEL_SIZE,
->;
-/// Sender side of the UI refresh channel.
-pub type UIRefreshQueueSender = embassy_sync::channel::Sender<
-    'static,
-    CriticalSectionRawMutex,
-    ui::UIRefreshState,
-    UI_REFRESH_CHANNEL_SIZE,
->;
+/// Prioritized queue for UI state updates from the network task to the UI
+/// (see `ui::refresh_queue`), with its sender and receiver sides.
+pub use ui::refresh_queue::{UIRefreshQueue, UIRefreshQueueReceiver, UIRefreshQueueSender};
 
 /// Capacity of the UI command channel (UI → network).
 /// Smaller than refresh channel as user commands are infrequent.
 pub const UI_COMMAND_CHANNEL_SIZE: usize = 100;
+
+/// Longest time the UI thread waits for the simulation to flush its outputs on exit.
+const SHUTDOWN_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 /// Bounded channel for sending commands from the UI to the network task.
 pub type UICommandQueue =
     embassy_sync::channel::Channel<CriticalSectionRawMutex, ui::UICommand, UI_COMMAND_CHANNEL_SIZE>;
@@ -119,17 +126,18 @@ fn embassy_init(
 /// Depending on the selected mode, it spawns either:
 /// - `network_task` for Simulation mode
 /// - `analyzer_task` for RealtimeTracking or LogVisualization modes
+///
+/// It then waits until the session task ended (see `session`), confirms the end to
+/// the UI with `SessionEnded` and waits for the next mode selection.
 #[embassy_executor::task]
 async fn mode_dispatcher_task(
     spawner: Spawner,
     ui_refresh_tx: UIRefreshQueueSender,
     ui_command_rx: UICommandQueueReceiver,
 ) {
-    log::info!("Mode dispatcher waiting for mode selection...");
-
     loop {
-        let cmd = ui_command_rx.receive().await;
-        match cmd {
+        log::info!("Mode dispatcher waiting for mode selection...");
+        let spawned = match ui_command_rx.receive().await {
             ui::UICommand::StartMode {
                 mode,
                 scene_path,
@@ -139,17 +147,14 @@ async fn mode_dispatcher_task(
                 match mode {
                     ui::OperatingMode::Simulation => {
                         // Simulation mode: spawn network_task with scene path
-                        let _ = ui_refresh_tx
-                            .send(ui::UIRefreshState::ModeChanged(mode))
-                            .await;
-                        let _ = spawner.spawn(simulation::network_task(
+                        ui_refresh_tx.send(ui::UIRefreshState::ModeChanged(mode));
+                        log::info!("Starting simulation mode, scene: {}", scene_path);
+                        spawner.spawn(simulation::network_task(
                             spawner,
                             ui_refresh_tx,
                             ui_command_rx,
-                            Some(scene_path.clone()),
-                        ));
-                        log::info!("Simulation mode started, scene: {}", scene_path);
-                        return;
+                            Some(scene_path),
+                        ))
                     }
                     ui::OperatingMode::RealtimeTracking | ui::OperatingMode::LogVisualization => {
                         // Analyzer modes: spawn analyzer_task
@@ -163,18 +168,15 @@ async fn mode_dispatcher_task(
                             }
                             _ => unreachable!(),
                         };
-                        let _ = ui_refresh_tx
-                            .send(ui::UIRefreshState::ModeChanged(mode))
-                            .await;
-                        let _ = spawner.spawn(analyzer::analyzer_task(
+                        ui_refresh_tx.send(ui::UIRefreshState::ModeChanged(mode));
+                        log::info!("Starting analyzer mode");
+                        spawner.spawn(analyzer::analyzer_task(
                             analyzer_mode,
                             scene_path,
                             log_path,
                             ui_refresh_tx,
                             ui_command_rx,
-                        ));
-                        log::info!("Analyzer mode started");
-                        return;
+                        ))
                     }
                 }
             }
@@ -182,25 +184,44 @@ async fn mode_dispatcher_task(
                 // Legacy path: Simulation mode with direct LoadFile
                 // This happens when user selects Simulation and picks a scene file (old behavior)
                 log::info!("Direct LoadFile received (Simulation mode): {}", path);
-                let _ = ui_refresh_tx
-                    .send(ui::UIRefreshState::ModeChanged(
-                        ui::OperatingMode::Simulation,
-                    ))
-                    .await;
+                ui_refresh_tx.send(ui::UIRefreshState::ModeChanged(
+                    ui::OperatingMode::Simulation,
+                ));
                 // Spawn the network_task with the scene path
-                let _ = spawner.spawn(simulation::network_task(
+                spawner.spawn(simulation::network_task(
                     spawner,
                     ui_refresh_tx,
                     ui_command_rx,
                     Some(path),
-                ));
-                return;
+                ))
+            }
+            ui::UICommand::EndSession => {
+                // The session already ended on its own (e.g. its scene failed to load)
+                ui_refresh_tx.send(ui::UIRefreshState::SessionEnded);
+                continue;
             }
-            _ => {
+            cmd => {
                 // Ignore other commands before mode is selected
                 log::debug!("Ignoring command before mode selection: {:?}", cmd);
+                continue;
+            }
+        };
+
+        match spawned {
+            Ok(()) => {
+                session::wait_for_session_end().await;
+                log::info!("Session ended");
+            }
+            Err(err) => {
+                log::error!("Cannot start the session: {:?}", err);
+                ui_refresh_tx.send(ui::UIRefreshState::Alert(
+                    "Cannot start the session, the previous one is still running".to_string(),
+                ));
             }
         }
+        // The ended session may have left the virtual clock frozen
+        time_driver::set_simulation_paused(false);
+        ui_refresh_tx.send(ui::UIRefreshState::SessionEnded);
     }
 }
 
@@ -231,10 +252,430 @@ fn load_app_icon() -> Option<egui::IconData> {
     }
 }
 
+/// Run the path loss calibration tool from command-line arguments (without the GUI).
+///
+/// # Parameters
+///
+/// * `args` - Arguments of the `calibrate` subcommand
+///
+/// # Returns
+///
+/// The process exit code (0 on success).
+fn run_calibrate_command(args: CalibrateArgs) -> i32 {
+    let output_path = args.output.as_deref().unwrap_or(&args.scene);
+
+    match simulation::calibration::run_calibration(
+        &args.scene,
+        &args.samples,
+        args.tx_power,
+        output_path,
+    ) {
+        Ok(result) => {
+            println!(
+                "Fitted {} samples: path_loss_exponent = {:.3}, path_loss_at_reference_distance = {:.2} dB",
+                result.sample_count,
+                result.path_loss_exponent,
+                result.path_loss_at_reference_distance
+            );
+            println!(
+                "Residual standard deviation: {:.2} dB (candidate shadowing_sigma)",
+                result.residual_sigma
+            );
+            println!("Updated scene written to {}", output_path);
+            0
+        }
+        Err(err) => {
+            eprintln!("Calibration failed: {:#}", err);
+            1
+        }
+    }
+}
+
+/// Print the differences between two scene files (without the GUI).
+///
+/// # Parameters
+///
+/// * `args` - Arguments of the `diff` subcommand
+///
+/// # Returns
+///
+/// The process exit code: 0 if the scenes are identical, 1 if they differ, 2 on errors.
+fn run_diff_command(args: DiffArgs) -> i32 {
+    match common::scene_diff::diff_scene_files(&args.before, &args.after) {
+        Ok(diff) if diff.is_empty() => {
+            println!("Scenes are identical");
+            0
+        }
+        Ok(diff) => {
+            for line in diff.to_lines() {
+                println!("{}", line);
+            }
+            1
+        }
+        Err(err) => {
+            eprintln!("Scene diff failed: {}", err);
+            2
+        }
+    }
+}
+
+/// Usage text of the scene schema command-line tools.
+const SCENE_SCHEMA_USAGE: &str = "Usage: moonblokz-radio-simulator --validate-scene <scene.json> [--analyzer]\n       moonblokz-radio-simulator --scene-schema [--analyzer]";
+
+/// Scene format selected by the optional `--analyzer` flag of the schema tools.
+fn scene_schema_mode(flag: Option<&String>) -> Option<common::scene::SceneMode> {
+    match flag.map(String::as_str) {
+        None => Some(common::scene::SceneMode::Simulation),
+        Some("--analyzer") => Some(common::scene::SceneMode::Analyzer),
+        Some(_) => None,
+    }
+}
+
+/// Check a scene file against the scene JSON Schema (without the GUI).
+///
+/// # Parameters
+///
+/// * `args` - Arguments following `--validate-scene`
+///
+/// # Returns
+///
+/// The process exit code: 0 if the scene is valid, 1 if it has violations, 2 on errors.
+fn run_validate_scene_command(args: &[String]) -> i32 {
+    let (Some(path), Some(mode), None) =
+        (args.first(), scene_schema_mode(args.get(1)), args.get(2))
+    else {
+        eprintln!("{}", SCENE_SCHEMA_USAGE);
+        return 2;
+    };
+    match common::scene_schema::validate_scene_file(path, mode) {
+        Ok(violations) if violations.is_empty() => {
+            println!("{}: valid", path);
+            // Stale analyzer scenes are valid but their ranges no longer match the physics
+            if mode == common::scene::SceneMode::Analyzer {
+                if let Ok(scene) = common::scene::load_scene(path, mode) {
+                    for warning in common::scene::effective_distance_warnings(&scene) {
+                        println!("warning: {}", warning);
+                    }
+                }
+            }
+            0
+        }
+        Ok(violations) => {
+            for violation in &violations {
+                println!("{}", violation);
+            }
+            eprintln!("{}: {} schema violation(s)", path, violations.len());
+            1
+        }
+        Err(err) => {
+            eprintln!("Scene validation failed: {}", err);
+            2
+        }
+    }
+}
+
+/// Print the scene JSON Schema (without the GUI).
+///
+/// # Parameters
+///
+/// * `args` - Arguments following `--scene-schema`
+///
+/// # Returns
+///
+/// The process exit code: 0 on success, 2 on invalid arguments.
+fn run_scene_schema_command(args: &[String]) -> i32 {
+    let (Some(mode), None) = (scene_schema_mode(args.first()), args.get(1)) else {
+        eprintln!("{}", SCENE_SCHEMA_USAGE);
+        return 2;
+    };
+    let schema = common::scene_schema::scene_schema(mode);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).unwrap_or_default()
+    );
+    0
+}
+
+/// Run the determinism audit from command-line arguments (without the GUI).
+///
+/// # Parameters
+///
+/// * `args` - Arguments of the `audit` subcommand
+///
+/// # Returns
+///
+/// The process exit code: 0 if both runs match, 1 on a divergence, 2 on errors.
+fn run_audit_command(args: AuditArgs) -> i32 {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    println!(
+        "Running {} twice: {} virtual seconds on the event-driven clock, seed {}",
+        args.scene, args.duration, seed
+    );
+    match simulation::determinism_audit::run_audit(&args.scene, args.duration, seed) {
+        Ok(report) => {
+            println!(
+                "Run 1: {} events, run 2: {} events",
+                report.event_counts.0, report.event_counts.1
+            );
+            let Some(divergence) = report.divergence else {
+                println!(
+                    "Identical event streams (max timing drift {:.3} s)",
+                    report.max_timing_drift
+                );
+                return 0;
+            };
+            let node = divergence
+                .node_id
+                .map_or(String::new(), |id| format!(" (node {})", id));
+            println!(
+                "First divergence at event #{}{}: {}",
+                divergence.index, node, divergence.cause
+            );
+            println!(
+                "  run 1: {}",
+                divergence.first.as_deref().unwrap_or("<end of run>")
+            );
+            println!(
+                "  run 2: {}",
+                divergence.second.as_deref().unwrap_or("<end of run>")
+            );
+            println!(
+                "Max timing drift before the divergence: {:.3} s",
+                report.max_timing_drift
+            );
+            1
+        }
+        Err(err) => {
+            eprintln!("Audit failed: {:#}", err);
+            2
+        }
+    }
+}
+
+/// Run a scene without the GUI until a stop condition is met.
+///
+/// # Parameters
+///
+/// * `args` - Arguments of the `run` subcommand
+///
+/// # Returns
+///
+/// The process exit code (0 when a stop condition ended the run, 2 on errors).
+fn run_headless_command(args: RunArgs) -> i32 {
+    let mut conditions = simulation::types::StopConditions {
+        time_limit: args.time_limit,
+        measurement_complete: args.until_measurement_complete,
+        blocks_distributed: args.blocks,
+        summary: None,
+    };
+    if let Some(summary) = args.summary {
+        // Relative to the working directory, not to the scene file
+        match std::path::absolute(&summary) {
+            Ok(path) => conditions.summary = Some(path.to_string_lossy().to_string()),
+            Err(err) => {
+                eprintln!("Invalid summary path {}: {}", summary.display(), err);
+                return 2;
+            }
+        }
+    }
+    if let Some(delay_ms) = args.tx_random_delay {
+        simulation::network::set_tx_random_delay_override(delay_ms);
+    }
+
+    Builder::new()
+        .filter_level(LevelFilter::Info)
+        .filter(Some("moonblokz_radio_lib"), LevelFilter::Warn)
+        .init();
+    simulation::event_ring::init_event_ring();
+    simulation::event_ring::install_crash_dump_hook();
+    match simulation::stop_conditions::run_headless(
+        &args.scene,
+        conditions,
+        args.speed,
+        args.event_driven,
+    ) {
+        Ok(reason) => {
+            println!("Run stopped: {}", reason);
+            0
+        }
+        Err(err) => {
+            eprintln!("Run failed: {:#}", err);
+            simulation::event_ring::report_crash_dump(&format!("run failed: {:#}", err));
+            2
+        }
+    }
+}
+
+/// Convert analyzer scenes into simulation scenes (without the GUI).
+///
+/// Each scene is written as `<name>_simulation.json` next to it, or into `--output-dir`.
+///
+/// # Parameters
+///
+/// * `args` - Arguments of the `convert` subcommand
+///
+/// # Returns
+///
+/// The process exit code: 0 if every scene was converted, 1 if any failed, 2 if the
+/// template cannot be read.
+fn run_convert_command(args: ConvertArgs) -> i32 {
+    let template = match args
+        .template
+        .as_deref()
+        .map(common::scene::read_scene_value)
+        .transpose()
+    {
+        Ok(template) => template,
+        Err(err) => {
+            eprintln!("Failed to read the template: {}", err);
+            return 2;
+        }
+    };
+
+    let mut failed = 0;
+    for scene_path in &args.scenes {
+        let path = std::path::Path::new(scene_path);
+        let stem = path
+            .file_stem()
+            .map_or("scene".into(), |s| s.to_string_lossy());
+        let file_name = format!("{}_simulation.json", stem);
+        let output_path = match &args.output_dir {
+            Some(dir) => std::path::Path::new(dir).join(file_name),
+            None => path.with_file_name(file_name),
+        }
+        .to_string_lossy()
+        .to_string();
+        match simulation::scene_conversion::run_conversion(
+            scene_path,
+            template.as_ref(),
+            &output_path,
+        ) {
+            Ok(conversion) => {
+                println!(
+                    "{} -> {}: {} nodes, TX power {:.1} to {:.1} dBm",
+                    scene_path,
+                    output_path,
+                    conversion.nodes,
+                    conversion.tx_power_range.0,
+                    conversion.tx_power_range.1
+                );
+                if !conversion.clamped.is_empty() {
+                    let ids: Vec<String> = conversion.clamped.iter().map(u32::to_string).collect();
+                    println!(
+                        "  warning: the range of nodes {} is out of reach of the TX power limits",
+                        ids.join(", ")
+                    );
+                }
+            }
+            Err(err) => {
+                eprintln!("{}: conversion failed: {:#}", scene_path, err);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 { 1 } else { 0 }
+}
+
+/// Run the node placement planner from command-line arguments (without the GUI).
+///
+/// # Parameters
+///
+/// * `args` - Arguments of the `plan` subcommand
+///
+/// # Returns
+///
+/// The process exit code (0 on success).
+fn run_plan_command(args: PlanArgs) -> i32 {
+    use simulation::coverage_planner::{PlanSettings, run_planner};
+
+    let settings = PlanSettings {
+        strategy: args.strategy.into(),
+        redundancy: args.redundancy,
+        tx_power: args.tx_power,
+        max_nodes: args.max_nodes,
+    };
+    let scene_path = args.template.as_str();
+    // The template is never overwritten by default
+    let output_path = args.output.unwrap_or_else(|| {
+        let path = std::path::Path::new(scene_path);
+        let stem = path
+            .file_stem()
+            .map_or("scene".into(), |s| s.to_string_lossy());
+        path.with_file_name(format!("{}_planned.json", stem))
+            .to_string_lossy()
+            .to_string()
+    });
+
+    match run_planner(scene_path, &settings, &output_path) {
+        Ok(plan) => {
+            println!(
+                "Placed {} nodes (effective distance {:.0} m at {} dBm)",
+                plan.positions.len(),
+                plan.effective_distance,
+                settings.tx_power
+            );
+            println!(
+                "Coverage with redundancy {}: {:.1}% of the world",
+                settings.redundancy,
+                plan.coverage * 100.0
+            );
+            println!(
+                "Neighbors per node: min {}, average {:.1}; connected components: {}",
+                plan.neighbors.0, plan.neighbors.1, plan.components
+            );
+            println!("Planned scene written to {}", output_path);
+            0
+        }
+        Err(err) => {
+            eprintln!("Planning failed: {:#}", err);
+            1
+        }
+    }
+}
+
+/// Run a command-line tool (without the GUI).
+///
+/// # Returns
+///
+/// The process exit code of the tool.
+fn run_tool_command(tool: ToolCommand) -> i32 {
+    match tool {
+        ToolCommand::Calibrate(args) => run_calibrate_command(args),
+        ToolCommand::Diff(args) => run_diff_command(args),
+        ToolCommand::Audit(args) => run_audit_command(args),
+        ToolCommand::Plan(args) => run_plan_command(args),
+        ToolCommand::Convert(args) => run_convert_command(args),
+        ToolCommand::Run(args) => run_headless_command(args),
+    }
+}
+
 fn main() {
+    // The scene schema tools are flags, not subcommands
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("--validate-scene") => std::process::exit(run_validate_scene_command(&args[2..])),
+        Some("--scene-schema") => std::process::exit(run_scene_schema_command(&args[2..])),
+        _ => {}
+    }
+    // Command-line tools (path loss calibration, scene diff, determinism audit, node
+    // placement planning, scene conversion, headless runs) run without the GUI; other
+    // arguments start a GUI session directly (mode, scene and log files)
+    let launch = match ui::launch_options::parse(&args[1..]) {
+        Ok(Launch::Selector) => None,
+        Ok(Launch::Session(options)) => Some(options),
+        Ok(Launch::Tool(tool)) => std::process::exit(run_tool_command(tool)),
+        // Prints the error with the usage (or the help) and exits
+        Err(err) => err.exit(),
+    };
+
     // Initialize log capture buffer before setting up the logger
     simulation::log_capture::init_log_capture();
 
+    // Keep the most recent simulation events and dump them to disk on a panic or
+    // another abnormal exit
+    simulation::event_ring::init_event_ring();
+    simulation::event_ring::install_crash_dump_hook();
+
     // Build the env_logger without initializing it
     let env_logger = Builder::new()
         .filter_level(LevelFilter::Info)
@@ -291,7 +732,7 @@ fn main() {
         ..Default::default()
     };
     // Run the eframe event loop with our AppState managing UI updates
-    let _ = eframe::run_native(
+    let result = eframe::run_native(
         "MoonBlokz Radio Simulator/Analyzer",
         native_options,
         Box::new(move |cc| {
@@ -299,7 +740,30 @@ fn main() {
                 ui_refresh_rx,
                 ui_command_tx,
                 cc.storage,
+                launch,
             )))
         }),
     );
+
+    if let Err(err) = result {
+        error!("GUI event loop failed: {}", err);
+        simulation::event_ring::report_crash_dump(&format!("GUI event loop failed: {}", err));
+    }
+
+    // The window is closed: let the running task flush its outputs before exiting
+    if ui_command_tx.try_send(ui::UICommand::Shutdown).is_err() {
+        log::warn!("Command queue full, cannot request a graceful shutdown");
+    }
+    if !shutdown::wait_for_flush(SHUTDOWN_FLUSH_TIMEOUT) {
+        error!(
+            "Shutdown did not complete within {}s, output files may be incomplete",
+            SHUTDOWN_FLUSH_TIMEOUT.as_secs()
+        );
+        simulation::event_ring::report_crash_dump(&format!(
+            "shutdown did not complete within {}s",
+            SHUTDOWN_FLUSH_TIMEOUT.as_secs()
+        ));
+    }
+    info!("Shutting down");
+    log::logger().flush();
 }
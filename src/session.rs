@@ -0,0 +1,30 @@
+//! # Session Lifecycle
+//!
+//! A session is the task of the selected mode: the simulation `network_task` or the
+//! `analyzer_task`. The mode dispatcher spawns it and waits until it ended before it
+//! accepts the next `UICommand::StartMode`, so the user can return to the mode
+//! selector and start another mode without restarting the process.
+//!
+//! A session task holds a [`SessionGuard`] for its whole lifetime. The guard signals
+//! the end when it is dropped, so every way out of the task (an ended session, a
+//! scene that failed to load, a cancelled load) releases the dispatcher.
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::signal::Signal;
+
+/// Signaled when the running session task returned.
+static SESSION_ENDED: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Marks the running session; dropping it (the task returned) ends the session.
+pub struct SessionGuard;
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        SESSION_ENDED.signal(());
+    }
+}
+
+/// Wait until the running session task returned.
+pub async fn wait_for_session_end() {
+    SESSION_ENDED.wait().await;
+}
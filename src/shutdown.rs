@@ -0,0 +1,66 @@
+//! # Graceful Shutdown
+//!
+//! Closing the window ends `eframe::run_native` on the main thread; returning from
+//! `main` then ends the process, including the Embassy executor thread mid-work. To
+//! keep output files complete, a running simulation registers a pending flush. On
+//! close the main thread sends `UICommand::Shutdown` and waits (bounded) until the
+//! network task has written its outputs and stopped the node processes.
+//!
+//! The Embassy `Executor::run` never returns, so the executor thread cannot be
+//! joined; the flush acknowledgment below takes the place of the join.
+
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// Whether a task has outputs to flush before the process exits.
+static FLUSH_PENDING: Mutex<bool> = Mutex::new(false);
+/// Signaled when the pending flush completed.
+static FLUSHED: Condvar = Condvar::new();
+
+/// Register that the running task must flush its outputs before the process exits.
+pub fn register_flush() {
+    *FLUSH_PENDING.lock().unwrap_or_else(|e| e.into_inner()) = true;
+}
+
+/// Acknowledge that the outputs were flushed.
+pub fn flush_complete() {
+    *FLUSH_PENDING.lock().unwrap_or_else(|e| e.into_inner()) = false;
+    FLUSHED.notify_all();
+}
+
+/// Wait until the pending flush (if any) completed.
+///
+/// # Parameters
+///
+/// * `timeout` - Longest time to wait
+///
+/// # Returns
+///
+/// `true` if nothing is left to flush, `false` if the wait timed out.
+pub fn wait_for_flush(timeout: Duration) -> bool {
+    let pending = FLUSH_PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    let (pending, _) = FLUSHED
+        .wait_timeout_while(pending, timeout, |pending| *pending)
+        .unwrap_or_else(|e| e.into_inner());
+    !*pending
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_returns_once_the_flush_completed() {
+        assert!(wait_for_flush(Duration::ZERO));
+
+        register_flush();
+        assert!(!wait_for_flush(Duration::from_millis(10)));
+
+        let flusher = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(20));
+            flush_complete();
+        });
+        assert!(wait_for_flush(Duration::from_secs(10)));
+        flusher.join().unwrap();
+    }
+}
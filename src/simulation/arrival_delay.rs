@@ -0,0 +1,58 @@
+//! # Arrival Delay
+//!
+//! Offset of a packet's arrival at a receiver relative to the start of the
+//! transmission: the propagation delay (distance over the speed of light) and a
+//! receiver processing / turnaround latency, fixed plus an optional random jitter.
+//! The offset shifts the start of the receiver's airtime window, so receivers no
+//! longer hear a transmission at exactly the same instant across the whole map,
+//! which could mask race conditions in the protocol. Everything is off by default.
+
+use embassy_time::Duration;
+use rand::Rng;
+
+use super::rng::with_simulation_rng;
+use super::types::ArrivalDelayConfig;
+
+/// Speed of light in m/s.
+const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+/// Arrival offset of a packet at a receiver `distance` meters away.
+///
+/// The jitter draws from the simulation generator only when it is configured, so
+/// seeded runs without it are unchanged.
+pub fn arrival_offset(config: &ArrivalDelayConfig, distance: f64) -> Duration {
+    if config.processing_jitter_us == 0 {
+        return fixed_offset(config, distance);
+    }
+    let jitter = with_simulation_rng(|rng| rng.gen_range(0..=config.processing_jitter_us));
+    fixed_offset(config, distance) + Duration::from_micros(jitter)
+}
+
+/// Propagation delay (if enabled) plus the fixed processing latency.
+fn fixed_offset(config: &ArrivalDelayConfig, distance: f64) -> Duration {
+    let propagation_us = if config.propagation {
+        (distance.max(0.0) / SPEED_OF_LIGHT * 1_000_000.0).round() as u64
+    } else {
+        0
+    };
+    Duration::from_micros(propagation_us + config.processing_delay_us)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_adds_propagation_and_processing_delay() {
+        let mut config = ArrivalDelayConfig::default();
+        assert_eq!(arrival_offset(&config, 15_000.0), Duration::from_micros(0));
+
+        config.propagation = true;
+        // 15 km at the speed of light: 50 µs
+        assert_eq!(fixed_offset(&config, 15_000.0), Duration::from_micros(50));
+
+        config.processing_delay_us = 200;
+        assert_eq!(fixed_offset(&config, 15_000.0), Duration::from_micros(250));
+        assert_eq!(fixed_offset(&config, 0.0), Duration::from_micros(200));
+    }
+}
@@ -0,0 +1,345 @@
+//! Gateway backhaul bookkeeping.
+//!
+//! Nodes with the `gateway` role forward every payload message they receive
+//! (AddBlock, AddTransaction) to a backhaul. The tracker counts the unique messages
+//! delivered to the backhaul, credits the gateway that delivered each one first and
+//! measures the origin → backhaul latency of messages originated by measurements and
+//! the stress test. With re-injection enabled, every first delivery is scheduled for
+//! re-transmission at the other gateways after the configured backhaul latency.
+//!
+//! Like the stress test runner, the tracker only keeps the bookkeeping;
+//! `network_task` feeds it receptions and sends the due re-injections.
+//!
+//! Messages are remembered for `MESSAGE_RETENTION` of virtual time after their first
+//! delivery (or origin, if never delivered), so long runs do not accumulate them; a
+//! gateway receiving an expired message again counts it as a new delivery.
+
+use embassy_time::{Duration, Instant};
+use moonblokz_radio_lib::{MessageType, RadioMessage};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use super::types::BackhaulConfig;
+
+/// Identity of a payload message: (message type, sequence or transaction checksum).
+pub type MessageKey = (u8, u32);
+
+/// Virtual time a message is remembered after its first delivery or origin (at
+/// least twice the re-injection latency); flooding completes well within it.
+const MESSAGE_RETENTION: Duration = Duration::from_secs(600);
+
+/// Backhaul counters of a single gateway.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GatewayStatistics {
+    /// Payload messages the gateway received over the radio or originated (each once).
+    pub received: u64,
+    /// Messages this gateway delivered to the backhaul before any other gateway.
+    pub first_deliveries: u64,
+}
+
+/// Snapshot of the backhaul statistics for the UI.
+#[derive(Debug, Clone, Default)]
+pub struct BackhaulStatistics {
+    /// Unique payload messages delivered to the backhaul.
+    pub delivered: u64,
+    /// Messages originated by measurements and the stress test.
+    pub originated: u64,
+    /// Originated messages that reached the backhaul.
+    pub originated_delivered: u64,
+    /// Average origin → backhaul latency of originated messages (None if none delivered).
+    pub average_latency: Option<Duration>,
+    /// Messages re-transmitted by gateways after crossing the backhaul.
+    pub reinjected: u64,
+    /// Counters per gateway node ID.
+    pub gateways: BTreeMap<u32, GatewayStatistics>,
+}
+
+impl BackhaulStatistics {
+    /// Share of originated messages that reached the backhaul in percent
+    /// (None if nothing was originated yet).
+    pub fn delivery_ratio(&self) -> Option<f64> {
+        if self.originated == 0 {
+            return None;
+        }
+        Some(self.originated_delivered as f64 / self.originated as f64 * 100.0)
+    }
+}
+
+/// A delivered message waiting to be re-transmitted by a gateway.
+struct Reinjection {
+    due: Instant,
+    gateway_id: u32,
+    key: MessageKey,
+    message: RadioMessage,
+}
+
+/// Bookkeeping of the gateway backhaul.
+pub struct BackhaulTracker {
+    config: BackhaulConfig,
+    statistics: BackhaulStatistics,
+    /// Gateways holding each delivered message (received by radio or re-injected).
+    holders: HashMap<MessageKey, HashSet<u32>>,
+    /// First deliveries in time order, to expire `holders`.
+    deliveries: VecDeque<(Instant, MessageKey)>,
+    /// Origin time of originated messages not yet delivered.
+    origins: HashMap<MessageKey, Instant>,
+    /// Originations in time order, to expire undelivered `origins`.
+    originations: VecDeque<(Instant, MessageKey)>,
+    latency_total: Duration,
+    /// Scheduled re-injections, ordered by due time (the latency is constant).
+    pending: VecDeque<Reinjection>,
+    /// Whether the statistics changed since the last snapshot.
+    changed: bool,
+}
+
+/// Identify a payload message relevant for the backhaul.
+///
+/// # Returns
+///
+/// `Some(key)` for AddBlock (block sequence) and AddTransaction (transaction
+/// checksum) messages, `None` for control traffic. The fields are read with the
+/// same radio library header accessors as `message_decoder::flood_packet_key`.
+pub fn message_key(message: &RadioMessage) -> Option<MessageKey> {
+    let message_type = message.message_type();
+    let id = if message_type == MessageType::AddBlock as u8 {
+        message.sequence()?
+    } else if message_type == MessageType::AddTransaction as u8 {
+        message.payload_checksum()?
+    } else {
+        return None;
+    };
+    Some((message_type, id))
+}
+
+impl BackhaulTracker {
+    /// Create a tracker for the given gateway nodes.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - Backhaul configuration from the scene
+    /// * `gateway_ids` - Node IDs with the gateway role
+    pub fn new(config: BackhaulConfig, gateway_ids: impl IntoIterator<Item = u32>) -> Self {
+        let gateways = gateway_ids
+            .into_iter()
+            .map(|id| (id, GatewayStatistics::default()))
+            .collect();
+        Self {
+            config,
+            statistics: BackhaulStatistics {
+                gateways,
+                ..Default::default()
+            },
+            holders: HashMap::new(),
+            deliveries: VecDeque::new(),
+            origins: HashMap::new(),
+            originations: VecDeque::new(),
+            latency_total: Duration::from_ticks(0),
+            pending: VecDeque::new(),
+            changed: true,
+        }
+    }
+
+    /// Whether the scene has any gateway.
+    pub fn is_active(&self) -> bool {
+        !self.statistics.gateways.is_empty()
+    }
+
+    /// Record a message originated by a measurement or the stress test.
+    ///
+    /// A message originated by a gateway is delivered to the backhaul immediately.
+    ///
+    /// # Parameters
+    ///
+    /// * `origin_node_id` - Node that sends the message
+    /// * `message` - The originated message
+    /// * `now` - Current virtual time
+    pub fn record_origin(&mut self, origin_node_id: u32, message: &RadioMessage, now: Instant) {
+        let Some(key) = message_key(message) else {
+            return;
+        };
+        if !self.is_active() {
+            return;
+        }
+        self.origins.insert(key, now);
+        self.originations.push_back((now, key));
+        self.statistics.originated += 1;
+        self.changed = true;
+        self.record_reception(origin_node_id, message, now);
+    }
+
+    /// Record a message received by a node (ignored for non-gateway nodes).
+    ///
+    /// The first gateway holding a message delivers it to the backhaul; with
+    /// re-injection enabled it is then scheduled at every other gateway.
+    ///
+    /// # Parameters
+    ///
+    /// * `node_id` - Receiving node
+    /// * `message` - The received message
+    /// * `now` - Current virtual time
+    pub fn record_reception(&mut self, node_id: u32, message: &RadioMessage, now: Instant) {
+        let Some(key) = message_key(message) else {
+            return;
+        };
+        let Some(gateway) = self.statistics.gateways.get_mut(&node_id) else {
+            return;
+        };
+        let holders = self.holders.entry(key).or_default();
+        let first = holders.is_empty();
+        if !holders.insert(node_id) {
+            return;
+        }
+        gateway.received += 1;
+        self.changed = true;
+        if !first {
+            return;
+        }
+
+        gateway.first_deliveries += 1;
+        self.statistics.delivered += 1;
+        self.deliveries.push_back((now, key));
+        if let Some(origin_time) = self.origins.remove(&key) {
+            self.statistics.originated_delivered += 1;
+            self.latency_total += now.saturating_duration_since(origin_time);
+            self.statistics.average_latency =
+                Some(self.latency_total / self.statistics.originated_delivered as u32);
+        }
+
+        if self.config.reinject {
+            let due = now + Duration::from_millis(self.config.reinject_latency);
+            for &gateway_id in self.statistics.gateways.keys() {
+                if gateway_id != node_id {
+                    self.pending.push_back(Reinjection {
+                        due,
+                        gateway_id,
+                        key,
+                        message: message.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Forget the messages delivered (or originated and never delivered) longer
+    /// than the retention ago.
+    fn expire(&mut self, now: Instant) {
+        let retention =
+            MESSAGE_RETENTION.max(Duration::from_millis(self.config.reinject_latency) * 2);
+        while let Some(&(delivered, key)) = self.deliveries.front() {
+            if now.saturating_duration_since(delivered) < retention {
+                break;
+            }
+            self.deliveries.pop_front();
+            self.holders.remove(&key);
+        }
+        while let Some(&(originated, key)) = self.originations.front() {
+            if now.saturating_duration_since(originated) < retention {
+                break;
+            }
+            self.originations.pop_front();
+            // Delivered messages already left `origins`
+            if self.origins.get(&key) == Some(&originated) {
+                self.origins.remove(&key);
+            }
+        }
+    }
+
+    /// Take the re-injections due at `now` and expire old messages.
+    ///
+    /// # Returns
+    ///
+    /// (gateway node ID, message) pairs to send; gateways that received the message
+    /// by radio in the meantime are skipped.
+    pub fn due_reinjections(&mut self, now: Instant) -> Vec<(u32, RadioMessage)> {
+        self.expire(now);
+        let mut due = Vec::new();
+        while self.pending.front().is_some_and(|r| r.due <= now) {
+            let Some(reinjection) = self.pending.pop_front() else {
+                break;
+            };
+            let holders = self.holders.entry(reinjection.key).or_default();
+            if holders.insert(reinjection.gateway_id) {
+                self.statistics.reinjected += 1;
+                self.changed = true;
+                due.push((reinjection.gateway_id, reinjection.message));
+            }
+        }
+        due
+    }
+
+    /// Snapshot of the statistics if they changed since the last call (and gateways exist).
+    pub fn take_statistics(&mut self) -> Option<BackhaulStatistics> {
+        if !self.changed || !self.is_active() {
+            return None;
+        }
+        self.changed = false;
+        Some(self.statistics.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(origin: u32, sequence: u32) -> RadioMessage {
+        RadioMessage::add_block_with(origin, sequence, &[7u8; 100])
+    }
+
+    #[test]
+    fn first_gateway_delivers_and_others_get_reinjections() {
+        let config = BackhaulConfig {
+            reinject: true,
+            reinject_latency: 500,
+        };
+        let mut tracker = BackhaulTracker::new(config, [10, 11, 12]);
+        let start = Instant::from_secs(100);
+        let message = block(1, 4242);
+
+        tracker.record_origin(1, &message, start);
+        tracker.record_reception(10, &message, start + Duration::from_secs(3));
+        tracker.record_reception(11, &message, start + Duration::from_secs(4));
+        tracker.record_reception(10, &message, start + Duration::from_secs(5));
+
+        // Gateway 11 already has the block by radio: only gateway 12 gets it
+        assert!(
+            tracker
+                .due_reinjections(start + Duration::from_millis(3400))
+                .is_empty()
+        );
+        let due = tracker.due_reinjections(start + Duration::from_secs(4));
+        assert_eq!(due.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![12]);
+
+        let statistics = tracker.take_statistics().unwrap();
+        assert_eq!(statistics.delivered, 1);
+        assert_eq!(statistics.delivery_ratio(), Some(100.0));
+        assert_eq!(statistics.average_latency, Some(Duration::from_secs(3)));
+        assert_eq!(statistics.reinjected, 1);
+        assert_eq!(statistics.gateways[&10].first_deliveries, 1);
+        assert_eq!(statistics.gateways[&11].received, 1);
+        assert_eq!(statistics.gateways[&11].first_deliveries, 0);
+        assert!(tracker.take_statistics().is_none());
+    }
+
+    #[test]
+    fn delivered_and_undelivered_messages_expire() {
+        let mut tracker = BackhaulTracker::new(BackhaulConfig::default(), [10]);
+        let start = Instant::from_secs(100);
+        tracker.record_origin(1, &block(1, 1), start);
+        tracker.record_reception(10, &block(2, 2), start);
+        assert_eq!(tracker.holders.len(), 1);
+        assert_eq!(tracker.origins.len(), 1);
+
+        tracker.due_reinjections(start + MESSAGE_RETENTION);
+        assert!(tracker.holders.is_empty());
+        assert!(tracker.deliveries.is_empty());
+        assert!(tracker.origins.is_empty());
+        assert!(tracker.originations.is_empty());
+    }
+
+    #[test]
+    fn tracker_without_gateways_is_inactive() {
+        let mut tracker = BackhaulTracker::new(BackhaulConfig::default(), []);
+        tracker.record_origin(1, &block(1, 1), Instant::from_secs(1));
+        assert!(!tracker.is_active());
+        assert!(tracker.take_statistics().is_none());
+    }
+}
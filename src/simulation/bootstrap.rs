@@ -0,0 +1,124 @@
+//! # Node Bootstrap Schedule
+//!
+//! Start times of the nodes in virtual time, for studying network formation and
+//! late-joiner synchronization. By default all nodes start when the scene is
+//! loaded; the scene's `node_start` can stagger them in scene order (with an
+//! optional random jitter drawn from the simulation generator), and a node's own
+//! `start_time` schedules its join explicitly (e.g. hours after the others).
+//!
+//! Delayed nodes are part of the node map from the start but have no node task and
+//! a switched-off radio until their start time, when the network task spawns them.
+
+use embassy_time::Duration;
+use rand::Rng;
+
+use super::rng::with_simulation_rng;
+use super::types::{Node, NodeStartConfig};
+
+/// Pending node starts, ordered by start time.
+#[derive(Debug, Clone, Default)]
+pub struct BootstrapSchedule {
+    /// (offset after scene load, node ID), latest start first.
+    pending: Vec<(Duration, u32)>,
+}
+
+impl BootstrapSchedule {
+    /// Compute the start offsets of the scene's nodes. Nodes starting at scene load
+    /// are not part of the schedule.
+    ///
+    /// # Parameters
+    ///
+    /// * `nodes` - Nodes of the scene, in scene order
+    /// * `config` - Scene-wide start mode (all at once if missing)
+    pub fn new(nodes: &[Node], config: Option<&NodeStartConfig>) -> Self {
+        let mut pending: Vec<(Duration, u32)> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let offset_secs = match (node.start_time, config) {
+                    (Some(start_time), _) => start_time as f64,
+                    (None, Some(NodeStartConfig::Staggered { interval, jitter })) => {
+                        let jitter = if *jitter > 0.0 {
+                            with_simulation_rng(|rng| rng.gen_range(0.0..=*jitter))
+                        } else {
+                            0.0
+                        };
+                        index as f64 * interval + jitter
+                    }
+                    (None, Some(NodeStartConfig::Simultaneous) | None) => 0.0,
+                };
+                (
+                    Duration::from_millis((offset_secs.max(0.0) * 1000.0) as u64),
+                    node.node_id,
+                )
+            })
+            .filter(|(offset, _)| *offset > Duration::from_ticks(0))
+            .collect();
+        pending.sort_unstable_by(|a, b| b.cmp(a));
+        Self { pending }
+    }
+
+    /// Whether the node starts later than the scene load.
+    pub fn is_delayed(&self, node_id: u32) -> bool {
+        self.pending.iter().any(|(_, id)| *id == node_id)
+    }
+
+    /// Remove and return the nodes whose start time has been reached.
+    ///
+    /// # Parameters
+    ///
+    /// * `elapsed` - Virtual time since the scene was loaded
+    pub fn take_due(&mut self, elapsed: Duration) -> Vec<u32> {
+        let mut due = Vec::new();
+        while let Some((offset, node_id)) = self.pending.last().copied() {
+            if offset > elapsed {
+                break;
+            }
+            self.pending.pop();
+            due.push(node_id);
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(node_id: u32, start_time: Option<u64>) -> Node {
+        serde_json::from_value(serde_json::json!({
+            "node_id": node_id,
+            "position": { "x": 0.0, "y": 0.0 },
+            "radio_strength": 14.0,
+            "start_time": start_time,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_staggered_starts_with_scripted_override() {
+        let nodes = vec![
+            node(1, None),
+            node(2, None),
+            node(3, Some(7200)),
+            node(4, None),
+        ];
+        let config = NodeStartConfig::Staggered {
+            interval: 30.0,
+            jitter: 0.0,
+        };
+        let mut schedule = BootstrapSchedule::new(&nodes, Some(&config));
+
+        assert!(!schedule.is_delayed(1));
+        assert!(schedule.is_delayed(4));
+        assert!(schedule.take_due(Duration::from_secs(29)).is_empty());
+        assert_eq!(schedule.take_due(Duration::from_secs(30)), vec![2]);
+        assert_eq!(schedule.take_due(Duration::from_secs(90)), vec![4]);
+        assert_eq!(schedule.take_due(Duration::from_secs(7200)), vec![3]);
+        assert!(schedule.take_due(Duration::from_secs(10_000)).is_empty());
+
+        let simultaneous = BootstrapSchedule::new(&nodes, None);
+        assert!(!simultaneous.is_delayed(2));
+        assert!(simultaneous.is_delayed(3));
+    }
+}
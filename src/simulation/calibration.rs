@@ -0,0 +1,342 @@
+//! Path loss calibration from field RSSI measurements.
+//!
+//! Fits the log-distance path loss model used by the simulator
+//!
+//! ```text
+//! RSSI = P_tx - PL(d0) - 10 * n * log10(d)      (d0 = 1 m)
+//! ```
+//!
+//! to measured samples with ordinary least squares on `x = 10 * log10(d)` and
+//! `y = RSSI - P_tx`, giving `n = -slope` and `PL(d0) = -intercept`. The standard
+//! deviation of the residuals is reported as an estimate of `shadowing_sigma`.
+//!
+//! Samples are read from a CSV file with a header line in one of two layouts:
+//! - `distance,rssi[,tx_power]` — distance in meters; the TX power column is optional
+//!   when a default TX power is given.
+//! - `sender_id,receiver_id,rssi` — node pair; distance and TX power (the sender's
+//!   `radio_strength`) are taken from the scene.
+//!
+//! The fitted values are written back into the scene's `path_loss_parameters`.
+
+use anyhow::{Context, anyhow, bail};
+use std::collections::HashMap;
+use std::fs;
+
+use super::geometry::distance2;
+use super::types::Scene;
+use crate::common::scene::read_scene_value;
+use crate::common::units::WorldTransform;
+
+/// One calibration sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationSample {
+    /// Distance between transmitter and receiver in meters.
+    pub distance: f64,
+    /// Measured received signal strength in dBm.
+    pub rssi: f64,
+    /// Transmit power in dBm.
+    pub tx_power: f64,
+}
+
+/// Result of fitting the path loss model.
+#[derive(Debug, Clone, Copy)]
+pub struct CalibrationResult {
+    /// Fitted path loss exponent (n).
+    pub path_loss_exponent: f64,
+    /// Fitted path loss at the 1 m reference distance in dB.
+    pub path_loss_at_reference_distance: f64,
+    /// Standard deviation of the residuals in dB (estimate of shadowing sigma).
+    pub residual_sigma: f64,
+    /// Number of samples used for the fit.
+    pub sample_count: usize,
+}
+
+/// Parse calibration samples from CSV text.
+///
+/// # Parameters
+///
+/// * `csv` - CSV content including the header line
+/// * `scene` - Scene used to resolve node pairs (positions, TX power)
+/// * `default_tx_power` - TX power for distance samples without a `tx_power` column
+///
+/// # Returns
+///
+/// The parsed samples, or an error describing the first invalid line.
+pub fn parse_samples(
+    csv: &str,
+    scene: &Scene,
+    default_tx_power: Option<f64>,
+) -> anyhow::Result<Vec<CalibrationSample>> {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines.next().ok_or_else(|| anyhow!("CSV file is empty"))?;
+    let columns: Vec<String> = header.split(',').map(|c| c.trim().to_lowercase()).collect();
+    let column = |name: &str| columns.iter().position(|c| c == name);
+
+    let nodes: HashMap<u32, _> = scene.nodes.iter().map(|n| (n.node_id, n)).collect();
+    let mut samples = Vec::new();
+
+    for (line_index, line) in lines {
+        let line_number = line_index + 1;
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |index: usize| -> anyhow::Result<f64> {
+            fields
+                .get(index)
+                .ok_or_else(|| anyhow!("line {}: missing column {}", line_number, index + 1))?
+                .parse::<f64>()
+                .with_context(|| format!("line {}: invalid number", line_number))
+        };
+        let node_id = |index: usize| -> anyhow::Result<u32> {
+            let value = fields
+                .get(index)
+                .ok_or_else(|| anyhow!("line {}: missing column {}", line_number, index + 1))?;
+            value
+                .parse::<u32>()
+                .with_context(|| format!("line {}: invalid node ID '{}'", line_number, value))
+        };
+
+        let sample = match (
+            column("distance"),
+            column("sender_id"),
+            column("receiver_id"),
+            column("rssi"),
+        ) {
+            (Some(distance_column), _, _, Some(rssi_column)) => {
+                let tx_power = match column("tx_power") {
+                    Some(tx_column) => field(tx_column)?,
+                    None => default_tx_power.ok_or_else(|| {
+                        anyhow!("no tx_power column; provide a default TX power with --tx-power")
+                    })?,
+                };
+                CalibrationSample {
+                    distance: field(distance_column)?,
+                    rssi: field(rssi_column)?,
+                    tx_power,
+                }
+            }
+            (None, Some(sender_column), Some(receiver_column), Some(rssi_column)) => {
+                let sender_id = node_id(sender_column)?;
+                let receiver_id = node_id(receiver_column)?;
+                let sender = nodes.get(&sender_id).ok_or_else(|| {
+                    anyhow!("line {}: unknown sender node {}", line_number, sender_id)
+                })?;
+                let receiver = nodes.get(&receiver_id).ok_or_else(|| {
+                    anyhow!(
+                        "line {}: unknown receiver node {}",
+                        line_number,
+                        receiver_id
+                    )
+                })?;
+                CalibrationSample {
+                    distance: distance2(&sender.position, &receiver.position, scene).sqrt(),
+                    rssi: field(rssi_column)?,
+                    tx_power: sender.radio_strength as f64,
+                }
+            }
+            _ => bail!(
+                "unsupported CSV header '{}', expected 'distance,rssi[,tx_power]' or 'sender_id,receiver_id,rssi'",
+                header
+            ),
+        };
+        samples.push(sample);
+    }
+
+    Ok(samples)
+}
+
+/// Fit the log-distance path loss model to the samples with least squares.
+///
+/// Samples closer than the 1 m reference distance are ignored (the model is flat there).
+///
+/// # Returns
+///
+/// The fitted parameters, or an error if fewer than two distinct distances remain.
+pub fn fit_path_loss(samples: &[CalibrationSample]) -> anyhow::Result<CalibrationResult> {
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .filter(|s| s.distance >= 1.0)
+        .map(|s| (10.0 * s.distance.log10(), s.rssi - s.tx_power))
+        .collect();
+    if points.len() < 2 {
+        bail!("at least two samples with distance >= 1 m are required");
+    }
+
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    if sxx <= f64::EPSILON {
+        bail!("all samples have the same distance; cannot fit the path loss exponent");
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let residual_sum: f64 = points
+        .iter()
+        .map(|(x, y)| (y - (intercept + slope * x)).powi(2))
+        .sum();
+
+    Ok(CalibrationResult {
+        path_loss_exponent: -slope,
+        path_loss_at_reference_distance: -intercept,
+        residual_sigma: (residual_sum / count).sqrt(),
+        sample_count: points.len(),
+    })
+}
+
+/// Run the calibration: read the scene and samples, fit the model and write the
+/// fitted parameters into the scene file at `output_path`.
+///
+/// Only `path_loss_exponent` and `path_loss_at_reference_distance` are changed;
+/// all other scene content is kept.
+///
+/// # Parameters
+///
+/// * `scene_path` - Scene file providing node positions and the parameters to update
+/// * `samples_path` - CSV file with the measured samples
+/// * `default_tx_power` - TX power for distance samples without a `tx_power` column
+/// * `output_path` - Where to write the updated scene (may equal `scene_path`)
+pub fn run_calibration(
+    scene_path: &str,
+    samples_path: &str,
+    default_tx_power: Option<f64>,
+    output_path: &str,
+) -> anyhow::Result<CalibrationResult> {
+    let scene_text = fs::read_to_string(scene_path)
+        .with_context(|| format!("Failed to read file: {}", scene_path))?;
+    let scene_value = read_scene_value(scene_path).map_err(|e| anyhow!(e))?;
+    let mut scene: Scene = serde_json::from_value(scene_value).context("Invalid JSON format")?;
+    scene.transform = WorldTransform::from_scene(
+        (scene.world_top_left.x, scene.world_top_left.y),
+        (scene.world_bottom_right.x, scene.world_bottom_right.y),
+        scene.width,
+        scene.height,
+        scene.meters_per_unit,
+    )
+    .map_err(|e| anyhow!(e))?;
+
+    let csv = fs::read_to_string(samples_path)
+        .with_context(|| format!("Failed to read file: {}", samples_path))?;
+    let samples = parse_samples(&csv, &scene, default_tx_power)?;
+    let result = fit_path_loss(&samples)?;
+    if result.path_loss_exponent <= 0.0 {
+        bail!(
+            "fitted path_loss_exponent {:.3} is not positive; check the samples",
+            result.path_loss_exponent
+        );
+    }
+
+    // Written into the scene file itself: when the parameters come from an included
+    // file, the two fitted values are added as an override next to the includes.
+    // serde_json's `preserve_order` feature keeps the file's key order.
+    let mut scene_json: serde_json::Value =
+        serde_json::from_str(&scene_text).context("Invalid JSON format")?;
+    let parameters = scene_json
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("scene is not a JSON object"))?
+        .entry("path_loss_parameters")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("scene has no path_loss_parameters object"))?;
+    parameters.insert(
+        "path_loss_exponent".to_string(),
+        serde_json::json!(round_to(result.path_loss_exponent, 3)),
+    );
+    parameters.insert(
+        "path_loss_at_reference_distance".to_string(),
+        serde_json::json!(round_to(result.path_loss_at_reference_distance, 2)),
+    );
+    let output = serde_json::to_string_pretty(&scene_json)?;
+    fs::write(output_path, output)
+        .with_context(|| format!("Failed to write file: {}", output_path))?;
+
+    Ok(result)
+}
+
+/// Round to the given number of decimals (keeps the scene file readable).
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(distance: f64, rssi: f64) -> CalibrationSample {
+        CalibrationSample {
+            distance,
+            rssi,
+            tx_power: 14.0,
+        }
+    }
+
+    #[test]
+    fn calibration_recovers_exact_model() {
+        // n = 3.0, PL0 = 40 dB, P_tx = 14 dBm
+        let model = |d: f64| 14.0 - 40.0 - 30.0 * d.log10();
+        let samples: Vec<_> = [10.0, 50.0, 200.0, 1000.0]
+            .iter()
+            .map(|d| sample(*d, model(*d)))
+            .collect();
+
+        let result = fit_path_loss(&samples).unwrap();
+        assert!((result.path_loss_exponent - 3.0).abs() < 1e-9);
+        assert!((result.path_loss_at_reference_distance - 40.0).abs() < 1e-9);
+        assert!(result.residual_sigma < 1e-9);
+        assert_eq!(result.sample_count, 4);
+    }
+
+    #[test]
+    fn calibration_rejects_degenerate_samples() {
+        assert!(fit_path_loss(&[sample(100.0, -80.0)]).is_err());
+        assert!(fit_path_loss(&[sample(100.0, -80.0), sample(100.0, -82.0)]).is_err());
+        // Samples below the reference distance are ignored
+        assert!(fit_path_loss(&[sample(0.5, -20.0), sample(100.0, -80.0)]).is_err());
+    }
+
+    #[test]
+    fn calibration_rejects_invalid_node_ids() {
+        let scene: Scene = serde_json::from_value(serde_json::json!({
+            "path_loss_parameters": {
+                "path_loss_exponent": 2.8, "shadowing_sigma": 2.0,
+                "path_loss_at_reference_distance": 38.0, "noise_floor": -117.0
+            },
+            "lora_parameters": {
+                "bandwidth": 250000, "spreading_factor": 7, "coding_rate": 1,
+                "preamble_symbols": 8.0, "crc_enabled": true, "low_data_rate_optimization": false
+            },
+            "radio_module_config": {
+                "delay_between_tx_packets": 200, "delay_between_tx_messages": 5,
+                "echo_request_minimal_interval": 3, "echo_messages_target_interval": 100,
+                "echo_gathering_timeout": 1, "relay_position_delay": 1,
+                "scoring_matrix": [173, 242, 49, 123, 42],
+                "retry_interval_for_missing_packets": 60, "tx_maximum_random_delay": 200
+            },
+            "nodes": [
+                { "node_id": 1, "position": { "x": 0, "y": 0 }, "radio_strength": 14.0 },
+                { "node_id": 2, "position": { "x": 3, "y": 4 }, "radio_strength": 14.0 }
+            ],
+            "obstacles": [],
+            "world_top_left": { "x": 0, "y": 0 },
+            "world_bottom_right": { "x": 10, "y": 10 }
+        }))
+        .unwrap();
+
+        let samples = parse_samples("sender_id,receiver_id,rssi\n1,2,-60\n", &scene, None).unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].tx_power, 14.0);
+
+        for row in ["1.9,2,-60", "-1,2,-60", "1,x,-60"] {
+            let csv = format!("sender_id,receiver_id,rssi\n{}\n", row);
+            let error = parse_samples(&csv, &scene, None).unwrap_err().to_string();
+            assert!(error.starts_with("line 2: invalid node ID"), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! # Checkpoint Exports
+//!
+//! Rolling metrics snapshots of long unattended runs, so a crash or power loss
+//! after hours of virtual time does not lose all results. With `checkpoints` in the
+//! scene, the network task writes a snapshot every `interval_minutes` virtual
+//! minutes to `<scene>_checkpoints/checkpoint_<virtual seconds>s.json` next to the
+//! scene file (or the configured directory). A snapshot has the format of the run
+//! summary (see `stop_conditions::run_summary`) with the reason `checkpoint`.
+//!
+//! Files are written to a temporary name and renamed, so an interrupted write never
+//! leaves a truncated checkpoint behind. Only the newest `retention` checkpoints of
+//! the run are kept; older ones are deleted as new ones are written.
+
+use anyhow::{Context, Result};
+use embassy_time::{Duration, Instant};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::types::CheckpointConfig;
+
+/// Writes the periodic checkpoints of a run.
+#[derive(Debug)]
+pub struct CheckpointWriter {
+    directory: PathBuf,
+    interval: Duration,
+    retention: usize,
+    scene_start: Instant,
+    next_due: Instant,
+    /// Checkpoints written in this run, oldest first.
+    written: VecDeque<PathBuf>,
+}
+
+impl CheckpointWriter {
+    /// Create the writer of a scene; the first checkpoint is due one interval after
+    /// the scene start.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - The scene's checkpoint settings
+    /// * `scene_path` - Scene file (relative directories are resolved from it)
+    /// * `scene_start` - Virtual time when the scene was loaded
+    pub fn new(config: &CheckpointConfig, scene_path: &str, scene_start: Instant) -> Self {
+        let interval = Duration::from_secs(config.interval_minutes.max(1) * 60);
+        Self {
+            directory: checkpoint_directory(scene_path, config),
+            interval,
+            retention: config.retention,
+            scene_start,
+            next_due: scene_start + interval,
+            written: VecDeque::new(),
+        }
+    }
+
+    /// Whether the next checkpoint is due at `now`.
+    pub fn due(&self, now: Instant) -> bool {
+        now >= self.next_due
+    }
+
+    /// Write a checkpoint, schedule the next one and delete the checkpoints beyond
+    /// the retention.
+    ///
+    /// # Parameters
+    ///
+    /// * `snapshot` - Run summary at the current virtual time
+    /// * `now` - Current virtual time
+    ///
+    /// # Returns
+    ///
+    /// The path of the written checkpoint.
+    pub fn write(&mut self, snapshot: &Value, now: Instant) -> Result<PathBuf> {
+        while self.next_due <= now {
+            self.next_due += self.interval;
+        }
+        let elapsed_secs = now.saturating_duration_since(self.scene_start).as_secs();
+        fs::create_dir_all(&self.directory)
+            .with_context(|| format!("cannot create {}", self.directory.display()))?;
+        let path = self
+            .directory
+            .join(format!("checkpoint_{:08}s.json", elapsed_secs));
+        let temporary = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(snapshot)?;
+        fs::write(&temporary, json)
+            .and_then(|()| fs::rename(&temporary, &path))
+            .with_context(|| format!("cannot write {}", path.display()))?;
+
+        if self.written.back() != Some(&path) {
+            self.written.push_back(path.clone());
+        }
+        for expired in expired_checkpoints(&mut self.written, self.retention) {
+            if let Err(err) = fs::remove_file(&expired) {
+                log::warn!(
+                    "Cannot delete old checkpoint {}: {}",
+                    expired.display(),
+                    err
+                );
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Remove the checkpoints beyond the newest `retention` from `written` (0 keeps all).
+///
+/// # Returns
+///
+/// The removed checkpoints, oldest first.
+fn expired_checkpoints(written: &mut VecDeque<PathBuf>, retention: usize) -> Vec<PathBuf> {
+    if retention == 0 || written.len() <= retention {
+        return Vec::new();
+    }
+    let excess = written.len() - retention;
+    written.drain(..excess).collect()
+}
+
+/// Directory of the checkpoints: the configured directory relative to the scene
+/// file, or `<scene>_checkpoints` next to it.
+fn checkpoint_directory(scene_path: &str, config: &CheckpointConfig) -> PathBuf {
+    let scene_path = Path::new(scene_path);
+    let directory = scene_path.parent().unwrap_or(Path::new(""));
+    match &config.directory {
+        Some(configured) => directory.join(configured),
+        None => {
+            let stem = scene_path
+                .file_stem()
+                .map_or("scene".into(), |stem| stem.to_string_lossy());
+            directory.join(format!("{}_checkpoints", stem))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_are_scheduled_and_pruned() {
+        let config = CheckpointConfig {
+            interval_minutes: 10,
+            retention: 2,
+            directory: None,
+        };
+        assert_eq!(
+            checkpoint_directory("scenes/city.json", &config),
+            Path::new("scenes/city_checkpoints")
+        );
+
+        let start = Instant::from_secs(100);
+        let writer = CheckpointWriter::new(&config, "scenes/city.json", start);
+        assert!(!writer.due(start + Duration::from_secs(599)));
+        assert!(writer.due(start + Duration::from_secs(600)));
+
+        let mut written: VecDeque<PathBuf> =
+            ["a", "b", "c"].into_iter().map(PathBuf::from).collect();
+        assert_eq!(
+            expired_checkpoints(&mut written, 2),
+            vec![PathBuf::from("a")]
+        );
+        assert_eq!(written.len(), 2);
+        assert!(expired_checkpoints(&mut written, 0).is_empty());
+    }
+}
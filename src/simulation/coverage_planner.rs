@@ -0,0 +1,373 @@
+//! Node placement planning for a target coverage and redundancy.
+//!
+//! Given a template scene (world rectangle, obstacles, LoRa and path loss parameters)
+//! the planner suggests node positions and writes them as the scene's `nodes`:
+//!
+//! - **Grid**: a regular grid whose spacing follows the effective radio range
+//!   (`calculate_effective_distance`), shrunk by `sqrt(redundancy)`; grid points
+//!   inside obstacles are skipped.
+//! - **Greedy**: the world is sampled with demand points; candidate positions are
+//!   added one by one, each time the one covering the most demand points that are
+//!   still covered by fewer than `redundancy` nodes. After the first node only
+//!   candidates linked to an already placed node are considered, so the result
+//!   stays connected where the obstacles allow it.
+//!
+//! A point is covered by a node when it is within the effective distance and the
+//! line of sight is not blocked, the same rule the simulation uses for reachability.
+//! The placement is summarized by the coverage ratio, the neighbor counts and the
+//! number of connected components.
+
+use anyhow::{Context, anyhow, bail};
+use std::fs;
+
+use super::geometry::is_intersect;
+use super::signal_calculations::calculate_effective_distance;
+use super::types::{Obstacle, Point, Scene};
+use crate::common::scene::read_scene_value;
+use crate::common::units::WorldTransform;
+
+/// Maximum number of demand points sampled from the world (spacing grows beyond it).
+const MAX_DEMAND_POINTS: usize = 10_000;
+
+/// Node placement strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementStrategy {
+    Grid,
+    Greedy,
+}
+
+/// Planner settings.
+#[derive(Debug, Clone, Copy)]
+pub struct PlanSettings {
+    pub strategy: PlacementStrategy,
+    /// Number of nodes each demand point must be covered by.
+    pub redundancy: usize,
+    /// TX power of the placed nodes (their `radio_strength`) in dBm.
+    pub tx_power: f64,
+    /// Upper bound on the number of placed nodes.
+    pub max_nodes: usize,
+}
+
+/// World area to place the nodes in.
+#[derive(Clone, Copy)]
+pub struct PlanArea<'a> {
+    pub transform: &'a WorldTransform,
+    pub obstacles: &'a [Obstacle],
+}
+
+/// Suggested placement and its quality.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// Node positions in world units.
+    pub positions: Vec<(f64, f64)>,
+    /// Effective radio range used for the placement (meters).
+    pub effective_distance: f64,
+    /// Share of demand points covered by at least `redundancy` nodes (0..=1).
+    pub coverage: f64,
+    /// Smallest and average number of neighbors per node.
+    pub neighbors: (usize, f64),
+    /// Number of connected components of the node graph.
+    pub components: usize,
+}
+
+fn point((x, y): (f64, f64)) -> Point {
+    Point { x, y }
+}
+
+/// Whether a node at `a` reaches `b` (within range and line of sight).
+fn reaches(area: PlanArea, range2: f64, a: (f64, f64), b: (f64, f64)) -> bool {
+    area.transform.distance2(a, b) < range2 && !is_intersect(&point(a), &point(b), area.obstacles)
+}
+
+/// Regular grid over the world with the given spacing in meters, skipping points
+/// inside obstacles. Points are placed at cell centers.
+fn grid_points(area: PlanArea, spacing_meters: f64) -> Vec<(f64, f64)> {
+    let transform = area.transform;
+    let (step_x, step_y) = transform.offset_to_units(spacing_meters, spacing_meters);
+    let columns = (transform.width_meters() / spacing_meters).ceil().max(1.0) as usize;
+    let rows = (transform.height_meters() / spacing_meters).ceil().max(1.0) as usize;
+    let (left, top) = transform.top_left();
+    let (right, bottom) = transform.bottom_right();
+    let (sign_x, sign_y) = ((right - left).signum(), (bottom - top).signum());
+
+    let mut points = Vec::with_capacity(columns * rows);
+    for row in 0..rows {
+        for column in 0..columns {
+            let p = (
+                left + sign_x * step_x * (column as f64 + 0.5),
+                top + sign_y * step_y * (row as f64 + 0.5),
+            );
+            if !is_intersect(&point(p), &point(p), area.obstacles) {
+                points.push(p);
+            }
+        }
+    }
+    points
+}
+
+/// Spacing (meters) of the demand point grid: a quarter of the range, coarser
+/// if the world would need more than `MAX_DEMAND_POINTS` points.
+fn demand_spacing(transform: &WorldTransform, range: f64) -> f64 {
+    let area = transform.width_meters() * transform.height_meters();
+    (range / 4.0).max((area / MAX_DEMAND_POINTS as f64).sqrt())
+}
+
+/// Greedy placement on the demand point grid (candidates are the demand points).
+fn place_greedy(area: PlanArea, settings: &PlanSettings, range: f64) -> Vec<(f64, f64)> {
+    let range2 = range * range;
+    let demand = grid_points(area, demand_spacing(area.transform, range));
+    // Demand points reached from each candidate; reachability is symmetric, so this
+    // is also the list of candidates linked to a placed node
+    let reach: Vec<Vec<usize>> = demand
+        .iter()
+        .map(|c| {
+            (0..demand.len())
+                .filter(|&d| reaches(area, range2, *c, demand[d]))
+                .collect()
+        })
+        .collect();
+    let mut coverage = vec![0usize; demand.len()];
+    let mut used = vec![false; demand.len()];
+    let mut linked = vec![false; demand.len()];
+    let mut placed: Vec<(f64, f64)> = Vec::new();
+
+    while placed.len() < settings.max_nodes {
+        let mut best: Option<(usize, usize)> = None;
+        for candidate in 0..demand.len() {
+            if used[candidate] || (!placed.is_empty() && !linked[candidate]) {
+                continue;
+            }
+            let gain = reach[candidate]
+                .iter()
+                .filter(|&&d| coverage[d] < settings.redundancy)
+                .count();
+            if gain > 0 && best.is_none_or(|(_, best_gain)| gain > best_gain) {
+                best = Some((candidate, gain));
+            }
+        }
+        let Some((candidate, _)) = best else {
+            break;
+        };
+        used[candidate] = true;
+        for &d in &reach[candidate] {
+            coverage[d] += 1;
+            linked[d] = true;
+        }
+        placed.push(demand[candidate]);
+    }
+    placed
+}
+
+/// Coverage ratio, neighbor counts and component count of a placement.
+fn evaluate(
+    area: PlanArea,
+    positions: &[(f64, f64)],
+    range: f64,
+    redundancy: usize,
+) -> (f64, (usize, f64), usize) {
+    let range2 = range * range;
+    let demand = grid_points(area, demand_spacing(area.transform, range));
+    let covered = demand
+        .iter()
+        .filter(|d| {
+            positions
+                .iter()
+                .filter(|p| reaches(area, range2, **p, **d))
+                .count()
+                >= redundancy
+        })
+        .count();
+    let coverage = if demand.is_empty() {
+        0.0
+    } else {
+        covered as f64 / demand.len() as f64
+    };
+
+    let neighbors: Vec<Vec<usize>> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            positions
+                .iter()
+                .enumerate()
+                .filter(|(j, b)| i != *j && reaches(area, range2, *a, **b))
+                .map(|(j, _)| j)
+                .collect()
+        })
+        .collect();
+    let min_neighbors = neighbors.iter().map(Vec::len).min().unwrap_or(0);
+    let average_neighbors = if positions.is_empty() {
+        0.0
+    } else {
+        neighbors.iter().map(Vec::len).sum::<usize>() as f64 / positions.len() as f64
+    };
+
+    let mut component = vec![usize::MAX; positions.len()];
+    let mut components = 0;
+    for start in 0..positions.len() {
+        if component[start] != usize::MAX {
+            continue;
+        }
+        let mut stack = vec![start];
+        component[start] = components;
+        while let Some(node) = stack.pop() {
+            for &next in &neighbors[node] {
+                if component[next] == usize::MAX {
+                    component[next] = components;
+                    stack.push(next);
+                }
+            }
+        }
+        components += 1;
+    }
+
+    (coverage, (min_neighbors, average_neighbors), components)
+}
+
+/// Compute a node placement for the scene.
+///
+/// # Parameters
+///
+/// * `area` - World bounds and obstacles
+/// * `range` - Effective radio range of the placed nodes (meters)
+/// * `settings` - Strategy, redundancy and node limit
+///
+/// # Returns
+///
+/// The suggested placement.
+pub fn plan_nodes(area: PlanArea, range: f64, settings: &PlanSettings) -> Plan {
+    let mut positions = match settings.strategy {
+        // Cell diagonal equal to the range, denser for redundancy
+        PlacementStrategy::Grid => grid_points(
+            area,
+            range / std::f64::consts::SQRT_2 / (settings.redundancy as f64).sqrt(),
+        ),
+        PlacementStrategy::Greedy => place_greedy(area, settings, range),
+    };
+    positions.truncate(settings.max_nodes);
+
+    let (coverage, neighbors, components) = evaluate(area, &positions, range, settings.redundancy);
+    Plan {
+        positions,
+        effective_distance: range,
+        coverage,
+        neighbors,
+        components,
+    }
+}
+
+/// Plan the nodes of a template scene and write the scene with the placed nodes.
+///
+/// The template's own nodes are replaced; all other fields (including `includes`)
+/// are kept as written.
+///
+/// # Parameters
+///
+/// * `scene_path` - Template scene file
+/// * `settings` - Planner settings
+/// * `output_path` - Where to write the planned scene
+pub fn run_planner(
+    scene_path: &str,
+    settings: &PlanSettings,
+    output_path: &str,
+) -> anyhow::Result<Plan> {
+    let mut scene_value = read_scene_value(scene_path).map_err(|e| anyhow!(e))?;
+    if let Some(object) = scene_value.as_object_mut() {
+        object
+            .entry("nodes")
+            .or_insert_with(|| serde_json::json!([]));
+    }
+    let mut scene: Scene = serde_json::from_value(scene_value).context("Invalid JSON format")?;
+    scene.transform = WorldTransform::from_scene(
+        (scene.world_top_left.x, scene.world_top_left.y),
+        (scene.world_bottom_right.x, scene.world_bottom_right.y),
+        scene.width,
+        scene.height,
+        scene.meters_per_unit,
+    )
+    .map_err(|e| anyhow!(e))?;
+
+    if settings.redundancy == 0 {
+        bail!("redundancy must be at least 1");
+    }
+    let range = calculate_effective_distance(
+        settings.tx_power as f32,
+        &scene.lora_parameters,
+        scene.propagation_model.model(),
+        &scene.path_loss_parameters,
+    ) as f64;
+    if range <= 1.0 {
+        bail!(
+            "effective distance at {} dBm is {:.1} m; raise the TX power",
+            settings.tx_power,
+            range
+        );
+    }
+    let area = PlanArea {
+        transform: &scene.transform,
+        obstacles: &scene.obstacles,
+    };
+    let plan = plan_nodes(area, range, settings);
+    if plan.positions.is_empty() {
+        bail!("no node position found outside the obstacles");
+    }
+
+    let scene_text = fs::read_to_string(scene_path)
+        .with_context(|| format!("Failed to read file: {}", scene_path))?;
+    let mut scene_json: serde_json::Value =
+        serde_json::from_str(&scene_text).context("Invalid JSON format")?;
+    let nodes: Vec<serde_json::Value> = plan
+        .positions
+        .iter()
+        .enumerate()
+        .map(|(index, (x, y))| {
+            serde_json::json!({
+                "node_id": index + 1,
+                "position": { "x": (x * 100.0).round() / 100.0, "y": (y * 100.0).round() / 100.0 },
+                "radio_strength": settings.tx_power,
+            })
+        })
+        .collect();
+    scene_json
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("scene is not a JSON object"))?
+        .insert("nodes".to_string(), serde_json::Value::Array(nodes));
+    let output = serde_json::to_string_pretty(&scene_json)?;
+    fs::write(output_path, output)
+        .with_context(|| format!("Failed to write file: {}", output_path))?;
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placements_cover_the_world_connected() {
+        let transform = WorldTransform::from_scene(
+            (0.0, 0.0),
+            (1000.0, 1000.0),
+            Some(1000.0),
+            Some(1000.0),
+            None,
+        )
+        .unwrap();
+        let area = PlanArea {
+            transform: &transform,
+            obstacles: &[],
+        };
+        for strategy in [PlacementStrategy::Grid, PlacementStrategy::Greedy] {
+            let settings = PlanSettings {
+                strategy,
+                redundancy: 1,
+                tx_power: 14.0,
+                max_nodes: 500,
+            };
+            let plan = plan_nodes(area, 300.0, &settings);
+            assert!(plan.positions.len() > 1);
+            assert!(plan.coverage > 0.99, "{:?}: {}", strategy, plan.coverage);
+            assert_eq!(plan.components, 1, "{:?}", strategy);
+        }
+    }
+}
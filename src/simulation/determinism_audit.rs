@@ -0,0 +1,296 @@
+//! Simulation determinism audit.
+//!
+//! The audit runs the same scene twice with the same random seed and compares the
+//! resulting event streams (the events the event recorder observes: transmissions,
+//! receptions, collisions, measurement starts, ...). Both runs are sessions of the
+//! network task in this process, one after the other, with in-process node tasks and
+//! the simulation generator seeded identically; each records every event for a fixed
+//! virtual duration. Both runs use the event-driven clock (virtual time jumps to the
+//! next deadline whenever the executor is idle, see `time_driver::set_event_driven`),
+//! so host scheduling cannot shift event timing between them. Events are compared by
+//! content and order; a difference of their virtual timestamps (relative to the run
+//! start) is reported as timing drift.
+//!
+//! The first divergence is reported with its event index, the node involved and a
+//! probable cause, so non-determinism (e.g. from channel or map iteration ordering)
+//! no longer sneaks in unnoticed.
+
+use anyhow::{Context, Result, bail};
+use embassy_executor::Spawner;
+use embassy_time::{Duration, Instant, Timer};
+use std::sync::mpsc;
+
+use super::event_ring::{start_event_recording, take_recorded_events};
+use super::rng::set_seed_override;
+use crate::executor::Executor;
+use crate::ui::{UICommand, UIRefreshState};
+use crate::{UICommandQueue, UIRefreshQueue, UIRefreshQueueSender, session, time_driver};
+
+/// First difference between the event streams of two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Index of the first differing event (0-based).
+    pub index: usize,
+    /// Node of the differing event (first run, or second run if the first ended).
+    pub node_id: Option<u32>,
+    /// Probable cause of the difference.
+    pub cause: String,
+    /// Event of the first run (None if the run ended earlier).
+    pub first: Option<String>,
+    /// Event of the second run (None if the run ended earlier).
+    pub second: Option<String>,
+}
+
+/// Result of a determinism audit.
+pub struct AuditReport {
+    /// Number of events recorded by each run.
+    pub event_counts: (usize, usize),
+    /// First divergence (None if the streams are identical).
+    pub divergence: Option<Divergence>,
+    /// Largest virtual timestamp difference of matching events (seconds).
+    pub max_timing_drift: f64,
+}
+
+/// Split a recorded event into its virtual timestamp (seconds) and text.
+fn split_event(line: &str) -> (Option<f64>, &str) {
+    let Some(rest) = line.strip_prefix('[') else {
+        return (None, line);
+    };
+    match rest.split_once("s] ") {
+        Some((time, text)) => (time.trim().parse().ok(), text),
+        None => (None, line),
+    }
+}
+
+/// Node of an event text (the number following `node`, `NODE` or `gateway`).
+fn event_node(text: &str) -> Option<u32> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words
+        .windows(2)
+        .find(|pair| matches!(pair[0], "node" | "NODE" | "gateway"))
+        .and_then(|pair| pair[1].parse().ok())
+}
+
+/// Compare two event streams by content and order.
+///
+/// # Parameters
+///
+/// * `first` - Events of the first run (with timestamps)
+/// * `second` - Events of the second run (with timestamps)
+///
+/// # Returns
+///
+/// The first divergence, or `None` if both runs recorded the same events.
+pub fn find_divergence(first: &[String], second: &[String]) -> Option<Divergence> {
+    let length = first.len().max(second.len());
+    (0..length).find_map(|index| {
+        let a = first.get(index).map(|line| split_event(line).1);
+        let b = second.get(index).map(|line| split_event(line).1);
+        if a == b {
+            return None;
+        }
+        let cause = match (a, b) {
+            (Some(_), None) => format!("second run ended after {} events", second.len()),
+            (None, Some(_)) => format!("first run ended after {} events", first.len()),
+            (Some(a), Some(b)) => {
+                let kind = |text: &str| text.split_whitespace().next().map(str::to_string);
+                if kind(a) == kind(b) && event_node(a) == event_node(b) {
+                    "same event with different details (random draws or interference differ)"
+                        .to_string()
+                } else {
+                    "events in a different order (task scheduling or channel ordering)".to_string()
+                }
+            }
+            (None, None) => unreachable!(),
+        };
+        Some(Divergence {
+            index,
+            node_id: a.and_then(event_node).or_else(|| b.and_then(event_node)),
+            cause,
+            first: first.get(index).cloned(),
+            second: second.get(index).cloned(),
+        })
+    })
+}
+
+/// Largest timestamp difference of the events before `until`.
+fn max_timing_drift(first: &[String], second: &[String], until: usize) -> f64 {
+    first
+        .iter()
+        .zip(second)
+        .take(until)
+        .filter_map(|(a, b)| Some((split_event(a).0? - split_event(b).0?).abs()))
+        .fold(0.0, f64::max)
+}
+
+/// Rebase the timestamps of a run's events on the run start, so the runs of one
+/// process (which share the virtual clock) can be compared.
+fn rebase_events(events: Vec<String>, start: f64) -> Vec<String> {
+    events
+        .into_iter()
+        .map(|line| match split_event(&line) {
+            (Some(time), text) => format!("[{:>12.3}s] {}", time - start, text),
+            (None, _) => line,
+        })
+        .collect()
+}
+
+/// Audit task: runs the network task twice with the same seed, one session after
+/// the other, and publishes the events each run recorded within `duration`.
+///
+/// # Parameters
+///
+/// * `spawner` - Embassy spawner for the network task
+/// * `ui_refresh_tx` - UI updates of the runs (drained by `run_audit`)
+/// * `ui_command_channel` - Commands of the runs (ends each session)
+/// * `scene_path` - Scene file to run
+/// * `duration` - Virtual seconds per run
+/// * `seed` - Random seed of both runs
+/// * `runs_tx` - Receives the events of each run (dropped if a run cannot start)
+#[embassy_executor::task]
+async fn audit_task(
+    spawner: Spawner,
+    ui_refresh_tx: UIRefreshQueueSender,
+    ui_command_channel: &'static UICommandQueue,
+    scene_path: String,
+    duration: u64,
+    seed: u64,
+    runs_tx: mpsc::Sender<Vec<String>>,
+) {
+    for run in 1..=2 {
+        // Ending the previous run froze the virtual clock
+        time_driver::set_simulation_paused(false);
+        set_seed_override(seed);
+        // Drop the events of the previous run's shutdown
+        take_recorded_events();
+        let start = Instant::now().as_millis() as f64 / 1000.0;
+        if let Err(err) = spawner.spawn(super::network_task(
+            spawner,
+            ui_refresh_tx,
+            ui_command_channel.receiver(),
+            Some(scene_path.clone()),
+        )) {
+            log::error!("Cannot start audit run {}: {:?}", run, err);
+            return;
+        }
+        Timer::after(Duration::from_secs(duration)).await;
+        let events = rebase_events(take_recorded_events(), start);
+
+        ui_command_channel.send(UICommand::EndSession).await;
+        session::wait_for_session_end().await;
+        if runs_tx.send(events).is_err() {
+            return;
+        }
+    }
+}
+
+/// Run the audit: two runs of the scene with the same seed, then compare.
+///
+/// Both runs are sessions of the network task on one headless executor thread
+/// (in-process node tasks), started one after the other like a mode switch in the
+/// GUI, on the event-driven clock. UI updates are discarded.
+///
+/// # Parameters
+///
+/// * `scene_path` - Scene file to run
+/// * `duration` - Virtual seconds per run
+/// * `seed` - Random seed of both runs
+///
+/// # Returns
+///
+/// The audit report, or an error if a run failed.
+pub fn run_audit(scene_path: &str, duration: u64, seed: u64) -> Result<AuditReport> {
+    start_event_recording();
+    // Timing must not depend on the host: both runs jump from event to event
+    time_driver::set_event_driven(true);
+
+    // INTENTIONAL LEAK: 'static channels and executor, as in the GUI application
+    let ui_refresh_channel: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+    let ui_command_channel: &'static UICommandQueue = Box::leak(Box::new(UICommandQueue::new()));
+    let ui_refresh_tx = ui_refresh_channel.sender();
+    let (runs_tx, runs_rx) = mpsc::channel();
+    let scene_path = scene_path.to_string();
+    std::thread::Builder::new()
+        .stack_size(192 * 1024 * 1024)
+        .name("embassy-executor".to_string())
+        .spawn(move || {
+            let executor: &'static mut Executor = Box::leak(Box::new(Executor::new()));
+            executor.run(|spawner| {
+                let _ = spawner.spawn(audit_task(
+                    spawner,
+                    ui_refresh_tx,
+                    ui_command_channel,
+                    scene_path,
+                    duration,
+                    seed,
+                    runs_tx,
+                ));
+            });
+        })
+        .context("cannot start the executor")?;
+
+    let ui_refresh_rx = ui_refresh_channel.receiver();
+    let mut runs: Vec<Vec<String>> = Vec::new();
+    while runs.len() < 2 {
+        // Drain UI updates so the queue does not grow; stop on alerts
+        while let Ok(update) = ui_refresh_rx.try_receive() {
+            if let UIRefreshState::Alert(message) = update {
+                bail!("run {} failed: {}", runs.len() + 1, message);
+            }
+        }
+        match runs_rx.recv_timeout(std::time::Duration::from_millis(10)) {
+            Ok(events) => runs.push(events),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                bail!("run {} could not be started", runs.len() + 1)
+            }
+        }
+    }
+
+    let divergence = find_divergence(&runs[0], &runs[1]);
+    let matching = divergence.as_ref().map_or(runs[0].len(), |d| d.index);
+    Ok(AuditReport {
+        event_counts: (runs[0].len(), runs[1].len()),
+        max_timing_drift: max_timing_drift(&runs[0], &runs[1], matching),
+        divergence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(events: &[&str]) -> Vec<String> {
+        events.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_divergence_ignores_timestamps_and_names_node() {
+        let first = stream(&[
+            "[       1.000s] TX node 1 type 6 packet 1/1 (40 bytes)",
+            "[       1.200s] RX node 2 from 1 type 6 (rssi -80.0 dBm, sinr 20.0 dB, lq 30)",
+            "[       1.200s] RX node 3 from 1 type 6 (rssi -90.0 dBm, sinr 10.0 dB, lq 20)",
+        ]);
+        let second = stream(&[
+            "[       1.004s] TX node 1 type 6 packet 1/1 (40 bytes)",
+            "[       1.203s] RX node 3 from 1 type 6 (rssi -90.0 dBm, sinr 10.0 dB, lq 20)",
+        ]);
+
+        assert!(find_divergence(&first, &first).is_none());
+        let divergence = find_divergence(&first, &second).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.node_id, Some(2));
+        assert!(divergence.cause.contains("different order"));
+        assert!((max_timing_drift(&first, &second, 1) - 0.004).abs() < 1e-9);
+
+        let divergence = find_divergence(&first[..2], &first[..1]).unwrap();
+        assert_eq!(divergence.cause, "second run ended after 1 events");
+
+        let rebased = rebase_events(second.clone(), 1.0);
+        assert_eq!(
+            rebased[0],
+            "[       0.004s] TX node 1 type 6 packet 1/1 (40 bytes)"
+        );
+        assert!(find_divergence(&second, &rebased).is_none());
+    }
+}
@@ -0,0 +1,92 @@
+//! # Duty-Cycled Receivers
+//!
+//! Energy-constrained nodes switch their receiver off periodically. With a
+//! `duty_cycle` in the scene (or on a node), the receiver listens for `on_ms` and
+//! sleeps for `off_ms`, repeating from the simulation start shifted by the cycle
+//! offset. A packet is only heard if the receiver listens during its whole
+//! airtime; packets overlapping a sleep period are lost and accounted separately
+//! from collisions and half-duplex losses ("missed while sleeping"). Transmitting
+//! wakes the radio, so sleeping does not delay a node's own packets.
+
+use embassy_time::Instant;
+use rand::Rng;
+
+use super::rng::with_simulation_rng;
+use super::types::DutyCycleConfig;
+
+/// Receiver sleep schedule of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepSchedule {
+    on_ms: u64,
+    period_ms: u64,
+    offset_ms: u64,
+}
+
+impl SleepSchedule {
+    /// Schedule of `config`, drawing a random cycle offset when none is configured.
+    ///
+    /// # Returns
+    ///
+    /// None for a schedule that never sleeps (`off_ms` of zero).
+    pub fn new(config: &DutyCycleConfig) -> Option<Self> {
+        if config.off_ms == 0 {
+            return None;
+        }
+        let period_ms = config.on_ms + config.off_ms;
+        let offset_ms = match config.offset_ms {
+            Some(offset) => offset % period_ms,
+            None => with_simulation_rng(|rng| rng.gen_range(0..period_ms)),
+        };
+        Some(Self {
+            on_ms: config.on_ms,
+            period_ms,
+            offset_ms,
+        })
+    }
+
+    /// Position within the cycle at `at` (ms; below `on_ms` while listening).
+    fn phase(&self, at: Instant) -> u64 {
+        (at.as_millis() + self.offset_ms) % self.period_ms
+    }
+
+    /// Whether the receiver is listening at `at`.
+    pub fn is_listening(&self, at: Instant) -> bool {
+        self.phase(at) < self.on_ms
+    }
+
+    /// Whether the receiver sleeps at any time between `from` and `to`.
+    pub fn sleeps_during(&self, from: Instant, to: Instant) -> bool {
+        let phase = self.phase(from);
+        // Listening at `from`, until the end of the current listen period
+        phase >= self.on_ms || phase + to.saturating_duration_since(from).as_millis() > self.on_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packets_must_fit_into_a_listen_period() {
+        let schedule = SleepSchedule::new(&DutyCycleConfig {
+            on_ms: 100,
+            off_ms: 900,
+            offset_ms: Some(50),
+        })
+        .unwrap();
+
+        // Cycle positions: 0-50 ms listening, 50-950 ms sleeping, 950-1050 ms listening
+        assert!(schedule.is_listening(Instant::from_millis(20)));
+        assert!(!schedule.is_listening(Instant::from_millis(500)));
+        assert!(!schedule.sleeps_during(Instant::from_millis(960), Instant::from_millis(1040)));
+        assert!(schedule.sleeps_during(Instant::from_millis(1000), Instant::from_millis(1060)));
+        assert!(schedule.sleeps_during(Instant::from_millis(900), Instant::from_millis(1000)));
+
+        let always_on = DutyCycleConfig {
+            on_ms: 100,
+            off_ms: 0,
+            offset_ms: None,
+        };
+        assert_eq!(SleepSchedule::new(&always_on), None);
+    }
+}
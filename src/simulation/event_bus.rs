@@ -0,0 +1,661 @@
+//! # Simulation Event Bus
+//!
+//! Radio events of the network task (transmissions, receptions, collisions,
+//! half-duplex and sleep losses) are published once on an `EventBus` instead of being
+//! counted, logged and forwarded inline. The bus keeps the global packet counters
+//! and hands every event to its subscribed observers in subscription order:
+//!
+//! - `UiForwarder`: packet counters, transmission animations, deliveries and echo link
+//!   qualities for the UI
+//! - `EventRecorder`: the events of the crash dump ring and the determinism audit
+//!   (see `event_ring`), formatted only when dumped
+//! - `EventStoreObserver`: the central event store answering searches across all
+//!   nodes (see `event_store`)
+//! - `StatsCollector`: the cumulative per-node radio statistics (see
+//!   `common::node_statistics`)
+//! - `CaptureWriter`: the scene's optional `event_capture` file, one line per event
+//! - `AlertEngine`: the collision rate rule of the UI's alert rules
+//!
+//! New subsystems implement `SimulationObserver` and are subscribed in
+//! `network_task`, without touching the radio event handling. Observers run on the
+//! executor thread and must not block; UI updates use `try_send`.
+
+use embassy_time::Instant;
+use moonblokz_radio_lib::{MessageType, RadioPacket};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::rc::Rc;
+
+use super::event_ring::record_radio_event;
+use super::message_decoder::{FloodPacketKey, flood_packet_key};
+use super::packet_faults::PacketFault;
+use crate::UIRefreshQueueSender;
+use crate::common::node_statistics::NodeStatistics;
+use crate::ui::UIRefreshState;
+
+/// Minimum number of packets (received + collisions) before the collision rate rule applies.
+const MIN_COLLISION_RATE_SAMPLES: u64 = 100;
+
+/// A radio event of the simulation.
+#[derive(Debug, Clone, Copy)]
+pub enum SimulationEvent<'a> {
+    /// A node started transmitting a packet.
+    Transmitted {
+        node_id: u32,
+        packet: &'a RadioPacket,
+        /// Effective distance of the transmitter in meters.
+        effective_distance: f32,
+    },
+    /// A node decoded a packet (possibly damaged by an injected fault).
+    Received {
+        node_id: u32,
+        sender_id: u32,
+        packet: &'a RadioPacket,
+        rssi: f32,
+        sinr: f32,
+        link_quality: u8,
+        fault: Option<PacketFault>,
+    },
+    /// A packet was lost to a collision at a node.
+    Collided {
+        node_id: u32,
+        sender_id: u32,
+        packet: &'a RadioPacket,
+        sinr: f32,
+    },
+    /// A packet was missed because the node was transmitting (half-duplex).
+    MissedWhileTransmitting {
+        node_id: u32,
+        sender_id: u32,
+        packet: &'a RadioPacket,
+    },
+    /// A packet was missed because the node's receiver was asleep (duty cycling).
+    MissedWhileSleeping {
+        node_id: u32,
+        sender_id: u32,
+        packet: &'a RadioPacket,
+    },
+}
+
+/// Global packet counters of the run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacketCounters {
+    pub sent: u64,
+    pub received: u64,
+    pub collisions: u64,
+}
+
+impl PacketCounters {
+    /// Count an event.
+    fn record(&mut self, event: &SimulationEvent) {
+        match event {
+            SimulationEvent::Transmitted { .. } => self.sent += 1,
+            SimulationEvent::Received { .. } => self.received += 1,
+            SimulationEvent::Collided { .. } => self.collisions += 1,
+            SimulationEvent::MissedWhileTransmitting { .. }
+            | SimulationEvent::MissedWhileSleeping { .. } => {}
+        }
+    }
+}
+
+/// Subscriber of simulation events.
+pub trait SimulationObserver {
+    /// Handle an event; `counters` already include it.
+    fn on_event(&mut self, event: &SimulationEvent, counters: &PacketCounters);
+
+    /// Write out buffered output; called when the run stops or the session ends.
+    fn flush(&mut self) {}
+}
+
+/// Distributes simulation events to the observers and keeps the packet counters.
+#[derive(Default)]
+pub struct EventBus {
+    counters: PacketCounters,
+    observers: Vec<Box<dyn SimulationObserver>>,
+}
+
+impl EventBus {
+    /// Add an observer; observers receive events in subscription order.
+    pub fn subscribe(&mut self, observer: impl SimulationObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Count an event and hand it to every observer.
+    pub fn publish(&mut self, event: SimulationEvent) {
+        self.counters.record(&event);
+        for observer in &mut self.observers {
+            observer.on_event(&event, &self.counters);
+        }
+    }
+
+    /// Packet counters of the run so far.
+    pub fn counters(&self) -> PacketCounters {
+        self.counters
+    }
+
+    /// Let every observer write out its buffered output.
+    pub fn flush(&mut self) {
+        for observer in &mut self.observers {
+            observer.flush();
+        }
+    }
+}
+
+/// Forwards packet counters, transmissions, deliveries and echo link qualities to the UI.
+pub struct UiForwarder {
+    pub ui_refresh_tx: UIRefreshQueueSender,
+}
+
+impl SimulationObserver for UiForwarder {
+    fn on_event(&mut self, event: &SimulationEvent, counters: &PacketCounters) {
+        match *event {
+            SimulationEvent::Transmitted {
+                node_id,
+                packet,
+                effective_distance,
+            } => {
+                self.ui_refresh_tx
+                    .try_send(UIRefreshState::NodeSentRadioMessage(
+                        node_id,
+                        packet.message_type(),
+                        effective_distance as u32,
+                        packet.length,
+                    ))
+                    .ok();
+            }
+            SimulationEvent::Received {
+                node_id,
+                sender_id,
+                packet,
+                link_quality,
+                ..
+            } => {
+                self.ui_refresh_tx
+                    .try_send(UIRefreshState::PacketDelivered(
+                        sender_id,
+                        node_id,
+                        packet.message_type(),
+                    ))
+                    .ok();
+                // Echo traffic carries the link quality samples used by the neighbor graph overlay
+                if packet.message_type() == MessageType::Echo as u8 {
+                    self.ui_refresh_tx
+                        .try_send(UIRefreshState::LinkQualityObserved(
+                            sender_id,
+                            node_id,
+                            link_quality,
+                        ))
+                        .ok();
+                }
+            }
+            _ => {}
+        }
+        if !matches!(
+            event,
+            SimulationEvent::MissedWhileTransmitting { .. }
+                | SimulationEvent::MissedWhileSleeping { .. }
+        ) {
+            self.ui_refresh_tx
+                .try_send(UIRefreshState::RadioMessagesCountUpdated(
+                    counters.sent,
+                    counters.received,
+                    counters.collisions,
+                ))
+                .ok();
+        }
+    }
+}
+
+/// Records every radio event in the event ring (crash dump, determinism audit).
+pub struct EventRecorder;
+
+impl SimulationObserver for EventRecorder {
+    fn on_event(&mut self, event: &SimulationEvent, _counters: &PacketCounters) {
+        record_radio_event(RecordedEvent::from(event));
+    }
+}
+
+/// Cumulative per-node radio statistics of the run (node ID -> statistics), shared
+/// by the `StatsCollector` and the network task, which also counts relays and
+/// channel activity detections.
+pub type SharedNodeStatistics = Rc<RefCell<HashMap<u32, NodeStatistics>>>;
+
+/// Updates the per-node radio statistics from the radio events.
+pub struct StatsCollector {
+    statistics: SharedNodeStatistics,
+    /// Senders each node received packets from, to count its neighbors.
+    heard_senders: HashMap<u32, HashSet<u32>>,
+    /// Flooded packets each node received or sent, to count received duplicates.
+    known_flood_packets: HashMap<u32, HashSet<FloodPacketKey>>,
+}
+
+impl StatsCollector {
+    /// Create a collector updating the given statistics.
+    pub fn new(statistics: SharedNodeStatistics) -> Self {
+        Self {
+            statistics,
+            heard_senders: HashMap::new(),
+            known_flood_packets: HashMap::new(),
+        }
+    }
+}
+
+impl SimulationObserver for StatsCollector {
+    fn on_event(&mut self, event: &SimulationEvent, _counters: &PacketCounters) {
+        let mut statistics = self.statistics.borrow_mut();
+        match *event {
+            SimulationEvent::Transmitted {
+                node_id, packet, ..
+            } => {
+                statistics.entry(node_id).or_default().record_tx();
+                if let Some(key) = flood_packet_key(packet) {
+                    self.known_flood_packets
+                        .entry(node_id)
+                        .or_default()
+                        .insert(key);
+                }
+            }
+            SimulationEvent::Received {
+                node_id,
+                sender_id,
+                packet,
+                link_quality,
+                fault,
+                ..
+            } => {
+                let node = statistics.entry(node_id).or_default();
+                let new_neighbor = self
+                    .heard_senders
+                    .entry(node_id)
+                    .or_default()
+                    .insert(sender_id);
+                node.record_rx(link_quality, new_neighbor);
+                // Faulty packets are counted separately; a flooded packet the node already
+                // received or sent carries no new information
+                match fault {
+                    Some(fault) => {
+                        node.record_faulty_packet(matches!(fault, PacketFault::Truncated { .. }))
+                    }
+                    None => {
+                        if let Some(key) = flood_packet_key(packet) {
+                            let duplicate = !self
+                                .known_flood_packets
+                                .entry(node_id)
+                                .or_default()
+                                .insert(key);
+                            node.record_flood_packet(duplicate);
+                        }
+                    }
+                }
+            }
+            SimulationEvent::Collided { node_id, .. } => {
+                statistics.entry(node_id).or_default().record_collision();
+            }
+            SimulationEvent::MissedWhileTransmitting { node_id, .. } => {
+                statistics
+                    .entry(node_id)
+                    .or_default()
+                    .record_missed_while_transmitting();
+            }
+            SimulationEvent::MissedWhileSleeping { node_id, .. } => {
+                statistics
+                    .entry(node_id)
+                    .or_default()
+                    .record_missed_while_sleeping();
+            }
+        }
+    }
+}
+
+/// Writes every radio event to the scene's `event_capture` file: one line per event,
+/// the virtual milliseconds since scene load and the event line, tab separated.
+pub struct CaptureWriter {
+    path: String,
+    writer: BufWriter<File>,
+    scene_start: Instant,
+    /// Set after a write error; the capture stops instead of logging every event.
+    failed: bool,
+}
+
+impl CaptureWriter {
+    /// Create (or truncate) the capture file.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - Capture file path
+    /// * `scene_start` - Virtual time when the scene was loaded
+    ///
+    /// # Returns
+    ///
+    /// The writer, or an error message if the file cannot be created.
+    pub fn create(path: &str, scene_start: Instant) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Cannot create the event capture {}: {}", path, e))?;
+        Ok(Self {
+            path: path.to_string(),
+            writer: BufWriter::new(file),
+            scene_start,
+            failed: false,
+        })
+    }
+
+    /// Log a write error and stop capturing.
+    fn fail(&mut self, err: std::io::Error) {
+        log::error!("Cannot write the event capture {}: {}", self.path, err);
+        self.failed = true;
+    }
+}
+
+impl SimulationObserver for CaptureWriter {
+    fn on_event(&mut self, event: &SimulationEvent, _counters: &PacketCounters) {
+        if self.failed {
+            return;
+        }
+        let elapsed = Instant::now().saturating_duration_since(self.scene_start);
+        if let Err(err) = writeln!(
+            self.writer,
+            "{}\t{}",
+            elapsed.as_millis(),
+            RecordedEvent::from(event)
+        ) {
+            self.fail(err);
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.failed {
+            return;
+        }
+        if let Err(err) = self.writer.flush() {
+            self.fail(err);
+        }
+    }
+}
+
+/// Collision rate limit in percent of the UI's alert rule (None: rule disabled), set
+/// by `UICommand::SetCollisionRateAlert`.
+pub type CollisionRateLimit = Rc<Cell<Option<f64>>>;
+
+/// Raises `UIRefreshState::AlertRaised` when the network-wide collision rate exceeds
+/// the limit. Edge-triggered: re-armed once the rate is back under the limit.
+pub struct AlertEngine {
+    limit: CollisionRateLimit,
+    ui_refresh_tx: UIRefreshQueueSender,
+    raised: bool,
+}
+
+impl AlertEngine {
+    /// Create an engine checking the given limit.
+    pub fn new(limit: CollisionRateLimit, ui_refresh_tx: UIRefreshQueueSender) -> Self {
+        Self {
+            limit,
+            ui_refresh_tx,
+            raised: false,
+        }
+    }
+
+    /// Check the collision rate.
+    ///
+    /// # Returns
+    ///
+    /// The alert message if the limit was exceeded just now.
+    fn check(&mut self, counters: &PacketCounters) -> Option<String> {
+        let limit = self.limit.get();
+        let total = counters.received + counters.collisions;
+        let rate = counters.collisions as f64 * 100.0 / total.max(1) as f64;
+        let exceeded =
+            limit.is_some_and(|limit| total >= MIN_COLLISION_RATE_SAMPLES && rate > limit);
+        let newly_exceeded = exceeded && !self.raised;
+        self.raised = exceeded;
+        newly_exceeded.then(|| {
+            format!(
+                "Collision rate {:.2}% exceeds {:.2}%",
+                rate,
+                limit.unwrap_or_default()
+            )
+        })
+    }
+}
+
+impl SimulationObserver for AlertEngine {
+    fn on_event(&mut self, event: &SimulationEvent, counters: &PacketCounters) {
+        if !matches!(
+            event,
+            SimulationEvent::Received { .. } | SimulationEvent::Collided { .. }
+        ) {
+            return;
+        }
+        if let Some(message) = self.check(counters) {
+            self.ui_refresh_tx
+                .try_send(UIRefreshState::AlertRaised(message))
+                .ok();
+        }
+    }
+}
+
+/// The fields of a radio event shown in its event line, without the packet, so
+/// the event ring stores events without formatting them.
+#[derive(Debug, Clone, Copy)]
+pub struct RecordedEvent {
+    kind: RecordedEventKind,
+    node_id: u32,
+    sender_id: u32,
+    message_type: u8,
+    /// One-based packet index.
+    packet_number: u8,
+    packet_count: u8,
+    length: usize,
+}
+
+/// Kind and signal values of a `RecordedEvent`.
+#[derive(Debug, Clone, Copy)]
+enum RecordedEventKind {
+    Transmitted,
+    Received {
+        rssi: f32,
+        sinr: f32,
+        link_quality: u8,
+        fault: Option<PacketFault>,
+    },
+    Collided {
+        sinr: f32,
+    },
+    MissedWhileTransmitting,
+    MissedWhileSleeping,
+}
+
+impl From<&SimulationEvent<'_>> for RecordedEvent {
+    fn from(event: &SimulationEvent) -> Self {
+        let (kind, node_id, sender_id, packet) = match *event {
+            SimulationEvent::Transmitted {
+                node_id, packet, ..
+            } => (RecordedEventKind::Transmitted, node_id, node_id, packet),
+            SimulationEvent::Received {
+                node_id,
+                sender_id,
+                packet,
+                rssi,
+                sinr,
+                link_quality,
+                fault,
+            } => (
+                RecordedEventKind::Received {
+                    rssi,
+                    sinr,
+                    link_quality,
+                    fault,
+                },
+                node_id,
+                sender_id,
+                packet,
+            ),
+            SimulationEvent::Collided {
+                node_id,
+                sender_id,
+                packet,
+                sinr,
+            } => (
+                RecordedEventKind::Collided { sinr },
+                node_id,
+                sender_id,
+                packet,
+            ),
+            SimulationEvent::MissedWhileTransmitting {
+                node_id,
+                sender_id,
+                packet,
+            } => (
+                RecordedEventKind::MissedWhileTransmitting,
+                node_id,
+                sender_id,
+                packet,
+            ),
+            SimulationEvent::MissedWhileSleeping {
+                node_id,
+                sender_id,
+                packet,
+            } => (
+                RecordedEventKind::MissedWhileSleeping,
+                node_id,
+                sender_id,
+                packet,
+            ),
+        };
+        Self {
+            kind,
+            node_id,
+            sender_id,
+            message_type: packet.message_type(),
+            packet_number: packet.packet_index() + 1,
+            packet_count: packet.total_packet_count(),
+            length: packet.length,
+        }
+    }
+}
+
+impl std::fmt::Display for RecordedEvent {
+    /// Event line of the event (crash dump, determinism audit, event capture).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            RecordedEventKind::Transmitted => write!(
+                f,
+                "TX node {} type {} packet {}/{} ({} bytes)",
+                self.node_id, self.message_type, self.packet_number, self.packet_count, self.length
+            ),
+            RecordedEventKind::Received {
+                rssi,
+                sinr,
+                link_quality,
+                fault,
+            } => {
+                write!(
+                    f,
+                    "RX node {} from {} type {} (rssi {:.1} dBm, sinr {:.1} dB, lq {})",
+                    self.node_id, self.sender_id, self.message_type, rssi, sinr, link_quality
+                )?;
+                if let Some(fault) = fault {
+                    write!(f, " FAULT {}", fault.describe())?;
+                }
+                Ok(())
+            }
+            RecordedEventKind::Collided { sinr } => write!(
+                f,
+                "COLLISION node {} from {} type {} (sinr {:.1} dB)",
+                self.node_id, self.sender_id, self.message_type, sinr
+            ),
+            RecordedEventKind::MissedWhileTransmitting => write!(
+                f,
+                "MISSED_TX node {} from {} type {}",
+                self.node_id, self.sender_id, self.message_type
+            ),
+            RecordedEventKind::MissedWhileSleeping => write!(
+                f,
+                "MISSED_SLEEP node {} from {} type {}",
+                self.node_id, self.sender_id, self.message_type
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UIRefreshQueue;
+
+    /// Observer remembering the counters it saw.
+    struct Probe(Rc<RefCell<Vec<PacketCounters>>>);
+
+    impl SimulationObserver for Probe {
+        fn on_event(&mut self, _event: &SimulationEvent, counters: &PacketCounters) {
+            self.0.borrow_mut().push(*counters);
+        }
+    }
+
+    #[test]
+    fn test_observers_see_counters_including_the_event() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut bus = EventBus::default();
+        bus.subscribe(Probe(seen.clone()));
+        let packet = RadioPacket {
+            data: std::array::from_fn(|_| 0),
+            length: 10,
+        };
+
+        bus.publish(SimulationEvent::Transmitted {
+            node_id: 1,
+            packet: &packet,
+            effective_distance: 500.0,
+        });
+        bus.publish(SimulationEvent::Collided {
+            node_id: 2,
+            sender_id: 1,
+            packet: &packet,
+            sinr: -3.0,
+        });
+        bus.publish(SimulationEvent::MissedWhileTransmitting {
+            node_id: 3,
+            sender_id: 1,
+            packet: &packet,
+        });
+
+        let expected = PacketCounters {
+            sent: 1,
+            received: 0,
+            collisions: 1,
+        };
+        assert_eq!(bus.counters(), expected);
+        assert_eq!(seen.borrow().len(), 3);
+        assert_eq!(seen.borrow()[0].sent, 1);
+        assert_eq!(seen.borrow()[2], expected);
+    }
+
+    #[test]
+    fn test_collision_rate_alert_is_edge_triggered() {
+        let queue: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+        let limit = CollisionRateLimit::default();
+        let mut engine = AlertEngine::new(Rc::clone(&limit), queue.sender());
+        let high = PacketCounters {
+            sent: 100,
+            received: 80,
+            collisions: 20,
+        };
+        let low = PacketCounters {
+            sent: 100,
+            received: 99,
+            collisions: 1,
+        };
+
+        // Rule disabled
+        assert!(engine.check(&high).is_none());
+
+        limit.set(Some(10.0));
+        assert!(engine.check(&high).is_some());
+        // Still above the limit: no new alert
+        assert!(engine.check(&high).is_none());
+        // Rate clears, then triggers again
+        assert!(engine.check(&low).is_none());
+        assert!(engine.check(&high).is_some());
+    }
+}
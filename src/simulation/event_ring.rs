@@ -0,0 +1,270 @@
+//! Bounded ring of recent simulation events for post-mortem debugging.
+//!
+//! The network task records every radio transmission, reception, collision and
+//! measurement start. Only the last `EVENT_RING_CAPACITY` events are kept, so the
+//! ring can stay enabled during multi-hour runs.
+//!
+//! Recording is cheap: every thread keeps its own ring (its lock is only contended
+//! while a dump is written) and radio events are stored as `RecordedEvent` values.
+//! Events are formatted only when they are read, then the rings of all threads are
+//! merged in recording order.
+//!
+//! The determinism audit additionally records the complete event stream of a run
+//! (`start_event_recording`) to compare two runs of the same scene.
+//!
+//! A panic hook (installed once at startup) writes the ring to a
+//! `crash_dump_<timestamp>.log` file in the working directory when any thread
+//! panics, e.g. when the network task aborts on the Embassy executor thread.
+//! Abnormal exits without a panic (failed headless run, GUI error, shutdown
+//! timeout) write the dump through `report_crash_dump`.
+
+use embassy_time::Instant;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+
+use super::event_bus::RecordedEvent;
+use crate::common::provenance;
+
+/// Number of most recent events kept in the ring.
+const EVENT_RING_CAPACITY: usize = 5000;
+
+/// Whether events are kept in the rings (set by `init_event_ring`).
+static RING_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the complete event stream is kept (determinism audit).
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Order of the recorded events across threads.
+static NEXT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Event storage of every thread that recorded an event.
+static THREADS: Mutex<Vec<(ThreadId, Arc<Mutex<ThreadEvents>>)>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// Event storage of the current thread (registered in `THREADS` on first use).
+    static EVENTS: Arc<Mutex<ThreadEvents>> = register_thread();
+}
+
+/// Text of a recorded event.
+#[derive(Debug, Clone)]
+enum EventText {
+    Radio(RecordedEvent),
+    Text(String),
+}
+
+/// A recorded event with its virtual time.
+#[derive(Debug, Clone)]
+struct RingEvent {
+    sequence: u64,
+    time: Instant,
+    text: EventText,
+}
+
+impl std::fmt::Display for RingEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:>12.3}s] ", self.time.as_millis() as f64 / 1000.0)?;
+        match &self.text {
+            EventText::Radio(event) => write!(f, "{}", event),
+            EventText::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+/// Events recorded by one thread.
+#[derive(Default)]
+struct ThreadEvents {
+    /// Most recent events, oldest first.
+    ring: VecDeque<RingEvent>,
+    /// Complete event stream, kept only while recording.
+    recording: Vec<RingEvent>,
+}
+
+/// Register the event storage of the current thread.
+///
+/// Storage of ended threads is dropped here, so only threads that recorded since
+/// the last thread start keep their events.
+fn register_thread() -> Arc<Mutex<ThreadEvents>> {
+    let events = Arc::new(Mutex::new(ThreadEvents::default()));
+    let mut threads = THREADS.lock().unwrap_or_else(|e| e.into_inner());
+    threads.retain(|(_, thread)| Arc::strong_count(thread) > 1);
+    threads.push((std::thread::current().id(), Arc::clone(&events)));
+    events
+}
+
+/// Initialize the event ring.
+pub fn init_event_ring() {
+    RING_ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Store an event in the ring of the current thread.
+fn record(text: EventText) {
+    let ring_enabled = RING_ENABLED.load(Ordering::Relaxed);
+    let recording = RECORDING.load(Ordering::Relaxed);
+    if !ring_enabled && !recording {
+        return;
+    }
+    let event = RingEvent {
+        sequence: NEXT_SEQUENCE.fetch_add(1, Ordering::Relaxed),
+        time: Instant::now(),
+        text,
+    };
+    // Thread-local storage is gone while the thread exits
+    let _ = EVENTS.try_with(|events| {
+        let mut events = events.lock().unwrap_or_else(|e| e.into_inner());
+        if recording {
+            events.recording.push(event.clone());
+        }
+        if ring_enabled {
+            if events.ring.len() >= EVENT_RING_CAPACITY {
+                events.ring.pop_front();
+            }
+            events.ring.push_back(event);
+        }
+    });
+}
+
+/// Record an event, dropping the oldest one when the ring is full.
+///
+/// The current virtual time is prepended to the event text.
+pub fn record_event(event: impl Into<String>) {
+    record(EventText::Text(event.into()));
+}
+
+/// Record a radio event (formatted only when the ring is dumped).
+pub fn record_radio_event(event: RecordedEvent) {
+    record(EventText::Radio(event));
+}
+
+/// Run `f` on the event storage of every thread.
+///
+/// The storage of the current thread is skipped if it is locked (a panic while
+/// recording) instead of deadlocking.
+fn for_each_thread(mut f: impl FnMut(&mut ThreadEvents)) {
+    let current = std::thread::current().id();
+    let threads = THREADS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    for (thread_id, thread) in threads {
+        if thread_id == current {
+            if let Ok(mut events) = thread.try_lock() {
+                f(&mut events);
+            }
+        } else {
+            f(&mut thread.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
+
+/// Start keeping every recorded event (not only the most recent ones).
+pub fn start_event_recording() {
+    RECORDING.store(true, Ordering::Relaxed);
+    for_each_thread(|events| events.recording.clear());
+}
+
+/// Take the events recorded since `start_event_recording` as event lines.
+pub fn take_recorded_events() -> Vec<String> {
+    let mut recorded = Vec::new();
+    for_each_thread(|events| recorded.append(&mut events.recording));
+    recorded.sort_by_key(|event| event.sequence);
+    recorded.iter().map(RingEvent::to_string).collect()
+}
+
+/// The most recent events of all threads, oldest first.
+fn recent_events() -> Vec<RingEvent> {
+    let mut recent = Vec::new();
+    for_each_thread(|events| recent.extend(events.ring.iter().cloned()));
+    recent.sort_by_key(|event| event.sequence);
+    let skip = recent.len().saturating_sub(EVENT_RING_CAPACITY);
+    recent.drain(..skip);
+    recent
+}
+
+/// Format the dump file content: header, reason and the recorded events.
+fn format_dump(reason: &str, events: &[RingEvent]) -> String {
+    let mut dump = String::new();
+    let _ = writeln!(dump, "MoonBlokz Radio Simulator crash dump");
+    let _ = writeln!(dump, "Created: {}", chrono::Local::now().to_rfc3339());
+    let _ = writeln!(dump, "Reason: {}", reason);
+    let _ = writeln!(
+        dump,
+        "Provenance: {}",
+        serde_json::to_string(&provenance::current().without_scene()).unwrap_or_default()
+    );
+    let _ = writeln!(dump, "Last {} events (oldest first):", events.len());
+    for event in events {
+        let _ = writeln!(dump, "{}", event);
+    }
+    dump
+}
+
+/// Write the recorded events to a new `crash_dump_<timestamp>.log` file.
+///
+/// # Parameters
+///
+/// * `reason` - Why the dump is written (e.g. the panic message)
+///
+/// # Returns
+///
+/// The path of the written file, or an error if it could not be written.
+pub fn write_crash_dump(reason: &str) -> std::io::Result<String> {
+    let dump = format_dump(reason, &recent_events());
+
+    let path = format!(
+        "crash_dump_{}.log",
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    fs::write(&path, dump)?;
+    Ok(path)
+}
+
+/// Write a crash dump and report its path (or the failure) on stderr.
+///
+/// # Parameters
+///
+/// * `reason` - Why the dump is written (e.g. the error that ended the run)
+pub fn report_crash_dump(reason: &str) {
+    match write_crash_dump(reason) {
+        Ok(path) => eprintln!("Crash dump written to {}", path),
+        Err(e) => eprintln!("Failed to write crash dump: {}", e),
+    }
+}
+
+/// Install a panic hook that writes a crash dump before running the default hook.
+pub fn install_crash_dump_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let reason = format!(
+            "panic in thread '{}': {}",
+            thread.name().unwrap_or("<unnamed>"),
+            info
+        );
+        report_crash_dump(&reason);
+        default_hook(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_event(sequence: u64, text: &str) -> RingEvent {
+        RingEvent {
+            sequence,
+            time: Instant::from_millis(1500),
+            text: EventText::Text(text.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_format_dump_lists_events_in_order() {
+        let events = [text_event(0, "first"), text_event(1, "second")];
+        let dump = format_dump("test", &events);
+        assert!(dump.contains("Reason: test"));
+        assert!(dump.contains("Last 2 events"));
+        assert!(dump.contains("[       1.500s] first"));
+        assert!(dump.find("first").unwrap() < dump.find("second").unwrap());
+    }
+}
@@ -0,0 +1,318 @@
+//! # Central Event Store
+//!
+//! Every radio event of the run (transmissions, receptions, collisions, half-duplex
+//! and sleep losses) is kept in one time-ordered store, so questions spanning all
+//! nodes ("all collisions of RequestBlockPart between 300 s and 600 s involving
+//! node 12") can be answered without walking the per-node history rings, which
+//! are bounded and only cover recent traffic.
+//!
+//! Events arrive from the event bus in virtual time order, so time ranges are
+//! found by binary search. A per-node index lists the positions of the events a
+//! node took part in (as transmitter, receiver or sender of the lost packet), so
+//! node queries only visit that node's events. The store keeps the latest
+//! `MAX_STORED_EVENTS` events; older ones are evicted together with their index
+//! entries.
+
+use embassy_time::Instant;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+use super::event_bus::{PacketCounters, SimulationEvent, SimulationObserver};
+
+/// Maximum number of events kept (about 24 bytes each plus the node index).
+const MAX_STORED_EVENTS: usize = 2_000_000;
+
+/// Maximum number of events returned by a query (the total is still counted).
+pub const MAX_QUERY_RESULTS: usize = 1000;
+
+/// Kind of a stored event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Transmission,
+    Reception,
+    Collision,
+    MissedWhileTransmitting,
+    MissedWhileSleeping,
+}
+
+impl EventKind {
+    /// All kinds, in display order.
+    pub const ALL: [EventKind; 5] = [
+        EventKind::Transmission,
+        EventKind::Reception,
+        EventKind::Collision,
+        EventKind::MissedWhileTransmitting,
+        EventKind::MissedWhileSleeping,
+    ];
+
+    /// Short display name.
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::Transmission => "TX",
+            EventKind::Reception => "RX",
+            EventKind::Collision => "Collision",
+            EventKind::MissedWhileTransmitting => "Missed (TX)",
+            EventKind::MissedWhileSleeping => "Missed (sleep)",
+        }
+    }
+}
+
+/// A radio event in the store.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StoredEvent {
+    /// Virtual time in milliseconds since the simulation start.
+    pub time_ms: u64,
+    pub kind: EventKind,
+    /// Transmitting node (transmissions) or receiving node (all other kinds).
+    pub node_id: u32,
+    /// Transmitter of the packet (equal to `node_id` for transmissions).
+    pub sender_id: u32,
+    pub message_type: u8,
+}
+
+impl StoredEvent {
+    /// Event of the store for a bus event at `time_ms`.
+    fn from_event(event: &SimulationEvent, time_ms: u64) -> Self {
+        let (kind, node_id, sender_id, packet) = match *event {
+            SimulationEvent::Transmitted {
+                node_id, packet, ..
+            } => (EventKind::Transmission, node_id, node_id, packet),
+            SimulationEvent::Received {
+                node_id,
+                sender_id,
+                packet,
+                ..
+            } => (EventKind::Reception, node_id, sender_id, packet),
+            SimulationEvent::Collided {
+                node_id,
+                sender_id,
+                packet,
+                ..
+            } => (EventKind::Collision, node_id, sender_id, packet),
+            SimulationEvent::MissedWhileTransmitting {
+                node_id,
+                sender_id,
+                packet,
+            } => (
+                EventKind::MissedWhileTransmitting,
+                node_id,
+                sender_id,
+                packet,
+            ),
+            SimulationEvent::MissedWhileSleeping {
+                node_id,
+                sender_id,
+                packet,
+            } => (EventKind::MissedWhileSleeping, node_id, sender_id, packet),
+        };
+        StoredEvent {
+            time_ms,
+            kind,
+            node_id,
+            sender_id,
+            message_type: packet.message_type(),
+        }
+    }
+}
+
+/// Query against the event store. Unset fields match everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventQuery {
+    /// Event kinds to match (empty = all kinds).
+    pub kinds: Vec<EventKind>,
+    pub message_type: Option<u8>,
+    /// Node taking part in the event (as transmitter, receiver or sender).
+    pub node_id: Option<u32>,
+    /// Inclusive time range in milliseconds since the simulation start.
+    pub from_ms: Option<u64>,
+    pub to_ms: Option<u64>,
+}
+
+impl EventQuery {
+    fn matches(&self, event: &StoredEvent) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&event.kind))
+            && self.message_type.is_none_or(|t| t == event.message_type)
+            && self
+                .node_id
+                .is_none_or(|id| id == event.node_id || id == event.sender_id)
+    }
+}
+
+/// Answer to an `EventQuery`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventQueryResult {
+    /// The first `MAX_QUERY_RESULTS` matching events, oldest first.
+    pub events: Vec<StoredEvent>,
+    /// Number of matching events.
+    pub total: usize,
+    /// Time of the oldest event still stored (older events were evicted).
+    pub oldest_ms: Option<u64>,
+}
+
+/// Time-ordered store of all radio events with a per-node index.
+#[derive(Debug, Default)]
+pub struct EventStore {
+    events: VecDeque<StoredEvent>,
+    /// Absolute position of `events[0]` (number of evicted events).
+    first: u64,
+    /// Absolute positions of the events each node took part in, ascending.
+    node_index: HashMap<u32, VecDeque<u64>>,
+}
+
+impl EventStore {
+    /// Append an event (events must arrive in time order).
+    pub fn push(&mut self, event: StoredEvent) {
+        if self.events.len() >= MAX_STORED_EVENTS {
+            self.evict_oldest();
+        }
+        let position = self.first + self.events.len() as u64;
+        self.node_index
+            .entry(event.node_id)
+            .or_default()
+            .push_back(position);
+        if event.sender_id != event.node_id {
+            self.node_index
+                .entry(event.sender_id)
+                .or_default()
+                .push_back(position);
+        }
+        self.events.push_back(event);
+    }
+
+    /// Drop the oldest event and its index entries (the first entries of its nodes).
+    fn evict_oldest(&mut self) {
+        let Some(event) = self.events.pop_front() else {
+            return;
+        };
+        for node_id in [event.node_id, event.sender_id] {
+            if let Some(positions) = self.node_index.get_mut(&node_id) {
+                if positions.front() == Some(&self.first) {
+                    positions.pop_front();
+                }
+            }
+        }
+        self.first += 1;
+    }
+
+    /// Run a query.
+    ///
+    /// # Returns
+    ///
+    /// The first `MAX_QUERY_RESULTS` matches and the number of all matches.
+    pub fn query(&self, query: &EventQuery) -> EventQueryResult {
+        // Absolute position range of the time window
+        let start = self.first
+            + query.from_ms.map_or(0, |from| {
+                self.events.partition_point(|event| event.time_ms < from)
+            }) as u64;
+        let end = self.first
+            + query.to_ms.map_or(self.events.len(), |to| {
+                self.events.partition_point(|event| event.time_ms <= to)
+            }) as u64;
+
+        let mut result = EventQueryResult {
+            oldest_ms: self.events.front().map(|event| event.time_ms),
+            ..Default::default()
+        };
+        let mut visit = |position: u64| {
+            let event = &self.events[(position - self.first) as usize];
+            if query.matches(event) {
+                result.total += 1;
+                if result.events.len() < MAX_QUERY_RESULTS {
+                    result.events.push(*event);
+                }
+            }
+        };
+        match query.node_id {
+            Some(node_id) => {
+                if let Some(positions) = self.node_index.get(&node_id) {
+                    let from = positions.partition_point(|&p| p < start);
+                    let to = positions.partition_point(|&p| p < end);
+                    positions.range(from..to).for_each(|&p| visit(p));
+                }
+            }
+            None => (start..end.max(start)).for_each(visit),
+        }
+        result
+    }
+}
+
+/// Stores every bus event in the shared event store at the current virtual time.
+pub struct EventStoreObserver(pub Rc<RefCell<EventStore>>);
+
+impl SimulationObserver for EventStoreObserver {
+    fn on_event(&mut self, event: &SimulationEvent, _counters: &PacketCounters) {
+        let time_ms = Instant::now().as_millis();
+        self.0
+            .borrow_mut()
+            .push(StoredEvent::from_event(event, time_ms));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(
+        time_ms: u64,
+        kind: EventKind,
+        node_id: u32,
+        sender_id: u32,
+        message_type: u8,
+    ) -> StoredEvent {
+        StoredEvent {
+            time_ms,
+            kind,
+            node_id,
+            sender_id,
+            message_type,
+        }
+    }
+
+    #[test]
+    fn queries_combine_time_node_kind_and_type_and_survive_eviction() {
+        let mut store = EventStore::default();
+        store.push(event(100_000, EventKind::Collision, 12, 3, 5));
+        store.push(event(350_000, EventKind::Collision, 12, 3, 5));
+        store.push(event(400_000, EventKind::Collision, 7, 12, 5));
+        store.push(event(450_000, EventKind::Collision, 7, 8, 5));
+        store.push(event(500_000, EventKind::Reception, 12, 3, 5));
+        store.push(event(550_000, EventKind::Collision, 12, 3, 6));
+        store.push(event(700_000, EventKind::Collision, 12, 3, 5));
+
+        let query = EventQuery {
+            kinds: vec![EventKind::Collision],
+            message_type: Some(5),
+            node_id: Some(12),
+            from_ms: Some(300_000),
+            to_ms: Some(600_000),
+        };
+        let result = store.query(&query);
+        let times: Vec<u64> = result.events.iter().map(|e| e.time_ms).collect();
+        assert_eq!(times, vec![350_000, 400_000]);
+        assert_eq!(result.total, 2);
+
+        // Without the node the collision between 7 and 8 matches as well
+        let all_nodes = store.query(&EventQuery {
+            node_id: None,
+            ..query.clone()
+        });
+        assert_eq!(all_nodes.total, 3);
+
+        // Evicting the oldest events keeps the index consistent
+        store.evict_oldest();
+        store.evict_oldest();
+        assert_eq!(store.query(&query).total, 1);
+        assert_eq!(store.query(&query).oldest_ms, Some(400_000));
+        assert_eq!(
+            store
+                .query(&EventQuery {
+                    node_id: Some(12),
+                    ..Default::default()
+                })
+                .total,
+            4
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! # External LoRaWAN Traffic
+//!
+//! Background traffic of an unrelated LoRaWAN network sharing the channel, for
+//! coexistence studies. `external_traffic` in the scene places a number of end
+//! devices at random positions within the world; each sends uplinks at random
+//! (exponentially distributed) intervals with the configured mean rate, deferred
+//! when the device's duty cycle budget forbids sending (after an uplink of airtime
+//! `T` a device stays silent for `T × (1 / duty_cycle − 1)`, as LoRaWAN devices do).
+//!
+//! The uplinks use the scene's LoRa modulation, i.e. the same channel and spreading
+//! factor as the mesh (the worst case). They are not delivered to any node: their
+//! received power (TX power minus the mean path loss, blocked by obstacles) adds to
+//! the interference of overlapping mesh packets with the same preamble lock and
+//! capture rules as mesh frames (see `physics`), and CAD reports them as channel
+//! activity where they are above the receiver sensitivity.
+//!
+//! Device positions and uplink times are drawn from the simulation RNG on the
+//! executor thread, so runs with the same seed see the same external traffic.
+
+use embassy_time::{Duration, Instant};
+use rand::Rng;
+use rand_distr::{Distribution, Exp};
+
+use super::geometry::{distance_from_d2, distance2, is_intersect};
+use super::rng::with_simulation_rng;
+use super::signal_calculations::calculate_air_time;
+use super::types::{ExternalTrafficConfig, Point, Scene};
+
+/// Frames are kept this long after their end: mesh packets are evaluated at their
+/// end, and no packet is longer than this.
+const FRAME_RETENTION: Duration = Duration::from_secs(30);
+
+/// An uplink of an external device.
+#[derive(Debug, Clone)]
+struct ExternalFrame {
+    position: Point,
+    start: Instant,
+    end: Instant,
+}
+
+/// An external end device.
+#[derive(Debug, Clone)]
+struct ExternalDevice {
+    position: Point,
+    /// When the device wants to send its next uplink.
+    next_uplink: Instant,
+    /// End of the silence its duty cycle imposes after the last uplink.
+    silent_until: Instant,
+}
+
+/// State of the external network: its devices and their recent uplinks.
+#[derive(Debug, Clone)]
+pub struct ExternalTraffic {
+    devices: Vec<ExternalDevice>,
+    /// Recent uplinks, ordered by start time.
+    frames: Vec<ExternalFrame>,
+    tx_power: f32,
+    airtime: Duration,
+    /// Silence after an uplink imposed by the duty cycle.
+    off_time: Duration,
+    /// Inter-uplink interval distribution of a device (ms).
+    interval: Exp<f64>,
+    uplinks: u64,
+}
+
+impl ExternalTraffic {
+    /// Place the devices at random positions and draw their first uplinks.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - The scene's external traffic settings (validated)
+    /// * `scene` - Scene (world bounds and LoRa parameters)
+    /// * `now` - Virtual time the traffic starts at
+    pub fn new(config: &ExternalTrafficConfig, scene: &Scene, now: Instant) -> Self {
+        let airtime_s = calculate_air_time(&scene.lora_parameters, config.payload_size) as f64;
+        let interval =
+            Exp::new(config.uplinks_per_hour / 3_600_000.0).expect("validated uplinks_per_hour");
+        let (min_x, min_y, max_x, max_y) = scene.transform.bounds();
+        let devices = with_simulation_rng(|rng| {
+            (0..config.devices)
+                .map(|_| ExternalDevice {
+                    position: Point {
+                        x: rng.gen_range(min_x..=max_x),
+                        y: rng.gen_range(min_y..=max_y),
+                    },
+                    next_uplink: now + Duration::from_millis(interval.sample(rng) as u64),
+                    silent_until: now,
+                })
+                .collect()
+        });
+        Self {
+            devices,
+            frames: Vec::new(),
+            tx_power: config.tx_power,
+            airtime: Duration::from_micros((airtime_s * 1_000_000.0) as u64),
+            off_time: Duration::from_micros(
+                (airtime_s * (1.0 / config.duty_cycle - 1.0) * 1_000_000.0) as u64,
+            ),
+            interval,
+            uplinks: 0,
+        }
+    }
+
+    /// Send the uplinks due until `now` and forget the frames no packet can overlap.
+    pub fn advance(&mut self, now: Instant) {
+        let interval = self.interval;
+        with_simulation_rng(|rng| {
+            self.schedule(now, || {
+                Duration::from_millis(interval.sample(&mut *rng) as u64)
+            })
+        });
+    }
+
+    /// Send the uplinks due until `now`, drawing the next intervals from `next_interval`.
+    fn schedule(&mut self, now: Instant, mut next_interval: impl FnMut() -> Duration) {
+        let (airtime, off_time) = (self.airtime, self.off_time);
+        let mut sent = Vec::new();
+        for device in &mut self.devices {
+            loop {
+                // A device over its duty cycle budget defers the uplink
+                let start = device.next_uplink.max(device.silent_until);
+                if start > now {
+                    break;
+                }
+                sent.push(ExternalFrame {
+                    position: device.position.clone(),
+                    start,
+                    end: start + airtime,
+                });
+                device.silent_until = start + airtime + off_time;
+                device.next_uplink = start + next_interval();
+            }
+        }
+        self.uplinks += sent.len() as u64;
+        self.frames.extend(sent);
+        self.frames.sort_by_key(|frame| frame.start);
+        if let Some(cutoff) = now.checked_sub(FRAME_RETENTION) {
+            self.frames.retain(|frame| frame.end >= cutoff);
+        }
+    }
+
+    /// Frames overlapping the interval from `start` to `end`.
+    fn overlapping(&self, start: Instant, end: Instant) -> impl Iterator<Item = &ExternalFrame> {
+        let last = self.frames.partition_point(|frame| frame.start < end);
+        self.frames[..last]
+            .iter()
+            .filter(move |frame| frame.end > start)
+    }
+
+    /// Frames overlapping the interval from `start` to `end` as heard at `receiver`.
+    ///
+    /// # Returns
+    ///
+    /// Start, end and received power (dBm, without shadowing) of every overlapping
+    /// frame with a clear line of sight to the receiver.
+    pub fn frames_at<'a>(
+        &'a self,
+        receiver: &'a Point,
+        start: Instant,
+        end: Instant,
+        scene: &'a Scene,
+    ) -> impl Iterator<Item = (Instant, Instant, f32)> + 'a {
+        self.overlapping(start, end).filter_map(move |frame| {
+            if is_intersect(&frame.position, receiver, &scene.obstacles) {
+                return None;
+            }
+            let distance = distance_from_d2(distance2(&frame.position, receiver, scene)) as f32;
+            let path_loss = scene
+                .propagation_model
+                .model()
+                .mean_path_loss(distance, &scene.path_loss_parameters);
+            Some((frame.start, frame.end, self.tx_power - path_loss))
+        })
+    }
+
+    /// Uplinks sent so far.
+    pub fn uplinks(&self) -> u64 {
+        self.uplinks
+    }
+
+    /// Mean fraction of the time the channel carries an external uplink (offered
+    /// load without the duty cycle deferrals; above 1 frames always overlap).
+    pub fn offered_load(config: &ExternalTrafficConfig, scene: &Scene) -> f64 {
+        let airtime_s = calculate_air_time(&scene.lora_parameters, config.payload_size) as f64;
+        config.devices as f64 * config.uplinks_per_hour / 3600.0 * airtime_s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duty_cycle_defers_uplinks_and_old_frames_expire() {
+        let start = Instant::from_secs(100);
+        let mut traffic = ExternalTraffic {
+            devices: vec![ExternalDevice {
+                position: Point { x: 0.0, y: 0.0 },
+                next_uplink: start,
+                silent_until: start,
+            }],
+            frames: Vec::new(),
+            tx_power: 14.0,
+            airtime: Duration::from_millis(100),
+            // 10% duty cycle
+            off_time: Duration::from_millis(900),
+            interval: Exp::new(1.0).unwrap(),
+            uplinks: 0,
+        };
+
+        // The device wants to send every 200 ms but may only send once per second
+        traffic.schedule(start + Duration::from_millis(2500), || {
+            Duration::from_millis(200)
+        });
+        let starts: Vec<u64> = traffic.frames.iter().map(|f| f.start.as_millis()).collect();
+        assert_eq!(starts, vec![100_000, 101_000, 102_000]);
+        assert_eq!(traffic.uplinks(), 3);
+
+        let overlapping: Vec<u64> = traffic
+            .overlapping(
+                start + Duration::from_millis(1050),
+                start + Duration::from_millis(2050),
+            )
+            .map(|f| f.start.as_millis())
+            .collect();
+        assert_eq!(overlapping, vec![101_000, 102_000]);
+
+        traffic.schedule(start + Duration::from_millis(31_500), || {
+            Duration::from_secs(3600)
+        });
+        // Frames ending more than 30 s ago are dropped
+        let starts: Vec<u64> = traffic.frames.iter().map(|f| f.start.as_millis()).collect();
+        assert_eq!(starts, vec![102_000, 103_000]);
+    }
+}
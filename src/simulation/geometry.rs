@@ -18,7 +18,7 @@ use super::types::{CirclePos, Obstacle, Point, RectPos, Scene};
 ///
 /// * `a` - First point
 /// * `b` - Second point
-/// * `scene` - Scene configuration with pre-calculated scaling factors
+/// * `scene` - Scene configuration with the world unit ↔ meter transform
 ///
 /// # Returns
 ///
@@ -26,17 +26,9 @@ use super::types::{CirclePos, Obstacle, Point, RectPos, Scene};
 ///
 /// # Note
 /// Uses f64 for higher precision to avoid numerical errors with large coordinate spaces.
-/// Applies pre-calculated scale_x and scale_y from scene to handle non-uniform scaling.
+/// Uses the scene's `WorldTransform`, which handles non-uniform scaling per axis.
 pub fn distance2(a: &Point, b: &Point, scene: &Scene) -> f64 {
-    let dx = a.x - b.x;
-    let dy = a.y - b.y;
-
-    // Apply pre-calculated scaling factors to each component before squaring
-    // This handles non-uniform scaling correctly: each axis scaled independently
-    let dx_meters = dx * scene.scale_x;
-    let dy_meters = dy * scene.scale_y;
-
-    dx_meters * dx_meters + dy_meters * dy_meters
+    scene.transform.distance2((a.x, a.y), (b.x, b.y))
 }
 
 /// Convert squared distance to actual distance in meters.
@@ -66,7 +58,9 @@ pub fn distance_from_d2(d2: f64) -> f64 {
 ///
 /// This is the main line-of-sight check used by the radio propagation model.
 /// A transmission from point1 to point2 is blocked if the straight line between
-/// them intersects any obstacle (circle or rectangle).
+/// them intersects any obstacle (circle or rectangle). A `one_way` obstacle only
+/// blocks transmissions crossing it from its back side, so the check is not
+/// symmetric: callers pass the transmitter first.
 ///
 /// ## Degenerate Case Handling
 ///
@@ -105,6 +99,12 @@ pub fn is_intersect(point1: &Point, point2: &Point, obstacles: &[Obstacle]) -> b
     }
 
     for obs in obstacles {
+        if obs
+            .one_way()
+            .is_some_and(|one_way| !one_way.blocks(point1, point2))
+        {
+            continue;
+        }
         match obs {
             Obstacle::Rectangle { position, .. } => {
                 if segment_intersects_rect(point1, point2, &position) {
@@ -170,30 +170,27 @@ fn segment_intersects_rect(p1: &Point, p2: &Point, rect: &RectPos) -> bool {
 }
 
 /// Segment vs. circle intersection using projection and clamped parameter t.
+///
+/// Works in f64 relative to the circle center, so projected coordinates in the
+/// millions (e.g. UTM northings) keep sub-unit precision.
 fn segment_intersects_circle(p1: &Point, p2: &Point, circle: &CirclePos) -> bool {
     // Distance from circle center to segment <= radius
-    let x1 = p1.x as f32;
-    let y1 = p1.y as f32;
-    let x2 = p2.x as f32;
-    let y2 = p2.y as f32;
-    let cx = circle.center.x as f32;
-    let cy = circle.center.y as f32;
-    let r = circle.radius as f32;
+    let x1 = p1.x - circle.center.x;
+    let y1 = p1.y - circle.center.y;
+    let x2 = p2.x - circle.center.x;
+    let y2 = p2.y - circle.center.y;
+    let r = circle.radius;
 
     let dx = x2 - x1;
     let dy = y2 - y1;
     if dx == 0.0 && dy == 0.0 {
-        let ddx = x1 - cx;
-        let ddy = y1 - cy;
-        return ddx * ddx + ddy * ddy <= r * r;
+        return x1 * x1 + y1 * y1 <= r * r;
     }
-    let t = ((cx - x1) * dx + (cy - y1) * dy) / (dx * dx + dy * dy);
-    let t_clamped = t.max(0.0).min(1.0);
+    let t = -(x1 * dx + y1 * dy) / (dx * dx + dy * dy);
+    let t_clamped = t.clamp(0.0, 1.0);
     let closest_x = x1 + t_clamped * dx;
     let closest_y = y1 + t_clamped * dy;
-    let ddx = closest_x - cx;
-    let ddy = closest_y - cy;
-    ddx * ddx + ddy * ddy <= r * r
+    closest_x * closest_x + closest_y * closest_y <= r * r
 }
 
 /// Orientation of ordered triplet (a,b,c): returns 1 if clockwise, -1 if
@@ -263,6 +260,7 @@ pub fn segments_intersect(p1: &Point, q1: &Point, p2: &Point, q2: &Point) -> boo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::simulation::types::OneWay;
 
     fn p(x: f64, y: f64) -> Point {
         Point { x, y }
@@ -318,10 +316,61 @@ mod tests {
                 top_left: p(0.0, 0.0),
                 bottom_right: p(10.0, 10.0),
             },
+            one_way: None,
         }];
         // Point inside rectangle → considered intersecting
         assert!(is_intersect(&p(5.0, 5.0), &p(5.0, 5.0), &obstacles));
         // Point outside → not intersecting
         assert!(!is_intersect(&p(20.0, 20.0), &p(20.0, 20.0), &obstacles));
     }
+
+    #[test]
+    fn intersections_hold_for_negative_and_projected_coordinates() {
+        // Negative coordinates and corners given bottom-right first (inverted Y)
+        let wall = vec![Obstacle::Rectangle {
+            position: RectPos {
+                top_left: p(-10.0, 50.0),
+                bottom_right: p(10.0, -50.0),
+            },
+            one_way: None,
+        }];
+        assert!(is_intersect(&p(-100.0, 0.0), &p(100.0, 0.0), &wall));
+        assert!(!is_intersect(&p(-100.0, 60.0), &p(100.0, 60.0), &wall));
+
+        // A circle of 0.4 units at UTM-scale coordinates, passed 0.3 and 0.5 units away
+        let (east, north) = (651_234.25, 5_271_872.5);
+        let pond = vec![Obstacle::Circle {
+            position: CirclePos {
+                center: p(east, north),
+                radius: 0.4,
+            },
+            one_way: None,
+        }];
+        assert!(is_intersect(
+            &p(east - 100.0, north + 0.3),
+            &p(east + 100.0, north + 0.3),
+            &pond
+        ));
+        assert!(!is_intersect(
+            &p(east - 100.0, north + 0.5),
+            &p(east + 100.0, north + 0.5),
+            &pond
+        ));
+    }
+
+    #[test]
+    fn one_way_obstacle_blocks_only_from_its_back_side() {
+        // A hillside facing +x: blocks paths travelling from -x to +x only
+        let hill = vec![Obstacle::Rectangle {
+            position: RectPos {
+                top_left: p(-10.0, -10.0),
+                bottom_right: p(10.0, 10.0),
+            },
+            one_way: Some(OneWay { front: p(1.0, 0.0) }),
+        }];
+        assert!(is_intersect(&p(-100.0, 0.0), &p(100.0, 0.0), &hill));
+        assert!(!is_intersect(&p(100.0, 0.0), &p(-100.0, 0.0), &hill));
+        // A path along the hillside does not cross from the back
+        assert!(!is_intersect(&p(0.0, -100.0), &p(0.0, 100.0), &hill));
+    }
 }
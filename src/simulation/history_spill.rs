@@ -0,0 +1,209 @@
+//! # History Spill
+//!
+//! Disk spill of node histories for the `spill_to_disk` history policy: entries
+//! pushed out of a node's in-memory radio packet, full message and log line
+//! histories are appended to `<scene>_history/node_<id>.tsv` next to the scene file.
+//! Post-run analysis gets the full history of the node while its memory stays
+//! bounded by the history sizes. Lines are buffered and written in batches; the rest
+//! is flushed when the run stops or the window is closed.
+//!
+//! Each line starts with its kind and the virtual time in milliseconds, tab separated:
+//! - `radio  time  outcome  sender  type  packet/count  size  link_quality  sequence`
+//! - `message  time  direction  sender  type  sequence  length`
+//! - `log  time  level  content`
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::types::{FullMessage, LogLine, NodeMessage};
+
+/// Spilled lines buffered before they are written.
+const SPILL_BATCH: usize = 256;
+
+/// History spill file of one node.
+#[derive(Debug)]
+pub struct HistorySpill {
+    node_id: u32,
+    path: PathBuf,
+    /// Lines not written yet.
+    pending: Vec<String>,
+    /// The file was created in this run (later batches are appended).
+    created: bool,
+}
+
+/// A copy shares the file but not the pending lines, so nothing is written twice.
+impl Clone for HistorySpill {
+    fn clone(&self) -> Self {
+        Self {
+            node_id: self.node_id,
+            path: self.path.clone(),
+            pending: Vec::new(),
+            created: true,
+        }
+    }
+}
+
+impl HistorySpill {
+    /// Spill file of `node_id` in `directory` (the file is replaced on the first write).
+    pub fn new(directory: &Path, node_id: u32) -> Self {
+        Self {
+            node_id,
+            path: directory.join(format!("node_{}.tsv", node_id)),
+            pending: Vec::new(),
+            created: false,
+        }
+    }
+
+    /// Spill a radio packet leaving the in-memory history.
+    pub fn radio_packet(&mut self, msg: &NodeMessage) {
+        let line = radio_packet_line(self.node_id, msg);
+        self.push(line);
+    }
+
+    /// Spill a full message leaving the in-memory history.
+    pub fn full_message(&mut self, msg: &FullMessage) {
+        self.push(format!(
+            "message\t{}\t{}\t{}\t{}\t{}\t{}",
+            msg.timestamp.as_millis(),
+            if msg.is_outgoing { "sent" } else { "received" },
+            msg.sender_node,
+            msg.message_type,
+            msg.sequence,
+            msg.length
+        ));
+    }
+
+    /// Spill a log line leaving the in-memory history.
+    pub fn log_line(&mut self, line: &LogLine) {
+        self.push(format!(
+            "log\t{}\t{:?}\t{}",
+            line.timestamp.as_millis(),
+            line.level,
+            line.content.replace(['\t', '\n', '\r'], " ")
+        ));
+    }
+
+    fn push(&mut self, line: String) {
+        self.pending.push(line);
+        if self.pending.len() >= SPILL_BATCH {
+            self.flush();
+        }
+    }
+
+    /// Write the buffered lines. Failures are logged and the lines dropped, so a full
+    /// disk does not stop the simulation.
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(self.created)
+                    .truncate(!self.created)
+                    .open(&self.path)
+            })
+            .and_then(|mut file| {
+                let mut text = self.pending.join("\n");
+                text.push('\n');
+                file.write_all(text.as_bytes())
+            });
+        if let Err(e) = result {
+            log::warn!(
+                "Failed to spill the history of node {} to {}: {}",
+                self.node_id,
+                self.path.display(),
+                e
+            );
+        }
+        self.created = true;
+        self.pending.clear();
+    }
+}
+
+/// Directory of the spill files: `<scene>_history` next to the scene file.
+pub fn spill_directory(scene_path: &str) -> PathBuf {
+    let scene_path = Path::new(scene_path);
+    let stem = scene_path
+        .file_stem()
+        .map_or("scene".into(), |stem| stem.to_string_lossy());
+    scene_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(format!("{}_history", stem))
+}
+
+/// Spill line of a radio packet in the history of `node_id`.
+fn radio_packet_line(node_id: u32, msg: &NodeMessage) -> String {
+    let outcome = if msg.sender_node == node_id {
+        "tx"
+    } else if msg.missed_while_transmitting {
+        "missed_tx"
+    } else if msg.missed_while_sleeping {
+        "missed_sleep"
+    } else if msg.collision {
+        "collision"
+    } else {
+        "rx"
+    };
+    format!(
+        "radio\t{}\t{}\t{}\t{}\t{}/{}\t{}\t{}\t{}",
+        msg.timestamp.as_millis(),
+        outcome,
+        msg.sender_node,
+        msg.message_type,
+        msg.packet_index,
+        msg.packet_count,
+        msg.packet_size,
+        msg.link_quality,
+        msg.sequence
+            .map_or("-".to_string(), |sequence| sequence.to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_time::Instant;
+
+    #[test]
+    fn test_radio_lines_classify_the_outcome_and_spill_path() {
+        let mut msg = NodeMessage {
+            timestamp: Instant::from_millis(1500),
+            message_type: 6,
+            sender_node: 3,
+            packet_size: 200,
+            packet_index: 2,
+            packet_count: 9,
+            link_quality: 40,
+            collision: false,
+            missed_while_transmitting: false,
+            missed_while_sleeping: false,
+            sequence: Some(17),
+            packet: None,
+        };
+        assert_eq!(
+            radio_packet_line(3, &msg),
+            "radio\t1500\ttx\t3\t6\t2/9\t200\t40\t17"
+        );
+        assert!(radio_packet_line(5, &msg).starts_with("radio\t1500\trx\t"));
+
+        msg.collision = true;
+        msg.sequence = None;
+        assert_eq!(
+            radio_packet_line(5, &msg),
+            "radio\t1500\tcollision\t3\t6\t2/9\t200\t40\t-"
+        );
+
+        assert_eq!(
+            spill_directory("scenes/city.json"),
+            Path::new("scenes").join("city_history")
+        );
+    }
+}
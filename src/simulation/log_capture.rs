@@ -46,6 +46,23 @@ pub fn drain_captured_logs() -> Vec<CapturedLogEntry> {
     }
 }
 
+/// Capture a log line of a node that does not log through this process
+/// (e.g. the output of a node process).
+///
+/// # Parameters
+///
+/// * `node_id` - Node the line belongs to
+/// * `level` - Log level of the line
+/// * `content` - Log message
+pub fn capture_node_log(node_id: u32, level: LogLevel, content: String) {
+    push_log_entry(CapturedLogEntry {
+        node_id,
+        timestamp: Instant::now(),
+        content,
+        level,
+    });
+}
+
 /// Push a captured log entry to the buffer.
 fn push_log_entry(entry: CapturedLogEntry) {
     let mut guard = CAPTURED_LOGS.lock().unwrap();
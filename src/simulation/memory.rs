@@ -0,0 +1,84 @@
+//! # Memory Usage
+//!
+//! Estimates of the simulation's memory footprint, published with the node
+//! statistics about once per second: the per-node histories and event queues (see
+//! `Node::estimated_memory_bytes`), the airtime packets queued over all nodes and,
+//! on Linux, the resident memory of the process. Long runs with thousands of nodes
+//! are bounded by the scene's `history` limits; when the memory exceeds
+//! `history.memory_warning_mb` a warning is logged and raised as an alert toast.
+
+use std::collections::HashMap;
+
+use super::types::{HistoryConfig, Node};
+
+/// Memory usage snapshot of a running simulation.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryUsage {
+    /// Estimated memory of all node histories and queues in bytes.
+    pub estimated_bytes: u64,
+    /// Airtime packets queued over all nodes.
+    pub airtime_packets: usize,
+    /// Resident memory of the process in bytes (Linux only).
+    pub resident_bytes: Option<u64>,
+    /// Warning limit in bytes (scene `history.memory_warning_mb`).
+    pub warning_bytes: u64,
+}
+
+impl MemoryUsage {
+    /// Measure the memory usage of the nodes and the process.
+    pub fn measure(nodes_map: &HashMap<u32, Node>, history: &HistoryConfig) -> Self {
+        Self {
+            estimated_bytes: nodes_map.values().map(Node::estimated_memory_bytes).sum(),
+            airtime_packets: nodes_map
+                .values()
+                .map(|node| node.airtime_waiting_packets.len())
+                .sum(),
+            resident_bytes: resident_memory_bytes(),
+            warning_bytes: history.memory_warning_mb * 1024 * 1024,
+        }
+    }
+
+    /// Whether the process (or, where unknown, the estimate) exceeds the warning limit.
+    pub fn exceeds_warning(&self) -> bool {
+        self.resident_bytes.unwrap_or(self.estimated_bytes) > self.warning_bytes
+    }
+}
+
+/// Resident memory of this process in bytes, from `/proc/self/status`.
+fn resident_memory_bytes() -> Option<u64> {
+    parse_vm_rss(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
+
+/// Parse the `VmRSS:` line (in kB) of a `/proc/<pid>/status` file into bytes.
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vm_rss_is_parsed_and_compared_to_the_limit() {
+        let status = "Name:\tsimulator\nVmPeak:\t  900000 kB\nVmRSS:\t  524288 kB\nThreads:\t12\n";
+        assert_eq!(parse_vm_rss(status), Some(512 * 1024 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tsimulator\n"), None);
+
+        let usage = MemoryUsage {
+            estimated_bytes: 10,
+            resident_bytes: parse_vm_rss(status),
+            warning_bytes: 256 * 1024 * 1024,
+            ..Default::default()
+        };
+        assert!(usage.exceeds_warning());
+        assert!(
+            !MemoryUsage {
+                resident_bytes: None,
+                ..usage
+            }
+            .exceeds_warning()
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Protocol field decoding of radio packets for the node inspector.
+//!
+//! The radio stream only records type, sender, sequence and packet index of each
+//! packet. To follow protocol exchanges (who an echo answers, which block parts a
+//! node asks for, which transaction is relayed), every radio stream entry keeps its
+//! packet (`RecordedPacket`), and the inspector decodes it into (label, value) pairs
+//! when it shows the row detail or searches the stream. Nothing is decoded while the
+//! simulation records transmissions, receptions and collisions.
+//!
+//! Packets of flooded messages also get a `FloodPacketKey`, their identity across
+//! relays, so duplicates can be told from new information.
+//!
+//! Single-packet messages are decoded with the radio library's `RadioMessage`
+//! accessors; for parts of multi-packet messages (AddBlock, AddTransaction) only
+//! the header fields are available, read with the `RadioPacket` header accessors.
+
+use moonblokz_radio_lib::{MessageType, RadioMessage, RadioPacket};
+
+use super::packet_faults::PacketFault;
+
+/// Decoded protocol fields of a packet: (label, value) pairs in display order.
+pub type PacketDetails = Vec<(&'static str, String)>;
+
+/// Packet of a radio stream entry, decoded on demand.
+#[derive(Debug, Clone)]
+pub struct RecordedPacket {
+    /// The packet as transmitted (received packets before fault injection).
+    pub packet: RadioPacket,
+    /// Fault injected into the delivered copy, if any.
+    pub fault: Option<PacketFault>,
+}
+
+impl RecordedPacket {
+    /// Decoded protocol fields, followed by the injected fault.
+    pub fn details(&self) -> PacketDetails {
+        let mut details = decode_packet(&self.packet);
+        if let Some(fault) = self.fault {
+            details.push(("Fault", fault.describe()));
+        }
+        details
+    }
+}
+
+/// Identity of a packet of a flooded message (AddBlock or AddTransaction part):
+/// message type, block (or anchor) sequence, transaction ID and packet index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FloodPacketKey {
+    message_type: u8,
+    sequence: u32,
+    /// Transaction ID of AddTransaction parts, 0 for AddBlock.
+    transaction_id: u32,
+    packet_index: u8,
+}
+
+/// The flood identity of a packet (None for message types that are not flooded).
+pub fn flood_packet_key(packet: &RadioPacket) -> Option<FloodPacketKey> {
+    let message_type = packet.message_type();
+    let transaction_id = if message_type == MessageType::AddTransaction as u8 {
+        packet.payload_checksum()?
+    } else if message_type == MessageType::AddBlock as u8 {
+        0
+    } else {
+        return None;
+    };
+    Some(FloodPacketKey {
+        message_type,
+        sequence: packet.sequence()?,
+        transaction_id,
+        packet_index: packet.packet_index(),
+    })
+}
+
+/// Format packet indices as compact zero-based ranges, e.g. "0-3, 7, 9-10".
+fn format_ranges(indices: impl IntoIterator<Item = u8>) -> String {
+    let mut sorted: Vec<u8> = indices.into_iter().collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = sorted.into_iter().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while let Some(&next) = iter.peek() {
+            if next != end.wrapping_add(1) {
+                break;
+            }
+            end = next;
+            iter.next();
+        }
+        ranges.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{}-{}", start, end)
+        });
+    }
+    if ranges.is_empty() {
+        "-".to_string()
+    } else {
+        ranges.join(", ")
+    }
+}
+
+/// Decode the protocol fields of a radio packet.
+///
+/// # Parameters
+///
+/// * `packet` - The transmitted or received packet
+///
+/// # Returns
+///
+/// The decoded fields; unknown message types yield the payload length only.
+pub fn decode_packet(packet: &RadioPacket) -> PacketDetails {
+    let message_type = packet.message_type();
+    let mut details: PacketDetails = vec![("Payload", format!("{} B", packet.length))];
+
+    // Parts of multi-packet messages: only the header is decodable per packet
+    if message_type == MessageType::AddBlock as u8 {
+        if let Some(sequence) = packet.sequence() {
+            details.push(("Block sequence", format!("#{}", sequence)));
+        }
+        details.push((
+            "Part",
+            format!(
+                "{} of {}",
+                packet.packet_index() + 1,
+                packet.total_packet_count()
+            ),
+        ));
+        return details;
+    }
+    if message_type == MessageType::AddTransaction as u8 {
+        if let Some(anchor_sequence) = packet.sequence() {
+            details.push(("Anchor sequence", format!("#{}", anchor_sequence)));
+        }
+        if let Some(checksum) = packet.payload_checksum() {
+            details.push(("Transaction ID", format!("{:08x}", checksum)));
+        }
+        details.push((
+            "Part",
+            format!(
+                "{} of {}",
+                packet.packet_index() + 1,
+                packet.total_packet_count()
+            ),
+        ));
+        return details;
+    }
+
+    if packet.total_packet_count() != 1 {
+        return details;
+    }
+    let message = RadioMessage::from_single_packet(packet.clone());
+
+    if message_type == MessageType::RequestEcho as u8 {
+        details.push(("Request", "echo from all neighbors".to_string()));
+    } else if message_type == MessageType::Echo as u8 {
+        if let Some((target_node_id, link_quality)) = message.get_echo_data() {
+            details.push(("Echo target", format!("#{}", target_node_id)));
+            details.push(("LQ at sender", link_quality.to_string()));
+        }
+    } else if message_type == MessageType::EchoResult as u8 {
+        if let Some(items) = message.get_echo_result_data_iterator() {
+            for item in items {
+                details.push((
+                    "Neighbor",
+                    format!(
+                        "#{} (LQ out {}, in {})",
+                        item.neighbor_node, item.send_link_quality, item.receive_link_quality
+                    ),
+                ));
+            }
+        }
+    } else if message_type == MessageType::RequestFullBlock as u8 {
+        if let Some(sequence) = message.sequence() {
+            details.push(("Requested block", format!("#{}", sequence)));
+        }
+    } else if message_type == MessageType::RequestBlockPart as u8 {
+        if let Some(sequence) = message.sequence() {
+            details.push(("Requested block", format!("#{}", sequence)));
+        }
+        if let Some(parts) = message.get_request_block_part_iterator() {
+            details.push((
+                "Requested parts",
+                format_ranges(parts.map(|part| part.packet_index)),
+            ));
+        }
+    } else if message_type == MessageType::RequestNewMempoolItem as u8
+        || message_type == MessageType::Support as u8
+    {
+        if let Some(sequence) = message.sequence() {
+            details.push(("Sequence", format!("#{}", sequence)));
+        }
+    }
+
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_ranges_merges_consecutive_indices() {
+        assert_eq!(format_ranges([7, 0, 1, 2, 3, 10, 9]), "0-3, 7, 9-10");
+        assert_eq!(format_ranges([5]), "5");
+        assert_eq!(format_ranges([]), "-");
+        assert_eq!(format_ranges([254, 255]), "254-255");
+    }
+}
@@ -11,9 +11,42 @@
 //! ## Module Organization
 //!
 //! - `types`: Core data structures (Scene, Node, messages, channels)
+//! - `arrival_delay`: Propagation and receiver processing delay of packet arrivals
+//! - `backhaul`: Gateway backhaul delivery statistics and message re-injection
+//! - `bootstrap`: Node start schedule (simultaneous, staggered or scripted joins)
+//! - `calibration`: Path loss parameter fitting from measured RSSI samples
+//! - `checkpoints`: Periodic metrics snapshots of long runs with a retention limit
+//! - `connectivity_graph`: Reachability graph export as Graphviz DOT or GraphML
+//! - `coverage_planner`: Node placement suggestions for a target coverage and redundancy
+//! - `determinism_audit`: Two runs of a scene with the same seed, compared event by event
+//! - `duty_cycle`: Periodic receiver sleep schedules of duty-cycled nodes
+//! - `event_bus`: Radio events published to observers (UI, statistics, capture, alerts) with packet counters
+//! - `event_ring`: Bounded ring of recent events written to a crash dump on panic
+//! - `event_store`: Central time-ordered store of all radio events with a per-node index for searches
+//! - `external_traffic`: Duty-cycled uplinks of a co-channel LoRaWAN network interfering with the mesh
 //! - `signal_calculations`: Radio signal and timing calculations
 //! - `geometry`: Line-of-sight and obstacle intersection logic
+//! - `history_spill`: Disk spill of node histories for the `spill_to_disk` history policy
+//! - `link_histograms`: Per-link RSSI and SINR histograms of received and collided packets
+//! - `memory`: Memory usage estimates of node histories and queues, with a warning limit
+//! - `message_decoder`: Protocol field decoding of packets for the inspector
 //! - `node_task`: Per-node task managing radio communication
+//! - `physics`: Parallel (rayon) stage for link discovery and reception SINR/collision evaluation
+//! - `propagation`: Selectable path loss models (log-distance, two-ray ground, Okumura-Hata)
+//! - `queue_pool`: Reuse of the leaked node channels across sessions
+//! - `packet_faults`: Truncation and bit corruption of delivered packets
+//! - `origin_sampling`: Distribution measurement repeated from origins chosen by strategy, aggregated per origin class
+//! - `node_process`: Alternative node backend running the firmware binary as subprocesses
+//! - `signal_probe`: Link budget of a transmitter at an arbitrary location (map probe tool)
+//! - `rng`: Seedable random number generator shared by all random draws
+//! - `scene_conversion`: Analyzer scene to simulation scene conversion (TX powers matching the observed ranges)
+//! - `scripting`: Rhai experiment scripts observing simulation events and issuing commands
+//! - `speed_target`: Auto speed solving for a virtual duration within a wall-clock budget
+//! - `stage_timing`: Wall-clock timing of the network loop stages for the loop profile window
+//! - `stop_conditions`: Conditions ending unattended runs, run summary and headless runner
+//! - `stress_test`: Throughput stress test with saturation detection
+//! - `unreached_diagnosis`: Classification of the nodes a stalled measurement did not reach
+//! - `watchdog`: Node task input queue health (dropped deliveries, stalled tasks)
 //! - `network_task`: Central simulation task coordinating all nodes
 //!
 //! ## Public API
@@ -22,12 +55,45 @@
 //! Embassy executor. It communicates with the UI via channels defined in
 //! the parent module.
 
+pub mod arrival_delay;
+pub mod backhaul;
+pub mod bootstrap;
+pub mod calibration;
+pub mod checkpoints;
+pub mod connectivity_graph;
+pub mod coverage_planner;
+pub mod determinism_audit;
+pub mod duty_cycle;
+pub mod event_bus;
+pub mod event_ring;
+pub mod event_store;
+pub mod external_traffic;
 pub mod geometry;
+pub mod history_spill;
+pub mod link_histograms;
 pub mod log_capture;
+pub mod memory;
+pub mod message_decoder;
 pub mod network;
+pub mod node_process;
 pub mod node_task;
+pub mod origin_sampling;
+pub mod packet_faults;
+pub mod physics;
+pub mod propagation;
+pub mod queue_pool;
+pub mod rng;
+pub mod scene_conversion;
+pub mod scripting;
 pub mod signal_calculations;
+pub mod signal_probe;
+pub mod speed_target;
+pub mod stage_timing;
+pub mod stop_conditions;
+pub mod stress_test;
 pub mod types;
+pub mod unreached_diagnosis;
+pub mod watchdog;
 
 // Re-export the main network task for convenience
 pub use network::network_task;
@@ -14,27 +14,97 @@ use embassy_executor::Spawner;
 use embassy_futures::select::{Either3, select3};
 use embassy_time::{Duration, Instant, Timer};
 use moonblokz_radio_lib::{MessageType, RadioMessage, RadioPacket, ScoringMatrix};
+use rand::Rng;
+use rand::seq::IteratorRandom;
+use serde::Deserialize;
+use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
-use std::fs;
-
-use crate::common::connection_matrix::ConnectionMatrixParser;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+use crate::common::annotations::validate_annotations;
+use crate::common::connection_matrix::{ConnectionMatrixParser, parse_link_matrices};
+use crate::common::measurement_ids;
+use crate::common::provenance;
+use crate::common::scene::{SceneLoadError, SceneMode, read_scene_value};
+use crate::common::scene_schema::validate_scene_value;
+use crate::common::units::WorldTransform;
 use crate::{
-    UICommandQueueReceiver, UIRefreshQueueSender, time_driver,
-    ui::{NodeInfo, NodeUIState, UICommand, UIRefreshState},
+    UICommandQueueReceiver, UIRefreshQueueSender, session, shutdown, time_driver,
+    ui::{MeasurementKind, NodeInfo, NodeUIState, UICommand, UIRefreshState},
 };
 
-use super::geometry::{distance_from_d2, distance2, is_intersect};
+use super::arrival_delay::arrival_offset;
+use super::backhaul::BackhaulTracker;
+use super::bootstrap::BootstrapSchedule;
+use super::checkpoints::CheckpointWriter;
+use super::connectivity_graph;
+use super::duty_cycle::SleepSchedule;
+use super::event_bus::{
+    AlertEngine, CaptureWriter, CollisionRateLimit, EventBus, EventRecorder, PacketCounters,
+    SharedNodeStatistics, SimulationEvent, StatsCollector, UiForwarder,
+};
+use super::event_ring::record_event;
+use super::event_store::{EventStore, EventStoreObserver};
+use super::external_traffic::ExternalTraffic;
+use super::history_spill::{HistorySpill, spill_directory};
+use super::link_histograms::{LinkHistogramObserver, LinkHistograms};
 use super::log_capture::drain_captured_logs;
-use super::node_task::node_task;
+use super::memory::MemoryUsage;
+use super::message_decoder::RecordedPacket;
+use super::node_process::node_process_task;
+use super::node_task::{NODE_INPUT_QUEUES, RunningNodeTask, node_task, running_node_tasks};
+use super::origin_sampling::{OriginCandidate, OriginSamplingRunner};
+use super::packet_faults::inject_fault;
+use super::physics::{
+    LinkCandidate, ReceptionOutcome, evaluate_pending_receptions, find_link_candidates,
+};
+use super::queue_pool::QueuePool;
+use super::rng::{init_simulation_rng, with_simulation_rng};
+use super::scripting::{ScriptAction, ScriptHost, ScriptObserver};
 use super::signal_calculations::{
-    calculate_air_time, calculate_effective_distance, calculate_rssi, calculate_snr_limit,
-    dbm_to_mw, get_cad_time, mw_to_dbm,
+    ReceptionModel, calculate_air_time, calculate_effective_distance,
+    calculate_packet_success_probability, calculate_receiving_limit_with_basic_noise,
+    calculate_rssi, calculate_snr_limit, get_cad_time,
+};
+use super::signal_probe;
+use super::speed_target::SpeedTargetController;
+use super::stage_timing::{LoopStage, StageTimer};
+use super::stop_conditions::{
+    RunProgress, RunTotals, effective_conditions, run_summary, stop_reason, summary_path,
 };
+use super::stress_test::StressTestRunner;
 use super::types::{
-    AirtimeWaitingPacket, CAPTURE_THRESHOLD, CadItem, FullMessage, LogLine,
-    NODE_FULL_MESSAGES_CAPACITY, NODE_MESSAGES_CAPACITY, Node, NodeInputMessage, NodeInputQueue,
-    NodeMessage, NodeOutputMessage, NodeOutputPayload, NodesOutputQueue, Point, Scene,
+    AirtimeWaitingPacket, CadItem, FullMessage, HistoryConfig, HistoryPolicy, LogLine,
+    MeasurementTracker, Node, NodeInputMessage, NodeMessage, NodeOutputMessage, NodeOutputPayload,
+    NodeRole, NodeStartConfig, NodesOutputQueue, Obstacle, Point, QueueFullPolicy, Scene,
+    measurement_kind,
 };
+use super::unreached_diagnosis;
+use super::watchdog;
+
+/// Nodes spawned between two progress updates (and cancellation checks) of a scene load.
+const NODE_SPAWN_BATCH: usize = 100;
+
+/// Payload size of the AddTransaction originated by a transaction measurement (bytes).
+const TRANSACTION_MEASUREMENT_PAYLOAD_SIZE: usize = 200;
+
+/// Lowest speed the auto speed may set, to avoid stalling the simulation (percent).
+pub const AUTO_SPEED_MIN_PERCENT: u32 = 20;
+
+/// Highest speed the auto speed may set, the UI slider's max (percent).
+pub const AUTO_SPEED_MAX_PERCENT: u32 = 1000;
+
+/// Random TX delay forced from the command line (parameter sweeps), replacing the
+/// scene's `radio_module_config.tx_maximum_random_delay`. Per-node overrides still apply.
+static TX_RANDOM_DELAY_OVERRIDE: Mutex<Option<u16>> = Mutex::new(None);
+
+/// Force the random TX delay (ms) of the scenes loaded afterwards.
+pub fn set_tx_random_delay_override(delay_ms: u16) {
+    *TX_RANDOM_DELAY_OVERRIDE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some(delay_ms);
+}
 
 /// Wait for a configuration file path from UI commands.
 ///
@@ -62,27 +132,30 @@ async fn wait_for_config_file(ui_command_rx: &UICommandQueueReceiver) -> String
 
 /// Load and parse the scene configuration from a file.
 ///
-/// Reads the JSON file, parses it into a Scene struct, and reports errors to the UI
-/// via alerts if file reading or JSON parsing fails.
+/// Reads the JSON file (with includes resolved), parses it into a Scene struct, and
+/// reports errors to the UI via alerts if file reading or JSON parsing fails.
 ///
 /// # Parameters
 ///
 /// * `config_file_path` - Path to the scene JSON file
-/// * `ui_refresh_tx` - Channel for sending error alerts to the UI
+/// * `ui_refresh_tx` - Channel for sending error alerts and load progress to the UI
+/// * `ui_command_rx` - Receiver for UI commands (checked for a load cancellation)
 ///
 /// # Returns
 ///
-/// `Some(Scene)` if successful, `None` if file read or parse errors occurred.
+/// `Some(Scene)` if successful, `None` if file read or parse errors occurred or the
+/// load was cancelled.
 /// Validate scene configuration to reject malformed inputs.
 ///
 /// Checks for common issues that would cause runtime problems:
 /// - Excessive node count (>10000 causes UI/performance issues)
-/// - Node positions outside world bounds (0-10000)
+/// - Node positions outside the world bounds (`world_top_left` / `world_bottom_right`)
 /// - Unrealistic radio strength values (outside -30 to +30 dBm)
 /// - Invalid LoRa parameters (SF must be 5-12, bandwidth must be positive)
 /// - Invalid path loss parameters (exponent must be positive)
 /// - Obstacle geometry issues (invalid rectangles, zero-radius circles)
 /// - Duplicate node IDs
+/// - TX random delays above `MAX_TX_RANDOM_DELAY_MS` (scene or per-node override)
 ///
 /// # Parameters
 ///
@@ -92,8 +165,6 @@ async fn wait_for_config_file(ui_command_rx: &UICommandQueueReceiver) -> String
 ///
 /// `Ok(())` if validation passes, `Err(String)` with error description if validation fails.
 fn validate_scene(scene: &Scene) -> Result<(), String> {
-    // World coordinate bounds as per Obstacle documentation
-    const MAX_WORLD_COORD: f64 = 10000.0;
     const MAX_NODES: usize = 10000;
     const MIN_RADIO_STRENGTH: f32 = -50.0;
     const MAX_RADIO_STRENGTH: f32 = 50.0;
@@ -121,10 +192,13 @@ fn validate_scene(scene: &Scene) -> Result<(), String> {
     // Validate each node
     for node in &scene.nodes {
         // Check position bounds
-        if node.position.x > MAX_WORLD_COORD || node.position.y > MAX_WORLD_COORD {
+        if !scene.transform.contains(node.position.x, node.position.y) {
             return Err(format!(
-                "Node {} position ({}, {}) exceeds world bounds (0-{})",
-                node.node_id, node.position.x, node.position.y, MAX_WORLD_COORD
+                "Node {} position ({}, {}) is outside the world bounds {}",
+                node.node_id,
+                node.position.x,
+                node.position.y,
+                scene.transform.bounds_label()
             ));
         }
 
@@ -135,6 +209,124 @@ fn validate_scene(scene: &Scene) -> Result<(), String> {
                 node.node_id, node.radio_strength, MIN_RADIO_STRENGTH, MAX_RADIO_STRENGTH
             ));
         }
+        scene
+            .regulatory_limits
+            .check_tx_power(node.radio_strength)
+            .map_err(|e| format!("Node {} radio_strength {}", node.node_id, e))?;
+    }
+
+    // Validate the spreading factor against the regional limits
+    let limits = &scene.regulatory_limits;
+    let spreading_factor = scene.lora_parameters.spreading_factor;
+    if limits
+        .min_spreading_factor
+        .is_some_and(|min| spreading_factor < min)
+        || limits
+            .max_spreading_factor
+            .is_some_and(|max| spreading_factor > max)
+    {
+        return Err(format!(
+            "lora_parameters spreading_factor {} is outside the {} limits (SF{}-{})",
+            spreading_factor,
+            limits.region.as_deref().unwrap_or("regulatory"),
+            limits.min_spreading_factor.unwrap_or(5),
+            limits.max_spreading_factor.unwrap_or(12)
+        ));
+    }
+
+    // Validate the random TX delay (channel access jitter)
+    let tx_delays = std::iter::once((
+        "radio_module_config",
+        None,
+        scene.radio_module_config.tx_maximum_random_delay,
+    ))
+    .chain(scene.nodes.iter().filter_map(|node| {
+        node.tx_maximum_random_delay
+            .map(|delay| ("node", Some(node.node_id), delay))
+    }));
+    for (source, node_id, delay) in tx_delays {
+        if delay > MAX_TX_RANDOM_DELAY_MS {
+            let source = node_id.map_or(source.to_string(), |id| format!("node {}", id));
+            return Err(format!(
+                "{} tx_maximum_random_delay {} ms exceeds the maximum of {} ms",
+                source, delay, MAX_TX_RANDOM_DELAY_MS
+            ));
+        }
+    }
+
+    // Validate the per-node history limits
+    if scene.history.radio_packets == 0
+        || scene.history.full_messages == 0
+        || scene.history.log_lines == 0
+    {
+        return Err(
+            "history radio_packets, full_messages and log_lines must be at least 1".to_string(),
+        );
+    }
+    let keeps_nothing =
+        |policy: Option<HistoryPolicy>| policy == Some(HistoryPolicy::KeepMinutes { minutes: 0 });
+    if keeps_nothing(Some(scene.history.policy))
+        || scene
+            .nodes
+            .iter()
+            .any(|node| keeps_nothing(node.history_policy))
+    {
+        return Err("history policy keep_minutes must keep at least 1 minute".to_string());
+    }
+
+    // Validate the packet fault probabilities
+    let faults = &scene.packet_faults;
+    if !(0.0..=1.0).contains(&faults.truncation_probability)
+        || !(0.0..=1.0).contains(&faults.corruption_probability)
+        || faults.truncation_probability + faults.corruption_probability > 1.0
+    {
+        return Err(
+            "packet_faults probabilities must be within 0-1 and sum to at most 1".to_string(),
+        );
+    }
+
+    // Validate the receiver sleep schedules
+    if scene
+        .duty_cycle
+        .iter()
+        .chain(
+            scene
+                .nodes
+                .iter()
+                .filter_map(|node| node.duty_cycle.as_ref()),
+        )
+        .any(|config| config.on_ms == 0)
+    {
+        return Err("duty_cycle on_ms must be at least 1".to_string());
+    }
+
+    // Validate the external LoRaWAN traffic
+    if let Some(external) = &scene.external_traffic {
+        if external.uplinks_per_hour <= 0.0 {
+            return Err("external_traffic uplinks_per_hour must be positive".to_string());
+        }
+        if external.duty_cycle <= 0.0 || external.duty_cycle > 1.0 {
+            return Err("external_traffic duty_cycle must be within (0, 1]".to_string());
+        }
+        if external.payload_size == 0 || external.payload_size > 255 {
+            return Err("external_traffic payload_size must be within 1-255 bytes".to_string());
+        }
+    }
+
+    // Validate the checkpoint cadence
+    if scene
+        .checkpoints
+        .as_ref()
+        .is_some_and(|config| config.interval_minutes == 0)
+    {
+        return Err("checkpoints interval_minutes must be at least 1".to_string());
+    }
+
+    // Validate the node start schedule
+    if let Some(NodeStartConfig::Staggered { interval, jitter }) = &scene.node_start {
+        if *interval < 0.0 || *jitter < 0.0 {
+            return Err("node_start interval and jitter must be non-negative".to_string());
+        }
     }
 
     // Validate LoRa parameters
@@ -164,28 +356,171 @@ fn validate_scene(scene: &Scene) -> Result<(), String> {
     if scene.path_loss_parameters.shadowing_sigma < 0.0 {
         return Err("Invalid shadowing_sigma, must be non-negative".to_string());
     }
+    scene.propagation_model.validate()?;
+
+    // Validate automatic measurement
+    if let Some(auto) = &scene.auto_measurement {
+        if !node_ids.contains(&auto.origin_node_id) {
+            return Err(format!(
+                "auto_measurement origin_node_id {} does not exist in the scene",
+                auto.origin_node_id
+            ));
+        }
+        if auto.repeat_count == 0 {
+            return Err("auto_measurement repeat_count must be at least 1".to_string());
+        }
+        if auto.repeat_count > 1 && auto.repeat_interval == 0 {
+            return Err(
+                "auto_measurement repeat_interval must be positive when repeat_count > 1"
+                    .to_string(),
+            );
+        }
+    }
+
+    // Validate stress test
+    if let Some(stress_test) = &scene.stress_test {
+        if stress_test.initial_rate <= 0.0 || stress_test.rate_step <= 0.0 {
+            return Err("stress_test initial_rate and rate_step must be positive".to_string());
+        }
+        if stress_test.step_duration == 0 {
+            return Err("stress_test step_duration must be positive".to_string());
+        }
+        if stress_test.payload_size == 0 {
+            return Err("stress_test payload_size must be positive".to_string());
+        }
+    }
+
+    // Validate origin sampling
+    if let Some(sampling) = &scene.origin_sampling {
+        if sampling.strategies.is_empty() || sampling.origins_per_strategy == 0 {
+            return Err(
+                "origin_sampling needs at least one strategy and one origin per strategy"
+                    .to_string(),
+            );
+        }
+        if sampling.interval == 0 {
+            return Err("origin_sampling interval must be positive".to_string());
+        }
+    }
+
+    // Validate the experiment script
+    if let Some(script) = &scene.script {
+        if script.tick_interval == 0 {
+            return Err("script tick_interval must be positive".to_string());
+        }
+    }
+
+    // Validate the node queue quotas of the chosen policy
+    match scene.node_queue.full_policy {
+        QueueFullPolicy::Block if scene.node_queue.max_block_ms == 0 => {
+            return Err(
+                "node_queue max_block_ms must be positive for the block policy".to_string(),
+            );
+        }
+        QueueFullPolicy::Grow if scene.node_queue.max_overflow == 0 => {
+            return Err("node_queue max_overflow must be positive for the grow policy".to_string());
+        }
+        _ => {}
+    }
+
+    // Validate speed profile
+    for (idx, step) in scene.speed_profile.iter().enumerate() {
+        if step.speed_percent == 0 || step.speed_percent > 1000 {
+            return Err(format!(
+                "speed_profile step {} has invalid speed_percent {}, must be 1-1000",
+                idx, step.speed_percent
+            ));
+        }
+        if idx > 0 && step.start_time <= scene.speed_profile[idx - 1].start_time {
+            return Err(format!(
+                "speed_profile step {} start_time must be greater than the previous step's",
+                idx
+            ));
+        }
+    }
+
+    validate_obstacles(&scene.obstacles, &scene.transform)?;
+    validate_annotations(&scene.annotations)?;
+
+    Ok(())
+}
+
+/// Upper limit of the random TX delay (ms); longer delays stall relaying.
+const MAX_TX_RANDOM_DELAY_MS: u16 = 10_000;
+
+/// Warnings about random TX delays too short to spread the relays of a packet.
+///
+/// Neighbors relaying the same packet start within `tx_maximum_random_delay` of
+/// each other; if that window is shorter than the airtime of a full packet, most of
+/// their transmissions overlap and collide.
+///
+/// # Returns
+///
+/// One warning per affected setting (scene default and per-node overrides).
+fn tx_jitter_warnings(scene: &Scene) -> Vec<String> {
+    let airtime_ms = (calculate_air_time(&scene.lora_parameters, 255) * 1000.0) as u64;
+    let too_short = |delay: u16| (delay as u64) < airtime_ms;
+    let mut warnings = Vec::new();
+    let default_delay = scene.radio_module_config.tx_maximum_random_delay;
+    if too_short(default_delay)
+        && scene
+            .nodes
+            .iter()
+            .any(|n| n.tx_maximum_random_delay.is_none())
+    {
+        warnings.push(format!(
+            "tx_maximum_random_delay {} ms is shorter than the {} ms airtime of a full packet; relays of the same packet will mostly collide",
+            default_delay, airtime_ms
+        ));
+    }
+    let short_nodes: Vec<String> = scene
+        .nodes
+        .iter()
+        .filter(|n| n.tx_maximum_random_delay.is_some_and(too_short))
+        .map(|n| n.node_id.to_string())
+        .collect();
+    if !short_nodes.is_empty() {
+        warnings.push(format!(
+            "Nodes {} override tx_maximum_random_delay below the {} ms airtime of a full packet",
+            short_nodes.join(", "),
+            airtime_ms
+        ));
+    }
+    warnings
+}
 
-    // Validate obstacles
-    for (idx, obstacle) in scene.obstacles.iter().enumerate() {
+/// Validate obstacle geometry (world bounds, corner order, non-zero radius).
+///
+/// Used for the scene's obstacles and for obstacles edited at runtime.
+///
+/// # Parameters
+///
+/// * `obstacles` - Obstacles to validate
+/// * `transform` - World transform of the scene (bounds and meter scale of circle radii)
+///
+/// # Returns
+///
+/// `Ok(())` if all obstacles are valid, `Err(String)` describing the first invalid one otherwise.
+fn validate_obstacles(obstacles: &[Obstacle], transform: &WorldTransform) -> Result<(), String> {
+    for (idx, obstacle) in obstacles.iter().enumerate() {
         match obstacle {
-            super::types::Obstacle::Rectangle { position } => {
+            Obstacle::Rectangle { position, .. } => {
                 // Check bounds
-                if position.top_left.x > MAX_WORLD_COORD
-                    || position.top_left.y > MAX_WORLD_COORD
-                    || position.bottom_right.x > MAX_WORLD_COORD
-                    || position.bottom_right.y > MAX_WORLD_COORD
+                if !transform.contains(position.top_left.x, position.top_left.y)
+                    || !transform.contains(position.bottom_right.x, position.bottom_right.y)
                 {
                     return Err(format!(
-                        "Obstacle {} (rectangle) has coordinates exceeding world bounds (0-{})",
-                        idx, MAX_WORLD_COORD
+                        "Obstacle {} (rectangle) has coordinates outside the world bounds {}",
+                        idx,
+                        transform.bounds_label()
                     ));
                 }
-                // Check that top-left is actually top-left and bottom-right is bottom-right
-                if position.top_left.x >= position.bottom_right.x
-                    || position.top_left.y >= position.bottom_right.y
+                // Corners may be given in either order (inverted axes), but must span an area
+                if position.top_left.x == position.bottom_right.x
+                    || position.top_left.y == position.bottom_right.y
                 {
                     return Err(format!(
-                        "Obstacle {} (rectangle) has invalid geometry: top-left ({}, {}) must be strictly less than bottom-right ({}, {})",
+                        "Obstacle {} (rectangle) has invalid geometry: top-left ({}, {}) and bottom-right ({}, {}) must differ on both axes",
                         idx,
                         position.top_left.x,
                         position.top_left.y,
@@ -194,62 +529,91 @@ fn validate_scene(scene: &Scene) -> Result<(), String> {
                     ));
                 }
             }
-            super::types::Obstacle::Circle { position } => {
+            Obstacle::Circle { position, .. } => {
                 // Check bounds
-                if position.center.x > MAX_WORLD_COORD || position.center.y > MAX_WORLD_COORD {
+                if !transform.contains(position.center.x, position.center.y) {
                     return Err(format!(
-                        "Obstacle {} (circle) center ({}, {}) exceeds world bounds (0-{})",
-                        idx, position.center.x, position.center.y, MAX_WORLD_COORD
+                        "Obstacle {} (circle) center ({}, {}) is outside the world bounds {}",
+                        idx,
+                        position.center.x,
+                        position.center.y,
+                        transform.bounds_label()
                     ));
                 }
                 // Check radius is non-zero and reasonable
                 if position.radius == 0.0 {
                     return Err(format!("Obstacle {} (circle) has zero radius", idx));
                 }
-                // Check circle doesn't extend beyond world bounds
-                let max_extent_x = position.center.x + position.radius;
-                let max_extent_y = position.center.y + position.radius;
-                if max_extent_x > MAX_WORLD_COORD || max_extent_y > MAX_WORLD_COORD {
+                // Check circle doesn't extend beyond world bounds (radius is in meters)
+                let (radius_x, radius_y) =
+                    transform.offset_to_units(position.radius, position.radius);
+                if !transform.contains(position.center.x - radius_x, position.center.y - radius_y)
+                    || !transform
+                        .contains(position.center.x + radius_x, position.center.y + radius_y)
+                {
                     return Err(format!(
-                        "Obstacle {} (circle) extends beyond world bounds (0-{})",
-                        idx, MAX_WORLD_COORD
+                        "Obstacle {} (circle) extends beyond the world bounds {}",
+                        idx,
+                        transform.bounds_label()
                     ));
                 }
             }
         }
+        if let Some(one_way) = obstacle.one_way() {
+            if one_way.front.x == 0.0 && one_way.front.y == 0.0 {
+                return Err(format!(
+                    "Obstacle {} one_way front direction must not be zero",
+                    idx
+                ));
+            }
+        }
     }
 
     Ok(())
 }
 
-async fn load_scene(config_file_path: &str, ui_refresh_tx: &UIRefreshQueueSender) -> Option<Scene> {
-    let file_result = fs::read_to_string(config_file_path)
-        .with_context(|| format!("Failed to read file: {config_file_path}"));
-
-    let data = match file_result {
+async fn load_scene(
+    config_file_path: &str,
+    ui_refresh_tx: &UIRefreshQueueSender,
+    ui_command_rx: &UICommandQueueReceiver,
+) -> Option<Scene> {
+    if report_load_progress("Reading scene", 0, 1, ui_refresh_tx, ui_command_rx).await {
+        return None;
+    }
+    let data = match read_scene_value(config_file_path) {
         Ok(data) => data,
+        Err(SceneLoadError::FileReadError(err)) => {
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Error reading config file: {}",
+                err
+            )));
+            return None;
+        }
         Err(err) => {
-            ui_refresh_tx
-                .send(UIRefreshState::Alert(format!(
-                    "Error reading config file: {}",
-                    err
-                )))
-                .await;
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Error parsing config file: {}",
+                err
+            )));
             return None;
         }
     };
 
-    let result = serde_json::from_str::<Scene>(&data).context("Invalid JSON format");
+    provenance::record_scene(config_file_path, &data);
+    let result = Scene::deserialize(&data).context("Invalid JSON format");
 
     let mut scene = match result {
         Ok(scene) => scene,
         Err(err) => {
-            ui_refresh_tx
-                .send(UIRefreshState::Alert(format!(
-                    "Error parsing config file: {}",
-                    err
-                )))
-                .await;
+            // The schema violations locate what the serde error only hints at
+            let violations: String = validate_scene_value(&data, SceneMode::Simulation)
+                .iter()
+                .take(10)
+                .map(|violation| format!("\n{}", violation))
+                .collect();
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Error parsing config file: {}{}",
+                err, violations
+            )));
             return None;
         }
     };
@@ -263,26 +627,138 @@ async fn load_scene(config_file_path: &str, ui_refresh_tx: &UIRefreshQueueSender
         }
     }
 
-    // Pre-calculate scaling factors for distance calculations
-    let world_width = scene.world_bottom_right.x - scene.world_top_left.x;
-    let world_height = scene.world_bottom_right.y - scene.world_top_left.y;
-    scene.scale_x = scene.width / world_width;
-    scene.scale_y = scene.height / world_height;
+    // Node process binaries are resolved like the background image
+    if let Some(process) = scene.node_process.as_mut() {
+        if let Some(parent_dir) = std::path::Path::new(config_file_path).parent() {
+            process.binary = parent_dir
+                .join(&process.binary)
+                .to_string_lossy()
+                .to_string();
+        }
+        log::warn!(
+            "Nodes run as processes of {}; firmware timing is wall-clock, keep the simulation speed at 100%",
+            process.binary
+        );
+    }
+
+    // The initial link matrix file is resolved like the background image
+    if let Some(initial) = scene
+        .link_matrix
+        .as_mut()
+        .and_then(|config| config.initial.as_mut())
+    {
+        if let Some(parent_dir) = std::path::Path::new(config_file_path).parent() {
+            *initial = parent_dir.join(&*initial).to_string_lossy().to_string();
+        }
+    }
+
+    // The event capture file is resolved like the background image
+    if let Some(event_capture) = scene.event_capture.as_mut() {
+        if let Some(parent_dir) = std::path::Path::new(config_file_path).parent() {
+            *event_capture = parent_dir
+                .join(&*event_capture)
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+
+    // The experiment script and its metrics file are resolved like the background image
+    if let Some(script) = scene.script.as_mut() {
+        if let Some(parent_dir) = std::path::Path::new(config_file_path).parent() {
+            script.path = parent_dir.join(&script.path).to_string_lossy().to_string();
+            if let Some(metrics_file) = script.metrics_file.as_mut() {
+                *metrics_file = parent_dir
+                    .join(&*metrics_file)
+                    .to_string_lossy()
+                    .to_string();
+            }
+        }
+    }
+
+    if let Some(delay) = *TX_RANDOM_DELAY_OVERRIDE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+    {
+        log::info!("tx_maximum_random_delay forced to {} ms", delay);
+        scene.radio_module_config.tx_maximum_random_delay = delay;
+    }
+
+    // Build the world unit ↔ meter transform used for all distance calculations
+    scene.transform = match WorldTransform::from_scene(
+        (scene.world_top_left.x, scene.world_top_left.y),
+        (scene.world_bottom_right.x, scene.world_bottom_right.y),
+        scene.width,
+        scene.height,
+        scene.meters_per_unit,
+    ) {
+        Ok(transform) => transform,
+        Err(err) => {
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Invalid scene configuration: {}",
+                err
+            )));
+            return None;
+        }
+    };
 
     // Validate the parsed scene before returning
+    if report_load_progress("Validating scene", 0, 1, ui_refresh_tx, ui_command_rx).await {
+        return None;
+    }
     if let Err(validation_error) = validate_scene(&scene) {
-        ui_refresh_tx
-            .send(UIRefreshState::Alert(format!(
-                "Invalid scene configuration: {}",
-                validation_error
-            )))
-            .await;
+        ui_refresh_tx.send(UIRefreshState::Alert(format!(
+            "Invalid scene configuration: {}",
+            validation_error
+        )));
         return None;
     }
+    for warning in tx_jitter_warnings(&scene) {
+        log::warn!("{}", warning);
+    }
 
     Some(scene)
 }
 
+/// Publish the progress of the scene load and check whether the user cancelled it.
+///
+/// Yields to the executor so the load does not monopolize the simulation thread.
+/// Other UI commands received while loading are ignored.
+///
+/// # Parameters
+///
+/// * `stage` - Current load stage
+/// * `done` - Completed steps of the stage
+/// * `total` - Total steps of the stage
+/// * `ui_refresh_tx` - Channel for the progress update and the cancellation alert
+/// * `ui_command_rx` - Receiver for UI commands
+///
+/// # Returns
+///
+/// `true` if the load was cancelled.
+async fn report_load_progress(
+    stage: &'static str,
+    done: usize,
+    total: usize,
+    ui_refresh_tx: &UIRefreshQueueSender,
+    ui_command_rx: &UICommandQueueReceiver,
+) -> bool {
+    ui_refresh_tx.send(UIRefreshState::SceneLoadProgress(stage, done, total));
+    embassy_futures::yield_now().await;
+    let mut cancelled = false;
+    while let Ok(command) = ui_command_rx.try_receive() {
+        match command {
+            // Leaving the session while loading cancels the load
+            UICommand::CancelSceneLoad | UICommand::EndSession => cancelled = true,
+            other => log::debug!("Ignoring command while loading the scene: {:?}", other),
+        }
+    }
+    if cancelled {
+        log::info!("Scene loading cancelled ({})", stage);
+        ui_refresh_tx.send(UIRefreshState::Alert("Scene loading cancelled".to_string()));
+    }
+    cancelled
+}
+
 /// Initialize and publish scene state to the UI.
 ///
 /// Sends initial updates to the UI including:
@@ -293,123 +769,199 @@ async fn load_scene(config_file_path: &str, ui_refresh_tx: &UIRefreshQueueSender
 /// # Parameters
 ///
 /// * `scene` - The loaded scene configuration
+/// * `bootstrap` - Node start schedule (delayed nodes are shown switched off)
 /// * `ui_refresh_tx` - Channel for sending UI updates
-async fn initialize_scene_ui(scene: &Scene, ui_refresh_tx: &UIRefreshQueueSender) {
+async fn initialize_scene_ui(
+    scene: &Scene,
+    bootstrap: &BootstrapSchedule,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
     let scoring_matrix = ScoringMatrix::new_from_encoded(&scene.radio_module_config.scoring_matrix);
     let poor_limit = scoring_matrix.poor_limit;
     let excellent_limit = scoring_matrix.excellent_limit;
 
-    _ = ui_refresh_tx
-        .send(UIRefreshState::PoorAndExcellentLimits(
-            poor_limit,
-            excellent_limit,
-        ))
-        .await;
+    _ = ui_refresh_tx.send(UIRefreshState::PoorAndExcellentLimits(
+        poor_limit,
+        excellent_limit,
+    ));
 
     // Publish initial nodes to the UI (radio_strength rendered as effective distance in world units).
-    ui_refresh_tx
-        .send(UIRefreshState::NodesUpdated(
-            scene
-                .nodes
-                .iter()
-                .map(|n| NodeUIState {
-                    node_id: n.node_id,
-                    position: Point {
-                        x: n.position.x,
-                        y: n.position.y,
-                    },
-                    radio_strength: calculate_effective_distance(
-                        n.radio_strength as f32,
-                        &scene.lora_parameters,
-                        &scene.path_loss_parameters,
-                    ) as u32,
-                })
-                .collect(),
-        ))
-        .await;
+    ui_refresh_tx.send(UIRefreshState::NodesUpdated(
+        scene
+            .nodes
+            .iter()
+            .map(|n| NodeUIState {
+                node_id: n.node_id,
+                position: Point {
+                    x: n.position.x,
+                    y: n.position.y,
+                },
+                radio_strength: calculate_effective_distance(
+                    n.radio_strength as f32,
+                    &scene.lora_parameters,
+                    scene.propagation_model.model(),
+                    &scene.path_loss_parameters,
+                ) as u32,
+                tx_power: Some(n.radio_strength),
+                is_gateway: n.role == NodeRole::Gateway,
+                disabled: bootstrap.is_delayed(n.node_id),
+            })
+            .collect(),
+    ));
 
     // Publish obstacles to the UI
-    ui_refresh_tx
-        .send(UIRefreshState::ObstaclesUpdated(scene.obstacles.clone()))
-        .await;
+    ui_refresh_tx.send(UIRefreshState::ObstaclesUpdated(scene.obstacles.clone()));
+    ui_refresh_tx.send(UIRefreshState::AnnotationsUpdated(
+        scene.annotations.clone(),
+    ));
 
     // Publish scene dimensions to the UI
     {
-        ui_refresh_tx
-            .send(UIRefreshState::SceneDimensionsUpdated(
-                scene.world_top_left.clone(),
-                scene.world_bottom_right.clone(),
-                scene.width,
-                scene.height,
-            ))
-            .await;
+        ui_refresh_tx.send(UIRefreshState::SceneDimensionsUpdated(scene.transform));
     }
 
     if let Some(ref bg_image) = scene.background_image {
         log::info!("Background image specified: {:?}", bg_image);
-        ui_refresh_tx
-            .send(UIRefreshState::BackgroundImageUpdated(Some(
-                bg_image.clone(),
-            )))
-            .await;
+        ui_refresh_tx.send(UIRefreshState::BackgroundImageUpdated(Some(
+            bg_image.clone(),
+        )));
+    }
+}
+
+/// Nodes output queues, returned when a session ended with all node tasks stopped.
+static NODES_OUTPUT_QUEUES: QueuePool<NodesOutputQueue> = QueuePool::new();
+
+/// Spawn the node task of a node (or its firmware subprocess with `node_process`)
+/// and connect its input queue.
+///
+/// The input queue is taken from the pool the node tasks return it to on shutdown.
+/// A failed spawn is logged and leaves the node silent.
+///
+/// # Parameters
+///
+/// * `spawner` - Embassy spawner for creating async tasks
+/// * `scene` - The loaded scene configuration
+/// * `node` - Node to start
+/// * `nodes_output_channel` - Shared output channel for all nodes
+fn spawn_node(
+    spawner: &Spawner,
+    scene: &Scene,
+    node: &mut Node,
+    nodes_output_channel: &'static NodesOutputQueue,
+) {
+    let node_input_channel = NODE_INPUT_QUEUES.take();
+    let mut radio_module_config = scene.radio_module_config.clone();
+    if let Some(delay) = node.tx_maximum_random_delay {
+        radio_module_config.tx_maximum_random_delay = delay;
+    }
+    let spawned = match &scene.node_process {
+        Some(process) => spawner.spawn(node_process_task(
+            process.clone(),
+            radio_module_config,
+            node.node_id,
+            nodes_output_channel.sender(),
+            node_input_channel,
+            RunningNodeTask::spawned(),
+        )),
+        None => spawner.spawn(node_task(
+            *spawner,
+            radio_module_config,
+            node.node_id,
+            nodes_output_channel.sender(),
+            node_input_channel,
+            RunningNodeTask::spawned(),
+        )),
+    };
+    if let Err(err) = spawned {
+        log::error!("Cannot spawn the task of node {}: {:?}", node.node_id, err);
     }
+    node.node_input_queue_sender = Some(node_input_channel.sender());
 }
 
 /// Initialize nodes map and spawn node tasks.
 ///
-/// For each node in the scene:
+/// Nodes are spawned in batches of `NODE_SPAWN_BATCH`; progress is published and a
+/// cancellation checked after each batch. For each node in the scene:
 /// 1. Creates dedicated input/output queues for communication
-/// 2. Spawns an async `node_task` to manage that node's radio stack
+/// 2. Spawns an async `node_task` to manage that node's radio stack (or a
+///    `node_process_task` running the firmware binary with `node_process`)
 /// 3. Pre-calculates effective radio distance for range checks
 /// 4. Initializes runtime-only fields (message history, event queues)
 ///
+/// Nodes with a later start time (see `bootstrap`) are not spawned; their radio is
+/// switched off until `start_due_nodes` spawns them.
+///
 /// # Parameters
 ///
 /// * `spawner` - Embassy spawner for creating async tasks
 /// * `scene` - The loaded scene configuration
+/// * `bootstrap` - Node start schedule
+/// * `spill_directory` - Directory of the history spill files (`spill_to_disk` policy)
 /// * `nodes_output_channel` - Shared output channel for all nodes
+/// * `ui_refresh_tx` - Channel for load progress
+/// * `ui_command_rx` - Receiver for UI commands (checked for a load cancellation)
 ///
 /// # Returns
 ///
-/// HashMap mapping node IDs to their initialized Node structs.
-fn initialize_nodes(
+/// HashMap mapping node IDs to their initialized Node structs, or `None` if the load
+/// was cancelled (the node processes spawned so far are stopped).
+async fn initialize_nodes(
     spawner: &Spawner,
     scene: &Scene,
+    bootstrap: &BootstrapSchedule,
+    spill_directory: &std::path::Path,
     nodes_output_channel: &'static NodesOutputQueue,
-) -> HashMap<u32, Node> {
+    ui_refresh_tx: &UIRefreshQueueSender,
+    ui_command_rx: &UICommandQueueReceiver,
+) -> Option<HashMap<u32, Node>> {
     let mut nodes_map: HashMap<u32, Node> = HashMap::new();
+    let total = scene.nodes.len();
 
-    for node in &scene.nodes {
-        // INTENTIONAL LEAK: Box::leak provides 'static lifetime for Embassy channels.
-        // Required to use the embedded moonblokz-radio-lib code in the simulator.
-        let node_input_channel = Box::leak(Box::new(NodeInputQueue::new()));
-        let _ = spawner.spawn(node_task(
-            *spawner,
-            scene.radio_module_config.clone(),
-            node.node_id,
-            nodes_output_channel.sender(),
-            node_input_channel.receiver(),
-        ));
+    for (index, node) in scene.nodes.iter().enumerate() {
+        if index.is_multiple_of(NODE_SPAWN_BATCH)
+            && report_load_progress("Spawning nodes", index, total, ui_refresh_tx, ui_command_rx)
+                .await
+        {
+            stop_nodes(&nodes_map, nodes_output_channel).await;
+            return None;
+        }
 
         let mut new_node = node.clone();
-        new_node.node_input_queue_sender = Some(node_input_channel.sender());
+        if bootstrap.is_delayed(node.node_id) {
+            new_node.disabled = true;
+        } else {
+            spawn_node(spawner, scene, &mut new_node, nodes_output_channel);
+        }
         new_node.cached_effective_distance = calculate_effective_distance(
             new_node.radio_strength as f32,
             &scene.lora_parameters,
+            scene.propagation_model.model(),
             &scene.path_loss_parameters,
         );
 
         // Ensure runtime-only fields are initialized
+        new_node.sleep_schedule = node
+            .duty_cycle
+            .or(scene.duty_cycle)
+            .and_then(|config| SleepSchedule::new(&config));
+        new_node.history = HistoryConfig {
+            policy: node.history_policy.unwrap_or(scene.history.policy),
+            ..scene.history
+        };
+        if new_node.history.policy == HistoryPolicy::SpillToDisk {
+            new_node.history_spill = Some(HistorySpill::new(spill_directory, node.node_id));
+        }
         if new_node.node_radio_packets.is_empty() {
-            new_node.node_radio_packets = VecDeque::with_capacity(NODE_MESSAGES_CAPACITY.min(64));
+            new_node.node_radio_packets =
+                VecDeque::with_capacity(scene.history.radio_packets.min(64));
         }
         if new_node.full_messages.is_empty() {
-            new_node.full_messages = VecDeque::with_capacity(NODE_FULL_MESSAGES_CAPACITY.min(64));
+            new_node.full_messages = VecDeque::with_capacity(scene.history.full_messages.min(64));
         }
         nodes_map.insert(new_node.node_id, new_node);
     }
 
-    nodes_map
+    Some(nodes_map)
 }
 
 /// Calculate the next interesting event time (earliest CAD or airtime completion).
@@ -459,7 +1011,8 @@ fn calculate_next_event_time(nodes_map: &HashMap<u32, Node>) -> Instant {
 /// 4. Find all target nodes within radio range and not blocked by obstacles
 /// 5. Distribute the packet to each target by queueing their airtime windows
 ///
-/// Special handling for AddBlock messages: notifies UI to track measurement progress.
+/// Special handling for AddBlock and AddTransaction messages: notifies UI to track
+/// measurement progress.
 ///
 /// # Parameters
 ///
@@ -467,31 +1020,44 @@ fn calculate_next_event_time(nodes_map: &HashMap<u32, Node>) -> Instant {
 /// * `packet` - The radio packet being transmitted
 /// * `nodes_map` - Mutable map of all nodes
 /// * `scene` - Scene configuration (for propagation parameters)
+/// * `measurements` - Running measurements (packets of measured messages are counted)
 /// * `ui_refresh_tx` - Channel for UI updates
-/// * `total_sent_packets` - Mutable counter for total packets sent
-/// * `total_received_packets` - Current count of received packets (for UI update)
-/// * `total_collision` - Current collision count (for UI update)
+/// * `event_bus` - Event bus the transmission is published on
+///
+/// # Returns
+///
+/// The relay delay if the packet is the first one the node sends of a received
+/// measurement message (recorded in the node statistics by the caller).
 async fn handle_radio_transfer(
     node_id: u32,
     packet: RadioPacket,
     nodes_map: &mut HashMap<u32, Node>,
     scene: &Scene,
+    measurements: &HashMap<u32, MeasurementTracker>,
     ui_refresh_tx: &UIRefreshQueueSender,
-    total_sent_packets: &mut u64,
-    total_received_packets: u64,
-    total_collision: u64,
-) {
-    // Handle special message types for UI
-    let sequence: Option<u32> = if packet.message_type() == MessageType::AddBlock as u8 {
+    event_bus: &mut EventBus,
+) -> Option<Duration> {
+    // Handle special message types for UI (the AddTransaction anchor sequence is at
+    // the same offset as the AddBlock sequence)
+    let measured = measurement_kind(packet.message_type());
+    let sequence: Option<u32> = if measured.is_some() {
         let seq = u32::from_le_bytes([
             packet.data[5],
             packet.data[6],
             packet.data[7],
             packet.data[8],
         ]);
-        _ = ui_refresh_tx
-            .try_send(UIRefreshState::SendMessageInMeasurement(seq))
-            .ok();
+        if measurements
+            .get(&seq)
+            .is_some_and(|tracker| Some(tracker.kind) == measured)
+        {
+            _ = ui_refresh_tx
+                .try_send(UIRefreshState::SendMessageInMeasurement(
+                    seq,
+                    Instant::now(),
+                ))
+                .ok();
+        }
         Some(seq)
     } else if packet.message_type() == MessageType::RequestBlockPart as u8 {
         // For RequestBlockPart, sequence is at the same offset as AddBlock
@@ -505,10 +1071,20 @@ async fn handle_radio_transfer(
         None
     };
 
-    let (node_position, node_radio_strength, node_effective_distance) = {
-        let node = match nodes_map.get_mut(&node_id) {
-            Some(n) => n,
-            None => return,
+    let (node_position, node_radio_strength, node_effective_distance, relay_delay) = {
+        let node = nodes_map.get_mut(&node_id)?;
+        // A switched-off radio transmits nothing
+        if node.disabled {
+            return None;
+        }
+
+        // First packet of a received measurement message: the node starts relaying it
+        let relay_delay = if measured.is_some() && packet.packet_index() == 0 {
+            sequence
+                .and_then(|seq| node.pending_relays.remove(&seq))
+                .map(|received| Instant::now().saturating_duration_since(received))
+        } else {
+            None
         };
 
         node.push_radio_packet(NodeMessage {
@@ -520,7 +1096,13 @@ async fn handle_radio_transfer(
             link_quality: 63,
             packet_count: packet.total_packet_count(),
             collision: false,
+            missed_while_transmitting: false,
+            missed_while_sleeping: false,
             sequence,
+            packet: Some(RecordedPacket {
+                packet: packet.clone(),
+                fault: None,
+            }),
         });
 
         // Enqueue the transmitter's own airtime window for collision modeling.
@@ -531,146 +1113,93 @@ async fn handle_radio_transfer(
             sender_node_id: node_id,
             start_time: Instant::now(),
             airtime: Duration::from_millis(airtime_ms),
-            rssi: calculate_rssi(0.0, node.radio_strength, &scene.path_loss_parameters),
+            rssi: calculate_rssi(
+                0.0,
+                node.radio_strength,
+                scene.propagation_model.model(),
+                &scene.path_loss_parameters,
+            ),
             processed: true,
         });
 
-        *total_sent_packets += 1;
-
-        ui_refresh_tx
-            .try_send(UIRefreshState::RadioMessagesCountUpdated(
-                *total_sent_packets,
-                total_received_packets,
-                total_collision,
-            ))
-            .ok();
-
         (
             node.position.clone(),
             node.radio_strength,
             node.cached_effective_distance,
+            relay_delay,
         )
     };
 
-    // Notify UI of transmission
-    _ = ui_refresh_tx.try_send(UIRefreshState::NodeSentRadioMessage(
+    event_bus.publish(SimulationEvent::Transmitted {
         node_id,
-        packet.message_type(),
-        node_effective_distance as u32,
-    ));
+        packet: &packet,
+        effective_distance: node_effective_distance,
+    });
 
-    // Find target receivers within range and not occluded
-    let target_node_ids = find_target_nodes(
+    // Find target receivers within range and not occluded (parallel physics stage)
+    let candidates = find_link_candidates(
         node_id,
         &node_position,
         node_effective_distance,
+        node_radio_strength,
         nodes_map,
         scene,
     );
 
     // Queue packet reception for each target
-    distribute_packet_to_targets(
-        &packet,
-        node_id,
-        &node_position,
-        node_radio_strength,
-        &target_node_ids,
-        nodes_map,
-        scene,
-    );
+    distribute_packet_to_targets(&packet, node_id, &candidates, nodes_map, scene);
+    relay_delay
 }
 
-/// Find all target nodes within radio range and not blocked by obstacles.
+/// Distribute a packet to all target nodes, completing their RSSI and airtime.
 ///
-/// Uses squared distance for efficiency (avoiding sqrt in the hot path).
-/// Only nodes within the sender's effective distance AND with clear line-of-sight
-/// (no obstacle intersection) are included.
-///
-/// # Parameters
+/// For each receiver found by the physics stage (in node ID order):
+/// 1. Sample the shadowing of the link and add it to the mean RSSI
+/// 2. Calculate packet airtime based on LoRa parameters
+/// 3. Queue an `AirtimeWaitingPacket` for later collision evaluation, starting after
+///    the arrival delay of the link (propagation and receiver processing)
 ///
-/// * `sender_id` - ID of the transmitting node (excluded from targets)
-/// * `sender_position` - 2D position of the sender
-/// * `sender_effective_distance` - Pre-calculated maximum range
-/// * `nodes_map` - Map of all nodes
-/// * `scene` - Scene configuration (for obstacle checks)
+/// The queued packets are processed by the main event loop when their airtime expires.
 ///
-/// # Returns
+/// # Parameters
 ///
-/// Vector of node IDs that can receive the transmission.
-fn find_target_nodes(
+/// * `packet` - The radio packet to distribute
+/// * `sender_id` - ID of the sender (for logging)
+/// * `candidates` - Receivers within range and line of sight, ordered by node ID
+/// * `nodes_map` - Mutable map of all nodes
+/// * `scene` - Scene configuration (for propagation model and arrival delay)
+fn distribute_packet_to_targets(
+    packet: &RadioPacket,
     sender_id: u32,
-    sender_position: &Point,
-    sender_effective_distance: f32,
-    nodes_map: &HashMap<u32, Node>,
-    scene: &Scene,
-) -> Vec<u32> {
-    let eff2 = (sender_effective_distance as f64).powi(2);
-    let mut target_ids = Vec::new();
-
-    for (&other_id, other_node) in nodes_map.iter() {
-        if other_id == sender_id {
-            continue;
-        }
-
-        let d2 = distance2(sender_position, &other_node.position, scene);
-        if d2 < eff2 {
-            if !is_intersect(sender_position, &other_node.position, &scene.obstacles) {
-                target_ids.push(other_id);
-            }
-        }
-    }
-
-    target_ids
-}
-
-/// Distribute a packet to all target nodes, computing RSSI and airtime.
-///
-/// For each target node:
-/// 1. Calculate the distance from sender to receiver
-/// 2. Compute received signal strength (RSSI) including path loss and shadowing
-/// 3. Calculate packet airtime based on LoRa parameters
-/// 4. Queue an `AirtimeWaitingPacket` for later collision evaluation
-///
-/// The queued packets are processed by the main event loop when their airtime expires.
-///
-/// # Parameters
-///
-/// * `packet` - The radio packet to distribute
-/// * `sender_id` - ID of the sender (for logging)
-/// * `sender_position` - Sender's 2D position (for distance calculation)
-/// * `sender_radio_strength` - Sender's TX power in dBm
-/// * `target_node_ids` - List of nodes within range
-/// * `nodes_map` - Mutable map of all nodes
-/// * `scene` - Scene configuration (for propagation model)
-fn distribute_packet_to_targets(
-    packet: &RadioPacket,
-    sender_id: u32,
-    sender_position: &Point,
-    sender_radio_strength: f32,
-    target_node_ids: &[u32],
-    nodes_map: &mut HashMap<u32, Node>,
+    candidates: &[LinkCandidate],
+    nodes_map: &mut HashMap<u32, Node>,
     scene: &Scene,
 ) {
     let airtime_ms = (calculate_air_time(&scene.lora_parameters, packet.length) * 1000.0) as u64;
+    let now = Instant::now();
 
-    for &target_id in target_node_ids {
-        let target_node = match nodes_map.get_mut(&target_id) {
+    for candidate in candidates {
+        let target_node = match nodes_map.get_mut(&candidate.target_id) {
             Some(n) => n,
             None => continue,
         };
 
-        let d2 = distance2(sender_position, &target_node.position, scene);
-        let distance = distance_from_d2(d2);
+        // Shadowing draws from the simulation RNG, so it is sampled here in a fixed order
+        let shadowing = if candidate.distance < 1.0 {
+            0.0
+        } else {
+            scene
+                .propagation_model
+                .model()
+                .sample_shadowing(&scene.path_loss_parameters)
+        };
+        let arrival = now + arrival_offset(&scene.arrival_delay, candidate.distance);
         target_node.push_airtime_packet(AirtimeWaitingPacket {
             packet: packet.clone(),
             sender_node_id: sender_id,
-            start_time: Instant::now(),
+            start_time: arrival,
             airtime: Duration::from_millis(airtime_ms),
-            rssi: calculate_rssi(
-                distance as f32,
-                sender_radio_strength,
-                &scene.path_loss_parameters,
-            ),
+            rssi: candidate.mean_rssi - shadowing,
             processed: false,
         });
     }
@@ -680,7 +1209,8 @@ fn distribute_packet_to_targets(
 ///
 /// CAD is used by nodes to sense if the channel is busy before transmitting.
 /// For each pending CAD request:
-/// 1. Check if any airtime windows overlap with the CAD window
+/// 1. Check if any airtime windows (or external uplinks above the receiver
+///    sensitivity) overlap with the CAD window
 /// 2. Send CAD response (true if activity detected, false otherwise)
 /// 3. Remove completed CAD items from the waiting list
 ///
@@ -689,8 +1219,20 @@ fn distribute_packet_to_targets(
 /// # Parameters
 ///
 /// * `nodes_map` - Mutable map of all nodes with pending CAD requests
-fn process_cad_requests(nodes_map: &mut HashMap<u32, Node>) {
+/// * `scene` - Scene configuration (input queue policy, receiver sensitivity)
+/// * `external` - Co-channel external traffic, if the scene has any
+/// * `node_statistics` - Per-node statistics counting the CAD answers
+async fn process_cad_requests(
+    nodes_map: &mut HashMap<u32, Node>,
+    scene: &Scene,
+    external: Option<&ExternalTraffic>,
+    node_statistics: &SharedNodeStatistics,
+) {
     let now = Instant::now();
+    let sensitivity = calculate_receiving_limit_with_basic_noise(
+        &scene.lora_parameters,
+        &scene.path_loss_parameters,
+    );
 
     for node in nodes_map.values_mut() {
         for cad_item in node.cad_waiting_list.iter() {
@@ -698,13 +1240,31 @@ fn process_cad_requests(nodes_map: &mut HashMap<u32, Node>) {
                 let activity = node.airtime_waiting_packets.iter().any(|packet| {
                     packet.start_time < cad_item.end_time
                         && packet.start_time + packet.airtime > cad_item.start_time
+                }) || external.is_some_and(|traffic| {
+                    traffic
+                        .frames_at(
+                            &node.position,
+                            cad_item.start_time,
+                            cad_item.end_time,
+                            scene,
+                        )
+                        .any(|(_, _, rssi)| rssi >= sensitivity)
                 });
-
-                let _ = node
-                    .node_input_queue_sender
-                    .as_ref()
-                    .unwrap()
-                    .try_send(NodeInputMessage::CADResponse(activity));
+                node_statistics
+                    .borrow_mut()
+                    .entry(node.node_id)
+                    .or_default()
+                    .record_cad_result(activity);
+
+                if let Some(sender) = &node.node_input_queue_sender {
+                    node.health
+                        .deliver(
+                            sender,
+                            NodeInputMessage::CADResponse(activity),
+                            &scene.node_queue,
+                        )
+                        .await;
+                }
             }
         }
 
@@ -713,112 +1273,46 @@ fn process_cad_requests(nodes_map: &mut HashMap<u32, Node>) {
     }
 }
 
-/// Clean up outdated airtime packets and find the next packet to process.
-fn find_next_packet_to_process(node: &mut Node) -> Option<(usize, Instant, Instant, f32)> {
-    // Find earliest start time of unprocessed packets
-    let earliest_start_time = node
-        .airtime_waiting_packets
-        .iter()
-        .filter(|p| !p.processed)
-        .map(|p| p.start_time)
-        .min()
-        .unwrap_or_else(Instant::now);
-
-    // Clean up processed packets that are no longer relevant
-    node.airtime_waiting_packets.retain(|packet| {
-        !packet.processed || packet.start_time + packet.airtime >= earliest_start_time
-    });
-
-    // Find the first unprocessed packet
-    node.airtime_waiting_packets
-        .iter()
-        .enumerate()
-        .find(|(_, p)| !p.processed)
-        .map(|(i, p)| (i, p.start_time, p.start_time + p.airtime, p.rssi))
-}
-
-/// Process packet reception, including collision detection and SINR calculation.
+/// Apply the outcome of a packet reception evaluated by the physics stage.
 ///
-/// Determines whether a packet is successfully received by:
-/// 1. Computing total noise (baseline noise floor + interfering signals)
-/// 2. Calculating SINR (Signal to Interference plus Noise Ratio)
-/// 3. Checking for destructive collisions (capture effect)
-/// 4. Comparing SINR against the required SNR limit
+/// Decides decoding from the SINR: against the required SNR limit (threshold model)
+/// or by sampling the packet success probability (packet error rate model).
 ///
-/// ## Collision Detection
-///
-/// - **Preamble lock loss**: Earlier packet above SNR destroys later packet
-/// - **Capture effect**: Later stronger packet (>6dB) captures the receiver
-/// - **Interference**: Overlapping signals add to noise floor
-///
-/// Successful packets are delivered to the node's input queue with link quality.
-/// Collisions are logged to the message history but not delivered.
+/// Successful packets are delivered to the node's input queue with link quality,
+/// truncated or corrupted with the scene's `packet_faults` probabilities.
+/// Collisions and packets missed while transmitting or sleeping are logged to the
+/// message history but not delivered; only collisions count towards the global
+/// collision counter.
 ///
 /// # Parameters
 ///
 /// * `node` - Mutable reference to the receiving node
-/// * `packet_index` - Index of packet in the airtime waiting list
-/// * `packet_start` - When packet transmission started
-/// * `packet_end` - When packet transmission ends
-/// * `packet_rssi` - Received signal strength in dBm
-/// * `scene` - Scene configuration (for SNR limit)
-/// * `total_received_packets` - Counter for successful receptions
-/// * `total_collision` - Counter for detected collisions
-/// * `ui_refresh_tx` - Channel for UI updates
-/// * `total_sent_packets` - Current sent count (for UI)
-async fn process_packet_reception(
+/// * `outcome` - Collision, SINR and receiver state of the packet
+/// * `scene` - Scene configuration (for SNR limit and reception model)
+/// * `event_bus` - Event bus the reception, collision or loss is published on
+async fn apply_packet_reception(
     node: &mut Node,
-    packet_index: usize,
-    packet_start: Instant,
-    packet_end: Instant,
-    packet_rssi: f32,
+    outcome: &ReceptionOutcome,
     scene: &Scene,
-    total_received_packets: &mut u64,
-    total_collision: &mut u64,
-    ui_refresh_tx: &UIRefreshQueueSender,
-    total_sent_packets: u64,
+    event_bus: &mut EventBus,
 ) {
-    node.airtime_waiting_packets[packet_index].processed = true;
-
     let snr_limit = calculate_snr_limit(&scene.lora_parameters);
-    let mut sum_noise = dbm_to_mw(scene.path_loss_parameters.noise_floor);
-    let mut collision = false;
-    let mut destructive_collision = false;
-
-    // Check for overlapping packets and collisions
-    for (i, other_packet) in node.airtime_waiting_packets.iter().enumerate() {
-        if i == packet_index {
-            continue;
-        }
-
-        let other_start = other_packet.start_time;
-        let other_end = other_start + other_packet.airtime;
-
-        // Check if packets overlap in time
-        if other_start < packet_end && other_end > packet_start {
-            // Preamble/header lock lost if earlier packet is above SNR limit
-            if other_start < packet_start && other_packet.rssi > snr_limit {
-                destructive_collision = true;
-            }
-
-            // Capture effect: later stronger packet captures the receiver
-            if other_start >= packet_start && packet_rssi - other_packet.rssi > CAPTURE_THRESHOLD {
-                destructive_collision = true;
-            }
-
-            sum_noise += dbm_to_mw(other_packet.rssi);
-            collision = true;
-        }
-    }
-
-    let total_noise = mw_to_dbm(sum_noise);
-    let sinr = packet_rssi - total_noise;
-    let link_quality = moonblokz_radio_lib::calculate_link_quality(packet_rssi as i16, sinr as i16);
+    let ReceptionOutcome {
+        packet_index,
+        rssi: packet_rssi,
+        sinr,
+        link_quality,
+        collision,
+        destructive_collision,
+        missed_while_transmitting,
+        missed_while_sleeping,
+        ..
+    } = *outcome;
 
     let packet = &node.airtime_waiting_packets[packet_index];
 
-    // Extract sequence for AddBlock and RequestBlockPart messages
-    let sequence: Option<u32> = if packet.packet.message_type() == MessageType::AddBlock as u8 {
+    // Extract sequence for AddBlock, AddTransaction (anchor) and RequestBlockPart messages
+    let sequence: Option<u32> = if measurement_kind(packet.packet.message_type()).is_some() {
         Some(u32::from_le_bytes([
             packet.packet.data[5],
             packet.packet.data[6],
@@ -837,98 +1331,1089 @@ async fn process_packet_reception(
         None
     };
 
-    // Successful reception
-    if sinr >= snr_limit && !destructive_collision {
-        if let Some(sender) = &node.node_input_queue_sender {
-            let _ = sender
-                .send(NodeInputMessage::RadioTransfer(
-                    moonblokz_radio_lib::ReceivedPacket {
-                        packet: packet.packet.clone(),
-                        link_quality,
-                    },
-                ))
-                .await;
+    let decoded = match scene.reception_model {
+        ReceptionModel::Threshold => sinr >= snr_limit,
+        ReceptionModel::PacketErrorRate => {
+            let success_probability = calculate_packet_success_probability(
+                sinr,
+                packet.packet.length,
+                &scene.lora_parameters,
+            );
+            with_simulation_rng(|rng| rng.gen_bool(success_probability.clamp(0.0, 1.0) as f64))
+        }
+    };
+
+    if missed_while_transmitting {
+        // Half-duplex loss: accounted separately from collisions
+        event_bus.publish(SimulationEvent::MissedWhileTransmitting {
+            node_id: node.node_id,
+            sender_id: packet.sender_node_id,
+            packet: &packet.packet,
+        });
+
+        node.push_radio_packet(NodeMessage {
+            timestamp: Instant::now(),
+            message_type: packet.packet.message_type(),
+            sender_node: packet.sender_node_id,
+            packet_size: packet.packet.length,
+            packet_index: packet.packet.packet_index() + 1,
+            packet_count: packet.packet.total_packet_count(),
+            collision: false,
+            missed_while_transmitting: true,
+            missed_while_sleeping: false,
+            link_quality,
+            sequence,
+            packet: Some(RecordedPacket {
+                packet: packet.packet.clone(),
+                fault: None,
+            }),
+        });
+    } else if missed_while_sleeping {
+        // Duty-cycled receiver asleep: accounted separately from collisions too
+        event_bus.publish(SimulationEvent::MissedWhileSleeping {
+            node_id: node.node_id,
+            sender_id: packet.sender_node_id,
+            packet: &packet.packet,
+        });
+
+        node.push_radio_packet(NodeMessage {
+            timestamp: Instant::now(),
+            message_type: packet.packet.message_type(),
+            sender_node: packet.sender_node_id,
+            packet_size: packet.packet.length,
+            packet_index: packet.packet.packet_index() + 1,
+            packet_count: packet.packet.total_packet_count(),
+            collision: false,
+            missed_while_transmitting: false,
+            missed_while_sleeping: true,
+            link_quality,
+            sequence,
+            packet: Some(RecordedPacket {
+                packet: packet.packet.clone(),
+                fault: None,
+            }),
+        });
+    } else if decoded && !destructive_collision {
+        // Successful reception (possibly damaged by an injected radio device fault)
+        let mut delivered = packet.packet.clone();
+        let fault = inject_fault(&scene.packet_faults, &mut delivered);
+        if let Some(sender) = &node.node_input_queue_sender {
+            node.health
+                .deliver(
+                    sender,
+                    NodeInputMessage::RadioTransfer(moonblokz_radio_lib::ReceivedPacket {
+                        packet: delivered,
+                        link_quality,
+                    }),
+                    &scene.node_queue,
+                )
+                .await;
+        }
+
+        event_bus.publish(SimulationEvent::Received {
+            node_id: node.node_id,
+            sender_id: packet.sender_node_id,
+            packet: &packet.packet,
+            rssi: packet_rssi,
+            sinr,
+            link_quality,
+            fault,
+        });
+
+        node.push_radio_packet(NodeMessage {
+            timestamp: Instant::now(),
+            message_type: packet.packet.message_type(),
+            sender_node: packet.sender_node_id,
+            packet_size: packet.packet.length,
+            packet_index: packet.packet.packet_index() + 1,
+            packet_count: packet.packet.total_packet_count(),
+            collision: false,
+            missed_while_transmitting: false,
+            missed_while_sleeping: false,
+            link_quality,
+            sequence,
+            packet: Some(RecordedPacket {
+                packet: packet.packet.clone(),
+                fault,
+            }),
+        });
+    } else if collision {
+        event_bus.publish(SimulationEvent::Collided {
+            node_id: node.node_id,
+            sender_id: packet.sender_node_id,
+            packet: &packet.packet,
+            sinr,
+        });
+
+        node.push_radio_packet(NodeMessage {
+            timestamp: Instant::now(),
+            message_type: packet.packet.message_type(),
+            sender_node: packet.sender_node_id,
+            packet_size: packet.packet.length,
+            packet_index: packet.packet.packet_index() + 1,
+            packet_count: packet.packet.total_packet_count(),
+            collision: true,
+            missed_while_transmitting: false,
+            missed_while_sleeping: false,
+            link_quality,
+            sequence,
+            packet: Some(RecordedPacket {
+                packet: packet.packet.clone(),
+                fault: None,
+            }),
+        });
+    }
+}
+
+/// Process all pending packet receptions across all nodes.
+///
+/// The physics of every node's next packet is evaluated in parallel, with the
+/// external traffic (if any) as additional interference; the outcomes are then
+/// applied here, on the executor thread, in node ID order.
+async fn process_all_packet_receptions(
+    nodes_map: &mut HashMap<u32, Node>,
+    scene: &Scene,
+    external: Option<&ExternalTraffic>,
+    event_bus: &mut EventBus,
+) {
+    for outcome in evaluate_pending_receptions(nodes_map, scene, external) {
+        if let Some(node) = nodes_map.get_mut(&outcome.node_id) {
+            apply_packet_reception(node, &outcome, scene, event_bus).await;
+        }
+    }
+}
+
+/// Ask a node to originate a measurement AddBlock or AddTransaction.
+///
+/// The measurement identifier is used as the AddBlock sequence (or AddTransaction
+/// anchor sequence), so every node that receives the message reports
+/// `NodeReachedInMeasurement` with this identifier.
+///
+/// # Parameters
+///
+/// * `node_id` - Origin node of the measurement
+/// * `measurement_identifier` - Unique identifier (message sequence) of the measurement
+/// * `kind` - Measured message type
+/// * `nodes_map` - Map of all nodes
+/// * `measurements` - Mutable map of running measurements (a tracker is registered here)
+/// * `backhaul` - Gateway backhaul tracker (the measurement block is recorded as originated)
+async fn start_measurement(
+    node_id: u32,
+    measurement_identifier: u32,
+    kind: MeasurementKind,
+    nodes_map: &HashMap<u32, Node>,
+    measurements: &mut HashMap<u32, MeasurementTracker>,
+    backhaul: &mut BackhaulTracker,
+) {
+    if let Some(node) = nodes_map.get(&node_id) {
+        measurements.insert(
+            measurement_identifier,
+            MeasurementTracker::new(node_id, kind),
+        );
+        record_event(format!(
+            "MEASUREMENT {} ({}) started on node {}",
+            measurement_identifier,
+            kind.label(),
+            node_id
+        ));
+        if let Some(sender) = &node.node_input_queue_sender {
+            let message = match kind {
+                MeasurementKind::Block => {
+                    let message_body: [u8; 2000] = [22; 2000];
+                    RadioMessage::add_block_with(node_id, measurement_identifier, &message_body)
+                }
+                MeasurementKind::Transaction => {
+                    let payload = [33u8; TRANSACTION_MEASUREMENT_PAYLOAD_SIZE];
+                    RadioMessage::add_transaction_with(
+                        node_id,
+                        measurement_identifier,
+                        measurement_identifier,
+                        &payload,
+                    )
+                }
+            };
+            backhaul.record_origin(node_id, &message, Instant::now());
+            let _ = sender.send(NodeInputMessage::SendMessage(message)).await;
+        }
+    }
+}
+
+/// Record that a node was reached by a measurement and log when it completes.
+///
+/// Reports for unknown measurement IDs (e.g. AddBlocks not started as measurements
+/// or stopped by the UI) are ignored.
+///
+/// # Parameters
+///
+/// * `measurements` - Mutable map of running measurements
+/// * `measurement_id` - Measurement (AddBlock sequence) the node received
+/// * `node_id` - Node that was reached
+/// * `node_count` - Total number of nodes in the scene
+///
+/// # Returns
+///
+/// The number of nodes the measurement reached so far (0 for unknown measurements).
+fn record_measurement_reach(
+    measurements: &mut HashMap<u32, MeasurementTracker>,
+    measurement_id: u32,
+    node_id: u32,
+    node_count: usize,
+) -> usize {
+    let Some(tracker) = measurements.get_mut(&measurement_id) else {
+        return 0;
+    };
+    if tracker.record_reached(node_id, node_count) {
+        log::info!(
+            "Measurement {} from node {} reached all {} nodes in {}s",
+            measurement_id,
+            tracker.origin_node_id,
+            node_count,
+            tracker.start_time.elapsed().as_secs()
+        );
+    }
+    tracker.reached_count
+}
+
+/// Node a reached node first received a measurement message from.
+///
+/// Found in the node's radio packet history: the sender of the oldest successfully
+/// received packet of the measurement. None if that packet already left the history.
+///
+/// # Parameters
+///
+/// * `node` - The reached node
+/// * `measurement_id` - Measurement (message sequence) the node received
+/// * `kind` - Message type of the measurement
+fn first_relayer(node: &Node, measurement_id: u32, kind: MeasurementKind) -> Option<u32> {
+    node.node_radio_packets
+        .iter()
+        .find(|message| {
+            message.sender_node != node.node_id
+                && !message.collision
+                && !message.missed_while_transmitting
+                && !message.missed_while_sleeping
+                && message.sequence == Some(measurement_id)
+                && measurement_kind(message.message_type) == Some(kind)
+        })
+        .map(|message| message.sender_node)
+}
+
+/// Start the scene-defined automatic measurement if its next run is due.
+///
+/// # Parameters
+///
+/// * `scene` - Scene configuration (for the auto measurement definition)
+/// * `scene_start` - Virtual time when the scene was loaded
+/// * `runs_started` - Mutable count of automatic measurements started so far
+/// * `nodes_map` - Map of all nodes
+/// * `measurements` - Mutable map of running measurements
+/// * `backhaul` - Gateway backhaul tracker
+/// * `ui_refresh_tx` - Channel for notifying the UI about the new measurement
+async fn check_auto_measurement(
+    scene: &Scene,
+    scene_start: Instant,
+    runs_started: &mut u32,
+    nodes_map: &HashMap<u32, Node>,
+    measurements: &mut HashMap<u32, MeasurementTracker>,
+    backhaul: &mut BackhaulTracker,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    let Some(auto) = &scene.auto_measurement else {
+        return;
+    };
+    if *runs_started >= auto.repeat_count {
+        return;
+    }
+
+    let due = scene_start
+        + Duration::from_secs(auto.start_time + auto.repeat_interval * *runs_started as u64);
+    if Instant::now() < due {
+        return;
+    }
+
+    *runs_started += 1;
+    let measurement_identifier = measurement_ids::next();
+    log::info!(
+        "Starting automatic measurement {} ({}/{}) on node {}",
+        measurement_identifier,
+        runs_started,
+        auto.repeat_count,
+        auto.origin_node_id
+    );
+    start_measurement(
+        auto.origin_node_id,
+        measurement_identifier,
+        MeasurementKind::Block,
+        nodes_map,
+        measurements,
+        backhaul,
+    )
+    .await;
+    ui_refresh_tx.send(UIRefreshState::MeasurementStarted(
+        auto.origin_node_id,
+        measurement_identifier,
+        Instant::now(),
+    ));
+}
+
+/// Drive the scene-defined throughput stress test.
+///
+/// Starts the test when its start time is reached, originates due AddTransaction
+/// messages from random nodes and evaluates finished load steps. When saturation is
+/// detected the achievable capacity is reported to the UI.
+///
+/// The transaction ID is carried in the anchor sequence field so deliveries can be
+/// matched in `network_task`.
+///
+/// # Parameters
+///
+/// * `scene` - Scene configuration (for the stress test definition)
+/// * `scene_start` - Virtual time when the scene was loaded
+/// * `stress_test` - Mutable runner state (None until the test starts)
+/// * `nodes_map` - Map of all nodes
+/// * `backhaul` - Gateway backhaul tracker (transactions are recorded as originated)
+/// * `counters` - Current global packet counters
+/// * `ui_refresh_tx` - Channel for reporting the result
+async fn check_stress_test(
+    scene: &Scene,
+    scene_start: Instant,
+    stress_test: &mut Option<StressTestRunner>,
+    nodes_map: &HashMap<u32, Node>,
+    backhaul: &mut BackhaulTracker,
+    counters: PacketCounters,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    let Some(config) = &scene.stress_test else {
+        return;
+    };
+    let now = Instant::now();
+
+    if stress_test.is_none() {
+        if now < scene_start + Duration::from_secs(config.start_time) {
+            return;
+        }
+        log::info!(
+            "Starting stress test at {} transactions/minute",
+            config.initial_rate
+        );
+        *stress_test = Some(StressTestRunner::new(
+            config.clone(),
+            now,
+            counters.received,
+            counters.collisions,
+        ));
+    }
+    let Some(runner) = stress_test.as_mut() else {
+        return;
+    };
+    if runner.is_finished() {
+        return;
+    }
+
+    // A tick can be longer than the send interval at high rates or simulation
+    // speeds: every transaction that became due since the last tick is sent
+    while let Some(transaction_id) = runner.due_transaction(now) {
+        let origin = with_simulation_rng(|rng| nodes_map.values().choose(rng));
+        if let Some(node) = origin {
+            if let Some(sender) = &node.node_input_queue_sender {
+                let payload = vec![33u8; runner.payload_size()];
+                let message = RadioMessage::add_transaction_with(
+                    node.node_id,
+                    transaction_id,
+                    transaction_id,
+                    &payload,
+                );
+                backhaul.record_origin(node.node_id, &message, now);
+                let _ = sender.send(NodeInputMessage::SendMessage(message)).await;
+            }
+        }
+    }
+
+    let Some(step) = runner.evaluate_step(now, counters.received, counters.collisions) else {
+        return;
+    };
+    let latency_string = match step.average_latency {
+        Some(latency) => format!("{:.1}s", latency.as_millis() as f64 / 1000.0),
+        None => "-".to_string(),
+    };
+    log::info!(
+        "Stress test step at {:.1} tx/min: {} transactions, collision rate {:.2}%, avg latency {}",
+        step.rate,
+        step.transactions_sent,
+        step.collision_rate,
+        latency_string
+    );
+
+    if !step.saturated {
+        log::info!(
+            "Stress test load increased to {:.1} transactions/minute",
+            runner.rate()
+        );
+        return;
+    }
+
+    let capacity = match runner.last_sustainable_rate() {
+        Some(rate) => format!("{:.1} transactions/minute", rate),
+        None => "below the initial rate".to_string(),
+    };
+    let report = format!(
+        "Stress test finished: saturation at {:.1} transactions/minute (collision rate {:.2}%, average latency {}). Achievable capacity: {}.",
+        step.rate, step.collision_rate, latency_string, capacity
+    );
+    log::info!("{}", report);
+    ui_refresh_tx.send(UIRefreshState::Alert(report));
+}
+
+/// Drive the scene-defined origin sampling experiment.
+///
+/// Chooses the origins when the experiment's start time is reached (the degree of a
+/// node is its number of link candidates), starts the due measurements and reports
+/// the distribution times per origin class to the UI when the last one finished.
+///
+/// # Parameters
+///
+/// * `scene` - Scene configuration (for the experiment definition)
+/// * `scene_start` - Virtual time when the scene was loaded
+/// * `origin_sampling` - Mutable runner state (None until the experiment starts)
+/// * `nodes_map` - Map of all nodes
+/// * `measurements` - Mutable map of running measurements
+/// * `backhaul` - Gateway backhaul tracker
+/// * `ui_refresh_tx` - Channel for the started measurements and the report
+async fn check_origin_sampling(
+    scene: &Scene,
+    scene_start: Instant,
+    origin_sampling: &mut Option<OriginSamplingRunner>,
+    nodes_map: &HashMap<u32, Node>,
+    measurements: &mut HashMap<u32, MeasurementTracker>,
+    backhaul: &mut BackhaulTracker,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    let Some(config) = &scene.origin_sampling else {
+        return;
+    };
+    let now = Instant::now();
+
+    if origin_sampling.is_none() {
+        if now < scene_start + Duration::from_secs(config.start_time) {
+            return;
+        }
+        let candidates: Vec<OriginCandidate> = nodes_map
+            .values()
+            .filter(|node| !node.disabled)
+            .map(|node| OriginCandidate {
+                node_id: node.node_id,
+                position: (node.position.x, node.position.y),
+                degree: find_link_candidates(
+                    node.node_id,
+                    &node.position,
+                    node.cached_effective_distance,
+                    node.radio_strength,
+                    nodes_map,
+                    scene,
+                )
+                .len(),
+            })
+            .collect();
+        let runner = with_simulation_rng(|rng| {
+            OriginSamplingRunner::new(
+                config,
+                &candidates,
+                &scene.transform,
+                rng,
+                nodes_map.len(),
+                now,
+            )
+        });
+        log::info!(
+            "Starting origin sampling: {} measurements every {}s",
+            runner.run_count(),
+            config.interval
+        );
+        *origin_sampling = Some(runner);
+    }
+    let Some(runner) = origin_sampling.as_mut() else {
+        return;
+    };
+
+    if let Some((strategy, origin)) = runner.due_run(now) {
+        let measurement_identifier = measurement_ids::next();
+        log::info!(
+            "Starting origin sampling measurement {} on {} origin {}",
+            measurement_identifier,
+            strategy.label(),
+            origin
+        );
+        runner.record_start(measurement_identifier, now);
+        start_measurement(
+            origin,
+            measurement_identifier,
+            MeasurementKind::Block,
+            nodes_map,
+            measurements,
+            backhaul,
+        )
+        .await;
+        ui_refresh_tx.send(UIRefreshState::MeasurementStarted(
+            origin,
+            measurement_identifier,
+            now,
+        ));
+    }
+
+    if let Some(report) = runner.take_report(now) {
+        let lines = report.to_lines();
+        for line in &lines {
+            log::info!("Origin sampling: {}", line);
+        }
+        ui_refresh_tx.send(UIRefreshState::Alert(format!(
+            "Origin sampling finished:\n{}",
+            lines.join("\n")
+        )));
+    }
+}
+
+/// Apply the commands issued by the scene script since the last loop iteration.
+///
+/// # Parameters
+///
+/// * `actions` - Commands of the script, in issue order
+/// * `nodes_map` - Mutable map of all nodes
+/// * `measurements` - Mutable map of running measurements
+/// * `backhaul` - Gateway backhaul tracker
+/// * `ui_refresh_tx` - Channel for the started measurements and node updates
+async fn apply_script_actions(
+    actions: Vec<ScriptAction>,
+    nodes_map: &mut HashMap<u32, Node>,
+    measurements: &mut HashMap<u32, MeasurementTracker>,
+    backhaul: &mut BackhaulTracker,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    for action in actions {
+        match action {
+            ScriptAction::StartMeasurement {
+                node_id,
+                measurement_id,
+            } => {
+                if !nodes_map.contains_key(&node_id) {
+                    log::warn!("Script started a measurement on unknown node {}", node_id);
+                    continue;
+                }
+                log::info!(
+                    "Script starts measurement {} on node {}",
+                    measurement_id,
+                    node_id
+                );
+                start_measurement(
+                    node_id,
+                    measurement_id,
+                    MeasurementKind::Block,
+                    nodes_map,
+                    measurements,
+                    backhaul,
+                )
+                .await;
+                ui_refresh_tx.send(UIRefreshState::MeasurementStarted(
+                    node_id,
+                    measurement_id,
+                    Instant::now(),
+                ));
+            }
+            ScriptAction::SetNodeEnabled { node_id, enabled } => {
+                if !nodes_map.contains_key(&node_id) {
+                    log::warn!("Script switched unknown node {}", node_id);
+                    continue;
+                }
+                set_node_enabled(nodes_map, node_id, enabled);
+                let Some(node) = nodes_map.get(&node_id) else {
+                    continue;
+                };
+                ui_refresh_tx.send(UIRefreshState::NodeUpdated(NodeUIState {
+                    node_id,
+                    position: node.position.clone(),
+                    radio_strength: node.cached_effective_distance as u32,
+                    tx_power: Some(node.radio_strength),
+                    is_gateway: node.role == NodeRole::Gateway,
+                    disabled: node.disabled,
+                }));
+            }
+        }
+    }
+}
+
+/// Replace the scene's obstacles with an edited set (runtime obstacle editing).
+///
+/// Line-of-sight is evaluated per transmission against `scene.obstacles`, so the new
+/// obstacles take effect for the next transmission; no reachability is cached.
+/// Invalid obstacle sets are rejected with an alert. In both cases the effective
+/// obstacles are published back to the UI.
+///
+/// # Parameters
+///
+/// * `scene` - Mutable scene whose obstacles are replaced
+/// * `obstacles` - Edited obstacle list from the UI
+/// * `ui_refresh_tx` - Channel for publishing the effective obstacles
+async fn update_obstacles(
+    scene: &mut Scene,
+    obstacles: Vec<Obstacle>,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    match validate_obstacles(&obstacles, &scene.transform) {
+        Ok(()) => {
+            log::info!("Obstacles updated: {} obstacles", obstacles.len());
+            record_event(format!("OBSTACLES updated ({} obstacles)", obstacles.len()));
+            scene.obstacles = obstacles;
+        }
+        Err(err) => {
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Obstacle update rejected: {}",
+                err
+            )));
+        }
+    }
+    ui_refresh_tx.send(UIRefreshState::ObstaclesUpdated(scene.obstacles.clone()));
+}
+
+/// Move a node to a new world position (runtime topology editing).
+///
+/// Packets already in the air keep the RSSI computed at transmission time; the new
+/// position applies from the next transmission on.
+///
+/// # Parameters
+///
+/// * `nodes_map` - Map of all nodes
+/// * `node_id` - Node to move
+/// * `position` - New position in world coordinates
+/// * `transform` - World transform of the scene (bounds of valid positions)
+/// * `ui_refresh_tx` - Channel for rejection alerts and the reverted node position
+async fn move_node(
+    nodes_map: &mut HashMap<u32, Node>,
+    node_id: u32,
+    position: Point,
+    transform: &WorldTransform,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    if !transform.contains(position.x, position.y) {
+        ui_refresh_tx.send(UIRefreshState::Alert(format!(
+            "Node move rejected: position ({}, {}) is outside the world bounds {}",
+            position.x,
+            position.y,
+            transform.bounds_label()
+        )));
+        // The UI already shows the node at the rejected position: move it back
+        if let Some(node) = nodes_map.get(&node_id) {
+            ui_refresh_tx.send(UIRefreshState::NodeUpdated(NodeUIState {
+                node_id,
+                position: node.position.clone(),
+                radio_strength: node.cached_effective_distance as u32,
+                tx_power: Some(node.radio_strength),
+                is_gateway: node.role == NodeRole::Gateway,
+                disabled: node.disabled,
+            }));
+        }
+        return;
+    }
+    if let Some(node) = nodes_map.get_mut(&node_id) {
+        log::info!(
+            "Node {} moved to ({:.1}, {:.1})",
+            node_id,
+            position.x,
+            position.y
+        );
+        record_event(format!(
+            "NODE {} moved to ({:.1}, {:.1})",
+            node_id, position.x, position.y
+        ));
+        node.position = position;
+    }
+}
+
+/// Switch a node's radio on or off (runtime topology editing).
+///
+/// # Parameters
+///
+/// * `nodes_map` - Map of all nodes
+/// * `node_id` - Node to switch
+/// * `enabled` - Whether the radio is on
+fn set_node_enabled(nodes_map: &mut HashMap<u32, Node>, node_id: u32, enabled: bool) {
+    if let Some(node) = nodes_map.get_mut(&node_id) {
+        if enabled && node.node_input_queue_sender.is_none() {
+            log::warn!("Node {} has not started yet, its radio stays off", node_id);
+            return;
+        }
+        log::info!(
+            "Node {} radio {}",
+            node_id,
+            if enabled { "enabled" } else { "disabled" }
+        );
+        record_event(format!(
+            "NODE {} radio {}",
+            node_id,
+            if enabled { "enabled" } else { "disabled" }
+        ));
+        node.disabled = !enabled;
+        if !enabled {
+            // Drop receptions in progress: a switched-off radio decodes nothing
+            node.airtime_waiting_packets.clear();
+        }
+    }
+}
+
+/// Set a node's TX power (runtime topology editing) and publish its new range.
+///
+/// Recomputes the node's cached effective distance; packets already in the air keep
+/// the RSSI computed at transmission time.
+///
+/// # Parameters
+///
+/// * `nodes_map` - Map of all nodes
+/// * `scene` - Scene with the propagation and LoRa parameters
+/// * `node_id` - Node to update
+/// * `tx_power` - New TX power in dBm
+/// * `ui_refresh_tx` - Channel for the updated node state and rejection alerts
+async fn set_node_tx_power(
+    nodes_map: &mut HashMap<u32, Node>,
+    scene: &Scene,
+    node_id: u32,
+    tx_power: f32,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    const MIN_RADIO_STRENGTH: f32 = -50.0;
+    const MAX_RADIO_STRENGTH: f32 = 50.0;
+    if !(MIN_RADIO_STRENGTH..=MAX_RADIO_STRENGTH).contains(&tx_power) {
+        ui_refresh_tx.send(UIRefreshState::Alert(format!(
+            "TX power change rejected: {} dBm outside realistic range ({} to {} dBm)",
+            tx_power, MIN_RADIO_STRENGTH, MAX_RADIO_STRENGTH
+        )));
+        return;
+    }
+    if let Err(e) = scene.regulatory_limits.check_tx_power(tx_power) {
+        ui_refresh_tx.send(UIRefreshState::Alert(format!(
+            "TX power change rejected: {}",
+            e
+        )));
+        return;
+    }
+    let Some(node) = nodes_map.get_mut(&node_id) else {
+        return;
+    };
+    node.radio_strength = tx_power;
+    node.cached_effective_distance = calculate_effective_distance(
+        tx_power,
+        &scene.lora_parameters,
+        scene.propagation_model.model(),
+        &scene.path_loss_parameters,
+    );
+    log::info!(
+        "Node {} TX power set to {:.1} dBm (range {:.0} m)",
+        node_id,
+        tx_power,
+        node.cached_effective_distance
+    );
+    record_event(format!("NODE {} TX power {:.1} dBm", node_id, tx_power));
+    ui_refresh_tx.send(UIRefreshState::NodeUpdated(NodeUIState {
+        node_id,
+        position: node.position.clone(),
+        radio_strength: node.cached_effective_distance as u32,
+        tx_power: Some(tx_power),
+        is_gateway: node.role == NodeRole::Gateway,
+        disabled: node.disabled,
+    }));
+}
+
+/// Spawn the delayed nodes whose start time has been reached and switch their
+/// radio on.
+///
+/// # Parameters
+///
+/// * `spawner` - Embassy spawner for creating async tasks
+/// * `scene` - The loaded scene configuration
+/// * `scene_start` - Time the scene was loaded
+/// * `bootstrap` - Node start schedule
+/// * `nodes_map` - Mutable map of all nodes
+/// * `nodes_output_channel` - Shared output channel for all nodes
+/// * `ui_refresh_tx` - Channel for the node updates
+async fn start_due_nodes(
+    spawner: &Spawner,
+    scene: &Scene,
+    scene_start: Instant,
+    bootstrap: &mut BootstrapSchedule,
+    nodes_map: &mut HashMap<u32, Node>,
+    nodes_output_channel: &'static NodesOutputQueue,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    let elapsed = Instant::now().saturating_duration_since(scene_start);
+    for node_id in bootstrap.take_due(elapsed) {
+        let Some(node) = nodes_map.get_mut(&node_id) else {
+            continue;
+        };
+        spawn_node(spawner, scene, node, nodes_output_channel);
+        node.disabled = false;
+        log::info!("Node {} joined at {} s", node_id, elapsed.as_secs());
+        record_event(format!("NODE {} start", node_id));
+        ui_refresh_tx.send(UIRefreshState::NodeUpdated(NodeUIState {
+            node_id,
+            position: node.position.clone(),
+            radio_strength: node.cached_effective_distance as u32,
+            tx_power: Some(node.radio_strength),
+            is_gateway: node.role == NodeRole::Gateway,
+            disabled: false,
+        }));
+    }
+}
+
+/// Send the backhaul re-injections that are due to their gateways.
+///
+/// # Parameters
+///
+/// * `backhaul` - Gateway backhaul tracker
+/// * `nodes_map` - Map of all nodes
+async fn send_backhaul_reinjections(
+    backhaul: &mut BackhaulTracker,
+    nodes_map: &HashMap<u32, Node>,
+) {
+    for (gateway_id, message) in backhaul.due_reinjections(Instant::now()) {
+        record_event(format!(
+            "BACKHAUL re-inject type {} at gateway {}",
+            message.message_type(),
+            gateway_id
+        ));
+        if let Some(sender) = nodes_map
+            .get(&gateway_id)
+            .and_then(|node| node.node_input_queue_sender.as_ref())
+        {
+            let _ = sender.send(NodeInputMessage::SendMessage(message)).await;
+        }
+    }
+}
+
+/// Apply the scene-defined speed profile when the next step's start time is reached.
+///
+/// # Parameters
+///
+/// * `scene` - Scene configuration (for the speed profile)
+/// * `scene_start` - Virtual time when the scene was loaded
+/// * `steps_applied` - Mutable count of profile steps applied so far
+/// * `ui_refresh_tx` - Channel for notifying the UI about the speed change
+fn check_speed_profile(
+    scene: &Scene,
+    scene_start: Instant,
+    steps_applied: &mut usize,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    let Some(step) = scene.speed_profile.get(*steps_applied) else {
+        return;
+    };
+    if Instant::now() < scene_start + Duration::from_secs(step.start_time) {
+        return;
+    }
+
+    if *steps_applied == 0 {
+        log::info!("Speed profile active: auto speed and the speed target are paused");
+    }
+    *steps_applied += 1;
+    log::info!(
+        "Speed profile: setting simulation speed to {}% at {}s",
+        step.speed_percent,
+        step.start_time
+    );
+    time_driver::set_simulation_speed_percent(step.speed_percent);
+    _ = ui_refresh_tx.try_send(UIRefreshState::SimulationSpeedChanged(step.speed_percent));
+}
+
+/// Virtual time between two matrix requests of a sweep, so the matrix dumps of
+/// consecutive nodes do not interleave in the captured logs.
+const LINK_MATRIX_REQUEST_SPACING: Duration = Duration::from_millis(250);
+
+/// Progress of the scene-defined link matrix sweeps.
+struct LinkMatrixSweep {
+    /// Virtual time when the next sweep starts.
+    next_sweep: Instant,
+    /// Nodes of the current sweep not asked yet.
+    queue: VecDeque<u32>,
+    /// Virtual time of the next matrix request.
+    next_request: Instant,
+}
+
+/// Drive the scene-defined link matrix sweeps: every `snapshot_interval` all nodes
+/// are queued, then asked one by one to dump their connection matrix (collected by
+/// the connection matrix parser and forwarded to the UI).
+///
+/// # Parameters
+///
+/// * `scene` - Scene configuration (for the link matrix settings)
+/// * `sweep` - Mutable sweep progress
+/// * `nodes_map` - Map of all nodes
+async fn check_link_matrix_sweep(
+    scene: &Scene,
+    sweep: &mut LinkMatrixSweep,
+    nodes_map: &HashMap<u32, Node>,
+) {
+    let Some(interval) = scene
+        .link_matrix
+        .as_ref()
+        .and_then(|config| config.snapshot_interval)
+    else {
+        return;
+    };
+    let now = Instant::now();
+    if now >= sweep.next_sweep {
+        sweep.next_sweep = now + Duration::from_secs(interval.max(1));
+        if !sweep.queue.is_empty() {
+            log::warn!(
+                "Link matrix sweep still had {} nodes to ask when the next one started",
+                sweep.queue.len()
+            );
+        }
+        let mut node_ids: Vec<u32> = nodes_map.keys().copied().collect();
+        node_ids.sort_unstable();
+        sweep.queue = node_ids.into();
+    }
+    if now < sweep.next_request {
+        return;
+    }
+    let Some(node_id) = sweep.queue.pop_front() else {
+        return;
+    };
+    sweep.next_request = now + LINK_MATRIX_REQUEST_SPACING;
+    if let Some(sender) = nodes_map
+        .get(&node_id)
+        .and_then(|node| node.node_input_queue_sender.as_ref())
+    {
+        let _ = sender.send(NodeInputMessage::RequestConnectionMatrix).await;
+    }
+}
+
+/// Import the scene's initial link matrix file into the nodes.
+///
+/// Matrices of nodes missing from the scene are skipped; an unreadable file is
+/// reported to the UI and leaves the nodes with their empty initial state.
+///
+/// # Parameters
+///
+/// * `scene` - Scene configuration (for the link matrix settings)
+/// * `nodes_map` - Map of all nodes
+/// * `ui_refresh_tx` - Channel for reporting import errors
+async fn import_initial_link_matrices(
+    scene: &Scene,
+    nodes_map: &HashMap<u32, Node>,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    let Some(path) = scene
+        .link_matrix
+        .as_ref()
+        .and_then(|config| config.initial.as_ref())
+    else {
+        return;
+    };
+    let matrices = match std::fs::read_to_string(path)
+        .context("cannot read the file")
+        .and_then(|text| parse_link_matrices(&text))
+    {
+        Ok(matrices) => matrices,
+        Err(err) => {
+            ui_refresh_tx.send(UIRefreshState::Alert(format!(
+                "Error importing link matrices from {}: {:#}",
+                path, err
+            )));
+            return;
         }
+    };
 
-        *total_received_packets += 1;
+    let mut imported = 0;
+    for matrix in matrices {
+        let Some(sender) = nodes_map
+            .get(&matrix.requester_node_id)
+            .and_then(|node| node.node_input_queue_sender.as_ref())
+        else {
+            log::warn!(
+                "Skipping imported link matrix of node {} (not in the scene)",
+                matrix.requester_node_id
+            );
+            continue;
+        };
+        sender
+            .send(NodeInputMessage::ImportConnectionMatrix(matrix))
+            .await;
+        imported += 1;
+    }
+    log::info!(
+        "Imported the link matrices of {} nodes from {}",
+        imported,
+        path
+    );
+}
 
-        node.push_radio_packet(NodeMessage {
-            timestamp: Instant::now(),
-            message_type: packet.packet.message_type(),
-            sender_node: packet.sender_node_id,
-            packet_size: packet.packet.length,
-            packet_index: packet.packet.packet_index() + 1,
-            packet_count: packet.packet.total_packet_count(),
-            collision: false,
-            link_quality,
-            sequence,
-        });
+/// End the run: publish the final statistics, write the run summary, pause the
+/// virtual clock and report the reason to the UI (the headless runner exits on it).
+///
+/// # Parameters
+///
+/// * `reason` - Stop condition that was met
+/// * `summary` - Run summary to write
+/// * `summary_path` - Path of the summary file
+/// * `node_statistics` - Per-node statistics of the run
+/// * `ui_refresh_tx` - Channel for notifying the UI
+async fn stop_run(
+    reason: String,
+    summary: serde_json::Value,
+    summary_path: std::path::PathBuf,
+    node_statistics: &SharedNodeStatistics,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    log::info!("Stopping the simulation: {}", reason);
+    let statistics = node_statistics.borrow().clone();
+    ui_refresh_tx.send(UIRefreshState::NodeStatisticsUpdated(statistics));
 
-        ui_refresh_tx
-            .try_send(UIRefreshState::RadioMessagesCountUpdated(
-                total_sent_packets,
-                *total_received_packets,
-                *total_collision,
-            ))
-            .ok();
-    } else if collision {
-        // Collision detected
-        *total_collision += 1;
+    write_run_summary(&summary, &summary_path);
 
-        node.push_radio_packet(NodeMessage {
-            timestamp: Instant::now(),
-            message_type: packet.packet.message_type(),
-            sender_node: packet.sender_node_id,
-            packet_size: packet.packet.length,
-            packet_index: packet.packet.packet_index() + 1,
-            packet_count: packet.packet.total_packet_count(),
-            collision: true,
-            link_quality,
-            sequence,
-        });
+    time_driver::set_simulation_paused(true);
+    ui_refresh_tx.send(UIRefreshState::SimulationStopped(reason));
+}
 
-        ui_refresh_tx
-            .try_send(UIRefreshState::RadioMessagesCountUpdated(
-                total_sent_packets,
-                *total_received_packets,
-                *total_collision,
-            ))
-            .ok();
+/// Write the run summary as pretty-printed JSON (errors are logged).
+fn write_run_summary(summary: &serde_json::Value, summary_path: &std::path::Path) {
+    let written = serde_json::to_string_pretty(summary)
+        .context("cannot serialize the run summary")
+        .and_then(|json| std::fs::write(summary_path, json).context("cannot write the file"));
+    match written {
+        Ok(()) => log::info!("Run summary written to {}", summary_path.display()),
+        Err(err) => log::error!(
+            "Cannot write the run summary {}: {:#}",
+            summary_path.display(),
+            err
+        ),
     }
 }
 
-/// Process all pending packet receptions across all nodes.
-async fn process_all_packet_receptions(
-    nodes_map: &mut HashMap<u32, Node>,
-    scene: &Scene,
-    total_received_packets: &mut u64,
-    total_collision: &mut u64,
-    ui_refresh_tx: &UIRefreshQueueSender,
-    total_sent_packets: u64,
-) {
-    for node in nodes_map.values_mut() {
-        if let Some((packet_index, packet_start, packet_end, packet_rssi)) =
-            find_next_packet_to_process(node)
-        {
-            process_packet_reception(
-                node,
-                packet_index,
-                packet_start,
-                packet_end,
-                packet_rssi,
-                scene,
-                total_received_packets,
-                total_collision,
-                ui_refresh_tx,
-                total_sent_packets,
-            )
-            .await;
-        }
+/// Ask every node to stop and wait until all node tasks finished: in-process node
+/// tasks stop their radio library tasks and return, freeing their task slots and
+/// queues for the next session, and node process tasks kill and reap their child,
+/// so no firmware process outlives the session.
+///
+/// Node outputs are drained meanwhile, so no node task stays blocked on a full queue.
+/// Nodes whose task already ended (failed) never take the `Shutdown`; the wait
+/// ends with the last running task.
+///
+/// # Parameters
+///
+/// * `nodes_map` - Map of all nodes
+/// * `nodes_output_channel` - Channel the node tasks report to
+async fn stop_nodes(nodes_map: &HashMap<u32, Node>, nodes_output_channel: &NodesOutputQueue) {
+    let mut pending: Vec<_> = nodes_map
+        .values()
+        .filter_map(|node| node.node_input_queue_sender.as_ref())
+        .collect();
+    while running_node_tasks() > 0 {
+        pending.retain(|sender| sender.try_send(NodeInputMessage::Shutdown).is_err());
+        while nodes_output_channel.try_receive().is_ok() {}
+        embassy_futures::yield_now().await;
     }
 }
 
 /// Adjust simulation speed based on processing delay (auto-speed controller).
-fn adjust_auto_speed(
+///
+/// Also drives the log playback speed of the analyzer (log visualization mode).
+pub fn adjust_auto_speed(
     time_delay: Duration,
     upcounter: &mut u32,
     auto_speed_min_percent: u32,
@@ -962,6 +2447,20 @@ fn adjust_auto_speed(
     }
 }
 
+/// Adjust simulation speed toward an active speed target (see `speed_target`).
+fn adjust_speed_target(
+    controller: &mut SpeedTargetController,
+    time_delay: Duration,
+    ui_refresh_tx: &UIRefreshQueueSender,
+) {
+    let current = time_driver::get_simulation_speed_percent();
+    let percent = controller.adjust(time_delay, current);
+    if percent != current {
+        time_driver::set_simulation_speed_percent(percent);
+        _ = ui_refresh_tx.try_send(UIRefreshState::SimulationSpeedChanged(percent));
+    }
+}
+
 /// Distribute captured logs from moonblokz_radio_lib to corresponding nodes.
 /// Logs are parsed for node_id prefix ([N]) and routed to the appropriate node's log buffer.
 fn distribute_captured_logs(
@@ -1004,10 +2503,26 @@ pub async fn network_task(
     ui_command_rx: UICommandQueueReceiver,
     scene_path: Option<String>,
 ) {
-    // Global counters for the UI
-    let mut total_collision = 0;
-    let mut total_received_packets = 0;
-    let mut total_sent_packets = 0;
+    // Releases the mode dispatcher however the task returns
+    let _session = session::SessionGuard;
+    // Measurements of every run of a scene get the same IDs
+    measurement_ids::reset(1);
+    // Radio events are counted on the bus and handed to its observers
+    let mut event_bus = EventBus::default();
+    event_bus.subscribe(UiForwarder { ui_refresh_tx });
+    event_bus.subscribe(EventRecorder);
+    // Every event is also indexed for searches across all nodes
+    let event_store = Rc::new(RefCell::new(EventStore::default()));
+    event_bus.subscribe(EventStoreObserver(Rc::clone(&event_store)));
+    let link_histograms = Rc::new(RefCell::new(LinkHistograms::default()));
+    event_bus.subscribe(LinkHistogramObserver(Rc::clone(&link_histograms)));
+    let node_statistics = SharedNodeStatistics::default();
+    event_bus.subscribe(StatsCollector::new(Rc::clone(&node_statistics)));
+    let collision_rate_limit = CollisionRateLimit::default();
+    event_bus.subscribe(AlertEngine::new(
+        Rc::clone(&collision_rate_limit),
+        ui_refresh_tx,
+    ));
     let mut connection_matrix_parser = ConnectionMatrixParser::new();
 
     // Get configuration file path (either from parameter or wait for UI command)
@@ -1020,32 +2535,181 @@ pub async fn network_task(
     };
 
     // Load and parse scene
-    let scene = match load_scene(&config_file_path, &ui_refresh_tx).await {
+    let mut scene = match load_scene(&config_file_path, &ui_refresh_tx, &ui_command_rx).await {
         Some(s) => s,
-        None => return,
+        None => {
+            ui_refresh_tx.send(UIRefreshState::SceneLoadEnded);
+            return;
+        }
     };
 
+    // Seed the simulation's random draws (repeatable runs with the same seed)
+    let seed = init_simulation_rng(scene.seed);
+    log::info!("Simulation random seed: {}", seed);
+    log::info!(
+        "Propagation model: {}",
+        scene.propagation_model.model().name()
+    );
+    provenance::record_seed(seed);
+
+    // Node start times (staggered starts draw their jitter from the seeded generator)
+    let mut bootstrap = BootstrapSchedule::new(&scene.nodes, scene.node_start.as_ref());
+
     // Initialize UI with scene data
-    initialize_scene_ui(&scene, &ui_refresh_tx).await;
+    initialize_scene_ui(&scene, &bootstrap, &ui_refresh_tx).await;
 
     // Set up nodes and spawn tasks
-    // INTENTIONAL LEAK: Box::leak provides 'static lifetime for Embassy channels.
-    // Required to use the embedded moonblokz-radio-lib code in the simulator.
-    let nodes_output_channel = Box::leak(Box::new(NodesOutputQueue::new()));
-    let mut nodes_map = initialize_nodes(&spawner, &scene, nodes_output_channel);
+    let nodes_output_channel = NODES_OUTPUT_QUEUES.take();
+    let Some(mut nodes_map) = initialize_nodes(
+        &spawner,
+        &scene,
+        &bootstrap,
+        &spill_directory(&config_file_path),
+        nodes_output_channel,
+        &ui_refresh_tx,
+        &ui_command_rx,
+    )
+    .await
+    else {
+        // The spawned nodes were stopped, nothing sends to the output queue anymore
+        NODES_OUTPUT_QUEUES.give_back(nodes_output_channel);
+        ui_refresh_tx.send(UIRefreshState::SceneLoadEnded);
+        return;
+    };
+    ui_refresh_tx.send(UIRefreshState::SceneLoadEnded);
+    // Every node is listed in the statistics, also before its first radio event
+    for &node_id in nodes_map.keys() {
+        node_statistics.borrow_mut().entry(node_id).or_default();
+    }
+    import_initial_link_matrices(&scene, &nodes_map, &ui_refresh_tx).await;
+    // From here on the run summary and node processes are flushed on window close
+    shutdown::register_flush();
 
     let mut delay_warning_issued = false;
+    // Real time of the last processing delay report (alert rules) and the largest
+    // delay since then
+    let mut last_delay_report = std::time::Instant::now();
+    let mut max_delay_since_report = Duration::from_millis(0);
     let cad_time = get_cad_time(&scene.lora_parameters);
 
     let mut upcounter = 0;
     let mut auto_speed_enabled = false;
     // Auto-speed guardrails to avoid stalling the simulation
-    let auto_speed_min_percent: u32 = 20; // don't go below 20%
-    let auto_speed_max_percent: u32 = 1000; // don't exceed UI slider's max
+    let auto_speed_min_percent = AUTO_SPEED_MIN_PERCENT;
+    let auto_speed_max_percent = AUTO_SPEED_MAX_PERCENT;
+    // Active "finish N virtual seconds within M wall-clock seconds" target, if any
+    let mut speed_target: Option<SpeedTargetController> = None;
+
+    // Scene-defined automatic measurement scheduling
+    let scene_start = Instant::now();
+    let mut auto_measurement_runs_started: u32 = 0;
+
+    // Scene-defined capture of the radio events
+    if let Some(path) = &scene.event_capture {
+        match CaptureWriter::create(path, scene_start) {
+            Ok(writer) => {
+                log::info!("Capturing the radio events to {}", path);
+                event_bus.subscribe(writer);
+            }
+            Err(message) => {
+                log::error!("{}", message);
+                ui_refresh_tx.send(UIRefreshState::Alert(message));
+            }
+        }
+    }
+
+    // Measurements keyed by measurement ID (several may be active concurrently; completed
+    // ones only keep their reach count, stopped ones are removed)
+    let mut measurements: HashMap<u32, MeasurementTracker> = HashMap::new();
+
+    // Scene-defined stress test (created when its start time is reached)
+    let mut stress_test: Option<StressTestRunner> = None;
+
+    // Scene-defined origin sampling experiment (created when its start time is reached)
+    let mut origin_sampling: Option<OriginSamplingRunner> = None;
+
+    // Scene-defined experiment script, also observing the radio events
+    let script = match &scene.script {
+        Some(config) => {
+            let node_ids = scene.nodes.iter().map(|n| n.node_id).collect();
+            match ScriptHost::load(config, node_ids, scene_start) {
+                Ok(host) => {
+                    let host = Rc::new(RefCell::new(host));
+                    event_bus.subscribe(ScriptObserver(Rc::clone(&host)));
+                    Some(host)
+                }
+                Err(message) => {
+                    log::error!("{}", message);
+                    ui_refresh_tx.send(UIRefreshState::Alert(message));
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Deliveries of gateway nodes to the backhaul
+    let mut backhaul = BackhaulTracker::new(
+        scene.backhaul.clone(),
+        scene
+            .nodes
+            .iter()
+            .filter(|n| n.role == NodeRole::Gateway)
+            .map(|n| n.node_id),
+    );
+
+    let mut next_statistics_publish = Instant::now();
+    // The memory warning is logged once per scene
+    let mut memory_warned = false;
+
+    // Scene-defined speed profile progress
+    let mut speed_profile_steps_applied: usize = 0;
+
+    // Stop conditions of unattended runs (scene, overridden by the command line)
+    let stop_conditions = effective_conditions(scene.stop_conditions.as_ref());
+    let mut stopped = false;
+
+    // Scene-defined co-channel LoRaWAN traffic
+    let mut external_traffic = scene.external_traffic.as_ref().map(|config| {
+        log::info!(
+            "External LoRaWAN traffic: {} devices, {} uplinks/h each at {} dBm, offered load {:.1}% of the channel",
+            config.devices,
+            config.uplinks_per_hour,
+            config.tx_power,
+            ExternalTraffic::offered_load(config, &scene) * 100.0
+        );
+        ExternalTraffic::new(config, &scene, scene_start)
+    });
+
+    // Scene-defined periodic checkpoints of long runs
+    let mut checkpoint_writer = scene
+        .checkpoints
+        .as_ref()
+        .map(|config| CheckpointWriter::new(config, &config_file_path, scene_start));
+
+    // Scene-defined link matrix sweeps (the first one starts after one interval)
+    let mut link_matrix_sweep = LinkMatrixSweep {
+        next_sweep: scene_start
+            + Duration::from_secs(
+                scene
+                    .link_matrix
+                    .as_ref()
+                    .and_then(|config| config.snapshot_interval)
+                    .unwrap_or(0),
+            ),
+        queue: VecDeque::new(),
+        next_request: scene_start,
+    };
+
+    let mut stage_timer = StageTimer::default();
 
     loop {
+        stage_timer.iteration();
+
         // Calculate the next interesting event time
-        let next_airtime_event = calculate_next_event_time(&nodes_map);
+        let next_airtime_event = stage_timer.measure(LoopStage::EventCalculation, || {
+            calculate_next_event_time(&nodes_map)
+        });
 
         // Keep the loop responsive even when no events are near by ticking every 10 ms
         let tick_deadline = Instant::now() + Duration::from_millis(10);
@@ -1065,20 +2729,37 @@ pub async fn network_task(
         {
             Either3::First(NodeOutputMessage { node_id, payload }) => match payload {
                 NodeOutputPayload::RadioTransfer(packet) => {
-                    handle_radio_transfer(
+                    let started = std::time::Instant::now();
+                    let relay_delay = handle_radio_transfer(
                         node_id,
                         packet,
                         &mut nodes_map,
                         &scene,
+                        &measurements,
                         &ui_refresh_tx,
-                        &mut total_sent_packets,
-                        total_received_packets,
-                        total_collision,
+                        &mut event_bus,
                     )
                     .await;
+                    if let Some(delay) = relay_delay {
+                        node_statistics
+                            .borrow_mut()
+                            .entry(node_id)
+                            .or_default()
+                            .record_relay(delay.as_millis());
+                    }
+                    stage_timer.record(LoopStage::RadioTransfers, started);
                 }
-                NodeOutputPayload::MessageReceived(_message) => {
-                    // TODO: handle message receipt UI/state if needed
+                NodeOutputPayload::MessageReceived(message) => {
+                    backhaul.record_reception(node_id, &message, Instant::now());
+
+                    // Stress test transactions carry their ID in the anchor sequence
+                    if message.message_type() == MessageType::AddTransaction as u8 {
+                        if let (Some(runner), Some(transaction_id)) =
+                            (stress_test.as_mut(), message.sequence())
+                        {
+                            runner.record_delivery(transaction_id, Instant::now());
+                        }
+                    }
                 }
                 NodeOutputPayload::FullMessageReceived {
                     message_type,
@@ -1114,21 +2795,65 @@ pub async fn network_task(
                         });
                     }
                 }
+                NodeOutputPayload::ProtocolState(state) => {
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::NodeProtocolStateUpdated(node_id, state))
+                        .ok();
+                }
                 NodeOutputPayload::RequestCAD => {
                     if let Some(node) = nodes_map.get_mut(&node_id) {
+                        node_statistics
+                            .borrow_mut()
+                            .entry(node_id)
+                            .or_default()
+                            .record_cad_request();
                         node.cad_waiting_list.push(CadItem {
                             start_time: Instant::now(),
                             end_time: Instant::now() + cad_time,
                         });
                     }
                 }
-                NodeOutputPayload::NodeReachedInMeasurement(measurement_id) => {
-                    ui_refresh_tx
-                        .try_send(UIRefreshState::NodeReachedInMeasurement(
-                            node_id,
+                NodeOutputPayload::NodeReachedInMeasurement {
+                    measurement_id,
+                    kind,
+                } => {
+                    // Only messages started as measurements are tracked (a stress test
+                    // transaction may share the ID of a block measurement)
+                    let tracked = measurements
+                        .get(&measurement_id)
+                        .is_some_and(|tracker| tracker.kind == kind);
+                    if tracked {
+                        // The relay delay is measured up to the node's first packet of the message
+                        let mut relayer = None;
+                        if let Some(node) = nodes_map.get_mut(&node_id) {
+                            node.pending_relays.insert(measurement_id, Instant::now());
+                            relayer = first_relayer(node, measurement_id, kind);
+                        }
+                        let reached = record_measurement_reach(
+                            &mut measurements,
                             measurement_id,
-                        ))
-                        .ok();
+                            node_id,
+                            nodes_map.len(),
+                        );
+                        if let Some(runner) = origin_sampling.as_mut() {
+                            runner.record_reach(measurement_id, reached, Instant::now());
+                        }
+                        if let Some(host) = &script {
+                            host.borrow_mut().on_reached(
+                                measurement_id,
+                                node_id,
+                                event_bus.counters(),
+                            );
+                        }
+                        ui_refresh_tx
+                            .try_send(UIRefreshState::NodeReachedInMeasurement(
+                                node_id,
+                                measurement_id,
+                                relayer,
+                                Instant::now(),
+                            ))
+                            .ok();
+                    }
                 }
             },
             Either3::Second(cmd) => match cmd {
@@ -1150,22 +2875,40 @@ pub async fn network_task(
                         }));
                     }
                 }
-                UICommand::StartMeasurement(node_id, measurement_identifier) => {
-                    if let Some(node) = nodes_map.get(&node_id) {
-                        if let Some(sender) = &node.node_input_queue_sender {
-                            let message_body: [u8; 2000] = [22; 2000];
-                            let message = RadioMessage::add_block_with(
-                                node_id,
-                                measurement_identifier,
-                                &message_body,
-                            );
-                            let _ = sender.send(NodeInputMessage::SendMessage(message)).await;
-                        }
-                    }
+                UICommand::StartMeasurement(node_id, measurement_identifier, kind) => {
+                    start_measurement(
+                        node_id,
+                        measurement_identifier,
+                        kind,
+                        &nodes_map,
+                        &mut measurements,
+                        &mut backhaul,
+                    )
+                    .await;
                 }
                 UICommand::SetAutoSpeed(enabled) => {
                     auto_speed_enabled = enabled;
                 }
+                UICommand::SetSpeedTarget(target) => {
+                    speed_target = target.map(|target| {
+                        log::info!(
+                            "Speed target: {} virtual s within {} s",
+                            target.virtual_secs,
+                            target.wall_clock_secs
+                        );
+                        SpeedTargetController::new(
+                            target,
+                            auto_speed_min_percent,
+                            auto_speed_max_percent,
+                        )
+                    });
+                    let status = speed_target.as_mut().map(|controller| {
+                        controller.status(time_driver::get_simulation_speed_percent())
+                    });
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::SpeedTargetUpdated(status))
+                        .ok();
+                }
                 UICommand::StartMode { .. } => {
                     // StartMode is handled by the mode selector before simulation starts
                     log::debug!("StartMode command ignored in running simulation");
@@ -1181,6 +2924,127 @@ pub async fn network_task(
                         }
                     }
                 }
+                UICommand::QueryNodeState(node_id) => {
+                    if let Some(node) = nodes_map.get(&node_id) {
+                        if let Some(sender) = &node.node_input_queue_sender {
+                            let _ = sender.send(NodeInputMessage::QueryState).await;
+                        }
+                    }
+                }
+                UICommand::QueryEvents(query) => {
+                    let result = event_store.borrow().query(&query);
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::EventQueryAnswered(result))
+                        .ok();
+                }
+                UICommand::QueryLinkHistograms(node_id) => {
+                    let links = link_histograms.borrow().incoming(node_id);
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::LinkHistogramsUpdated(node_id, links))
+                        .ok();
+                }
+                UICommand::StopMeasurement(measurement_id) => {
+                    measurements.remove(&measurement_id);
+                }
+                UICommand::DiagnoseUnreached(measurement_id, origin_node_id, reached) => {
+                    let reached: std::collections::HashSet<u32> = reached.into_iter().collect();
+                    let diagnosis = unreached_diagnosis::diagnose(
+                        origin_node_id,
+                        &reached,
+                        &nodes_map,
+                        &scene,
+                        &link_histograms.borrow(),
+                    );
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::UnreachedDiagnosed(
+                            measurement_id,
+                            diagnosis,
+                        ))
+                        .ok();
+                }
+                UICommand::UpdateObstacles(obstacles) => {
+                    update_obstacles(&mut scene, obstacles, &ui_refresh_tx).await;
+                }
+                UICommand::MoveNode(node_id, position) => {
+                    move_node(
+                        &mut nodes_map,
+                        node_id,
+                        position,
+                        &scene.transform,
+                        &ui_refresh_tx,
+                    )
+                    .await;
+                }
+                UICommand::SetNodeEnabled(node_id, enabled) => {
+                    set_node_enabled(&mut nodes_map, node_id, enabled);
+                }
+                UICommand::SetNodeTxPower(node_id, tx_power) => {
+                    set_node_tx_power(&mut nodes_map, &scene, node_id, tx_power, &ui_refresh_tx)
+                        .await;
+                }
+                UICommand::ExportConnectivityGraph(path) => {
+                    match connectivity_graph::export(
+                        &nodes_map,
+                        &scene,
+                        std::path::Path::new(&path),
+                    ) {
+                        Ok(summary) => log::info!("{}", summary),
+                        Err(message) => {
+                            log::error!("{}", message);
+                            ui_refresh_tx.send(UIRefreshState::Alert(message));
+                        }
+                    }
+                }
+                UICommand::CancelSceneLoad => {
+                    log::debug!("CancelSceneLoad ignored, the scene is already loaded");
+                }
+                UICommand::ProbeSignal(node_id, position) => {
+                    if let Some(node) = nodes_map.get(&node_id) {
+                        ui_refresh_tx.send(UIRefreshState::SignalProbed(signal_probe::probe(
+                            node, &position, &scene,
+                        )));
+                    }
+                }
+                UICommand::SetCollisionRateAlert(limit) => {
+                    collision_rate_limit.set(limit);
+                }
+                UICommand::SetPlaybackPaused(_) | UICommand::RequestNodeStateAt(..) => {
+                    // Time travel only applies to log visualization
+                    log::debug!("Log playback command ignored in simulation mode");
+                }
+                UICommand::OpenSite { .. } => {
+                    log::debug!("Site command ignored in simulation mode");
+                }
+                UICommand::Shutdown | UICommand::EndSession => {
+                    let reason = if matches!(cmd, UICommand::Shutdown) {
+                        "window closed"
+                    } else {
+                        "session ended"
+                    };
+                    log::info!("Simulation {}, flushing its outputs", reason);
+                    time_driver::set_simulation_paused(true);
+                    if let Some(conditions) = stop_conditions.as_ref().filter(|_| !stopped) {
+                        let summary = run_summary(
+                            reason,
+                            seed,
+                            scene_start,
+                            scene.radio_module_config.tx_maximum_random_delay,
+                            &measurements,
+                            &nodes_map,
+                            RunTotals {
+                                counters: event_bus.counters(),
+                                statistics: &node_statistics.borrow(),
+                            },
+                        );
+                        write_run_summary(&summary, &summary_path(&config_file_path, conditions));
+                    }
+                    nodes_map.values_mut().for_each(Node::flush_history_spill);
+                    event_bus.flush();
+                    stop_nodes(&nodes_map, nodes_output_channel).await;
+                    NODES_OUTPUT_QUEUES.give_back(nodes_output_channel);
+                    shutdown::flush_complete();
+                    return;
+                }
             },
             Either3::Third(_) => {
                 // Determine whether the real event was reached or this was just the periodic tick
@@ -1192,6 +3056,7 @@ pub async fn network_task(
                     // Check and report processing delay relative to the scheduled event
                     let time_delay = now.duration_since(next_airtime_event);
                     delay_for_autospeed = Some(time_delay);
+                    max_delay_since_report = max_delay_since_report.max(time_delay);
                     if time_delay > Duration::from_millis(10) {
                         if !delay_warning_issued {
                             delay_warning_issued = true;
@@ -1206,41 +3071,278 @@ pub async fn network_task(
                         );
                     }
                 }
+                if last_delay_report.elapsed() >= std::time::Duration::from_secs(1) {
+                    last_delay_report = std::time::Instant::now();
+                    let _ = ui_refresh_tx.try_send(UIRefreshState::SimulationDelayUpdated(
+                        max_delay_since_report,
+                    ));
+                    max_delay_since_report = Duration::from_millis(0);
+                }
 
-                if auto_speed_enabled {
-                    if let Some(time_delay) = delay_for_autospeed {
-                        adjust_auto_speed(
-                            time_delay,
-                            &mut upcounter,
-                            auto_speed_min_percent,
-                            auto_speed_max_percent,
-                            &ui_refresh_tx,
-                        );
+                // Once a speed profile step applied, the profile owns the speed: auto
+                // speed and the speed target pause instead of overriding its segments
+                if speed_profile_steps_applied == 0 {
+                    if let (Some(controller), Some(time_delay)) =
+                        (speed_target.as_mut(), delay_for_autospeed)
+                    {
+                        adjust_speed_target(controller, time_delay, &ui_refresh_tx);
+                    } else if auto_speed_enabled {
+                        if let Some(time_delay) = delay_for_autospeed {
+                            adjust_auto_speed(
+                                time_delay,
+                                &mut upcounter,
+                                auto_speed_min_percent,
+                                auto_speed_max_percent,
+                                &ui_refresh_tx,
+                            );
+                        }
                     }
                 }
 
-                // Distribute captured logs from moonblokz_radio_lib to nodes
-                distribute_captured_logs(
-                    &mut nodes_map,
-                    &mut connection_matrix_parser,
+                // Switch simulation speed at the speed profile boundaries
+                let started = std::time::Instant::now();
+                check_speed_profile(
+                    &scene,
+                    scene_start,
+                    &mut speed_profile_steps_applied,
                     &ui_refresh_tx,
                 );
 
+                // Spawn the nodes joining the network now
+                start_due_nodes(
+                    &spawner,
+                    &scene,
+                    scene_start,
+                    &mut bootstrap,
+                    &mut nodes_map,
+                    nodes_output_channel,
+                    &ui_refresh_tx,
+                )
+                .await;
+
+                // Start the scene-defined measurement when due
+                check_auto_measurement(
+                    &scene,
+                    scene_start,
+                    &mut auto_measurement_runs_started,
+                    &nodes_map,
+                    &mut measurements,
+                    &mut backhaul,
+                    &ui_refresh_tx,
+                )
+                .await;
+
+                // Drive the scene-defined stress test
+                check_stress_test(
+                    &scene,
+                    scene_start,
+                    &mut stress_test,
+                    &nodes_map,
+                    &mut backhaul,
+                    event_bus.counters(),
+                    &ui_refresh_tx,
+                )
+                .await;
+
+                // Drive the scene-defined origin sampling experiment
+                check_origin_sampling(
+                    &scene,
+                    scene_start,
+                    &mut origin_sampling,
+                    &nodes_map,
+                    &mut measurements,
+                    &mut backhaul,
+                    &ui_refresh_tx,
+                )
+                .await;
+
+                // Run the script's tick hook and apply the commands of all its hooks
+                if let Some(host) = &script {
+                    let actions = {
+                        let mut host = host.borrow_mut();
+                        host.tick(Instant::now(), event_bus.counters());
+                        host.take_actions()
+                    };
+                    apply_script_actions(
+                        actions,
+                        &mut nodes_map,
+                        &mut measurements,
+                        &mut backhaul,
+                        &ui_refresh_tx,
+                    )
+                    .await;
+                }
+
+                // Ask the next node of a link matrix sweep for its matrix
+                check_link_matrix_sweep(&scene, &mut link_matrix_sweep, &nodes_map).await;
+
+                // Forward messages that crossed the backhaul to the other gateways
+                send_backhaul_reinjections(&mut backhaul, &nodes_map).await;
+                stage_timer.record(LoopStage::SceneDrivers, started);
+
+                // Publish per-node and backhaul statistics once per second
+                if Instant::now() >= next_statistics_publish {
+                    let started = std::time::Instant::now();
+                    next_statistics_publish = Instant::now() + Duration::from_secs(1);
+                    let statistics = {
+                        let mut statistics = node_statistics.borrow_mut();
+                        for node in nodes_map.values() {
+                            statistics.entry(node.node_id).or_default().memory_bytes =
+                                node.estimated_memory_bytes();
+                        }
+                        statistics.clone()
+                    };
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::NodeStatisticsUpdated(statistics))
+                        .ok();
+                    let memory_usage = MemoryUsage::measure(&nodes_map, &scene.history);
+                    if memory_usage.exceeds_warning() && !memory_warned {
+                        memory_warned = true;
+                        log::warn!(
+                            "Memory usage above {} MiB (node histories: {} MiB); reduce the scene's history limits or retention",
+                            scene.history.memory_warning_mb,
+                            memory_usage.estimated_bytes / (1024 * 1024)
+                        );
+                    }
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::MemoryUsageUpdated(memory_usage))
+                        .ok();
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::NodeHealthUpdated(watchdog::check(
+                            &mut nodes_map,
+                        )))
+                        .ok();
+                    if let Some(statistics) = backhaul.take_statistics() {
+                        ui_refresh_tx
+                            .try_send(UIRefreshState::BackhaulStatisticsUpdated(statistics))
+                            .ok();
+                    }
+                    if let Some(controller) = speed_target.as_mut() {
+                        let status = controller.status(time_driver::get_simulation_speed_percent());
+                        if status.finished {
+                            log::info!(
+                                "Speed target reached: {} virtual s after {:.0} s",
+                                status.target.virtual_secs,
+                                status.wall_elapsed_secs
+                            );
+                            speed_target = None;
+                        }
+                        ui_refresh_tx
+                            .try_send(UIRefreshState::SpeedTargetUpdated(Some(status)))
+                            .ok();
+                    }
+                    stage_timer.record(LoopStage::UiNotifications, started);
+                    ui_refresh_tx
+                        .try_send(UIRefreshState::StageTimingsUpdated(
+                            stage_timer.take_report(),
+                        ))
+                        .ok();
+                }
+
+                // Write a metrics snapshot when the next checkpoint is due (not after the stop)
+                if let Some(writer) = checkpoint_writer
+                    .as_mut()
+                    .filter(|writer| !stopped && writer.due(Instant::now()))
+                {
+                    let snapshot = run_summary(
+                        "checkpoint",
+                        seed,
+                        scene_start,
+                        scene.radio_module_config.tx_maximum_random_delay,
+                        &measurements,
+                        &nodes_map,
+                        RunTotals {
+                            counters: event_bus.counters(),
+                            statistics: &node_statistics.borrow(),
+                        },
+                    );
+                    match writer.write(&snapshot, Instant::now()) {
+                        Ok(path) => log::info!("Checkpoint written to {}", path.display()),
+                        Err(err) => log::error!("Cannot write the checkpoint: {:#}", err),
+                    }
+                }
+
+                // End the run at the first stop condition met
+                if let Some(conditions) = stop_conditions.as_ref().filter(|_| !stopped) {
+                    let progress =
+                        RunProgress {
+                            elapsed_secs: Instant::now()
+                                .saturating_duration_since(scene_start)
+                                .as_secs(),
+                            measurements: &measurements,
+                            measurements_pending: scene.auto_measurement.as_ref().is_some_and(
+                                |auto| auto_measurement_runs_started < auto.repeat_count,
+                            ) || (scene.origin_sampling.is_some()
+                                && origin_sampling
+                                    .as_ref()
+                                    .is_none_or(|runner| !runner.is_finished(Instant::now()))),
+                        };
+                    if let Some(reason) = stop_reason(conditions, &progress) {
+                        stopped = true;
+                        let summary = run_summary(
+                            &reason,
+                            seed,
+                            scene_start,
+                            scene.radio_module_config.tx_maximum_random_delay,
+                            &measurements,
+                            &nodes_map,
+                            RunTotals {
+                                counters: event_bus.counters(),
+                                statistics: &node_statistics.borrow(),
+                            },
+                        );
+                        let path = summary_path(&config_file_path, conditions);
+                        if let Some(traffic) = &external_traffic {
+                            log::info!("External LoRaWAN uplinks sent: {}", traffic.uplinks());
+                        }
+                        nodes_map.values_mut().for_each(Node::flush_history_spill);
+                        event_bus.flush();
+                        stop_run(reason, summary, path, &node_statistics, &ui_refresh_tx).await;
+                    }
+                }
+
+                // Distribute captured logs from moonblokz_radio_lib to nodes
+                stage_timer.measure(LoopStage::LogDistribution, || {
+                    distribute_captured_logs(
+                        &mut nodes_map,
+                        &mut connection_matrix_parser,
+                        &ui_refresh_tx,
+                    )
+                });
+
                 // Only run event processing when the actual event deadline was reached
                 if event_reached {
+                    // Move buffered deliveries into the node queues that drained
+                    if scene.node_queue.full_policy == QueueFullPolicy::Grow {
+                        watchdog::flush_overflows(&mut nodes_map);
+                    }
+
+                    // Send the external uplinks that started by now
+                    if let Some(traffic) = external_traffic.as_mut() {
+                        traffic.advance(Instant::now());
+                    }
+
                     // Process CAD requests
-                    process_cad_requests(&mut nodes_map);
+                    let started = std::time::Instant::now();
+                    process_cad_requests(
+                        &mut nodes_map,
+                        &scene,
+                        external_traffic.as_ref(),
+                        &node_statistics,
+                    )
+                    .await;
+                    stage_timer.record(LoopStage::CadProcessing, started);
 
                     // Process all pending packet receptions
+                    let started = std::time::Instant::now();
                     process_all_packet_receptions(
                         &mut nodes_map,
                         &scene,
-                        &mut total_received_packets,
-                        &mut total_collision,
-                        &ui_refresh_tx,
-                        total_sent_packets,
+                        external_traffic.as_ref(),
+                        &mut event_bus,
                     )
                     .await;
+                    stage_timer.record(LoopStage::ReceptionProcessing, started);
                 } // event_reached
             }
         }
@@ -0,0 +1,607 @@
+//! Node backend running the real embedded firmware as subprocesses.
+//!
+//! With `node_process` configured in the scene, every node is a subprocess of a
+//! host-target firmware build instead of an in-process `node_task`. The firmware's
+//! radio device is a socket shim: the simulator listens on a localhost TCP port per
+//! node, starts the binary with `MOONBLOKZ_NODE_ID` and `MOONBLOKZ_RADIO_SOCKET`
+//! (`127.0.0.1:<port>`) in its environment and exchanges one text frame per line.
+//! Packets and messages are hex encoded.
+//!
+//! Simulator → node:
+//! - `CONFIG <json>`: radio module configuration (first frame after connecting)
+//! - `RX <link_quality> <packet>`: deliver a received packet
+//! - `CAD <0|1>`: result of the requested channel activity detection
+//! - `SEND <message_type> <payload>`: originate a message (measurements, stress test)
+//! - `MATRIX`: log the connection matrix
+//! - `IMPORT {"node_ids": [...], "values": [[...]]}`: load an imported connection
+//!   matrix (link qualities per node pair)
+//! - `STATE`: report the radio manager's internal state
+//! - `QUIT`: the simulator shuts down (the process is killed right after)
+//!
+//! Node → simulator:
+//! - `TX <packet>`: transmit a packet
+//! - `CAD`: request channel activity detection
+//! - `RECEIVED <message_type> <sender> <sequence> <length>`: full message received
+//! - `STATE <json>`: answer to `STATE` (`NodeProtocolState` fields)
+//!
+//! `node_process_task` bridges these frames to the same node channels as `node_task`,
+//! so the network task applies identical physics to both backends. Lines the
+//! firmware writes to stdout appear in the node's log stream.
+//!
+//! Socket I/O runs on plain threads per node: a writer fed by a bounded channel (a
+//! node process that stops reading is reported instead of blocking the executor) and
+//! a reader that parks while the frame queue is full.
+
+use anyhow::{Context, Result, anyhow, bail};
+use embassy_futures::select::{Either, select};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::Channel;
+use embassy_time::{Duration, Timer};
+use moonblokz_radio_lib::{MAX_NODE_COUNT, RadioPacket};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::task::{Poll, Wake, Waker};
+
+use super::log_capture::capture_node_log;
+use super::node_task::{NODE_INPUT_QUEUES, RunningNodeTask};
+use super::queue_pool::QueuePool;
+use super::types::{
+    LogLevel, NodeInputMessage, NodeInputQueue, NodeOutputMessage, NodeOutputPayload,
+    NodeProcessConfig, NodeProtocolState, NodesOutputQueueSender, RadioModuleConfig,
+    measurement_kind,
+};
+use crate::ui::MeasurementKind;
+
+/// Environment variable holding the node ID of a node process.
+const NODE_ID_ENV: &str = "MOONBLOKZ_NODE_ID";
+/// Environment variable holding the radio socket address of a node process.
+const RADIO_SOCKET_ENV: &str = "MOONBLOKZ_RADIO_SOCKET";
+
+/// Depth of the channel carrying frames from the socket reader thread.
+const NODE_FRAME_QUEUE_SIZE: usize = 16;
+/// Frames read from a node process; `None` once the connection closed.
+type NodeFrameQueue = Channel<CriticalSectionRawMutex, Option<NodeFrame>, NODE_FRAME_QUEUE_SIZE>;
+/// Frame queues whose reader thread finished.
+static NODE_FRAME_QUEUES: QueuePool<NodeFrameQueue> = QueuePool::new();
+/// Frames waiting for the socket writer thread; a full queue means the node process
+/// stopped reading its radio socket.
+const NODE_WRITE_QUEUE_SIZE: usize = 256;
+/// Frame telling the node process that the simulator shuts down.
+const QUIT_FRAME: &str = "QUIT";
+
+/// Frame sent by a node process.
+#[derive(Debug, Clone, PartialEq)]
+enum NodeFrame {
+    /// Packet bytes to transmit.
+    Transmit(Vec<u8>),
+    /// Channel activity detection request.
+    RequestCad,
+    /// A full message was received.
+    Received {
+        message_type: u8,
+        sender_node: u32,
+        sequence: u32,
+        length: usize,
+    },
+    /// Radio manager state reported for a `STATE` request.
+    ProtocolState(NodeProtocolState),
+}
+
+/// Encode bytes as lowercase hex.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode a hex string.
+fn from_hex(text: &str) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        bail!("odd hex length");
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| {
+            let digits = text.get(i..i + 2).ok_or_else(|| anyhow!("invalid hex"))?;
+            u8::from_str_radix(digits, 16).with_context(|| format!("invalid hex byte {}", digits))
+        })
+        .collect()
+}
+
+/// Parse a frame sent by a node process.
+///
+/// # Parameters
+///
+/// * `line` - One line read from the radio socket
+///
+/// # Returns
+///
+/// The frame, or an error for unknown or malformed frames.
+fn parse_node_frame(line: &str) -> Result<NodeFrame> {
+    let mut fields = line.split_whitespace();
+    let frame = match fields.next() {
+        Some("TX") => NodeFrame::Transmit(from_hex(
+            fields.next().ok_or_else(|| anyhow!("TX without packet"))?,
+        )?),
+        Some("CAD") => NodeFrame::RequestCad,
+        Some("RECEIVED") => {
+            let mut number = |name: &str| -> Result<u64> {
+                fields
+                    .next()
+                    .ok_or_else(|| anyhow!("RECEIVED without {}", name))?
+                    .parse()
+                    .with_context(|| format!("invalid {}", name))
+            };
+            NodeFrame::Received {
+                message_type: number("message type")?.try_into()?,
+                sender_node: number("sender")?.try_into()?,
+                sequence: number("sequence")?.try_into()?,
+                length: number("length")?.try_into()?,
+            }
+        }
+        Some("STATE") => {
+            let json = line
+                .trim()
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| anyhow!("STATE without state"))?
+                .1;
+            NodeFrame::ProtocolState(serde_json::from_str(json).context("invalid state")?)
+        }
+        Some(other) => bail!("unknown frame {}", other),
+        None => bail!("empty frame"),
+    };
+    Ok(frame)
+}
+
+/// Encode a node input as a frame for the node process.
+fn encode_input(input: &NodeInputMessage) -> String {
+    match input {
+        NodeInputMessage::RadioTransfer(received) => format!(
+            "RX {} {}",
+            received.link_quality,
+            to_hex(&received.packet.data[..received.packet.length])
+        ),
+        NodeInputMessage::SendMessage(message) => format!(
+            "SEND {} {}",
+            message.message_type(),
+            to_hex(message.payload())
+        ),
+        NodeInputMessage::CADResponse(activity) => format!("CAD {}", u8::from(*activity)),
+        NodeInputMessage::RequestConnectionMatrix => "MATRIX".to_string(),
+        NodeInputMessage::QueryState => "STATE".to_string(),
+        NodeInputMessage::ImportConnectionMatrix(matrix) => format!(
+            "IMPORT {}",
+            serde_json::json!({ "node_ids": matrix.node_ids, "values": matrix.values })
+        ),
+        NodeInputMessage::Shutdown => QUIT_FRAME.to_string(),
+    }
+}
+
+/// Build a radio packet from the bytes of a `TX` frame.
+fn packet_from_bytes(bytes: &[u8]) -> Result<RadioPacket> {
+    let packet = RadioPacket {
+        data: std::array::from_fn(|i| bytes.get(i).copied().unwrap_or(0)),
+        length: bytes.len(),
+    };
+    if bytes.len() > packet.data.len() {
+        bail!(
+            "packet of {} bytes exceeds the radio packet size",
+            bytes.len()
+        );
+    }
+    Ok(packet)
+}
+
+/// Log level of a firmware output line (the first level name among its leading words).
+fn line_level(line: &str) -> LogLevel {
+    line.split_whitespace()
+        .take(3)
+        .find_map(
+            |word| match word.trim_matches(|c: char| c == '[' || c == ']') {
+                "ERROR" => Some(LogLevel::Error),
+                "WARN" => Some(LogLevel::Warn),
+                "INFO" => Some(LogLevel::Info),
+                "DEBUG" => Some(LogLevel::Debug),
+                "TRACE" => Some(LogLevel::Trace),
+                _ => None,
+            },
+        )
+        .unwrap_or(LogLevel::Info)
+}
+
+/// Start the node process and wait until it connects to its radio socket.
+async fn launch(config: &NodeProcessConfig, node_id: u32) -> Result<(Child, TcpStream)> {
+    let listener = TcpListener::bind("127.0.0.1:0").context("cannot open radio socket")?;
+    listener.set_nonblocking(true)?;
+    let address = listener.local_addr()?;
+
+    let mut child = Command::new(&config.binary)
+        .args(&config.args)
+        .env(NODE_ID_ENV, node_id.to_string())
+        .env(RADIO_SOCKET_ENV, address.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("cannot start {}", config.binary))?;
+
+    // Real time: the firmware starts independently of the simulation speed
+    let deadline =
+        std::time::Instant::now() + std::time::Duration::from_secs(config.connect_timeout);
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                stream.set_nodelay(true)?;
+                return Ok((child, stream));
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                if let Some(status) = child.try_wait()? {
+                    bail!("exited before connecting ({})", status);
+                }
+                if std::time::Instant::now() >= deadline {
+                    let _ = child.kill();
+                    bail!("did not connect within {} s", config.connect_timeout);
+                }
+                Timer::after(Duration::from_millis(20)).await;
+            }
+            Err(err) => return Err(err).context("radio socket accept failed"),
+        }
+    }
+}
+
+/// Forward the firmware's stdout lines into the node's log stream (own thread).
+fn spawn_output_reader(child: &mut Child, node_id: u32) {
+    let Some(stdout) = child.stdout.take() else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            capture_node_log(node_id, line_level(&line), line);
+        }
+    });
+}
+
+/// Waker unparking the thread waiting in `park_on`.
+struct ThreadWaker(std::thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run a future to completion on a plain thread, parking the thread until the
+/// future is woken (`embassy_futures::block_on` polls in a busy loop).
+fn park_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut context = std::task::Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::park();
+    }
+}
+
+/// Read frames from the radio socket into `frames` (own thread).
+fn spawn_frame_reader(stream: TcpStream, node_id: u32, frames: &'static NodeFrameQueue) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            match parse_node_frame(&line) {
+                Ok(frame) => park_on(frames.send(Some(frame))),
+                Err(err) => log::warn!("Node process {}: {:#} in '{}'", node_id, err, line),
+            }
+        }
+        park_on(frames.send(None));
+    });
+}
+
+/// Write the queued frames to the radio socket (own thread).
+///
+/// A failed write is reported and closes the socket, which also ends the frame
+/// reader and with it the node's task.
+///
+/// # Returns
+///
+/// The sender of the frames (without the trailing newline).
+fn spawn_frame_writer(mut stream: TcpStream, node_id: u32) -> SyncSender<String> {
+    let (frames_tx, frames_rx) = mpsc::sync_channel::<String>(NODE_WRITE_QUEUE_SIZE);
+    std::thread::spawn(move || {
+        for frame in frames_rx {
+            if let Err(err) = stream.write_all(format!("{}\n", frame).as_bytes()) {
+                // The process is killed right after QUIT, so a failed QUIT write is fine
+                if frame != QUIT_FRAME {
+                    report_failure(node_id, format!("radio socket write failed: {}", err));
+                }
+                let _ = stream.shutdown(Shutdown::Both);
+                break;
+            }
+        }
+    });
+    frames_tx
+}
+
+/// Report a node process failure in the application log and the node's log stream.
+fn report_failure(node_id: u32, message: String) {
+    log::error!("Node process {}: {}", node_id, message);
+    capture_node_log(node_id, LogLevel::Error, message);
+}
+
+/// Per-node task running the firmware binary as a subprocess.
+///
+/// Counterpart of `node_task` with the same channels: node inputs are handed to the
+/// socket writer thread and frames from the firmware are published to the network
+/// task. The task ends (and the node stays silent) if the configuration cannot be
+/// encoded, the process cannot be started, stops reading or the connection closes.
+/// After `Shutdown` the node input queue is returned for reuse, and the frame queue
+/// once its reader thread saw the socket close.
+///
+/// # Parameters
+///
+/// * `config` - Node process configuration from the scene
+/// * `radio_module_config` - Radio configuration sent to the firmware
+/// * `node_id` - Node ID passed to the firmware
+/// * `out_tx` - Channel to the network task
+/// * `in_queue` - Inputs from the network task
+/// * `_running` - Counts the task as running until it returns
+#[embassy_executor::task(pool_size = MAX_NODE_COUNT)]
+pub async fn node_process_task(
+    config: NodeProcessConfig,
+    radio_module_config: RadioModuleConfig,
+    node_id: u32,
+    out_tx: NodesOutputQueueSender,
+    in_queue: &'static NodeInputQueue,
+    _running: RunningNodeTask,
+) {
+    let in_rx = in_queue.receiver();
+    let config_json = match serde_json::to_string(&radio_module_config) {
+        Ok(json) => json,
+        Err(err) => {
+            report_failure(node_id, format!("cannot encode configuration: {}", err));
+            return;
+        }
+    };
+    let (mut child, stream) = match launch(&config, node_id).await {
+        Ok(launched) => launched,
+        Err(err) => {
+            report_failure(node_id, format!("{:#}", err));
+            return;
+        }
+    };
+    spawn_output_reader(&mut child, node_id);
+
+    let frames = NODE_FRAME_QUEUES.take();
+    match stream.try_clone() {
+        Ok(reader) => spawn_frame_reader(reader, node_id, frames),
+        Err(err) => {
+            report_failure(node_id, format!("cannot read radio socket: {}", err));
+            let _ = child.kill();
+            NODE_FRAME_QUEUES.give_back(frames);
+            return;
+        }
+    }
+
+    let writer = spawn_frame_writer(stream, node_id);
+    // The first frame of an empty queue; a failed write ends the task through the reader
+    let _ = writer.try_send(format!("CONFIG {}", config_json));
+
+    // Measurement reach is reported once per AddBlock sequence and AddTransaction
+    // anchor sequence, as in `node_task`
+    let mut reached_sequences: HashSet<(MeasurementKind, u32)> = HashSet::new();
+    // Whether the reader thread sent its final `None` (socket closed)
+    let mut reader_finished = false;
+    let mut shut_down = false;
+
+    loop {
+        match select(in_rx.receive(), frames.receive()).await {
+            Either::First(input) => {
+                if let NodeInputMessage::SendMessage(message) = &input {
+                    if let Some(kind) = measurement_kind(message.message_type()) {
+                        if let Some(sequence) = message.sequence() {
+                            reached_sequences.insert((kind, sequence));
+                            let _ = out_tx
+                                .send(NodeOutputMessage {
+                                    node_id,
+                                    payload: NodeOutputPayload::FullMessageSent {
+                                        message_type: message.message_type(),
+                                        sender_node: node_id,
+                                        sequence,
+                                        length: message.payload().len(),
+                                    },
+                                })
+                                .await;
+                        }
+                    }
+                }
+                let sent = writer.try_send(encode_input(&input));
+                if matches!(input, NodeInputMessage::Shutdown) {
+                    // The process is killed right after, so a lost QUIT is fine
+                    shut_down = true;
+                    break;
+                }
+                match sent {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        report_failure(node_id, "stopped reading its radio socket".to_string());
+                        break;
+                    }
+                    // The writer thread already reported the failed write
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+            Either::Second(Some(NodeFrame::Transmit(bytes))) => match packet_from_bytes(&bytes) {
+                Ok(packet) => {
+                    let _ = out_tx
+                        .send(NodeOutputMessage {
+                            node_id,
+                            payload: NodeOutputPayload::RadioTransfer(packet),
+                        })
+                        .await;
+                }
+                Err(err) => log::warn!("Node process {}: {:#}", node_id, err),
+            },
+            Either::Second(Some(NodeFrame::RequestCad)) => {
+                let _ = out_tx
+                    .send(NodeOutputMessage {
+                        node_id,
+                        payload: NodeOutputPayload::RequestCAD,
+                    })
+                    .await;
+            }
+            Either::Second(Some(NodeFrame::Received {
+                message_type,
+                sender_node,
+                sequence,
+                length,
+            })) => {
+                if let Some(kind) = measurement_kind(message_type) {
+                    if !reached_sequences.insert((kind, sequence)) {
+                        continue;
+                    }
+                    let _ = out_tx
+                        .send(NodeOutputMessage {
+                            node_id,
+                            payload: NodeOutputPayload::NodeReachedInMeasurement {
+                                measurement_id: sequence,
+                                kind,
+                            },
+                        })
+                        .await;
+                }
+                let _ = out_tx
+                    .send(NodeOutputMessage {
+                        node_id,
+                        payload: NodeOutputPayload::FullMessageReceived {
+                            message_type,
+                            sender_node,
+                            sequence,
+                            length,
+                        },
+                    })
+                    .await;
+            }
+            Either::Second(Some(NodeFrame::ProtocolState(state))) => {
+                let _ = out_tx
+                    .send(NodeOutputMessage {
+                        node_id,
+                        payload: NodeOutputPayload::ProtocolState(state),
+                    })
+                    .await;
+            }
+            Either::Second(None) => {
+                report_failure(node_id, "radio socket closed".to_string());
+                reader_finished = true;
+                break;
+            }
+        }
+    }
+
+    // The writer thread ends once it wrote the queued frames (or the socket closes)
+    drop(writer);
+    stop_process(&mut child, node_id, frames, reader_finished).await;
+    if shut_down {
+        // The network task sends nothing after `Shutdown`; after a failure it may
+        // still deliver to the node, so the queue stays leaked
+        NODE_INPUT_QUEUES.give_back(in_queue);
+    }
+}
+
+/// Kill and reap a node process, then return its frame queue for reuse.
+///
+/// The killed process closes its socket, so the reader thread ends; the queue is
+/// drained until the thread's final `None`, which also unblocks a thread waiting
+/// on a full queue.
+///
+/// # Parameters
+///
+/// * `child` - The node process
+/// * `node_id` - Node ID for the log
+/// * `frames` - Frame queue of the node's reader thread
+/// * `reader_finished` - Whether the final `None` was already received
+async fn stop_process(
+    child: &mut Child,
+    node_id: u32,
+    frames: &'static NodeFrameQueue,
+    reader_finished: bool,
+) {
+    let _ = child.kill();
+    if let Ok(status) = child.wait() {
+        log::info!("Node process {} finished ({})", node_id, status);
+    }
+    if !reader_finished {
+        while frames.receive().await.is_some() {}
+    }
+    NODE_FRAME_QUEUES.give_back(frames);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_node_frames_and_hex() {
+        assert_eq!(
+            parse_node_frame("TX 0a0bff").unwrap(),
+            NodeFrame::Transmit(vec![0x0a, 0x0b, 0xff])
+        );
+        assert_eq!(parse_node_frame("CAD").unwrap(), NodeFrame::RequestCad);
+        assert_eq!(
+            parse_node_frame("RECEIVED 6 12 3001 215").unwrap(),
+            NodeFrame::Received {
+                message_type: 6,
+                sender_node: 12,
+                sequence: 3001,
+                length: 215,
+            }
+        );
+        assert!(parse_node_frame("TX 0a0").is_err());
+        assert!(parse_node_frame("RECEIVED 6 12").is_err());
+        assert!(parse_node_frame("HELLO").is_err());
+        match parse_node_frame(
+            r#"STATE {"neighbors": [{"node_id": 4, "score": 200}], "tx_queue_len": 2, "next_echo_request_ms": 1500, "echo_gathering_end_ms": null}"#,
+        )
+        .unwrap()
+        {
+            NodeFrame::ProtocolState(state) => {
+                assert_eq!(state.neighbors.len(), 1);
+                assert_eq!(state.tx_queue_len, 2);
+                assert_eq!(state.next_echo_request_ms, Some(1500));
+            }
+            other => panic!("unexpected frame {:?}", other),
+        }
+
+        assert_eq!(
+            from_hex(&to_hex(&[0, 1, 254, 255])).unwrap(),
+            vec![0, 1, 254, 255]
+        );
+        assert_eq!(encode_input(&NodeInputMessage::CADResponse(true)), "CAD 1");
+        assert_eq!(encode_input(&NodeInputMessage::Shutdown), "QUIT");
+        assert_eq!(
+            line_level("[2025-01-01T00:00:00Z WARN node] queue full"),
+            LogLevel::Warn
+        );
+    }
+
+    #[test]
+    fn test_park_on_waits_for_queue_space() {
+        let frames = NODE_FRAME_QUEUES.take();
+        for _ in 0..NODE_FRAME_QUEUE_SIZE {
+            frames.try_send(None).unwrap();
+        }
+        let reader = std::thread::spawn(move || park_on(frames.send(Some(NodeFrame::RequestCad))));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!reader.is_finished());
+
+        // Receiving wakes the parked thread
+        assert_eq!(frames.try_receive().unwrap(), None);
+        reader.join().unwrap();
+        let mut last = None;
+        while let Ok(frame) = frames.try_receive() {
+            last = frame;
+        }
+        assert_eq!(last, Some(NodeFrame::RequestCad));
+    }
+}
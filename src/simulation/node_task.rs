@@ -8,6 +8,7 @@
 
 use embassy_executor::Spawner;
 use embassy_futures::select::{Either3, select3};
+use embassy_time::Instant;
 use moonblokz_radio_lib::{
     IncomingMessageItem, MAX_NODE_COUNT, MessageType, RADIO_MAX_PACKET_COUNT,
     RadioCommunicationManager,
@@ -15,37 +16,79 @@ use moonblokz_radio_lib::{
         RadioInputQueue, RadioInputQueueSender, RadioOutputQueue, RadioOutputQueueReceiver,
     },
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use super::log_capture::capture_node_log;
+use super::queue_pool::QueuePool;
 use super::types::{
-    NodeInputMessage, NodeInputQueueReceiver, NodeOutputMessage, NodeOutputPayload,
-    NodesOutputQueueSender, RadioModuleConfig,
+    LogLevel, NeighborScore, NodeInputMessage, NodeInputQueue, NodeOutputMessage,
+    NodeOutputPayload, NodeProtocolState, NodesOutputQueueSender, RadioModuleConfig,
 };
+use crate::ui::MeasurementKind;
+
+/// Node input queues, returned by the node tasks that received `Shutdown`.
+pub static NODE_INPUT_QUEUES: QueuePool<NodeInputQueue> = QueuePool::new();
+/// Radio device queues, returned once the radio library's tasks stopped.
+static RADIO_OUTPUT_QUEUES: QueuePool<RadioOutputQueue> = QueuePool::new();
+static RADIO_INPUT_QUEUES: QueuePool<RadioInputQueue> = QueuePool::new();
+
+/// Node tasks (in-process or node process) spawned and not finished yet.
+static RUNNING_NODE_TASKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a node task as running from its spawn until the task drops it.
+///
+/// Created before spawning and moved into the task, so a task that was spawned but
+/// not polled yet is counted too (and a failed spawn drops it right away).
+pub struct RunningNodeTask(());
+
+impl RunningNodeTask {
+    /// Count a node task about to be spawned.
+    pub fn spawned() -> Self {
+        RUNNING_NODE_TASKS.fetch_add(1, Ordering::SeqCst);
+        Self(())
+    }
+}
+
+impl Drop for RunningNodeTask {
+    fn drop(&mut self) {
+        RUNNING_NODE_TASKS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Number of node tasks still running (the session shutdown waits for zero).
+pub fn running_node_tasks() -> usize {
+    RUNNING_NODE_TASKS.load(Ordering::SeqCst)
+}
 
 /// Context for managing node state and communication channels.
 struct NodeContext {
     node_id: u32,
     manager: RadioCommunicationManager,
     arrived_messages: HashMap<u32, moonblokz_radio_lib::RadioMessage>,
+    /// Anchor sequences of the AddTransactions seen (sent or received) by this node.
+    arrived_transactions: HashSet<u32>,
     out_tx: NodesOutputQueueSender,
     radio_input_queue_sender: RadioInputQueueSender,
+    radio_output_queue: &'static RadioOutputQueue,
+    radio_input_queue: &'static RadioInputQueue,
 }
 
 impl NodeContext {
-    /// Creates radio queues and initializes the radio device and manager.
+    /// Takes radio queues from the pools and initializes the radio device and manager.
+    ///
+    /// # Returns
+    ///
+    /// The context and the receiver of the radio device's outputs, or the radio
+    /// library's error if the manager could not start its tasks.
     fn initialize(
         spawner: Spawner,
         radio_module_config: RadioModuleConfig,
         node_id: u32,
         out_tx: NodesOutputQueueSender,
-    ) -> (Self, RadioOutputQueueReceiver) {
-        // INTENTIONAL LEAK: Box::leak provides 'static lifetimes for Embassy radio device channels.
-        // This allows the embedded moonblokz-radio-lib code to run unmodified in the simulator.
-        // These queues live for the entire simulation lifetime and are cleaned up on process exit.
-        let radio_output_queue: &'static mut RadioOutputQueue =
-            Box::leak(Box::new(RadioOutputQueue::new()));
-        let radio_input_queue: &'static mut RadioInputQueue =
-            Box::leak(Box::new(RadioInputQueue::new()));
+    ) -> Result<(Self, RadioOutputQueueReceiver), String> {
+        let radio_output_queue = RADIO_OUTPUT_QUEUES.take();
+        let radio_input_queue = RADIO_INPUT_QUEUES.take();
 
         let radio_output_queue_receiver = radio_output_queue.receiver();
         let radio_input_queue_sender = radio_input_queue.sender();
@@ -72,17 +115,31 @@ impl NodeContext {
             tx_maximum_random_delay: radio_module_config.tx_maximum_random_delay,
         };
 
-        let _ = manager.initialize(radio_config, spawner, radio_device, node_id, node_id as u64);
+        // On failure the library may have started some of its tasks with the radio
+        // device, so its queues are not returned to the pools
+        manager
+            .initialize(radio_config, spawner, radio_device, node_id, node_id as u64)
+            .map_err(|err| format!("{:?}", err))?;
 
         let context = Self {
             node_id,
             manager,
             arrived_messages: HashMap::new(),
+            arrived_transactions: HashSet::new(),
             out_tx,
             radio_input_queue_sender,
+            radio_output_queue,
+            radio_input_queue,
         };
 
-        (context, radio_output_queue_receiver)
+        Ok((context, radio_output_queue_receiver))
+    }
+
+    /// Stops the radio library's tasks and returns the radio queues to the pools.
+    async fn shut_down(self) {
+        self.manager.shutdown().await;
+        RADIO_OUTPUT_QUEUES.give_back(self.radio_output_queue);
+        RADIO_INPUT_QUEUES.give_back(self.radio_input_queue);
     }
 
     /// Extracts the sequence number from an AddBlock message payload.
@@ -105,7 +162,10 @@ impl NodeContext {
             .out_tx
             .send(NodeOutputMessage {
                 node_id: self.node_id,
-                payload: NodeOutputPayload::NodeReachedInMeasurement(sequence),
+                payload: NodeOutputPayload::NodeReachedInMeasurement {
+                    measurement_id: sequence,
+                    kind: MeasurementKind::Block,
+                },
             })
             .await;
 
@@ -130,6 +190,41 @@ impl NodeContext {
         true
     }
 
+    /// Handles a received AddTransaction: reports its first arrival for mempool
+    /// propagation measurements. Duplicates are still forwarded to the network task.
+    async fn handle_add_transaction_message(&mut self, msg: &moonblokz_radio_lib::RadioMessage) {
+        let Some(sequence) = msg.sequence() else {
+            return;
+        };
+        if !self.arrived_transactions.insert(sequence) {
+            return;
+        }
+
+        let _ = self
+            .out_tx
+            .send(NodeOutputMessage {
+                node_id: self.node_id,
+                payload: NodeOutputPayload::NodeReachedInMeasurement {
+                    measurement_id: sequence,
+                    kind: MeasurementKind::Transaction,
+                },
+            })
+            .await;
+
+        let _ = self
+            .out_tx
+            .send(NodeOutputMessage {
+                node_id: self.node_id,
+                payload: NodeOutputPayload::FullMessageReceived {
+                    message_type: MessageType::AddTransaction as u8,
+                    sender_node: msg.sender_node_id(),
+                    sequence,
+                    length: msg.payload().len(),
+                },
+            })
+            .await;
+    }
+
     /// Handles a RequestBlockPart message: finds the block and responds with requested parts.
     fn handle_request_block_part(&mut self, msg: &moonblokz_radio_lib::RadioMessage) {
         let Some(sequence) = msg.sequence() else {
@@ -170,6 +265,10 @@ impl NodeContext {
             }
         }
 
+        if message_type == MessageType::AddTransaction as u8 {
+            self.handle_add_transaction_message(&msg).await;
+        }
+
         if message_type == MessageType::RequestBlockPart as u8 {
             self.handle_request_block_part(&msg);
         }
@@ -233,6 +332,22 @@ impl NodeContext {
                             },
                         })
                         .await;
+                } else if msg.message_type() == MessageType::AddTransaction as u8 {
+                    if let Some(sequence) = msg.sequence() {
+                        self.arrived_transactions.insert(sequence);
+                        let _ = self
+                            .out_tx
+                            .send(NodeOutputMessage {
+                                node_id: self.node_id,
+                                payload: NodeOutputPayload::FullMessageSent {
+                                    message_type: MessageType::AddTransaction as u8,
+                                    sender_node: self.node_id,
+                                    sequence,
+                                    length: msg.payload().len(),
+                                },
+                            })
+                            .await;
+                    }
                 }
                 let _ = self.manager.send_message(msg);
             }
@@ -253,6 +368,30 @@ impl NodeContext {
                     moonblokz_radio_lib::MessageProcessingResult::RequestConnectionMatrixIntoLog,
                 );
             }
+            NodeInputMessage::ImportConnectionMatrix(matrix) => {
+                if let Err(err) = self
+                    .manager
+                    .import_connection_matrix(&matrix.node_ids, &matrix.values)
+                {
+                    log::warn!(
+                        "Node {} rejected the imported connection matrix: {:?}",
+                        self.node_id,
+                        err
+                    );
+                }
+            }
+            NodeInputMessage::QueryState => {
+                let _ = self
+                    .out_tx
+                    .send(NodeOutputMessage {
+                        node_id: self.node_id,
+                        payload: NodeOutputPayload::ProtocolState(protocol_state(&self.manager)),
+                    })
+                    .await;
+            }
+            NodeInputMessage::Shutdown => {
+                // Handled by `node_task`, which stops the radio library's tasks and returns
+            }
         }
     }
 
@@ -286,6 +425,35 @@ impl NodeContext {
     }
 }
 
+/// Snapshot of the radio manager's internals through the radio-lib introspection API.
+///
+/// # Parameters
+///
+/// * `manager` - The node's radio communication manager
+///
+/// # Returns
+///
+/// Neighbor scores (best first), TX queue length and the time left on the echo timers.
+fn protocol_state(manager: &RadioCommunicationManager) -> NodeProtocolState {
+    let snapshot = manager.query_state();
+    let now = Instant::now();
+    let remaining_ms = |deadline: Option<Instant>| {
+        deadline.map(|deadline| deadline.saturating_duration_since(now).as_millis())
+    };
+    let mut neighbors: Vec<NeighborScore> = snapshot
+        .neighbor_scores
+        .iter()
+        .map(|&(node_id, score)| NeighborScore { node_id, score })
+        .collect();
+    neighbors.sort_by(|a, b| b.score.cmp(&a.score).then(a.node_id.cmp(&b.node_id)));
+    NodeProtocolState {
+        neighbors,
+        tx_queue_len: snapshot.tx_queue_len,
+        next_echo_request_ms: remaining_ms(snapshot.next_echo_request),
+        echo_gathering_end_ms: remaining_ms(snapshot.echo_gathering_end),
+    }
+}
+
 /// Per-node asynchronous task bridging the simulated radio device, the radio
 /// manager from `moonblokz_radio_lib`, and the network task.
 ///
@@ -293,16 +461,31 @@ impl NodeContext {
 /// - Initialize the per-node radio manager and device queues.
 /// - Forward outgoing radio events to the network task via `out_tx`.
 /// - Accept incoming control messages (packets to deliver, sends, CAD results).
+/// - On `Shutdown`, stop the radio library's tasks and return the queues for reuse.
+///
+/// If the radio manager cannot be initialized, the error is logged and the node
+/// stays silent.
 #[embassy_executor::task(pool_size = MAX_NODE_COUNT)]
 pub async fn node_task(
     spawner: Spawner,
     radio_module_config: RadioModuleConfig,
     node_id: u32,
     out_tx: NodesOutputQueueSender,
-    in_rx: NodeInputQueueReceiver,
+    in_queue: &'static NodeInputQueue,
+    _running: RunningNodeTask,
 ) {
     let (mut context, radio_output_queue_receiver) =
-        NodeContext::initialize(spawner, radio_module_config, node_id, out_tx);
+        match NodeContext::initialize(spawner, radio_module_config, node_id, out_tx) {
+            Ok(initialized) => initialized,
+            Err(err) => {
+                let message = format!("radio manager initialization failed: {}", err);
+                log::error!("Node {}: {}", node_id, message);
+                capture_node_log(node_id, LogLevel::Error, message);
+                // The network task keeps sending to the input queue, so it is not reused
+                return;
+            }
+        };
+    let in_rx = in_queue.receiver();
 
     loop {
         match select3(
@@ -315,6 +498,12 @@ pub async fn node_task(
             Either3::First(Ok(item)) => {
                 context.handle_incoming_message_item(item).await;
             }
+            Either3::Second(NodeInputMessage::Shutdown) => {
+                // Free the task slot and the queues for the nodes of the next session
+                context.shut_down().await;
+                NODE_INPUT_QUEUES.give_back(in_queue);
+                return;
+            }
             Either3::Second(cmd) => {
                 context.handle_input_command(cmd).await;
             }
@@ -0,0 +1,488 @@
+//! Origin sampling experiment: the distribution measurement from many origins.
+//!
+//! The distribution time of a single measurement depends heavily on where it starts:
+//! a block originated at the edge of the network needs more hops than one from a
+//! well-connected node in the middle. The experiment repeats the AddBlock measurement
+//! from `origins_per_strategy` origins of every configured strategy (origin class):
+//!
+//! - `random`: uniformly random nodes (simulation RNG, so reproducible with a seed)
+//! - `periphery`: the nodes farthest from the centroid of all nodes (in meters)
+//! - `max_degree`: the nodes with the most neighbors in radio range
+//!
+//! The runs alternate between the strategies, so slow changes of the network (e.g.
+//! link quality learning) do not bias one class. One measurement runs at a time, every
+//! `interval` virtual seconds; a run that did not reach every node before the next
+//! start is counted as incomplete. The report gives the mean, variance and range of
+//! the distribution times per class and the share of the total variance explained by
+//! the class (between-class variance).
+//!
+//! The runner only keeps the bookkeeping; `network_task` starts the measurements from
+//! its tick branch and feeds back the reached node counts.
+
+use embassy_time::{Duration, Instant};
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use super::types::{OriginSamplingConfig, OriginStrategy};
+use crate::common::units::WorldTransform;
+
+/// A node that can originate a measurement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OriginCandidate {
+    pub node_id: u32,
+    /// World position (world units).
+    pub position: (f64, f64),
+    /// Nodes within radio range with a clear line of sight.
+    pub degree: usize,
+}
+
+/// Choose up to `count` distinct origins by `strategy`.
+///
+/// # Parameters
+///
+/// * `strategy` - Origin class
+/// * `count` - Number of origins (fewer if there are fewer candidates)
+/// * `candidates` - All nodes that can originate a measurement
+/// * `transform` - World transform (periphery distances are compared in meters)
+/// * `rng` - Random generator (random strategy)
+///
+/// # Returns
+///
+/// The origin node IDs; ties are broken by the lower node ID.
+pub fn select_origins(
+    strategy: OriginStrategy,
+    count: usize,
+    candidates: &[OriginCandidate],
+    transform: &WorldTransform,
+    rng: &mut impl Rng,
+) -> Vec<u32> {
+    let mut ranked: Vec<&OriginCandidate> = candidates.iter().collect();
+    ranked.sort_by_key(|candidate| candidate.node_id);
+    match strategy {
+        OriginStrategy::Random => {
+            ranked.shuffle(rng);
+        }
+        OriginStrategy::Periphery => {
+            let n = ranked.len().max(1) as f64;
+            let centroid = (
+                ranked.iter().map(|c| c.position.0).sum::<f64>() / n,
+                ranked.iter().map(|c| c.position.1).sum::<f64>() / n,
+            );
+            ranked.sort_by(|a, b| {
+                let distance_a = transform.distance2(a.position, centroid);
+                let distance_b = transform.distance2(b.position, centroid);
+                distance_b.total_cmp(&distance_a)
+            });
+        }
+        OriginStrategy::MaxDegree => {
+            ranked.sort_by(|a, b| b.degree.cmp(&a.degree));
+        }
+    }
+    ranked
+        .into_iter()
+        .take(count)
+        .map(|candidate| candidate.node_id)
+        .collect()
+}
+
+/// One measurement of the experiment.
+#[derive(Debug, Clone)]
+struct OriginRun {
+    strategy: OriginStrategy,
+    origin: u32,
+    measurement_id: Option<u32>,
+    start: Option<Instant>,
+    /// Nodes reached so far (including the origin).
+    reached: usize,
+    /// Time until every node was reached (None = incomplete).
+    distribution_time: Option<Duration>,
+}
+
+/// Distribution times of one origin class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategySummary {
+    pub strategy: OriginStrategy,
+    /// Measurements run.
+    pub runs: usize,
+    /// Measurements that reached every node.
+    pub completed: usize,
+    /// Mean distribution time of the completed runs (seconds).
+    pub mean_secs: Option<f64>,
+    /// Sample variance of the distribution times (seconds², 0 for a single run).
+    pub variance: Option<f64>,
+    pub min_secs: Option<f64>,
+    pub max_secs: Option<f64>,
+    /// Mean share of nodes reached, incomplete runs included (percent).
+    pub mean_reach_percent: f64,
+}
+
+/// Result of the experiment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OriginSamplingReport {
+    pub strategies: Vec<StrategySummary>,
+    /// Sample variance of all completed distribution times (seconds²).
+    pub total_variance: Option<f64>,
+    /// Share of the total variance explained by the origin class (0-1).
+    pub between_class_share: Option<f64>,
+}
+
+impl OriginSamplingReport {
+    /// Report lines for the log and the alert.
+    pub fn to_lines(&self) -> Vec<String> {
+        let secs = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.1}s", v));
+        let mut lines: Vec<String> = self
+            .strategies
+            .iter()
+            .map(|summary| {
+                format!(
+                    "{}: {}/{} complete, mean {}, std dev {}, range {} - {}, mean reach {:.1}%",
+                    summary.strategy.label(),
+                    summary.completed,
+                    summary.runs,
+                    secs(summary.mean_secs),
+                    secs(summary.variance.map(f64::sqrt)),
+                    secs(summary.min_secs),
+                    secs(summary.max_secs),
+                    summary.mean_reach_percent
+                )
+            })
+            .collect();
+        lines.push(format!(
+            "All origins: std dev {}, variance explained by origin class {}",
+            secs(self.total_variance.map(f64::sqrt)),
+            self.between_class_share
+                .map_or("-".to_string(), |share| format!("{:.0}%", share * 100.0))
+        ));
+        lines
+    }
+}
+
+/// Mean and sample variance of a non-empty slice.
+fn mean_and_variance(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = if values.len() > 1 {
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    } else {
+        0.0
+    };
+    Some((mean, variance))
+}
+
+/// Bookkeeping of a running origin sampling experiment.
+pub struct OriginSamplingRunner {
+    runs: Vec<OriginRun>,
+    /// Start of the first run.
+    start: Instant,
+    interval: Duration,
+    /// Runs started so far.
+    started: usize,
+    node_count: usize,
+    /// Whether the report was taken.
+    reported: bool,
+}
+
+impl OriginSamplingRunner {
+    /// Create a runner whose first measurement is due at `start`.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - Experiment configuration from the scene
+    /// * `candidates` - All nodes that can originate a measurement
+    /// * `transform` - World transform of the scene
+    /// * `rng` - Random generator (random strategy)
+    /// * `node_count` - Nodes a measurement must reach to complete
+    /// * `start` - Time of the first measurement
+    pub fn new(
+        config: &OriginSamplingConfig,
+        candidates: &[OriginCandidate],
+        transform: &WorldTransform,
+        rng: &mut impl Rng,
+        node_count: usize,
+        start: Instant,
+    ) -> Self {
+        let origins: Vec<(OriginStrategy, Vec<u32>)> = config
+            .strategies
+            .iter()
+            .map(|strategy| {
+                (
+                    *strategy,
+                    select_origins(
+                        *strategy,
+                        config.origins_per_strategy as usize,
+                        candidates,
+                        transform,
+                        rng,
+                    ),
+                )
+            })
+            .collect();
+        // Alternate between the strategies: first origin of each, then the second, ...
+        let mut runs = Vec::new();
+        for index in 0..config.origins_per_strategy as usize {
+            for (strategy, strategy_origins) in &origins {
+                if let Some(origin) = strategy_origins.get(index) {
+                    runs.push(OriginRun {
+                        strategy: *strategy,
+                        origin: *origin,
+                        measurement_id: None,
+                        start: None,
+                        reached: 0,
+                        distribution_time: None,
+                    });
+                }
+            }
+        }
+        Self {
+            runs,
+            start,
+            interval: Duration::from_secs(config.interval),
+            started: 0,
+            node_count,
+            reported: false,
+        }
+    }
+
+    /// Measurements of the experiment.
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Strategy and origin of the next measurement if it is due at `now`.
+    pub fn due_run(&self, now: Instant) -> Option<(OriginStrategy, u32)> {
+        let run = self.runs.get(self.started)?;
+        let due = self.start + self.interval * self.started as u32;
+        (now >= due).then_some((run.strategy, run.origin))
+    }
+
+    /// Record that the due measurement was started as `measurement_id` at `now`.
+    pub fn record_start(&mut self, measurement_id: u32, now: Instant) {
+        if let Some(run) = self.runs.get_mut(self.started) {
+            run.measurement_id = Some(measurement_id);
+            run.start = Some(now);
+            run.reached = 1;
+            self.started += 1;
+        }
+    }
+
+    /// Record the reached node count of a measurement at `now`.
+    ///
+    /// Measurements of other sources and runs past their interval are ignored.
+    pub fn record_reach(&mut self, measurement_id: u32, reached: usize, now: Instant) {
+        let interval = self.interval;
+        let node_count = self.node_count;
+        let Some(run) = self
+            .runs
+            .iter_mut()
+            .find(|run| run.measurement_id == Some(measurement_id))
+        else {
+            return;
+        };
+        let Some(start) = run.start else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(start);
+        if elapsed > interval || run.distribution_time.is_some() {
+            return;
+        }
+        run.reached = run.reached.max(reached);
+        if run.reached >= node_count {
+            run.distribution_time = Some(elapsed);
+        }
+    }
+
+    /// Whether every measurement was started and the last one finished or timed out.
+    pub fn is_finished(&self, now: Instant) -> bool {
+        if self.started < self.runs.len() {
+            return false;
+        }
+        self.runs.last().is_none_or(|run| {
+            run.distribution_time.is_some()
+                || run
+                    .start
+                    .is_some_and(|start| now.saturating_duration_since(start) >= self.interval)
+        })
+    }
+
+    /// The report, once, when the experiment is finished at `now`.
+    pub fn take_report(&mut self, now: Instant) -> Option<OriginSamplingReport> {
+        if self.reported || !self.is_finished(now) {
+            return None;
+        }
+        self.reported = true;
+        Some(self.report())
+    }
+
+    /// Aggregate the distribution times per origin class.
+    pub fn report(&self) -> OriginSamplingReport {
+        let mut strategies: Vec<OriginStrategy> = Vec::new();
+        for run in &self.runs {
+            if !strategies.contains(&run.strategy) {
+                strategies.push(run.strategy);
+            }
+        }
+        let times_of = |strategy: OriginStrategy| -> Vec<f64> {
+            self.runs
+                .iter()
+                .filter(|run| run.strategy == strategy)
+                .filter_map(|run| run.distribution_time)
+                .map(|time| time.as_millis() as f64 / 1000.0)
+                .collect()
+        };
+
+        let summaries: Vec<StrategySummary> = strategies
+            .iter()
+            .map(|&strategy| {
+                let runs: Vec<&OriginRun> = self
+                    .runs
+                    .iter()
+                    .filter(|run| run.strategy == strategy)
+                    .collect();
+                let times = times_of(strategy);
+                let statistics = mean_and_variance(&times);
+                StrategySummary {
+                    strategy,
+                    runs: runs.len(),
+                    completed: times.len(),
+                    mean_secs: statistics.map(|(mean, _)| mean),
+                    variance: statistics.map(|(_, variance)| variance),
+                    min_secs: times.iter().copied().reduce(f64::min),
+                    max_secs: times.iter().copied().reduce(f64::max),
+                    mean_reach_percent: runs
+                        .iter()
+                        .map(|run| run.reached as f64 * 100.0 / self.node_count.max(1) as f64)
+                        .sum::<f64>()
+                        / runs.len().max(1) as f64,
+                }
+            })
+            .collect();
+
+        let all_times: Vec<f64> = strategies.iter().flat_map(|&s| times_of(s)).collect();
+        let total = mean_and_variance(&all_times);
+        // Sum of squares between the class means over the total sum of squares
+        let between_class_share = total.and_then(|(mean, variance)| {
+            let total_squares = variance * (all_times.len() as f64 - 1.0);
+            let between: f64 = summaries
+                .iter()
+                .filter_map(|s| s.mean_secs.map(|m| s.completed as f64 * (m - mean).powi(2)))
+                .sum();
+            (total_squares > 0.0).then(|| between / total_squares)
+        });
+
+        OriginSamplingReport {
+            strategies: summaries,
+            total_variance: total.map(|(_, variance)| variance),
+            between_class_share,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn candidate(node_id: u32, x: f64, y: f64, degree: usize) -> OriginCandidate {
+        OriginCandidate {
+            node_id,
+            position: (x, y),
+            degree,
+        }
+    }
+
+    #[test]
+    fn test_strategies_runs_and_report() {
+        let transform = WorldTransform::default();
+        let candidates = vec![
+            candidate(1, 50.0, 50.0, 4),
+            candidate(2, 0.0, 0.0, 1),
+            candidate(3, 100.0, 100.0, 1),
+            candidate(4, 60.0, 40.0, 3),
+            candidate(5, 45.0, 55.0, 4),
+        ];
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(
+            select_origins(
+                OriginStrategy::Periphery,
+                2,
+                &candidates,
+                &transform,
+                &mut rng
+            ),
+            vec![2, 3]
+        );
+        assert_eq!(
+            select_origins(
+                OriginStrategy::MaxDegree,
+                3,
+                &candidates,
+                &transform,
+                &mut rng
+            ),
+            vec![1, 5, 4]
+        );
+        let mut random =
+            select_origins(OriginStrategy::Random, 9, &candidates, &transform, &mut rng);
+        random.sort();
+        assert_eq!(random, vec![1, 2, 3, 4, 5]);
+
+        let config = OriginSamplingConfig {
+            strategies: vec![OriginStrategy::Periphery, OriginStrategy::MaxDegree],
+            origins_per_strategy: 2,
+            start_time: 0,
+            interval: 100,
+        };
+        let start = Instant::from_secs(10);
+        let mut runner =
+            OriginSamplingRunner::new(&config, &candidates, &transform, &mut rng, 5, start);
+        assert_eq!(runner.run_count(), 4);
+        assert_eq!(runner.due_run(start), Some((OriginStrategy::Periphery, 2)));
+        assert!(!runner.is_finished(start));
+
+        // Periphery: 60 s and incomplete; max degree: 20 s and 30 s
+        let mut at = start;
+        for (id, (strategy, time)) in [
+            (OriginStrategy::Periphery, Some(60)),
+            (OriginStrategy::MaxDegree, Some(20)),
+            (OriginStrategy::Periphery, None),
+            (OriginStrategy::MaxDegree, Some(30)),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            assert_eq!(runner.due_run(at).map(|(s, _)| s), Some(strategy));
+            assert!(runner.due_run(at - Duration::from_secs(1)).is_none());
+            runner.record_start(id as u32, at);
+            runner.record_reach(id as u32, 3, at + Duration::from_secs(5));
+            if let Some(time) = time {
+                runner.record_reach(id as u32, 5, at + Duration::from_secs(time));
+            }
+            // Reaches after the interval do not count
+            runner.record_reach(id as u32, 5, at + Duration::from_secs(150));
+            at += Duration::from_secs(100);
+        }
+        assert!(runner.due_run(at).is_none());
+        assert!(runner.is_finished(at));
+        assert!(runner.take_report(at).is_some());
+        assert!(runner.take_report(at).is_none());
+
+        let report = runner.report();
+        let periphery = &report.strategies[0];
+        assert_eq!((periphery.runs, periphery.completed), (2, 1));
+        assert_eq!(periphery.mean_secs, Some(60.0));
+        assert_eq!(periphery.variance, Some(0.0));
+        assert_eq!(periphery.mean_reach_percent, 80.0);
+        let max_degree = &report.strategies[1];
+        assert_eq!(max_degree.mean_secs, Some(25.0));
+        assert_eq!(max_degree.variance, Some(50.0));
+        assert_eq!(max_degree.max_secs, Some(30.0));
+        // Times 60, 20, 30: mean 36.67, SS total 866.67, SS between 816.67
+        let share = report.between_class_share.unwrap();
+        assert!((share - 816.667 / 866.667).abs() < 1e-3);
+        assert_eq!(report.to_lines().len(), 3);
+    }
+}
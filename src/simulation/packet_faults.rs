@@ -0,0 +1,134 @@
+//! # Packet Faults
+//!
+//! Radio device faults injected into otherwise successful receptions, to exercise
+//! the radio library's length and CRC validation. With the scene's `packet_faults`
+//! probabilities a delivered packet is either truncated (delivered with a shorter
+//! length) or corrupted (random bits of its payload flipped). Faulty packets are
+//! still delivered to the node; they are counted separately in the node statistics.
+
+use moonblokz_radio_lib::RadioPacket;
+use rand::Rng;
+use rand::seq::index::sample;
+
+use super::rng::with_simulation_rng;
+use super::types::PacketFaultConfig;
+
+/// Fault injected into a delivered packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketFault {
+    /// The packet was cut to `length` bytes.
+    Truncated { length: usize },
+    /// `bit_errors` bits of the payload were flipped.
+    Corrupted { bit_errors: u32 },
+}
+
+impl PacketFault {
+    /// Short description for the event log and the packet details.
+    pub fn describe(&self) -> String {
+        match self {
+            PacketFault::Truncated { length } => format!("truncated to {} B", length),
+            PacketFault::Corrupted { bit_errors } => format!("{} bit errors", bit_errors),
+        }
+    }
+}
+
+/// Inject a fault into a packet about to be delivered, drawing from the
+/// simulation generator.
+///
+/// # Parameters
+///
+/// * `config` - Fault probabilities of the scene
+/// * `packet` - Packet to modify in place
+///
+/// # Returns
+///
+/// The injected fault, or `None` if the packet is delivered intact.
+pub fn inject_fault(config: &PacketFaultConfig, packet: &mut RadioPacket) -> Option<PacketFault> {
+    if !config.is_enabled() {
+        return None;
+    }
+    with_simulation_rng(|rng| inject_fault_with(config, packet, rng))
+}
+
+fn inject_fault_with(
+    config: &PacketFaultConfig,
+    packet: &mut RadioPacket,
+    rng: &mut impl Rng,
+) -> Option<PacketFault> {
+    let length = packet.length.min(packet.data.len());
+    if length == 0 {
+        return None;
+    }
+    let draw = rng.gen_range(0.0..1.0);
+    if draw < config.truncation_probability && length > 1 {
+        packet.length = rng.gen_range(1..length);
+        return Some(PacketFault::Truncated {
+            length: packet.length,
+        });
+    }
+    if draw < config.truncation_probability + config.corruption_probability {
+        let bit_count = length * 8;
+        let bit_errors = rng
+            .gen_range(1..=config.max_bit_errors.max(1))
+            .min(bit_count as u32);
+        for bit in sample(rng, bit_count, bit_errors as usize) {
+            packet.data[bit / 8] ^= 1 << (bit % 8);
+        }
+        return Some(PacketFault::Corrupted { bit_errors });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn packet(length: usize) -> RadioPacket {
+        RadioPacket {
+            data: std::array::from_fn(|i| i as u8),
+            length,
+        }
+    }
+
+    #[test]
+    fn test_faults_truncate_or_flip_bits_within_the_payload() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let truncate = PacketFaultConfig {
+            truncation_probability: 1.0,
+            ..Default::default()
+        };
+        let mut truncated = packet(40);
+        match inject_fault_with(&truncate, &mut truncated, &mut rng) {
+            Some(PacketFault::Truncated { length }) => {
+                assert!((1..40).contains(&length));
+                assert_eq!(truncated.length, length);
+            }
+            other => panic!("expected a truncation, got {:?}", other),
+        }
+
+        let corrupt = PacketFaultConfig {
+            corruption_probability: 1.0,
+            max_bit_errors: 3,
+            ..Default::default()
+        };
+        let original = packet(20);
+        let mut corrupted = packet(20);
+        let Some(PacketFault::Corrupted { bit_errors }) =
+            inject_fault_with(&corrupt, &mut corrupted, &mut rng)
+        else {
+            panic!("expected a corruption");
+        };
+        assert!((1..=3).contains(&bit_errors));
+        assert_eq!(corrupted.length, 20);
+        let flipped: u32 = (0..20)
+            .map(|i| (corrupted.data[i] ^ original.data[i]).count_ones())
+            .sum();
+        assert_eq!(flipped, bit_errors);
+        assert_eq!(corrupted.data[20..], original.data[20..]);
+
+        let none = PacketFaultConfig::default();
+        assert_eq!(inject_fault_with(&none, &mut packet(20), &mut rng), None);
+    }
+}
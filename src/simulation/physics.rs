@@ -0,0 +1,444 @@
+//! # Parallel Physics Stage
+//!
+//! The radio physics is the hot path of large scenes: every transmission is checked
+//! against every node (range, line of sight through all obstacles, path loss), and
+//! every tick evaluates the overlapping frames, SINR and receiver state of the next
+//! pending packet at every node. Both computations only read the node map, so they
+//! run on the rayon thread pool instead of stalling the Embassy event loop. Scenes
+//! with fewer than `PARALLEL_MIN_NODES` nodes stay on the executor thread, where the
+//! work is cheaper than handing it to the pool.
+//!
+//! Everything with side effects stays on the executor thread: shadowing and packet
+//! error rate draws from the simulation RNG, queueing airtime packets, node input
+//! queues and UI updates. The parallel results are applied in node ID order, so a
+//! run with the same seed draws the same random numbers in the same order.
+
+use embassy_time::Instant;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::external_traffic::ExternalTraffic;
+use super::geometry::{distance_from_d2, distance2, is_intersect};
+use super::signal_calculations::{
+    calculate_receiving_limit_with_basic_noise, calculate_snr_limit, dbm_to_mw, get_preamble_time,
+    mw_to_dbm,
+};
+use super::types::{CAPTURE_THRESHOLD, Node, Point, ReceiverState, Scene};
+
+/// Node count from which the physics runs on the rayon thread pool.
+const PARALLEL_MIN_NODES: usize = 256;
+
+/// A receiver in range and line of sight of a transmission.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkCandidate {
+    pub target_id: u32,
+    /// Distance from the sender in meters.
+    pub distance: f64,
+    /// Received signal strength without shadowing (dBm).
+    pub mean_rssi: f32,
+}
+
+/// Find the receivers of a transmission (in parallel in large scenes).
+///
+/// Only nodes within the sender's effective distance AND with clear line-of-sight
+/// (no obstacle intersection) are included; nodes with a disabled radio are skipped.
+///
+/// # Parameters
+///
+/// * `sender_id` - ID of the transmitting node (excluded from targets)
+/// * `sender_position` - 2D position of the sender
+/// * `sender_effective_distance` - Pre-calculated maximum range
+/// * `sender_radio_strength` - Sender's TX power in dBm
+/// * `nodes_map` - Map of all nodes
+/// * `scene` - Scene configuration (obstacles, propagation model)
+///
+/// # Returns
+///
+/// The receivers ordered by node ID.
+pub fn find_link_candidates(
+    sender_id: u32,
+    sender_position: &Point,
+    sender_effective_distance: f32,
+    sender_radio_strength: f32,
+    nodes_map: &HashMap<u32, Node>,
+    scene: &Scene,
+) -> Vec<LinkCandidate> {
+    collect_link_candidates(
+        sender_id,
+        sender_position,
+        sender_effective_distance,
+        sender_radio_strength,
+        nodes_map,
+        scene,
+        nodes_map.len() >= PARALLEL_MIN_NODES,
+    )
+}
+
+/// `find_link_candidates` on the rayon thread pool (`parallel`) or on the calling thread.
+fn collect_link_candidates(
+    sender_id: u32,
+    sender_position: &Point,
+    sender_effective_distance: f32,
+    sender_radio_strength: f32,
+    nodes_map: &HashMap<u32, Node>,
+    scene: &Scene,
+    parallel: bool,
+) -> Vec<LinkCandidate> {
+    let eff2 = (sender_effective_distance as f64).powi(2);
+    let candidate = |(&other_id, other_node): (&u32, &Node)| -> Option<LinkCandidate> {
+        if other_id == sender_id || other_node.disabled {
+            return None;
+        }
+        let d2 = distance2(sender_position, &other_node.position, scene);
+        if d2 >= eff2 || is_intersect(sender_position, &other_node.position, &scene.obstacles) {
+            return None;
+        }
+        let distance = distance_from_d2(d2);
+        Some(LinkCandidate {
+            target_id: other_id,
+            distance,
+            mean_rssi: sender_radio_strength
+                - scene
+                    .propagation_model
+                    .model()
+                    .mean_path_loss(distance as f32, &scene.path_loss_parameters),
+        })
+    };
+    let mut candidates: Vec<LinkCandidate> = if parallel {
+        nodes_map.par_iter().filter_map(candidate).collect()
+    } else {
+        nodes_map.iter().filter_map(candidate).collect()
+    };
+    candidates.sort_unstable_by_key(|candidate| candidate.target_id);
+    candidates
+}
+
+/// Physics of one packet reception at one node, before the decoding decision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReceptionOutcome {
+    pub node_id: u32,
+    /// Index of the packet in the node's `airtime_waiting_packets`.
+    pub packet_index: usize,
+    /// Received signal strength in dBm.
+    pub rssi: f32,
+    /// Signal to interference plus noise ratio in dB.
+    pub sinr: f32,
+    pub link_quality: u8,
+    /// Another frame overlapped the packet.
+    pub collision: bool,
+    /// The overlap (or a busy receiver) destroys the packet regardless of SINR.
+    pub destructive_collision: bool,
+    /// The node transmitted during the packet (half-duplex loss).
+    pub missed_while_transmitting: bool,
+    /// The node's receiver slept during the packet (duty cycling).
+    pub missed_while_sleeping: bool,
+}
+
+/// Clean up outdated airtime packets and find the next packet to process.
+fn find_next_packet_to_process(
+    node: &mut Node,
+    now: Instant,
+) -> Option<(usize, Instant, Instant, f32)> {
+    // Find earliest start time of unprocessed packets
+    let earliest_start_time = node
+        .airtime_waiting_packets
+        .iter()
+        .filter(|p| !p.processed)
+        .map(|p| p.start_time)
+        .min()
+        .unwrap_or(now);
+
+    // Clean up processed packets that are no longer relevant
+    node.airtime_waiting_packets.retain(|packet| {
+        !packet.processed || packet.start_time + packet.airtime >= earliest_start_time
+    });
+
+    // Find the earliest arriving unprocessed packet (arrival delays can reorder the
+    // packets against the order they were queued in)
+    node.airtime_waiting_packets
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| !p.processed)
+        .min_by_key(|(_, p)| p.start_time)
+        .map(|(i, p)| (i, p.start_time, p.start_time + p.airtime, p.rssi))
+}
+
+/// Evaluate collisions, SINR and the receiver state of a packet at a node.
+///
+/// ## Collision Detection
+///
+/// - **Receiver state**: Packets overlapping the receiver's own transmission, or whose
+///   preamble completes while the receiver is locked onto another frame, are missed;
+///   so are packets overlapping a sleep period of a duty-cycled receiver
+/// - **Preamble lock loss**: Earlier packet above SNR destroys later packet
+/// - **Capture effect**: Later stronger packet (>6dB) captures the receiver
+/// - **Interference**: Overlapping signals add to noise floor
+///
+/// Uplinks of an external LoRaWAN network count as overlapping frames as well.
+///
+/// # Parameters
+///
+/// * `node` - Receiving node
+/// * `packet_index` - Index of packet in the airtime waiting list
+/// * `packet_start` - When packet transmission started
+/// * `packet_end` - When packet transmission ends
+/// * `packet_rssi` - Received signal strength in dBm
+/// * `scene` - Scene configuration (LoRa and path loss parameters)
+/// * `external` - Co-channel external traffic, if the scene has any
+fn evaluate_reception(
+    node: &Node,
+    packet_index: usize,
+    packet_start: Instant,
+    packet_end: Instant,
+    packet_rssi: f32,
+    scene: &Scene,
+    external: Option<&ExternalTraffic>,
+) -> ReceptionOutcome {
+    let snr_limit = calculate_snr_limit(&scene.lora_parameters);
+    let mut sum_noise = dbm_to_mw(scene.path_loss_parameters.noise_floor);
+    let mut collision = false;
+    let mut destructive_collision = false;
+
+    // Other frames heard by the node: its other airtime packets and external uplinks
+    let mesh_frames = node
+        .airtime_waiting_packets
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != packet_index)
+        .map(|(_, other)| {
+            (
+                other.start_time,
+                other.start_time + other.airtime,
+                other.rssi,
+            )
+        });
+    let external_frames = external
+        .into_iter()
+        .flat_map(|traffic| traffic.frames_at(&node.position, packet_start, packet_end, scene));
+
+    // Check for overlapping packets and collisions
+    for (other_start, other_end, other_rssi) in mesh_frames.chain(external_frames) {
+        // Check if packets overlap in time
+        if other_start < packet_end && other_end > packet_start {
+            // Preamble/header lock lost if earlier packet is above SNR limit
+            if other_start < packet_start && other_rssi > snr_limit {
+                destructive_collision = true;
+            }
+
+            // Capture effect: later stronger packet captures the receiver
+            if other_start >= packet_start && packet_rssi - other_rssi > CAPTURE_THRESHOLD {
+                destructive_collision = true;
+            }
+
+            sum_noise += dbm_to_mw(other_rssi);
+            collision = true;
+        }
+    }
+
+    // Receiver state: the preamble must be heard by a listening receiver. A node that
+    // transmits during the frame (half-duplex) misses it; this is accounted separately
+    // from collisions. A node already locked onto another frame when the preamble
+    // completes misses it too, unless the new frame is strong enough to capture the
+    // receiver.
+    let preamble_time = get_preamble_time(&scene.lora_parameters);
+    let sensitivity = calculate_receiving_limit_with_basic_noise(
+        &scene.lora_parameters,
+        &scene.path_loss_parameters,
+    );
+    let missed_while_transmitting = node.is_transmitting_during(packet_start, packet_end);
+    let missed_while_sleeping = !missed_while_transmitting
+        && node
+            .sleep_schedule
+            .is_some_and(|schedule| schedule.sleeps_during(packet_start, packet_end));
+    let receiver_busy = if missed_while_transmitting || missed_while_sleeping {
+        false
+    } else {
+        match node.receiver_state_at(
+            packet_start + preamble_time,
+            packet_index,
+            preamble_time,
+            sensitivity,
+        ) {
+            ReceiverState::Idle => false,
+            ReceiverState::Transmitting => true,
+            ReceiverState::Receiving(locked_index) => {
+                packet_rssi - node.airtime_waiting_packets[locked_index].rssi <= CAPTURE_THRESHOLD
+            }
+        }
+    };
+    if receiver_busy {
+        destructive_collision = true;
+        collision = true;
+    }
+
+    let sinr = packet_rssi - mw_to_dbm(sum_noise);
+    ReceptionOutcome {
+        node_id: node.node_id,
+        packet_index,
+        rssi: packet_rssi,
+        sinr,
+        link_quality: moonblokz_radio_lib::calculate_link_quality(packet_rssi as i16, sinr as i16),
+        collision,
+        destructive_collision,
+        missed_while_transmitting,
+        missed_while_sleeping,
+    }
+}
+
+/// Take the next pending packet of every node and evaluate its reception (in parallel
+/// in large scenes).
+///
+/// The evaluated packets are marked as processed; `external` adds the uplinks of a
+/// co-channel LoRaWAN network to the interference.
+///
+/// # Returns
+///
+/// One outcome per node with a pending packet, ordered by node ID.
+pub fn evaluate_pending_receptions(
+    nodes_map: &mut HashMap<u32, Node>,
+    scene: &Scene,
+    external: Option<&ExternalTraffic>,
+) -> Vec<ReceptionOutcome> {
+    let parallel = nodes_map.len() >= PARALLEL_MIN_NODES;
+    collect_pending_receptions(nodes_map, scene, external, parallel)
+}
+
+/// `evaluate_pending_receptions` on the rayon thread pool (`parallel`) or on the
+/// calling thread.
+fn collect_pending_receptions(
+    nodes_map: &mut HashMap<u32, Node>,
+    scene: &Scene,
+    external: Option<&ExternalTraffic>,
+    parallel: bool,
+) -> Vec<ReceptionOutcome> {
+    // Read the clock once: the time driver is shared by all threads
+    let now = Instant::now();
+    let outcome = |(_, node): (&u32, &mut Node)| -> Option<ReceptionOutcome> {
+        let (packet_index, packet_start, packet_end, packet_rssi) =
+            find_next_packet_to_process(node, now)?;
+        node.airtime_waiting_packets[packet_index].processed = true;
+        Some(evaluate_reception(
+            node,
+            packet_index,
+            packet_start,
+            packet_end,
+            packet_rssi,
+            scene,
+            external,
+        ))
+    };
+    let mut outcomes: Vec<ReceptionOutcome> = if parallel {
+        nodes_map.par_iter_mut().filter_map(outcome).collect()
+    } else {
+        nodes_map.iter_mut().filter_map(outcome).collect()
+    };
+    outcomes.sort_unstable_by_key(|outcome| outcome.node_id);
+    outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::types::AirtimeWaitingPacket;
+    use embassy_time::Duration;
+    use moonblokz_radio_lib::RadioPacket;
+
+    /// A scene with a wall across a grid of nodes; every node has two overlapping
+    /// pending packets.
+    fn grid_scene(node_count: u32) -> (Scene, HashMap<u32, Node>) {
+        let nodes: Vec<serde_json::Value> = (0..node_count)
+            .map(|id| {
+                serde_json::json!({
+                    "node_id": id,
+                    "position": { "x": (id % 20) as f64 * 150.0, "y": (id / 20) as f64 * 150.0 },
+                    "radio_strength": 14.0,
+                })
+            })
+            .collect();
+        let scene: Scene = serde_json::from_value(serde_json::json!({
+            "path_loss_parameters": {
+                "path_loss_exponent": 2.8, "shadowing_sigma": 0.0,
+                "path_loss_at_reference_distance": 38.0, "noise_floor": -117.0
+            },
+            "lora_parameters": {
+                "bandwidth": 250000, "spreading_factor": 7, "coding_rate": 1,
+                "preamble_symbols": 8.0, "crc_enabled": true, "low_data_rate_optimization": false
+            },
+            "radio_module_config": {
+                "delay_between_tx_packets": 200, "delay_between_tx_messages": 5,
+                "echo_request_minimal_interval": 3, "echo_messages_target_interval": 100,
+                "echo_gathering_timeout": 1, "relay_position_delay": 1,
+                "scoring_matrix": [173, 242, 49, 123, 42],
+                "retry_interval_for_missing_packets": 60, "tx_maximum_random_delay": 200
+            },
+            "nodes": nodes,
+            "obstacles": [{
+                "type": "rectangle",
+                "top_left": { "x": 1000.0, "y": 0.0 },
+                "bottom_right": { "x": 1100.0, "y": 1000.0 }
+            }],
+            "world_top_left": { "x": 0.0, "y": 0.0 },
+            "world_bottom_right": { "x": 3000.0, "y": 3000.0 }
+        }))
+        .unwrap();
+
+        let mut nodes_map: HashMap<u32, Node> =
+            scene.nodes.iter().map(|n| (n.node_id, n.clone())).collect();
+        for (id, node) in nodes_map.iter_mut() {
+            for (offset, rssi) in [(0, -90.0 - (*id % 13) as f32), (15, -95.0)] {
+                node.push_airtime_packet(AirtimeWaitingPacket {
+                    packet: RadioPacket {
+                        data: std::array::from_fn(|_| 0),
+                        length: 10,
+                    },
+                    sender_node_id: (id + 1) % node_count,
+                    start_time: Instant::from_millis(10 * (*id as u64 % 5) + offset),
+                    airtime: Duration::from_millis(30),
+                    processed: false,
+                    rssi,
+                });
+            }
+        }
+        (scene, nodes_map)
+    }
+
+    #[test]
+    fn test_parallel_and_sequential_physics_agree() {
+        for node_count in [PARALLEL_MIN_NODES as u32 / 2, PARALLEL_MIN_NODES as u32 * 2] {
+            let (scene, nodes_map) = grid_scene(node_count);
+            let sender = &nodes_map[&0];
+            let links = |parallel| {
+                collect_link_candidates(
+                    0,
+                    &sender.position,
+                    2000.0,
+                    14.0,
+                    &nodes_map,
+                    &scene,
+                    parallel,
+                )
+            };
+            let sequential = links(false);
+            assert!(!sequential.is_empty());
+            assert!(
+                sequential
+                    .windows(2)
+                    .all(|w| w[0].target_id < w[1].target_id)
+            );
+            assert_eq!(sequential, links(true));
+            assert_eq!(
+                sequential,
+                find_link_candidates(0, &sender.position, 2000.0, 14.0, &nodes_map, &scene)
+            );
+
+            let receptions = |parallel| {
+                collect_pending_receptions(&mut nodes_map.clone(), &scene, None, parallel)
+            };
+            let sequential = receptions(false);
+            assert_eq!(sequential.len(), node_count as usize);
+            assert!(sequential.windows(2).all(|w| w[0].node_id < w[1].node_id));
+            assert!(sequential.iter().any(|outcome| outcome.collision));
+            assert_eq!(sequential, receptions(true));
+        }
+    }
+}
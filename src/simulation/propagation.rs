@@ -0,0 +1,293 @@
+//! # Radio Propagation Models
+//!
+//! The path loss between two nodes is computed by a `PropagationModel` selected with
+//! the scene's `propagation_model` field:
+//! - `log_distance` (default): `PL(d) = PL(d₀) + 10·n·log10(d)` from `path_loss_parameters`
+//! - `two_ray_ground`: log-distance up to the crossover distance `4π·h_t·h_r/λ`, then
+//!   the ground reflection's 40 dB/decade slope; suits flat open terrain
+//! - `okumura_hata`: Hata's empirical model for urban, suburban or open areas
+//!   (150-1500 MHz, base station height 30-200 m, mobile height 1-10 m)
+//!
+//! All models add the log-normal shadowing of `path_loss_parameters.shadowing_sigma`
+//! and share the receiver noise floor; the effective distance (range check and UI) is
+//! derived from each model's mean path loss.
+
+use rand_distr::{Distribution, Normal};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::rng::with_simulation_rng;
+use super::signal_calculations::PathLossParameters;
+
+/// Speed of light divided by 10⁶, so `SPEED_OF_LIGHT_MEGA / f_MHz` is the wavelength in meters.
+const SPEED_OF_LIGHT_MEGA: f32 = 299.792_45;
+/// Longest distance the numeric effective distance search considers (meters).
+const MAX_EFFECTIVE_DISTANCE: f32 = 1_000_000.0;
+
+/// A path loss model of the radio channel.
+///
+/// Implementations must be deterministic in `mean_path_loss`: the physics stage calls
+/// it from the rayon thread pool, while shadowing is drawn on the executor thread.
+pub(crate) trait PropagationModel: Sync {
+    /// Name of the model for logs.
+    fn name(&self) -> &'static str;
+
+    /// Path loss in dB at `distance` meters, without shadowing.
+    fn mean_path_loss(&self, distance: f32, params: &PathLossParameters) -> f32;
+
+    /// Sample the log-normal shadowing term `X_σ` in dB (0 if `shadowing_sigma` is 0).
+    ///
+    /// Draws from the simulation RNG, so calls must happen in a deterministic order.
+    fn sample_shadowing(&self, params: &PathLossParameters) -> f32 {
+        if params.shadowing_sigma > 0.0 {
+            let normal =
+                Normal::new(0.0_f32, params.shadowing_sigma).expect("invalid normal sigma");
+            with_simulation_rng(|rng| normal.sample(rng))
+        } else {
+            0.0
+        }
+    }
+
+    /// Distance in meters at which the mean path loss reaches `max_path_loss` dB.
+    ///
+    /// The default searches the (monotonic) mean path loss numerically between 1 m
+    /// and 1000 km; returns 0 if even 1 m exceeds the budget.
+    fn effective_distance(&self, max_path_loss: f32, params: &PathLossParameters) -> f32 {
+        if self.mean_path_loss(1.0, params) >= max_path_loss {
+            return 0.0;
+        }
+        if self.mean_path_loss(MAX_EFFECTIVE_DISTANCE, params) < max_path_loss {
+            return MAX_EFFECTIVE_DISTANCE;
+        }
+        let (mut low, mut high) = (1.0_f32, MAX_EFFECTIVE_DISTANCE);
+        for _ in 0..60 {
+            let middle = (low * high).sqrt();
+            if self.mean_path_loss(middle, params) < max_path_loss {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+}
+
+/// Log-distance path loss: `PL(d) = PL(d₀) + 10·n·log10(d/d₀)` with d₀ = 1 m.
+pub(crate) struct LogDistance;
+
+impl PropagationModel for LogDistance {
+    fn name(&self) -> &'static str {
+        "log-distance"
+    }
+
+    fn mean_path_loss(&self, distance: f32, params: &PathLossParameters) -> f32 {
+        if distance < 1.0 {
+            return params.path_loss_at_reference_distance;
+        }
+        params.path_loss_at_reference_distance + 10.0 * params.path_loss_exponent * distance.log10()
+    }
+
+    fn effective_distance(&self, max_path_loss: f32, params: &PathLossParameters) -> f32 {
+        // PL0 + 10n·log10(d) = max  =>  d = 10^((max - PL0) / (10n))
+        let numerator = max_path_loss - params.path_loss_at_reference_distance;
+        if numerator <= 0.0 {
+            return 0.0;
+        }
+        10.0_f32.powf(numerator / (10.0 * params.path_loss_exponent))
+    }
+}
+
+/// Two-ray ground reflection model.
+#[derive(Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub(crate) struct TwoRayGround {
+    /// Carrier frequency in MHz.
+    pub(crate) frequency_mhz: f32,
+    /// Transmitter antenna height above ground in meters.
+    pub(crate) tx_antenna_height: f32,
+    /// Receiver antenna height above ground in meters.
+    pub(crate) rx_antenna_height: f32,
+}
+
+impl TwoRayGround {
+    /// Distance beyond which the ground reflection dominates: `4π·h_t·h_r/λ`.
+    fn crossover_distance(&self) -> f32 {
+        let wavelength = SPEED_OF_LIGHT_MEGA / self.frequency_mhz;
+        4.0 * std::f32::consts::PI * self.tx_antenna_height * self.rx_antenna_height / wavelength
+    }
+}
+
+impl PropagationModel for TwoRayGround {
+    fn name(&self) -> &'static str {
+        "two-ray ground"
+    }
+
+    fn mean_path_loss(&self, distance: f32, params: &PathLossParameters) -> f32 {
+        // Log-distance below the crossover, 40 dB/decade beyond it (continuous at d_c)
+        let crossover = self.crossover_distance().max(1.0);
+        if distance <= crossover {
+            return LogDistance.mean_path_loss(distance, params);
+        }
+        LogDistance.mean_path_loss(crossover, params) + 40.0 * (distance / crossover).log10()
+    }
+}
+
+/// Area type of the Okumura-Hata model.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum HataEnvironment {
+    /// Small or medium-sized city.
+    #[default]
+    Urban,
+    Suburban,
+    /// Open (rural) area.
+    Open,
+}
+
+/// Okumura-Hata empirical model.
+#[derive(Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub(crate) struct OkumuraHata {
+    /// Carrier frequency in MHz (150-1500).
+    pub(crate) frequency_mhz: f32,
+    /// Effective base station (transmitter) antenna height in meters (30-200).
+    pub(crate) base_station_height: f32,
+    /// Mobile (receiver) antenna height in meters (1-10).
+    pub(crate) mobile_height: f32,
+    #[serde(default)]
+    pub(crate) environment: HataEnvironment,
+}
+
+impl PropagationModel for OkumuraHata {
+    fn name(&self) -> &'static str {
+        "Okumura-Hata"
+    }
+
+    fn mean_path_loss(&self, distance: f32, params: &PathLossParameters) -> f32 {
+        let log_f = self.frequency_mhz.log10();
+        let log_hb = self.base_station_height.log10();
+        let log_d_km = (distance.max(1.0) / 1000.0).log10();
+        // Mobile antenna correction for small and medium-sized cities
+        let mobile_correction = (1.1 * log_f - 0.7) * self.mobile_height - (1.56 * log_f - 0.8);
+        let urban = 69.55 + 26.16 * log_f - 13.82 * log_hb - mobile_correction
+            + (44.9 - 6.55 * log_hb) * log_d_km;
+        let loss = match self.environment {
+            HataEnvironment::Urban => urban,
+            HataEnvironment::Suburban => {
+                urban - 2.0 * (self.frequency_mhz / 28.0).log10().powi(2) - 5.4
+            }
+            HataEnvironment::Open => urban - 4.78 * log_f.powi(2) + 18.33 * log_f - 40.94,
+        };
+        // The empirical fit is meant for kilometers; never lose less than at d₀
+        loss.max(params.path_loss_at_reference_distance)
+    }
+}
+
+/// The scene's `propagation_model` selection.
+#[derive(Deserialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum PropagationModelConfig {
+    #[default]
+    LogDistance,
+    TwoRayGround(TwoRayGround),
+    OkumuraHata(OkumuraHata),
+}
+
+impl PropagationModelConfig {
+    /// The selected model.
+    pub(crate) fn model(&self) -> &dyn PropagationModel {
+        match self {
+            Self::LogDistance => &LogDistance,
+            Self::TwoRayGround(model) => model,
+            Self::OkumuraHata(model) => model,
+        }
+    }
+
+    /// Check the model parameters.
+    ///
+    /// # Returns
+    ///
+    /// An error message describing the first invalid parameter.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::LogDistance => Ok(()),
+            Self::TwoRayGround(model) => {
+                if model.frequency_mhz <= 0.0 {
+                    return Err(
+                        "Invalid two_ray_ground frequency_mhz, must be positive".to_string()
+                    );
+                }
+                if model.tx_antenna_height <= 0.0 || model.rx_antenna_height <= 0.0 {
+                    return Err(
+                        "Invalid two_ray_ground antenna height, must be positive".to_string()
+                    );
+                }
+                Ok(())
+            }
+            Self::OkumuraHata(model) => {
+                if !(150.0..=1500.0).contains(&model.frequency_mhz) {
+                    return Err(format!(
+                        "Invalid okumura_hata frequency_mhz {}, must be 150-1500",
+                        model.frequency_mhz
+                    ));
+                }
+                if model.base_station_height <= 0.0 || model.mobile_height <= 0.0 {
+                    return Err("Invalid okumura_hata antenna height, must be positive".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> PathLossParameters {
+        PathLossParameters {
+            path_loss_exponent: 2.0,
+            shadowing_sigma: 0.0,
+            path_loss_at_reference_distance: 31.0,
+            noise_floor: -120.0,
+        }
+    }
+
+    #[test]
+    fn test_models_are_monotonic_and_invert_to_the_effective_distance() {
+        let params = params();
+        let two_ray: PropagationModelConfig = serde_json::from_str(
+            r#"{ "type": "two_ray_ground", "frequency_mhz": 868, "tx_antenna_height": 2, "rx_antenna_height": 2 }"#,
+        )
+        .unwrap();
+        let hata: PropagationModelConfig = serde_json::from_str(
+            r#"{ "type": "okumura_hata", "frequency_mhz": 868, "base_station_height": 30, "mobile_height": 1.5, "environment": "suburban" }"#,
+        )
+        .unwrap();
+        let log_distance: PropagationModelConfig =
+            serde_json::from_str(r#"{ "type": "log_distance" }"#).unwrap();
+        assert_eq!(log_distance, PropagationModelConfig::default());
+
+        for config in [&log_distance, &two_ray, &hata] {
+            config.validate().unwrap();
+            let model = config.model();
+            let near = model.mean_path_loss(100.0, &params);
+            let far = model.mean_path_loss(5000.0, &params);
+            assert!(far > near, "{} is not monotonic", model.name());
+
+            let range = model.effective_distance(far, &params);
+            assert!((range - 5000.0).abs() < 5.0, "{}: {}", model.name(), range);
+            assert_eq!(model.effective_distance(0.0, &params), 0.0);
+        }
+
+        // Beyond the crossover the ground reflection loses 40 dB per decade
+        let two_ray = two_ray.model();
+        let slope =
+            two_ray.mean_path_loss(10_000.0, &params) - two_ray.mean_path_loss(1000.0, &params);
+        assert!((slope - 40.0).abs() < 0.01);
+
+        let out_of_range: PropagationModelConfig = serde_json::from_str(
+            r#"{ "type": "okumura_hata", "frequency_mhz": 2400, "base_station_height": 30, "mobile_height": 1.5 }"#,
+        )
+        .unwrap();
+        assert!(out_of_range.validate().is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Reusable `'static` channels of the node tasks.
+//!
+//! Embassy tasks need `'static` channels, so the per-node queues (node inputs, radio
+//! device queues, node process frames) and the shared node output queue are leaked.
+//! Leaking a fresh set for every session would grow the process with each mode
+//! switch; instead a task hands its queues back to a pool once nothing can touch
+//! them anymore, and the next session takes them from there (cleared) before
+//! leaking new ones. The pools therefore hold at most the queues of the largest
+//! session run so far.
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Channel;
+use std::sync::Mutex;
+
+/// Channel that can be emptied and used again.
+pub trait ReusableQueue: 'static {
+    /// Create an empty channel.
+    fn create() -> Self;
+
+    /// Drop all queued items.
+    fn clear(&self);
+}
+
+impl<M: RawMutex + 'static, T: 'static, const N: usize> ReusableQueue for Channel<M, T, N> {
+    fn create() -> Self {
+        Channel::new()
+    }
+
+    fn clear(&self) {
+        Channel::clear(self);
+    }
+}
+
+/// Free `'static` channels of one type.
+pub struct QueuePool<C: ReusableQueue> {
+    free: Mutex<Vec<&'static C>>,
+}
+
+impl<C: ReusableQueue> QueuePool<C> {
+    /// Create an empty pool.
+    pub const fn new() -> Self {
+        Self {
+            free: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Take an empty channel: a returned one (cleared) or a newly leaked one.
+    pub fn take(&self) -> &'static C {
+        let reused = self.free.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        match reused {
+            Some(channel) => {
+                channel.clear();
+                channel
+            }
+            // INTENTIONAL LEAK: Box::leak provides 'static lifetime for Embassy channels.
+            // Required to use the embedded moonblokz-radio-lib code in the simulator.
+            None => Box::leak(Box::new(C::create())),
+        }
+    }
+
+    /// Return a channel for reuse.
+    ///
+    /// Only call this once no task or thread can send to or receive from the channel
+    /// anymore; queues of failed tasks that may still be in use stay leaked.
+    pub fn give_back(&self, channel: &'static C) {
+        self.free
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(channel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+
+    #[test]
+    fn returned_channels_are_reused_empty() {
+        let pool: QueuePool<Channel<CriticalSectionRawMutex, u32, 4>> = QueuePool::new();
+        let first = pool.take();
+        first.try_send(7).unwrap();
+        pool.give_back(first);
+
+        let reused = pool.take();
+        assert!(std::ptr::eq(first, reused));
+        assert!(reused.try_receive().is_err());
+
+        // The pool is empty again, so another channel is leaked
+        assert!(!std::ptr::eq(reused, pool.take()));
+    }
+}
@@ -0,0 +1,44 @@
+//! Seedable random number generator of the simulation.
+//!
+//! All random draws of the network task (shadowing, packet error rate decoding,
+//! automatic measurement IDs, stress test origins) use one global generator, so a
+//! run can be repeated with the same seed. The seed comes from the scene's `seed`
+//! field; without it a random seed is drawn and logged. The determinism audit
+//! overrides the seed of both runs.
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::sync::Mutex;
+
+/// Global simulation generator (seeded from entropy until `init_simulation_rng`).
+static SIMULATION_RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// Seed forced by the determinism audit, taking precedence over the scene seed.
+static SEED_OVERRIDE: Mutex<Option<u64>> = Mutex::new(None);
+
+/// Force the seed used by the next `init_simulation_rng` call.
+pub fn set_seed_override(seed: u64) {
+    *SEED_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) = Some(seed);
+}
+
+/// Seed the simulation generator when a scene is loaded.
+///
+/// # Parameters
+///
+/// * `scene_seed` - Seed from the scene file, if any
+///
+/// # Returns
+///
+/// The seed in use: the audit override, the scene seed or a random seed.
+pub fn init_simulation_rng(scene_seed: Option<u64>) -> u64 {
+    let forced = *SEED_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner());
+    let seed = forced.or(scene_seed).unwrap_or_else(rand::random);
+    *SIMULATION_RNG.lock().unwrap_or_else(|e| e.into_inner()) = Some(StdRng::seed_from_u64(seed));
+    seed
+}
+
+/// Run `f` with the simulation generator.
+pub fn with_simulation_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    let mut guard = SIMULATION_RNG.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(StdRng::from_entropy))
+}
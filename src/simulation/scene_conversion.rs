@@ -0,0 +1,269 @@
+//! Conversion of analyzer scenes into simulation scenes.
+//!
+//! Analyzer scenes describe a real deployment by each node's observed range
+//! (`effective_distance`); simulation scenes need radio physics and a TX power per
+//! node. The converter takes the physics sections (`path_loss_parameters`,
+//! `lora_parameters`, `radio_module_config` and, when present, `propagation_model`
+//! and `regulatory_limits`) from a simulation template scene or from the analyzer
+//! scene itself (template first), and chooses every node's `radio_strength` so that
+//! `calculate_effective_distance` reproduces its `effective_distance`.
+//!
+//! TX powers are searched between -50 dBm and the regional maximum (50 dBm without
+//! `regulatory_limits`); nodes whose range cannot be reached within these bounds
+//! get the nearest bound and are reported. Positions, obstacles, world bounds and
+//! the other shared fields are kept; the analyzer-only fields are dropped.
+
+use anyhow::{Context, anyhow, bail};
+use serde_json::Value;
+use std::fs;
+
+use super::propagation::{PropagationModel, PropagationModelConfig};
+use super::signal_calculations::{
+    LoraParameters, PathLossParameters, calculate_effective_distance,
+};
+use super::types::RegulatoryLimits;
+use crate::common::scene::read_scene_value;
+
+/// Lowest TX power considered (the scene validation's lower bound).
+const MIN_TX_POWER: f32 = -50.0;
+/// Highest TX power considered without regulatory limits (the scene validation's upper bound).
+const MAX_TX_POWER: f32 = 50.0;
+
+/// Physics sections copied into the simulation scene.
+const PHYSICS_FIELDS: [&str; 5] = [
+    "path_loss_parameters",
+    "lora_parameters",
+    "radio_module_config",
+    "propagation_model",
+    "regulatory_limits",
+];
+
+/// Physics sections a simulation scene cannot do without.
+const REQUIRED_FIELDS: [&str; 3] = [
+    "path_loss_parameters",
+    "lora_parameters",
+    "radio_module_config",
+];
+
+/// Fields only the analyzer understands.
+const ANALYZER_ONLY_FIELDS: [&str; 2] = [
+    "link_quality_weak_threshold",
+    "link_quality_excellent_threshold",
+];
+
+/// Summary of a converted scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    /// Number of converted nodes.
+    pub nodes: usize,
+    /// Lowest and highest chosen TX power (dBm).
+    pub tx_power_range: (f32, f32),
+    /// Nodes whose range is out of reach of the TX power bounds.
+    pub clamped: Vec<u32>,
+}
+
+/// TX power (dBm) whose effective distance is `range` meters, within `bounds`.
+///
+/// The effective distance grows with the TX power for every propagation model, so
+/// the power is found by bisection.
+///
+/// # Returns
+///
+/// The TX power rounded to 0.1 dBm, and whether it was clamped to a bound.
+fn tx_power_for_range(
+    range: f32,
+    bounds: (f32, f32),
+    lora: &LoraParameters,
+    model: &dyn PropagationModel,
+    path_loss: &PathLossParameters,
+) -> (f32, bool) {
+    let distance = |tx_power| calculate_effective_distance(tx_power, lora, model, path_loss);
+    let (mut low, mut high) = bounds;
+    if distance(high) < range {
+        return (high, true);
+    }
+    if distance(low) > range {
+        return (low, true);
+    }
+    for _ in 0..40 {
+        let middle = (low + high) / 2.0;
+        if distance(middle) < range {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (((low + high) / 2.0 * 10.0).round() / 10.0, false)
+}
+
+/// Deserialize a physics section of the converted scene.
+fn section<T: serde::de::DeserializeOwned>(scene: &Value, field: &str) -> anyhow::Result<T> {
+    serde_json::from_value(scene.get(field).cloned().unwrap_or(Value::Null))
+        .with_context(|| format!("Invalid {}", field))
+}
+
+/// Convert a resolved analyzer scene into a simulation scene.
+///
+/// # Parameters
+///
+/// * `analyzer` - Analyzer scene JSON (includes resolved)
+/// * `template` - Simulation scene whose physics sections take precedence, if any
+///
+/// # Returns
+///
+/// The simulation scene JSON and a summary of the conversion.
+pub fn convert_scene(
+    analyzer: &Value,
+    template: Option<&Value>,
+) -> anyhow::Result<(Value, Conversion)> {
+    let mut scene = analyzer.clone();
+    let object = scene
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("scene is not a JSON object"))?;
+    for field in ANALYZER_ONLY_FIELDS {
+        object.remove(field);
+    }
+    if let Some(template) = template {
+        for field in PHYSICS_FIELDS {
+            if let Some(value) = template.get(field) {
+                object.insert(field.to_string(), value.clone());
+            }
+        }
+    }
+    let missing: Vec<&str> = REQUIRED_FIELDS
+        .into_iter()
+        .filter(|field| !object.contains_key(*field))
+        .collect();
+    if !missing.is_empty() {
+        bail!(
+            "missing {} (add them to the scene or pass a simulation scene as --template)",
+            missing.join(", ")
+        );
+    }
+
+    let path_loss: PathLossParameters = section(&scene, "path_loss_parameters")?;
+    let lora: LoraParameters = section(&scene, "lora_parameters")?;
+    let propagation: PropagationModelConfig = match scene.get("propagation_model") {
+        Some(_) => section(&scene, "propagation_model")?,
+        None => PropagationModelConfig::default(),
+    };
+    let limits: RegulatoryLimits = match scene.get("regulatory_limits") {
+        Some(_) => section(&scene, "regulatory_limits")?,
+        None => RegulatoryLimits::default(),
+    };
+    let bounds = (
+        MIN_TX_POWER,
+        limits.max_tx_power_dbm.unwrap_or(MAX_TX_POWER),
+    );
+
+    let nodes = scene
+        .get_mut("nodes")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| anyhow!("scene has no nodes"))?;
+    let mut conversion = Conversion {
+        nodes: nodes.len(),
+        tx_power_range: (f32::MAX, f32::MIN),
+        clamped: Vec::new(),
+    };
+    for node in nodes.iter_mut() {
+        let node_id = node.get("node_id").and_then(Value::as_u64).unwrap_or(0) as u32;
+        let node = node
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("node {} is not a JSON object", node_id))?;
+        let range = node
+            .remove("effective_distance")
+            .and_then(|v| v.as_f64())
+            .ok_or_else(|| anyhow!("node {} has no effective_distance", node_id))?;
+        let (tx_power, clamped) =
+            tx_power_for_range(range as f32, bounds, &lora, propagation.model(), &path_loss);
+        if clamped {
+            conversion.clamped.push(node_id);
+        }
+        conversion.tx_power_range.0 = conversion.tx_power_range.0.min(tx_power);
+        conversion.tx_power_range.1 = conversion.tx_power_range.1.max(tx_power);
+        // Round in f64 so the file shows 7.3 rather than the f32's 7.300000190734863
+        let tx_power = (tx_power as f64 * 10.0).round() / 10.0;
+        node.insert("radio_strength".to_string(), tx_power.into());
+    }
+    if conversion.nodes == 0 {
+        bail!("scene has no nodes");
+    }
+    Ok((scene, conversion))
+}
+
+/// Convert an analyzer scene file and write the simulation scene.
+///
+/// # Parameters
+///
+/// * `scene_path` - Analyzer scene file
+/// * `template` - Simulation template scene (already read), if any
+/// * `output_path` - Where to write the simulation scene
+pub fn run_conversion(
+    scene_path: &str,
+    template: Option<&Value>,
+    output_path: &str,
+) -> anyhow::Result<Conversion> {
+    let analyzer = read_scene_value(scene_path).map_err(|e| anyhow!(e))?;
+    let (scene, conversion) = convert_scene(&analyzer, template)?;
+    let output = serde_json::to_string_pretty(&scene)?;
+    fs::write(output_path, output)
+        .with_context(|| format!("Failed to write file: {}", output_path))?;
+    Ok(conversion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::propagation::LogDistance;
+    use serde_json::json;
+
+    #[test]
+    fn converted_tx_powers_reproduce_the_ranges() {
+        let analyzer = json!({
+            "nodes": [
+                { "node_id": 1, "position": { "x": 0, "y": 0 }, "effective_distance": 800 },
+                { "node_id": 2, "position": { "x": 5, "y": 5 }, "effective_distance": 2500 },
+                { "node_id": 3, "position": { "x": 9, "y": 9 }, "effective_distance": 10_000_000 }
+            ],
+            "world_top_left": { "x": 0, "y": 0 },
+            "world_bottom_right": { "x": 10, "y": 10 },
+            "link_quality_weak_threshold": 10
+        });
+        assert!(convert_scene(&analyzer, None).is_err());
+
+        let template = json!({
+            "path_loss_parameters": {
+                "path_loss_exponent": 3.0, "shadowing_sigma": 0.0,
+                "path_loss_at_reference_distance": 40.0, "noise_floor": -120.0
+            },
+            "lora_parameters": {
+                "bandwidth": 125000, "spreading_factor": 9, "coding_rate": 1,
+                "preamble_symbols": 8.0, "crc_enabled": true, "low_data_rate_optimization": false
+            },
+            "radio_module_config": {},
+            "regulatory_limits": { "max_tx_power_dbm": 14.0 },
+            "nodes": []
+        });
+        let (scene, conversion) = convert_scene(&analyzer, Some(&template)).unwrap();
+        assert_eq!(conversion.nodes, 3);
+        assert_eq!(conversion.clamped, vec![3]);
+        assert!(scene.get("link_quality_weak_threshold").is_none());
+        assert_eq!(scene["nodes"][2]["radio_strength"], json!(14.0));
+
+        let path_loss: PathLossParameters = section(&scene, "path_loss_parameters").unwrap();
+        let lora: LoraParameters = section(&scene, "lora_parameters").unwrap();
+        for (index, range) in [(0, 800.0), (1, 2500.0)] {
+            let node = &scene["nodes"][index];
+            assert!(node.get("effective_distance").is_none());
+            let tx_power = node["radio_strength"].as_f64().unwrap() as f32;
+            let distance = calculate_effective_distance(tx_power, &lora, &LogDistance, &path_loss);
+            // 0.1 dB of rounding moves a 30 dB/decade range by < 1%
+            assert!(
+                (distance - range).abs() / range < 0.01,
+                "{} vs {}",
+                distance,
+                range
+            );
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! # Experiment Scripts
+//!
+//! A scene can name a Rhai script (`script` in the scene file) holding custom
+//! experiment logic, so experiments beyond the built-in drivers (automatic
+//! measurement, stress test, origin sampling) need no recompilation. The script's
+//! top level runs once when the scene is loaded; afterwards the hook functions it
+//! defines are called by the network task:
+//!
+//! - `on_start()`: first loop iteration after the nodes were started
+//! - `on_tick(now_ms)`: every `tick_interval` virtual milliseconds
+//! - `on_transmit(node_id, message_type)`
+//! - `on_receive(node_id, sender_id, message_type, rssi)`
+//! - `on_collision(node_id, sender_id)`
+//! - `on_reached(measurement_id, node_id)`: a node was reached by a measurement
+//!
+//! Hooks that the script does not define are skipped. Rhai functions do not see
+//! the script's global variables, so the hooks share a state object bound as
+//! `this` that lives for the whole run (e.g. `this.switched = true`). The script observes the
+//! simulation through `now_ms()`, `node_ids()`, `sent()`, `received()` and
+//! `collisions()` and acts through `start_measurement(node_id)` (returns the
+//! measurement ID), `set_node_enabled(node_id, enabled)` and
+//! `record_metric(name, value)`, which appends a `time_s,name,value` row to the
+//! metrics CSV file. `print` writes to the simulator log.
+//!
+//! Commands are queued while a hook runs and applied by the network task in its
+//! next loop iteration, so a hook never changes the network state under the radio
+//! event being handled. Every hook call is limited to `MAX_OPERATIONS` script
+//! operations; a failing hook is logged and the run continues.
+
+use embassy_time::{Duration, Instant};
+use rhai::{AST, Array, CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use super::event_bus::{PacketCounters, SimulationEvent, SimulationObserver};
+use super::types::ScriptConfig;
+use crate::common::measurement_ids;
+
+/// Maximum number of script operations of a single hook call (runaway loop guard).
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A command issued by the script, applied by the network task.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptAction {
+    /// Originate a measurement AddBlock from a node.
+    StartMeasurement { node_id: u32, measurement_id: u32 },
+    /// Switch a node's radio on or off.
+    SetNodeEnabled { node_id: u32, enabled: bool },
+}
+
+/// State shared between the host and the functions registered in the engine.
+#[derive(Default)]
+struct ScriptShared {
+    actions: Vec<ScriptAction>,
+    now_ms: u64,
+    counters: PacketCounters,
+    node_ids: Vec<u32>,
+    metrics: Option<BufWriter<File>>,
+}
+
+/// Compiled scene script with its engine and global scope.
+pub struct ScriptHost {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    /// State object shared by the hooks as `this`.
+    this: Dynamic,
+    shared: Arc<Mutex<ScriptShared>>,
+    /// Names of the functions the script defines.
+    hooks: HashSet<String>,
+    scene_start: Instant,
+    tick_interval: Duration,
+    next_tick: Instant,
+    started: bool,
+}
+
+/// Convert a script integer to a node ID.
+fn node_id_arg(node_id: i64) -> u32 {
+    u32::try_from(node_id).unwrap_or(u32::MAX)
+}
+
+/// Create the engine with the simulation API registered.
+fn build_engine(shared: &Arc<Mutex<ScriptShared>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| log::info!("[script] {}", text));
+    engine.on_debug(|text, _, _| log::debug!("[script] {}", text));
+
+    let state = Arc::clone(shared);
+    engine.register_fn("now_ms", move || -> i64 {
+        state.lock().map_or(0, |s| s.now_ms as i64)
+    });
+    let state = Arc::clone(shared);
+    engine.register_fn("node_ids", move || -> Array {
+        state.lock().map_or_else(
+            |_| Array::new(),
+            |s| {
+                s.node_ids
+                    .iter()
+                    .map(|id| Dynamic::from(*id as i64))
+                    .collect()
+            },
+        )
+    });
+    let state = Arc::clone(shared);
+    engine.register_fn("sent", move || -> i64 {
+        state.lock().map_or(0, |s| s.counters.sent as i64)
+    });
+    let state = Arc::clone(shared);
+    engine.register_fn("received", move || -> i64 {
+        state.lock().map_or(0, |s| s.counters.received as i64)
+    });
+    let state = Arc::clone(shared);
+    engine.register_fn("collisions", move || -> i64 {
+        state.lock().map_or(0, |s| s.counters.collisions as i64)
+    });
+
+    let state = Arc::clone(shared);
+    engine.register_fn("start_measurement", move |node_id: i64| -> i64 {
+        let measurement_id = measurement_ids::next();
+        if let Ok(mut s) = state.lock() {
+            s.actions.push(ScriptAction::StartMeasurement {
+                node_id: node_id_arg(node_id),
+                measurement_id,
+            });
+        }
+        measurement_id as i64
+    });
+    let state = Arc::clone(shared);
+    engine.register_fn("set_node_enabled", move |node_id: i64, enabled: bool| {
+        if let Ok(mut s) = state.lock() {
+            s.actions.push(ScriptAction::SetNodeEnabled {
+                node_id: node_id_arg(node_id),
+                enabled,
+            });
+        }
+    });
+    let state = Arc::clone(shared);
+    let record_metric = move |name: &str, value: f64| {
+        let Ok(mut s) = state.lock() else {
+            return;
+        };
+        let time_s = s.now_ms as f64 / 1000.0;
+        if let Some(writer) = s.metrics.as_mut() {
+            if let Err(e) = writeln!(writer, "{:.3},{},{}", time_s, name, value) {
+                log::warn!("Failed to write script metric {}: {}", name, e);
+            }
+        }
+    };
+    let record_int_metric = record_metric.clone();
+    engine.register_fn("record_metric", record_metric);
+    engine.register_fn("record_metric", move |name: &str, value: i64| {
+        record_int_metric(name, value as f64)
+    });
+    engine
+}
+
+/// Default metrics file of a script: `<script name>_metrics.csv` next to it.
+fn default_metrics_path(script_path: &str) -> String {
+    let path = Path::new(script_path);
+    let stem = path
+        .file_stem()
+        .map_or("script".into(), |s| s.to_string_lossy());
+    path.with_file_name(format!("{}_metrics.csv", stem))
+        .to_string_lossy()
+        .to_string()
+}
+
+impl ScriptHost {
+    /// Read, compile and run the scene script.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - Scene script definition (paths already resolved)
+    /// * `node_ids` - IDs of the scene's nodes
+    /// * `scene_start` - Virtual time when the scene was loaded
+    ///
+    /// # Returns
+    ///
+    /// The host, or a message for unreadable scripts and metrics files, syntax
+    /// errors and errors of the script's top level.
+    pub fn load(
+        config: &ScriptConfig,
+        node_ids: Vec<u32>,
+        scene_start: Instant,
+    ) -> Result<Self, String> {
+        let source = std::fs::read_to_string(&config.path)
+            .map_err(|e| format!("Failed to read script {}: {}", config.path, e))?;
+        let metrics_path = config
+            .metrics_file
+            .clone()
+            .unwrap_or_else(|| default_metrics_path(&config.path));
+        let metrics = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&metrics_path)
+            .map_err(|e| format!("Failed to open script metrics file {}: {}", metrics_path, e))?;
+        let mut host = Self::compile(&source, node_ids, scene_start, config.tick_interval)?;
+        if let Ok(mut shared) = host.shared.lock() {
+            shared.metrics = Some(BufWriter::new(metrics));
+        }
+        host.run_top_level()?;
+        log::info!("Script {} loaded, hooks: {}", config.path, host.hook_list());
+        Ok(host)
+    }
+
+    /// Compile a script without running it.
+    fn compile(
+        source: &str,
+        node_ids: Vec<u32>,
+        scene_start: Instant,
+        tick_interval_ms: u64,
+    ) -> Result<Self, String> {
+        let shared = Arc::new(Mutex::new(ScriptShared {
+            node_ids,
+            ..ScriptShared::default()
+        }));
+        let engine = build_engine(&shared);
+        let ast = engine
+            .compile(source)
+            .map_err(|e| format!("Script syntax error: {}", e))?;
+        let hooks = ast.iter_functions().map(|f| f.name.to_string()).collect();
+        let tick_interval = Duration::from_millis(tick_interval_ms);
+        Ok(Self {
+            engine,
+            ast,
+            scope: Scope::new(),
+            this: Dynamic::from_map(Map::new()),
+            shared,
+            hooks,
+            scene_start,
+            tick_interval,
+            next_tick: scene_start + tick_interval,
+            started: false,
+        })
+    }
+
+    /// Run the script's top-level statements (global variables, setup).
+    fn run_top_level(&mut self) -> Result<(), String> {
+        self.engine
+            .run_ast_with_scope(&mut self.scope, &self.ast)
+            .map_err(|e| format!("Script error: {}", e))
+    }
+
+    /// Comma-separated names of the defined hooks (for the load log line).
+    fn hook_list(&self) -> String {
+        let mut hooks: Vec<&str> = self
+            .hooks
+            .iter()
+            .map(String::as_str)
+            .filter(|name| name.starts_with("on_"))
+            .collect();
+        hooks.sort_unstable();
+        if hooks.is_empty() {
+            "none".to_string()
+        } else {
+            hooks.join(", ")
+        }
+    }
+
+    /// Call a hook if the script defines it; errors are logged.
+    fn call(&mut self, hook: &str, now: Instant, counters: PacketCounters, args: impl FuncArgs) {
+        if !self.hooks.contains(hook) {
+            return;
+        }
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.now_ms = now.saturating_duration_since(self.scene_start).as_millis();
+            shared.counters = counters;
+        }
+        // The top level already ran at load time
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.this);
+        if let Err(e) = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            hook,
+            args,
+        ) {
+            log::error!("Script hook {} failed: {}", hook, e);
+        }
+    }
+
+    /// Call `on_start` once, then `on_tick` whenever a tick interval has passed.
+    ///
+    /// # Parameters
+    ///
+    /// * `now` - Current virtual time
+    /// * `counters` - Current global packet counters
+    pub fn tick(&mut self, now: Instant, counters: PacketCounters) {
+        if !self.started {
+            self.started = true;
+            self.call("on_start", now, counters, ());
+        }
+        if now >= self.next_tick {
+            // Ticks missed while the loop was busy are not replayed
+            self.next_tick = now + self.tick_interval;
+            let now_ms = now.saturating_duration_since(self.scene_start).as_millis() as i64;
+            self.call("on_tick", now, counters, (now_ms,));
+        }
+        if let Ok(mut shared) = self.shared.lock() {
+            if let Some(writer) = shared.metrics.as_mut() {
+                let _ = writer.flush();
+            }
+        }
+    }
+
+    /// Call the radio event hook matching `event`.
+    pub fn on_event(&mut self, event: &SimulationEvent, counters: PacketCounters) {
+        let now = Instant::now();
+        match *event {
+            SimulationEvent::Transmitted {
+                node_id, packet, ..
+            } => self.call(
+                "on_transmit",
+                now,
+                counters,
+                (node_id as i64, packet.message_type() as i64),
+            ),
+            SimulationEvent::Received {
+                node_id,
+                sender_id,
+                packet,
+                rssi,
+                ..
+            } => self.call(
+                "on_receive",
+                now,
+                counters,
+                (
+                    node_id as i64,
+                    sender_id as i64,
+                    packet.message_type() as i64,
+                    rssi as f64,
+                ),
+            ),
+            SimulationEvent::Collided {
+                node_id, sender_id, ..
+            } => self.call(
+                "on_collision",
+                now,
+                counters,
+                (node_id as i64, sender_id as i64),
+            ),
+            SimulationEvent::MissedWhileTransmitting { .. }
+            | SimulationEvent::MissedWhileSleeping { .. } => {}
+        }
+    }
+
+    /// Call `on_reached` for a node reached by a measurement.
+    pub fn on_reached(&mut self, measurement_id: u32, node_id: u32, counters: PacketCounters) {
+        self.call(
+            "on_reached",
+            Instant::now(),
+            counters,
+            (measurement_id as i64, node_id as i64),
+        );
+    }
+
+    /// Commands issued by the script since the last call.
+    pub fn take_actions(&mut self) -> Vec<ScriptAction> {
+        self.shared
+            .lock()
+            .map(|mut shared| std::mem::take(&mut shared.actions))
+            .unwrap_or_default()
+    }
+}
+
+/// Hands the radio events of the event bus to the script's hooks.
+pub struct ScriptObserver(pub Rc<RefCell<ScriptHost>>);
+
+impl SimulationObserver for ScriptObserver {
+    fn on_event(&mut self, event: &SimulationEvent, counters: &PacketCounters) {
+        self.0.borrow_mut().on_event(event, *counters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_queue_commands_and_keep_state() {
+        let source = r#"
+            fn on_start() {
+                this.toggled = false;
+            }
+            fn on_tick(now_ms) {
+                if now_ms >= 2000 && !this.toggled {
+                    this.toggled = true;
+                    set_node_enabled(7, false);
+                    start_measurement(node_ids()[0]);
+                }
+            }
+        "#;
+        let start = Instant::from_secs(0);
+        let mut host = ScriptHost::compile(source, vec![3, 7], start, 1000).unwrap();
+        host.run_top_level().unwrap();
+
+        host.tick(Instant::from_millis(1000), PacketCounters::default());
+        assert!(host.take_actions().is_empty());
+
+        host.tick(Instant::from_millis(2000), PacketCounters::default());
+        let actions = host.take_actions();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(
+            actions[0],
+            ScriptAction::SetNodeEnabled {
+                node_id: 7,
+                enabled: false
+            }
+        );
+        assert!(matches!(
+            actions[1],
+            ScriptAction::StartMeasurement { node_id: 3, .. }
+        ));
+
+        host.tick(Instant::from_millis(3000), PacketCounters::default());
+        assert!(host.take_actions().is_empty());
+    }
+}
@@ -1,10 +1,12 @@
 //! Radio signal and timing calculations.
 //!
 //! Contains helpers for:
-//! - Log-distance path loss with optional log-normal shadowing
+//! - Path loss with optional log-normal shadowing through the scene's propagation
+//!   model (see `propagation`)
 //! - LoRa-inspired airtime, preamble, and CAD duration estimates
 //! - Effective communication distance estimation given a simple link budget
 //! - SNR thresholds and RSSI sampling utilities
+//! - SINR → packet success probability for the packet error rate reception model
 //!
 //! Units:
 //! - Power: dBm, mW (conversion provided)
@@ -12,16 +14,18 @@
 //! - Distance: meters in the physical model; world units are interpreted by the caller
 
 use embassy_time::Duration;
-use rand::thread_rng;
-use rand_distr::{Distribution, Normal};
+use schemars::JsonSchema;
 use serde::Deserialize;
 
+use super::propagation::PropagationModel;
+
 /// Parameters defining the radio channel propagation model.
 ///
 /// This struct encapsulates the constants used in the log-distance path loss model
 /// with log-normal shadowing. These parameters determine how signal strength
-/// decays over distance and how much random variation (shadowing) is applied.
-#[derive(Deserialize, Clone)]
+/// decays over distance and how much random variation (shadowing) is applied; the
+/// other propagation models use the shadowing, noise floor and reference loss.
+#[derive(Deserialize, JsonSchema, Clone)]
 pub(crate) struct PathLossParameters {
     /// Path loss exponent (n).
     ///
@@ -52,7 +56,7 @@ pub(crate) struct PathLossParameters {
     pub(crate) noise_floor: f32,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub(crate) struct LoraParameters {
     pub(crate) bandwidth: u32,
     pub(crate) spreading_factor: u8,
@@ -65,26 +69,36 @@ pub(crate) struct LoraParameters {
     pub(crate) low_data_rate_optimization: bool,
 }
 
-/// Calculate the path loss (in dB) at a given distance using a log-distance
-/// path loss model with log-normal shadowing.
+/// How a packet's SINR decides whether it is decoded.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ReceptionModel {
+    /// Decoded if and only if the SINR reaches the SF-dependent SNR limit.
+    #[default]
+    Threshold,
+    /// Decoded with the probability given by `calculate_packet_success_probability`.
+    PacketErrorRate,
+}
+
+/// Calculate the path loss (in dB) at a given distance using the scene's
+/// propagation model with log-normal shadowing.
 ///
 /// # Formula
 ///
 /// ```text
-/// PL(d) = PL(d₀) + 10 × n × log₁₀(d/d₀) + X_σ
-/// where d₀ = 1 meter (reference distance)
+/// PL(d) = PL_model(d) + X_σ
 /// ```
 ///
 /// Where:
-/// - `PL(d₀)`: Path loss at reference distance (1 meter), from `params.path_loss_at_reference_distance`
-/// - `n`: Path loss exponent, from `params.path_loss_exponent` (typical values: 2.0 for free space, 2-4 for urban environments)
-/// - `d`: Distance in meters
+/// - `PL_model(d)`: Mean path loss of the propagation model (log-distance, two-ray
+///   ground or Okumura-Hata, see `propagation`)
 /// - `X_σ`: Log-normal shadowing term sampled from Normal(0, σ) where σ = `params.shadowing_sigma`
 ///
 /// # Parameters
 ///
 /// - `distance`: The distance between transmitter and receiver in meters
-/// - `params`: Path loss model parameters including exponent, shadowing sigma, and reference loss
+/// - `model`: Propagation model of the scene
+/// - `params`: Path loss parameters including shadowing sigma and reference loss
 ///
 /// # Returns
 ///
@@ -92,55 +106,34 @@ pub(crate) struct LoraParameters {
 ///
 /// # Notes
 ///
-/// - For distances < 1.0 meter, returns the reference path loss without further attenuation
-/// - The shadowing term introduces randomness to model environmental variations and multipath effects
+/// - For distances < 1.0 meter, returns the model's reference path loss without shadowing
 /// - Each call samples a new shadowing value, so repeated calls with the same distance yield different results
-pub(crate) fn calculate_path_loss(distance: f32, params: &PathLossParameters) -> f32 {
+pub(crate) fn calculate_path_loss(
+    distance: f32,
+    model: &dyn PropagationModel,
+    params: &PathLossParameters,
+) -> f32 {
     if distance < 1.0 {
-        return params.path_loss_at_reference_distance;
+        return model.mean_path_loss(distance, params);
     }
-    let path_loss = params.path_loss_at_reference_distance
-        + 10.0 * params.path_loss_exponent * distance.log10();
-    // Sample log-normal shadowing as a Normal(0, sigma) in dB
-    let shadowing = if params.shadowing_sigma > 0.0 {
-        let normal = Normal::new(0.0_f32, params.shadowing_sigma).expect("invalid normal sigma");
-        let mut rng = thread_rng();
-        normal.sample(&mut rng)
-    } else {
-        0.0
-    };
-    path_loss + shadowing
+    model.mean_path_loss(distance, params) + model.sample_shadowing(params)
 }
 
-// Estimate an "effective" communication distance from a simplified log-distance path loss model.
-// Model: PL(d) = PL(d0) + 10 * n * log10(d / d0).
-// Solving for distance with a link budget L = P_tx - PL(d0) gives:
-//   d = d0 * 10^((P_tx - PL(d0)) / (10 * n))
-// Assumptions:
-// - d0 = 1 meter (anchor). We take PL(d0) from the provided parameters.
-// - Receiver sensitivity, antenna gains, margins, and noise floor are ignored; this yields an upper-bound distance.
-// - Shadowing is intentionally not sampled here to keep the estimate stable across calls. The result is a statistical average, not a specific link instance.
 /// Estimate a deterministic “effective distance” based on a basic link budget
 /// without sampling shadowing. Used for range checks and UI visualization.
+///
+/// Finds the distance `d` where the received power equals the receiving limit
+/// (sensitivity threshold): `P_tx - PL_model(d) = RL`. Antenna gains and margins are
+/// ignored, so this is an upper-bound distance and a statistical average, not a
+/// specific link instance.
 pub(crate) fn calculate_effective_distance(
     tx_power_dbm: f32,
     lora_parameters: &LoraParameters,
+    model: &dyn PropagationModel,
     path_loss_parameters: &PathLossParameters,
 ) -> f32 {
-    // Find distance d where received power equals the receiving limit (sensitivity threshold):
-    //   P_rx(dBm) = P_tx(dBm) - PL(d) = receiving_limit
-    // With PL(d) = PL(d0) + 10 * n * log10(d/d0) and d0 = 1 m, PL(d) = PL0 + 10n*log10(d)
-    // Solve for d:
-    //   P_tx - (PL0 + 10n log10 d) = RL  =>  10n log10 d = P_tx - RL - PL0  =>
-    //   d = 10^((P_tx - RL - PL0) / (10n))
-    let pl0 = path_loss_parameters.path_loss_at_reference_distance;
     let rl = calculate_receiving_limit_with_basic_noise(lora_parameters, path_loss_parameters);
-    let numerator = tx_power_dbm - rl - pl0;
-    let denom = 10.0 * path_loss_parameters.path_loss_exponent;
-    if numerator <= 0.0 {
-        return 0.0; // or 1.0, depending on desired semantics
-    }
-    10.0_f32.powf(numerator / denom)
+    model.effective_distance(tx_power_dbm - rl, path_loss_parameters)
 }
 
 pub(crate) fn calculate_receiving_limit_with_basic_noise(
@@ -168,6 +161,54 @@ pub(crate) fn calculate_snr_limit(lora_parameters: &LoraParameters) -> f32 {
     return snr_limit;
 }
 
+/// Complementary error function (Abramowitz & Stegun 7.1.26, |error| < 1.5e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let result = poly * (-z * z).exp();
+    if x >= 0.0 { result } else { 2.0 - result }
+}
+
+/// Probability that a packet is decoded at the given SINR (packet error rate model).
+///
+/// # Formula
+///
+/// ```text
+/// SER ≈ Q(√(2^(SF+1) × γ) − √(1.386 × SF + 1.154))    (Elshabrawy & Robert, 2018)
+/// BER ≈ SER / 2
+/// P_success = (1 − BER)^(8 × payload_size)
+/// ```
+///
+/// Where `γ` is the linear SINR and `Q(x) = erfc(x / √2) / 2`. The curve drops from
+/// ~1 to ~0 within a few dB around the SF's SNR limit instead of the hard cliff of
+/// the threshold model. Forward error correction (coding rate) is not credited.
+///
+/// # Parameters
+///
+/// - `sinr`: Signal to interference plus noise ratio in dB
+/// - `payload_size`: Packet payload size in bytes
+/// - `lora_parameters`: LoRa parameters (spreading factor)
+///
+/// # Returns
+///
+/// Packet success probability in 0.0..=1.0
+pub(crate) fn calculate_packet_success_probability(
+    sinr: f32,
+    payload_size: usize,
+    lora_parameters: &LoraParameters,
+) -> f32 {
+    let sf = lora_parameters.spreading_factor as f64;
+    let snr_linear = 10f64.powf(sinr as f64 / 10.0);
+    let q_argument = (2f64.powf(sf + 1.0) * snr_linear).sqrt() - (1.386 * sf + 1.154).sqrt();
+    let symbol_error_rate = 0.5 * erfc(q_argument / std::f64::consts::SQRT_2);
+    let bit_error_rate = (symbol_error_rate / 2.0).clamp(0.0, 0.5);
+    let bits = (payload_size.max(1) * 8) as f64;
+    (1.0 - bit_error_rate).powf(bits) as f32
+}
+
 pub(crate) fn calculate_air_time(lora_parameters: &LoraParameters, payload_size: usize) -> f32 {
     // LoRa maximum payload is typically 255 bytes (SX126x/SX127x standard)
     const MAX_LORA_PAYLOAD: usize = 255;
@@ -227,17 +268,22 @@ pub(crate) fn calculate_air_time(lora_parameters: &LoraParameters, payload_size:
 }
 
 /// Calculate the instantaneous RSSI (in dBm) at a given distance using the
-/// existing log-distance path loss model with log-normal shadowing.
+/// scene's propagation model with log-normal shadowing.
 ///
 /// Formula: RSSI(dBm) = P_tx(dBm) - PL(dB)
 /// - P_tx(dBm): transmit power at the antenna port
-/// - PL(dB): path loss sampled via `calculate_path_loss(distance, params)`
+/// - PL(dB): path loss sampled via `calculate_path_loss(distance, model, params)`
 ///
 /// Note: Because `calculate_path_loss` includes a random shadowing term, the
-/// returned RSSI is a stochastic sample. For a deterministic estimate, consider
-/// using the reference path loss without shadowing.
-pub(crate) fn calculate_rssi(distance: f32, tx_power_dbm: f32, params: &PathLossParameters) -> f32 {
-    let path_loss_db = calculate_path_loss(distance, params);
+/// returned RSSI is a stochastic sample. For a deterministic estimate, use the
+/// model's `mean_path_loss`.
+pub(crate) fn calculate_rssi(
+    distance: f32,
+    tx_power_dbm: f32,
+    model: &dyn PropagationModel,
+    params: &PathLossParameters,
+) -> f32 {
+    let path_loss_db = calculate_path_loss(distance, model, params);
     tx_power_dbm - path_loss_db
 }
 
@@ -309,7 +355,6 @@ pub(crate) fn mw_to_dbm(mw: f32) -> f32 {
     10.0 * mw.log10()
 }
 
-#[allow(dead_code)]
 pub(crate) fn get_preamble_time(lora_parameters: &LoraParameters) -> Duration {
     // Calculate the preamble time based on LoRa parameters
     let symbol_time =
@@ -329,6 +374,7 @@ pub(crate) fn get_cad_time(lora_parameters: &LoraParameters) -> Duration {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::simulation::propagation::LogDistance;
 
     fn params_sf_bw(sf: u8, bw: u32) -> LoraParameters {
         LoraParameters {
@@ -389,13 +435,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn packet_success_probability_softens_the_snr_cliff() {
+        let lp = params_sf_bw(7, 125_000);
+        let limit = calculate_snr_limit(&lp);
+        let well_below = calculate_packet_success_probability(limit - 4.0, 100, &lp);
+        let at_limit = calculate_packet_success_probability(limit, 100, &lp);
+        let well_above = calculate_packet_success_probability(limit + 4.0, 100, &lp);
+        assert!(well_below < 0.01);
+        assert!(at_limit > 0.05 && at_limit < 0.99);
+        assert!(well_above > 0.99);
+
+        // Longer packets fail more often; higher SF tolerates lower SINR
+        assert!(calculate_packet_success_probability(limit, 255, &lp) < at_limit);
+        let sf12 = params_sf_bw(12, 125_000);
+        assert!(calculate_packet_success_probability(limit - 4.0, 100, &sf12) > 0.99);
+    }
+
     #[test]
     fn effective_distance_monotonic_with_tx_power() {
         let lp = params_sf_bw(7, 125_000);
         let pl = default_pathloss();
-        let d_low = calculate_effective_distance(0.0, &lp, &pl);
-        let d_mid = calculate_effective_distance(10.0, &lp, &pl);
-        let d_high = calculate_effective_distance(20.0, &lp, &pl);
+        let d_low = calculate_effective_distance(0.0, &lp, &LogDistance, &pl);
+        let d_mid = calculate_effective_distance(10.0, &lp, &LogDistance, &pl);
+        let d_high = calculate_effective_distance(20.0, &lp, &LogDistance, &pl);
         assert!(d_low < d_mid && d_mid < d_high);
     }
 
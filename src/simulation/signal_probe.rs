@@ -0,0 +1,93 @@
+//! # Signal Probe
+//!
+//! Link budget of a transmitter at an arbitrary map location, for the map's probe
+//! tool: the distance, the received signal strength without shadowing, the SNR
+//! against the noise floor and its margin above the decoding limit, and whether an
+//! obstacle blocks the line of sight. It uses the same propagation model, range
+//! and line-of-sight checks as a real transmission (see `physics`).
+
+use super::geometry::{distance_from_d2, distance2, is_intersect};
+use super::signal_calculations::calculate_snr_limit;
+use super::types::{Node, Point, Scene};
+
+/// Link budget of a transmitter at a probed location.
+#[derive(Debug, Clone)]
+pub struct SignalProbe {
+    pub transmitter_id: u32,
+    /// Probed location in world units.
+    pub position: Point,
+    /// Distance from the transmitter in meters.
+    pub distance: f64,
+    /// Received signal strength without shadowing (dBm).
+    pub rssi: f32,
+    /// Signal to noise ratio against the noise floor (dB).
+    pub snr: f32,
+    /// SNR above the decoding limit of the LoRa parameters (dB, negative = below).
+    pub snr_margin: f32,
+    /// Beyond the transmitter's effective distance (receivers there are not reached).
+    pub out_of_range: bool,
+    /// An obstacle blocks the line of sight.
+    pub los_blocked: bool,
+}
+
+impl SignalProbe {
+    /// Whether a receiver at the location would decode the transmitter without
+    /// interference.
+    pub fn decodable(&self) -> bool {
+        !self.los_blocked && !self.out_of_range && self.snr_margin >= 0.0
+    }
+}
+
+/// Compute the link budget of `transmitter` at `position`.
+///
+/// # Parameters
+///
+/// * `transmitter` - Transmitting node (position, TX power, effective distance)
+/// * `position` - Probed location in world units
+/// * `scene` - Scene configuration (propagation model, obstacles, LoRa parameters)
+pub fn probe(transmitter: &Node, position: &Point, scene: &Scene) -> SignalProbe {
+    let distance = distance_from_d2(distance2(&transmitter.position, position, scene));
+    let path_loss = scene
+        .propagation_model
+        .model()
+        .mean_path_loss(distance as f32, &scene.path_loss_parameters);
+    let (rssi, snr, snr_margin) = link_budget(
+        transmitter.radio_strength,
+        path_loss,
+        scene.path_loss_parameters.noise_floor,
+        calculate_snr_limit(&scene.lora_parameters),
+    );
+    SignalProbe {
+        transmitter_id: transmitter.node_id,
+        position: position.clone(),
+        distance,
+        rssi,
+        snr,
+        snr_margin,
+        out_of_range: distance >= transmitter.cached_effective_distance as f64,
+        los_blocked: is_intersect(&transmitter.position, position, &scene.obstacles),
+    }
+}
+
+/// RSSI, SNR and SNR margin (dBm, dB, dB) of a link.
+fn link_budget(tx_power: f32, path_loss: f32, noise_floor: f32, snr_limit: f32) -> (f32, f32, f32) {
+    let rssi = tx_power - path_loss;
+    let snr = rssi - noise_floor;
+    (rssi, snr, snr - snr_limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_budget_margin_is_relative_to_the_snr_limit() {
+        let (rssi, snr, margin) = link_budget(14.0, 130.0, -120.0, -20.0);
+        assert_eq!(rssi, -116.0);
+        assert_eq!(snr, 4.0);
+        assert_eq!(margin, 24.0);
+
+        let (_, _, margin) = link_budget(0.0, 145.0, -120.0, -7.5);
+        assert_eq!(margin, -17.5);
+    }
+}
@@ -0,0 +1,219 @@
+//! # Speed Target
+//!
+//! Auto speed mode that solves for the speed needed to finish a virtual duration
+//! within a wall-clock budget ("2 virtual hours within 10 minutes"). At every
+//! airtime event the required speed is the remaining virtual time divided by the
+//! remaining wall-clock time. The controller ramps toward it while the loop keeps up
+//! and backs off like the plain auto speed when the processing delay exceeds the
+//! limit, so the delay constraint always wins over the target. A target whose
+//! required speed is above the speed range, or above the speed at which the loop
+//! falls behind, is reported as infeasible together with the projected wall-clock
+//! time at the current speed.
+
+use embassy_time::{Duration, Instant};
+
+/// Processing delay above which the loop is behind (same limit as the auto speed).
+const DELAY_LIMIT: Duration = Duration::from_millis(8);
+
+/// Largest speed increase per airtime event while ramping toward the required speed.
+const RAMP_STEP_PERCENT: u32 = 5;
+
+/// Virtual duration to finish within a wall-clock budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedTarget {
+    pub virtual_secs: u64,
+    pub wall_clock_secs: u64,
+}
+
+/// Progress of a speed target, for the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeedTargetStatus {
+    pub target: SpeedTarget,
+    pub virtual_elapsed_secs: f64,
+    pub wall_elapsed_secs: f64,
+    /// Speed needed to finish on time (None once the wall-clock budget is used up).
+    pub required_percent: Option<u32>,
+    pub current_percent: u32,
+    /// Wall-clock seconds from the start until the virtual duration is covered at
+    /// the current speed.
+    pub projected_wall_secs: f64,
+    /// The target can still be met.
+    pub feasible: bool,
+    /// The virtual duration has been covered.
+    pub finished: bool,
+}
+
+/// Speed controller of an active speed target.
+#[derive(Debug, Clone)]
+pub struct SpeedTargetController {
+    target: SpeedTarget,
+    virtual_start: Instant,
+    wall_start: std::time::Instant,
+    min_percent: u32,
+    max_percent: u32,
+    /// The last airtime event was processed later than `DELAY_LIMIT`.
+    behind: bool,
+    /// The target was already reported as infeasible.
+    infeasible_reported: bool,
+}
+
+impl SpeedTargetController {
+    /// Start a target now, keeping the speed within `min_percent..=max_percent`.
+    pub fn new(target: SpeedTarget, min_percent: u32, max_percent: u32) -> Self {
+        Self {
+            target,
+            virtual_start: Instant::now(),
+            wall_start: std::time::Instant::now(),
+            min_percent,
+            max_percent,
+            behind: false,
+            infeasible_reported: false,
+        }
+    }
+
+    /// Elapsed virtual and wall-clock seconds since the start.
+    fn elapsed(&self) -> (f64, f64) {
+        (
+            Instant::now()
+                .saturating_duration_since(self.virtual_start)
+                .as_millis() as f64
+                / 1000.0,
+            self.wall_start.elapsed().as_secs_f64(),
+        )
+    }
+
+    /// Speed needed to cover the remaining virtual time in the remaining budget.
+    fn required_percent(&self, virtual_elapsed: f64, wall_elapsed: f64) -> Option<u32> {
+        let remaining_wall = self.target.wall_clock_secs as f64 - wall_elapsed;
+        if remaining_wall <= 0.0 {
+            return None;
+        }
+        let remaining_virtual = (self.target.virtual_secs as f64 - virtual_elapsed).max(0.0);
+        Some((remaining_virtual / remaining_wall * 100.0).ceil() as u32)
+    }
+
+    /// Next speed after an airtime event processed `time_delay` late.
+    fn next_percent(&mut self, time_delay: Duration, current: u32, required: Option<u32>) -> u32 {
+        self.behind = time_delay > DELAY_LIMIT;
+        let wanted = required
+            .unwrap_or(self.max_percent)
+            .clamp(self.min_percent, self.max_percent);
+        if self.behind {
+            current.saturating_sub(1).max(self.min_percent)
+        } else if wanted > current {
+            (current + RAMP_STEP_PERCENT).min(wanted)
+        } else {
+            wanted
+        }
+    }
+
+    /// Speed to use after an airtime event processed `time_delay` late.
+    ///
+    /// # Parameters
+    ///
+    /// * `time_delay` - Processing delay of the event
+    /// * `current` - Current speed in percent
+    ///
+    /// # Returns
+    ///
+    /// The new speed in percent (may equal `current`).
+    pub fn adjust(&mut self, time_delay: Duration, current: u32) -> u32 {
+        let (virtual_elapsed, wall_elapsed) = self.elapsed();
+        let required = self.required_percent(virtual_elapsed, wall_elapsed);
+        self.next_percent(time_delay, current, required)
+    }
+
+    fn status_at(
+        &self,
+        virtual_elapsed: f64,
+        wall_elapsed: f64,
+        current: u32,
+    ) -> SpeedTargetStatus {
+        let required = self.required_percent(virtual_elapsed, wall_elapsed);
+        let remaining_virtual = (self.target.virtual_secs as f64 - virtual_elapsed).max(0.0);
+        let finished = remaining_virtual <= 0.0;
+        let feasible = finished
+            || required.is_some_and(|required| {
+                required <= self.max_percent && !(self.behind && required > current)
+            });
+        SpeedTargetStatus {
+            target: self.target,
+            virtual_elapsed_secs: virtual_elapsed,
+            wall_elapsed_secs: wall_elapsed,
+            required_percent: required,
+            current_percent: current,
+            projected_wall_secs: wall_elapsed + remaining_virtual / (current.max(1) as f64 / 100.0),
+            feasible,
+            finished,
+        }
+    }
+
+    /// Status of the target at the `current` speed; an infeasible target is logged
+    /// once.
+    pub fn status(&mut self, current: u32) -> SpeedTargetStatus {
+        let (virtual_elapsed, wall_elapsed) = self.elapsed();
+        let status = self.status_at(virtual_elapsed, wall_elapsed, current);
+        if !status.feasible && !self.infeasible_reported {
+            self.infeasible_reported = true;
+            log::warn!(
+                "Speed target infeasible: {} virtual s within {} s needs {} but the simulation sustains {}% (projected finish after {:.0} s)",
+                self.target.virtual_secs,
+                self.target.wall_clock_secs,
+                status.required_percent.map_or(
+                    "more than the remaining budget".to_string(),
+                    |required| format!("{}%", required)
+                ),
+                current,
+                status.projected_wall_secs
+            );
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_controller_ramps_to_the_required_speed_and_yields_to_delay() {
+        // 2 virtual hours within 10 minutes needs 1200%
+        let target = SpeedTarget {
+            virtual_secs: 7200,
+            wall_clock_secs: 600,
+        };
+        let mut controller = SpeedTargetController::new(target, 20, 1000);
+        assert_eq!(controller.required_percent(0.0, 0.0), Some(1200));
+        assert_eq!(controller.required_percent(3600.0, 300.0), Some(1200));
+        assert_eq!(controller.required_percent(100.0, 600.0), None);
+
+        let no_delay = Duration::from_millis(1);
+        assert_eq!(controller.next_percent(no_delay, 100, Some(300)), 105);
+        assert_eq!(controller.next_percent(no_delay, 298, Some(300)), 300);
+        assert_eq!(controller.next_percent(no_delay, 500, Some(300)), 300);
+        // The delay constraint wins over the target
+        assert_eq!(
+            controller.next_percent(Duration::from_millis(20), 300, Some(400)),
+            299
+        );
+
+        // Behind and below the required speed: infeasible
+        let status = controller.status_at(0.0, 0.0, 299);
+        assert!(!status.feasible);
+        assert_eq!(status.projected_wall_secs, 7200.0 / 2.99);
+
+        controller.behind = false;
+        assert!(controller.status_at(0.0, 0.0, 900).feasible);
+        // Above the speed range
+        let slow = SpeedTargetController::new(
+            SpeedTarget {
+                virtual_secs: 7200,
+                wall_clock_secs: 60,
+            },
+            20,
+            1000,
+        );
+        assert!(!slow.status_at(0.0, 0.0, 1000).feasible);
+        assert!(slow.status_at(7200.0, 90.0, 1000).finished);
+    }
+}
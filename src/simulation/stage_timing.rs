@@ -0,0 +1,215 @@
+//! # Network Loop Stage Timing
+//!
+//! Profiling hooks of the network task: every stage of the event loop is timed in
+//! real (wall-clock) time, not virtual time, so the counters show what limits the
+//! achievable simulation speed of a scene. Once per second the accumulated times
+//! are turned into a `StageTimingReport` for the loop profile window and the
+//! counters restart. Time not spent in any stage is idle time waiting for the next
+//! event; a loop that is busy most of the time cannot follow a higher speed.
+//!
+//! Stages that deliver to node queues (radio transfers, scene drivers) include the
+//! time spent waiting for room in a full queue.
+
+use std::time::{Duration, Instant};
+
+/// A stage of the network loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopStage {
+    /// Finding the next airtime event (`calculate_next_event_time`).
+    EventCalculation,
+    /// Starting transmissions and distributing packets to their targets.
+    RadioTransfers,
+    /// Answering channel activity detection requests.
+    CadProcessing,
+    /// SINR and collision evaluation of ended packets, delivery to the receivers.
+    ReceptionProcessing,
+    /// Speed profile, node joins, auto measurement, stress test, sweeps and backhaul.
+    SceneDrivers,
+    /// Distribution of the captured radio library logs to the nodes.
+    LogDistribution,
+    /// Statistics, memory and health snapshots sent to the UI.
+    UiNotifications,
+}
+
+impl LoopStage {
+    /// All stages, in loop order.
+    pub const ALL: [LoopStage; 7] = [
+        LoopStage::EventCalculation,
+        LoopStage::RadioTransfers,
+        LoopStage::CadProcessing,
+        LoopStage::ReceptionProcessing,
+        LoopStage::SceneDrivers,
+        LoopStage::LogDistribution,
+        LoopStage::UiNotifications,
+    ];
+
+    /// Display name of the stage.
+    pub fn label(self) -> &'static str {
+        match self {
+            LoopStage::EventCalculation => "Event calculation",
+            LoopStage::RadioTransfers => "Radio transfers",
+            LoopStage::CadProcessing => "CAD processing",
+            LoopStage::ReceptionProcessing => "Reception processing",
+            LoopStage::SceneDrivers => "Scene drivers",
+            LoopStage::LogDistribution => "Log distribution",
+            LoopStage::UiNotifications => "UI notifications",
+        }
+    }
+}
+
+/// Accumulated time of one stage.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct StageCounter {
+    calls: u64,
+    total: Duration,
+    max: Duration,
+}
+
+/// Timing of one stage over a reporting window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageTiming {
+    pub stage: LoopStage,
+    pub calls: u64,
+    /// Total time spent in the stage (microseconds).
+    pub total_us: u64,
+    /// Longest single run of the stage (microseconds).
+    pub max_us: u64,
+    /// Share of the window spent in the stage (percent).
+    pub share_percent: f64,
+}
+
+/// Stage timings of the last reporting window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageTimingReport {
+    /// Real duration of the window (milliseconds).
+    pub window_ms: u64,
+    /// Timings in loop order.
+    pub stages: Vec<StageTiming>,
+    /// Loop iterations in the window.
+    pub iterations: u64,
+}
+
+impl StageTimingReport {
+    /// Share of the window spent in any stage (percent).
+    pub fn busy_percent(&self) -> f64 {
+        self.stages.iter().map(|stage| stage.share_percent).sum()
+    }
+
+    /// The stage that took the most time, if any stage ran.
+    pub fn dominant_stage(&self) -> Option<&StageTiming> {
+        self.stages
+            .iter()
+            .filter(|stage| stage.total_us > 0)
+            .max_by_key(|stage| stage.total_us)
+    }
+}
+
+/// Stage time counters of the network loop.
+#[derive(Debug, Clone)]
+pub struct StageTimer {
+    counters: [StageCounter; LoopStage::ALL.len()],
+    iterations: u64,
+    window_start: Instant,
+}
+
+impl Default for StageTimer {
+    fn default() -> Self {
+        Self {
+            counters: Default::default(),
+            iterations: 0,
+            window_start: Instant::now(),
+        }
+    }
+}
+
+impl StageTimer {
+    /// Count a loop iteration.
+    pub fn iteration(&mut self) {
+        self.iterations += 1;
+    }
+
+    /// Add a run of `stage` that started at `started`.
+    pub fn record(&mut self, stage: LoopStage, started: Instant) {
+        self.add(stage, started.elapsed());
+    }
+
+    /// Run `f` as a run of `stage`.
+    pub fn measure<T>(&mut self, stage: LoopStage, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        self.record(stage, started);
+        result
+    }
+
+    fn add(&mut self, stage: LoopStage, elapsed: Duration) {
+        let counter = &mut self.counters[stage as usize];
+        counter.calls += 1;
+        counter.total += elapsed;
+        counter.max = counter.max.max(elapsed);
+    }
+
+    /// Report of the window since the last report, and restart the counters.
+    pub fn take_report(&mut self) -> StageTimingReport {
+        let now = Instant::now();
+        let report = self.report(now.saturating_duration_since(self.window_start));
+        *self = Self {
+            window_start: now,
+            ..Self::default()
+        };
+        report
+    }
+
+    fn report(&self, window: Duration) -> StageTimingReport {
+        let window_us = window.as_micros().max(1) as f64;
+        StageTimingReport {
+            window_ms: window.as_millis() as u64,
+            stages: LoopStage::ALL
+                .iter()
+                .map(|stage| {
+                    let counter = self.counters[*stage as usize];
+                    StageTiming {
+                        stage: *stage,
+                        calls: counter.calls,
+                        total_us: counter.total.as_micros() as u64,
+                        max_us: counter.max.as_micros() as u64,
+                        share_percent: counter.total.as_micros() as f64 / window_us * 100.0,
+                    }
+                })
+                .collect(),
+            iterations: self.iterations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_shares_and_dominant_stage() {
+        let mut timer = StageTimer::default();
+        timer.iteration();
+        timer.iteration();
+        timer.add(LoopStage::ReceptionProcessing, Duration::from_millis(300));
+        timer.add(LoopStage::ReceptionProcessing, Duration::from_millis(100));
+        timer.add(LoopStage::CadProcessing, Duration::from_millis(50));
+
+        let report = timer.report(Duration::from_secs(1));
+        assert_eq!(report.iterations, 2);
+        assert_eq!(report.stages.len(), LoopStage::ALL.len());
+        let reception = &report.stages[LoopStage::ReceptionProcessing as usize];
+        assert_eq!(reception.stage, LoopStage::ReceptionProcessing);
+        assert_eq!(reception.calls, 2);
+        assert_eq!(reception.max_us, 300_000);
+        assert!((reception.share_percent - 40.0).abs() < 1e-9);
+        assert!((report.busy_percent() - 45.0).abs() < 1e-9);
+        assert_eq!(
+            report.dominant_stage().map(|stage| stage.stage),
+            Some(LoopStage::ReceptionProcessing)
+        );
+
+        let report = timer.take_report();
+        assert_eq!(report.stages[LoopStage::CadProcessing as usize].calls, 1);
+        assert_eq!(timer.report(Duration::from_secs(1)).dominant_stage(), None);
+    }
+}
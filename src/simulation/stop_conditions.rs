@@ -0,0 +1,324 @@
+//! # Stop Conditions
+//!
+//! Ends unattended runs without babysitting. A run stops at the first condition
+//! met: a virtual time limit, completion of all measurements, or a number of
+//! measurement blocks distributed to every node. On stop the network task publishes
+//! the final statistics, writes a JSON run summary and freezes the virtual clock;
+//! the GUI shows the reason with a "Resume" button, while the headless `run`
+//! command exits.
+//!
+//! Conditions come from the scene's `stop_conditions`; conditions given on the
+//! command line of `run` take precedence over the scene's.
+
+use anyhow::{Context, Result, bail};
+use embassy_time::Instant;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::event_bus::PacketCounters;
+use super::types::{MeasurementTracker, Node, StopConditions};
+use crate::common::node_statistics::NodeStatistics;
+use crate::common::provenance;
+use crate::common::scene::read_scene_value;
+use crate::executor::Executor;
+use crate::ui::UIRefreshState;
+use crate::{UICommandQueue, UIRefreshQueue, time_driver};
+
+/// Conditions given on the command line, applied over the scene's conditions.
+static CONDITIONS_OVERRIDE: Mutex<Option<StopConditions>> = Mutex::new(None);
+
+impl StopConditions {
+    /// Whether any condition can end the run.
+    pub fn has_condition(&self) -> bool {
+        self.time_limit.is_some() || self.measurement_complete || self.blocks_distributed.is_some()
+    }
+}
+
+/// Conditions in effect for a scene: the scene's conditions with the command-line
+/// conditions applied over them.
+///
+/// # Returns
+///
+/// The conditions, or `None` if no condition is set.
+pub fn effective_conditions(scene: Option<&StopConditions>) -> Option<StopConditions> {
+    let forced = CONDITIONS_OVERRIDE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let mut conditions = scene.cloned().unwrap_or_default();
+    if let Some(forced) = forced {
+        conditions.time_limit = forced.time_limit.or(conditions.time_limit);
+        conditions.measurement_complete |= forced.measurement_complete;
+        conditions.blocks_distributed = forced.blocks_distributed.or(conditions.blocks_distributed);
+        conditions.summary = forced.summary.or(conditions.summary);
+    }
+    conditions.has_condition().then_some(conditions)
+}
+
+/// Progress of a run as seen by the stop conditions.
+pub struct RunProgress<'a> {
+    /// Virtual seconds since the scene was loaded.
+    pub elapsed_secs: u64,
+    /// Measurements started so far.
+    pub measurements: &'a HashMap<u32, MeasurementTracker>,
+    /// Whether scene-defined measurements are still to be started.
+    pub measurements_pending: bool,
+}
+
+/// Radio totals of a run for its summary.
+pub struct RunTotals<'a> {
+    /// Sent, received and collided packets of the whole network.
+    pub counters: PacketCounters,
+    /// Per-node radio statistics (node ID -> statistics).
+    pub statistics: &'a HashMap<u32, NodeStatistics>,
+}
+
+/// Check the stop conditions.
+///
+/// # Returns
+///
+/// The reason to stop, or `None` if the run continues.
+pub fn stop_reason(conditions: &StopConditions, progress: &RunProgress) -> Option<String> {
+    if let Some(limit) = conditions.time_limit {
+        if progress.elapsed_secs >= limit {
+            return Some(format!("time limit of {}s reached", limit));
+        }
+    }
+    let completed = progress
+        .measurements
+        .values()
+        .filter(|tracker| tracker.completed)
+        .count();
+    if let Some(blocks) = conditions.blocks_distributed {
+        if completed >= blocks as usize {
+            return Some(format!("{} blocks distributed to every node", completed));
+        }
+    }
+    if conditions.measurement_complete
+        && !progress.measurements_pending
+        && !progress.measurements.is_empty()
+        && completed == progress.measurements.len()
+    {
+        return Some(format!("all {} measurements complete", completed));
+    }
+    None
+}
+
+/// Path of the run summary: the configured file relative to the scene file, or
+/// `<scene>_summary.json` next to it.
+pub fn summary_path(scene_path: &str, conditions: &StopConditions) -> PathBuf {
+    let scene_path = Path::new(scene_path);
+    let directory = scene_path.parent().unwrap_or(Path::new(""));
+    match &conditions.summary {
+        Some(summary) => directory.join(summary),
+        None => {
+            let stem = scene_path
+                .file_stem()
+                .map_or("scene".into(), |stem| stem.to_string_lossy());
+            directory.join(format!("{}_summary.json", stem))
+        }
+    }
+}
+
+/// Build the run summary: stop reason, packet totals, measurements, per-node
+/// statistics (ordered by ID) and the provenance of the run.
+///
+/// # Parameters
+///
+/// * `reason` - Why the run stopped
+/// * `seed` - Random seed of the run
+/// * `scene_start` - Virtual time when the scene was loaded
+/// * `tx_maximum_random_delay` - Scene random TX delay (ms), unless overridden per node
+/// * `measurements` - Measurements started during the run
+/// * `nodes_map` - Map of all nodes
+/// * `totals` - Network packet counters and per-node statistics
+pub fn run_summary(
+    reason: &str,
+    seed: u64,
+    scene_start: Instant,
+    tx_maximum_random_delay: u16,
+    measurements: &HashMap<u32, MeasurementTracker>,
+    nodes_map: &HashMap<u32, Node>,
+    totals: RunTotals,
+) -> Value {
+    let counters = totals.counters;
+    let mut measurement_ids: Vec<&u32> = measurements.keys().collect();
+    measurement_ids.sort_unstable();
+    let measurements: Vec<Value> = measurement_ids
+        .into_iter()
+        .map(|id| {
+            let tracker = &measurements[id];
+            json!({
+                "id": id,
+                "origin_node_id": tracker.origin_node_id,
+                "kind": tracker.kind.label(),
+                "start_time_s": tracker.start_time.saturating_duration_since(scene_start).as_secs(),
+                "reached_nodes": tracker.reached_count,
+                "completed": tracker.completed,
+            })
+        })
+        .collect();
+
+    let mut node_ids: Vec<&u32> = nodes_map.keys().collect();
+    node_ids.sort_unstable();
+    let nodes: Vec<Value> = node_ids
+        .into_iter()
+        .map(|id| {
+            let node = &nodes_map[id];
+            let statistics = totals.statistics.get(id).copied().unwrap_or_default();
+            json!({
+                "node_id": id,
+                "tx_packets": statistics.tx_packets,
+                "rx_packets": statistics.rx_packets,
+                "collisions": statistics.collisions,
+                "missed_while_transmitting": statistics.missed_while_transmitting,
+                "missed_while_sleeping": statistics.missed_while_sleeping,
+                "truncated_packets": statistics.truncated_packets,
+                "corrupted_packets": statistics.corrupted_packets,
+                "neighbors": statistics.neighbors,
+                "tx_maximum_random_delay": node.tx_maximum_random_delay.unwrap_or(tx_maximum_random_delay),
+                "relays": statistics.relays,
+                "average_relay_delay_ms": statistics.average_relay_delay(),
+                "max_relay_delay_ms": statistics.relay_delay_max_ms,
+            })
+        })
+        .collect();
+
+    json!({
+        "reason": reason,
+        "seed": seed,
+        "virtual_time_s": Instant::now().saturating_duration_since(scene_start).as_secs(),
+        "sent_packets": counters.sent,
+        "received_packets": counters.received,
+        "collisions": counters.collisions,
+        "tx_maximum_random_delay": tx_maximum_random_delay,
+        "measurements": measurements,
+        "nodes": nodes,
+        "provenance": provenance::current(),
+    })
+}
+
+/// Run a scene without the GUI until a stop condition is met.
+///
+/// # Parameters
+///
+/// * `scene_path` - Scene file to run
+/// * `overrides` - Conditions from the command line (applied over the scene's)
+/// * `speed_percent` - Simulation speed of the run
+/// * `event_driven` - Jump the virtual clock from event to event instead (see
+///   `time_driver::set_event_driven`)
+///
+/// # Returns
+///
+/// The stop reason, or an error if the scene has no stop condition or cannot be run.
+pub fn run_headless(
+    scene_path: &str,
+    overrides: StopConditions,
+    speed_percent: u32,
+    event_driven: bool,
+) -> Result<String> {
+    let scene = read_scene_value(scene_path)
+        .map_err(|err| anyhow::anyhow!("cannot read {}: {}", scene_path, err))?;
+    let scene_conditions: Option<StopConditions> = scene
+        .get("stop_conditions")
+        .map(|value| serde_json::from_value(value.clone()))
+        .transpose()
+        .context("invalid stop_conditions")?;
+    *CONDITIONS_OVERRIDE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some(overrides);
+    if effective_conditions(scene_conditions.as_ref()).is_none() {
+        bail!("no stop condition in the scene or on the command line, the run would never end");
+    }
+
+    time_driver::set_simulation_speed_percent(speed_percent);
+    time_driver::set_event_driven(event_driven);
+
+    // INTENTIONAL LEAK: 'static channels and executor, as in the GUI application
+    let ui_refresh_channel: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+    let ui_command_channel: &'static UICommandQueue = Box::leak(Box::new(UICommandQueue::new()));
+    let ui_refresh_tx = ui_refresh_channel.sender();
+    let ui_command_rx = ui_command_channel.receiver();
+    let scene_path = scene_path.to_string();
+    std::thread::Builder::new()
+        .stack_size(192 * 1024 * 1024)
+        .name("embassy-executor".to_string())
+        .spawn(move || {
+            let executor: &'static mut Executor = Box::leak(Box::new(Executor::new()));
+            executor.run(|spawner| {
+                let _ = spawner.spawn(super::network_task(
+                    spawner,
+                    ui_refresh_tx,
+                    ui_command_rx,
+                    Some(scene_path),
+                ));
+            });
+        })
+        .context("cannot start the executor")?;
+
+    let ui_refresh_rx = ui_refresh_channel.receiver();
+    loop {
+        while let Ok(update) = ui_refresh_rx.try_receive() {
+            match update {
+                UIRefreshState::SimulationStopped(reason) => return Ok(reason),
+                UIRefreshState::Alert(message) => bail!("{}", message),
+                _ => {}
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::MeasurementKind;
+
+    #[test]
+    fn test_stop_reason_follows_conditions() {
+        let mut measurements = HashMap::new();
+        let mut tracker = MeasurementTracker::new(1, MeasurementKind::Block);
+        assert!(!tracker.record_reached(1, 2));
+        assert!(tracker.record_reached(2, 2));
+        // The reached set is released on completion, the count stays
+        assert!(tracker.reached_nodes.is_empty());
+        assert_eq!(tracker.reached_count, 2);
+        measurements.insert(10, tracker);
+        measurements.insert(11, MeasurementTracker::new(2, MeasurementKind::Transaction));
+        let progress = RunProgress {
+            elapsed_secs: 100,
+            measurements: &measurements,
+            measurements_pending: false,
+        };
+
+        let conditions = StopConditions {
+            measurement_complete: true,
+            ..Default::default()
+        };
+        assert!(conditions.has_condition());
+        assert_eq!(stop_reason(&conditions, &progress), None);
+
+        let conditions = StopConditions {
+            blocks_distributed: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            stop_reason(&conditions, &progress).as_deref(),
+            Some("1 blocks distributed to every node")
+        );
+
+        let conditions = StopConditions {
+            time_limit: Some(100),
+            ..Default::default()
+        };
+        assert!(stop_reason(&conditions, &progress).is_some());
+        assert!(!StopConditions::default().has_condition());
+
+        assert_eq!(
+            summary_path("scenes/city.json", &StopConditions::default()),
+            Path::new("scenes/city_summary.json")
+        );
+    }
+}
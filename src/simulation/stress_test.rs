@@ -0,0 +1,281 @@
+//! Throughput stress test with saturation detection.
+//!
+//! The stress test offers a gradually increasing AddTransaction load to the network
+//! and evaluates every load step by its collision rate and average delivery latency.
+//! The first step exceeding either threshold marks saturation; the load of the last
+//! step below the thresholds is reported as the achievable capacity.
+//!
+//! The runner only keeps the bookkeeping; `network_task` drives it from its tick
+//! branch, sends the generated transactions and feeds back deliveries.
+
+use embassy_time::{Duration, Instant};
+use std::collections::HashMap;
+
+use super::types::StressTestConfig;
+
+/// Result of one completed load step.
+#[derive(Debug, Clone)]
+pub struct StressTestStep {
+    /// Offered load of the step (transactions per minute).
+    pub rate: f64,
+    /// Collision rate observed during the step (percent).
+    pub collision_rate: f64,
+    /// Average delivery latency of the step (None if nothing was delivered).
+    pub average_latency: Option<Duration>,
+    /// Number of transactions sent during the step.
+    pub transactions_sent: u32,
+    /// Whether the step exceeded the collision rate or latency threshold.
+    pub saturated: bool,
+}
+
+/// Bookkeeping of a running stress test.
+pub struct StressTestRunner {
+    config: StressTestConfig,
+    /// Current offered load (transactions per minute).
+    rate: f64,
+    /// Start of the current load step.
+    step_start: Instant,
+    /// Time when the next transaction is due.
+    next_send: Instant,
+    /// Received packet counter at the start of the step.
+    step_received_baseline: u64,
+    /// Collision counter at the start of the step.
+    step_collision_baseline: u64,
+    /// Transactions sent in the current step.
+    step_transactions_sent: u32,
+    /// Send time of every transaction of the current step, keyed by transaction ID.
+    pending: HashMap<u32, Instant>,
+    latency_total: Duration,
+    latency_samples: u32,
+    next_transaction_id: u32,
+    /// Highest load that stayed below both thresholds.
+    last_sustainable_rate: Option<f64>,
+    finished: bool,
+}
+
+impl StressTestRunner {
+    /// Create a runner whose first load step starts at `now`.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - Stress test configuration from the scene
+    /// * `now` - Start time of the first step
+    /// * `total_received_packets` - Current global received packet counter
+    /// * `total_collision` - Current global collision counter
+    pub fn new(
+        config: StressTestConfig,
+        now: Instant,
+        total_received_packets: u64,
+        total_collision: u64,
+    ) -> Self {
+        let rate = config.initial_rate;
+        Self {
+            config,
+            rate,
+            step_start: now,
+            next_send: now,
+            step_received_baseline: total_received_packets,
+            step_collision_baseline: total_collision,
+            step_transactions_sent: 0,
+            pending: HashMap::new(),
+            latency_total: Duration::from_ticks(0),
+            latency_samples: 0,
+            next_transaction_id: 1,
+            last_sustainable_rate: None,
+            finished: false,
+        }
+    }
+
+    /// Whether saturation was detected and the test has stopped.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Current offered load (transactions per minute).
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Highest load that stayed below both thresholds (None if the first step saturated).
+    pub fn last_sustainable_rate(&self) -> Option<f64> {
+        self.last_sustainable_rate
+    }
+
+    /// Payload size of generated transactions in bytes.
+    pub fn payload_size(&self) -> usize {
+        self.config.payload_size
+    }
+
+    /// Return the ID of a new transaction if one is due at `now`.
+    ///
+    /// Transactions are spread evenly over the step according to the current rate.
+    /// Call it until it returns None: after a long tick several transactions are due.
+    pub fn due_transaction(&mut self, now: Instant) -> Option<u32> {
+        if self.finished || self.rate <= 0.0 || now < self.next_send {
+            return None;
+        }
+        let interval_ms = (60_000.0 / self.rate).max(1.0) as u64;
+        self.next_send = self.next_send + Duration::from_millis(interval_ms);
+
+        let transaction_id = self.next_transaction_id;
+        self.next_transaction_id = self.next_transaction_id.wrapping_add(1).max(1);
+        self.pending.insert(transaction_id, now);
+        self.step_transactions_sent += 1;
+        Some(transaction_id)
+    }
+
+    /// Record that a node received the transaction `transaction_id` at `now`.
+    ///
+    /// Every reception counts as a latency sample; transactions from earlier steps are ignored.
+    pub fn record_delivery(&mut self, transaction_id: u32, now: Instant) {
+        if let Some(sent_at) = self.pending.get(&transaction_id) {
+            self.latency_total += now.duration_since(*sent_at);
+            self.latency_samples += 1;
+        }
+    }
+
+    /// Close the current step if its duration elapsed and start the next one.
+    ///
+    /// # Parameters
+    ///
+    /// * `now` - Current virtual time
+    /// * `total_received_packets` - Current global received packet counter
+    /// * `total_collision` - Current global collision counter
+    ///
+    /// # Returns
+    ///
+    /// The evaluation of the completed step, or None if the step is still running.
+    pub fn evaluate_step(
+        &mut self,
+        now: Instant,
+        total_received_packets: u64,
+        total_collision: u64,
+    ) -> Option<StressTestStep> {
+        if self.finished || now < self.step_start + Duration::from_secs(self.config.step_duration) {
+            return None;
+        }
+
+        let received = total_received_packets.saturating_sub(self.step_received_baseline);
+        let collisions = total_collision.saturating_sub(self.step_collision_baseline);
+        let collision_rate = if received + collisions > 0 {
+            (collisions as f64 / (received + collisions) as f64) * 100.0
+        } else {
+            0.0
+        };
+        let average_latency = if self.latency_samples > 0 {
+            Some(self.latency_total / self.latency_samples)
+        } else {
+            None
+        };
+        let latency_exceeded = average_latency.map_or(false, |latency| {
+            latency.as_millis() as f64 > self.config.max_latency * 1000.0
+        });
+        let saturated = collision_rate > self.config.max_collision_rate || latency_exceeded;
+
+        let step = StressTestStep {
+            rate: self.rate,
+            collision_rate,
+            average_latency,
+            transactions_sent: self.step_transactions_sent,
+            saturated,
+        };
+
+        if saturated {
+            self.finished = true;
+        } else {
+            self.last_sustainable_rate = Some(self.rate);
+            self.rate += self.config.rate_step;
+        }
+
+        self.step_start = now;
+        self.next_send = now;
+        self.step_received_baseline = total_received_packets;
+        self.step_collision_baseline = total_collision;
+        self.step_transactions_sent = 0;
+        self.pending.clear();
+        self.latency_total = Duration::from_ticks(0);
+        self.latency_samples = 0;
+
+        Some(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> StressTestConfig {
+        StressTestConfig {
+            start_time: 0,
+            initial_rate: 60.0,
+            rate_step: 30.0,
+            step_duration: 10,
+            max_collision_rate: 10.0,
+            max_latency: 5.0,
+            payload_size: 100,
+        }
+    }
+
+    #[test]
+    fn stress_test_spreads_transactions_by_rate() {
+        let start = Instant::from_secs(100);
+        let mut runner = StressTestRunner::new(config(), start, 0, 0);
+        // 60 tx/min = one per second
+        assert_eq!(runner.due_transaction(start), Some(1));
+        assert_eq!(
+            runner.due_transaction(start + Duration::from_millis(500)),
+            None
+        );
+        assert_eq!(
+            runner.due_transaction(start + Duration::from_secs(1)),
+            Some(2)
+        );
+
+        // A long tick catches up on every transaction that became due meanwhile
+        let late = start + Duration::from_millis(4500);
+        let backlog: Vec<u32> = std::iter::from_fn(|| runner.due_transaction(late)).collect();
+        assert_eq!(backlog, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn stress_test_increases_rate_until_saturation() {
+        let start = Instant::from_secs(100);
+        let mut runner = StressTestRunner::new(config(), start, 0, 0);
+
+        assert!(
+            runner
+                .evaluate_step(start + Duration::from_secs(5), 100, 0)
+                .is_none()
+        );
+
+        // 5% collisions: sustainable, load grows by rate_step
+        let step = runner
+            .evaluate_step(start + Duration::from_secs(10), 95, 5)
+            .unwrap();
+        assert!(!step.saturated);
+        assert_eq!(runner.rate(), 90.0);
+
+        // 20% collisions in the second step: saturated
+        let step = runner
+            .evaluate_step(start + Duration::from_secs(20), 95 + 80, 5 + 20)
+            .unwrap();
+        assert!(step.saturated);
+        assert!(runner.is_finished());
+        assert_eq!(runner.last_sustainable_rate(), Some(60.0));
+    }
+
+    #[test]
+    fn stress_test_saturates_on_latency() {
+        let start = Instant::from_secs(100);
+        let mut runner = StressTestRunner::new(config(), start, 0, 0);
+        let id = runner.due_transaction(start).unwrap();
+        runner.record_delivery(id, start + Duration::from_secs(8));
+
+        let step = runner
+            .evaluate_step(start + Duration::from_secs(10), 10, 0)
+            .unwrap();
+        assert_eq!(step.average_latency, Some(Duration::from_secs(8)));
+        assert!(step.saturated);
+        assert_eq!(runner.last_sustainable_rate(), None);
+    }
+}
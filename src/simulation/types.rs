@@ -8,11 +8,21 @@
 
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_time::{Duration, Instant};
-use moonblokz_radio_lib::{RadioMessage, RadioPacket};
-use serde::Deserialize;
-use std::collections::VecDeque;
+use moonblokz_radio_lib::{MessageType, RadioMessage, RadioPacket};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use super::signal_calculations::{LoraParameters, PathLossParameters};
+use super::duty_cycle::SleepSchedule;
+use super::history_spill::HistorySpill;
+use super::message_decoder::RecordedPacket;
+use super::propagation::PropagationModelConfig;
+use super::signal_calculations::{LoraParameters, PathLossParameters, ReceptionModel};
+use super::watchdog::NodeHealth;
+use crate::common::annotations::Annotation;
+use crate::common::connection_matrix::ConnectionMatrix;
+use crate::common::units::WorldTransform;
+use crate::ui::MeasurementKind;
 
 /// Minimum RSSI dominance (dB) for the capture effect to destroy a later
 /// overlapping packet. If the in-progress packet is stronger by this margin,
@@ -64,12 +74,18 @@ pub type NodesOutputQueueSender = embassy_sync::channel::Sender<
 >;
 
 /// Root structure representing the entire scene
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct Scene {
     /// Path loss model parameters for the physical layer.
     pub path_loss_parameters: PathLossParameters,
     /// LoRa-like parameters for airtime/SNR limit and symbol timings.
     pub lora_parameters: LoraParameters,
+    /// How SINR decides packet decoding (hard SNR threshold by default).
+    #[serde(default)]
+    pub reception_model: ReceptionModel,
+    /// Path loss model (log-distance by default).
+    #[serde(default)]
+    pub propagation_model: PropagationModelConfig,
     /// Module-level configuration for the simulated radio manager.
     pub radio_module_config: RadioModuleConfig,
     /// All nodes present in the scene (positions and radios).
@@ -82,21 +98,602 @@ pub struct Scene {
     /// Bottom-right corner of the world coordinate system.
     #[serde(rename = "world_bottom_right")]
     pub world_bottom_right: Point,
-    /// Width of the world in meters.
-    pub width: f64,
-    /// Height of the world in meters.
-    pub height: f64,
-    /// Pre-calculated X-axis scaling factor (meters per world unit).
-    /// Calculated as: width / (world_bottom_right.x - world_top_left.x)
+    /// Width of the world in meters (alternative to `meters_per_unit`).
+    #[serde(default)]
+    pub width: Option<f64>,
+    /// Height of the world in meters (alternative to `meters_per_unit`).
+    #[serde(default)]
+    pub height: Option<f64>,
+    /// Uniform scale in meters per world unit (alternative to `width`/`height`).
+    #[serde(default)]
+    pub meters_per_unit: Option<f64>,
+    /// World unit ↔ meter transform, built from the fields above when the scene is loaded.
     #[serde(skip)]
-    pub scale_x: f64,
-    /// Pre-calculated Y-axis scaling factor (meters per world unit).
-    /// Calculated as: height / (world_bottom_right.y - world_top_left.y)
-    #[serde(skip)]
-    pub scale_y: f64,
+    pub transform: WorldTransform,
     /// Optional path to background image for visualization.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub background_image: Option<String>,
+    /// Optional measurement started automatically without UI interaction.
+    #[serde(default)]
+    pub auto_measurement: Option<AutoMeasurement>,
+    /// Optional throughput stress test (gradually increasing AddTransaction load).
+    #[serde(default)]
+    pub stress_test: Option<StressTestConfig>,
+    /// Optional experiment repeating the measurement from origins chosen by strategy.
+    #[serde(default)]
+    pub origin_sampling: Option<OriginSamplingConfig>,
+    /// Optional Rhai script with custom experiment logic.
+    #[serde(default)]
+    pub script: Option<ScriptConfig>,
+    /// Optional simulation speed schedule applied at virtual-time boundaries.
+    #[serde(default)]
+    pub speed_profile: Vec<SpeedProfileStep>,
+    /// Backhaul behavior of gateway nodes (see `NodeRole::Gateway`).
+    #[serde(default)]
+    pub backhaul: BackhaulConfig,
+    /// Seed of the simulation's random draws (random if missing, see `rng`).
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// File the radio events are written to, one line per event (see
+    /// `event_bus::CaptureWriter`; relative to the scene file).
+    #[serde(default)]
+    pub event_capture: Option<String>,
+    /// Optional node backend running the embedded firmware as subprocesses
+    /// instead of in-process node tasks.
+    #[serde(default)]
+    pub node_process: Option<NodeProcessConfig>,
+    /// Optional periodic extraction and initial import of the nodes' link matrices.
+    #[serde(default)]
+    pub link_matrix: Option<LinkMatrixConfig>,
+    /// Optional conditions ending an unattended run (see `stop_conditions`).
+    #[serde(default)]
+    pub stop_conditions: Option<StopConditions>,
+    /// Optional periodic metrics snapshots written during the run (see `checkpoints`).
+    #[serde(default)]
+    pub checkpoints: Option<CheckpointConfig>,
+    /// Per-node history sizes, retention and memory warning limit.
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// When the nodes start (all at scene load if missing, see `bootstrap`).
+    #[serde(default)]
+    pub node_start: Option<NodeStartConfig>,
+    /// Truncation and corruption of delivered packets (none by default).
+    #[serde(default)]
+    pub packet_faults: PacketFaultConfig,
+    /// Propagation and receiver processing delay of packet arrivals (none by default).
+    #[serde(default)]
+    pub arrival_delay: ArrivalDelayConfig,
+    /// Regional limits, filled by a `lora_preset` or set by hand (none by default).
+    #[serde(default)]
+    pub regulatory_limits: RegulatoryLimits,
+    /// What happens to deliveries when a node task's input queue is full (see `watchdog`).
+    #[serde(default)]
+    pub node_queue: NodeQueueConfig,
+    /// Receiver sleep schedule of every node (always listening when omitted; see `duty_cycle`).
+    #[serde(default)]
+    pub duty_cycle: Option<DutyCycleConfig>,
+    /// Labels, arrows and regions drawn on the map (see `common::annotations`).
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// Optional background uplinks of a co-channel LoRaWAN network (see `external_traffic`).
+    #[serde(default)]
+    pub external_traffic: Option<ExternalTrafficConfig>,
+}
+
+/// Backpressure of the node tasks' input queues (`NODE_INPUT_QUEUE_SIZE` messages).
+/// At high speeds a node task may fall behind the radio traffic addressed to it;
+/// the policy decides whether the experiment loses messages, time or memory.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct NodeQueueConfig {
+    /// Behavior when a radio packet or CAD response finds the queue full.
+    pub full_policy: QueueFullPolicy,
+    /// `grow`: messages buffered per node beyond the queue before further ones are
+    /// dropped.
+    pub max_overflow: usize,
+    /// `block`: longest wall-clock wait (ms) for room in the queue before the message
+    /// is dropped, so a stalled node task cannot freeze the simulation.
+    pub max_block_ms: u64,
+}
+
+impl Default for NodeQueueConfig {
+    fn default() -> Self {
+        Self {
+            full_policy: QueueFullPolicy::Drop,
+            max_overflow: 1000,
+            max_block_ms: 2000,
+        }
+    }
+}
+
+/// Behavior when a node task's input queue is full.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueFullPolicy {
+    /// Drop the message and count it. Radio packets first wait up to
+    /// `watchdog::DELIVERY_TIMEOUT` (virtual time) for room.
+    #[default]
+    Drop,
+    /// Freeze the virtual clock until the node task makes room, so the node sees
+    /// every message without the experiment's timing running ahead.
+    Block,
+    /// Buffer the message in a per-node overflow delivered in order as the queue
+    /// drains (up to `max_overflow`).
+    Grow,
+}
+
+/// Probabilities of radio device faults on successfully received packets (see
+/// `packet_faults`). The two probabilities are exclusive, their sum is at most 1.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct PacketFaultConfig {
+    /// Probability that a delivered packet is cut short.
+    pub truncation_probability: f64,
+    /// Probability that a delivered packet has flipped payload bits.
+    pub corruption_probability: f64,
+    /// Maximum number of flipped bits of a corrupted packet.
+    pub max_bit_errors: u32,
+}
+
+impl Default for PacketFaultConfig {
+    fn default() -> Self {
+        Self {
+            truncation_probability: 0.0,
+            corruption_probability: 0.0,
+            max_bit_errors: 8,
+        }
+    }
+}
+
+impl PacketFaultConfig {
+    /// Whether any fault can occur.
+    pub fn is_enabled(&self) -> bool {
+        self.truncation_probability > 0.0 || self.corruption_probability > 0.0
+    }
+}
+
+/// Regulatory limits of the region the scene models (see `common::lora_presets`);
+/// the scene validation and runtime TX power edits enforce them.
+#[derive(Deserialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct RegulatoryLimits {
+    /// Region of the preset the limits come from (for messages).
+    pub region: Option<String>,
+    /// Maximum transmit power of a node (dBm).
+    pub max_tx_power_dbm: Option<f32>,
+    /// Spreading factors allowed in the region.
+    pub min_spreading_factor: Option<u8>,
+    pub max_spreading_factor: Option<u8>,
+}
+
+impl RegulatoryLimits {
+    /// Error message if `tx_power` (dBm) exceeds the maximum TX power.
+    pub fn check_tx_power(&self, tx_power: f32) -> Result<(), String> {
+        match self.max_tx_power_dbm {
+            Some(max) if tx_power > max => Err(format!(
+                "{} dBm exceeds the {} maximum TX power of {} dBm",
+                tx_power,
+                self.region.as_deref().unwrap_or("regulatory"),
+                max
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Offsets of a packet's arrival at each receiver (see `arrival_delay`). All zero by
+/// default: every receiver hears a transmission at the instant it starts.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ArrivalDelayConfig {
+    /// Add the propagation delay (distance over the speed of light).
+    pub propagation: bool,
+    /// Receiver processing / turnaround latency added to every arrival (µs).
+    pub processing_delay_us: u64,
+    /// Maximum random latency added on top, drawn per packet and receiver (µs).
+    pub processing_jitter_us: u64,
+}
+
+/// Periodic receiver sleep of energy-constrained nodes: the receiver listens for `on_ms`,
+/// then sleeps for `off_ms`, repeatedly. Packets overlapping a sleep period are lost.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+pub struct DutyCycleConfig {
+    /// Listening time per cycle (ms).
+    pub on_ms: u64,
+    /// Sleeping time per cycle (ms).
+    pub off_ms: u64,
+    /// Position within the cycle at the simulation start (ms). Drawn at random per
+    /// node when omitted, so the nodes do not sleep in lockstep.
+    #[serde(default)]
+    pub offset_ms: Option<u64>,
+}
+
+/// Scene-wide start mode of the nodes; a node's own `start_time` takes precedence.
+#[derive(Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum NodeStartConfig {
+    /// All nodes start when the scene is loaded.
+    Simultaneous,
+    /// Nodes start one after the other in scene order.
+    Staggered {
+        /// Virtual seconds between two consecutive node starts.
+        interval: f64,
+        /// Maximum random delay (seconds) added to each start.
+        #[serde(default)]
+        jitter: f64,
+    },
+}
+
+/// Per-node inspector history limits. Every node keeps its own radio packet, full
+/// message and log line history, so with thousands of nodes these limits decide the
+/// memory footprint of long runs.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Radio packets kept per node (Radio Stream tab).
+    pub radio_packets: usize,
+    /// Full messages kept per node (Message Stream tab).
+    pub full_messages: usize,
+    /// Log lines kept per node (Log Stream tab).
+    pub log_lines: usize,
+    /// Entries older than this many virtual seconds are dropped (None = kept until
+    /// pushed out by newer entries).
+    pub retention: Option<u64>,
+    /// What nodes keep of their history (a node's `history_policy` overrides it).
+    pub policy: HistoryPolicy,
+    /// Estimated simulation memory (MiB) above which a warning is raised.
+    pub memory_warning_mb: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            radio_packets: NODE_MESSAGES_CAPACITY,
+            full_messages: NODE_FULL_MESSAGES_CAPACITY,
+            log_lines: NODE_LOG_LINES_CAPACITY,
+            retention: None,
+            policy: HistoryPolicy::KeepLast,
+            memory_warning_mb: 1024,
+        }
+    }
+}
+
+/// What a node keeps of its radio packet, full message and log line history.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum HistoryPolicy {
+    /// The newest entries, up to the history sizes (and the `retention`).
+    #[default]
+    KeepLast,
+    /// The entries of the last `minutes` virtual minutes, however many there are.
+    KeepMinutes { minutes: u64 },
+    /// Everything: entries leaving the in-memory history (sizes, `retention`) are
+    /// appended to a file per node (see `history_spill`).
+    SpillToDisk,
+}
+
+/// Conditions ending a run. The first condition met stops the simulation: the
+/// statistics are finalized and a run summary is written, then the headless runner
+/// exits and the GUI pauses.
+#[derive(Deserialize, JsonSchema, Clone, Debug, Default)]
+pub struct StopConditions {
+    /// Virtual seconds after scene load.
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+    /// Stop when measurements were started and all of them reached every node (with
+    /// `auto_measurement`, after its last run).
+    #[serde(default)]
+    pub measurement_complete: bool,
+    /// Stop when this many measurement blocks reached every node.
+    #[serde(default)]
+    pub blocks_distributed: Option<u32>,
+    /// Run summary file, relative to the scene file (default `<scene>_summary.json`).
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+/// Co-channel traffic of an unrelated LoRaWAN network (see `external_traffic`).
+#[derive(Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ExternalTrafficConfig {
+    /// Number of end devices, placed at random positions within the world.
+    pub devices: u32,
+    /// Mean uplinks per device and hour (exponentially distributed intervals).
+    pub uplinks_per_hour: f64,
+    /// TX power of the devices (dBm).
+    pub tx_power: f32,
+    /// Uplink payload size (bytes); the airtime uses the scene's LoRa parameters.
+    pub payload_size: usize,
+    /// Fraction of the time a device may transmit (0.01 = the EU868 1% limit).
+    pub duty_cycle: f64,
+}
+
+impl Default for ExternalTrafficConfig {
+    fn default() -> Self {
+        Self {
+            devices: 100,
+            uplinks_per_hour: 12.0,
+            tx_power: 14.0,
+            payload_size: 20,
+            duty_cycle: 0.01,
+        }
+    }
+}
+
+/// Periodic metrics snapshots of long runs (see `checkpoints`).
+#[derive(Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct CheckpointConfig {
+    /// Virtual minutes between two checkpoints (at least 1).
+    pub interval_minutes: u64,
+    /// Newest checkpoints kept on disk (0 = all).
+    pub retention: usize,
+    /// Checkpoint directory relative to the scene file (default `<scene>_checkpoints`).
+    pub directory: Option<String>,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            interval_minutes: 10,
+            retention: 12,
+            directory: None,
+        }
+    }
+}
+
+/// Periodic extraction and initial import of the nodes' link quality (connection) matrices.
+///
+/// Sweeps ask one node after the other to dump its matrix; the inspector collects
+/// them and exports all matrices into a link matrix file, which a later scene can
+/// import as the nodes' initial state (see `common::connection_matrix`).
+#[derive(Deserialize, JsonSchema, Clone, Debug, Default)]
+pub struct LinkMatrixConfig {
+    /// Virtual seconds between the starts of two sweeps (None = no sweeps).
+    #[serde(default)]
+    pub snapshot_interval: Option<u64>,
+    /// Link matrix file imported into the nodes at start, relative to the scene file.
+    #[serde(default)]
+    pub initial: Option<String>,
+}
+
+/// Role of a node in the deployment.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeRole {
+    /// Mesh-only node.
+    #[default]
+    Node,
+    /// Gateway: messages it receives are delivered to the backhaul.
+    Gateway,
+}
+
+/// Scene-defined backhaul shared by all gateway nodes.
+///
+/// Every payload message (AddBlock, AddTransaction) received by a gateway counts as
+/// delivered to the backhaul. With `reinject` enabled, the first delivery of a message
+/// is forwarded over the backhaul and re-transmitted by every other gateway after
+/// `reinject_latency` milliseconds (unless it already received the message by radio).
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+pub struct BackhaulConfig {
+    /// Whether delivered messages are re-injected at the other gateways.
+    #[serde(default)]
+    pub reinject: bool,
+    /// Backhaul latency (ms) before a delivered message is re-injected.
+    #[serde(default = "default_backhaul_reinject_latency")]
+    pub reinject_latency: u64,
+}
+
+impl Default for BackhaulConfig {
+    fn default() -> Self {
+        Self {
+            reinject: false,
+            reinject_latency: default_backhaul_reinject_latency(),
+        }
+    }
+}
+
+fn default_backhaul_reinject_latency() -> u64 {
+    500
+}
+
+/// Scene-defined node backend launching the real firmware binary per node.
+///
+/// Each node runs `binary` (a host-target build of the embedded firmware) as a
+/// subprocess whose radio device is a socket shim connected to the network task
+/// (see `node_process`). The firmware runs on wall-clock time, so the simulation
+/// speed should stay at 100%.
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+pub struct NodeProcessConfig {
+    /// Path of the firmware binary (relative paths are resolved from the scene file).
+    pub binary: String,
+    /// Extra command-line arguments passed to every node process.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds to wait for a node process to connect to its radio socket.
+    #[serde(default = "default_node_process_connect_timeout")]
+    pub connect_timeout: u64,
+}
+
+fn default_node_process_connect_timeout() -> u64 {
+    10
+}
+
+/// One step of the scene-defined simulation speed schedule.
+///
+/// When virtual time reaches `start_time` seconds after scene load, the simulation
+/// speed is set to `speed_percent` (e.g. fast warm-up, real-time measurement phase).
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+pub struct SpeedProfileStep {
+    /// Virtual time (seconds after scene load) when this speed takes effect.
+    pub start_time: u64,
+    /// Simulation speed in percent (1-1000).
+    pub speed_percent: u32,
+}
+
+/// Scene-defined measurement that the network task starts on its own.
+///
+/// The first measurement starts `start_time` virtual seconds after the scene is
+/// loaded; further runs (if `repeat_count` > 1) follow every `repeat_interval` seconds.
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+pub struct AutoMeasurement {
+    /// Node that originates the measurement AddBlock.
+    pub origin_node_id: u32,
+    /// Virtual time (seconds after scene load) of the first measurement.
+    pub start_time: u64,
+    /// Number of measurements to run in total.
+    #[serde(default = "default_auto_measurement_repeat_count")]
+    pub repeat_count: u32,
+    /// Virtual seconds between the starts of consecutive measurements.
+    #[serde(default = "default_auto_measurement_repeat_interval")]
+    pub repeat_interval: u64,
+}
+
+fn default_auto_measurement_repeat_count() -> u32 {
+    1
+}
+
+fn default_auto_measurement_repeat_interval() -> u64 {
+    600
+}
+
+/// Scene-defined throughput stress test.
+///
+/// Starting at `start_time`, random nodes originate AddTransaction messages at
+/// `initial_rate` transactions per minute. After each `step_duration` the offered
+/// load grows by `rate_step` until the collision rate or the average delivery
+/// latency of the step exceeds its threshold (saturation).
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+pub struct StressTestConfig {
+    /// Virtual time (seconds after scene load) when the stress test starts.
+    #[serde(default)]
+    pub start_time: u64,
+    /// Offered load of the first step (transactions per minute).
+    #[serde(default = "default_stress_test_initial_rate")]
+    pub initial_rate: f64,
+    /// Load increase between consecutive steps (transactions per minute).
+    #[serde(default = "default_stress_test_rate_step")]
+    pub rate_step: f64,
+    /// Length of one load step in virtual seconds.
+    #[serde(default = "default_stress_test_step_duration")]
+    pub step_duration: u64,
+    /// Collision rate (percent) above which the network is considered saturated.
+    #[serde(default = "default_stress_test_max_collision_rate")]
+    pub max_collision_rate: f64,
+    /// Average delivery latency (seconds) above which the network is considered saturated.
+    #[serde(default = "default_stress_test_max_latency")]
+    pub max_latency: f64,
+    /// Size of the generated transaction payload in bytes.
+    #[serde(default = "default_stress_test_payload_size")]
+    pub payload_size: usize,
+}
+
+fn default_stress_test_initial_rate() -> f64 {
+    1.0
+}
+
+fn default_stress_test_rate_step() -> f64 {
+    1.0
+}
+
+fn default_stress_test_step_duration() -> u64 {
+    300
+}
+
+fn default_stress_test_max_collision_rate() -> f64 {
+    10.0
+}
+
+fn default_stress_test_max_latency() -> f64 {
+    30.0
+}
+
+fn default_stress_test_payload_size() -> usize {
+    100
+}
+
+/// How the origins of an origin sampling experiment are chosen.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum OriginStrategy {
+    /// Uniformly random nodes.
+    Random,
+    /// Nodes farthest from the centroid of all nodes.
+    Periphery,
+    /// Nodes with the most neighbors in radio range.
+    MaxDegree,
+}
+
+impl OriginStrategy {
+    /// Name used in the scene file and the report.
+    pub fn label(self) -> &'static str {
+        match self {
+            OriginStrategy::Random => "random",
+            OriginStrategy::Periphery => "periphery",
+            OriginStrategy::MaxDegree => "max_degree",
+        }
+    }
+}
+
+/// Scene-defined origin sampling experiment (see `origin_sampling`).
+///
+/// The AddBlock distribution measurement is repeated from `origins_per_strategy`
+/// origins of every strategy, one measurement every `interval` virtual seconds
+/// starting at `start_time`; the distribution times are aggregated per strategy.
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+pub struct OriginSamplingConfig {
+    /// Origin classes to sample.
+    #[serde(default = "default_origin_sampling_strategies")]
+    pub strategies: Vec<OriginStrategy>,
+    /// Origins (measurements) per strategy.
+    #[serde(default = "default_origin_sampling_origins_per_strategy")]
+    pub origins_per_strategy: u32,
+    /// Virtual time (seconds after scene load) of the first measurement.
+    #[serde(default)]
+    pub start_time: u64,
+    /// Virtual seconds between measurement starts; a measurement that has not reached
+    /// every node by the next start counts as incomplete.
+    #[serde(default = "default_origin_sampling_interval")]
+    pub interval: u64,
+}
+
+fn default_origin_sampling_strategies() -> Vec<OriginStrategy> {
+    vec![
+        OriginStrategy::Random,
+        OriginStrategy::Periphery,
+        OriginStrategy::MaxDegree,
+    ]
+}
+
+fn default_origin_sampling_origins_per_strategy() -> u32 {
+    5
+}
+
+fn default_origin_sampling_interval() -> u64 {
+    600
+}
+
+/// Scene-defined experiment script (see `scripting`).
+///
+/// The Rhai script is run once at scene load; the hook functions it defines
+/// (`on_start`, `on_tick`, `on_transmit`, `on_receive`, `on_collision`,
+/// `on_reached`) are called on simulation events and may start measurements,
+/// switch node radios and record custom metrics.
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+pub struct ScriptConfig {
+    /// Path of the Rhai script (relative paths are resolved from the scene file).
+    pub path: String,
+    /// Virtual milliseconds between two `on_tick` calls.
+    #[serde(default = "default_script_tick_interval")]
+    pub tick_interval: u64,
+    /// CSV file the recorded metrics are appended to (relative paths are resolved
+    /// from the scene file; `<script name>_metrics.csv` next to the script if missing).
+    #[serde(default)]
+    pub metrics_file: Option<String>,
+}
+
+fn default_script_tick_interval() -> u64 {
+    1000
 }
 
 #[derive(Debug, Clone)]
@@ -117,8 +714,15 @@ pub struct NodeMessage {
     pub link_quality: u8,
     /// Whether this event represents a detected collision.
     pub collision: bool,
+    /// Whether the packet was missed because this node was transmitting (half-duplex).
+    pub missed_while_transmitting: bool,
+    /// Whether the packet was missed because this node's receiver was asleep (duty cycling).
+    pub missed_while_sleeping: bool,
     /// Sequence number for AddBlock and RequestBlockPart messages.
     pub sequence: Option<u32>,
+    /// The packet, decoded by the inspector's row detail (None in analyzer modes,
+    /// where packet payloads are not logged).
+    pub packet: Option<RecordedPacket>,
 }
 
 #[derive(Clone)]
@@ -145,6 +749,78 @@ pub struct CadItem {
     pub end_time: Instant,
 }
 
+/// Backend-side progress of a single measurement (keyed by measurement ID in `network_task`).
+///
+/// Several measurements may run concurrently from different origin nodes; each one
+/// tracks its own set of reached nodes independently. The set is released when the
+/// measurement completes, only the count is kept for the stop conditions and the
+/// run summary.
+#[derive(Debug, Clone)]
+pub struct MeasurementTracker {
+    /// Node that originated the measurement.
+    pub origin_node_id: u32,
+    /// Message type whose propagation is measured.
+    pub kind: MeasurementKind,
+    /// Virtual time when the measurement was started.
+    pub start_time: Instant,
+    /// Nodes that received the measurement block (including the origin); emptied on completion.
+    pub reached_nodes: HashSet<u32>,
+    /// Number of nodes that received the measurement block (including the origin).
+    pub reached_count: usize,
+    /// Whether every node has been reached (completion already reported).
+    pub completed: bool,
+}
+
+/// Measurement kind of a message type: AddBlock sequences and AddTransaction anchor
+/// sequences are tracked, other messages are not.
+pub fn measurement_kind(message_type: u8) -> Option<MeasurementKind> {
+    if message_type == MessageType::AddBlock as u8 {
+        Some(MeasurementKind::Block)
+    } else if message_type == MessageType::AddTransaction as u8 {
+        Some(MeasurementKind::Transaction)
+    } else {
+        None
+    }
+}
+
+impl MeasurementTracker {
+    /// Create a tracker for a measurement of `kind` starting now at `origin_node_id`.
+    pub fn new(origin_node_id: u32, kind: MeasurementKind) -> Self {
+        let mut reached_nodes = HashSet::new();
+        reached_nodes.insert(origin_node_id);
+        Self {
+            origin_node_id,
+            kind,
+            start_time: Instant::now(),
+            reached_nodes,
+            reached_count: 1,
+            completed: false,
+        }
+    }
+
+    /// Record that `node_id` received the measurement.
+    ///
+    /// # Parameters
+    ///
+    /// * `node_id` - Node that was reached
+    /// * `node_count` - Total number of nodes in the scene
+    ///
+    /// # Returns
+    ///
+    /// Whether this node completed the measurement (every node reached).
+    pub fn record_reached(&mut self, node_id: u32, node_count: usize) -> bool {
+        if self.completed || !self.reached_nodes.insert(node_id) {
+            return false;
+        }
+        self.reached_count = self.reached_nodes.len();
+        if self.reached_count >= node_count {
+            self.completed = true;
+            self.reached_nodes = HashSet::new();
+        }
+        self.completed
+    }
+}
+
 /// Node structure with position and radio strength
 ///
 /// Runtime-only fields are skipped from serde and initialized at scene load:
@@ -155,11 +831,36 @@ pub struct CadItem {
 ///   by `network_task`.
 /// - `cached_effective_distance`: per-node cache to avoid recomputing the same
 ///   range value for each candidate receiver.
-#[derive(Deserialize, Clone)]
+/// - `disabled`: radio switched off at runtime (topology editing); the node task keeps
+///   running, but its packets are dropped and it receives nothing.
+/// - `history`: the scene's history limits with the node's policy, applied by the
+///   `push_*` methods.
+/// - `history_spill`: spill file of the `spill_to_disk` history policy.
+/// - `health`: input queue health (dropped deliveries, full queue) for the watchdog.
+/// - `history_heap_bytes`: heap memory of the histories (log text),
+///   kept up to date on push and pop for the memory estimate.
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct Node {
     pub node_id: u32,
     pub position: Point,
     pub radio_strength: f32,
+    #[serde(default)]
+    pub role: NodeRole,
+    /// Per-node override of `radio_module_config.tx_maximum_random_delay` (ms).
+    #[serde(default)]
+    pub tx_maximum_random_delay: Option<u16>,
+    /// Virtual seconds after scene load when the node joins (overrides `node_start`).
+    #[serde(default)]
+    pub start_time: Option<u64>,
+    /// Per-node override of `history.policy`.
+    #[serde(default)]
+    pub history_policy: Option<HistoryPolicy>,
+    /// Per-node override of the scene's `duty_cycle`.
+    #[serde(default)]
+    pub duty_cycle: Option<DutyCycleConfig>,
+    /// Free-form labels, e.g. from a CSV import (see `common::node_import`).
+    #[serde(default)]
+    pub tags: Vec<String>,
     #[serde(skip)]
     pub node_input_queue_sender: Option<NodeInputQueueSender>,
     #[serde(skip)]
@@ -174,17 +875,33 @@ pub struct Node {
     pub cad_waiting_list: Vec<CadItem>,
     #[serde(skip)]
     pub cached_effective_distance: f32,
+    #[serde(skip)]
+    pub disabled: bool,
+    /// Measurement blocks received but not relayed yet (measurement ID → receive time).
+    #[serde(skip)]
+    pub pending_relays: HashMap<u32, Instant>,
+    #[serde(skip)]
+    pub history: HistoryConfig,
+    #[serde(skip)]
+    pub history_spill: Option<HistorySpill>,
+    #[serde(skip)]
+    pub health: NodeHealth,
+    /// Receiver sleep schedule (None: always listening).
+    #[serde(skip)]
+    pub sleep_schedule: Option<SleepSchedule>,
+    #[serde(skip)]
+    history_heap_bytes: usize,
 }
 
 /// Simple 2D point
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
 /// Rectangle position with two corners
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct RectPos {
     #[serde(rename = "top-left-position")]
     pub top_left: Point,
@@ -193,7 +910,7 @@ pub struct RectPos {
 }
 
 /// Circle position defined by its center
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct CirclePos {
     #[serde(rename = "center_position")]
     pub center: Point,
@@ -206,22 +923,55 @@ pub struct CirclePos {
 /// Circle radii are specified in meters in the scene files.
 /// Rectangles are defined by two corners; circles by center and radius.
 /// Intersection checks are conservative with degenerate segment handling.
-#[derive(Debug, Deserialize, Clone)]
+/// A `one_way` obstacle blocks only the paths crossing it from its back side.
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(tag = "type")]
 pub enum Obstacle {
     #[serde(rename = "rectangle")]
     Rectangle {
         #[serde(flatten)]
         position: RectPos,
+        #[serde(default)]
+        one_way: Option<OneWay>,
     },
     #[serde(rename = "circle")]
     Circle {
         #[serde(flatten)]
         position: CirclePos,
+        #[serde(default)]
+        one_way: Option<OneWay>,
     },
 }
 
-#[derive(Deserialize, Clone)]
+impl Obstacle {
+    /// Direction-dependent blocking of the obstacle (None = blocks both directions).
+    pub fn one_way(&self) -> Option<&OneWay> {
+        match self {
+            Obstacle::Rectangle { one_way, .. } | Obstacle::Circle { one_way, .. } => {
+                one_way.as_ref()
+            }
+        }
+    }
+}
+
+/// Direction-dependent blocking, e.g. a hillside that shadows the paths coming from
+/// behind it while a transmitter on its slope still reaches the valley beyond.
+#[derive(Debug, Deserialize, JsonSchema, Clone, PartialEq)]
+pub struct OneWay {
+    /// Direction (world units vector) pointing out of the obstacle's open front side.
+    /// A path is blocked only when it crosses the obstacle travelling this way,
+    /// i.e. from a transmitter on the back side to a receiver on the front side.
+    pub front: Point,
+}
+
+impl OneWay {
+    /// Whether a path from `from` to `to` travels from the back to the front side.
+    pub fn blocks(&self, from: &Point, to: &Point) -> bool {
+        (to.x - from.x) * self.front.x + (to.y - from.y) * self.front.y > 0.0
+    }
+}
+
+#[derive(Deserialize, JsonSchema, Serialize, Clone)]
 pub struct RadioModuleConfig {
     /// Inter-packet gap inside a single message (ms) used by the TX scheduler.
     pub delay_between_tx_packets: u16,
@@ -243,6 +993,26 @@ pub struct RadioModuleConfig {
     pub tx_maximum_random_delay: u16,
 }
 
+/// Link quality score the radio manager keeps for a neighbor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct NeighborScore {
+    pub node_id: u32,
+    pub score: u8,
+}
+
+/// Snapshot of a node's radio manager internals, answered to `NodeInputMessage::QueryState`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct NodeProtocolState {
+    /// Neighbor scores, best first.
+    pub neighbors: Vec<NeighborScore>,
+    /// Packets waiting in the TX queue.
+    pub tx_queue_len: usize,
+    /// Time until the next echo request (ms), if one is scheduled.
+    pub next_echo_request_ms: Option<u64>,
+    /// Time until the running echo result gathering ends (ms), if one is running.
+    pub echo_gathering_end_ms: Option<u64>,
+}
+
 pub enum NodeOutputPayload {
     /// Node emitted a packet over the simulated radio.
     RadioTransfer(RadioPacket),
@@ -250,8 +1020,12 @@ pub enum NodeOutputPayload {
     MessageReceived(RadioMessage),
     /// Node requests a channel activity detection operation window.
     RequestCAD,
-    /// A node reached during a measurement (by sequence/measurement ID).
-    NodeReachedInMeasurement(u32), // measurement ID
+    /// A node reached during a measurement: first reception (or origination) of an
+    /// AddBlock or AddTransaction, identified by its (anchor) sequence.
+    NodeReachedInMeasurement {
+        measurement_id: u32,
+        kind: MeasurementKind,
+    },
     /// A full message was received by the node (e.g., complete AddBlock).
     FullMessageReceived {
         message_type: u8,
@@ -266,6 +1040,8 @@ pub enum NodeOutputPayload {
         sequence: u32,
         length: usize,
     },
+    /// Answer to `NodeInputMessage::QueryState`.
+    ProtocolState(NodeProtocolState),
 }
 
 /// Envelope for events emitted by node tasks into the network loop.
@@ -283,15 +1059,21 @@ pub enum NodeInputMessage {
     CADResponse(bool),
     /// Request the node to dump its connection matrix into logs.
     RequestConnectionMatrix,
+    /// Load a previously exported connection matrix as the node's link quality state.
+    ImportConnectionMatrix(ConnectionMatrix),
+    /// Request a snapshot of the radio manager's internal state (`NodeOutputPayload::ProtocolState`).
+    QueryState,
+    /// The session ends or the simulator shuts down (node tasks return, node processes quit).
+    Shutdown,
 }
 
-/// Maximum message history per node (ring buffer). Bounded to keep UI/memory predictable.
+/// Default message history per node (ring buffer), see `HistoryConfig`.
 pub const NODE_MESSAGES_CAPACITY: usize = 1000;
 
-/// Maximum number of log lines to retain per node.
+/// Default number of log lines to retain per node.
 pub const NODE_LOG_LINES_CAPACITY: usize = 1000;
 
-/// Maximum number of full messages to retain per node.
+/// Default number of full messages to retain per node.
 pub const NODE_FULL_MESSAGES_CAPACITY: usize = 1000;
 
 /// Log level for log stream entries.
@@ -342,31 +1124,82 @@ pub const MAX_AIRTIME_WAITING_PACKETS: usize = 500;
 const AIRTIME_CAPACITY_WARNING_THRESHOLD: f32 = 0.8; // 80%
 
 impl Node {
-    /// Push a radio packet into this node's bounded history, popping the oldest if
-    /// at capacity.
+    /// Push a radio packet into this node's bounded history, popping the entries the
+    /// history policy no longer keeps.
     pub fn push_radio_packet(&mut self, msg: NodeMessage) {
-        if self.node_radio_packets.len() >= NODE_MESSAGES_CAPACITY {
-            self.node_radio_packets.pop_front();
+        let limit = self.history_limit(self.history.radio_packets);
+        let cutoff = self.history_cutoff();
+        for oldest in evict(&mut self.node_radio_packets, limit, cutoff, |m| m.timestamp) {
+            if let Some(spill) = &mut self.history_spill {
+                spill.radio_packet(&oldest);
+            }
         }
         self.node_radio_packets.push_back(msg);
     }
 
     /// Push a full message into this node's bounded history.
     pub fn push_full_message(&mut self, msg: FullMessage) {
-        if self.full_messages.len() >= NODE_FULL_MESSAGES_CAPACITY {
-            self.full_messages.pop_front();
+        let limit = self.history_limit(self.history.full_messages);
+        let cutoff = self.history_cutoff();
+        for oldest in evict(&mut self.full_messages, limit, cutoff, |m| m.timestamp) {
+            if let Some(spill) = &mut self.history_spill {
+                spill.full_message(&oldest);
+            }
         }
         self.full_messages.push_back(msg);
     }
 
     /// Push a log line into this node's bounded history.
     pub fn push_log_line(&mut self, line: LogLine) {
-        if self.log_lines.len() >= NODE_LOG_LINES_CAPACITY {
-            self.log_lines.pop_front();
+        let limit = self.history_limit(self.history.log_lines);
+        let cutoff = self.history_cutoff();
+        for oldest in evict(&mut self.log_lines, limit, cutoff, |l| l.timestamp) {
+            self.history_heap_bytes -= oldest.content.capacity();
+            if let Some(spill) = &mut self.history_spill {
+                spill.log_line(&oldest);
+            }
         }
+        self.history_heap_bytes += line.content.capacity();
         self.log_lines.push_back(line);
     }
 
+    /// Entries kept of a history of `size` (None = no size limit).
+    fn history_limit(&self, size: usize) -> Option<usize> {
+        match self.history.policy {
+            HistoryPolicy::KeepMinutes { .. } => None,
+            HistoryPolicy::KeepLast | HistoryPolicy::SpillToDisk => Some(size.max(1)),
+        }
+    }
+
+    /// Oldest timestamp kept by the history policy or retention (None = no time limit).
+    fn history_cutoff(&self) -> Option<Instant> {
+        let retention = match self.history.policy {
+            HistoryPolicy::KeepMinutes { minutes } => Duration::from_secs(minutes * 60),
+            HistoryPolicy::KeepLast | HistoryPolicy::SpillToDisk => {
+                Duration::from_secs(self.history.retention?)
+            }
+        };
+        Instant::now().checked_sub(retention)
+    }
+
+    /// Write the lines of the history spill file that are still buffered.
+    pub fn flush_history_spill(&mut self) {
+        if let Some(spill) = &mut self.history_spill {
+            spill.flush();
+        }
+    }
+
+    /// Estimated memory of this node's histories and event queues in bytes
+    /// (allocated ring buffers plus the heap data of their entries).
+    pub fn estimated_memory_bytes(&self) -> u64 {
+        let buffers = self.node_radio_packets.capacity() * size_of::<NodeMessage>()
+            + self.full_messages.capacity() * size_of::<FullMessage>()
+            + self.log_lines.capacity() * size_of::<LogLine>()
+            + self.airtime_waiting_packets.capacity() * size_of::<AirtimeWaitingPacket>()
+            + self.cad_waiting_list.capacity() * size_of::<CadItem>();
+        (buffers + self.history_heap_bytes) as u64
+    }
+
     /// Push an airtime waiting packet with capacity checking and overflow warning.
     /// Returns true if the packet was added, false if capacity was exceeded.
     pub fn push_airtime_packet(&mut self, packet: AirtimeWaitingPacket) -> bool {
@@ -398,4 +1231,84 @@ impl Node {
         self.airtime_waiting_packets.push(packet);
         true
     }
+
+    /// Whether one of this node's own transmissions is on air at any point in `[from, to)`.
+    pub fn is_transmitting_during(&self, from: Instant, to: Instant) -> bool {
+        self.airtime_waiting_packets.iter().any(|p| {
+            p.sender_node_id == self.node_id && p.start_time < to && p.start_time + p.airtime > from
+        })
+    }
+
+    /// Determine the state of this node's (half-duplex) radio at `at`.
+    ///
+    /// The node is receiving while it is locked onto another frame: the frame's
+    /// preamble was complete before `at`, the frame is still on air, it was strong
+    /// enough to be detected and it started while the receiver was listening.
+    ///
+    /// # Parameters
+    ///
+    /// * `at` - Point in time to evaluate
+    /// * `exclude_index` - Index in `airtime_waiting_packets` to ignore (the frame under evaluation)
+    /// * `preamble_time` - Duration of the preamble (lock point relative to frame start)
+    /// * `sensitivity` - Minimum RSSI (dBm) for a preamble to be detected
+    pub fn receiver_state_at(
+        &self,
+        at: Instant,
+        exclude_index: usize,
+        preamble_time: Duration,
+        sensitivity: f32,
+    ) -> ReceiverState {
+        if self.is_transmitting_during(at, at + Duration::from_ticks(1)) {
+            return ReceiverState::Transmitting;
+        }
+        self.airtime_waiting_packets
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| {
+                *i != exclude_index
+                    && p.sender_node_id != self.node_id
+                    && self
+                        .sleep_schedule
+                        .is_none_or(|schedule| schedule.is_listening(p.start_time))
+                    && p.rssi >= sensitivity
+                    && p.start_time + preamble_time <= at
+                    && p.start_time + p.airtime > at
+            })
+            .min_by_key(|(_, p)| p.start_time)
+            .map(|(i, _)| ReceiverState::Receiving(i))
+            .unwrap_or(ReceiverState::Idle)
+    }
+}
+
+/// Pop the oldest entries of a history until one more fits within `limit` and none is
+/// older than `cutoff`, returning them oldest first.
+fn evict<T>(
+    entries: &mut VecDeque<T>,
+    limit: Option<usize>,
+    cutoff: Option<Instant>,
+    timestamp: impl Fn(&T) -> Instant,
+) -> Vec<T> {
+    let mut evicted = Vec::new();
+    while limit.is_some_and(|limit| entries.len() >= limit)
+        || entries
+            .front()
+            .is_some_and(|oldest| cutoff.is_some_and(|cutoff| timestamp(oldest) < cutoff))
+    {
+        match entries.pop_front() {
+            Some(oldest) => evicted.push(oldest),
+            None => break,
+        }
+    }
+    evicted
+}
+
+/// State of a node's half-duplex radio at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiverState {
+    /// Listening for a preamble.
+    Idle,
+    /// Locked onto the frame at this index of `airtime_waiting_packets`.
+    Receiving(usize),
+    /// Transmitting one of its own packets.
+    Transmitting,
 }
@@ -0,0 +1,289 @@
+//! # Unreached Node Diagnosis
+//!
+//! When a measurement stalls below 100%, the UI asks the network task why the
+//! remaining nodes were not reached. Each unreached node is classified from the
+//! current connectivity graph (the links a transmission can use, see
+//! `physics::find_link_candidates`) and the collision records of its incoming links
+//! (see `link_histograms`):
+//!
+//! - **Radio off**: the node's radio is switched off.
+//! - **No path**: no chain of links leads from the origin to the node (out of range
+//!   or blocked by obstacles); no protocol change can reach it.
+//! - **Weak links**: a path exists, but every path contains a link whose mean
+//!   received power is less than the fade margin above the receiving limit, so the
+//!   packets mostly drown in shadowing. The margin is the shadowing σ, at least
+//!   `MIN_FADE_MARGIN_DB`.
+//! - **Congestion / timeout**: a path of strong links exists; the copies were lost
+//!   to collisions or the relays gave up before the node got the message.
+//!
+//! The collision counts cover the whole run, not only the measurement.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::link_histograms::LinkHistograms;
+use super::physics::find_link_candidates;
+use super::signal_calculations::calculate_receiving_limit_with_basic_noise;
+use super::types::{Node, Scene};
+
+/// Smallest fade margin (dB above the receiving limit) of a strong link.
+const MIN_FADE_MARGIN_DB: f32 = 3.0;
+
+/// Why a node was not reached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnreachedCause {
+    RadioOff,
+    NoPath,
+    /// Best incoming link from a node the origin can reach.
+    WeakLinks {
+        sender_id: u32,
+        margin_db: f32,
+    },
+    /// Reached senders with a strong link to the node, and packets lost to
+    /// collisions on its incoming links.
+    Congestion {
+        reached_senders: usize,
+        collisions: u64,
+    },
+}
+
+impl UnreachedCause {
+    /// Short name of the cause.
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnreachedCause::RadioOff => "radio off",
+            UnreachedCause::NoPath => "no path",
+            UnreachedCause::WeakLinks { .. } => "weak links",
+            UnreachedCause::Congestion { .. } => "congestion / timeout",
+        }
+    }
+
+    /// Explanation of the cause for the node list.
+    pub fn explanation(&self) -> String {
+        match *self {
+            UnreachedCause::RadioOff => "the node's radio is switched off".to_string(),
+            UnreachedCause::NoPath => {
+                "no chain of links from the origin (out of range or blocked by obstacles)"
+                    .to_string()
+            }
+            UnreachedCause::WeakLinks {
+                sender_id,
+                margin_db,
+            } => format!(
+                "every path needs a weak link; best incoming link from #{} at {:.1} dB above the receiving limit",
+                sender_id, margin_db
+            ),
+            UnreachedCause::Congestion {
+                reached_senders: 0,
+                collisions,
+            } => format!(
+                "strong path exists, but no strong neighbor was reached; {} packets lost to collisions on its links",
+                collisions
+            ),
+            UnreachedCause::Congestion {
+                reached_senders,
+                collisions,
+            } => format!(
+                "{} reached neighbors have strong links to it; {} packets lost to collisions, the rest not relayed in time",
+                reached_senders, collisions
+            ),
+        }
+    }
+}
+
+/// Diagnosis of one unreached node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnreachedDiagnosis {
+    pub node_id: u32,
+    pub cause: UnreachedCause,
+}
+
+/// A directed link of the connectivity graph.
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    from: u32,
+    to: u32,
+    /// Mean received power above the receiving limit (dB).
+    margin_db: f32,
+}
+
+/// Classify the unreached nodes of a measurement in the current simulation.
+///
+/// # Parameters
+///
+/// * `origin_node_id` - Node that started the measurement
+/// * `reached` - Nodes reached by the measurement
+/// * `nodes_map` - Map of all nodes
+/// * `scene` - Scene configuration (obstacles, propagation model, LoRa parameters)
+/// * `link_histograms` - Collision records of all links
+///
+/// # Returns
+///
+/// One diagnosis per unreached node, ordered by node ID.
+pub fn diagnose(
+    origin_node_id: u32,
+    reached: &HashSet<u32>,
+    nodes_map: &HashMap<u32, Node>,
+    scene: &Scene,
+    link_histograms: &LinkHistograms,
+) -> Vec<UnreachedDiagnosis> {
+    let receiving_limit = calculate_receiving_limit_with_basic_noise(
+        &scene.lora_parameters,
+        &scene.path_loss_parameters,
+    );
+    let mut links = Vec::new();
+    for node in nodes_map.values().filter(|node| !node.disabled) {
+        let candidates = find_link_candidates(
+            node.node_id,
+            &node.position,
+            node.cached_effective_distance,
+            node.radio_strength,
+            nodes_map,
+            scene,
+        );
+        links.extend(candidates.into_iter().map(|candidate| Link {
+            from: node.node_id,
+            to: candidate.target_id,
+            margin_db: candidate.mean_rssi - receiving_limit,
+        }));
+    }
+    let mut unreached: Vec<(u32, bool)> = nodes_map
+        .values()
+        .filter(|node| !reached.contains(&node.node_id))
+        .map(|node| (node.node_id, node.disabled))
+        .collect();
+    unreached.sort_unstable_by_key(|&(node_id, _)| node_id);
+    let fade_margin = scene
+        .path_loss_parameters
+        .shadowing_sigma
+        .max(MIN_FADE_MARGIN_DB);
+    classify(
+        origin_node_id,
+        reached,
+        &unreached,
+        &links,
+        fade_margin,
+        |node_id| {
+            link_histograms
+                .incoming(node_id)
+                .iter()
+                .map(|link| link.sinr_lost.total())
+                .sum()
+        },
+    )
+}
+
+/// Classify the unreached nodes (node ID, radio off) from the graph's links.
+fn classify(
+    origin_node_id: u32,
+    reached: &HashSet<u32>,
+    unreached: &[(u32, bool)],
+    links: &[Link],
+    fade_margin: f32,
+    collisions: impl Fn(u32) -> u64,
+) -> Vec<UnreachedDiagnosis> {
+    let physical = reachable_from(origin_node_id, links.iter());
+    let strong = reachable_from(
+        origin_node_id,
+        links.iter().filter(|link| link.margin_db >= fade_margin),
+    );
+    unreached
+        .iter()
+        .map(|&(node_id, disabled)| {
+            let cause = if disabled {
+                UnreachedCause::RadioOff
+            } else if !physical.contains(&node_id) {
+                UnreachedCause::NoPath
+            } else if !strong.contains(&node_id) {
+                let best = links
+                    .iter()
+                    .filter(|link| link.to == node_id && physical.contains(&link.from))
+                    .max_by(|a, b| a.margin_db.total_cmp(&b.margin_db));
+                UnreachedCause::WeakLinks {
+                    sender_id: best.map_or(origin_node_id, |link| link.from),
+                    margin_db: best.map_or(f32::NEG_INFINITY, |link| link.margin_db),
+                }
+            } else {
+                UnreachedCause::Congestion {
+                    reached_senders: links
+                        .iter()
+                        .filter(|link| {
+                            link.to == node_id
+                                && link.margin_db >= fade_margin
+                                && reached.contains(&link.from)
+                        })
+                        .count(),
+                    collisions: collisions(node_id),
+                }
+            };
+            UnreachedDiagnosis { node_id, cause }
+        })
+        .collect()
+}
+
+/// Nodes reachable from `origin` over the given links (breadth-first).
+fn reachable_from<'a>(origin: u32, links: impl Iterator<Item = &'a Link>) -> HashSet<u32> {
+    let mut adjacency: HashMap<u32, Vec<u32>> = HashMap::new();
+    for link in links {
+        adjacency.entry(link.from).or_default().push(link.to);
+    }
+    let mut visited = HashSet::from([origin]);
+    let mut queue = VecDeque::from([origin]);
+    while let Some(node_id) = queue.pop_front() {
+        for &next in adjacency.get(&node_id).map_or(&[][..], Vec::as_slice) {
+            if visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreached_nodes_are_classified_by_graph_and_collisions() {
+        let link = |from, to, margin_db| Link {
+            from,
+            to,
+            margin_db,
+        };
+        // 1 → 2 strong, 2 → 3 weak, 2 → 4 strong, 4 → 5 strong; 6 isolated; 7 off
+        let links = [
+            link(1, 2, 20.0),
+            link(2, 1, 20.0),
+            link(2, 3, 1.5),
+            link(4, 3, 0.5),
+            link(2, 4, 10.0),
+            link(4, 5, 12.0),
+        ];
+        let reached = HashSet::from([1, 2]);
+        let unreached = [(3, false), (4, false), (5, false), (6, false), (7, true)];
+        let diagnosis = classify(1, &reached, &unreached, &links, 3.0, |node_id| {
+            if node_id == 4 { 7 } else { 0 }
+        });
+
+        let causes: Vec<UnreachedCause> = diagnosis.iter().map(|d| d.cause).collect();
+        assert_eq!(
+            causes,
+            vec![
+                UnreachedCause::WeakLinks {
+                    sender_id: 2,
+                    margin_db: 1.5
+                },
+                UnreachedCause::Congestion {
+                    reached_senders: 1,
+                    collisions: 7
+                },
+                UnreachedCause::Congestion {
+                    reached_senders: 0,
+                    collisions: 0
+                },
+                UnreachedCause::NoPath,
+                UnreachedCause::RadioOff,
+            ]
+        );
+        assert_eq!(diagnosis[3].node_id, 6);
+    }
+}
@@ -0,0 +1,389 @@
+//! # Node Task Watchdog
+//!
+//! Health of the node tasks' input queues. What happens to a radio packet or CAD
+//! response that finds a queue full is the scene's `node_queue.full_policy`:
+//! - `drop` (default): CAD responses are dropped at once and radio packets after
+//!   waiting at most `DELIVERY_TIMEOUT` for room
+//! - `block`: the virtual clock is frozen until the node task makes room (at most
+//!   `max_block_ms` of wall-clock time, then the message is dropped)
+//! - `grow`: the message is kept in a per-node overflow that is moved into the
+//!   queue in order as it drains (at most `max_overflow` messages, then dropped)
+//!
+//! Either way a node task that stopped consuming its queue cannot stall the network
+//! task, and every dropped, blocked or buffered message is counted per node, so the
+//! validity of an experiment can be judged from the report. Once per second the
+//! watchdog samples the queues: a queue that stays full for `STALL_TIMEOUT` marks
+//! the node task as stalled (logged once per stall). Nodes with dropped, blocked or
+//! buffered messages or a full queue are reported to the UI.
+
+use embassy_sync::channel::TrySendError;
+use embassy_time::{Duration, Instant, with_timeout};
+use std::collections::{HashMap, VecDeque};
+
+use super::types::{
+    Node, NodeInputMessage, NodeInputQueueSender, NodeQueueConfig, QueueFullPolicy,
+};
+use crate::time_driver;
+
+/// Longest wait for room in a node's input queue before a radio packet is dropped.
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Time a node's input queue must stay full before the node task counts as stalled.
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delivery health of a node task's input queue.
+#[derive(Default)]
+pub struct NodeHealth {
+    /// CAD responses dropped because the queue was full.
+    pub dropped_cad_responses: u64,
+    /// Radio packets dropped because the queue was full.
+    pub dropped_radio_transfers: u64,
+    /// Deliveries that froze the virtual clock (`block` policy).
+    pub blocked_deliveries: u64,
+    /// Wall-clock time the virtual clock was frozen for this node (`block` policy).
+    pub blocked_for: std::time::Duration,
+    /// Messages that went through the overflow (`grow` policy).
+    pub overflowed_messages: u64,
+    /// Largest overflow depth reached (`grow` policy).
+    pub peak_overflow: usize,
+    /// Messages waiting for room in the queue, oldest first (`grow` policy).
+    overflow: VecDeque<NodeInputMessage>,
+    /// Virtual time since which the queue has been found full (None = not full).
+    full_since: Option<Instant>,
+    /// The current stall was already logged.
+    stall_reported: bool,
+}
+
+/// A copy keeps the counters but not the buffered messages, so none is delivered twice.
+impl Clone for NodeHealth {
+    fn clone(&self) -> Self {
+        Self {
+            dropped_cad_responses: self.dropped_cad_responses,
+            dropped_radio_transfers: self.dropped_radio_transfers,
+            blocked_deliveries: self.blocked_deliveries,
+            blocked_for: self.blocked_for,
+            overflowed_messages: self.overflowed_messages,
+            peak_overflow: self.peak_overflow,
+            overflow: VecDeque::new(),
+            full_since: self.full_since,
+            stall_reported: self.stall_reported,
+        }
+    }
+}
+
+/// Health of a node with delivery problems, for the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeHealthReport {
+    pub node_id: u32,
+    pub dropped_cad_responses: u64,
+    pub dropped_radio_transfers: u64,
+    /// Seconds the input queue has been full (None = not full).
+    pub full_for_secs: Option<u64>,
+    /// The queue has been full for `STALL_TIMEOUT` or longer.
+    pub stalled: bool,
+    /// Deliveries that froze the virtual clock (`block` policy).
+    pub blocked_deliveries: u64,
+    /// Wall-clock milliseconds the virtual clock was frozen (`block` policy).
+    pub blocked_ms: u64,
+    /// Messages that went through the overflow (`grow` policy).
+    pub overflowed_messages: u64,
+    /// Messages currently in the overflow (`grow` policy).
+    pub overflow_depth: usize,
+    /// Largest overflow depth reached (`grow` policy).
+    pub peak_overflow: usize,
+}
+
+impl NodeHealth {
+    /// Deliver a radio packet or CAD response according to the queue policy.
+    ///
+    /// # Parameters
+    ///
+    /// * `sender` - The node's input queue
+    /// * `message` - Message to deliver
+    /// * `config` - The scene's node queue policy and quotas
+    ///
+    /// # Returns
+    ///
+    /// `false` if the message was dropped (and counted). A buffered message counts
+    /// as delivered.
+    pub async fn deliver(
+        &mut self,
+        sender: &NodeInputQueueSender,
+        message: NodeInputMessage,
+        config: &NodeQueueConfig,
+    ) -> bool {
+        let cad_response = matches!(message, NodeInputMessage::CADResponse(_));
+        // Buffered messages go first, so the node sees its messages in order
+        self.flush_overflow(sender);
+        let message = if self.overflow.is_empty() {
+            match sender.try_send(message) {
+                Ok(()) => return true,
+                Err(TrySendError::Full(message)) => message,
+            }
+        } else {
+            message
+        };
+
+        let delivered = match config.full_policy {
+            QueueFullPolicy::Drop if cad_response => false,
+            QueueFullPolicy::Drop => with_timeout(DELIVERY_TIMEOUT, sender.send(message))
+                .await
+                .is_ok(),
+            QueueFullPolicy::Block => self.deliver_blocking(sender, message, config).await,
+            QueueFullPolicy::Grow => {
+                if self.overflow.len() >= config.max_overflow {
+                    false
+                } else {
+                    self.overflow.push_back(message);
+                    self.overflowed_messages += 1;
+                    self.peak_overflow = self.peak_overflow.max(self.overflow.len());
+                    true
+                }
+            }
+        };
+        if !delivered {
+            self.record_dropped(cad_response);
+        }
+        delivered
+    }
+
+    /// Wait for room in the queue with the virtual clock frozen (`block` policy).
+    ///
+    /// # Returns
+    ///
+    /// `false` if the queue stayed full for `max_block_ms` of wall-clock time.
+    async fn deliver_blocking(
+        &mut self,
+        sender: &NodeInputQueueSender,
+        mut message: NodeInputMessage,
+        config: &NodeQueueConfig,
+    ) -> bool {
+        self.blocked_deliveries += 1;
+        // A run paused by a stop condition stays paused afterwards
+        let already_paused = time_driver::is_simulation_paused();
+        time_driver::set_simulation_paused(true);
+        let started = std::time::Instant::now();
+        let limit = std::time::Duration::from_millis(config.max_block_ms);
+        let delivered = loop {
+            match sender.try_send(message) {
+                Ok(()) => break true,
+                Err(TrySendError::Full(returned)) => message = returned,
+            }
+            if started.elapsed() >= limit {
+                break false;
+            }
+            // The node tasks run on the same executor and consume their queues
+            embassy_futures::yield_now().await;
+        };
+        if !already_paused {
+            time_driver::set_simulation_paused(false);
+        }
+        self.blocked_for += started.elapsed();
+        delivered
+    }
+
+    /// Move overflow messages into the queue while it has room (`grow` policy).
+    pub fn flush_overflow(&mut self, sender: &NodeInputQueueSender) {
+        while let Some(message) = self.overflow.pop_front() {
+            if let Err(TrySendError::Full(message)) = sender.try_send(message) {
+                self.overflow.push_front(message);
+                break;
+            }
+        }
+    }
+
+    fn record_dropped(&mut self, cad_response: bool) {
+        if cad_response {
+            self.dropped_cad_responses += 1;
+        } else {
+            self.dropped_radio_transfers += 1;
+        }
+    }
+
+    /// Update the queue state sampled at `now`.
+    ///
+    /// # Returns
+    ///
+    /// `true` when the node has just become stalled (to be logged once).
+    fn observe(&mut self, queue_full: bool, now: Instant) -> bool {
+        if !queue_full {
+            self.full_since = None;
+            self.stall_reported = false;
+            return false;
+        }
+        let since = *self.full_since.get_or_insert(now);
+        if now.saturating_duration_since(since) >= STALL_TIMEOUT && !self.stall_reported {
+            self.stall_reported = true;
+            return true;
+        }
+        false
+    }
+
+    /// Report of the node at `now`, or `None` if it has no delivery problems.
+    fn report(&self, node_id: u32, now: Instant) -> Option<NodeHealthReport> {
+        let full_for = self
+            .full_since
+            .map(|since| now.saturating_duration_since(since));
+        if full_for.is_none()
+            && self.dropped_cad_responses == 0
+            && self.dropped_radio_transfers == 0
+            && self.blocked_deliveries == 0
+            && self.overflowed_messages == 0
+        {
+            return None;
+        }
+        Some(NodeHealthReport {
+            node_id,
+            dropped_cad_responses: self.dropped_cad_responses,
+            dropped_radio_transfers: self.dropped_radio_transfers,
+            full_for_secs: full_for.map(|duration| duration.as_secs()),
+            stalled: full_for.is_some_and(|duration| duration >= STALL_TIMEOUT),
+            blocked_deliveries: self.blocked_deliveries,
+            blocked_ms: self.blocked_for.as_millis() as u64,
+            overflowed_messages: self.overflowed_messages,
+            overflow_depth: self.overflow.len(),
+            peak_overflow: self.peak_overflow,
+        })
+    }
+}
+
+/// Move the overflow of every node into its queue while there is room (`grow` policy).
+///
+/// # Parameters
+///
+/// * `nodes_map` - Mutable map of all nodes
+pub fn flush_overflows(nodes_map: &mut HashMap<u32, Node>) {
+    for node in nodes_map.values_mut() {
+        if let Some(sender) = &node.node_input_queue_sender {
+            node.health.flush_overflow(sender);
+        }
+    }
+}
+
+/// Sample the input queues of all running nodes and report the unhealthy ones.
+///
+/// # Parameters
+///
+/// * `nodes_map` - Mutable map of all nodes (their health is updated)
+///
+/// # Returns
+///
+/// Reports of the nodes with dropped messages or a full queue, ordered by node ID.
+pub fn check(nodes_map: &mut HashMap<u32, Node>) -> Vec<NodeHealthReport> {
+    let now = Instant::now();
+    let mut reports: Vec<NodeHealthReport> = nodes_map
+        .values_mut()
+        .filter_map(|node| {
+            let sender = node.node_input_queue_sender.as_ref()?;
+            if node.health.observe(sender.is_full(), now) {
+                log::warn!(
+                    "Node {} task stalled: input queue full for {}s ({} CAD responses and {} radio packets dropped)",
+                    node.node_id,
+                    STALL_TIMEOUT.as_secs(),
+                    node.health.dropped_cad_responses,
+                    node.health.dropped_radio_transfers
+                );
+            }
+            node.health.report(node.node_id, now)
+        })
+        .collect();
+    reports.sort_by_key(|report| report.node_id);
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_queue_becomes_a_stall_reported_once() {
+        let start = Instant::from_secs(100);
+        let mut health = NodeHealth::default();
+        assert!(!health.observe(false, start));
+        assert_eq!(health.report(1, start), None);
+
+        assert!(!health.observe(true, start));
+        assert!(!health.observe(true, start + Duration::from_secs(4)));
+        assert!(health.observe(true, start + STALL_TIMEOUT));
+        assert!(!health.observe(true, start + Duration::from_secs(8)));
+
+        health.record_dropped(true);
+        let report = health.report(1, start + Duration::from_secs(8)).unwrap();
+        assert_eq!(report.full_for_secs, Some(8));
+        assert!(report.stalled);
+        assert_eq!(report.dropped_cad_responses, 1);
+
+        // The queue drained: the drops stay reported, the stall is over
+        assert!(!health.observe(false, start + Duration::from_secs(9)));
+        let report = health.report(1, start + Duration::from_secs(9)).unwrap();
+        assert_eq!(report.full_for_secs, None);
+        assert!(!report.stalled);
+    }
+
+    #[test]
+    fn test_grow_policy_buffers_in_order_up_to_the_quota() {
+        use super::super::types::{NODE_INPUT_QUEUE_SIZE, NodeInputQueue};
+
+        let queue: &'static NodeInputQueue = Box::leak(Box::new(NodeInputQueue::new()));
+        let sender = queue.sender();
+        let grow = NodeQueueConfig {
+            full_policy: QueueFullPolicy::Grow,
+            max_overflow: 2,
+            ..NodeQueueConfig::default()
+        };
+        let mut health = NodeHealth::default();
+        for _ in 0..NODE_INPUT_QUEUE_SIZE {
+            let message = NodeInputMessage::CADResponse(false);
+            assert!(embassy_futures::block_on(
+                health.deliver(&sender, message, &grow)
+            ));
+        }
+        for activity in [true, false, true] {
+            let message = NodeInputMessage::CADResponse(activity);
+            embassy_futures::block_on(health.deliver(&sender, message, &grow));
+        }
+        // Two buffered, the third over the quota dropped
+        let report = health.report(1, Instant::from_secs(0)).unwrap();
+        assert_eq!(
+            (
+                report.overflowed_messages,
+                report.overflow_depth,
+                report.peak_overflow
+            ),
+            (2, 2, 2)
+        );
+        assert_eq!(report.dropped_cad_responses, 1);
+
+        // Drained queue: the overflow follows the queued messages in order
+        for _ in 0..NODE_INPUT_QUEUE_SIZE {
+            queue.try_receive().unwrap();
+        }
+        health.flush_overflow(&sender);
+        assert!(matches!(
+            queue.try_receive(),
+            Ok(NodeInputMessage::CADResponse(true))
+        ));
+        assert!(matches!(
+            queue.try_receive(),
+            Ok(NodeInputMessage::CADResponse(false))
+        ));
+        assert_eq!(
+            health
+                .report(1, Instant::from_secs(0))
+                .unwrap()
+                .overflow_depth,
+            0
+        );
+
+        // The default policy drops a CAD response at once
+        for _ in 0..NODE_INPUT_QUEUE_SIZE {
+            sender.try_send(NodeInputMessage::CADResponse(false)).ok();
+        }
+        let message = NodeInputMessage::CADResponse(false);
+        let drop = NodeQueueConfig::default();
+        assert!(!embassy_futures::block_on(
+            health.deliver(&sender, message, &drop)
+        ));
+        assert_eq!(health.dropped_cad_responses, 2);
+    }
+}
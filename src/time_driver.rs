@@ -13,9 +13,23 @@
 //!
 //! Public helpers `set_simulation_speed_percent` and
 //! `get_simulation_speed_percent` provide an exact set/get contract (no FP
-//! drift). The driver is registered with `time_driver_impl!` and is used by
+//! drift). `set_simulation_paused` freezes the virtual clock (stop conditions, the
+//! `block` node queue policy) and resumes it from the frozen point. The driver is registered with `time_driver_impl!` and is used by
 //! embassy-time throughout the app.
 //!
+//! ## Event-Driven Clock
+//!
+//! `set_event_driven` switches to a discrete-event clock for headless runs
+//! (`run --event-driven`): virtual time no longer follows the host clock but jumps
+//! straight to the earliest scheduled deadline once the executor is idle, so a run
+//! is limited by the CPU instead of the speed slider. Idleness comes from the
+//! executor itself (`crate::executor`): it reports when it blocks with no task
+//! ready (`executor_idle`) and when a wake makes it busy again (`executor_busy`).
+//! The scheduler jumps only while no executor is busy and none was woken since it
+//! read the earliest deadline, so a jump never overtakes a running task or a timer
+//! it is about to schedule. The speed setting is kept but has no effect until the
+//! clock is scaled again.
+//!
 //! ## Lock Ordering Rules (CRITICAL for deadlock prevention)
 //!
 //! To prevent lock inversion deadlocks, all code MUST follow this strict ordering:
@@ -40,6 +54,7 @@
 use core::task::Waker;
 use embassy_time_driver::{Driver, TICK_HZ, time_driver_impl};
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicIsize, AtomicU64, Ordering};
 use std::sync::{Condvar, Mutex, OnceLock};
 use std::time::{Duration, Instant as StdInstant};
 
@@ -58,6 +73,10 @@ struct ScaledClock {
     /// Last exact percent set by the UI; returned verbatim by `get_…` to
     /// avoid floating-point roundoff surprises.
     last_set_percent: u32,
+    /// Virtual time frozen by `set_simulation_paused` (None while running).
+    paused_at: Option<u64>,
+    /// Virtual time of the event-driven clock (None while following the host clock).
+    event_now: Option<u64>,
 }
 
 #[derive(Default)]
@@ -73,6 +92,10 @@ static CLOCK: OnceLock<Mutex<ScaledClock>> = OnceLock::new();
 static SCHED: OnceLock<Mutex<SchedulerState>> = OnceLock::new();
 static CV: OnceLock<Condvar> = OnceLock::new();
 static SCHEDULER_STARTED: OnceLock<()> = OnceLock::new();
+/// Executors that are polling tasks (not blocked waiting for a wake).
+static BUSY_EXECUTORS: AtomicIsize = AtomicIsize::new(0);
+/// Bumped whenever an executor becomes busy, to detect wakes between two checks.
+static EXECUTOR_WAKES: AtomicU64 = AtomicU64::new(0);
 
 fn clock() -> &'static Mutex<ScaledClock> {
     CLOCK.get_or_init(|| {
@@ -81,6 +104,8 @@ fn clock() -> &'static Mutex<ScaledClock> {
             origin_virtual_ticks: 0,
             scale_q32: ONE_Q32,
             last_set_percent: 100,
+            paused_at: None,
+            event_now: None,
         })
     })
 }
@@ -117,6 +142,12 @@ fn real_now() -> StdInstant {
 fn map_real_to_virtual(r: StdInstant) -> u64 {
     // LOCK ORDERING: CLOCK only (safe to call from any context)
     let clock_lock = clock().lock().unwrap();
+    if let Some(paused_at) = clock_lock.paused_at {
+        return paused_at;
+    }
+    if let Some(event_now) = clock_lock.event_now {
+        return event_now;
+    }
     let real_dt = r.saturating_duration_since(clock_lock.origin_real);
     let real_ticks = (real_dt.as_nanos() as u128 * tick_hz() as u128 / 1_000_000_000u128) as u64;
     let scaled = ((real_ticks as u128) * (clock_lock.scale_q32 as u128) / (ONE_Q32 as u128)) as u64;
@@ -142,6 +173,18 @@ fn map_real_to_virtual(r: StdInstant) -> u64 {
 fn map_virtual_to_real(v_target: u64) -> StdInstant {
     // LOCK ORDERING: CLOCK only (safe to call from any context)
     let clock_lock = clock().lock().unwrap();
+    // While paused no deadline is ever due; the scheduler re-checks every wait slice
+    if clock_lock.paused_at.is_some() {
+        return real_now() + Duration::from_secs(3600);
+    }
+    // The event-driven clock reaches a deadline only by a jump of the scheduler
+    if let Some(event_now) = clock_lock.event_now {
+        return if v_target <= event_now {
+            clock_lock.origin_real
+        } else {
+            real_now() + Duration::from_secs(3600)
+        };
+    }
     // If rebasing moved origin_virtual_ticks past v_target, treat it as due now instead of wrapping
     // (wrapping would create an enormous virt_dt and thus absurd wait durations).
     let virt_dt = match v_target.checked_sub(clock_lock.origin_virtual_ticks) {
@@ -190,6 +233,9 @@ fn scheduler_thread() {
     // 25ms chosen to balance UI responsiveness vs CPU overhead from frequent wake-ups
     const MAX_WAIT_SLICE: Duration = Duration::from_millis(25);
     loop {
+        // Executor wakes before the earliest deadline is read (event-driven clock)
+        let wakes = EXECUTOR_WAKES.load(Ordering::Acquire);
+
         // STEP 1: Extract next deadline from SCHED without holding lock during CLOCK access
         // LOCK ORDERING: Acquire SCHED, extract data, drop SCHED before calling map_virtual_to_real
         let (next_at, snapshot_epoch) = loop {
@@ -206,6 +252,29 @@ fn scheduler_thread() {
             break (next_at, snapshot_epoch);
         };
 
+        // STEP 1b: Event-driven clock: jump to the deadline once the executor is idle
+        // LOCK ORDERING: event_clock_now and jump_event_clock acquire CLOCK only
+        if let Some(event_now) = event_clock_now() {
+            if next_at > event_now {
+                // LOCK ORDERING: SCHED only; executor_idle notifies under SCHED, so the
+                // idle report cannot slip in between the check and the wait
+                let guard = sched().lock().unwrap();
+                if executors_busy() {
+                    let _ = cv().wait_timeout(guard, MAX_WAIT_SLICE).unwrap();
+                    continue;
+                }
+                drop(guard);
+                // A task ran since the deadline was read: it may have scheduled an
+                // earlier one
+                if EXECUTOR_WAKES.load(Ordering::Acquire) != wakes {
+                    continue;
+                }
+                if !jump_event_clock(next_at) {
+                    continue;
+                }
+            }
+        }
+
         // STEP 2: Convert virtual to real time (acquires CLOCK internally, but SCHED is released)
         // LOCK ORDERING: SCHED was released above, so map_virtual_to_real can safely acquire CLOCK
         let real_target = map_virtual_to_real(next_at);
@@ -284,6 +353,27 @@ impl Driver for ScaledDriver {
 // Register as the global time driver for embassy-time
 time_driver_impl!(static DRIVER: ScaledDriver = ScaledDriver);
 
+/// Move `origin_real` so that `v_now` maps to `r_now` under the current scale,
+/// keeping `origin_virtual_ticks` unchanged (see `set_simulation_speed_percent`).
+fn rebase_origin_real(c: &mut ScaledClock, v_now: u64, r_now: StdInstant) {
+    let delta_v = v_now.saturating_sub(c.origin_virtual_ticks) as u128; // ticks
+    // real_elapsed_ticks = delta_v / scale  (since v = origin + real*scale)
+    let real_elapsed_ticks = if c.scale_q32 == 0 {
+        0
+    } else {
+        delta_v * (ONE_Q32 as u128) / (c.scale_q32 as u128)
+    };
+    let real_elapsed_ns = real_elapsed_ticks * 1_000_000_000u128 / (tick_hz() as u128);
+    let dur = Duration::from_nanos(real_elapsed_ns.min(u64::MAX as u128) as u64);
+    // Set new origin_real = r_now - dur (checked to avoid panic if dur > r_now elapsed span)
+    if let Some(new_origin_real) = r_now.checked_sub(dur) {
+        c.origin_real = new_origin_real;
+    } else {
+        // Fallback: if subtraction underflows (extremely large dur), just anchor at now.
+        c.origin_real = r_now;
+    }
+}
+
 // Public UI helpers
 /// Set the simulation speed in percent (1..=1000). Preserves virtual-time
 /// continuity and updates the scheduler epoch for prompt responsiveness.
@@ -308,24 +398,11 @@ pub fn set_simulation_speed_percent(percent: u32) {
     // LOCK ORDERING: CLOCK first (step 1 of 2)
     {
         let mut c = clock().lock().unwrap();
-        let origin_virtual = c.origin_virtual_ticks; // unchanged
-        let delta_v = v_now_old.saturating_sub(origin_virtual) as u128; // ticks
-        // real_elapsed_new_ticks = delta_v / new_scale  (since v = origin + real*scale)
-        let real_elapsed_new_ticks = if new_scale_q32 == 0 {
-            0
-        } else {
-            delta_v * (ONE_Q32 as u128) / (new_scale_q32 as u128)
-        };
-        let real_elapsed_new_ns = real_elapsed_new_ticks * 1_000_000_000u128 / (tick_hz() as u128);
-        let dur = Duration::from_nanos(real_elapsed_new_ns.min(u64::MAX as u128) as u64);
-        // Set new origin_real = r_now - dur (checked to avoid panic if dur > r_now elapsed span)
-        if let Some(new_origin_real) = r_now.checked_sub(dur) {
-            c.origin_real = new_origin_real;
-        } else {
-            // Fallback: if subtraction underflows (extremely large dur), just anchor at now.
-            c.origin_real = r_now;
-        }
         c.scale_q32 = new_scale_q32;
+        // The event-driven clock re-anchors when it is switched off
+        if c.event_now.is_none() {
+            rebase_origin_real(&mut c, v_now_old, r_now);
+        }
         c.last_set_percent = percent; // record exact requested percent
     } // CLOCK lock dropped here
 
@@ -339,6 +416,141 @@ pub fn set_simulation_speed_percent(percent: u32) {
     cv().notify_all();
 }
 
+/// Pause (freeze the virtual clock) or resume the simulation. Resuming continues
+/// from the frozen virtual time, so no timer fires for the paused interval. Speed
+/// changes while paused take effect on resume.
+///
+/// ## Lock Ordering
+/// Acquires CLOCK then SCHED (correct ordering). Never acquires both simultaneously.
+pub fn set_simulation_paused(paused: bool) {
+    let r_now = real_now();
+    {
+        let mut c = clock().lock().unwrap();
+        match (paused, c.paused_at) {
+            (true, None) if c.event_now.is_some() => c.paused_at = c.event_now,
+            (true, None) => {
+                let real_dt = r_now.saturating_duration_since(c.origin_real);
+                let real_ticks =
+                    (real_dt.as_nanos() * tick_hz() as u128 / 1_000_000_000u128) as u64;
+                let scaled =
+                    ((real_ticks as u128) * (c.scale_q32 as u128) / (ONE_Q32 as u128)) as u64;
+                c.paused_at = Some(c.origin_virtual_ticks.wrapping_add(scaled));
+            }
+            (false, Some(paused_at)) => {
+                c.paused_at = None;
+                if c.event_now.is_none() {
+                    rebase_origin_real(&mut c, paused_at, r_now);
+                }
+            }
+            _ => return,
+        }
+    } // CLOCK lock dropped here
+
+    {
+        let mut s = sched().lock().unwrap();
+        s.epoch = s.epoch.wrapping_add(1);
+    } // SCHED lock dropped here
+
+    cv().notify_all();
+}
+
+/// Switch between the event-driven clock (virtual time jumps to the next deadline
+/// when the executor is idle) and scaled real time. Virtual time continues from
+/// the current value either way.
+///
+/// ## Lock Ordering
+/// Acquires CLOCK then SCHED (correct ordering). Never acquires both simultaneously.
+pub fn set_event_driven(enabled: bool) {
+    let r_now = real_now();
+    let v_now = map_real_to_virtual(r_now); // Acquires CLOCK internally
+    {
+        let mut c = clock().lock().unwrap();
+        if c.event_now.is_some() == enabled {
+            return;
+        }
+        if enabled {
+            c.event_now = Some(v_now);
+        } else {
+            // Anchor the scaled clock at the current virtual time. Moving the virtual
+            // origin forward is safe: earlier deadlines map to "due now".
+            c.event_now = None;
+            c.origin_virtual_ticks = v_now;
+            c.origin_real = r_now;
+        }
+    } // CLOCK lock dropped here
+
+    {
+        let mut s = sched().lock().unwrap();
+        s.epoch = s.epoch.wrapping_add(1);
+    } // SCHED lock dropped here
+
+    cv().notify_all();
+}
+
+/// Whether virtual time jumps from event to event (see `set_event_driven`).
+pub fn is_event_driven() -> bool {
+    clock().lock().unwrap().event_now.is_some()
+}
+
+/// Current time of the running event-driven clock (None when scaled or paused).
+///
+/// ## Lock Safety
+/// Acquires CLOCK lock only.
+fn event_clock_now() -> Option<u64> {
+    let c = clock().lock().unwrap();
+    c.event_now.filter(|_| c.paused_at.is_none())
+}
+
+/// Advance the event-driven clock to `v_target` (never backwards).
+///
+/// # Returns
+///
+/// `false` if the clock was switched off or paused meanwhile.
+///
+/// ## Lock Safety
+/// Acquires CLOCK lock only.
+fn jump_event_clock(v_target: u64) -> bool {
+    let mut c = clock().lock().unwrap();
+    if c.paused_at.is_some() {
+        return false;
+    }
+    match c.event_now.as_mut() {
+        Some(event_now) => {
+            *event_now = (*event_now).max(v_target);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether any executor is polling tasks (the event-driven clock must not jump).
+fn executors_busy() -> bool {
+    BUSY_EXECUTORS.load(Ordering::Acquire) > 0
+}
+
+/// Report that an executor started or was woken and polls its tasks.
+pub fn executor_busy() {
+    EXECUTOR_WAKES.fetch_add(1, Ordering::AcqRel);
+    BUSY_EXECUTORS.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Report that an executor polled every ready task and blocks until the next wake.
+///
+/// ## Lock Ordering
+/// Acquires SCHED only (for the notification).
+pub fn executor_idle() {
+    BUSY_EXECUTORS.fetch_sub(1, Ordering::AcqRel);
+    // Notify under SCHED: a scheduler that saw the executor busy is waiting by now
+    let guard = sched().lock().unwrap();
+    cv().notify_all();
+    drop(guard);
+}
+
+/// Whether the virtual clock is frozen by `set_simulation_paused`.
+pub fn is_simulation_paused() -> bool {
+    clock().lock().unwrap().paused_at.is_some()
+}
+
 /// Get the simulation speed as last set by `set_simulation_speed_percent`.
 /// This returns the exact UI-facing value without floating-point rounding.
 pub fn get_simulation_speed_percent() -> u32 {
@@ -396,6 +608,51 @@ mod tests {
         assert!(diff < 0.01, "expected ~{expected_secs}s, got {:?}", real_dt);
     }
 
+    #[test]
+    fn pause_freezes_and_resume_continues_virtual_time() {
+        let _g = TEST_GUARD.lock().unwrap();
+        set_simulation_speed_percent(100);
+        set_simulation_paused(true);
+        let v_paused = map_real_to_virtual(real_now());
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(map_real_to_virtual(real_now()), v_paused);
+
+        set_simulation_paused(false);
+        let v_resumed = map_real_to_virtual(real_now());
+        assert!(v_resumed >= v_paused);
+        assert!(v_resumed - v_paused < tick_hz() / 100);
+    }
+
+    #[test]
+    fn event_driven_clock_jumps_and_continues_when_scaled_again() {
+        let _g = TEST_GUARD.lock().unwrap();
+        set_simulation_speed_percent(100);
+        set_event_driven(true);
+        let v_start = map_real_to_virtual(real_now());
+        std::thread::sleep(Duration::from_millis(20));
+        // Host time does not move the event-driven clock
+        assert_eq!(map_real_to_virtual(real_now()), v_start);
+        // Deadlines are due only once the clock jumped to them
+        let deadline = v_start + tick_hz() * 60;
+        assert!(map_virtual_to_real(deadline) > real_now() + Duration::from_secs(60));
+        assert!(jump_event_clock(deadline));
+        assert!(!jump_event_clock(v_start));
+        assert_eq!(event_clock_now(), Some(deadline));
+        assert!(map_virtual_to_real(deadline) <= real_now());
+
+        // A pause freezes the jumps as well
+        set_simulation_paused(true);
+        assert_eq!(event_clock_now(), None);
+        assert!(!jump_event_clock(deadline + 1));
+        set_simulation_paused(false);
+        assert_eq!(map_real_to_virtual(real_now()), deadline);
+
+        set_event_driven(false);
+        assert!(!is_event_driven());
+        let v_scaled = map_real_to_virtual(real_now());
+        assert!(v_scaled >= deadline && v_scaled - deadline < tick_hz() / 100);
+    }
+
     #[test]
     fn map_virtual_to_real_handles_past_targets() {
         let _g = TEST_GUARD.lock().unwrap();
@@ -0,0 +1,508 @@
+//! # Alert Rules and Toast Notifications
+//!
+//! User-defined alert rules evaluated by `AppState` about once per second:
+//!
+//! - **Node silence**: a node produced no log event for a number of minutes (Real-time Tracking)
+//! - **Sustained delay**: the simulation's processing delay stays above a limit for a
+//!   period (Simulation mode)
+//! - **Measurement stall**: a measurement reached no new node for a virtual duration
+//!   while some nodes are still unreached
+//!
+//! The **collision rate** rule (network-wide collision rate exceeds a limit, Simulation
+//! mode) is evaluated by the simulation on every reception or collision: `AppState`
+//! sends the limit with `UICommand::SetCollisionRateAlert` and shows the
+//! `UIRefreshState::AlertRaised` notifications through `AlertMonitor::notify`.
+//!
+//! Independent of the rules, memory usage above the scene's `history.memory_warning_mb`
+//! (Simulation mode) always raises an alert.
+//!
+//! Rules are edge-triggered: a notification is raised when a condition starts to hold
+//! and re-armed once it clears. Notifications are shown as toasts in the bottom-right
+//! corner of the window and can optionally request the user's attention for the
+//! window (e.g. a flashing taskbar entry while it is in the background). Each rule
+//! can additionally sound a beep (the terminal bell of the console the simulator
+//! was started from) when it raises an alert; sounds are off by default.
+//!
+//! The rule configuration is persisted with the other application settings.
+
+use eframe::egui;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::time::{Duration, Instant};
+
+/// How long a toast notification stays visible.
+const TOAST_DURATION: Duration = Duration::from_secs(10);
+/// Maximum number of toasts shown at once (oldest are dropped first).
+const MAX_TOASTS: usize = 5;
+
+/// User-configurable alert rules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRules {
+    /// Raise an alert when the collision rate exceeds `collision_rate_limit`.
+    pub collision_rate_enabled: bool,
+    /// Collision rate limit in percent.
+    pub collision_rate_limit: f64,
+    /// Beep when the collision rate rule raises an alert.
+    pub collision_rate_sound: bool,
+    /// Raise an alert when a node is silent for `node_silence_minutes` (Real-time Tracking).
+    pub node_silence_enabled: bool,
+    /// Silence limit in minutes.
+    pub node_silence_minutes: u64,
+    /// Beep when the node silence rule raises an alert.
+    pub node_silence_sound: bool,
+    /// Raise an alert when the delay stays above `delay_limit_secs` for `delay_sustain_secs`.
+    pub delay_enabled: bool,
+    /// Delay limit in seconds.
+    pub delay_limit_secs: u64,
+    /// How long (seconds) the delay must stay above the limit.
+    pub delay_sustain_secs: u64,
+    /// Beep when the sustained delay rule raises an alert.
+    pub delay_sound: bool,
+    /// Raise an alert when a measurement stalls.
+    pub measurement_stall_enabled: bool,
+    /// Virtual seconds without a newly reached node after which a measurement is stalled.
+    pub measurement_stall_secs: u64,
+    /// Beep when the measurement stall rule raises an alert.
+    pub measurement_stall_sound: bool,
+    /// Request the user's attention for the window when an alert is raised.
+    #[serde(alias = "sound_enabled")]
+    pub attention_enabled: bool,
+}
+
+impl Default for AlertRules {
+    fn default() -> Self {
+        Self {
+            collision_rate_enabled: false,
+            collision_rate_limit: 10.0,
+            collision_rate_sound: false,
+            node_silence_enabled: false,
+            node_silence_minutes: 10,
+            node_silence_sound: false,
+            delay_enabled: false,
+            delay_limit_secs: 5,
+            delay_sustain_secs: 30,
+            delay_sound: false,
+            measurement_stall_enabled: true,
+            measurement_stall_secs: 60,
+            measurement_stall_sound: false,
+            attention_enabled: false,
+        }
+    }
+}
+
+/// Condition tracked by an alert rule (used for edge triggering).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum AlertCondition {
+    NodeSilent(u32),
+    SustainedDelay,
+    MeasurementStalled(u32),
+    MemoryHigh,
+}
+
+impl AlertCondition {
+    /// Whether the rule of this condition sounds a beep when it raises an alert.
+    fn sound(self, rules: &AlertRules) -> bool {
+        match self {
+            AlertCondition::NodeSilent(_) => rules.node_silence_sound,
+            AlertCondition::SustainedDelay => rules.delay_sound,
+            AlertCondition::MeasurementStalled(_) => rules.measurement_stall_sound,
+            AlertCondition::MemoryHigh => false,
+        }
+    }
+}
+
+/// Sound a short beep: the terminal bell of the console the simulator runs in.
+fn beep() {
+    let mut stderr = std::io::stderr();
+    let _ = stderr.write_all(b"\x07");
+    let _ = stderr.flush();
+}
+
+/// Snapshot of the metrics the alert rules are evaluated against.
+#[derive(Debug, Default)]
+pub struct AlertInputs {
+    /// Nodes currently silent longer than the configured limit (None if not applicable).
+    pub silent_nodes: Option<Vec<u32>>,
+    /// Current processing delay of the simulation (zero in the analyzer modes).
+    pub delay: Duration,
+    /// Stalled measurements: measurement ID and number of unreached nodes.
+    pub stalled_measurements: Vec<(u32, usize)>,
+    /// Memory usage and warning limit in MiB, if the usage exceeds the limit.
+    pub memory_over_limit: Option<(u64, u64)>,
+}
+
+/// A notification shown in the bottom-right corner.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    /// Notification text.
+    pub message: String,
+    /// Real time after which the toast disappears.
+    pub expires_at: Instant,
+}
+
+/// Alert rule configuration plus the runtime state needed to evaluate it.
+pub struct AlertMonitor {
+    /// Current rule configuration.
+    pub rules: AlertRules,
+    /// Whether the rule configuration window is open.
+    pub show_rules_window: bool,
+    /// Visible notifications, oldest first.
+    pub toasts: Vec<Toast>,
+    /// Conditions that currently hold (already notified).
+    active: HashSet<AlertCondition>,
+    /// Real time since the delay has been above the limit.
+    delay_exceeded_since: Option<Instant>,
+    /// An alert was raised that requests the window's attention (sent on the next frame).
+    attention_requested: bool,
+    /// An alert was raised whose rule beeps (played on the next frame).
+    sound_requested: bool,
+}
+
+impl AlertMonitor {
+    /// Create a monitor with the given (typically persisted) rules.
+    pub fn new(rules: AlertRules) -> Self {
+        Self {
+            rules,
+            show_rules_window: false,
+            toasts: Vec::new(),
+            active: HashSet::new(),
+            delay_exceeded_since: None,
+            attention_requested: false,
+            sound_requested: false,
+        }
+    }
+
+    /// Forget all runtime state (toasts, active conditions), keeping the rules.
+    pub fn reset(&mut self) {
+        self.toasts.clear();
+        self.active.clear();
+        self.delay_exceeded_since = None;
+        self.attention_requested = false;
+        self.sound_requested = false;
+    }
+
+    /// Evaluate all enabled rules and raise notifications for newly triggered ones.
+    ///
+    /// # Parameters
+    ///
+    /// * `now` - Current real time
+    /// * `inputs` - Metrics snapshot to evaluate
+    ///
+    /// # Returns
+    ///
+    /// The messages of the alerts raised by this evaluation.
+    pub fn evaluate(&mut self, now: Instant, inputs: &AlertInputs) -> Vec<String> {
+        let mut holding: Vec<(AlertCondition, String)> = Vec::new();
+
+        if self.rules.node_silence_enabled {
+            if let Some(silent_nodes) = &inputs.silent_nodes {
+                for node_id in silent_nodes {
+                    holding.push((
+                        AlertCondition::NodeSilent(*node_id),
+                        format!(
+                            "Node {} silent for more than {} minutes",
+                            node_id, self.rules.node_silence_minutes
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if self.rules.delay_enabled
+            && inputs.delay > Duration::from_secs(self.rules.delay_limit_secs)
+        {
+            let since = *self.delay_exceeded_since.get_or_insert(now);
+            if now.duration_since(since) >= Duration::from_secs(self.rules.delay_sustain_secs) {
+                holding.push((
+                    AlertCondition::SustainedDelay,
+                    format!(
+                        "Processing delay above {}s for more than {}s (currently {}s)",
+                        self.rules.delay_limit_secs,
+                        self.rules.delay_sustain_secs,
+                        inputs.delay.as_secs()
+                    ),
+                ));
+            }
+        } else {
+            self.delay_exceeded_since = None;
+        }
+
+        if self.rules.measurement_stall_enabled {
+            for (measurement_id, unreached) in &inputs.stalled_measurements {
+                holding.push((
+                    AlertCondition::MeasurementStalled(*measurement_id),
+                    format!(
+                        "Measurement {} stalled: {} nodes unreached for {}s",
+                        measurement_id, unreached, self.rules.measurement_stall_secs
+                    ),
+                ));
+            }
+        }
+
+        if let Some((used_mb, limit_mb)) = inputs.memory_over_limit {
+            holding.push((
+                AlertCondition::MemoryHigh,
+                format!(
+                    "Memory usage {} MiB exceeds {} MiB; reduce the scene's history limits",
+                    used_mb, limit_mb
+                ),
+            ));
+        }
+
+        // Re-arm conditions that no longer hold, notify the new ones
+        self.active
+            .retain(|condition| holding.iter().any(|(c, _)| c == condition));
+        let mut raised = Vec::new();
+        for (condition, message) in holding {
+            if self.active.insert(condition) {
+                let sound = condition.sound(&self.rules);
+                self.notify(now, message.clone(), sound);
+                raised.push(message);
+            }
+        }
+
+        raised
+    }
+
+    /// Show the notification of a raised alert (also used for alerts raised by the
+    /// simulation).
+    ///
+    /// # Parameters
+    ///
+    /// * `now` - Current real time
+    /// * `message` - Notification text
+    /// * `sound` - Whether the alert's rule beeps
+    pub fn notify(&mut self, now: Instant, message: String, sound: bool) {
+        log::warn!("Alert: {}", message);
+        self.toasts.push(Toast {
+            message,
+            expires_at: now + TOAST_DURATION,
+        });
+        if self.toasts.len() > MAX_TOASTS {
+            let excess = self.toasts.len() - MAX_TOASTS;
+            self.toasts.drain(..excess);
+        }
+        if self.rules.attention_enabled {
+            self.attention_requested = true;
+        }
+        if sound {
+            self.sound_requested = true;
+        }
+    }
+
+    /// Render the toast notifications in the bottom-right corner.
+    ///
+    /// Expired toasts are dropped; each toast can also be dismissed manually. A pending
+    /// attention request is sent to the window and a pending beep is played here (once
+    /// per frame, however many alerts were raised).
+    pub fn render_toasts(&mut self, ctx: &egui::Context) {
+        if std::mem::take(&mut self.sound_requested) {
+            beep();
+        }
+        if std::mem::take(&mut self.attention_requested) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+        }
+        let now = Instant::now();
+        self.toasts.retain(|toast| toast.expires_at > now);
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut dismissed: Option<usize> = None;
+        egui::Area::new(egui::Id::new("alert_toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+            .show(ctx, |ui| {
+                for (index, toast) in self.toasts.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(&toast.message)
+                                    .color(Color32::from_rgb(255, 140, 0))
+                                    .strong(),
+                            );
+                            if ui.small_button("x").clicked() {
+                                dismissed = Some(index);
+                            }
+                        });
+                    });
+                }
+            });
+        if let Some(index) = dismissed {
+            self.toasts.remove(index);
+        }
+    }
+
+    /// Render the alert rule configuration window (if open).
+    pub fn render_rules_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_rules_window;
+        egui::Window::new("Alert rules")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let rules = &mut self.rules;
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rules.collision_rate_enabled, "Collision rate above");
+                    ui.add(
+                        egui::DragValue::new(&mut rules.collision_rate_limit)
+                            .range(0.0..=100.0)
+                            .suffix("%"),
+                    );
+                    ui.checkbox(&mut rules.collision_rate_sound, "Sound");
+                });
+                ui.label(egui::RichText::new("Simulation mode only").small());
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rules.node_silence_enabled, "Node silent for more than");
+                    ui.add(
+                        egui::DragValue::new(&mut rules.node_silence_minutes)
+                            .range(1..=1440)
+                            .suffix(" min"),
+                    );
+                    ui.checkbox(&mut rules.node_silence_sound, "Sound");
+                });
+                ui.label(egui::RichText::new("Real-time Tracking only").small());
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rules.delay_enabled, "Processing delay above");
+                    ui.add(
+                        egui::DragValue::new(&mut rules.delay_limit_secs)
+                            .range(1..=3600)
+                            .suffix(" s"),
+                    );
+                    ui.label("for");
+                    ui.add(
+                        egui::DragValue::new(&mut rules.delay_sustain_secs)
+                            .range(0..=3600)
+                            .suffix(" s"),
+                    );
+                    ui.checkbox(&mut rules.delay_sound, "Sound");
+                });
+                ui.label(egui::RichText::new("Simulation mode only").small());
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(
+                        &mut rules.measurement_stall_enabled,
+                        "Measurement stalled for",
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut rules.measurement_stall_secs)
+                            .range(1..=86400)
+                            .suffix(" s"),
+                    );
+                    ui.checkbox(&mut rules.measurement_stall_sound, "Sound");
+                });
+                ui.label(egui::RichText::new("Virtual time without a newly reached node").small());
+                ui.add_space(5.0);
+                ui.separator();
+                ui.checkbox(
+                    &mut rules.attention_enabled,
+                    "Request window attention (e.g. flash the taskbar entry)",
+                );
+            });
+        self.show_rules_window = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> AlertRules {
+        AlertRules {
+            node_silence_enabled: true,
+            delay_enabled: true,
+            ..AlertRules::default()
+        }
+    }
+
+    #[test]
+    fn alerts_are_edge_triggered() {
+        let mut monitor = AlertMonitor::new(rules());
+        let now = Instant::now();
+        let inputs = AlertInputs {
+            silent_nodes: Some(vec![3]),
+            ..Default::default()
+        };
+
+        assert_eq!(monitor.evaluate(now, &inputs).len(), 1);
+        // Still silent: no new alert
+        assert!(monitor.evaluate(now, &inputs).is_empty());
+
+        // Condition clears, then triggers again
+        let ok = AlertInputs {
+            silent_nodes: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert!(monitor.evaluate(now, &ok).is_empty());
+        assert_eq!(monitor.evaluate(now, &inputs).len(), 1);
+        assert_eq!(monitor.toasts.len(), 2);
+    }
+
+    #[test]
+    fn alerts_beep_only_for_rules_with_sound() {
+        let mut monitor = AlertMonitor::new(rules());
+        let now = Instant::now();
+        let silent = AlertInputs {
+            silent_nodes: Some(vec![3]),
+            ..Default::default()
+        };
+        monitor.evaluate(now, &silent);
+        assert!(!monitor.sound_requested);
+
+        monitor.rules.node_silence_sound = true;
+        monitor.evaluate(
+            now,
+            &AlertInputs {
+                silent_nodes: Some(vec![4]),
+                ..Default::default()
+            },
+        );
+        assert!(monitor.sound_requested);
+
+        monitor.reset();
+        monitor.notify(now, "collision rate".to_string(), false);
+        assert!(!monitor.sound_requested);
+    }
+
+    #[test]
+    fn alerts_delay_must_be_sustained() {
+        let mut monitor = AlertMonitor::new(rules());
+        let start = Instant::now();
+        let inputs = AlertInputs {
+            delay: Duration::from_secs(10),
+            ..Default::default()
+        };
+
+        assert!(monitor.evaluate(start, &inputs).is_empty());
+        assert!(
+            monitor
+                .evaluate(start + Duration::from_secs(10), &inputs)
+                .is_empty()
+        );
+        assert_eq!(
+            monitor
+                .evaluate(start + Duration::from_secs(30), &inputs)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn alerts_report_each_silent_node() {
+        let mut monitor = AlertMonitor::new(rules());
+        let inputs = AlertInputs {
+            silent_nodes: Some(vec![3, 7]),
+            ..Default::default()
+        };
+        assert_eq!(monitor.evaluate(Instant::now(), &inputs).len(), 2);
+    }
+}
@@ -0,0 +1,276 @@
+//! # Adaptive Animation Detail
+//!
+//! Echo storms start hundreds of transmissions per second; drawing a pulse for each
+//! of them turns the map into noise and costs frame time. With adaptive detail
+//! enabled, the map counts the transmissions of the last second and lowers the
+//! animation fidelity while the rate is above the threshold:
+//!
+//! - **Full** (below the threshold): every transmission pulses.
+//! - **Reduced** (above the threshold): short packets (up to `SHORT_PACKET_BYTES`,
+//!   e.g. echoes and requests) start no pulse; longer packets still do.
+//! - **Aggregated** (above three times the threshold): pulses are additionally
+//!   merged per screen cell into one pulse at their centroid, drawn thicker the
+//!   more transmissions it stands for.
+//!
+//! A level is left only when the rate fell below 80% of the level's entry rate, so
+//! the detail does not flicker around the threshold. The settings are persisted.
+
+use eframe::egui;
+use embassy_time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Window of the transmission rate.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// Packets up to this length start no pulse at reduced detail.
+const SHORT_PACKET_BYTES: usize = 32;
+/// Aggregated detail starts at this multiple of the threshold.
+const AGGREGATION_FACTOR: usize = 3;
+/// Side of the screen cells pulses are merged in at aggregated detail (pixels).
+const CLUSTER_CELL_PIXELS: f32 = 48.0;
+
+/// Fidelity of the map transmission animations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum AnimationDetail {
+    #[default]
+    Full,
+    Reduced,
+    Aggregated,
+}
+
+/// Persisted adaptive detail settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnimationLodSettings {
+    /// Whether the detail adapts to the transmission rate.
+    pub enabled: bool,
+    /// Transmissions per second above which the detail is reduced.
+    pub rate_threshold: u32,
+}
+
+impl Default for AnimationLodSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rate_threshold: 40,
+        }
+    }
+}
+
+/// Transmission rate tracking and the current animation detail.
+#[derive(Debug, Clone, Default)]
+pub struct AnimationLod {
+    pub settings: AnimationLodSettings,
+    /// Start times of the transmissions in the rate window, oldest first.
+    recent: VecDeque<Instant>,
+    detail: AnimationDetail,
+}
+
+impl AnimationLod {
+    /// Create the tracker with persisted settings.
+    pub fn new(settings: AnimationLodSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Count a transmission and decide whether it starts a pulse.
+    ///
+    /// # Parameters
+    ///
+    /// * `now` - Time of the transmission (the clock of the pulse indicators)
+    /// * `length` - Packet length in bytes
+    ///
+    /// # Returns
+    ///
+    /// `false` if the transmission is skipped at the current detail.
+    pub fn admit(&mut self, now: Instant, length: usize) -> bool {
+        self.recent.push_back(now);
+        self.update(now);
+        self.detail == AnimationDetail::Full || length > SHORT_PACKET_BYTES
+    }
+
+    /// Drop the transmissions that left the rate window and recompute the detail.
+    pub fn update(&mut self, now: Instant) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&start| now.saturating_duration_since(start) > RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        self.detail = if self.settings.enabled {
+            next_detail(
+                self.detail,
+                self.recent.len(),
+                self.settings.rate_threshold as usize,
+            )
+        } else {
+            AnimationDetail::Full
+        };
+    }
+
+    /// Current animation detail.
+    pub fn detail(&self) -> AnimationDetail {
+        self.detail
+    }
+
+    /// Transmissions in the last second.
+    pub fn rate(&self) -> usize {
+        self.recent.len()
+    }
+
+    /// Forget the previous session's transmissions.
+    pub fn clear(&mut self) {
+        self.recent.clear();
+        self.detail = AnimationDetail::Full;
+    }
+}
+
+/// Detail for `rate` transmissions per second, leaving a level only below 80% of
+/// its entry rate.
+fn next_detail(current: AnimationDetail, rate: usize, threshold: usize) -> AnimationDetail {
+    let entry = |detail| match detail {
+        AnimationDetail::Full => 0,
+        AnimationDetail::Reduced => threshold.max(1),
+        AnimationDetail::Aggregated => threshold.max(1) * AGGREGATION_FACTOR,
+    };
+    let reached = [
+        AnimationDetail::Aggregated,
+        AnimationDetail::Reduced,
+        AnimationDetail::Full,
+    ]
+    .into_iter()
+    .find(|&detail| rate >= entry(detail))
+    .unwrap_or_default();
+    if reached < current && rate * 5 >= entry(current) * 4 {
+        current
+    } else {
+        reached
+    }
+}
+
+/// A pulse on the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pulse {
+    pub pos: egui::Pos2,
+    /// Current radius in pixels.
+    pub radius: f32,
+    /// Remaining opacity (1 at the start of the pulse).
+    pub alpha: f32,
+    pub message_type: u8,
+    /// Transmissions the pulse stands for.
+    pub count: usize,
+}
+
+/// Merge the pulses per screen cell: one pulse at the centroid with the largest
+/// radius and opacity, of the most frequent message type.
+pub fn aggregate(pulses: &[Pulse]) -> Vec<Pulse> {
+    let mut cells: HashMap<(i32, i32), Vec<&Pulse>> = HashMap::new();
+    for pulse in pulses {
+        let cell = (
+            (pulse.pos.x / CLUSTER_CELL_PIXELS).floor() as i32,
+            (pulse.pos.y / CLUSTER_CELL_PIXELS).floor() as i32,
+        );
+        cells.entry(cell).or_default().push(pulse);
+    }
+    let mut merged: Vec<Pulse> = cells
+        .into_values()
+        .map(|members| {
+            let count: usize = members.iter().map(|p| p.count).sum();
+            let centroid = members.iter().fold(egui::Vec2::ZERO, |sum, p| {
+                sum + p.pos.to_vec2() * p.count as f32
+            }) / count as f32;
+            let mut types: HashMap<u8, usize> = HashMap::new();
+            for p in &members {
+                *types.entry(p.message_type).or_default() += p.count;
+            }
+            let message_type = types
+                .into_iter()
+                .max_by_key(|&(message_type, n)| (n, std::cmp::Reverse(message_type)))
+                .map_or(0, |(message_type, _)| message_type);
+            Pulse {
+                pos: centroid.to_pos2(),
+                radius: members.iter().map(|p| p.radius).fold(0.0, f32::max),
+                alpha: members.iter().map(|p| p.alpha).fold(0.0, f32::max),
+                message_type,
+                count,
+            }
+        })
+        .collect();
+    // Stable draw order between frames
+    merged.sort_by(|a, b| {
+        a.pos
+            .x
+            .total_cmp(&b.pos.x)
+            .then(a.pos.y.total_cmp(&b.pos.y))
+    });
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detail_follows_the_rate_with_hysteresis() {
+        let mut lod = AnimationLod::new(AnimationLodSettings {
+            enabled: true,
+            rate_threshold: 10,
+        });
+        let start = Instant::from_secs(100);
+        for i in 0..9 {
+            assert!(lod.admit(start + Duration::from_millis(i), 10));
+        }
+        // The 10th transmission within a second reduces the detail: short packets are skipped
+        assert!(!lod.admit(start + Duration::from_millis(9), 10));
+        assert!(lod.admit(start + Duration::from_millis(10), 100));
+        assert_eq!(lod.detail(), AnimationDetail::Reduced);
+        for i in 0..20 {
+            lod.admit(start + Duration::from_millis(20 + i), 100);
+        }
+        assert_eq!(lod.detail(), AnimationDetail::Aggregated);
+
+        assert_eq!(
+            next_detail(AnimationDetail::Reduced, 9, 10),
+            AnimationDetail::Reduced
+        );
+        assert_eq!(
+            next_detail(AnimationDetail::Reduced, 7, 10),
+            AnimationDetail::Full
+        );
+        assert_eq!(
+            next_detail(AnimationDetail::Aggregated, 25, 10),
+            AnimationDetail::Aggregated
+        );
+        assert_eq!(
+            next_detail(AnimationDetail::Aggregated, 20, 10),
+            AnimationDetail::Reduced
+        );
+
+        // Once the storm is over the detail returns to full
+        lod.update(start + Duration::from_secs(3));
+        assert_eq!(lod.rate(), 0);
+        assert_eq!(lod.detail(), AnimationDetail::Full);
+
+        let pulse = |x: f32, y: f32, message_type: u8| Pulse {
+            pos: egui::pos2(x, y),
+            radius: x,
+            alpha: 0.5,
+            message_type,
+            count: 1,
+        };
+        let merged = aggregate(&[
+            pulse(10.0, 10.0, 1),
+            pulse(20.0, 30.0, 1),
+            pulse(30.0, 20.0, 6),
+            pulse(200.0, 10.0, 6),
+        ]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].pos, egui::pos2(20.0, 20.0));
+        assert_eq!(merged[0].count, 3);
+        assert_eq!(merged[0].radius, 30.0);
+        assert_eq!(merged[0].message_type, 1);
+        assert_eq!(merged[1].count, 1);
+    }
+}
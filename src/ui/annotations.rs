@@ -0,0 +1,412 @@
+//! # Annotation Layer
+//!
+//! Draws the scene's annotations (see `common::annotations`) on the map: labels,
+//! arrows and translucent regions in their colors, above the nodes so they stay
+//! readable in screenshots. The "Annotations" window toggles the layer and adds
+//! new annotations with a map tool (click for a label, drag for an arrow or a
+//! region) in the chosen text and color. Drawn annotations live until the next
+//! scene load; "Copy JSON" and "Save JSON" export all of them as the scene's
+//! `annotations` array so they can be pasted into the scene file.
+
+use eframe::egui;
+use egui::Color32;
+
+use super::AppState;
+use super::map::MapTransform;
+use crate::common::annotations::{Annotation, format_color, parse_color};
+use crate::common::scene::Point;
+
+/// Drags shorter than this many pixels do not add an arrow or a region.
+const MIN_DRAG_PIXELS: f32 = 5.0;
+
+const TEXT_SIZE: f32 = 13.0;
+
+/// What a map click or drag adds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Tool {
+    #[default]
+    None,
+    Label,
+    Arrow,
+    Region,
+}
+
+/// Annotations of the scene, the drawing tool and window visibility.
+#[derive(Debug, Clone)]
+pub struct AnnotationLayer {
+    /// Whether the window is open.
+    pub open: bool,
+    /// Whether the annotations are drawn on the map.
+    visible: bool,
+    annotations: Vec<Annotation>,
+    tool: Tool,
+    /// Text of the next annotation.
+    text: String,
+    /// Color of the next annotation.
+    color: Color32,
+    /// Screen position where the arrow or region drag in progress started.
+    drag_start: Option<egui::Pos2>,
+}
+
+impl Default for AnnotationLayer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            visible: true,
+            annotations: Vec::new(),
+            tool: Tool::None,
+            text: String::new(),
+            color: Color32::from_rgb(255, 220, 80),
+            drag_start: None,
+        }
+    }
+}
+
+impl AnnotationLayer {
+    /// Replace the annotations with the loaded scene's.
+    pub fn set(&mut self, annotations: Vec<Annotation>) {
+        self.annotations = annotations;
+        self.drag_start = None;
+    }
+
+    /// Whether map drags add an annotation (the map must sense drags).
+    pub fn drawing(&self) -> bool {
+        matches!(self.tool, Tool::Arrow | Tool::Region)
+    }
+}
+
+/// Annotation added by the tool between two world positions (equal for a click).
+///
+/// # Returns
+///
+/// `None` for no tool, or for a label without text.
+fn new_annotation(
+    tool: Tool,
+    start: (f64, f64),
+    end: (f64, f64),
+    text: &str,
+    color: [u8; 4],
+) -> Option<Annotation> {
+    let text = text.trim();
+    let optional_text = (!text.is_empty()).then(|| text.to_string());
+    let color = Some(format_color(color));
+    let point = |(x, y): (f64, f64)| Point { x, y };
+    match tool {
+        Tool::None => None,
+        Tool::Label => Some(Annotation::Label {
+            position: point(end),
+            text: optional_text?,
+            color,
+        }),
+        Tool::Arrow => Some(Annotation::Arrow {
+            from: point(start),
+            to: point(end),
+            text: optional_text,
+            color,
+        }),
+        Tool::Region => Some(Annotation::Region {
+            corner1: point(start),
+            corner2: point(end),
+            text: optional_text,
+            color,
+        }),
+    }
+}
+
+/// Display color of an annotation (white if unset or invalid).
+fn annotation_color(annotation: &Annotation) -> Color32 {
+    annotation
+        .color()
+        .and_then(parse_color)
+        .map_or(Color32::WHITE, |[r, g, b, a]| {
+            Color32::from_rgba_unmultiplied(r, g, b, a)
+        })
+}
+
+/// Draw `text` on a dark backdrop so it stays readable on any background.
+fn draw_text(
+    painter: &egui::Painter,
+    pos: egui::Pos2,
+    anchor: egui::Align2,
+    text: &str,
+    color: Color32,
+) {
+    let galley = painter.layout_no_wrap(
+        text.to_string(),
+        egui::FontId::proportional(TEXT_SIZE),
+        color,
+    );
+    let rect = anchor.anchor_size(pos, galley.size());
+    painter.rect_filled(
+        rect.expand(2.0),
+        3.0,
+        Color32::from_rgba_unmultiplied(0, 0, 0, 150),
+    );
+    painter.galley(rect.min, galley, color);
+}
+
+/// Draw one annotation.
+fn draw_annotation(painter: &egui::Painter, transform: &MapTransform, annotation: &Annotation) {
+    let color = annotation_color(annotation);
+    let screen = |point: &Point| transform.world_to_screen(point.x, point.y);
+    match annotation {
+        Annotation::Label { position, text, .. } => {
+            draw_text(
+                painter,
+                screen(position),
+                egui::Align2::CENTER_CENTER,
+                text,
+                color,
+            );
+        }
+        Annotation::Arrow { from, to, text, .. } => {
+            let start = screen(from);
+            painter.arrow(start, screen(to) - start, egui::Stroke::new(2.0, color));
+            if let Some(text) = text {
+                draw_text(painter, start, egui::Align2::CENTER_BOTTOM, text, color);
+            }
+        }
+        Annotation::Region {
+            corner1,
+            corner2,
+            text,
+            ..
+        } => {
+            let rect = egui::Rect::from_two_pos(screen(corner1), screen(corner2));
+            painter.rect(
+                rect,
+                0.0,
+                color.gamma_multiply(0.25),
+                egui::Stroke::new(1.5, color),
+                egui::StrokeKind::Middle,
+            );
+            if let Some(text) = text {
+                draw_text(
+                    painter,
+                    rect.left_top() + egui::vec2(4.0, 4.0),
+                    egui::Align2::LEFT_TOP,
+                    text,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Draw the annotations and add one with the active tool.
+///
+/// # Parameters
+///
+/// * `response` - egui response from the map interaction area
+/// * `painter` - Map painter
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Mutable application state (annotations)
+///
+/// # Returns
+///
+/// `true` if the click was used to place a label (it must not select a node).
+pub fn handle_map(
+    response: &egui::Response,
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    state: &mut AppState,
+) -> bool {
+    let transform = MapTransform::new(state.world, rect);
+    let layer = &mut state.annotations;
+    let [r, g, b, a] = layer.color.to_srgba_unmultiplied();
+    let mut consumed = false;
+
+    match layer.tool {
+        Tool::Label => {
+            if response.clicked() {
+                if let Some(pos) = response.interact_pointer_pos() {
+                    let position = transform.screen_to_world(pos);
+                    if let Some(label) =
+                        new_annotation(Tool::Label, position, position, &layer.text, [r, g, b, a])
+                    {
+                        layer.annotations.push(label);
+                    }
+                    consumed = true;
+                }
+            }
+        }
+        Tool::Arrow | Tool::Region => {
+            if response.drag_started_by(egui::PointerButton::Primary) {
+                layer.drag_start = response.ctx.input(|i| i.pointer.press_origin());
+            }
+            if let (Some(start), Some(current)) = (layer.drag_start, response.hover_pos()) {
+                let stroke = egui::Stroke::new(1.5, layer.color);
+                if layer.tool == Tool::Arrow {
+                    painter.arrow(start, current - start, stroke);
+                } else {
+                    painter.rect_stroke(
+                        egui::Rect::from_two_pos(start, current),
+                        0.0,
+                        stroke,
+                        egui::StrokeKind::Middle,
+                    );
+                }
+                if response.drag_stopped() {
+                    layer.drag_start = None;
+                    if start.distance(current) >= MIN_DRAG_PIXELS {
+                        if let Some(annotation) = new_annotation(
+                            layer.tool,
+                            transform.screen_to_world(start),
+                            transform.screen_to_world(current),
+                            &layer.text,
+                            [r, g, b, a],
+                        ) {
+                            layer.annotations.push(annotation);
+                        }
+                    }
+                }
+            }
+        }
+        Tool::None => layer.drag_start = None,
+    }
+
+    if layer.visible {
+        for annotation in &layer.annotations {
+            draw_annotation(painter, &transform, annotation);
+        }
+    }
+    consumed
+}
+
+/// The annotations as the scene's pretty-printed `annotations` array.
+fn annotations_json(annotations: &[Annotation]) -> String {
+    serde_json::to_string_pretty(annotations).unwrap_or_default()
+}
+
+/// Ask for a path and write the annotations JSON.
+fn save_json(json: &str, alert: &mut Option<String>) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_file_name("annotations.json")
+        .save_file()
+    else {
+        return;
+    };
+    match std::fs::write(&path, json) {
+        Ok(_) => log::info!("Saved annotations to {}", path.display()),
+        Err(e) => {
+            let message = format!("Failed to write {}: {}", path.display(), e);
+            log::error!("{}", message);
+            *alert = Some(message);
+        }
+    }
+}
+
+/// Render the annotations window (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.annotations.open {
+        return;
+    }
+    let mut open = true;
+    let mut remove: Option<usize> = None;
+    let mut save = false;
+    let layer = &mut state.annotations;
+    egui::Window::new("Annotations")
+        .open(&mut open)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut layer.visible, "Show on map");
+            ui.horizontal(|ui| {
+                ui.label("Tool:");
+                ui.selectable_value(&mut layer.tool, Tool::None, "None");
+                ui.selectable_value(&mut layer.tool, Tool::Label, "Label")
+                    .on_hover_text("Click the map to place the text");
+                ui.selectable_value(&mut layer.tool, Tool::Arrow, "Arrow")
+                    .on_hover_text("Drag on the map from the tail to the head");
+                ui.selectable_value(&mut layer.tool, Tool::Region, "Region")
+                    .on_hover_text("Drag a rectangle on the map");
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut layer.text)
+                        .hint_text("Text, e.g. dead zone")
+                        .desired_width(220.0),
+                );
+                ui.color_edit_button_srgba(&mut layer.color);
+            });
+            ui.separator();
+            if layer.annotations.is_empty() {
+                ui.label("No annotations. Pick a tool and draw on the map.");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    for (index, annotation) in layer.annotations.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let kind = match annotation {
+                                Annotation::Label { .. } => "Label",
+                                Annotation::Arrow { .. } => "Arrow",
+                                Annotation::Region { .. } => "Region",
+                            };
+                            ui.colored_label(annotation_color(annotation), "■");
+                            ui.label(format!("{} {}", kind, annotation.text().unwrap_or("")));
+                            if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                                remove = Some(index);
+                            }
+                        });
+                    }
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                let has_annotations = !layer.annotations.is_empty();
+                if ui
+                    .add_enabled(has_annotations, egui::Button::new("Copy JSON"))
+                    .on_hover_text("Copy the scene's \"annotations\" array to the clipboard")
+                    .clicked()
+                {
+                    ui.ctx().copy_text(annotations_json(&layer.annotations));
+                }
+                save = ui
+                    .add_enabled(has_annotations, egui::Button::new("Save JSON"))
+                    .clicked();
+            });
+        });
+
+    if let Some(index) = remove {
+        layer.annotations.remove(index);
+    }
+    if !open {
+        layer.open = false;
+        layer.tool = Tool::None;
+    }
+    if save {
+        let json = annotations_json(&layer.annotations);
+        save_json(&json, &mut state.alert);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tools_build_scene_annotations() {
+        let color = [255, 0, 0, 255];
+        assert!(new_annotation(Tool::None, (0.0, 0.0), (1.0, 1.0), "x", color).is_none());
+        // Labels need text, arrows and regions do not
+        assert!(new_annotation(Tool::Label, (5.0, 5.0), (5.0, 5.0), "  ", color).is_none());
+
+        let region =
+            new_annotation(Tool::Region, (0.0, 10.0), (20.0, 0.0), " dead zone ", color).unwrap();
+        assert_eq!(region.text(), Some("dead zone"));
+        assert_eq!(region.color(), Some("#ff0000"));
+
+        let arrow = new_annotation(Tool::Arrow, (0.0, 0.0), (3.0, 4.0), "", color).unwrap();
+        assert_eq!(arrow.text(), None);
+        let json = annotations_json(&[arrow]);
+        assert!(json.contains("\"type\": \"arrow\""));
+        assert!(!json.contains("\"text\""));
+        let parsed: Vec<Annotation> = serde_json::from_str(&json).unwrap();
+        assert!(matches!(&parsed[0], Annotation::Arrow { to, .. } if to.x == 3.0 && to.y == 4.0));
+    }
+}
@@ -25,14 +25,62 @@ use egui::Color32;
 use embassy_time::Duration;
 use embassy_time::Instant;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-use super::{NodeInfo, NodeUIState, OperatingMode, UICommand, UIRefreshState, mode_selector};
+use super::alerts::{AlertInputs, AlertMonitor, AlertRules};
+use super::animation_lod::{AnimationLod, AnimationLodSettings};
+use super::annotations::AnnotationLayer;
+use super::command_palette::CommandPaletteState;
+use super::delivery_flow::RecentDeliveries;
+use super::edit_history::EditHistory;
+use super::event_search::EventSearchState;
+use super::flood_efficiency::FloodEfficiencyState;
+use super::interval_marks::IntervalMarksState;
+use super::launch_options::LaunchOptions;
+use super::link_histograms::LinkHistogramView;
+use super::liveness::SilentNodeCache;
+use super::loop_profile::LoopProfileState;
+use super::map::MapView;
+use super::map_style::MapStyle;
+use super::measurement_history::MeasurementHistoryState;
+use super::neighbor_graph::NeighborGraphCache;
+use super::node_coloring::NodeColorMetric;
+use super::node_shapes::NodeShapeCache;
+use super::node_table::NodeTableState;
+use super::obstacle_editor::ObstacleEditorState;
+use super::recent_files::{self, RecentEntry};
+use super::reception_arrows::ReceptionArrows;
+use super::refresh_diagnostics::RefreshDiagnosticsState;
+use super::region_stats::RegionStatsState;
+use super::replay_export::TransmissionCapture;
+use super::ruler::RulerState;
+use super::scene_diff::SceneComparison;
+use super::scene_load::SceneLoad;
+use super::session_clock::SessionClock;
+use super::signal_probe::SignalProbeState;
+use super::sites_dashboard::SitesDashboard;
+use super::stream_filter::StreamFilter;
+use super::time_travel::TimeTravelState;
+use super::{
+    MeasurementKind, NodeInfo, NodeUIState, OperatingMode, UICommand, UIRefreshState, mode_selector,
+};
 use crate::common::connection_matrix::ConnectionMatrix;
+use crate::common::measurement_ids;
+use crate::common::node_statistics::NodeStatistics;
+use crate::common::units::WorldTransform;
 use crate::control::LogLevel;
-use crate::simulation::Obstacle;
-use crate::simulation::Point;
+use crate::simulation::backhaul::BackhaulStatistics;
+use crate::simulation::memory::MemoryUsage;
+use crate::simulation::speed_target::SpeedTargetStatus;
+use crate::simulation::types::NodeProtocolState;
+use crate::simulation::unreached_diagnosis::UnreachedDiagnosis;
+use crate::simulation::watchdog::NodeHealthReport;
+use crate::simulation::{Obstacle, Point};
+
+/// Interval between two evaluations of the alert rules (real time).
+const ALERT_EVALUATION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
 
 /// Duration (in milliseconds) that a radio transmission indicator remains visible on the map.
 /// The indicator fades from full opacity to transparent over this period.
@@ -41,6 +89,204 @@ pub const NODE_RADIO_TRANSFER_INDICATOR_TIMEOUT: u64 = 1000;
 pub const NODE_RADIO_TRANSFER_INDICATOR_DURATION: Duration =
     Duration::from_millis(NODE_RADIO_TRANSFER_INDICATOR_TIMEOUT);
 
+/// Distribution percentages at which measurement milestones are recorded.
+/// The last milestone uses 99.9 to tolerate floating point rounding at 100%.
+pub const MEASUREMENT_MILESTONE_PERCENTAGES: [f64; 3] = [50.0, 90.0, 99.9];
+
+/// A distribution milestone reached by a measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MeasurementMilestone {
+    /// Elapsed virtual time since the measurement start (seconds).
+    pub time: u64,
+    /// Number of measurement packets sent when the milestone was reached.
+    pub message_count: u32,
+}
+
+/// A measurement whose reached-node set stopped growing.
+#[derive(Debug, Clone)]
+pub struct MeasurementStall {
+    /// Elapsed virtual time (seconds) of the last newly reached node.
+    pub last_progress: f64,
+    /// Nodes not reached when the stall was detected, sorted by ID.
+    pub unreached_nodes: Vec<u32>,
+    /// Why each unreached node was not reached (Simulation mode; empty until the
+    /// network task answered, see `unreached_diagnosis`).
+    pub diagnosis: Vec<UnreachedDiagnosis>,
+}
+
+/// Progress of a single measurement as observed by the UI.
+///
+/// Each concurrent measurement keeps its own reached-node set and milestones so
+/// the measurement panel can switch between them without losing data.
+///
+/// Times are passed in explicitly: virtual time in Simulation mode, log timestamps
+/// in the analyzer modes (see `AppState::measurement_clock`), so field-test results
+/// are directly comparable with simulated ones.
+#[derive(Debug, Clone)]
+pub struct MeasurementProgress {
+    /// Node that originated the measurement.
+    pub origin_node_id: u32,
+    /// Message type whose propagation is measured.
+    pub kind: MeasurementKind,
+    /// Virtual time (Simulation) or log time (analyzer modes) when the measurement started.
+    pub start_time: embassy_time::Instant,
+    /// Set of node IDs reached so far (including the origin).
+    pub reached_nodes: HashSet<u32>,
+    /// Elapsed virtual time (seconds) at which each node was first reached.
+    pub reach_times: HashMap<u32, f64>,
+    /// Node each reached node first received the measurement from (where known).
+    pub first_relayers: HashMap<u32, u32>,
+    /// Hop depth of each reached node along its chain of first relayers (origin 0).
+    /// Nodes with an unknown link in the chain have no entry.
+    pub hops: HashMap<u32, u32>,
+    /// Elapsed virtual time at the last measurement packet (seconds).
+    pub total_time: u64,
+    /// Total packets sent for this measurement.
+    pub total_message_count: u32,
+    /// Milestones matching `MEASUREMENT_MILESTONE_PERCENTAGES` (None = not reached yet).
+    pub milestones: [Option<MeasurementMilestone>; 3],
+    /// Set while no new node has been reached for the stall duration.
+    pub stall: Option<MeasurementStall>,
+}
+
+impl MeasurementProgress {
+    /// Create the progress record for a measurement started at `start_time` by `origin_node_id`.
+    pub fn new(origin_node_id: u32, start_time: embassy_time::Instant) -> Self {
+        let mut reached_nodes = HashSet::new();
+        reached_nodes.insert(origin_node_id);
+        let mut reach_times = HashMap::new();
+        reach_times.insert(origin_node_id, 0.0);
+        Self {
+            origin_node_id,
+            kind: MeasurementKind::Block,
+            start_time,
+            reached_nodes,
+            reach_times,
+            first_relayers: HashMap::new(),
+            hops: HashMap::from([(origin_node_id, 0)]),
+            total_time: 0,
+            total_message_count: 0,
+            milestones: [None; 3],
+            stall: None,
+        }
+    }
+
+    /// Percentage of the `node_count` nodes reached by this measurement.
+    pub fn distribution_percentage(&self, node_count: usize) -> f64 {
+        if node_count == 0 {
+            return 0.0;
+        }
+        (self.reached_nodes.len() as f64 / node_count as f64) * 100.0
+    }
+
+    /// Elapsed time since the measurement start at `now`.
+    fn elapsed_at(&self, now: embassy_time::Instant) -> embassy_time::Duration {
+        now.saturating_duration_since(self.start_time)
+    }
+
+    /// Record a node reached at `now` and capture any milestone crossed by it.
+    ///
+    /// # Parameters
+    ///
+    /// * `node_id` - Node that was reached
+    /// * `relayer` - Node the measurement was first received from (None if unknown)
+    /// * `node_count` - Total number of nodes in the scene
+    /// * `now` - Current measurement clock time
+    pub fn record_reached(
+        &mut self,
+        node_id: u32,
+        relayer: Option<u32>,
+        node_count: usize,
+        now: embassy_time::Instant,
+    ) {
+        if self.reached_nodes.insert(node_id) {
+            if self.stall.take().is_some() {
+                log::info!(
+                    "Measurement from node {} resumed: node {} reached",
+                    self.origin_node_id,
+                    node_id
+                );
+            }
+            if let Some(relayer) = relayer {
+                self.first_relayers.insert(node_id, relayer);
+                if let Some(relayer_hops) = self.hops.get(&relayer).copied() {
+                    self.hops.insert(node_id, relayer_hops + 1);
+                }
+            }
+        }
+        let elapsed = self.elapsed_at(now);
+        self.reach_times
+            .entry(node_id)
+            .or_insert(elapsed.as_millis() as f64 / 1000.0);
+        let percentage = self.distribution_percentage(node_count);
+        for (milestone, threshold) in self
+            .milestones
+            .iter_mut()
+            .zip(MEASUREMENT_MILESTONE_PERCENTAGES)
+        {
+            if milestone.is_none() && percentage >= threshold {
+                *milestone = Some(MeasurementMilestone {
+                    time: elapsed.as_secs(),
+                    message_count: self.total_message_count,
+                });
+            }
+        }
+    }
+
+    /// Mark the measurement as stalled if some nodes are still unreached and no new
+    /// node has been reached for `stall_after_secs` virtual seconds.
+    ///
+    /// # Parameters
+    ///
+    /// * `node_ids` - IDs of all nodes in the scene
+    /// * `stall_after_secs` - Virtual duration without progress that counts as a stall
+    /// * `now` - Current measurement clock time
+    ///
+    /// # Returns
+    ///
+    /// `true` if the measurement became stalled by this check.
+    pub fn check_stall(
+        &mut self,
+        node_ids: &[u32],
+        stall_after_secs: u64,
+        now: embassy_time::Instant,
+    ) -> bool {
+        let elapsed = self.elapsed_at(now).as_millis() as f64 / 1000.0;
+        self.check_stall_at(elapsed, node_ids, stall_after_secs)
+    }
+
+    fn check_stall_at(&mut self, elapsed: f64, node_ids: &[u32], stall_after_secs: u64) -> bool {
+        if self.stall.is_some() {
+            return false;
+        }
+        let last_progress = self.reach_times.values().copied().fold(0.0, f64::max);
+        if elapsed - last_progress < stall_after_secs as f64 {
+            return false;
+        }
+        let mut unreached_nodes: Vec<u32> = node_ids
+            .iter()
+            .copied()
+            .filter(|id| !self.reached_nodes.contains(id))
+            .collect();
+        if unreached_nodes.is_empty() {
+            return false;
+        }
+        unreached_nodes.sort_unstable();
+        self.stall = Some(MeasurementStall {
+            last_progress,
+            unreached_nodes,
+            diagnosis: Vec::new(),
+        });
+        true
+    }
+
+    /// Record a packet sent at `now` as part of this measurement.
+    pub fn record_sent_packet(&mut self, now: embassy_time::Instant) {
+        self.total_message_count += 1;
+        self.total_time = self.elapsed_at(now).as_secs();
+    }
+}
+
 /// Currently selected tab in the right panel inspector.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum InspectorTab {
@@ -49,6 +295,9 @@ pub enum InspectorTab {
     MessageStream,
     LogStream,
     ConnectionMatrix,
+    ProtocolState,
+    LinkStats,
+    Waterfall,
 }
 
 /// Type of control modal currently open.
@@ -148,6 +397,15 @@ pub struct AppState {
     pub mode_selector: mode_selector::ModeSelector,
     /// Whether the user has selected a mode (determines if mode selector is shown).
     pub mode_selected: bool,
+    /// Scene comparison shown on the mode selector screen, if any.
+    pub scene_comparison: Option<SceneComparison>,
+    /// Multi-site real-time tracking session, if one was opened from a sites file.
+    pub sites: Option<SitesDashboard>,
+    /// Whether the UI left a session whose task has not confirmed its end yet
+    /// (`UIRefreshState::SessionEnded`); the ending session's late updates are dropped.
+    session_ending: bool,
+    /// Session started while the previous one was still ending, started once it ended.
+    deferred_start: Option<UICommand>,
 
     // Map visualization state
     /// Index of the currently selected node in the `nodes` vector, if any.
@@ -166,8 +424,10 @@ pub struct AppState {
     // Timing and metrics
     /// Simulation start time (virtual time, scaled by time driver).
     pub start_time: embassy_time::Instant,
+    /// Virtual and wall-clock start of the running session.
+    pub session_clock: SessionClock,
     /// Last time the node info was refreshed (real time, for throttling updates).
-    pub last_node_info_update: Instant,
+    pub last_node_info_update: std::time::Instant,
     /// Total packets sent across all nodes.
     pub total_sent_packets: u64,
     /// Total packets successfully received across all nodes.
@@ -176,16 +436,19 @@ pub struct AppState {
     pub total_collision: u64,
     /// Current simulation delay
     pub simulation_delay: Duration,
+    /// Processing delay of the simulation behind its schedule, reported once per second
+    /// (Simulation mode, read by the sustained delay alert rule).
+    pub processing_delay: Duration,
 
     pub last_simulation_time: Option<embassy_time::Instant>,
 
     // Measurement state
-    /// Unique identifier for the current measurement (0 = no active measurement).
+    /// Identifier of the measurement shown in the measurement panel and on the map
+    /// (0 = no measurement displayed).
     pub measurement_identifier: u32,
-    /// Set of node IDs reached during the current measurement.
-    pub reached_nodes: HashSet<u32>,
-    /// Virtual time when the current measurement started.
-    pub measurement_start_time: embassy_time::Instant,
+    /// All measurements started in this session, keyed by measurement identifier.
+    /// Several measurements may run concurrently.
+    pub measurements: BTreeMap<u32, MeasurementProgress>,
     /// Whether a scene file has been selected (after mode selection).
     pub scene_file_selected: bool,
 
@@ -200,6 +463,8 @@ pub struct AppState {
     pub last_open_dir_logvis_scene: Option<String>,
     /// Last directory used for log visualization log file picker.
     pub last_open_dir_logvis_log: Option<String>,
+    /// Recently started sessions, newest first (mode selector screen).
+    pub recent_files: Vec<RecentEntry>,
 
     // Statistics
     /// Count of echo result messages observed.
@@ -210,24 +475,14 @@ pub struct AppState {
     pub speed_percent: u32,
     /// Whether automatic speed adjustment is enabled.
     pub auto_speed_enabled: bool,
-
-    // Measurement milestones
-    /// Virtual time when 50% of nodes were reached (seconds).
-    pub measurement_50_time: u64,
-    /// Virtual time when 90% of nodes were reached (seconds).
-    pub measurement_90_time: u64,
-    /// Virtual time when 100% of nodes were reached (seconds).
-    pub measurement_100_time: u64,
-    /// Number of packets sent when 50% distribution was reached.
-    pub measurement_50_message_count: u32,
-    /// Number of packets sent when 90% distribution was reached.
-    pub measurement_90_message_count: u32,
-    /// Number of packets sent when 100% distribution was reached.
-    pub measurement_100_message_count: u32,
-    /// Total elapsed time for the current measurement (seconds).
-    pub measurement_total_time: u64,
-    /// Total packets sent during the current measurement.
-    pub measurement_total_message_count: u32,
+    /// Virtual minutes of the speed target being entered.
+    pub speed_target_virtual_minutes: u32,
+    /// Wall-clock minutes of the speed target being entered.
+    pub speed_target_wall_minutes: u32,
+    /// Progress of the active speed target (None = no target).
+    pub speed_target: Option<SpeedTargetStatus>,
+    /// Stop condition that paused the simulation (None while running).
+    pub simulation_stopped: Option<String>,
 
     // Link quality thresholds
     /// Link quality value considered "poor" (from scoring matrix).
@@ -241,17 +496,35 @@ pub struct AppState {
     /// Nodes with a pending connection matrix request.
     pub connection_matrix_pending: HashSet<u32>,
 
+    // Protocol state inspection (Simulation mode)
+    /// Latest radio manager state per node with the time it was received.
+    pub protocol_states: HashMap<u32, (embassy_time::Instant, NodeProtocolState)>,
+    /// Nodes with a pending protocol state query.
+    pub protocol_state_pending: HashSet<u32>,
+
     // Map display options
     /// Whether to display node IDs as text labels on the map.
     pub show_node_ids: bool,
-    /// Top-left corner of the world coordinate system.
-    pub world_top_left: Point,
-    /// Bottom-right corner of the world coordinate system.
-    pub world_bottom_right: Point,
-    /// Width of the world in meters.
-    pub width: f64,
-    /// Height of the world in meters.
-    pub height: f64,
+    /// Whether to draw the neighbor graph (edges between nodes in mutual radio range).
+    pub show_neighbor_graph: bool,
+    /// Cached edges of the neighbor graph.
+    pub neighbor_graph: NeighborGraphCache,
+    /// Whether to draw the collision heat overlay.
+    pub show_collision_map: bool,
+    /// Whether to highlight the recent receivers of the selected node.
+    pub show_delivery_flow: bool,
+    /// Per-node metric the map's nodes are colored by.
+    pub node_color_metric: NodeColorMetric,
+    /// Latest link quality observed from echo traffic: (sender, receiver) -> link quality.
+    pub observed_link_qualities: HashMap<(u32, u32), u8>,
+    /// Recent packet deliveries per sender (see `delivery_flow`).
+    pub recent_deliveries: RecentDeliveries,
+    /// Recent analyzer packet receptions drawn as arrows (see `reception_arrows`).
+    pub reception_arrows: ReceptionArrows,
+    /// Transmissions of the session for the measurement replay export (see `replay_export`).
+    pub transmission_capture: TransmissionCapture,
+    /// World bounds and world unit ↔ meter transform of the loaded scene.
+    pub world: WorldTransform,
     /// Optional path to background image for visualization.
     pub background_image: Option<String>,
     /// Loaded background image texture for rendering.
@@ -274,12 +547,125 @@ pub struct AppState {
     pub control_available: bool,
     /// Modal dialog state for control commands.
     pub control_modal: ControlModalState,
+
+    // Alerting
+    /// Alert rules, their runtime state and pending toast notifications.
+    pub alert_monitor: AlertMonitor,
+    /// Last real time the alert rules were evaluated.
+    pub last_alert_evaluation: std::time::Instant,
+    /// Collision rate limit last sent to the simulation (None: not sent since the
+    /// scene was loaded).
+    pub sent_collision_rate_limit: Option<Option<f64>>,
+    /// Timestamp of the latest log event per node (analyzer modes).
+    pub node_last_heard: HashMap<u32, Instant>,
+    /// Timestamp of the first log event seen; nodes never heard are silent since then.
+    pub first_activity_time: Option<Instant>,
+    /// Silent nodes as of the last request, kept until node activity changes.
+    pub silent_node_cache: SilentNodeCache,
+    /// Whether the silent nodes window is shown (analyzer modes).
+    pub show_silent_nodes: bool,
+
+    // Node table
+    /// Latest cumulative radio statistics per node (published by the backend).
+    pub node_statistics: HashMap<u32, NodeStatistics>,
+    /// Latest memory usage estimate of the simulation (None in analyzer modes).
+    pub memory_usage: Option<MemoryUsage>,
+    /// Node tasks with dropped deliveries or a full input queue (watchdog).
+    pub node_health: Vec<NodeHealthReport>,
+    /// Network loop stage timings and the loop profile window (Simulation mode).
+    pub loop_profile: LoopProfileState,
+    /// View state of the node table window.
+    pub node_table: NodeTableState,
+    /// Statistics regions drawn on the map and their window.
+    pub region_stats: RegionStatsState,
+    /// Scene annotations drawn on the map, the annotation tool and its window.
+    pub annotations: AnnotationLayer,
+    /// Query form and results of the event search window (Simulation mode).
+    pub event_search: EventSearchState,
+    /// Last link histogram answer of the inspector's "Link Stats" tab.
+    pub link_histograms: LinkHistogramView,
+    /// Named session marks and the statistics window between them.
+    pub interval_marks: IntervalMarksState,
+    /// View state of the command palette (Ctrl+P).
+    pub command_palette: CommandPaletteState,
+
+    // Obstacle editing
+    /// View state of the runtime obstacle editor (Simulation mode).
+    pub obstacle_editor: ObstacleEditorState,
+
+    // Topology editing
+    /// Whether nodes can be dragged on the map (Simulation mode).
+    pub move_nodes: bool,
+    /// Node being dragged on the map and its position when the drag started.
+    pub node_drag: Option<(u32, Point)>,
+    /// Undo/redo history of obstacle and topology edits.
+    pub edit_history: EditHistory,
+    /// Signal probe map tool (Simulation mode).
+    pub signal_probe: SignalProbeState,
+    /// Distance measuring map tool.
+    pub ruler: RulerState,
+
+    // Measurement origin picking
+    /// Whether clicking a node on the map starts a measurement from it.
+    pub pick_measurement_origin: bool,
+    /// Whether a picked origin must be confirmed before the measurement starts.
+    pub confirm_measurement_origin: bool,
+    /// Picked origin node waiting for confirmation.
+    pub pending_measurement_origin: Option<u32>,
+
+    // Map navigation
+    /// Zoom and pan state of the map.
+    pub map_view: MapView,
+    /// Node ID text of the top panel search box.
+    pub node_search: String,
+    /// Whether the map keeps the selected node centered.
+    pub follow_selected: bool,
+    /// Message types whose transmission animations are hidden on the map.
+    pub hidden_animation_types: HashSet<u8>,
+    /// Transmission rate and adaptive detail of the map animations.
+    pub animation_lod: AnimationLod,
+    /// Filter of the inspector radio and message stream tables.
+    pub stream_filter: StreamFilter,
+
+    // Time travel
+    /// Playback pause and past-time inspection state (Log Visualization mode).
+    pub time_travel: TimeTravelState,
+
+    // Gateways
+    /// Latest gateway backhaul statistics (None if the scene has no gateway).
+    pub backhaul_statistics: Option<BackhaulStatistics>,
+    /// Whether the backhaul window is shown.
+    pub show_backhaul: bool,
+    /// Map background, grid and obstacle colors.
+    pub map_style: MapStyle,
+    /// Whether the map style window is shown.
+    pub show_map_style: bool,
+    /// Whether the hop depth window of the displayed measurement is shown.
+    pub show_hop_depth: bool,
+    /// Cached mesh of the node bodies on the map.
+    pub node_shapes: NodeShapeCache,
+
+    /// Network-wide duplicate samples and window visibility (Simulation mode).
+    pub flood_efficiency: FloodEfficiencyState,
+
+    /// Saved measurement records and history browser visibility.
+    pub measurement_history: MeasurementHistoryState,
+
+    /// Visibility of the UI refresh saturation diagnostics window.
+    pub refresh_diagnostics: RefreshDiagnosticsState,
+
+    // Scene loading
+    /// Progress of the scene load in progress (Simulation mode).
+    pub scene_load: Option<SceneLoad>,
+    /// Auto speed requested on the command line, turned on once the scene is loaded.
+    launch_auto_speed: bool,
 }
 
 /// Settings persisted across application sessions.
 ///
-/// Currently only stores the last directory used for file selection,
-/// improving UX by remembering the user's working directory.
+/// Stores the last directories used for file selection, the inspector width,
+/// the alert rule configuration, the hidden map animation types, the adaptive animation
+/// detail settings and the map style.
 #[derive(Default, Serialize, Deserialize)]
 struct PersistedSettings {
     last_open_dir_sim_scene: Option<String>,
@@ -288,6 +674,11 @@ struct PersistedSettings {
     last_open_dir_logvis_scene: Option<String>,
     last_open_dir_logvis_log: Option<String>,
     right_panel_width: Option<f32>,
+    alert_rules: Option<AlertRules>,
+    hidden_animation_types: Option<Vec<u8>>,
+    animation_lod: Option<AnimationLodSettings>,
+    recent_files: Option<Vec<RecentEntry>>,
+    map_style: Option<MapStyle>,
 }
 
 impl AppState {
@@ -298,6 +689,7 @@ impl AppState {
     /// * `rx` - Receiver for UI refresh messages from the simulation
     /// * `tx` - Sender for commands to the simulation
     /// * `storage` - Optional persistent storage for loading saved settings
+    /// * `launch` - Session given on the command line (skips the mode selector)
     ///
     /// # Returns
     ///
@@ -306,18 +698,23 @@ impl AppState {
         rx: crate::UIRefreshQueueReceiver,
         tx: crate::UICommandQueueSender,
         storage: Option<&dyn eframe::Storage>,
+        launch: Option<LaunchOptions>,
     ) -> Self {
         // Load persisted settings if available
         let persisted: PersistedSettings = storage
             .and_then(|s| eframe::get_value(s, "app_settings"))
             .unwrap_or_default();
 
-        Self {
+        let mut state = Self {
             alert: None,
             ui_refresh_rx: rx,
             ui_command_tx: tx,
             mode_selector: mode_selector::ModeSelector::new(),
             mode_selected: false,
+            scene_comparison: None,
+            sites: None,
+            session_ending: false,
+            deferred_start: None,
             selected: None,
             nodes: Vec::new(),
             obstacles: Vec::new(),
@@ -325,40 +722,46 @@ impl AppState {
             node_info: None,
             inspector_tab: InspectorTab::default(),
             start_time: embassy_time::Instant::now(),
-            last_node_info_update: Instant::now(),
+            session_clock: SessionClock::start(),
+            last_node_info_update: std::time::Instant::now(),
             total_sent_packets: 0,
             total_received_packets: 0,
             total_collision: 0,
             simulation_delay: Duration::from_millis(0),
+            processing_delay: Duration::from_millis(0),
             measurement_identifier: 0,
-            reached_nodes: HashSet::new(),
-            measurement_start_time: embassy_time::Instant::now(),
+            measurements: BTreeMap::new(),
             scene_file_selected: false,
             last_open_dir_sim_scene: persisted.last_open_dir_sim_scene,
             last_open_dir_rt_scene: persisted.last_open_dir_rt_scene,
             last_open_dir_rt_log: persisted.last_open_dir_rt_log,
             last_open_dir_logvis_scene: persisted.last_open_dir_logvis_scene,
             last_open_dir_logvis_log: persisted.last_open_dir_logvis_log,
+            recent_files: persisted.recent_files.unwrap_or_default(),
             echo_result_count: 0,
             speed_percent: crate::time_driver::get_simulation_speed_percent(),
             auto_speed_enabled: false,
-            measurement_50_time: 0,
-            measurement_90_time: 0,
-            measurement_100_time: 0,
-            measurement_50_message_count: 0,
-            measurement_90_message_count: 0,
-            measurement_100_message_count: 0,
-            measurement_total_time: 0,
-            measurement_total_message_count: 0,
+            speed_target_virtual_minutes: 120,
+            speed_target_wall_minutes: 10,
+            speed_target: None,
+            simulation_stopped: None,
             poor_limit: 0,
             excellent_limit: 0,
             connection_matrices: HashMap::new(),
             connection_matrix_pending: HashSet::new(),
+            protocol_states: HashMap::new(),
+            protocol_state_pending: HashSet::new(),
             show_node_ids: true,
-            world_top_left: Point { x: 0.0, y: 0.0 },
-            world_bottom_right: Point { x: 100.0, y: 100.0 },
-            width: 1.0,
-            height: 1.0,
+            show_neighbor_graph: false,
+            neighbor_graph: NeighborGraphCache::default(),
+            show_collision_map: false,
+            show_delivery_flow: true,
+            node_color_metric: NodeColorMetric::default(),
+            observed_link_qualities: HashMap::new(),
+            recent_deliveries: RecentDeliveries::default(),
+            reception_arrows: ReceptionArrows::default(),
+            transmission_capture: TransmissionCapture::default(),
+            world: WorldTransform::default(),
             background_image: None,
             background_image_texture: None,
             operating_mode: OperatingMode::Simulation,
@@ -370,7 +773,83 @@ impl AppState {
             log_level_filter: log::LevelFilter::Info,
             control_available: false,
             control_modal: ControlModalState::default(),
+            alert_monitor: AlertMonitor::new(persisted.alert_rules.unwrap_or_default()),
+            last_alert_evaluation: std::time::Instant::now(),
+            sent_collision_rate_limit: None,
+            node_last_heard: HashMap::new(),
+            first_activity_time: None,
+            silent_node_cache: SilentNodeCache::default(),
+            show_silent_nodes: false,
+            node_statistics: HashMap::new(),
+            memory_usage: None,
+            node_health: Vec::new(),
+            loop_profile: LoopProfileState::default(),
+            node_table: NodeTableState::default(),
+            region_stats: RegionStatsState::default(),
+            annotations: AnnotationLayer::default(),
+            event_search: EventSearchState::default(),
+            link_histograms: LinkHistogramView::default(),
+            interval_marks: IntervalMarksState::default(),
+            command_palette: CommandPaletteState::default(),
+            obstacle_editor: ObstacleEditorState::default(),
+            move_nodes: false,
+            node_drag: None,
+            edit_history: EditHistory::default(),
+            signal_probe: SignalProbeState::default(),
+            ruler: RulerState::default(),
+            pick_measurement_origin: false,
+            confirm_measurement_origin: true,
+            pending_measurement_origin: None,
+            map_view: MapView::default(),
+            node_search: String::new(),
+            follow_selected: false,
+            hidden_animation_types: persisted
+                .hidden_animation_types
+                .map(|types| types.into_iter().collect())
+                .unwrap_or_default(),
+            animation_lod: AnimationLod::new(persisted.animation_lod.unwrap_or_default()),
+            stream_filter: StreamFilter::default(),
+            time_travel: TimeTravelState::default(),
+            backhaul_statistics: None,
+            show_backhaul: false,
+            map_style: persisted.map_style.unwrap_or_default(),
+            show_map_style: false,
+            show_hop_depth: false,
+            node_shapes: NodeShapeCache::default(),
+            flood_efficiency: FloodEfficiencyState::default(),
+            measurement_history: MeasurementHistoryState::default(),
+            refresh_diagnostics: RefreshDiagnosticsState::default(),
+            scene_load: None,
+            launch_auto_speed: false,
+        };
+        if let Some(launch) = launch {
+            state.start_launch_session(launch);
         }
+        state
+    }
+
+    /// Start the session given on the command line (see `launch_options`).
+    fn start_launch_session(&mut self, launch: LaunchOptions) {
+        if let Some(percent) = launch.speed_percent {
+            self.speed_percent = percent;
+            crate::time_driver::set_simulation_speed_percent(percent);
+        }
+        let mode = launch.mode;
+        self.start_session(mode, launch.scene_path, launch.log_path);
+        if launch.auto_speed {
+            if mode == OperatingMode::Simulation {
+                // Commands are ignored while the scene loads
+                self.launch_auto_speed = true;
+            } else {
+                self.enable_auto_speed();
+            }
+        }
+    }
+
+    /// Turn on auto speed.
+    fn enable_auto_speed(&mut self) {
+        self.auto_speed_enabled = true;
+        let _ = self.ui_command_tx.try_send(UICommand::SetAutoSpeed(true));
     }
 
     /// Load a background image from a file path and create an egui texture.
@@ -427,12 +906,7 @@ impl AppState {
         let files = dialog.pick_file();
         if let Some(file) = files {
             let scene_path = file.to_str().unwrap().to_string();
-            let _ = self.ui_command_tx.try_send(UICommand::StartMode {
-                mode: OperatingMode::Simulation,
-                scene_path,
-                log_path: None,
-            });
-            self.scene_file_selected = true;
+            self.start_session(OperatingMode::Simulation, scene_path, None);
             // Remember directory for next time
             if let Some(parent) = file.parent() {
                 self.last_open_dir_sim_scene = Some(parent.to_string_lossy().to_string());
@@ -489,7 +963,7 @@ impl AppState {
     /// Open a file picker for selecting a log file for log visualization.
     /// Returns the selected path or None if cancelled.
     pub fn open_logvis_log_file_picker(&mut self) -> Option<String> {
-        let mut dialog = rfd::FileDialog::new().add_filter("Log files", &["log", "txt", "*"]);
+        let mut dialog = rfd::FileDialog::new().add_filter("Log files", &["log", "txt", "gz", "*"]);
         if let Some(dir) = &self.last_open_dir_logvis_log {
             dialog = dialog.set_directory(dir);
         }
@@ -507,14 +981,7 @@ impl AppState {
             self.mode_selector.realtime_scene_path.clone(),
             self.mode_selector.realtime_log_path.clone(),
         ) {
-            self.mode_selected = true;
-            self.scene_file_selected = true;
-            self.operating_mode = OperatingMode::RealtimeTracking;
-            let _ = self.ui_command_tx.try_send(UICommand::StartMode {
-                mode: OperatingMode::RealtimeTracking,
-                scene_path: scene,
-                log_path: Some(log),
-            });
+            self.start_session(OperatingMode::RealtimeTracking, scene, Some(log));
         }
     }
 
@@ -525,17 +992,56 @@ impl AppState {
             self.mode_selector.logvis_scene_path.clone(),
             self.mode_selector.logvis_log_path.clone(),
         ) {
-            self.mode_selected = true;
-            self.scene_file_selected = true;
-            self.operating_mode = OperatingMode::LogVisualization;
-            let _ = self.ui_command_tx.try_send(UICommand::StartMode {
-                mode: OperatingMode::LogVisualization,
-                scene_path: scene,
-                log_path: Some(log),
-            });
+            self.start_session(OperatingMode::LogVisualization, scene, Some(log));
         }
     }
 
+    /// Start a session: send the StartMode command to the dispatcher, leave the mode
+    /// selector and remember the files in the recent list.
+    ///
+    /// # Parameters
+    ///
+    /// * `mode` - Operating mode to start
+    /// * `scene_path` - Scene file
+    /// * `log_path` - Log file (analyzer modes)
+    pub fn start_session(
+        &mut self,
+        mode: OperatingMode,
+        scene_path: String,
+        log_path: Option<String>,
+    ) {
+        recent_files::remember(
+            &mut self.recent_files,
+            RecentEntry {
+                mode: mode.into(),
+                scene_path: scene_path.clone(),
+                log_path: log_path.clone(),
+            },
+        );
+        self.mode_selected = true;
+        self.scene_file_selected = true;
+        self.operating_mode = mode;
+        let command = UICommand::StartMode {
+            mode,
+            scene_path,
+            log_path,
+        };
+        if self.session_ending {
+            // The dispatcher accepts a new mode only after the previous session ended
+            self.deferred_start = Some(command);
+        } else {
+            let _ = self.ui_command_tx.try_send(command);
+        }
+    }
+
+    /// Leave the running session: ask its task to flush and stop, and return to the
+    /// mode selector.
+    pub fn end_session(&mut self) {
+        let _ = self.ui_command_tx.try_send(UICommand::EndSession);
+        self.session_ending = true;
+        self.reset_to_mode_selector();
+    }
+
     /// Reset the application to the mode selector screen.
     ///
     /// This clears the current mode state and returns to the initial mode selection
@@ -551,46 +1057,325 @@ impl AppState {
         self.mode_selector.logvis_scene_path = None;
         self.mode_selector.logvis_log_path = None;
 
+        // Stop monitoring the sites of a multi-site session
+        self.sites = None;
+
+        // Reset operating mode to default
+        self.operating_mode = OperatingMode::Simulation;
+        // The next session's task starts running, with auto speed off
+        self.simulation_stopped = None;
+        self.auto_speed_enabled = false;
+
+        self.clear_session_view();
+    }
+
+    /// Clear everything shown about the current scene and log: nodes, measurements,
+    /// statistics, alerts and the map view.
+    ///
+    /// Used when leaving a session and when a multi-site session switches to another site.
+    pub fn clear_session_view(&mut self) {
         // Clear simulation state
         self.selected = None;
         self.nodes.clear();
         self.obstacles.clear();
+        self.neighbor_graph.invalidate();
+        self.annotations.set(Vec::new());
+        self.ruler.clear();
         self.node_radio_transfer_indicators.clear();
+        self.animation_lod.clear();
         self.node_info = None;
+        self.observed_link_qualities.clear();
+        self.recent_deliveries.clear();
+        self.reception_arrows.clear();
+        self.transmission_capture.clear();
+        self.event_search.clear();
+        self.link_histograms.clear();
 
         // Reset metrics
         self.total_sent_packets = 0;
         self.total_received_packets = 0;
         self.total_collision = 0;
+        self.interval_marks.clear();
         self.simulation_delay = Duration::from_millis(0);
-        self.measurement_identifier = 0;
-        self.reached_nodes.clear();
+        self.processing_delay = Duration::from_millis(0);
         self.echo_result_count = 0;
 
-        // Reset measurement milestones
-        self.measurement_50_time = 0;
-        self.measurement_90_time = 0;
-        self.measurement_100_time = 0;
-        self.measurement_50_message_count = 0;
-        self.measurement_90_message_count = 0;
-        self.measurement_100_message_count = 0;
-        self.measurement_total_time = 0;
-        self.measurement_total_message_count = 0;
+        // Drop all measurements and their milestones
+        self.measurements.clear();
+        self.measurement_identifier = 0;
+        self.measurement_history.clear_session();
 
         // Reset analyzer state
         self.analyzer_delay = 0;
         self.visualization_ended = false;
+        self.time_travel = TimeTravelState::default();
 
         // Clear background image
         self.background_image = None;
         self.background_image_texture = None;
 
-        // Reset operating mode to default
-        self.operating_mode = OperatingMode::Simulation;
-
         // Reset control state
         self.control_available = false;
         self.control_modal = ControlModalState::default();
+
+        // Reset alerting state (rules are kept)
+        self.alert_monitor.reset();
+        self.sent_collision_rate_limit = None;
+        self.node_last_heard.clear();
+        self.first_activity_time = None;
+        self.silent_node_cache.invalidate();
+
+        // Clear node and backhaul statistics
+        self.node_statistics.clear();
+        self.backhaul_statistics = None;
+        self.memory_usage = None;
+        self.node_health.clear();
+        self.loop_profile.report = None;
+        self.speed_target = None;
+
+        // Forget edits of the previous scene
+        self.node_drag = None;
+        self.edit_history.clear();
+        self.pending_measurement_origin = None;
+
+        // Show the whole world of the next scene
+        self.map_view = MapView::default();
+        self.follow_selected = false;
+    }
+
+    /// Start tracking a new measurement originated by `node_id` at `start_time` and display it.
+    ///
+    /// Measurements already running keep being tracked in the background.
+    /// The caller is responsible for asking the backend to send the measurement block.
+    pub fn begin_measurement_tracking(
+        &mut self,
+        node_id: u32,
+        measurement_identifier: u32,
+        start_time: Instant,
+    ) {
+        self.measurements.insert(
+            measurement_identifier,
+            MeasurementProgress::new(node_id, start_time),
+        );
+        self.measurement_identifier = measurement_identifier;
+    }
+
+    /// Clock of the measurement statistics: virtual time in Simulation mode, the log
+    /// timeline in the analyzer modes.
+    pub fn measurement_clock(&self) -> Instant {
+        self.analyzer_now().unwrap_or_else(Instant::now)
+    }
+
+    /// Start a new measurement from `node_id`: ask the backend to send the measured
+    /// message (AddBlock or AddTransaction) and begin tracking it.
+    pub fn start_measurement(&mut self, node_id: u32, kind: MeasurementKind) {
+        let measurement_identifier = measurement_ids::next();
+        self.ui_command_tx
+            .try_send(UICommand::StartMeasurement(
+                node_id,
+                measurement_identifier,
+                kind,
+            ))
+            .ok();
+        log::info!(
+            "Started {} measurement {} on node {}",
+            kind.label(),
+            measurement_identifier,
+            node_id
+        );
+        let start_time = self.measurement_clock();
+        self.begin_measurement_tracking(node_id, measurement_identifier, start_time);
+        if let Some(measurement) = self.measurements.get_mut(&measurement_identifier) {
+            measurement.kind = kind;
+        }
+    }
+
+    /// Stop tracking the displayed measurement and switch to the most recent remaining one.
+    pub fn reset_measurement(&mut self) {
+        self.measurements.remove(&self.measurement_identifier);
+        self.ui_command_tx
+            .try_send(UICommand::StopMeasurement(self.measurement_identifier))
+            .ok();
+        self.measurement_identifier = self
+            .measurements
+            .iter()
+            .max_by_key(|(_, m)| m.start_time)
+            .map(|(id, _)| *id)
+            .unwrap_or(0);
+    }
+
+    /// The measurement currently shown in the measurement panel, if any.
+    pub fn active_measurement(&self) -> Option<&MeasurementProgress> {
+        self.measurements.get(&self.measurement_identifier)
+    }
+
+    /// Current time on the log timeline in the analyzer modes.
+    ///
+    /// Real-time tracking uses the wall clock (logs are expected to be live), log
+    /// visualization uses the timestamp of the last processed log line.
+    /// Returns `None` in simulation mode or before any log line was processed.
+    pub fn analyzer_now(&self) -> Option<Instant> {
+        match self.operating_mode {
+            OperatingMode::Simulation => None,
+            OperatingMode::RealtimeTracking => {
+                let current_epoch = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                Some(Instant::from_secs(current_epoch))
+            }
+            OperatingMode::LogVisualization => self.last_simulation_time,
+        }
+    }
+
+    /// Select the node with `node_id` (as if clicked on the map) and request its details.
+    pub fn select_node(&mut self, node_id: u32) {
+        if let Some(index) = self.nodes.iter().position(|n| n.node_id == node_id) {
+            self.selected = Some(index);
+            self.request_node_info(node_id);
+        }
+    }
+
+    /// Request the details of a node: live, or as of the inspected time when
+    /// time travel inspection is active (log visualization).
+    pub fn request_node_info(&self, node_id: u32) {
+        let command = match self.time_travel.inspect_time {
+            Some(time) => UICommand::RequestNodeStateAt(node_id, time),
+            None => UICommand::RequestNodeInfo(node_id),
+        };
+        self.ui_command_tx.try_send(command).ok();
+    }
+
+    /// Re-request the details of the selected node (e.g. after the inspected time changed).
+    pub fn refresh_selected_node_info(&self) {
+        if let Some(node) = self.selected.and_then(|i| self.nodes.get(i)) {
+            self.request_node_info(node.node_id);
+        }
+    }
+
+    /// Bring the cached silent nodes (`silent_node_cache`) up to date: nodes that
+    /// produced no log event for longer than `limit` before `now`.
+    ///
+    /// Nodes never heard count as silent since the first log event of the session;
+    /// before any log event is seen no node is reported. The set is only recomputed
+    /// when node activity or the inputs changed (see `liveness::SilentNodeCache`).
+    pub fn refresh_silent_nodes(&mut self, now: Instant, limit: Duration) {
+        if self
+            .silent_node_cache
+            .needs_rebuild(now, limit, self.nodes.len())
+        {
+            let mut silent = Vec::new();
+            let mut next_change: Option<Instant> = None;
+            if let Some(first_activity_time) = self.first_activity_time {
+                for node in &self.nodes {
+                    let last_heard = self
+                        .node_last_heard
+                        .get(&node.node_id)
+                        .copied()
+                        .unwrap_or(first_activity_time);
+                    if now > last_heard && now.duration_since(last_heard) > limit {
+                        silent.push(node.node_id);
+                    } else if let Some(silent_after) = last_heard.checked_add(limit) {
+                        next_change =
+                            Some(next_change.map_or(silent_after, |t| t.min(silent_after)));
+                    }
+                }
+            }
+            // Never heard first, then the longest silence
+            silent.sort_by_key(|node_id| {
+                let last_heard = self.node_last_heard.get(node_id);
+                (last_heard.map(|t| t.as_ticks()), *node_id)
+            });
+            self.silent_node_cache
+                .store(now, limit, self.nodes.len(), silent, next_change);
+        }
+    }
+
+    /// Evaluate the alert rules against the current metrics (throttled to once per second).
+    fn evaluate_alert_rules(&mut self) {
+        let now = std::time::Instant::now();
+        if now.duration_since(self.last_alert_evaluation) < ALERT_EVALUATION_INTERVAL {
+            return;
+        }
+        self.last_alert_evaluation = now;
+
+        // The simulation evaluates the collision rate rule, it only needs the limit
+        if self.operating_mode == OperatingMode::Simulation {
+            let rules = &self.alert_monitor.rules;
+            let limit = rules
+                .collision_rate_enabled
+                .then_some(rules.collision_rate_limit);
+            if self.sent_collision_rate_limit != Some(limit)
+                && self
+                    .ui_command_tx
+                    .try_send(UICommand::SetCollisionRateAlert(limit))
+                    .is_ok()
+            {
+                self.sent_collision_rate_limit = Some(limit);
+            }
+        }
+        let silent_nodes = match self.analyzer_now() {
+            Some(now) if self.operating_mode == OperatingMode::RealtimeTracking => {
+                self.refresh_silent_nodes(now, super::liveness::silence_limit(self));
+                Some(self.silent_node_cache.silent().to_vec())
+            }
+            _ => None,
+        };
+
+        // Stall detection runs regardless of the alert rule, so stalls are always exported
+        let node_ids: Vec<u32> = self.nodes.iter().map(|n| n.node_id).collect();
+        let stall_after_secs = self.alert_monitor.rules.measurement_stall_secs;
+        let measurement_now = self.measurement_clock();
+        for (id, measurement) in self.measurements.iter_mut() {
+            if measurement.check_stall(&node_ids, stall_after_secs, measurement_now) {
+                log::warn!(
+                    "Measurement {} stalled: {} nodes unreached",
+                    id,
+                    measurement
+                        .stall
+                        .as_ref()
+                        .map_or(0, |s| s.unreached_nodes.len())
+                );
+                // Ask the network task why the remaining nodes were not reached
+                if self.operating_mode == OperatingMode::Simulation {
+                    let _ = self.ui_command_tx.try_send(UICommand::DiagnoseUnreached(
+                        *id,
+                        measurement.origin_node_id,
+                        measurement.reached_nodes.iter().copied().collect(),
+                    ));
+                }
+            }
+        }
+        let stalled_measurements = self
+            .measurements
+            .iter()
+            .filter_map(|(id, m)| Some((*id, m.stall.as_ref()?.unreached_nodes.len())))
+            .collect();
+
+        const MIB: u64 = 1024 * 1024;
+        let memory_over_limit = self
+            .memory_usage
+            .filter(|usage| usage.exceeds_warning())
+            .map(|usage| {
+                (
+                    usage.resident_bytes.unwrap_or(usage.estimated_bytes) / MIB,
+                    usage.warning_bytes / MIB,
+                )
+            });
+
+        // The sustained delay rule watches the simulation's processing delay
+        let delay = if self.operating_mode == OperatingMode::Simulation {
+            std::time::Duration::from_millis(self.processing_delay.as_millis())
+        } else {
+            std::time::Duration::ZERO
+        };
+        let inputs = AlertInputs {
+            silent_nodes,
+            stalled_measurements,
+            memory_over_limit,
+            delay,
+        };
+        self.alert_monitor.evaluate(now, &inputs);
     }
 
     /// Render control command modal dialogs.
@@ -920,6 +1705,27 @@ impl AppState {
     }
 }
 
+/// Short name of a message type code, as shown in the inspector.
+///
+/// # Parameters
+///
+/// * `message_type` - The numeric message type code (1-9, 255 for CRC errors)
+pub fn message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        1 => "Req echo",
+        2 => "Echo",
+        3 => "Echo result",
+        4 => "Req block",
+        5 => "Req blk prt",
+        6 => "Add block",
+        7 => "Add trans",
+        8 => "Req mempool",
+        9 => "Support",
+        255 => "Packet CRC Error",
+        _ => "Unknown",
+    }
+}
+
 /// Map a message type code to a color for visualization.
 ///
 /// Each message type in the MoonBlokz protocol is assigned a distinct color
@@ -970,6 +1776,11 @@ impl eframe::App for AppState {
             last_open_dir_logvis_scene: self.last_open_dir_logvis_scene.clone(),
             last_open_dir_logvis_log: self.last_open_dir_logvis_log.clone(),
             right_panel_width: Some(self.right_panel_width),
+            alert_rules: Some(self.alert_monitor.rules.clone()),
+            hidden_animation_types: Some(self.hidden_animation_types.iter().copied().collect()),
+            animation_lod: Some(self.animation_lod.settings),
+            recent_files: Some(self.recent_files.clone()),
+            map_style: Some(self.map_style),
         };
         eframe::set_value(storage, "app_settings", &settings);
     }
@@ -977,13 +1788,22 @@ impl eframe::App for AppState {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Show mode selector first if mode not yet selected
         if !self.mode_selected {
-            if let Some(selection) = self.mode_selector.render(ctx) {
+            recent_files::render_drop_overlay(ctx);
+            let dropped = recent_files::dropped_paths(ctx);
+            let selection = self
+                .mode_selector
+                .render(ctx, &self.recent_files)
+                .or_else(|| recent_files::resolve_drop(&mut self.mode_selector, &dropped));
+            if let Some(selection) = selection {
                 match selection {
                     mode_selector::ModeSelection::Simulation => {
                         self.mode_selected = true;
                         self.operating_mode = OperatingMode::Simulation;
                         self.open_file_selector();
                     }
+                    mode_selector::ModeSelection::SimulationScene(scene_path) => {
+                        self.start_session(OperatingMode::Simulation, scene_path, None);
+                    }
                     mode_selector::ModeSelection::RealtimeSelectScene => {
                         // Open scene file picker for real-time mode
                         if let Some(path) = self.open_rt_scene_file_picker() {
@@ -1002,14 +1822,11 @@ impl eframe::App for AppState {
                         scene_path,
                         log_path,
                     } => {
-                        self.mode_selected = true;
-                        self.scene_file_selected = true;
-                        self.operating_mode = OperatingMode::RealtimeTracking;
-                        let _ = self.ui_command_tx.try_send(UICommand::StartMode {
-                            mode: OperatingMode::RealtimeTracking,
+                        self.start_session(
+                            OperatingMode::RealtimeTracking,
                             scene_path,
-                            log_path: Some(log_path),
-                        });
+                            Some(log_path),
+                        );
                     }
                     mode_selector::ModeSelection::LogVisSelectScene => {
                         // Open scene file picker for log visualization mode
@@ -1029,17 +1846,23 @@ impl eframe::App for AppState {
                         scene_path,
                         log_path,
                     } => {
-                        self.mode_selected = true;
-                        self.scene_file_selected = true;
-                        self.operating_mode = OperatingMode::LogVisualization;
-                        let _ = self.ui_command_tx.try_send(UICommand::StartMode {
-                            mode: OperatingMode::LogVisualization,
+                        self.start_session(
+                            OperatingMode::LogVisualization,
                             scene_path,
-                            log_path: Some(log_path),
-                        });
+                            Some(log_path),
+                        );
+                    }
+                    mode_selector::ModeSelection::CompareScenes => {
+                        super::scene_diff::pick_and_compare(self);
+                    }
+                    mode_selector::ModeSelection::RealtimeSites => {
+                        super::sites_dashboard::pick_and_start(self);
                     }
                 }
             }
+            super::scene_diff::render(ctx, self);
+            super::command_palette::handle_shortcuts(ctx, self);
+            super::command_palette::render(ctx, self);
             return;
         }
 
@@ -1051,12 +1874,10 @@ impl eframe::App for AppState {
         // Repaint periodically so background updates are visible without input
         ctx.request_repaint_after(std::time::Duration::from_millis(20));
 
-        if self.last_node_info_update.elapsed() > Duration::from_secs(1) {
-            if let Some(node_info) = &self.node_info {
-                self.last_node_info_update = Instant::now();
-                _ = self
-                    .ui_command_tx
-                    .try_send(UICommand::RequestNodeInfo(node_info.node_id));
+        if self.last_node_info_update.elapsed() > std::time::Duration::from_secs(1) {
+            if let Some(node_id) = self.node_info.as_ref().map(|n| n.node_id) {
+                self.last_node_info_update = std::time::Instant::now();
+                self.request_node_info(node_id);
             }
         }
 
@@ -1066,6 +1887,16 @@ impl eframe::App for AppState {
             .retain(|_, (expiry_time, _, _)| *expiry_time > now);
 
         while let Ok(msg) = self.ui_refresh_rx.try_receive() {
+            if self.session_ending {
+                // Late updates of the session the user left are dropped
+                if matches!(msg, UIRefreshState::SessionEnded) {
+                    self.session_ending = false;
+                    if let Some(command) = self.deferred_start.take() {
+                        let _ = self.ui_command_tx.try_send(command);
+                    }
+                }
+                continue;
+            }
             match msg {
                 UIRefreshState::Alert(alert_msg) => {
                     self.alert = Some(alert_msg);
@@ -1078,24 +1909,59 @@ impl eframe::App for AppState {
                     } else {
                         self.nodes.push(node);
                     }
+                    self.signal_probe.invalidate();
+                    self.node_shapes.invalidate();
+                    self.neighbor_graph.invalidate();
                 }
                 UIRefreshState::NodesUpdated(nodes) => {
                     self.nodes = nodes;
+                    self.node_shapes.invalidate();
+                    self.neighbor_graph.invalidate();
+                    self.silent_node_cache.invalidate();
+                    self.node_statistics.clear();
+                    self.flood_efficiency.clear();
+                    self.backhaul_statistics = None;
+                    self.memory_usage = None;
+                    self.node_health.clear();
+                    self.loop_profile.report = None;
+                    self.observed_link_qualities.clear();
+                    self.recent_deliveries.clear();
+                    self.reception_arrows.clear();
+                    self.transmission_capture.clear();
                     self.connection_matrices.clear();
                     self.connection_matrix_pending.clear();
+                    self.protocol_states.clear();
+                    self.protocol_state_pending.clear();
+                    self.link_histograms.clear();
+                    self.node_drag = None;
+                    self.edit_history.clear();
+                    self.signal_probe.invalidate();
                 }
                 UIRefreshState::ObstaclesUpdated(obstacles) => {
                     self.obstacles = obstacles;
+                    self.neighbor_graph.invalidate();
                 }
-                UIRefreshState::NodeSentRadioMessage(node_id, message_type, distance) => {
-                    self.node_radio_transfer_indicators.insert(
-                        node_id,
-                        (
-                            Instant::now() + NODE_RADIO_TRANSFER_INDICATOR_DURATION,
-                            message_type,
-                            distance,
-                        ),
-                    );
+                UIRefreshState::AnnotationsUpdated(annotations) => {
+                    self.annotations.set(annotations);
+                }
+                UIRefreshState::NodeSentRadioMessage(node_id, message_type, distance, length) => {
+                    let now = self.measurement_clock();
+                    self.transmission_capture
+                        .record(node_id, message_type, distance, now);
+                    // Hidden types must not replace a visible animation of the same node,
+                    // and transmission storms skip the pulses of short packets
+                    if !self.hidden_animation_types.contains(&message_type)
+                        && self.animation_lod.admit(Instant::now(), length)
+                    {
+                        self.node_radio_transfer_indicators.insert(
+                            node_id,
+                            (
+                                Instant::now() + NODE_RADIO_TRANSFER_INDICATOR_DURATION,
+                                message_type,
+                                distance,
+                            ),
+                        );
+                    }
                     if message_type == moonblokz_radio_lib::MessageType::EchoResult as u8 {
                         self.echo_result_count += 1;
                     }
@@ -1115,30 +1981,44 @@ impl eframe::App for AppState {
                 UIRefreshState::SimulationDelayWarningChanged(delay) => {
                     self.simulation_delay = delay;
                 }
-                UIRefreshState::NodeReachedInMeasurement(node_id, measurement_id) => {
-                    if self.measurement_identifier == measurement_id {
-                        self.reached_nodes.insert(node_id);
+                UIRefreshState::SimulationDelayUpdated(delay) => {
+                    self.processing_delay = delay;
+                }
+                UIRefreshState::NodeReachedInMeasurement(
+                    node_id,
+                    measurement_id,
+                    relayer,
+                    time,
+                ) => {
+                    let node_count = self.nodes.len();
+                    if let Some(measurement) = self.measurements.get_mut(&measurement_id) {
+                        measurement.record_reached(node_id, relayer, node_count, time);
                     }
                 }
                 UIRefreshState::SimulationSpeedChanged(new_speed) => {
                     self.speed_percent = new_speed;
                 }
-                UIRefreshState::SendMessageInMeasurement(measurement_id) => {
-                    if self.measurement_identifier == measurement_id {
-                        self.measurement_total_message_count += 1;
-                        self.measurement_total_time =
-                            self.measurement_start_time.elapsed().as_secs();
+                UIRefreshState::UnreachedDiagnosed(measurement_id, diagnosis) => {
+                    if let Some(stall) = self
+                        .measurements
+                        .get_mut(&measurement_id)
+                        .and_then(|measurement| measurement.stall.as_mut())
+                    {
+                        stall.diagnosis = diagnosis;
+                    }
+                }
+                UIRefreshState::SendMessageInMeasurement(measurement_id, time) => {
+                    if let Some(measurement) = self.measurements.get_mut(&measurement_id) {
+                        measurement.record_sent_packet(time);
                     }
                 }
                 UIRefreshState::PoorAndExcellentLimits(poor, excellent) => {
                     self.poor_limit = poor;
                     self.excellent_limit = excellent;
                 }
-                UIRefreshState::SceneDimensionsUpdated(top_left, bottom_right, width, height) => {
-                    self.world_top_left = top_left;
-                    self.world_bottom_right = bottom_right;
-                    self.width = width;
-                    self.height = height;
+                UIRefreshState::SceneDimensionsUpdated(world) => {
+                    self.world = world;
+                    self.map_view = MapView::default();
                 }
                 UIRefreshState::BackgroundImageUpdated(image_path) => {
                     self.background_image = image_path.clone();
@@ -1154,10 +2034,20 @@ impl eframe::App for AppState {
                 UIRefreshState::VisualizationEnded => {
                     self.visualization_ended = true;
                 }
+                UIRefreshState::NodeStateAt(snapshot) => {
+                    self.time_travel.snapshot = Some(snapshot);
+                }
                 UIRefreshState::ModeChanged(mode) => {
                     self.operating_mode = mode;
+                    self.session_clock = SessionClock::start();
                     self.connection_matrices.clear();
                     self.connection_matrix_pending.clear();
+                    self.protocol_states.clear();
+                    self.protocol_state_pending.clear();
+                    self.link_histograms.clear();
+                }
+                UIRefreshState::SessionEnded => {
+                    // The session task ended on its own (e.g. its scene failed to load)
                 }
                 UIRefreshState::TimeUpdated(time) => {
                     let current_epoch = std::time::SystemTime::now()
@@ -1180,9 +2070,118 @@ impl eframe::App for AppState {
                     self.connection_matrices.insert(requester, matrix);
                     self.connection_matrix_pending.remove(&requester);
                 }
+                UIRefreshState::NodeProtocolStateUpdated(node_id, protocol_state) => {
+                    self.protocol_states
+                        .insert(node_id, (embassy_time::Instant::now(), protocol_state));
+                    self.protocol_state_pending.remove(&node_id);
+                }
+                UIRefreshState::EventQueryAnswered(result) => {
+                    self.event_search.receive(result);
+                }
+                UIRefreshState::LinkHistogramsUpdated(node_id, links) => {
+                    self.link_histograms.receive(node_id, links);
+                }
+                UIRefreshState::MeasurementStarted(node_id, measurement_id, time) => {
+                    match self.measurements.get_mut(&measurement_id) {
+                        // Started from the UI (real-time tracking): the log has the exact start
+                        Some(measurement) if measurement.origin_node_id == node_id => {
+                            measurement.start_time = time;
+                        }
+                        _ => self.begin_measurement_tracking(node_id, measurement_id, time),
+                    }
+                }
+                UIRefreshState::LinkQualityObserved(sender, receiver, link_quality) => {
+                    self.observed_link_qualities
+                        .insert((sender, receiver), link_quality);
+                }
+                UIRefreshState::PacketDelivered(sender, receiver, message_type) => {
+                    self.recent_deliveries
+                        .record(sender, receiver, embassy_time::Instant::now());
+                    // The analyzer modes draw the observed reception topology
+                    if self.operating_mode != OperatingMode::Simulation
+                        && !self.hidden_animation_types.contains(&message_type)
+                    {
+                        self.reception_arrows.record(
+                            sender,
+                            receiver,
+                            message_type,
+                            Instant::now(),
+                        );
+                    }
+                }
+                UIRefreshState::NodeStatisticsUpdated(statistics) => {
+                    if self.operating_mode == OperatingMode::Simulation {
+                        let now = self.session_clock.virtual_elapsed();
+                        self.flood_efficiency
+                            .record(now.as_millis() as f64 / 1000.0, &statistics);
+                    }
+                    self.node_statistics = statistics;
+                    if self.node_color_metric != NodeColorMetric::Uniform {
+                        self.node_shapes.invalidate();
+                    }
+                }
+                UIRefreshState::BackhaulStatisticsUpdated(statistics) => {
+                    self.backhaul_statistics = Some(statistics);
+                }
+                UIRefreshState::MemoryUsageUpdated(memory_usage) => {
+                    self.memory_usage = Some(memory_usage);
+                }
+                UIRefreshState::NodeHealthUpdated(reports) => {
+                    self.node_health = reports;
+                }
+                UIRefreshState::StageTimingsUpdated(report) => {
+                    self.loop_profile.report = Some(report);
+                }
+                UIRefreshState::SpeedTargetUpdated(status) => {
+                    self.speed_target = status;
+                }
+                UIRefreshState::SimulationStopped(reason) => {
+                    self.simulation_stopped = Some(reason);
+                }
+                UIRefreshState::SceneLoadProgress(stage, done, total) => {
+                    let cancel_requested = self
+                        .scene_load
+                        .as_ref()
+                        .is_some_and(|load| load.cancel_requested);
+                    self.scene_load = Some(SceneLoad {
+                        stage,
+                        done,
+                        total,
+                        cancel_requested,
+                    });
+                }
+                UIRefreshState::SceneLoadEnded => {
+                    self.scene_load = None;
+                    // Commands sent while loading are dropped, send the alert limit again
+                    self.sent_collision_rate_limit = None;
+                    self.session_clock = SessionClock::start();
+                    if std::mem::take(&mut self.launch_auto_speed) {
+                        self.enable_auto_speed();
+                    }
+                }
+                UIRefreshState::AlertRaised(message) => {
+                    // Raised by the collision rate rule in the simulation
+                    let sound = self.alert_monitor.rules.collision_rate_sound;
+                    self.alert_monitor
+                        .notify(std::time::Instant::now(), message, sound);
+                }
+                UIRefreshState::SignalProbed(probe) => {
+                    self.signal_probe.receive(probe);
+                }
+                UIRefreshState::NodeActivity(activity) => {
+                    for (node_id, timestamp) in activity {
+                        let first = self.first_activity_time.get_or_insert(timestamp);
+                        *first = (*first).min(timestamp);
+                        self.node_last_heard.insert(node_id, timestamp);
+                    }
+                    self.silent_node_cache.invalidate();
+                }
             }
         }
 
+        self.evaluate_alert_rules();
+        super::measurement_history::save_finished(self);
+
         if self.alert.is_some() {
             egui::Window::new("Alert")
                 .collapsible(false)
@@ -1207,9 +2206,61 @@ impl eframe::App for AppState {
         super::right_panel::render(ctx, self);
         super::map::render(ctx, self);
 
+        // Alert rule configuration and notifications
+        self.alert_monitor.render_rules_window(ctx);
+        self.alert_monitor.render_toasts(ctx);
+        super::liveness::render(ctx, self);
+        super::node_table::render(ctx, self);
+        super::region_stats::render(ctx, self);
+        super::annotations::render(ctx, self);
+        super::interval_marks::render(ctx, self);
+        super::map_style::render(ctx, self);
+        super::scene_load::render(ctx, self);
+        super::refresh_diagnostics::render(ctx, self);
+        super::command_palette::handle_shortcuts(ctx, self);
+        super::command_palette::render(ctx, self);
+        super::measurement_history::render(ctx, self);
+        super::hop_depth::render(ctx, self);
+        super::sites_dashboard::render(ctx, self);
+        if self.operating_mode == OperatingMode::Simulation {
+            super::edit_history::handle_shortcuts(ctx, self);
+            super::obstacle_editor::render_window(ctx, self);
+            super::backhaul::render(ctx, self);
+            super::event_search::render(ctx, self);
+            super::flood_efficiency::render(ctx, self);
+            super::loop_profile::render(ctx, self);
+        }
+
         // Render control modals (only in real-time tracking mode)
         if self.operating_mode == OperatingMode::RealtimeTracking {
             self.render_control_modals(ctx);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measurement_stall_records_unreached_nodes() {
+        let start = embassy_time::Instant::from_secs(1000);
+        let mut measurement = MeasurementProgress::new(1, start);
+        measurement.reached_nodes.insert(2);
+        measurement.reach_times.insert(2, 10.0);
+        let node_ids = [3, 1, 2, 4];
+
+        assert!(!measurement.check_stall_at(60.0, &node_ids, 60));
+        assert!(measurement.check_stall_at(70.0, &node_ids, 60));
+        let stall = measurement.stall.clone().unwrap();
+        assert_eq!(stall.unreached_nodes, vec![3, 4]);
+        assert_eq!(stall.last_progress, 10.0);
+        // Already stalled: not reported again
+        assert!(!measurement.check_stall_at(80.0, &node_ids, 60));
+
+        // Progress clears the stall
+        measurement.record_reached(3, None, node_ids.len(), start + Duration::from_secs(85));
+        assert!(measurement.stall.is_none());
+        assert_eq!(measurement.reach_times[&3], 85.0);
+    }
+}
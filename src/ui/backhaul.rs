@@ -0,0 +1,109 @@
+//! # Backhaul Window
+//!
+//! Delivery statistics of gateway nodes in Simulation mode. Messages received by a
+//! gateway count as delivered to the backhaul; the window shows how many of the
+//! originated messages (measurements, stress test) reached it and how fast, and
+//! lists every gateway with the number of messages it delivered first. Gateways
+//! that never deliver first add no coverage, which helps to size the number of
+//! gateways a deployment area needs.
+
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+
+use super::AppState;
+
+/// Render the backhaul window (if enabled and the scene has gateways).
+///
+/// Clicking a gateway row selects the node on the map and in the inspector.
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (backhaul statistics, selection)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.show_backhaul {
+        return;
+    }
+    let Some(statistics) = state.backhaul_statistics.clone() else {
+        return;
+    };
+
+    let mut open = state.show_backhaul;
+    let mut clicked_node: Option<u32> = None;
+    egui::Window::new("Backhaul")
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("backhaul_summary").show(ui, |ui| {
+                ui.label("Gateways:");
+                ui.label(egui::RichText::new(statistics.gateways.len().to_string()).strong());
+                ui.end_row();
+                ui.label("Delivered messages:");
+                ui.label(egui::RichText::new(statistics.delivered.to_string()).strong());
+                ui.end_row();
+                ui.label("Originated delivered:");
+                let ratio = match statistics.delivery_ratio() {
+                    Some(ratio) => format!(
+                        "{} / {} ({:.1}%)",
+                        statistics.originated_delivered, statistics.originated, ratio
+                    ),
+                    None => "-".to_string(),
+                };
+                ui.label(egui::RichText::new(ratio).strong());
+                ui.end_row();
+                ui.label("Average latency:");
+                let latency = match statistics.average_latency {
+                    Some(latency) => format!("{:.1} s", latency.as_millis() as f64 / 1000.0),
+                    None => "-".to_string(),
+                };
+                ui.label(egui::RichText::new(latency).strong());
+                ui.end_row();
+                ui.label("Re-injected:");
+                ui.label(egui::RichText::new(statistics.reinjected.to_string()).strong());
+                ui.end_row();
+            });
+            ui.separator();
+
+            TableBuilder::new(ui)
+                .striped(true)
+                .sense(egui::Sense::click())
+                .column(Column::auto().at_least(70.0))
+                .column(Column::auto().at_least(80.0))
+                .column(Column::remainder())
+                .max_scroll_height(300.0)
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        ui.strong("Gateway");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Received");
+                    });
+                    header.col(|ui| {
+                        ui.strong("First deliveries");
+                    });
+                })
+                .body(|mut body| {
+                    for (node_id, gateway) in &statistics.gateways {
+                        body.row(18.0, |mut row| {
+                            row.col(|ui| {
+                                ui.label(format!("#{}", node_id));
+                            });
+                            row.col(|ui| {
+                                ui.label(gateway.received.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(gateway.first_deliveries.to_string());
+                            });
+                            if row.response().clicked() {
+                                clicked_node = Some(*node_id);
+                            }
+                        });
+                    }
+                });
+        });
+    state.show_backhaul = open;
+
+    if let Some(node_id) = clicked_node {
+        state.select_node(node_id);
+    }
+}
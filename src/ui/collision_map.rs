@@ -0,0 +1,158 @@
+//! # Collision Map Overlay
+//!
+//! Heat overlay of where collisions happen. Collisions are counted at the receiving
+//! node, so every node's position is weighted by its collision count (from the
+//! per-node statistics) and spread over a grid with a Gaussian kernel. Congestion
+//! hotspots, e.g. around dense clusters, show up as red areas instead of being
+//! hidden in the global collision percentage.
+//!
+//! The grid covers the whole world in normalized coordinates and is rebuilt every
+//! frame; with a few hundred nodes this is cheap compared to the rest of the map.
+
+use eframe::egui;
+use egui::Color32;
+
+use super::AppState;
+
+/// Grid cells along each axis of the world.
+const GRID_CELLS: usize = 48;
+
+/// Standard deviation of the kernel in cells.
+const KERNEL_SIGMA: f64 = 1.5;
+
+/// Cells below this share of the hottest cell are not drawn.
+const MIN_VISIBLE_HEAT: f32 = 0.05;
+
+/// Collision heat grid (row-major, `cells` × `cells`), scaled so the hottest cell is 1.
+///
+/// # Parameters
+///
+/// * `points` - Normalized positions (0..1 on both axes) with their collision counts
+/// * `cells` - Grid cells along each axis
+///
+/// # Returns
+///
+/// The grid, or `None` if there are no collisions.
+pub fn collision_heat(points: &[((f64, f64), u64)], cells: usize) -> Option<Vec<f32>> {
+    let mut grid = vec![0.0f64; cells * cells];
+    let reach = (KERNEL_SIGMA * 3.0).ceil() as i64;
+    for &((tx, ty), collisions) in points.iter().filter(|(_, count)| *count > 0) {
+        let cx = tx * cells as f64;
+        let cy = ty * cells as f64;
+        let (col, row) = (cx.floor() as i64, cy.floor() as i64);
+        for r in (row - reach).max(0)..=(row + reach).min(cells as i64 - 1) {
+            for c in (col - reach).max(0)..=(col + reach).min(cells as i64 - 1) {
+                let dx = c as f64 + 0.5 - cx;
+                let dy = r as f64 + 0.5 - cy;
+                let weight = (-(dx * dx + dy * dy) / (2.0 * KERNEL_SIGMA * KERNEL_SIGMA)).exp();
+                grid[r as usize * cells + c as usize] += collisions as f64 * weight;
+            }
+        }
+    }
+    let max = grid.iter().copied().fold(0.0, f64::max);
+    (max > 0.0).then(|| grid.into_iter().map(|heat| (heat / max) as f32).collect())
+}
+
+/// Heat color: transparent yellow for cool cells to opaque red for the hottest.
+fn heat_color(heat: f32) -> Color32 {
+    let green = (220.0 * (1.0 - heat)) as u8;
+    let alpha = (40.0 + 150.0 * heat) as u8;
+    Color32::from_rgba_unmultiplied(255, green, 0, alpha)
+}
+
+/// Draw the collision heat overlay and its legend.
+///
+/// # Parameters
+///
+/// * `painter` - egui painter for drawing primitives (clipped to the visible map)
+/// * `rect` - The screen-space rectangle representing the (zoomed) world
+/// * `state` - Application state (nodes, statistics, world bounds)
+pub fn draw(painter: &egui::Painter, rect: egui::Rect, state: &AppState) {
+    let points: Vec<((f64, f64), u64)> = state
+        .nodes
+        .iter()
+        .map(|node| {
+            let collisions = state
+                .node_statistics
+                .get(&node.node_id)
+                .map_or(0, |statistics| statistics.collisions);
+            (
+                state.world.normalize(node.position.x, node.position.y),
+                collisions,
+            )
+        })
+        .collect();
+    let total: u64 = points.iter().map(|(_, count)| count).sum();
+    let legend_pos = painter.clip_rect().left_top() + egui::vec2(8.0, 8.0);
+    let Some(grid) = collision_heat(&points, GRID_CELLS) else {
+        painter.text(
+            legend_pos,
+            egui::Align2::LEFT_TOP,
+            "Collision map: no collisions yet",
+            egui::FontId::proportional(13.0),
+            Color32::WHITE,
+        );
+        return;
+    };
+
+    let cell_size = egui::vec2(
+        rect.width() / GRID_CELLS as f32,
+        rect.height() / GRID_CELLS as f32,
+    );
+    for (index, heat) in grid.iter().enumerate() {
+        if *heat < MIN_VISIBLE_HEAT {
+            continue;
+        }
+        let min = rect.min
+            + egui::vec2(
+                (index % GRID_CELLS) as f32 * cell_size.x,
+                (index / GRID_CELLS) as f32 * cell_size.y,
+            );
+        painter.rect_filled(
+            egui::Rect::from_min_size(min, cell_size),
+            0.0,
+            heat_color(*heat),
+        );
+    }
+
+    let hottest = state
+        .node_statistics
+        .iter()
+        .max_by_key(|(node_id, statistics)| (statistics.collisions, std::cmp::Reverse(**node_id)));
+    let legend = match hottest {
+        Some((node_id, statistics)) => format!(
+            "Collision map: {} collisions, most at node #{} ({})",
+            total, node_id, statistics.collisions
+        ),
+        None => format!("Collision map: {} collisions", total),
+    };
+    painter.text(
+        legend_pos,
+        egui::Align2::LEFT_TOP,
+        legend,
+        egui::FontId::proportional(13.0),
+        Color32::WHITE,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heat_peaks_at_the_busiest_receiver() {
+        assert!(collision_heat(&[((0.5, 0.5), 0)], 10).is_none());
+
+        let grid = collision_heat(&[((0.05, 0.05), 1), ((0.75, 0.25), 9)], 10).unwrap();
+        let hottest = grid
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(index, _)| index)
+            .unwrap();
+        assert_eq!(hottest, 2 * 10 + 7);
+        assert_eq!(grid[hottest], 1.0);
+        assert!(grid[0] > 0.0 && grid[0] < 0.2);
+        assert_eq!(grid[90], 0.0);
+    }
+}
@@ -0,0 +1,454 @@
+//! # Command Palette and Keyboard Navigation
+//!
+//! Keyboard access to the actions otherwise reached with the mouse:
+//!
+//! - **Command palette** (Ctrl+P / Cmd+P): a filterable list of the actions available
+//!   in the current mode (load a scene, start a measurement on the selected node,
+//!   toggle overlays and windows, set the speed). Typing filters by the letters of
+//!   the action name in order ("nbg" finds "Toggle neighbor graph"); a number sets the
+//!   speed ("speed 250" or just "250"). Up/Down pick an action, Enter runs it, Escape
+//!   closes the palette.
+//! - **Shortcuts** (ignored while a text field has focus): N / Shift+N select the next
+//!   / previous node by ID, 1-7 switch the inspector tab, Ctrl+T toggles the node
+//!   table, Ctrl+M starts a measurement on the selected node.
+
+use eframe::egui;
+
+use super::app_state::InspectorTab;
+use super::{AppState, MeasurementKind, OperatingMode};
+
+/// Speed range of the speed slider (percent).
+const SPEED_RANGE: std::ops::RangeInclusive<u32> = 20..=1000;
+
+/// An action of the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PaletteAction {
+    LoadScene,
+    CompareScenes,
+    StartMeasurement,
+    StartTransactionMeasurement,
+    NextNode,
+    PreviousNode,
+    ToggleNodeIds,
+    ToggleNeighborGraph,
+    ToggleCollisionMap,
+    ToggleDeliveryFlow,
+    ToggleSilentNodes,
+    ToggleNodeTable,
+    ToggleAutoSpeed,
+    OpenAlertRules,
+    OpenMapStyle,
+    InspectorTab(InspectorTab),
+    SetSpeed(u32),
+}
+
+impl PaletteAction {
+    fn label(self) -> String {
+        match self {
+            PaletteAction::LoadScene => "Load scene (Simulation)".to_string(),
+            PaletteAction::CompareScenes => "Compare scenes".to_string(),
+            PaletteAction::StartMeasurement => "Start measurement on selected node".to_string(),
+            PaletteAction::StartTransactionMeasurement => {
+                "Start transaction measurement on selected node".to_string()
+            }
+            PaletteAction::NextNode => "Select next node".to_string(),
+            PaletteAction::PreviousNode => "Select previous node".to_string(),
+            PaletteAction::ToggleNodeIds => "Toggle node IDs".to_string(),
+            PaletteAction::ToggleNeighborGraph => "Toggle neighbor graph".to_string(),
+            PaletteAction::ToggleCollisionMap => "Toggle collision map".to_string(),
+            PaletteAction::ToggleDeliveryFlow => "Toggle deliveries".to_string(),
+            PaletteAction::ToggleSilentNodes => "Toggle silent nodes".to_string(),
+            PaletteAction::ToggleNodeTable => "Toggle node table".to_string(),
+            PaletteAction::ToggleAutoSpeed => "Toggle auto speed".to_string(),
+            PaletteAction::OpenAlertRules => "Open alert rules".to_string(),
+            PaletteAction::OpenMapStyle => "Open map style".to_string(),
+            PaletteAction::InspectorTab(tab) => format!("Inspector: {}", tab_name(tab)),
+            PaletteAction::SetSpeed(percent) => format!("Set speed to {}%", percent),
+        }
+    }
+
+    /// Keyboard shortcut hint shown next to the label.
+    fn shortcut(self) -> &'static str {
+        match self {
+            PaletteAction::StartMeasurement => "Ctrl+M",
+            PaletteAction::NextNode => "N",
+            PaletteAction::PreviousNode => "Shift+N",
+            PaletteAction::ToggleNodeTable => "Ctrl+T",
+            PaletteAction::InspectorTab(tab) => match tab {
+                InspectorTab::RadioStream => "1",
+                InspectorTab::MessageStream => "2",
+                InspectorTab::LogStream => "3",
+                InspectorTab::ConnectionMatrix => "4",
+                InspectorTab::ProtocolState => "5",
+                InspectorTab::LinkStats => "6",
+                InspectorTab::Waterfall => "7",
+            },
+            _ => "",
+        }
+    }
+}
+
+fn tab_name(tab: InspectorTab) -> &'static str {
+    match tab {
+        InspectorTab::RadioStream => "Radio Stream",
+        InspectorTab::MessageStream => "Message Stream",
+        InspectorTab::LogStream => "Log Stream",
+        InspectorTab::ConnectionMatrix => "Connection Matrix",
+        InspectorTab::ProtocolState => "Protocol State",
+        InspectorTab::LinkStats => "Link Stats",
+        InspectorTab::Waterfall => "Waterfall",
+    }
+}
+
+/// View state of the command palette.
+#[derive(Debug, Clone, Default)]
+pub struct CommandPaletteState {
+    /// Whether the palette is open.
+    pub open: bool,
+    /// Filter text.
+    query: String,
+    /// Index of the highlighted action in the filtered list.
+    highlighted: usize,
+}
+
+/// Actions available in the current state, in display order.
+fn available_actions(state: &AppState) -> Vec<PaletteAction> {
+    if !state.mode_selected {
+        return vec![PaletteAction::LoadScene, PaletteAction::CompareScenes];
+    }
+    let mode = state.operating_mode;
+    let mut actions = Vec::new();
+    if mode != OperatingMode::LogVisualization && state.selected.is_some() {
+        actions.push(PaletteAction::StartMeasurement);
+    }
+    if mode == OperatingMode::Simulation && state.selected.is_some() {
+        actions.push(PaletteAction::StartTransactionMeasurement);
+    }
+    actions.extend([
+        PaletteAction::NextNode,
+        PaletteAction::PreviousNode,
+        PaletteAction::ToggleNodeIds,
+        PaletteAction::ToggleNeighborGraph,
+        PaletteAction::ToggleCollisionMap,
+        PaletteAction::ToggleDeliveryFlow,
+    ]);
+    if mode == OperatingMode::RealtimeTracking {
+        actions.push(PaletteAction::ToggleSilentNodes);
+    }
+    actions.push(PaletteAction::ToggleNodeTable);
+    if mode != OperatingMode::RealtimeTracking {
+        actions.push(PaletteAction::ToggleAutoSpeed);
+    }
+    actions.push(PaletteAction::OpenAlertRules);
+    actions.push(PaletteAction::OpenMapStyle);
+    actions.extend(
+        [
+            InspectorTab::RadioStream,
+            InspectorTab::MessageStream,
+            InspectorTab::LogStream,
+        ]
+        .map(PaletteAction::InspectorTab),
+    );
+    if mode != OperatingMode::LogVisualization {
+        actions.push(PaletteAction::InspectorTab(InspectorTab::ConnectionMatrix));
+    }
+    if mode == OperatingMode::Simulation {
+        actions.push(PaletteAction::InspectorTab(InspectorTab::ProtocolState));
+        actions.push(PaletteAction::InspectorTab(InspectorTab::LinkStats));
+    }
+    actions.push(PaletteAction::InspectorTab(InspectorTab::Waterfall));
+    if mode != OperatingMode::RealtimeTracking {
+        actions.extend([100, 200, 500, 1000].map(PaletteAction::SetSpeed));
+    }
+    actions
+}
+
+/// Whether the letters of `query` appear in `label` in order (case-insensitive).
+fn fuzzy_match(label: &str, query: &str) -> bool {
+    let mut label = label.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|wanted| label.any(|c| c == wanted))
+}
+
+/// Speed typed into the palette ("250", "speed 250" or "250%"), within the slider range.
+fn parse_speed(query: &str) -> Option<u32> {
+    let query = query.trim().to_lowercase();
+    let number = query
+        .strip_prefix("speed")
+        .unwrap_or(&query)
+        .trim()
+        .trim_end_matches('%');
+    number
+        .parse::<u32>()
+        .ok()
+        .filter(|s| SPEED_RANGE.contains(s))
+}
+
+/// Actions matching the query: a typed speed first, then the matching actions.
+fn filtered_actions(available: &[PaletteAction], query: &str) -> Vec<PaletteAction> {
+    let speed_available = available
+        .iter()
+        .any(|action| matches!(action, PaletteAction::SetSpeed(_)));
+    let typed_speed = parse_speed(query)
+        .filter(|_| speed_available)
+        .map(PaletteAction::SetSpeed);
+    typed_speed
+        .into_iter()
+        .chain(
+            available
+                .iter()
+                .copied()
+                .filter(|action| Some(*action) != typed_speed)
+                .filter(|action| fuzzy_match(&action.label(), query)),
+        )
+        .collect()
+}
+
+/// Select the next (or previous) node by ID, wrapping around.
+fn cycle_node(state: &mut AppState, forward: bool) {
+    let mut node_ids: Vec<u32> = state.nodes.iter().map(|n| n.node_id).collect();
+    node_ids.sort_unstable();
+    if node_ids.is_empty() {
+        return;
+    }
+    let current = state
+        .selected
+        .and_then(|i| state.nodes.get(i))
+        .and_then(|node| node_ids.iter().position(|id| *id == node.node_id));
+    let next = match (current, forward) {
+        (None, true) => 0,
+        (None, false) => node_ids.len() - 1,
+        (Some(i), true) => (i + 1) % node_ids.len(),
+        (Some(i), false) => (i + node_ids.len() - 1) % node_ids.len(),
+    };
+    state.select_node(node_ids[next]);
+}
+
+/// Run a palette action.
+fn apply(state: &mut AppState, action: PaletteAction) {
+    match action {
+        PaletteAction::LoadScene => {
+            state.mode_selected = true;
+            state.operating_mode = OperatingMode::Simulation;
+            state.open_file_selector();
+        }
+        PaletteAction::CompareScenes => super::scene_diff::pick_and_compare(state),
+        PaletteAction::StartMeasurement | PaletteAction::StartTransactionMeasurement => {
+            let kind = if action == PaletteAction::StartMeasurement {
+                MeasurementKind::Block
+            } else {
+                MeasurementKind::Transaction
+            };
+            if let Some(node_id) = state
+                .selected
+                .and_then(|i| state.nodes.get(i))
+                .map(|n| n.node_id)
+            {
+                state.start_measurement(node_id, kind);
+            }
+        }
+        PaletteAction::NextNode => cycle_node(state, true),
+        PaletteAction::PreviousNode => cycle_node(state, false),
+        PaletteAction::ToggleNodeIds => state.show_node_ids = !state.show_node_ids,
+        PaletteAction::ToggleNeighborGraph => {
+            state.show_neighbor_graph = !state.show_neighbor_graph
+        }
+        PaletteAction::ToggleCollisionMap => state.show_collision_map = !state.show_collision_map,
+        PaletteAction::ToggleDeliveryFlow => state.show_delivery_flow = !state.show_delivery_flow,
+        PaletteAction::ToggleSilentNodes => state.show_silent_nodes = !state.show_silent_nodes,
+        PaletteAction::ToggleNodeTable => state.node_table.open = !state.node_table.open,
+        PaletteAction::ToggleAutoSpeed => {
+            state.auto_speed_enabled = !state.auto_speed_enabled;
+            state
+                .ui_command_tx
+                .try_send(super::UICommand::SetAutoSpeed(state.auto_speed_enabled))
+                .ok();
+        }
+        PaletteAction::OpenAlertRules => state.alert_monitor.show_rules_window = true,
+        PaletteAction::OpenMapStyle => state.show_map_style = true,
+        PaletteAction::InspectorTab(tab) => state.inspector_tab = tab,
+        PaletteAction::SetSpeed(percent) => {
+            state.speed_percent = percent;
+            crate::time_driver::set_simulation_speed_percent(percent);
+        }
+    }
+}
+
+/// Handle the palette and navigation shortcuts.
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn handle_shortcuts(ctx: &egui::Context, state: &mut AppState) {
+    let shortcut = |modifiers, key| egui::KeyboardShortcut::new(modifiers, key);
+    if ctx.input_mut(|input| {
+        input.consume_shortcut(&shortcut(egui::Modifiers::COMMAND, egui::Key::P))
+    }) {
+        let palette = &mut state.command_palette;
+        palette.open = !palette.open;
+        palette.query.clear();
+        palette.highlighted = 0;
+        return;
+    }
+    if !state.mode_selected || ctx.wants_keyboard_input() {
+        return;
+    }
+
+    let available = available_actions(state);
+    let action = ctx.input_mut(|input| {
+        let tab_keys = [
+            (egui::Key::Num1, InspectorTab::RadioStream),
+            (egui::Key::Num2, InspectorTab::MessageStream),
+            (egui::Key::Num3, InspectorTab::LogStream),
+            (egui::Key::Num4, InspectorTab::ConnectionMatrix),
+            (egui::Key::Num5, InspectorTab::ProtocolState),
+            (egui::Key::Num6, InspectorTab::LinkStats),
+            (egui::Key::Num7, InspectorTab::Waterfall),
+        ];
+        // Check Shift+N first: the plain N shortcut would also match it
+        if input.consume_shortcut(&shortcut(egui::Modifiers::SHIFT, egui::Key::N)) {
+            Some(PaletteAction::PreviousNode)
+        } else if input.consume_shortcut(&shortcut(egui::Modifiers::NONE, egui::Key::N)) {
+            Some(PaletteAction::NextNode)
+        } else if input.consume_shortcut(&shortcut(egui::Modifiers::COMMAND, egui::Key::T)) {
+            Some(PaletteAction::ToggleNodeTable)
+        } else if input.consume_shortcut(&shortcut(egui::Modifiers::COMMAND, egui::Key::M)) {
+            Some(PaletteAction::StartMeasurement)
+        } else {
+            tab_keys.into_iter().find_map(|(key, tab)| {
+                input
+                    .consume_shortcut(&shortcut(egui::Modifiers::NONE, key))
+                    .then_some(PaletteAction::InspectorTab(tab))
+            })
+        }
+    });
+    if let Some(action) = action.filter(|action| available.contains(action)) {
+        apply(state, action);
+    }
+}
+
+/// Render the command palette (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.command_palette.open {
+        return;
+    }
+    let available = available_actions(state);
+    let actions = filtered_actions(&available, &state.command_palette.query);
+    let palette = &mut state.command_palette;
+    palette.highlighted = palette.highlighted.min(actions.len().saturating_sub(1));
+
+    let (up, down, enter, escape) = ctx.input_mut(|input| {
+        (
+            input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            input.key_pressed(egui::Key::Enter),
+            input.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        )
+    });
+    if up {
+        palette.highlighted = palette.highlighted.saturating_sub(1);
+    }
+    if down && palette.highlighted + 1 < actions.len() {
+        palette.highlighted += 1;
+    }
+
+    let mut chosen = enter
+        .then(|| actions.get(palette.highlighted).copied())
+        .flatten();
+    egui::Window::new("Command palette")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Type a command or a speed (e.g. 250)")
+                    .desired_width(f32::INFINITY),
+            );
+            if field.changed() {
+                palette.highlighted = 0;
+            }
+            field.request_focus();
+            ui.separator();
+            if actions.is_empty() {
+                ui.label("No matching command");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    for (index, action) in actions.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let row =
+                                ui.selectable_label(index == palette.highlighted, action.label());
+                            if index == palette.highlighted {
+                                row.scroll_to_me(None);
+                            }
+                            if row.clicked() {
+                                chosen = Some(*action);
+                            }
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.weak(action.shortcut());
+                                },
+                            );
+                        });
+                    }
+                });
+        });
+
+    if escape || chosen.is_some() {
+        state.command_palette = CommandPaletteState::default();
+    }
+    if let Some(action) = chosen {
+        apply(state, action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_filters_actions_and_parses_speed() {
+        assert!(fuzzy_match("Toggle neighbor graph", "nbg"));
+        assert!(fuzzy_match("Toggle neighbor graph", "Neighbor Graph"));
+        assert!(!fuzzy_match("Toggle node IDs", "graph"));
+
+        assert_eq!(parse_speed("speed 250"), Some(250));
+        assert_eq!(parse_speed("400%"), Some(400));
+        assert_eq!(parse_speed("5"), None);
+        assert_eq!(parse_speed("node"), None);
+
+        let available = [
+            PaletteAction::ToggleNodeIds,
+            PaletteAction::ToggleNeighborGraph,
+            PaletteAction::SetSpeed(100),
+        ];
+        assert_eq!(
+            filtered_actions(&available, "graph"),
+            vec![PaletteAction::ToggleNeighborGraph]
+        );
+        assert_eq!(
+            filtered_actions(&available, "100"),
+            vec![PaletteAction::SetSpeed(100)]
+        );
+        assert_eq!(
+            filtered_actions(&available, "speed 300"),
+            vec![PaletteAction::SetSpeed(300)]
+        );
+        assert!(filtered_actions(&available[..2], "300").is_empty());
+        assert_eq!(filtered_actions(&available, "").len(), 3);
+    }
+}
@@ -0,0 +1,197 @@
+//! # Selected Node Delivery Flow
+//!
+//! The radio range circle of the selected node shows where it could be heard under
+//! ideal conditions, which misleads in shadowed or congested spots. With "Deliveries"
+//! enabled, the map instead highlights the nodes that actually decoded the selected
+//! node's transmissions recently: a line and a ring per receiver, fading with the age
+//! of the last delivery and thicker for more decoded packets, and a pulse running from the sender to the receiver for
+//! fresh deliveries.
+//!
+//! Deliveries are reported per decoded packet (`UIRefreshState::PacketDelivered`):
+//! every successful reception in Simulation mode and every logged packet reception
+//! in the analyzer modes. They are kept for every sender, so selecting another node
+//! shows its receivers immediately. Deliveries older than `DELIVERY_WINDOW` (virtual
+//! time) are dropped. Collisions and packets missed while transmitting are not
+//! deliveries.
+
+use eframe::egui;
+use egui::Color32;
+use embassy_time::{Duration, Instant};
+use std::collections::HashMap;
+
+use super::AppState;
+use super::map::MapTransform;
+
+/// Deliveries older than this are not shown.
+pub const DELIVERY_WINDOW: Duration = Duration::from_secs(10);
+
+/// Duration of the pulse running to the receiver after a delivery.
+const PULSE_DURATION: Duration = Duration::from_millis(800);
+
+/// Recent deliveries from a sender to a receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delivery {
+    /// Time of the latest delivery.
+    pub last: Instant,
+    /// Deliveries since the last gap longer than the window.
+    pub count: u32,
+}
+
+/// Recent deliveries of every sender: sender ID -> receiver ID -> deliveries.
+#[derive(Debug, Clone, Default)]
+pub struct RecentDeliveries {
+    by_sender: HashMap<u32, HashMap<u32, Delivery>>,
+}
+
+impl RecentDeliveries {
+    /// Record a packet of `sender` decoded by `receiver` at `now`.
+    pub fn record(&mut self, sender: u32, receiver: u32, now: Instant) {
+        let receivers = self.by_sender.entry(sender).or_default();
+        receivers.retain(|_, delivery| !expired(delivery, now));
+        receivers
+            .entry(receiver)
+            .and_modify(|delivery| {
+                delivery.last = now;
+                delivery.count += 1;
+            })
+            .or_insert(Delivery {
+                last: now,
+                count: 1,
+            });
+    }
+
+    /// Receivers of `sender` with a delivery within the window, ordered by node ID.
+    pub fn receivers_of(&self, sender: u32, now: Instant) -> Vec<(u32, Delivery)> {
+        let mut receivers: Vec<(u32, Delivery)> = self
+            .by_sender
+            .get(&sender)
+            .into_iter()
+            .flatten()
+            .filter(|(_, delivery)| !expired(delivery, now))
+            .map(|(receiver, delivery)| (*receiver, *delivery))
+            .collect();
+        receivers.sort_by_key(|(receiver, _)| *receiver);
+        receivers
+    }
+
+    /// Forget all deliveries (scene load).
+    pub fn clear(&mut self) {
+        self.by_sender.clear();
+    }
+}
+
+/// Whether the latest delivery is older than the window.
+fn expired(delivery: &Delivery, now: Instant) -> bool {
+    now.saturating_duration_since(delivery.last) > DELIVERY_WINDOW
+}
+
+/// Draw the recent receivers of the selected node.
+///
+/// # Parameters
+///
+/// * `painter` - egui painter
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Application state (selection, node positions and deliveries)
+pub fn draw(painter: &egui::Painter, rect: egui::Rect, state: &AppState) {
+    let Some(sender) = state.selected.and_then(|index| state.nodes.get(index)) else {
+        return;
+    };
+    let now = Instant::now();
+    let receivers = state.recent_deliveries.receivers_of(sender.node_id, now);
+    let transform = MapTransform::new(state.world, rect);
+    let start = transform.world_to_screen(sender.position.x, sender.position.y);
+    let positions: HashMap<u32, egui::Pos2> = state
+        .nodes
+        .iter()
+        .map(|node| {
+            (
+                node.node_id,
+                transform.world_to_screen(node.position.x, node.position.y),
+            )
+        })
+        .collect();
+
+    for (receiver, delivery) in &receivers {
+        let Some(end) = positions.get(receiver).copied() else {
+            continue;
+        };
+        let age = now.saturating_duration_since(delivery.last);
+        // Fade from fully opaque to a faint trace over the window
+        let freshness = 1.0 - age.as_millis() as f32 / DELIVERY_WINDOW.as_millis() as f32;
+        let alpha = (60.0 + 195.0 * freshness.clamp(0.0, 1.0)) as u8;
+        let color = Color32::from_rgba_unmultiplied(0, 200, 255, alpha);
+        painter.line_segment([start, end], egui::Stroke::new(1.5, color));
+        // Receivers decoding more packets get a thicker ring
+        let ring_width = 1.0 + delivery.count.min(4) as f32 * 0.5;
+        painter.circle_stroke(end, 7.0, egui::Stroke::new(ring_width, color));
+        if age < PULSE_DURATION {
+            let progress = age.as_millis() as f32 / PULSE_DURATION.as_millis() as f32;
+            painter.circle_filled(start.lerp(end, progress), 3.0, Color32::WHITE);
+        }
+    }
+
+    painter.text(
+        start + egui::vec2(10.0, -10.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!(
+            "{} receivers in {} s",
+            receivers.len(),
+            DELIVERY_WINDOW.as_secs()
+        ),
+        egui::FontId::proportional(12.0),
+        Color32::from_rgb(0, 200, 255),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_receivers_expire_after_the_window() {
+        let start = Instant::from_secs(100);
+        let mut deliveries = RecentDeliveries::default();
+        deliveries.record(1, 3, start);
+        deliveries.record(1, 2, start + Duration::from_secs(2));
+        deliveries.record(1, 3, start + Duration::from_secs(4));
+        deliveries.record(2, 1, start + Duration::from_secs(4));
+
+        let receivers = deliveries.receivers_of(1, start + Duration::from_secs(5));
+        assert_eq!(
+            receivers,
+            vec![
+                (
+                    2,
+                    Delivery {
+                        last: start + Duration::from_secs(2),
+                        count: 1
+                    }
+                ),
+                (
+                    3,
+                    Delivery {
+                        last: start + Duration::from_secs(4),
+                        count: 2
+                    }
+                ),
+            ]
+        );
+        assert!(deliveries.receivers_of(5, start).is_empty());
+
+        // Node 2 expires first; a new delivery to it starts a new count
+        let later = start + Duration::from_secs(13);
+        assert_eq!(
+            deliveries
+                .receivers_of(1, later)
+                .iter()
+                .map(|(receiver, _)| *receiver)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+        deliveries.record(1, 2, later);
+        assert_eq!(deliveries.receivers_of(1, later)[0].1.count, 1);
+
+        deliveries.clear();
+        assert!(deliveries.receivers_of(1, later).is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+//! # Edit History (Undo/Redo)
+//!
+//! Interactive modifications of the running simulation are recorded as `Edit`
+//! commands so experiments can be rolled back without reloading the scene:
+//! - obstacle edits (move, resize, add, delete) from the obstacle editor
+//! - node moves from the map (with "Move nodes" enabled)
+//! - switching a node's radio on or off in the inspector
+//! - TX power and position changes in the inspector
+//!
+//! Undo applies the inverse of the latest edit and moves it to the redo stack;
+//! any new edit clears the redo stack. Applying an edit updates the UI state
+//! immediately and sends the matching `UICommand` to the network task. The
+//! history is cleared when a scene is loaded.
+//!
+//! Shortcuts: Ctrl+Z undoes, Ctrl+Shift+Z and Ctrl+Y redo (Cmd on macOS).
+
+use eframe::egui;
+
+use super::{AppState, UICommand};
+use crate::simulation::{Obstacle, Point};
+
+/// Maximum number of edits kept for undo.
+const MAX_HISTORY: usize = 100;
+
+/// A reversible modification of the running simulation.
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// The obstacle list was replaced.
+    Obstacles {
+        before: Vec<Obstacle>,
+        after: Vec<Obstacle>,
+    },
+    /// A node was moved.
+    MoveNode {
+        node_id: u32,
+        from: Point,
+        to: Point,
+    },
+    /// A node's radio was switched on (`enabled`) or off.
+    SetNodeEnabled { node_id: u32, enabled: bool },
+    /// A node's TX power (dBm) was changed.
+    SetTxPower { node_id: u32, from: f32, to: f32 },
+}
+
+impl Edit {
+    /// The edit that reverts this one.
+    fn inverse(&self) -> Edit {
+        match self {
+            Edit::Obstacles { before, after } => Edit::Obstacles {
+                before: after.clone(),
+                after: before.clone(),
+            },
+            Edit::MoveNode { node_id, from, to } => Edit::MoveNode {
+                node_id: *node_id,
+                from: to.clone(),
+                to: from.clone(),
+            },
+            Edit::SetNodeEnabled { node_id, enabled } => Edit::SetNodeEnabled {
+                node_id: *node_id,
+                enabled: !enabled,
+            },
+            Edit::SetTxPower { node_id, from, to } => Edit::SetTxPower {
+                node_id: *node_id,
+                from: *to,
+                to: *from,
+            },
+        }
+    }
+
+    /// Short description for the undo/redo button tooltips.
+    fn description(&self) -> String {
+        match self {
+            Edit::Obstacles { .. } => "obstacle edit".to_string(),
+            Edit::MoveNode { node_id, .. } => format!("move of node #{}", node_id),
+            Edit::SetNodeEnabled {
+                node_id,
+                enabled: true,
+            } => format!("enabling node #{}", node_id),
+            Edit::SetNodeEnabled {
+                node_id,
+                enabled: false,
+            } => format!("disabling node #{}", node_id),
+            Edit::SetTxPower { node_id, to, .. } => {
+                format!("TX power {:.1} dBm of node #{}", to, node_id)
+            }
+        }
+    }
+}
+
+/// Undo and redo stacks of edits.
+#[derive(Debug, Clone, Default)]
+pub struct EditHistory {
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+}
+
+impl EditHistory {
+    /// Record an edit that was just applied (clears the redo stack).
+    pub fn record(&mut self, edit: Edit) {
+        self.redo_stack.clear();
+        self.undo_stack.push(edit);
+        if self.undo_stack.len() > MAX_HISTORY {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// Take the latest edit for undo.
+    ///
+    /// # Returns
+    ///
+    /// The edit to apply to revert it (the inverse), or `None` if there is nothing to undo.
+    fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo_stack.pop()?;
+        let inverse = edit.inverse();
+        self.redo_stack.push(edit);
+        Some(inverse)
+    }
+
+    /// Take the latest undone edit for redo.
+    ///
+    /// # Returns
+    ///
+    /// The edit to apply again, or `None` if there is nothing to redo.
+    fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo_stack.pop()?;
+        self.undo_stack.push(edit.clone());
+        Some(edit)
+    }
+
+    /// Forget all edits (e.g. when a new scene is loaded).
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+/// Apply an edit to the UI state and send it to the network task.
+fn apply(state: &mut AppState, edit: Edit) {
+    match edit {
+        Edit::Obstacles { after, .. } => {
+            state.obstacles = after;
+            state.obstacle_editor.selected = None;
+            state
+                .ui_command_tx
+                .try_send(UICommand::UpdateObstacles(state.obstacles.clone()))
+                .ok();
+        }
+        Edit::MoveNode { node_id, to, .. } => {
+            if let Some(node) = state.nodes.iter_mut().find(|n| n.node_id == node_id) {
+                node.position = to.clone();
+            }
+            state.node_shapes.invalidate();
+            state
+                .ui_command_tx
+                .try_send(UICommand::MoveNode(node_id, to))
+                .ok();
+        }
+        Edit::SetNodeEnabled { node_id, enabled } => {
+            if let Some(node) = state.nodes.iter_mut().find(|n| n.node_id == node_id) {
+                node.disabled = !enabled;
+            }
+            state.node_shapes.invalidate();
+            state
+                .ui_command_tx
+                .try_send(UICommand::SetNodeEnabled(node_id, enabled))
+                .ok();
+        }
+        Edit::SetTxPower { node_id, to, .. } => {
+            // The range follows with the backend's NodeUpdated
+            if let Some(node) = state.nodes.iter_mut().find(|n| n.node_id == node_id) {
+                node.tx_power = Some(to);
+            }
+            state
+                .ui_command_tx
+                .try_send(UICommand::SetNodeTxPower(node_id, to))
+                .ok();
+        }
+    }
+    // The edit changes the link budgets of the probed location and the neighbor graph
+    state.signal_probe.invalidate();
+    state.neighbor_graph.invalidate();
+}
+
+/// Apply a new edit and record it for undo.
+///
+/// # Parameters
+///
+/// * `state` - Mutable application state
+/// * `edit` - The edit to apply
+pub fn perform(state: &mut AppState, edit: Edit) {
+    state.edit_history.record(edit.clone());
+    apply(state, edit);
+}
+
+/// Revert the latest edit (if any).
+pub fn undo(state: &mut AppState) {
+    if let Some(edit) = state.edit_history.undo() {
+        apply(state, edit);
+    }
+}
+
+/// Re-apply the latest undone edit (if any).
+pub fn redo(state: &mut AppState) {
+    if let Some(edit) = state.edit_history.redo() {
+        apply(state, edit);
+    }
+}
+
+/// Render the Undo and Redo buttons.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context (controls column of the top panel)
+/// * `state` - Mutable application state
+pub fn render_buttons(ui: &mut egui::Ui, state: &mut AppState) {
+    let undo_hint = state
+        .edit_history
+        .undo_stack
+        .last()
+        .map(|edit| format!("Undo {} (Ctrl+Z)", edit.description()));
+    let redo_hint = state
+        .edit_history
+        .redo_stack
+        .last()
+        .map(|edit| format!("Redo {} (Ctrl+Shift+Z)", edit.description()));
+
+    let undo_button = ui
+        .add_enabled(undo_hint.is_some(), egui::Button::new("⟲ Undo"))
+        .on_hover_text(undo_hint.unwrap_or_else(|| "Nothing to undo".to_string()));
+    if undo_button.clicked() {
+        undo(state);
+    }
+    let redo_button = ui
+        .add_enabled(redo_hint.is_some(), egui::Button::new("⟳ Redo"))
+        .on_hover_text(redo_hint.unwrap_or_else(|| "Nothing to redo".to_string()));
+    if redo_button.clicked() {
+        redo(state);
+    }
+}
+
+/// Handle the undo/redo keyboard shortcuts (ignored while a text field has focus).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn handle_shortcuts(ctx: &egui::Context, state: &mut AppState) {
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    let (undo_pressed, redo_pressed) = ctx.input_mut(|input| {
+        // Check Shift+Z first: the plain Ctrl+Z shortcut would also match it
+        let redo = input.consume_shortcut(&egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::Z,
+        )) || input.consume_shortcut(&egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND,
+            egui::Key::Y,
+        ));
+        let undo = input.consume_shortcut(&egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND,
+            egui::Key::Z,
+        ));
+        (undo, redo)
+    });
+    if redo_pressed {
+        redo(state);
+    } else if undo_pressed {
+        undo(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn move_edit(node_id: u32, from: f64, to: f64) -> Edit {
+        Edit::MoveNode {
+            node_id,
+            from: Point { x: from, y: from },
+            to: Point { x: to, y: to },
+        }
+    }
+
+    fn target_x(edit: &Edit) -> f64 {
+        match edit {
+            Edit::MoveNode { to, .. } => to.x,
+            other => panic!("unexpected edit {:?}", other),
+        }
+    }
+
+    #[test]
+    fn undo_returns_inverse_and_new_edit_clears_redo() {
+        let mut history = EditHistory::default();
+        history.record(move_edit(1, 10.0, 20.0));
+        history.record(move_edit(1, 20.0, 30.0));
+
+        assert_eq!(target_x(&history.undo().unwrap()), 20.0);
+        assert_eq!(target_x(&history.undo().unwrap()), 10.0);
+        assert!(history.undo().is_none());
+
+        assert_eq!(target_x(&history.redo().unwrap()), 20.0);
+        history.record(Edit::SetNodeEnabled {
+            node_id: 2,
+            enabled: false,
+        });
+        assert!(history.redo().is_none());
+        assert!(matches!(
+            history.undo(),
+            Some(Edit::SetNodeEnabled {
+                node_id: 2,
+                enabled: true
+            })
+        ));
+
+        let power = Edit::SetTxPower {
+            node_id: 3,
+            from: 14.0,
+            to: 17.0,
+        };
+        assert!(matches!(
+            power.inverse(),
+            Edit::SetTxPower {
+                node_id: 3,
+                from: 17.0,
+                to: 14.0
+            }
+        ));
+    }
+}
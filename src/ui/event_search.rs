@@ -0,0 +1,302 @@
+//! # Event Search
+//!
+//! Search across the radio events of all nodes (Simulation mode), answered by the
+//! network task's central event store (see `simulation::event_store`) rather than
+//! the bounded per-node histories of the inspector. A query combines an event
+//! kind, a message type, a node taking part in the event and a time range in
+//! seconds, e.g. all collisions of "Req blk prt" between 300 s and 600 s involving
+//! node 12. The first `MAX_QUERY_RESULTS` matches are listed with the total count;
+//! "Show" selects the event's node, centers the map on it, draws the sender →
+//! receiver arrow and narrows the inspector streams to the event's second and type.
+
+use eframe::egui;
+use embassy_time::Instant;
+
+use super::app_state::{color_for_message_type, message_type_name};
+use super::{AppState, UICommand};
+use crate::simulation::event_store::{EventKind, EventQuery, EventQueryResult, MAX_QUERY_RESULTS};
+
+/// Query form, the last answer and window visibility.
+#[derive(Debug, Clone, Default)]
+pub struct EventSearchState {
+    /// Whether the window is open.
+    pub open: bool,
+    kind: Option<EventKind>,
+    message_type: Option<u8>,
+    /// Node ID text (empty = any node).
+    node: String,
+    /// Time bounds in seconds (empty = open).
+    from: String,
+    to: String,
+    /// Whether a query is awaiting its answer.
+    pending: bool,
+    result: Option<EventQueryResult>,
+}
+
+impl EventSearchState {
+    /// Store the answer of the network task.
+    pub fn receive(&mut self, result: EventQueryResult) {
+        self.pending = false;
+        self.result = Some(result);
+    }
+
+    /// Forget the previous session's answer.
+    pub fn clear(&mut self) {
+        self.pending = false;
+        self.result = None;
+    }
+}
+
+/// Parse a time bound in seconds (`300`, `300s`, `12.5`) into milliseconds.
+fn parse_seconds(text: &str) -> Result<Option<u64>, String> {
+    let text = text.trim().trim_end_matches('s').trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse::<f64>() {
+        Ok(secs) if secs >= 0.0 => Ok(Some((secs * 1000.0).round() as u64)),
+        _ => Err(format!("Invalid time '{}'", text)),
+    }
+}
+
+/// Build the query of the form.
+///
+/// # Returns
+///
+/// The query, or a message naming the invalid field.
+fn build_query(search: &EventSearchState) -> Result<EventQuery, String> {
+    let node = search.node.trim().trim_start_matches('#');
+    let node_id = if node.is_empty() {
+        None
+    } else {
+        Some(
+            node.parse::<u32>()
+                .map_err(|_| format!("Invalid node ID '{}'", node))?,
+        )
+    };
+    let from_ms = parse_seconds(&search.from)?;
+    let to_ms = parse_seconds(&search.to)?;
+    if let (Some(from), Some(to)) = (from_ms, to_ms) {
+        if from > to {
+            return Err("The start of the time range is after its end".to_string());
+        }
+    }
+    Ok(EventQuery {
+        kinds: search.kind.into_iter().collect(),
+        message_type: search.message_type,
+        node_id,
+        from_ms,
+        to_ms,
+    })
+}
+
+/// Navigate to an event: select its node, center the map on it, draw the link and
+/// narrow the inspector streams to the event's second and message type.
+fn show_event(state: &mut AppState, node_id: u32, sender_id: u32, message_type: u8, time_ms: u64) {
+    state.select_node(node_id);
+    if let Some(node) = state.nodes.iter().find(|n| n.node_id == node_id) {
+        let (x, y) = state.world.normalize(node.position.x, node.position.y);
+        state.map_view.center_on((x as f32, y as f32));
+    }
+    if sender_id != node_id {
+        state
+            .reception_arrows
+            .record(sender_id, node_id, message_type, Instant::now());
+    }
+    let second = (time_ms / 1000).to_string();
+    state.stream_filter.time_from = second.clone();
+    state.stream_filter.time_to = second;
+    state.stream_filter.message_type = Some(message_type);
+}
+
+/// Render the event search window (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.event_search.open {
+        return;
+    }
+    let mut open = true;
+    let mut search = false;
+    let mut show: Option<(u32, u32, u8, u64)> = None;
+    let mut error: Option<String> = None;
+    let form = &mut state.event_search;
+    egui::Window::new("Event search")
+        .open(&mut open)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("event_search_kind")
+                    .selected_text(form.kind.map_or("All events", EventKind::name))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut form.kind, None, "All events");
+                        for kind in EventKind::ALL {
+                            ui.selectable_value(&mut form.kind, Some(kind), kind.name());
+                        }
+                    });
+                egui::ComboBox::from_id_salt("event_search_type")
+                    .selected_text(form.message_type.map_or("All types", message_type_name))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut form.message_type, None, "All types");
+                        for message_type in 1..=9 {
+                            ui.selectable_value(
+                                &mut form.message_type,
+                                Some(message_type),
+                                message_type_name(message_type),
+                            );
+                        }
+                    });
+                ui.label("Node:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.node)
+                        .hint_text("any")
+                        .desired_width(50.0),
+                );
+                ui.label("Time:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.from)
+                        .hint_text("from s")
+                        .desired_width(55.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.to)
+                        .hint_text("to s")
+                        .desired_width(55.0),
+                );
+                search = ui
+                    .add_enabled(!form.pending, egui::Button::new("Search"))
+                    .clicked();
+            });
+            ui.separator();
+
+            let Some(result) = &form.result else {
+                ui.label(if form.pending {
+                    "Searching…"
+                } else {
+                    "Set the filters and press Search."
+                });
+                return;
+            };
+            if result.total > result.events.len() {
+                ui.label(format!(
+                    "{} matching events, showing the first {}",
+                    result.total, MAX_QUERY_RESULTS
+                ));
+            } else {
+                ui.label(format!("{} matching events", result.total));
+            }
+            if let Some(oldest) = result.oldest_ms {
+                if oldest > 0 {
+                    ui.weak(format!(
+                        "Events before {:.1} s were evicted from the store",
+                        oldest as f64 / 1000.0
+                    ));
+                }
+            }
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("event_search_results")
+                        .striped(true)
+                        .num_columns(6)
+                        .show(ui, |ui| {
+                            ui.strong("Time");
+                            ui.strong("Event");
+                            ui.strong("Type");
+                            ui.strong("Node");
+                            ui.strong("From");
+                            ui.label("");
+                            ui.end_row();
+                            for event in &result.events {
+                                ui.label(format!("{:.3} s", event.time_ms as f64 / 1000.0));
+                                ui.label(event.kind.name());
+                                ui.colored_label(
+                                    color_for_message_type(event.message_type, 1.0),
+                                    message_type_name(event.message_type),
+                                );
+                                ui.label(format!("#{}", event.node_id));
+                                if event.kind == EventKind::Transmission {
+                                    ui.label("");
+                                } else {
+                                    ui.label(format!("#{}", event.sender_id));
+                                }
+                                if ui
+                                    .small_button("Show")
+                                    .on_hover_text(
+                                        "Select the node, show the link on the map and filter the inspector to this second",
+                                    )
+                                    .clicked()
+                                {
+                                    show = Some((
+                                        event.node_id,
+                                        event.sender_id,
+                                        event.message_type,
+                                        event.time_ms,
+                                    ));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+
+    if search {
+        match build_query(form) {
+            Ok(query) => {
+                if state
+                    .ui_command_tx
+                    .try_send(UICommand::QueryEvents(query))
+                    .is_ok()
+                {
+                    state.event_search.pending = true;
+                }
+            }
+            Err(message) => error = Some(message),
+        }
+    }
+    if let Some((node_id, sender_id, message_type, time_ms)) = show {
+        show_event(state, node_id, sender_id, message_type, time_ms);
+    }
+    if let Some(message) = error {
+        state.alert = Some(message);
+    }
+    if !open {
+        state.event_search.open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn form_fields_build_the_query() {
+        let mut search = EventSearchState {
+            kind: Some(EventKind::Collision),
+            message_type: Some(5),
+            node: "#12".to_string(),
+            from: "300s".to_string(),
+            to: "600".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_query(&search),
+            Ok(EventQuery {
+                kinds: vec![EventKind::Collision],
+                message_type: Some(5),
+                node_id: Some(12),
+                from_ms: Some(300_000),
+                to_ms: Some(600_000),
+            })
+        );
+
+        search.to = "200".to_string();
+        assert!(build_query(&search).is_err());
+        search.to.clear();
+        search.node = "twelve".to_string();
+        assert!(build_query(&search).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! # Duplicate Suppression Window
+//!
+//! Efficiency of flood relaying in Simulation mode: how many received AddBlock and
+//! AddTransaction packets were new to the receiving node and how many were
+//! duplicates of packets it already knew. Every node statistics snapshot (about
+//! once per second) adds a sample of the network-wide totals; the chart shows the
+//! duplicate share per sampling interval (yellow) and cumulatively (blue) over the
+//! simulation time. Per-node shares are in the node table ("Dup." column).
+
+use eframe::egui;
+use std::collections::{HashMap, VecDeque};
+
+use super::AppState;
+use crate::common::node_statistics::{NodeStatistics, duplicate_ratio};
+
+/// Samples kept for the chart (about 10 minutes at one snapshot per second).
+const MAX_SAMPLES: usize = 600;
+
+/// Network-wide flooded packet totals at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DuplicateSample {
+    /// Simulation time in seconds.
+    time_secs: f64,
+    new: u64,
+    duplicates: u64,
+}
+
+/// Sampled duplicate totals and window visibility.
+#[derive(Debug, Clone, Default)]
+pub struct FloodEfficiencyState {
+    /// Whether the window is open.
+    pub open: bool,
+    samples: VecDeque<DuplicateSample>,
+}
+
+impl FloodEfficiencyState {
+    /// Add a sample of the network-wide totals of a statistics snapshot.
+    pub fn record(&mut self, time_secs: f64, statistics: &HashMap<u32, NodeStatistics>) {
+        let (new, duplicates) = statistics.values().fold((0, 0), |(new, duplicates), s| {
+            (
+                new + s.flood_new_packets,
+                duplicates + s.flood_duplicate_packets,
+            )
+        });
+        if self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(DuplicateSample {
+            time_secs,
+            new,
+            duplicates,
+        });
+    }
+
+    /// Forget all samples (e.g. when a new scene is loaded).
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Duplicate percentage of each sampling interval with received flooded packets,
+    /// as (end time in seconds, percentage).
+    fn interval_ratios(&self) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .filter_map(|(previous, sample)| {
+                let ratio = duplicate_ratio(
+                    sample.new.saturating_sub(previous.new),
+                    sample.duplicates.saturating_sub(previous.duplicates),
+                )?;
+                Some((sample.time_secs, ratio))
+            })
+            .collect()
+    }
+
+    /// Cumulative duplicate percentage at each sample, as (time in seconds, percentage).
+    fn cumulative_ratios(&self) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .filter_map(|sample| {
+                Some((
+                    sample.time_secs,
+                    duplicate_ratio(sample.new, sample.duplicates)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Render the duplicate suppression window (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (duplicate samples)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.flood_efficiency.open {
+        return;
+    }
+    let flood = &mut state.flood_efficiency;
+    let mut open = flood.open;
+    egui::Window::new("Duplicate suppression")
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let latest = flood.samples.back().copied();
+            egui::Grid::new("flood_efficiency_totals").show(ui, |ui| {
+                ui.label("New packets:");
+                ui.label(egui::RichText::new(latest.map_or(0, |s| s.new).to_string()).strong());
+                ui.end_row();
+                ui.label("Duplicate packets:");
+                ui.label(
+                    egui::RichText::new(latest.map_or(0, |s| s.duplicates).to_string()).strong(),
+                );
+                ui.end_row();
+                ui.label("Duplicate share:");
+                let ratio = latest
+                    .and_then(|s| duplicate_ratio(s.new, s.duplicates))
+                    .map_or("-".to_string(), |ratio| format!("{:.1}%", ratio));
+                ui.label(egui::RichText::new(ratio).strong());
+                ui.end_row();
+            });
+            ui.separator();
+            draw_chart(ui, &flood.interval_ratios(), &flood.cumulative_ratios());
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::YELLOW, "■ per interval");
+                ui.colored_label(egui::Color32::LIGHT_BLUE, "■ cumulative");
+            });
+        });
+    flood.open = open;
+}
+
+/// Draw the duplicate percentage lines (0-100%) over the sampled time span.
+fn draw_chart(ui: &mut egui::Ui, interval: &[(f64, f64)], cumulative: &[(f64, f64)]) {
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width().max(200.0), 160.0),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let grid_stroke = egui::Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.3));
+    for percent in [25.0, 50.0, 75.0] {
+        let y = egui::lerp(rect.bottom()..=rect.top(), percent / 100.0);
+        painter.hline(rect.x_range(), y, grid_stroke);
+    }
+
+    let Some((start, end)) = cumulative
+        .first()
+        .zip(cumulative.last())
+        .map(|(first, last)| (first.0, last.0))
+        .filter(|(start, end)| end > start)
+    else {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "No flooded packets received yet",
+            egui::FontId::default(),
+            visuals.weak_text_color(),
+        );
+        return;
+    };
+    let to_screen = |(time, percent): (f64, f64)| {
+        egui::pos2(
+            egui::lerp(
+                rect.left()..=rect.right(),
+                ((time - start) / (end - start)) as f32,
+            ),
+            egui::lerp(rect.bottom()..=rect.top(), (percent / 100.0) as f32),
+        )
+    };
+    for (points, color) in [
+        (interval, egui::Color32::YELLOW),
+        (cumulative, egui::Color32::LIGHT_BLUE),
+    ] {
+        let line: Vec<egui::Pos2> = points.iter().copied().map(to_screen).collect();
+        painter.add(egui::Shape::line(line, egui::Stroke::new(1.5, color)));
+    }
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        "100%",
+        egui::FontId::proportional(10.0),
+        visuals.weak_text_color(),
+    );
+    painter.text(
+        rect.right_bottom() - egui::vec2(4.0, 2.0),
+        egui::Align2::RIGHT_BOTTOM,
+        format!("{:.0} s", end),
+        egui::FontId::proportional(10.0),
+        visuals.weak_text_color(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statistics(new: u64, duplicates: u64) -> HashMap<u32, NodeStatistics> {
+        let node = NodeStatistics {
+            flood_new_packets: new,
+            flood_duplicate_packets: duplicates,
+            ..Default::default()
+        };
+        HashMap::from([(1, node)])
+    }
+
+    #[test]
+    fn test_interval_ratios_use_the_difference_between_samples() {
+        let mut state = FloodEfficiencyState::default();
+        state.record(1.0, &statistics(10, 0));
+        state.record(2.0, &statistics(10, 0));
+        state.record(3.0, &statistics(14, 6));
+
+        // The idle interval has no ratio; the last one received 4 new and 6 duplicates
+        assert_eq!(state.interval_ratios(), vec![(3.0, 60.0)]);
+        assert_eq!(
+            state.cumulative_ratios(),
+            vec![(1.0, 0.0), (2.0, 0.0), (3.0, 30.0)]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! # Measurement Hop Depth
+//!
+//! First-hop vs multi-hop breakdown of a measurement. Every reached node records the
+//! node it first received the measurement from (its first relayer); following this
+//! chain back to the origin gives the node's hop depth: 1 for nodes that heard the
+//! origin directly, 2 for nodes reached through one relayer, and so on. The chain is
+//! an approximation of the propagation tree: a node may have received later packets
+//! of a multi-packet message from other relayers.
+//!
+//! In Simulation mode the first relayer is the sender of the oldest received packet
+//! of the measurement in the node's radio history; in the analyzer modes it is the
+//! sender of the logged AddBlock reception. Nodes whose chain has an unknown link are
+//! counted as unknown. The summary row is shown in the Measured data panel and the
+//! "Hops" window draws the hop count distribution.
+
+use eframe::egui;
+
+use super::AppState;
+use super::app_state::MeasurementProgress;
+
+/// Hop counts of the reached nodes of a measurement (origin excluded).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HopDistribution {
+    /// Reached nodes per hop count; index 0 is hop 1 (direct from the origin).
+    pub counts: Vec<usize>,
+    /// Reached nodes without a known relayer chain.
+    pub unknown: usize,
+}
+
+impl HopDistribution {
+    /// Distribution of the measurement's reached nodes.
+    pub fn of(measurement: &MeasurementProgress) -> Self {
+        let mut distribution = Self::default();
+        for node_id in &measurement.reached_nodes {
+            if *node_id == measurement.origin_node_id {
+                continue;
+            }
+            match measurement.hops.get(node_id) {
+                Some(&hops) if hops > 0 => {
+                    let index = hops as usize - 1;
+                    if distribution.counts.len() <= index {
+                        distribution.counts.resize(index + 1, 0);
+                    }
+                    distribution.counts[index] += 1;
+                }
+                _ => distribution.unknown += 1,
+            }
+        }
+        distribution
+    }
+
+    /// Nodes reached directly from the origin.
+    pub fn direct(&self) -> usize {
+        self.counts.first().copied().unwrap_or(0)
+    }
+
+    /// Nodes reached through at least one relayer.
+    pub fn multi_hop(&self) -> usize {
+        self.counts.iter().skip(1).sum()
+    }
+
+    /// Deepest hop count (0 if no node has a known hop count).
+    pub fn max_depth(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Mean hop count of the nodes with a known hop count.
+    pub fn mean(&self) -> Option<f64> {
+        let known: usize = self.counts.iter().sum();
+        let total: usize = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(index, count)| (index + 1) * count)
+            .sum();
+        (known > 0).then(|| total as f64 / known as f64)
+    }
+}
+
+/// Render the hop summary row of the displayed measurement (Measured data panel).
+pub fn render_summary(ui: &mut egui::Ui, state: &mut AppState) {
+    let distribution = state.active_measurement().map(HopDistribution::of);
+    ui.horizontal(|ui| {
+        ui.label("Hops: direct");
+        ui.label(
+            egui::RichText::new(
+                distribution
+                    .as_ref()
+                    .map_or("-".to_string(), |d| d.direct().to_string()),
+            )
+            .strong(),
+        );
+        ui.label(" multi-hop");
+        ui.label(
+            egui::RichText::new(
+                distribution
+                    .as_ref()
+                    .map_or("-".to_string(), |d| d.multi_hop().to_string()),
+            )
+            .strong(),
+        );
+        ui.label(" max");
+        ui.label(
+            egui::RichText::new(
+                distribution
+                    .as_ref()
+                    .map_or("-".to_string(), |d| d.max_depth().to_string()),
+            )
+            .strong(),
+        );
+        if ui
+            .add_enabled(distribution.is_some(), egui::Button::new("Chart"))
+            .on_hover_text("Hop count distribution of the displayed measurement")
+            .clicked()
+        {
+            state.show_hop_depth = true;
+        }
+    });
+}
+
+/// Render the hop count distribution window (if open).
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.show_hop_depth {
+        return;
+    }
+    let distribution = state.active_measurement().map(HopDistribution::of);
+    let mut open = true;
+    egui::Window::new("Hop depth")
+        .open(&mut open)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let Some(distribution) = distribution else {
+                ui.label("No measurement");
+                return;
+            };
+            ui.label(format!(
+                "Direct: {}   Multi-hop: {}   Unknown: {}   Mean: {}",
+                distribution.direct(),
+                distribution.multi_hop(),
+                distribution.unknown,
+                distribution
+                    .mean()
+                    .map_or("-".to_string(), |mean| format!("{:.2}", mean))
+            ));
+            ui.separator();
+            draw_histogram(ui, &distribution.counts);
+        });
+    if !open {
+        state.show_hop_depth = false;
+    }
+}
+
+/// Draw one bar per hop count, labeled with the hop count and the node count.
+fn draw_histogram(ui: &mut egui::Ui, counts: &[usize]) {
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width().max(200.0), 160.0),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let max_count = counts.iter().copied().max().unwrap_or(0);
+    if max_count == 0 {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "No hop counts yet",
+            egui::FontId::proportional(12.0),
+            visuals.weak_text_color(),
+        );
+        return;
+    }
+    let font = egui::FontId::proportional(10.0);
+    let slot = rect.width() / counts.len() as f32;
+    let plot_height = rect.height() - 30.0;
+    for (index, count) in counts.iter().enumerate() {
+        let height = plot_height * *count as f32 / max_count as f32;
+        let left = rect.left() + slot * index as f32 + slot * 0.15;
+        let bottom = rect.bottom() - 14.0;
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(left, bottom - height),
+                egui::pos2(left + slot * 0.7, bottom),
+            ),
+            1.0,
+            egui::Color32::LIGHT_BLUE,
+        );
+        let center = left + slot * 0.35;
+        painter.text(
+            egui::pos2(center, bottom - height - 2.0),
+            egui::Align2::CENTER_BOTTOM,
+            count.to_string(),
+            font.clone(),
+            visuals.text_color(),
+        );
+        painter.text(
+            egui::pos2(center, rect.bottom() - 2.0),
+            egui::Align2::CENTER_BOTTOM,
+            (index + 1).to_string(),
+            font.clone(),
+            visuals.weak_text_color(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_time::{Duration, Instant};
+
+    #[test]
+    fn test_hops_follow_the_first_relayer_chain() {
+        let start = Instant::from_secs(0);
+        let mut measurement = MeasurementProgress::new(1, start);
+        measurement.record_reached(2, Some(1), 6, start + Duration::from_secs(1));
+        measurement.record_reached(3, Some(1), 6, start + Duration::from_secs(1));
+        measurement.record_reached(4, Some(2), 6, start + Duration::from_secs(2));
+        measurement.record_reached(5, Some(4), 6, start + Duration::from_secs(3));
+        // Unknown relayer, and a node relayed by it
+        measurement.record_reached(6, None, 6, start + Duration::from_secs(3));
+        measurement.record_reached(7, Some(6), 7, start + Duration::from_secs(4));
+        // A later reception does not change the first relayer
+        measurement.record_reached(4, Some(3), 7, start + Duration::from_secs(5));
+
+        assert_eq!(measurement.hops[&5], 3);
+        assert_eq!(measurement.first_relayers[&4], 2);
+        assert!(!measurement.hops.contains_key(&7));
+
+        let distribution = HopDistribution::of(&measurement);
+        assert_eq!(distribution.counts, vec![2, 1, 1]);
+        assert_eq!(distribution.unknown, 2);
+        assert_eq!(distribution.direct(), 2);
+        assert_eq!(distribution.multi_hop(), 2);
+        assert_eq!(distribution.max_depth(), 3);
+        assert_eq!(distribution.mean(), Some(7.0 / 4.0));
+    }
+}
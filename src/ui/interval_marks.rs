@@ -0,0 +1,271 @@
+//! # Interval Marks
+//!
+//! Named timestamps that split a running session into windows, to compare the
+//! traffic before and after a parameter change without restarting. "Mark" records
+//! the current time and the cumulative TX, RX and collision counters; the "Marks"
+//! window lists every window between two consecutive marks (the first one starting
+//! at the session start, the last one ending now) with its duration, TX and RX
+//! throughput and collision rate (Simulation mode only; the analyzer modes do not
+//! see collisions).
+//!
+//! Times are taken from the measurement clock: virtual time in Simulation mode,
+//! the log timeline in the analyzer modes.
+
+use eframe::egui;
+use embassy_time::Instant;
+
+use super::{AppState, OperatingMode};
+
+/// Cumulative counters at one point of the session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Counters {
+    time: Instant,
+    sent: u64,
+    received: u64,
+    collisions: u64,
+}
+
+/// A named point of the session.
+#[derive(Debug, Clone, PartialEq)]
+struct Mark {
+    name: String,
+    counters: Counters,
+}
+
+/// Statistics of the window between two marks.
+#[derive(Debug, Clone, PartialEq)]
+struct IntervalStats {
+    from: String,
+    to: String,
+    duration_secs: f64,
+    tx_per_minute: f64,
+    rx_per_minute: f64,
+    /// Share of collisions among the packets arriving at receivers, in percent.
+    collision_rate: Option<f64>,
+}
+
+/// Recorded marks and window visibility.
+#[derive(Debug, Clone, Default)]
+pub struct IntervalMarksState {
+    /// Whether the window is open.
+    pub open: bool,
+    marks: Vec<Mark>,
+    /// Name of the next mark (a numbered default is used when empty).
+    next_name: String,
+}
+
+impl IntervalMarksState {
+    /// Forget the marks of the previous session.
+    pub fn clear(&mut self) {
+        self.marks.clear();
+    }
+}
+
+/// Statistics of the window from `start` to `end`.
+fn interval(from: &str, start: &Counters, to: &str, end: &Counters) -> IntervalStats {
+    let duration_secs = end.time.saturating_duration_since(start.time).as_millis() as f64 / 1000.0;
+    let per_minute = |count: u64| {
+        if duration_secs > 0.0 {
+            count as f64 * 60.0 / duration_secs
+        } else {
+            0.0
+        }
+    };
+    let received = end.received.saturating_sub(start.received);
+    let collisions = end.collisions.saturating_sub(start.collisions);
+    IntervalStats {
+        from: from.to_string(),
+        to: to.to_string(),
+        duration_secs,
+        tx_per_minute: per_minute(end.sent.saturating_sub(start.sent)),
+        rx_per_minute: per_minute(received),
+        collision_rate: (received + collisions > 0)
+            .then(|| collisions as f64 * 100.0 / (received + collisions) as f64),
+    }
+}
+
+/// Statistics of every window: session start → first mark → … → last mark → now.
+///
+/// # Parameters
+///
+/// * `start` - Time of the session start (all counters zero), if known
+/// * `marks` - Recorded marks, oldest first
+/// * `now` - Current counters
+fn intervals(start: Option<Instant>, marks: &[Mark], now: &Counters) -> Vec<IntervalStats> {
+    let mut points: Vec<(&str, Counters)> = Vec::with_capacity(marks.len() + 2);
+    if let Some(time) = start {
+        points.push((
+            "Start",
+            Counters {
+                time,
+                sent: 0,
+                received: 0,
+                collisions: 0,
+            },
+        ));
+    }
+    points.extend(marks.iter().map(|mark| (mark.name.as_str(), mark.counters)));
+    points.push(("Now", *now));
+    points
+        .windows(2)
+        .map(|pair| interval(pair[0].0, &pair[0].1, pair[1].0, &pair[1].1))
+        .collect()
+}
+
+/// Current counters of the session.
+fn current_counters(state: &AppState) -> Counters {
+    Counters {
+        time: state.measurement_clock(),
+        sent: state.total_sent_packets,
+        received: state.total_received_packets,
+        collisions: state.total_collision,
+    }
+}
+
+/// Start of the session on the measurement clock, if known.
+fn session_start(state: &AppState) -> Option<Instant> {
+    match state.operating_mode {
+        OperatingMode::Simulation => Some(Instant::from_ticks(0)),
+        OperatingMode::RealtimeTracking | OperatingMode::LogVisualization => {
+            state.first_activity_time
+        }
+    }
+}
+
+/// Record a mark at the current time.
+///
+/// # Parameters
+///
+/// * `state` - Mutable application state
+/// * `name` - Mark name; an empty name is replaced by a numbered default
+fn add_mark(state: &mut AppState, name: &str) {
+    let counters = current_counters(state);
+    let marks = &mut state.interval_marks;
+    let name = if name.trim().is_empty() {
+        format!("Mark {}", marks.marks.len() + 1)
+    } else {
+        name.trim().to_string()
+    };
+    marks.marks.push(Mark { name, counters });
+}
+
+/// Render the marks window (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.interval_marks.open {
+        return;
+    }
+    let now = current_counters(state);
+    let windows = intervals(session_start(state), &state.interval_marks.marks, &now);
+    let show_collisions = state.operating_mode == OperatingMode::Simulation;
+
+    let mut open = true;
+    let mut mark = false;
+    let mut clear = false;
+    let marks = &mut state.interval_marks;
+    egui::Window::new("Marks")
+        .open(&mut open)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut marks.next_name)
+                        .hint_text(format!("Mark {}", marks.marks.len() + 1))
+                        .desired_width(160.0),
+                );
+                mark = ui
+                    .button("Mark")
+                    .on_hover_text("Start a new statistics window now")
+                    .clicked();
+                clear = ui
+                    .add_enabled(!marks.marks.is_empty(), egui::Button::new("Clear"))
+                    .clicked();
+            });
+            ui.separator();
+            egui::Grid::new("interval_marks_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Window");
+                    ui.strong("Duration");
+                    ui.strong("TX/min");
+                    ui.strong("RX/min");
+                    if show_collisions {
+                        ui.strong("Collision rate");
+                    }
+                    ui.end_row();
+                    for window in &windows {
+                        ui.label(format!("{} → {}", window.from, window.to));
+                        ui.label(format!("{:.1} s", window.duration_secs));
+                        ui.label(format!("{:.1}", window.tx_per_minute));
+                        ui.label(format!("{:.1}", window.rx_per_minute));
+                        if show_collisions {
+                            ui.label(
+                                window
+                                    .collision_rate
+                                    .map_or("-".to_string(), |rate| format!("{:.2} %", rate)),
+                            );
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if clear {
+        marks.marks.clear();
+    }
+    if mark {
+        let name = std::mem::take(&mut marks.next_name);
+        add_mark(state, &name);
+    }
+    if !open {
+        state.interval_marks.open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(secs: u64, sent: u64, received: u64, collisions: u64) -> Counters {
+        Counters {
+            time: Instant::from_secs(secs),
+            sent,
+            received,
+            collisions,
+        }
+    }
+
+    #[test]
+    fn windows_use_counter_differences_between_marks() {
+        let marks = vec![Mark {
+            name: "SF9".to_string(),
+            counters: counters(60, 120, 300, 100),
+        }];
+        let windows = intervals(
+            Some(Instant::from_secs(0)),
+            &marks,
+            &counters(180, 180, 480, 120),
+        );
+
+        assert_eq!(windows.len(), 2);
+        assert_eq!(
+            (windows[0].from.as_str(), windows[0].to.as_str()),
+            ("Start", "SF9")
+        );
+        assert_eq!(windows[0].tx_per_minute, 120.0);
+        assert_eq!(windows[0].collision_rate, Some(25.0));
+
+        assert_eq!(
+            (windows[1].from.as_str(), windows[1].to.as_str()),
+            ("SF9", "Now")
+        );
+        assert_eq!(windows[1].duration_secs, 120.0);
+        assert_eq!(windows[1].tx_per_minute, 30.0);
+        assert_eq!(windows[1].rx_per_minute, 90.0);
+        assert_eq!(windows[1].collision_rate, Some(10.0));
+    }
+}
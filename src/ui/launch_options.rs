@@ -0,0 +1,390 @@
+//! # Command-Line Launch Options
+//!
+//! Starts a GUI session directly from the command line, bypassing the mode selector
+//! and the file pickers, e.g. for scripted repeated runs:
+//!
+//! ```text
+//! moonblokz-radio-simulator --mode simulation --scene foo.json --speed 500 --auto-speed
+//! moonblokz-radio-simulator --mode log --scene foo.json --log bar.log
+//! ```
+//!
+//! The arguments are parsed with a clap derive struct (`LaunchArgs`), which also
+//! renders `--help` and the usage of invalid arguments. Without `--mode` the mode is
+//! Log Visualization when `--log` is given and Simulation otherwise. Relative paths
+//! are resolved against the working directory, and the session is remembered in the
+//! recent files like one started from the selector.
+//!
+//! The command-line tools that run without the GUI (`calibrate`, `diff`, `audit`,
+//! `plan`, `convert`, `run`) are subcommands of the same parser (`ToolCommand`), so
+//! their unknown flags are rejected and `<tool> --help` prints their options.
+
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use super::OperatingMode;
+use crate::simulation::coverage_planner::PlacementStrategy;
+
+/// Command-line arguments starting a GUI session.
+#[derive(Debug, Parser)]
+#[command(
+    name = "moonblokz-radio-simulator",
+    no_binary_name = true,
+    args_conflicts_with_subcommands = true
+)]
+struct LaunchArgs {
+    /// Command-line tool to run instead of the GUI
+    #[command(subcommand)]
+    tool: Option<ToolCommand>,
+    /// Mode to start (default: log with --log, simulation otherwise)
+    #[arg(long, value_enum)]
+    mode: Option<LaunchMode>,
+    /// Scene file
+    #[arg(long)]
+    scene: Option<PathBuf>,
+    /// Log file (realtime and log modes)
+    #[arg(long)]
+    log: Option<PathBuf>,
+    /// Initial speed in percent
+    #[arg(long, value_parser = clap::value_parser!(u32).range(20..=1000))]
+    speed: Option<u32>,
+    /// Enable auto speed once the session runs
+    #[arg(long)]
+    auto_speed: bool,
+}
+
+/// Values of `--mode`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum LaunchMode {
+    Simulation,
+    Realtime,
+    Log,
+}
+
+impl From<LaunchMode> for OperatingMode {
+    fn from(mode: LaunchMode) -> Self {
+        match mode {
+            LaunchMode::Simulation => OperatingMode::Simulation,
+            LaunchMode::Realtime => OperatingMode::RealtimeTracking,
+            LaunchMode::Log => OperatingMode::LogVisualization,
+        }
+    }
+}
+
+/// Command-line tools running without the GUI.
+#[derive(Debug, Subcommand)]
+pub enum ToolCommand {
+    /// Fit the path loss parameters of a scene to field RSSI measurements
+    Calibrate(CalibrateArgs),
+    /// Print the differences between two scene files
+    Diff(DiffArgs),
+    /// Run a scene twice with the same seed and compare the event streams
+    Audit(AuditArgs),
+    /// Suggest node placements for a template scene
+    Plan(PlanArgs),
+    /// Convert analyzer scenes into simulation scenes
+    Convert(ConvertArgs),
+    /// Run a scene without the GUI until a stop condition is met
+    Run(RunArgs),
+}
+
+/// Arguments of the `calibrate` tool.
+#[derive(Debug, Args)]
+pub struct CalibrateArgs {
+    /// Scene file providing node positions and the parameters to update
+    pub scene: String,
+    /// CSV file with the measured samples
+    pub samples: String,
+    /// TX power in dBm for samples without a tx_power column
+    #[arg(long)]
+    pub tx_power: Option<f64>,
+    /// Where to write the updated scene (default: update the scene in place)
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+/// Arguments of the `diff` tool.
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Original scene
+    pub before: String,
+    /// Changed scene
+    pub after: String,
+}
+
+/// Arguments of the `audit` tool.
+#[derive(Debug, Args)]
+pub struct AuditArgs {
+    pub scene: String,
+    /// Virtual seconds per run
+    #[arg(long, default_value_t = 600, value_parser = clap::value_parser!(u64).range(1..))]
+    pub duration: u64,
+    /// Random seed of both runs (default: random)
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// Values of `plan --strategy`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PlanStrategy {
+    Grid,
+    Greedy,
+}
+
+impl From<PlanStrategy> for PlacementStrategy {
+    fn from(strategy: PlanStrategy) -> Self {
+        match strategy {
+            PlanStrategy::Grid => PlacementStrategy::Grid,
+            PlanStrategy::Greedy => PlacementStrategy::Greedy,
+        }
+    }
+}
+
+/// Arguments of the `plan` tool.
+#[derive(Debug, Args)]
+pub struct PlanArgs {
+    /// Template scene (world, obstacles, LoRa and path loss parameters)
+    pub template: String,
+    #[arg(long, value_enum, default_value_t = PlanStrategy::Greedy)]
+    pub strategy: PlanStrategy,
+    /// Number of nodes each point must be covered by
+    #[arg(long, default_value_t = 1)]
+    pub redundancy: usize,
+    /// TX power of the placed nodes in dBm
+    #[arg(long, default_value_t = 14.0)]
+    pub tx_power: f64,
+    #[arg(long, default_value_t = 1000)]
+    pub max_nodes: usize,
+    /// Where to write the planned scene (default: <template>_planned.json)
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+/// Arguments of the `convert` tool.
+#[derive(Debug, Args)]
+pub struct ConvertArgs {
+    /// Analyzer scenes to convert
+    #[arg(required = true)]
+    pub scenes: Vec<String>,
+    /// Simulation scene providing the physics sections
+    #[arg(long)]
+    pub template: Option<String>,
+    /// Directory of the converted scenes (default: next to each scene)
+    #[arg(long)]
+    pub output_dir: Option<String>,
+}
+
+/// Arguments of the headless `run` command.
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    pub scene: String,
+    /// Stop after this many virtual seconds
+    #[arg(long)]
+    pub time_limit: Option<u64>,
+    /// Stop when all measurements reached every node
+    #[arg(long)]
+    pub until_measurement_complete: bool,
+    /// Stop after this many measurement blocks reached every node
+    #[arg(long)]
+    pub blocks: Option<u32>,
+    /// Simulation speed in percent
+    #[arg(
+        long,
+        default_value_t = 1000,
+        value_parser = clap::value_parser!(u32).range(1..=1000),
+        conflicts_with = "event_driven"
+    )]
+    pub speed: u32,
+    /// Advance the virtual clock from event to event instead of in real time
+    #[arg(long)]
+    pub event_driven: bool,
+    /// Override the random TX delay of every node in milliseconds
+    #[arg(long)]
+    pub tx_random_delay: Option<u16>,
+    /// Run summary file (relative to the working directory)
+    #[arg(long)]
+    pub summary: Option<PathBuf>,
+}
+
+/// What the command line asks for.
+#[derive(Debug)]
+pub enum Launch {
+    /// No arguments: show the mode selector.
+    Selector,
+    /// Start a GUI session.
+    Session(LaunchOptions),
+    /// Run a command-line tool without the GUI.
+    Tool(ToolCommand),
+}
+
+/// Session to start when the window opens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchOptions {
+    pub mode: OperatingMode,
+    pub scene_path: String,
+    /// Log file (analyzer modes).
+    pub log_path: Option<String>,
+    /// Initial speed in percent (20-1000).
+    pub speed_percent: Option<u32>,
+    /// Enable auto speed once the session runs.
+    pub auto_speed: bool,
+}
+
+/// Error of the launch arguments with the usage appended.
+fn usage_error(kind: ErrorKind, message: impl std::fmt::Display) -> clap::Error {
+    LaunchArgs::command().error(kind, message)
+}
+
+/// Absolute form of a path argument.
+fn absolute_path(path: PathBuf) -> Result<String, clap::Error> {
+    std::path::absolute(&path)
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|err| {
+            usage_error(
+                ErrorKind::InvalidValue,
+                format!("invalid path {}: {}", path.display(), err),
+            )
+        })
+}
+
+/// Parse the launch options.
+///
+/// # Parameters
+///
+/// * `args` - Command-line arguments without the program name
+///
+/// # Returns
+///
+/// The mode selector without arguments, the session or tool to start, or a clap
+/// error for invalid arguments or `--help` (`clap::Error::exit` prints it).
+pub fn parse(args: &[String]) -> Result<Launch, clap::Error> {
+    if args.is_empty() {
+        return Ok(Launch::Selector);
+    }
+    let args = LaunchArgs::try_parse_from(args)?;
+    if let Some(tool) = args.tool {
+        return Ok(Launch::Tool(tool));
+    }
+
+    let mode = args.mode.map_or(
+        if args.log.is_some() {
+            OperatingMode::LogVisualization
+        } else {
+            OperatingMode::Simulation
+        },
+        OperatingMode::from,
+    );
+    let Some(scene) = args.scene else {
+        return Err(usage_error(
+            ErrorKind::MissingRequiredArgument,
+            "--scene is required",
+        ));
+    };
+    let conflict = match mode {
+        OperatingMode::Simulation if args.log.is_some() => {
+            Some("--log is only used in the realtime and log modes")
+        }
+        OperatingMode::RealtimeTracking | OperatingMode::LogVisualization if args.log.is_none() => {
+            Some("--log is required in the realtime and log modes")
+        }
+        OperatingMode::RealtimeTracking if args.speed.is_some() || args.auto_speed => {
+            Some("Real-time tracking always runs at 100% speed")
+        }
+        _ => None,
+    };
+    if let Some(message) = conflict {
+        return Err(usage_error(ErrorKind::ArgumentConflict, message));
+    }
+    Ok(Launch::Session(LaunchOptions {
+        mode,
+        scene_path: absolute_path(scene)?,
+        log_path: args.log.map(absolute_path).transpose()?,
+        speed_percent: args.speed,
+        auto_speed: args.auto_speed,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn session(line: &str) -> LaunchOptions {
+        match parse(&args(line)) {
+            Ok(Launch::Session(options)) => options,
+            other => panic!("{}: {:?}", line, other),
+        }
+    }
+
+    #[test]
+    fn test_parse_launch_options() {
+        assert!(matches!(parse(&[]), Ok(Launch::Selector)));
+
+        let options = session("--mode simulation --scene foo.json --speed 500 --auto-speed");
+        assert_eq!(options.mode, OperatingMode::Simulation);
+        assert!(options.scene_path.ends_with("foo.json"));
+        assert!(std::path::Path::new(&options.scene_path).is_absolute());
+        assert_eq!(options.speed_percent, Some(500));
+        assert!(options.auto_speed);
+
+        // A log file without a mode starts Log Visualization
+        let options = session("--scene foo.json --log bar.log");
+        assert_eq!(options.mode, OperatingMode::LogVisualization);
+        assert!(options.log_path.is_some_and(|log| log.ends_with("bar.log")));
+
+        for invalid in [
+            "--mode simulation",
+            "--mode log --scene foo.json",
+            "--mode simulation --scene foo.json --log bar.log",
+            "--mode realtime --scene foo.json --log bar.log --speed 200",
+            "--scene foo.json --speed 5000",
+            "--scene foo.json --mode replay",
+            "--scene",
+            "foo.json",
+        ] {
+            assert!(parse(&args(invalid)).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_tool_commands() {
+        let Ok(Launch::Tool(ToolCommand::Run(run))) = parse(&args(
+            "run foo.json --time-limit 60 --until-measurement-complete --tx-random-delay 500",
+        )) else {
+            panic!("run not parsed");
+        };
+        assert_eq!(run.scene, "foo.json");
+        assert_eq!(run.time_limit, Some(60));
+        assert!(run.until_measurement_complete);
+        assert_eq!(run.speed, 1000);
+        assert_eq!(run.tx_random_delay, Some(500));
+
+        let Ok(Launch::Tool(ToolCommand::Plan(plan))) =
+            parse(&args("plan template.json --strategy grid"))
+        else {
+            panic!("plan not parsed");
+        };
+        assert!(matches!(plan.strategy, PlanStrategy::Grid));
+        assert_eq!(plan.redundancy, 1);
+
+        for invalid in [
+            "calibrate foo.json",
+            "calibrate foo.json samples.csv --tx-pwr 14",
+            "diff before.json",
+            "audit foo.json --duration 0",
+            "audit foo.json --speed 200",
+            "plan template.json --strategy random",
+            "convert",
+            "run foo.json --speed 200 --event-driven",
+            "run foo.json --until-complete",
+            "--scene foo.json run foo.json",
+        ] {
+            assert!(parse(&args(invalid)).is_err(), "{}", invalid);
+        }
+        let help = parse(&args("calibrate --help")).unwrap_err();
+        assert_eq!(help.kind(), ErrorKind::DisplayHelp);
+    }
+}
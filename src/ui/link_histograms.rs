@@ -0,0 +1,295 @@
+//! # Link Signal Histograms
+//!
+//! The inspector's "Link Stats" tab (Simulation mode): RSSI and SINR distributions
+//! of the selected node's incoming links, collected by the network task for every
+//! link (see `simulation::link_histograms`). The tab shows all links combined or a
+//! single sender; the SINR histogram stacks the decoded packets (green) and the
+//! packets lost to collisions (red), so interference shows up as a second mode
+//! instead of disappearing in an average. The histograms are re-queried once per
+//! second while the tab is visible.
+
+use eframe::egui;
+use egui::Color32;
+
+use super::{AppState, OperatingMode, UICommand};
+use crate::simulation::link_histograms::{Histogram, LinkHistogram};
+
+/// Wall-clock interval between two queries while the tab is visible.
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Color of decoded packets.
+const DECODED_COLOR: Color32 = Color32::from_rgb(90, 190, 90);
+/// Color of packets lost to collisions.
+const LOST_COLOR: Color32 = Color32::from_rgb(220, 80, 80);
+
+/// The last answer for the selected node and the link shown.
+#[derive(Debug, Clone, Default)]
+pub struct LinkHistogramView {
+    /// Node the links belong to.
+    node_id: Option<u32>,
+    links: Vec<LinkHistogram>,
+    /// Sender of the link shown (None = all links combined).
+    sender: Option<u32>,
+    /// Whether a query is awaiting its answer.
+    pending: bool,
+    /// When the last query was sent.
+    requested_at: Option<std::time::Instant>,
+}
+
+impl LinkHistogramView {
+    /// Store the answer of the network task.
+    pub fn receive(&mut self, node_id: u32, links: Vec<LinkHistogram>) {
+        self.pending = false;
+        if self.node_id != Some(node_id) {
+            self.sender = None;
+        }
+        self.node_id = Some(node_id);
+        self.links = links;
+    }
+
+    /// Forget the previous session's histograms.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Whether the histograms of `node_id` should be queried now.
+    fn needs_query(&self, node_id: u32, now: std::time::Instant) -> bool {
+        if self.pending {
+            return false;
+        }
+        self.node_id != Some(node_id)
+            || self
+                .requested_at
+                .is_none_or(|at| now.duration_since(at) >= REFRESH_INTERVAL)
+    }
+}
+
+/// RSSI, decoded SINR and lost SINR histograms of one sender, or of all links combined.
+fn combined(links: &[LinkHistogram], sender: Option<u32>) -> (Histogram, Histogram, Histogram) {
+    let mut rssi = Histogram::default();
+    let mut sinr_decoded = Histogram::default();
+    let mut sinr_lost = Histogram::default();
+    for link in links
+        .iter()
+        .filter(|link| sender.is_none_or(|id| id == link.sender_id))
+    {
+        rssi.merge(&link.rssi);
+        sinr_decoded.merge(&link.sinr_decoded);
+        sinr_lost.merge(&link.sinr_lost);
+    }
+    (rssi, sinr_decoded, sinr_lost)
+}
+
+/// Render the "Link Stats" tab of the inspector.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI of the tab area
+/// * `state` - Mutable application state (histogram view, command queue)
+/// * `node_id` - The selected node
+pub fn render(ui: &mut egui::Ui, state: &mut AppState, node_id: u32) {
+    if state.operating_mode != OperatingMode::Simulation {
+        ui.label("Link signal histograms are available in Simulation mode only.");
+        return;
+    }
+
+    let now = std::time::Instant::now();
+    if state.link_histograms.needs_query(node_id, now)
+        && state
+            .ui_command_tx
+            .try_send(UICommand::QueryLinkHistograms(node_id))
+            .is_ok()
+    {
+        state.link_histograms.pending = true;
+        state.link_histograms.requested_at = Some(now);
+    }
+    // Keep refreshing while the tab is visible
+    ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+
+    let view = &mut state.link_histograms;
+    if view.node_id != Some(node_id) {
+        ui.add(egui::Spinner::new());
+        return;
+    }
+    if view.links.is_empty() {
+        ui.label("No packets received yet.");
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Link:");
+        let selected_text = view
+            .sender
+            .map_or("All links".to_string(), |id| format!("from #{}", id));
+        egui::ComboBox::from_id_salt("link_histogram_sender")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut view.sender, None, "All links");
+                for link in &view.links {
+                    let packets = link.sinr_decoded.total() + link.sinr_lost.total();
+                    ui.selectable_value(
+                        &mut view.sender,
+                        Some(link.sender_id),
+                        format!("from #{} ({} packets)", link.sender_id, packets),
+                    );
+                }
+            });
+    });
+
+    let (rssi, sinr_decoded, sinr_lost) = combined(&view.links, view.sender);
+    let mean = |histogram: &Histogram| {
+        histogram
+            .mean()
+            .map_or("-".to_string(), |mean| format!("{:.1}", mean))
+    };
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.label(format!(
+            "RSSI of decoded packets: {} packets, mean {} dBm",
+            rssi.total(),
+            mean(&rssi)
+        ));
+        draw_histogram(ui, &[(&rssi, DECODED_COLOR)], "dBm");
+        ui.add_space(6.0);
+        ui.label(format!(
+            "SINR: {} decoded (mean {} dB), {} lost to collisions (mean {} dB)",
+            sinr_decoded.total(),
+            mean(&sinr_decoded),
+            sinr_lost.total(),
+            mean(&sinr_lost)
+        ));
+        draw_histogram(
+            ui,
+            &[(&sinr_decoded, DECODED_COLOR), (&sinr_lost, LOST_COLOR)],
+            "dB",
+        );
+    });
+}
+
+/// Draw stacked 1 dB bars of the given series over their common range, with the
+/// range ends and the unit on the axis.
+fn draw_histogram(ui: &mut egui::Ui, series: &[(&Histogram, Color32)], unit: &str) {
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width().max(200.0), 120.0),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let Some((low, high)) = series
+        .iter()
+        .filter_map(|(histogram, _)| histogram.range())
+        .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+    else {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "No packets",
+            egui::FontId::proportional(12.0),
+            visuals.weak_text_color(),
+        );
+        return;
+    };
+    let stacked = |bin: i16| {
+        series
+            .iter()
+            .map(|(histogram, _)| histogram.count(bin) as u64)
+            .sum::<u64>()
+    };
+    let max_count = (low..=high).map(stacked).max().unwrap_or(1).max(1);
+    let font = egui::FontId::proportional(10.0);
+    let slot = rect.width() / (high - low + 1) as f32;
+    let plot_height = rect.height() - 16.0;
+    let bottom = rect.bottom() - 14.0;
+    for bin in low..=high {
+        let left = rect.left() + slot * (bin - low) as f32;
+        let mut top = bottom;
+        for (histogram, color) in series {
+            let height = plot_height * histogram.count(bin) as f32 / max_count as f32;
+            painter.rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(left + slot * 0.1, top - height),
+                    egui::pos2(left + slot * 0.9, top),
+                ),
+                0.0,
+                *color,
+            );
+            top -= height;
+        }
+    }
+    painter.text(
+        egui::pos2(rect.left() + 2.0, rect.bottom() - 2.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!("{} {}", low, unit),
+        font.clone(),
+        visuals.weak_text_color(),
+    );
+    painter.text(
+        egui::pos2(rect.right() - 2.0, rect.bottom() - 2.0),
+        egui::Align2::RIGHT_BOTTOM,
+        format!("{} {}", high + 1, unit),
+        font.clone(),
+        visuals.weak_text_color(),
+    );
+    painter.text(
+        egui::pos2(rect.left() + 2.0, rect.top() + 2.0),
+        egui::Align2::LEFT_TOP,
+        format!("max {}", max_count),
+        font,
+        visuals.weak_text_color(),
+    );
+
+    // Bin under the pointer
+    if let Some(pointer) = response.hover_pos() {
+        let bin = low + ((pointer.x - rect.left()) / slot).floor() as i16;
+        if (low..=high).contains(&bin) {
+            response.on_hover_text(format!(
+                "{} to {} {}: {} packets",
+                bin,
+                bin + 1,
+                unit,
+                stacked(bin)
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_combine_or_filter_by_sender() {
+        let mut first = LinkHistogram {
+            sender_id: 2,
+            ..Default::default()
+        };
+        first.rssi.record(-100.0);
+        first.sinr_lost.record(-3.0);
+        let mut second = LinkHistogram {
+            sender_id: 5,
+            ..Default::default()
+        };
+        second.rssi.record(-100.5);
+        second.rssi.record(-80.0);
+        let links = [first, second];
+
+        let (rssi, _, lost) = combined(&links, None);
+        assert_eq!(rssi.count(-101), 1);
+        assert_eq!(rssi.count(-100), 1);
+        assert_eq!(rssi.total(), 3);
+        assert_eq!(lost.total(), 1);
+
+        let (rssi, _, lost) = combined(&links, Some(5));
+        assert_eq!(rssi.total(), 2);
+        assert_eq!(lost.total(), 0);
+
+        let mut view = LinkHistogramView::default();
+        let now = std::time::Instant::now();
+        assert!(view.needs_query(1, now));
+        view.receive(1, links.to_vec());
+        view.requested_at = Some(now);
+        assert!(!view.needs_query(1, now));
+        assert!(view.needs_query(2, now));
+        assert!(view.needs_query(1, now + REFRESH_INTERVAL));
+    }
+}
@@ -0,0 +1,195 @@
+//! # Node Liveness Window
+//!
+//! Summary list of silent nodes in the analyzer modes (Real-time Tracking and Log
+//! Visualization). A node is silent when it produced no log event for longer than the
+//! configured silence interval; silent nodes are also greyed out on the map.
+//!
+//! The silence interval is shared with the node silence alert rule.
+//!
+//! The map, this window and the alert rules read the silent nodes on every frame,
+//! so the set is cached (`SilentNodeCache`, borrowed by its readers after
+//! `AppState::refresh_silent_nodes`) and only recomputed when a node
+//! was heard or the node list was replaced (`invalidate`), when the silence
+//! interval or the node count changed, when the log clock went backwards, or when
+//! it passed the moment the next node falls silent.
+
+use eframe::egui;
+use embassy_time::{Duration, Instant};
+use std::collections::HashSet;
+
+use super::node_shapes;
+use super::{AppState, OperatingMode};
+
+/// Cached silent node set (see the module documentation).
+#[derive(Debug, Clone, Default)]
+pub struct SilentNodeCache {
+    /// Silent node IDs, never heard first, then the longest silence.
+    silent: Vec<u32>,
+    /// The same IDs for lookups.
+    lookup: HashSet<u32>,
+    /// Order-independent hash of the IDs (see `node_shapes::silent_hash`).
+    hash: u64,
+    /// Clock, silence interval and node count the set was computed for.
+    inputs: Option<(Instant, Duration, usize)>,
+    /// Moment after which the next node that is still heard falls silent.
+    next_change: Option<Instant>,
+    dirty: bool,
+}
+
+impl SilentNodeCache {
+    /// Recompute the set on the next request (node activity or node list changed).
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether the set must be recomputed for `now`, `limit` and `node_count`.
+    pub fn needs_rebuild(&self, now: Instant, limit: Duration, node_count: usize) -> bool {
+        let Some((computed_at, computed_limit, computed_count)) = self.inputs else {
+            return true;
+        };
+        self.dirty
+            || limit != computed_limit
+            || node_count != computed_count
+            || now < computed_at
+            || self
+                .next_change
+                .is_some_and(|next_change| now > next_change)
+    }
+
+    /// Store the set computed for `now`, `limit` and `node_count`.
+    ///
+    /// # Parameters
+    ///
+    /// * `silent` - Silent node IDs in display order
+    /// * `next_change` - Moment after which the next node still heard falls silent
+    pub fn store(
+        &mut self,
+        now: Instant,
+        limit: Duration,
+        node_count: usize,
+        silent: Vec<u32>,
+        next_change: Option<Instant>,
+    ) {
+        self.lookup = silent.iter().copied().collect();
+        self.hash = node_shapes::silent_hash(&silent);
+        self.silent = silent;
+        self.inputs = Some((now, limit, node_count));
+        self.next_change = next_change;
+        self.dirty = false;
+    }
+
+    /// The cached silent node IDs, never heard first, then the longest silence.
+    pub fn silent(&self) -> &[u32] {
+        &self.silent
+    }
+
+    /// Whether `node_id` is in the cached set.
+    pub fn contains(&self, node_id: u32) -> bool {
+        self.lookup.contains(&node_id)
+    }
+
+    /// Order-independent hash of the cached set.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Silence interval configured by the user.
+pub fn silence_limit(state: &AppState) -> Duration {
+    Duration::from_secs(state.alert_monitor.rules.node_silence_minutes * 60)
+}
+
+/// Render the silent nodes window (if enabled and in an analyzer mode).
+///
+/// Lists silent nodes with the time since they were last heard, longest silence
+/// first. Clicking a node selects it on the map and in the inspector.
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (silence interval, selection)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.show_silent_nodes || state.operating_mode == OperatingMode::Simulation {
+        return;
+    }
+    let Some(now) = state.analyzer_now() else {
+        return;
+    };
+
+    state.refresh_silent_nodes(now, silence_limit(state));
+    let silent = state.silent_node_cache.silent();
+
+    let mut open = state.show_silent_nodes;
+    let mut clicked_node: Option<u32> = None;
+    egui::Window::new("Silent nodes")
+        .open(&mut open)
+        .default_width(260.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Silent for more than");
+                ui.add(
+                    egui::DragValue::new(&mut state.alert_monitor.rules.node_silence_minutes)
+                        .range(1..=1440)
+                        .suffix(" min"),
+                );
+            });
+            ui.label(format!(
+                "{} of {} nodes silent",
+                silent.len(),
+                state.nodes.len()
+            ));
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for node_id in silent {
+                        let since = match state.node_last_heard.get(node_id) {
+                            Some(t) => {
+                                let secs = now.duration_since(*t).as_secs();
+                                format!("{}m {:02}s ago", secs / 60, secs % 60)
+                            }
+                            None => "never heard".to_string(),
+                        };
+                        if ui
+                            .selectable_label(false, format!("#{}  last heard {}", node_id, since))
+                            .clicked()
+                        {
+                            clicked_node = Some(*node_id);
+                        }
+                    }
+                });
+        });
+    state.show_silent_nodes = open;
+
+    if let Some(node_id) = clicked_node {
+        state.select_node(node_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_silent_node_cache_expires_with_the_clock() {
+        let limit = Duration::from_secs(60);
+        let at = |secs| Instant::from_secs(secs);
+        let mut cache = SilentNodeCache::default();
+        assert!(cache.needs_rebuild(at(100), limit, 2));
+        cache.store(at(100), limit, 2, vec![1], Some(at(130)));
+        assert_eq!(cache.silent(), &[1]);
+        assert!(cache.contains(1) && !cache.contains(2));
+
+        // Valid until the next node falls silent
+        assert!(!cache.needs_rebuild(at(130), limit, 2));
+        assert!(cache.needs_rebuild(at(131), limit, 2));
+        // The clock going back (log seek), a new interval or node count
+        assert!(cache.needs_rebuild(at(99), limit, 2));
+        assert!(cache.needs_rebuild(at(100), Duration::from_secs(30), 2));
+        assert!(cache.needs_rebuild(at(100), limit, 3));
+
+        cache.invalidate();
+        assert!(cache.needs_rebuild(at(100), limit, 2));
+    }
+}
@@ -0,0 +1,103 @@
+//! # Network Loop Profile Window
+//!
+//! Per-stage timing of the simulation's network loop (see
+//! `simulation::stage_timing`), refreshed once per second: calls, total and longest
+//! run of every stage and its share of real time. The busy share tells whether the
+//! loop can follow a higher simulation speed; the dominant stage tells which part of
+//! the scene limits it. Opened from the "Loop busy" counter in the System Metrics
+//! panel (Simulation mode).
+
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+
+use super::AppState;
+use crate::simulation::stage_timing::StageTimingReport;
+
+/// Busy share (percent) above which the loop is considered CPU-bound.
+pub const BUSY_WARNING_PERCENT: f64 = 80.0;
+
+/// Latest stage timings and window visibility.
+#[derive(Debug, Clone, Default)]
+pub struct LoopProfileState {
+    /// Whether the window is open.
+    pub open: bool,
+    /// Timings of the last reporting window (None until the first report).
+    pub report: Option<StageTimingReport>,
+}
+
+/// Render the loop profile window (if open).
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.loop_profile.open {
+        return;
+    }
+    let speed_percent = state.speed_percent;
+    let mut open = true;
+    egui::Window::new("Network loop profile")
+        .open(&mut open)
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            let Some(report) = &state.loop_profile.report else {
+                ui.label("Waiting for the first timing report...");
+                return;
+            };
+            let busy = report.busy_percent();
+            ui.label(format!(
+                "Busy {:.0}% of the last {} ms at {}% speed ({} loop iterations)",
+                busy, report.window_ms, speed_percent, report.iterations
+            ));
+            if busy >= BUSY_WARNING_PERCENT {
+                let dominant = report
+                    .dominant_stage()
+                    .map_or("-", |stage| stage.stage.label());
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!(
+                        "The loop is CPU-bound: a higher speed will fall behind. Most time goes to {}.",
+                        dominant
+                    ),
+                );
+            }
+            ui.separator();
+            TableBuilder::new(ui)
+                .striped(true)
+                .column(Column::auto().at_least(150.0))
+                .column(Column::auto().at_least(60.0))
+                .column(Column::auto().at_least(70.0))
+                .column(Column::auto().at_least(70.0))
+                .column(Column::remainder().at_least(100.0))
+                .header(20.0, |mut header| {
+                    for title in ["Stage", "Calls", "Total", "Longest", "Share"] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    for timing in &report.stages {
+                        body.row(18.0, |mut row| {
+                            row.col(|ui| {
+                                ui.label(timing.stage.label());
+                            });
+                            row.col(|ui| {
+                                ui.label(timing.calls.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:.1} ms", timing.total_us as f64 / 1000.0));
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{:.1} ms", timing.max_us as f64 / 1000.0));
+                            });
+                            row.col(|ui| {
+                                ui.add(
+                                    egui::ProgressBar::new((timing.share_percent / 100.0) as f32)
+                                        .text(format!("{:.1}%", timing.share_percent)),
+                                );
+                            });
+                        });
+                    }
+                });
+        });
+    if !open {
+        state.loop_profile.open = false;
+    }
+}
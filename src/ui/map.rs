@@ -3,36 +3,199 @@
 //! This module renders the main 2D map view showing:
 //! - A grid representing the dynamic world coordinate system
 //! - Obstacles (circles and rectangles) that block radio signals
-//! - Nodes as colored circles with optional ID labels
-//! - Selected node with a semi-transparent radio range indicator
+//! - Nodes as colored circles with optional ID labels, optionally colored by a
+//!   per-node metric with a legend (see `node_coloring`); the node bodies are
+//!   painted from a cached mesh (see `node_shapes`)
+//! - Selected node with a semi-transparent radio range indicator and its recent
+//!   receivers (see `delivery_flow`)
+//! - Optional neighbor graph linking nodes within mutual radio range
+//! - Optional collision heat overlay (see `collision_map`)
 //! - Animated radio transmission pulses expanding from transmitting nodes
 //!
 //! ## Coordinate Mapping
 //!
 //! The simulation uses world coordinates defined by top_left and bottom_right bounds.
-//! These are linearly mapped to screen pixels using `egui::lerp`, maintaining
-//! aspect ratio (from width/height in meters) by fitting the map centered in the available space.
+//! `MapTransform` maps them linearly to screen pixels (on top of the scene's
+//! `WorldTransform` for world unit ↔ meter conversion), maintaining the aspect ratio
+//! in meters by fitting the map centered in the available space.
 //!
 //! ## Radio Transmission Animation
 //!
 //! When a node transmits, an animated indicator shows a colored circle expanding
 //! from the node to its effective radio range over 1 second, fading from fully
-//! opaque to transparent. The color indicates the message type.
+//! opaque to transparent. The color indicates the message type. During transmission
+//! storms short packets are skipped and pulses merged per screen cell (see
+//! `animation_lod`).
 //!
 //! ## Node Selection
 //!
 //! Clicking on the map selects the nearest node (using squared distance for
 //! efficiency). Selecting a node triggers a `RequestNodeInfo` command to populate
 //! the right panel inspector with that node's message history.
+//!
+//! With "Move nodes" enabled (Simulation mode), nodes can be dragged to a new
+//! position; nodes with a switched-off radio are drawn hollow.
+//!
+//! With "Draw region" enabled, dragging a rectangle adds a statistics region (see
+//! `region_stats`).
+//!
+//! Scene annotations are drawn above the nodes; with an annotation tool picked,
+//! clicks place labels and drags add arrows or regions (see `annotations`).
+//!
+//! With "Probe signal" enabled, the link budget of the selected node is shown at
+//! the pointer or a pinned probe marker (see `signal_probe`).
+//!
+//! With "Ruler" enabled, clicks measure the distance between two points in meters
+//! instead of selecting nodes (see `ruler`).
+//!
+//! With "Pick origin" enabled, clicking a node starts a measurement from it (after
+//! a confirmation). Origins of tracked measurements are marked with a diamond.
+//!
+//! ## Zoom and Pan
+//!
+//! The mouse wheel zooms around the pointer and dragging with the right mouse
+//! button pans (`MapView`). The node search in the top panel centers and zooms
+//! the map on a node; in follow mode the selected node stays centered.
 
-use crate::simulation::Obstacle;
+use crate::common::units::WorldTransform;
+use crate::simulation::{Obstacle, Point};
+use crate::ui::animation_lod::{self, AnimationDetail, Pulse};
 use crate::ui::app_state::InspectorTab;
 use crate::ui::app_state::{NODE_RADIO_TRANSFER_INDICATOR_TIMEOUT, color_for_message_type};
-use crate::ui::{AppState, UICommand};
+use crate::ui::edit_history::{self, Edit};
+use crate::ui::neighbor_graph::NeighborGraphKey;
+use crate::ui::node_shapes::{self, NodeGlyph, NodeShapeKey};
+use crate::ui::{
+    AppState, MeasurementKind, OperatingMode, annotations, collision_map, delivery_flow,
+    neighbor_graph, node_coloring, obstacle_editor, reception_arrows, region_stats, ruler,
+    signal_probe,
+};
 use eframe::egui;
 use egui::Color32;
-use embassy_time::{Duration, Instant};
-use std::collections::HashMap;
+use embassy_time::Instant;
+use std::collections::{HashMap, HashSet};
+
+/// World unit ↔ screen pixel mapping for the map rectangle.
+///
+/// The map rectangle keeps the world's aspect ratio in meters, so a meter covers
+/// (nearly) the same number of pixels on both axes.
+#[derive(Debug, Clone, Copy)]
+pub struct MapTransform {
+    world: WorldTransform,
+    rect: egui::Rect,
+}
+
+impl MapTransform {
+    /// Create the mapping of `world` onto the screen rectangle `rect`.
+    pub fn new(world: WorldTransform, rect: egui::Rect) -> Self {
+        Self { world, rect }
+    }
+
+    /// World unit ↔ meter transform (and world bounds) of the mapping.
+    pub fn world(&self) -> &WorldTransform {
+        &self.world
+    }
+
+    /// Screen position of a point given in world units.
+    pub fn world_to_screen(&self, x: f64, y: f64) -> egui::Pos2 {
+        let (tx, ty) = self.world.normalize(x, y);
+        egui::pos2(
+            egui::lerp(self.rect.left()..=self.rect.right(), tx as f32),
+            egui::lerp(self.rect.top()..=self.rect.bottom(), ty as f32),
+        )
+    }
+
+    /// World point (in world units) under a screen position.
+    pub fn screen_to_world(&self, pos: egui::Pos2) -> (f64, f64) {
+        let tx = (pos.x - self.rect.left()) / self.rect.width();
+        let ty = (pos.y - self.rect.top()) / self.rect.height();
+        self.world.denormalize(tx as f64, ty as f64)
+    }
+
+    /// Average number of pixels per meter over both axes.
+    pub fn pixels_per_meter(&self) -> f32 {
+        let pixels_per_meter_x = self.rect.width() / self.world.width_meters() as f32;
+        let pixels_per_meter_y = self.rect.height() / self.world.height_meters() as f32;
+        (pixels_per_meter_x + pixels_per_meter_y) / 2.0
+    }
+
+    /// Convert a distance in meters to pixels.
+    pub fn meters_to_pixels(&self, meters: f32) -> f32 {
+        meters * self.pixels_per_meter()
+    }
+}
+
+/// Maximum map zoom factor.
+const MAX_ZOOM: f32 = 50.0;
+
+/// Zoom and pan state of the map.
+///
+/// The whole world is drawn into a virtual rectangle `zoom` times the size of the
+/// visible map rectangle, positioned so that `center` (normalized world position,
+/// 0..1 on both axes) is in the middle of the visible rectangle. All drawing uses
+/// the virtual rectangle; the painter clips to the visible one.
+#[derive(Debug, Clone, Copy)]
+pub struct MapView {
+    /// Zoom factor (1 = whole world visible).
+    pub zoom: f32,
+    /// Normalized world position shown in the middle of the map.
+    pub center: (f32, f32),
+}
+
+impl Default for MapView {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            center: (0.5, 0.5),
+        }
+    }
+}
+
+impl MapView {
+    /// Virtual rectangle of the whole world for the visible map rectangle `rect`.
+    pub fn view_rect(&self, rect: egui::Rect) -> egui::Rect {
+        let size = rect.size() * self.zoom;
+        let min = rect.center() - egui::vec2(self.center.0 * size.x, self.center.1 * size.y);
+        egui::Rect::from_min_size(min, size)
+    }
+
+    /// Center the view on a normalized world position (clamped to keep the map filled).
+    pub fn center_on(&mut self, center: (f32, f32)) {
+        let half_visible = 0.5 / self.zoom;
+        self.center = (
+            center.0.clamp(half_visible, 1.0 - half_visible),
+            center.1.clamp(half_visible, 1.0 - half_visible),
+        );
+    }
+
+    /// Multiply the zoom by `factor`, keeping the world point under `anchor` in place.
+    ///
+    /// # Parameters
+    ///
+    /// * `factor` - Zoom multiplier (>1 zooms in)
+    /// * `anchor` - Screen position that stays fixed (e.g. the pointer)
+    /// * `rect` - Visible map rectangle
+    pub fn zoom_at(&mut self, factor: f32, anchor: egui::Pos2, rect: egui::Rect) {
+        let view = self.view_rect(rect);
+        let anchored = (anchor - view.min) / view.size();
+        self.zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+        let size = rect.size() * self.zoom;
+        let min = anchor - egui::vec2(anchored.x * size.x, anchored.y * size.y);
+        self.center_on((
+            (rect.center().x - min.x) / size.x,
+            (rect.center().y - min.y) / size.y,
+        ));
+    }
+
+    /// Move the view by a screen-space offset (e.g. a pointer drag).
+    pub fn pan(&mut self, delta: egui::Vec2, rect: egui::Rect) {
+        let size = rect.size() * self.zoom;
+        self.center_on((
+            self.center.0 - delta.x / size.x,
+            self.center.1 - delta.y / size.y,
+        ));
+    }
+}
 
 /// Render the central map panel showing the simulation world.
 ///
@@ -40,7 +203,8 @@ use std::collections::HashMap;
 /// 1. Reserves a drawing area with proper aspect ratio, centered in the available space
 /// 2. Draws the background and coordinate grid
 /// 3. Renders obstacles, then nodes, then selection indicators
-/// 4. Handles mouse clicks for node selection
+/// 4. Draws obstacle editor handles (if obstacle editing is enabled)
+/// 5. Handles mouse clicks for node selection
 ///
 /// # Parameters
 ///
@@ -52,8 +216,8 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
         ui.separator();
 
         // Calculate aspect ratio from world dimensions (width/height in meters)
-        let aspect_ratio = if state.height > 0.0 {
-            (state.width / state.height) as f32
+        let aspect_ratio = if state.world.height_meters() > 0.0 {
+            (state.world.width_meters() / state.world.height_meters()) as f32
         } else {
             1.0 // Fallback to square if height is invalid
         };
@@ -80,11 +244,51 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
         let x = avail_rect.center().x - map_width / 2.0;
         let y = avail_rect.center().y - map_height / 2.0;
         let rect = egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(map_width, map_height));
-        let response = ui.interact(rect, egui::Id::new("map_canvas"), egui::Sense::click());
+        let moving_nodes = state.move_nodes && state.operating_mode == OperatingMode::Simulation;
+        let sense = if moving_nodes || state.region_stats.drawing || state.annotations.drawing() {
+            egui::Sense::click_and_drag()
+        } else {
+            egui::Sense::click()
+        };
+        let mut response = ui.interact(rect, egui::Id::new("map_canvas"), sense);
+        let picking_origin = state.pick_measurement_origin
+            && state.operating_mode != OperatingMode::LogVisualization;
+        if picking_origin {
+            response = response.on_hover_cursor(egui::CursorIcon::Crosshair);
+        }
         let painter = ui.painter_at(rect);
 
+        // Zoom with the mouse wheel around the pointer, pan with the right mouse button
+        if let Some(pointer) = response.hover_pos() {
+            let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+            if scroll != 0.0 {
+                state
+                    .map_view
+                    .zoom_at((scroll / 200.0).exp(), pointer, rect);
+            }
+            let (panning, delta) = ui.input(|i| (i.pointer.secondary_down(), i.pointer.delta()));
+            if panning && delta != egui::Vec2::ZERO {
+                state.map_view.pan(delta, rect);
+            }
+        }
+        // Keep the selected node centered in follow mode
+        if state.follow_selected {
+            if let Some(node) = state.selected.and_then(|i| state.nodes.get(i)) {
+                let (tx, ty) = state.world.normalize(node.position.x, node.position.y);
+                state.map_view.center_on((tx as f32, ty as f32));
+            }
+        }
+
+        // Everything below is drawn into the zoomed world rectangle; the painter clips it
+        let visible_rect = rect;
+        let rect = state.map_view.view_rect(visible_rect);
+
         // Draw background
-        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+        painter.rect_filled(
+            visible_rect,
+            4.0,
+            state.map_style.background_color(ui.visuals()),
+        );
 
         // Draw background image if loaded
         if let Some(ref texture) = state.background_image_texture {
@@ -92,63 +296,109 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
             painter.image(texture.id(), rect, uv, Color32::WHITE);
         }
 
-        // Draw grid: dark blue lines every 1000 world units
+        // Draw grid lines in the map style's color
         draw_grid(&painter, rect, state);
 
         // Draw obstacles before nodes so nodes appear on top
         draw_obstacles(&painter, rect, state);
 
+        // Draw collision hotspots (if enabled) under the links and nodes
+        if state.show_collision_map {
+            collision_map::draw(&painter, rect, state);
+        }
+
+        // Draw the statistics regions (and add a drawn one) under the links and nodes
+        region_stats::handle_map(&response, &painter, rect, state);
+
+        // Draw neighbor graph (if enabled) before nodes
+        if state.show_neighbor_graph {
+            draw_neighbor_graph(&painter, rect, state);
+        }
+
         // Draw connection matrix links (if active) before nodes
         if state.inspector_tab == InspectorTab::ConnectionMatrix {
             draw_connection_matrix_links(&painter, rect, state);
         }
 
+        // Draw the analyzer's observed receptions (and links shown from the event search) before nodes
+        reception_arrows::draw(&painter, rect, state);
+
         // Draw nodes scaled into rect
         draw_nodes(&painter, rect, state, ui);
 
+        // Draw the scene annotations above the nodes (and add one with the annotation tool)
+        let annotation_click = annotations::handle_map(&response, &painter, rect, state);
+
         // Draw selected node's radio range
         if let Some(selected) = state.selected {
             draw_radio_range(&painter, rect, &state.nodes[selected], state);
         }
 
-        // Handle selection by nearest node (squared-distance comparison)
-        handle_node_selection(&response, rect, state);
+        // Highlight the nodes that actually decoded the selected node's recent packets
+        if state.show_delivery_flow {
+            delivery_flow::draw(&painter, rect, state);
+        }
+
+        // Obstacle handles are registered after the canvas so they win over node selection
+        if state.obstacle_editor.enabled && state.operating_mode == OperatingMode::Simulation {
+            let transform = MapTransform::new(state.world, rect);
+            obstacle_editor::render_handles(ui, &painter, &transform, state);
+        }
+
+        // Node drags (topology editing) before selection so a drag does not count as a click
+        if moving_nodes {
+            handle_node_drag(&response, rect, state);
+        }
+
+        // The signal probe pins on clicks away from the nodes
+        let probe_click = state.signal_probe.enabled
+            && state.operating_mode == OperatingMode::Simulation
+            && signal_probe::handle(&response, &painter, rect, state);
+
+        // The ruler measures with every click while it is enabled
+        let ruler_click = state.ruler.enabled && ruler::handle(&response, &painter, rect, state);
+
+        // Clicking a node in origin picking mode starts a measurement instead of toggling selection
+        if !probe_click
+            && !ruler_click
+            && !annotation_click
+            && !(picking_origin && handle_origin_pick(&response, rect, state))
+        {
+            // Handle selection by nearest node (squared-distance comparison)
+            handle_node_selection(&response, rect, state);
+        }
     });
+
+    render_origin_confirmation(ctx, state);
 }
 
 /// Draw the coordinate grid with square cells.
 ///
-/// The longer dimension (width or height) is divided into 10 cells, and that spacing
-/// is used for both axes to create square grid cells. Renders dark blue lines.
+/// The cell size comes from the map style: a fixed number of meters, or the longer
+/// dimension (width or height) divided into 10 cells. The spacing is used for both
+/// axes to create square grid cells, in the map style's grid color, and is shown in
+/// the bottom-left corner of the map.
 ///
 /// # Parameters
 ///
 /// * `painter` - egui painter for drawing primitives
 /// * `rect` - The screen-space rectangle representing the map area
-/// * `state` - Application state for world bounds
+/// * `state` - Application state for world bounds and map style
 fn draw_grid(painter: &egui::Painter, rect: egui::Rect, state: &AppState) {
-    let grid_color = Color32::from_rgb(0, 0, 100);
-    let grid_stroke = egui::Stroke::new(1.0, grid_color);
+    let grid_stroke = egui::Stroke::new(1.0, state.map_style.grid_color());
 
-    let world_min_x = state.world_top_left.x;
-    let world_max_x = state.world_bottom_right.x;
-    let world_min_y = state.world_top_left.y;
-    let world_max_y = state.world_bottom_right.y;
+    let (world_min_x, world_min_y) = state.world.top_left();
+    let (world_max_x, world_max_y) = state.world.bottom_right();
     let world_width = (world_max_x - world_min_x).abs();
     let world_height = (world_max_y - world_min_y).abs();
 
-    // Divide the longer dimension (in meters) into 10 cells
-    let spacing_meters = if state.width >= state.height {
-        state.width / 10.0
-    } else {
-        state.height / 10.0
-    };
+    let spacing_meters = state
+        .map_style
+        .grid_spacing(state.world.width_meters(), state.world.height_meters());
 
     // Convert spacing from meters to world coordinates
-    let scale_x = world_width / state.width;
-    let scale_y = world_height / state.height;
-    let grid_spacing_x = spacing_meters * scale_x;
-    let grid_spacing_y = spacing_meters * scale_y;
+    let (grid_spacing_x, grid_spacing_y) =
+        state.world.offset_to_units(spacing_meters, spacing_meters);
 
     // Vertical lines (handle both normal and inverted X coordinates)
     let (x_start, x_end) = if world_min_x <= world_max_x {
@@ -191,27 +441,33 @@ fn draw_grid(painter: &egui::Painter, rect: egui::Rect, state: &AppState) {
         );
         y += grid_spacing_y;
     }
+
+    let corner = painter.clip_rect().left_bottom() + egui::vec2(6.0, -4.0);
+    painter.text(
+        corner,
+        egui::Align2::LEFT_BOTTOM,
+        format!("Grid: {:.0} m", spacing_meters),
+        egui::FontId::proportional(11.0),
+        Color32::GRAY,
+    );
 }
 /// Draw all obstacles (rectangles and circles) on the map.
 ///
-/// Obstacles are rendered as white filled shapes with white outlines.
-/// They represent physical barriers that block line-of-sight radio propagation.
+/// Obstacles are rendered as filled shapes with outlines in the map style's colors
+/// (white by default; a translucent fill keeps the background image visible).
+/// They represent physical barriers that block line-of-sight radio propagation;
+/// one-way obstacles carry an arrow pointing to their open front side.
 ///
 /// # Parameters
 ///
 /// * `painter` - egui painter for drawing
 /// * `rect` - The screen-space map rectangle
-/// * `state` - Application state for world bounds and obstacles
+/// * `state` - Application state for world bounds, obstacles and map style
 fn draw_obstacles(painter: &egui::Painter, rect: egui::Rect, state: &AppState) {
-    let obstacle_fill = Color32::from_rgba_unmultiplied(255, 255, 255, 255);
-    let obstacle_stroke = egui::Stroke::new(1.5, Color32::from_rgb(255, 255, 255));
+    let obstacle_fill = state.map_style.obstacle_fill_color();
+    let obstacle_stroke = egui::Stroke::new(1.5, state.map_style.obstacle_stroke_color());
 
-    let world_min_x = state.world_top_left.x;
-    let world_max_x = state.world_bottom_right.x;
-    let world_min_y = state.world_top_left.y;
-    let world_max_y = state.world_bottom_right.y;
-    let world_width = world_max_x - world_min_x;
-    let world_height = world_max_y - world_min_y;
+    let transform = MapTransform::new(state.world, rect);
 
     for obs in &state.obstacles {
         match obs {
@@ -223,53 +479,112 @@ fn draw_obstacles(painter: &egui::Painter, rect: egui::Rect, state: &AppState) {
                 let b = position.top_left.y.max(position.bottom_right.y);
 
                 // Map world coordinates to rect coordinates
-                let left = egui::lerp(
-                    rect.left()..=rect.right(),
-                    ((l - world_min_x) / world_width) as f32,
-                );
-                let right = egui::lerp(
-                    rect.left()..=rect.right(),
-                    ((r - world_min_x) / world_width) as f32,
-                );
-                let top = egui::lerp(
-                    rect.top()..=rect.bottom(),
-                    ((t - world_min_y) / world_height) as f32,
-                );
-                let bottom = egui::lerp(
-                    rect.top()..=rect.bottom(),
-                    ((b - world_min_y) / world_height) as f32,
-                );
-                let rect_px = egui::Rect::from_min_max(
-                    egui::pos2(left.min(right), top.min(bottom)),
-                    egui::pos2(left.max(right), top.max(bottom)),
+                let rect_px = egui::Rect::from_two_pos(
+                    transform.world_to_screen(l, t),
+                    transform.world_to_screen(r, b),
                 );
                 painter.rect_filled(rect_px, 0.0, obstacle_fill);
                 painter.rect_stroke(rect_px, 0.0, obstacle_stroke, egui::StrokeKind::Middle);
             }
             Obstacle::Circle { position, .. } => {
-                let cx = egui::lerp(
-                    rect.left()..=rect.right(),
-                    ((position.center.x - world_min_x) / world_width) as f32,
-                );
-                let cy = egui::lerp(
-                    rect.top()..=rect.bottom(),
-                    ((position.center.y - world_min_y) / world_height) as f32,
-                );
-
-                // Radius is in meters, convert to pixels:
-                // meters_to_pixels = meters * (pixels / meters)
-                // where pixels/meters = rect_pixels / world_meters
-                let pixels_per_meter_x = rect.width() / state.width as f32;
-                let pixels_per_meter_y = rect.height() / state.height as f32;
-                let avg_pixels_per_meter = (pixels_per_meter_x + pixels_per_meter_y) / 2.0;
+                let center_px = transform.world_to_screen(position.center.x, position.center.y);
 
-                let r = position.radius as f32 * avg_pixels_per_meter;
+                // Radius is in meters, convert to pixels
+                let r = transform.meters_to_pixels(position.radius as f32);
 
-                let center_px = egui::pos2(cx, cy);
                 painter.circle_filled(center_px, r, obstacle_fill);
                 painter.circle_stroke(center_px, r, obstacle_stroke);
             }
         }
+        // One-way obstacles: arrow towards the open front side
+        if let Some(one_way) = obs.one_way() {
+            let (center_x, center_y) = match obs {
+                Obstacle::Rectangle { position, .. } => (
+                    (position.top_left.x + position.bottom_right.x) / 2.0,
+                    (position.top_left.y + position.bottom_right.y) / 2.0,
+                ),
+                Obstacle::Circle { position, .. } => (position.center.x, position.center.y),
+            };
+            let center_px = transform.world_to_screen(center_x, center_y);
+            let direction = (transform
+                .world_to_screen(center_x + one_way.front.x, center_y + one_way.front.y)
+                - center_px)
+                .normalized();
+            painter.arrow(center_px, direction * 14.0, obstacle_stroke);
+        }
+    }
+}
+
+/// Draw the neighbor graph: an edge between every pair of nodes in mutual radio range.
+///
+/// The edges come from the neighbor graph cache (see `neighbor_graph`), recomputed
+/// only when the nodes or obstacles changed. Edges with link quality observed from
+/// echo traffic are colored by the scoring matrix limits and drawn thicker; edges
+/// without observations are thin and grey.
+///
+/// # Parameters
+///
+/// * `painter` - egui painter
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Mutable application state (nodes, obstacles, observed link qualities
+///   and the neighbor graph cache)
+fn draw_neighbor_graph(painter: &egui::Painter, rect: egui::Rect, state: &mut AppState) {
+    let key = NeighborGraphKey {
+        world: state.world,
+        node_count: state.nodes.len(),
+        obstacle_count: state.obstacles.len(),
+    };
+    if state.neighbor_graph.needs_rebuild(&key) {
+        let edges = neighbor_graph::find_edges(&state.nodes, &state.obstacles, &state.world);
+        state.neighbor_graph.store(key, edges);
+    }
+
+    let transform = MapTransform::new(state.world, rect);
+    let screen_positions: Vec<egui::Pos2> = state
+        .nodes
+        .iter()
+        .map(|p| transform.world_to_screen(p.position.x, p.position.y))
+        .collect();
+
+    let unobserved_stroke =
+        egui::Stroke::new(1.0, Color32::from_rgba_unmultiplied(160, 160, 160, 90));
+
+    for &(i, j) in state.neighbor_graph.edges() {
+        let a = &state.nodes[i];
+        let b = &state.nodes[j];
+
+        // Use the better of the two directions if both were observed
+        let observed = [
+            state.observed_link_qualities.get(&(a.node_id, b.node_id)),
+            state.observed_link_qualities.get(&(b.node_id, a.node_id)),
+        ]
+        .into_iter()
+        .flatten()
+        .copied()
+        .max();
+
+        let stroke = match observed {
+            Some(lq) => {
+                let color = if state.poor_limit > 0
+                    && state.excellent_limit > 0
+                    && state.poor_limit < state.excellent_limit
+                {
+                    if lq <= state.poor_limit {
+                        Color32::RED
+                    } else if lq >= state.excellent_limit {
+                        Color32::GREEN
+                    } else {
+                        Color32::YELLOW
+                    }
+                } else {
+                    Color32::from_rgb(180, 180, 180)
+                };
+                egui::Stroke::new(2.0, color)
+            }
+            None => unobserved_stroke,
+        };
+
+        painter.line_segment([screen_positions[i], screen_positions[j]], stroke);
     }
 }
 
@@ -287,27 +602,17 @@ fn draw_connection_matrix_links(painter: &egui::Painter, rect: egui::Rect, state
         None => return,
     };
 
-    let world_min_x = state.world_top_left.x;
-    let world_max_x = state.world_bottom_right.x;
-    let world_min_y = state.world_top_left.y;
-    let world_max_y = state.world_bottom_right.y;
-    let world_width = world_max_x - world_min_x;
-    let world_height = world_max_y - world_min_y;
-
-    let mut positions: HashMap<u32, egui::Pos2> = HashMap::new();
-    for p in &state.nodes {
-        let pos = egui::pos2(
-            egui::lerp(
-                rect.left()..=rect.right(),
-                ((p.position.x - world_min_x) / world_width) as f32,
-            ),
-            egui::lerp(
-                rect.top()..=rect.bottom(),
-                ((p.position.y - world_min_y) / world_height) as f32,
-            ),
-        );
-        positions.insert(p.node_id, pos);
-    }
+    let transform = MapTransform::new(state.world, rect);
+    let positions: HashMap<u32, egui::Pos2> = state
+        .nodes
+        .iter()
+        .map(|p| {
+            (
+                p.node_id,
+                transform.world_to_screen(p.position.x, p.position.y),
+            )
+        })
+        .collect();
 
     let node_count = matrix.node_ids.len();
     if node_count == 0 {
@@ -406,13 +711,7 @@ fn draw_connection_matrix_links(painter: &egui::Painter, rect: egui::Rect, state
 /// * `ui` - UI context for text rendering
 fn draw_nodes(painter: &egui::Painter, rect: egui::Rect, state: &mut AppState, ui: &egui::Ui) {
     let radius = 4.0;
-
-    let world_min_x = state.world_top_left.x;
-    let world_max_x = state.world_bottom_right.x;
-    let world_min_y = state.world_top_left.y;
-    let world_max_y = state.world_bottom_right.y;
-    let world_width = world_max_x - world_min_x;
-    let world_height = world_max_y - world_min_y;
+    let transform = MapTransform::new(state.world, rect);
 
     // Collect expired indicators first to avoid borrowing issues
     let expired_indicators: Vec<u32> = state
@@ -427,30 +726,91 @@ fn draw_nodes(painter: &egui::Painter, rect: egui::Rect, state: &mut AppState, u
         state.node_radio_transfer_indicators.remove(&id);
     }
 
-    for (idx, p) in state.nodes.iter().enumerate() {
-        let pos = egui::pos2(
-            egui::lerp(
-                rect.left()..=rect.right(),
-                ((p.position.x - world_min_x) / world_width) as f32,
-            ),
-            egui::lerp(
-                rect.top()..=rect.bottom(),
-                ((p.position.y - world_min_y) / world_height) as f32,
-            ),
-        );
+    // Nodes without log events for longer than the silence interval (analyzer modes)
+    let silence_known = match state.analyzer_now() {
+        Some(now) => {
+            state.refresh_silent_nodes(now, crate::ui::liveness::silence_limit(state));
+            true
+        }
+        None => false,
+    };
+    let silent_nodes = &state.silent_node_cache;
 
-        let is_selected = state.selected == Some(idx);
-        let mut color = if is_selected {
-            Color32::from_rgb(0, 255, 0) // Green for selected node
+    // Origins of all tracked measurements get a diamond marker
+    let measurement_origins: HashSet<u32> = state
+        .measurements
+        .values()
+        .map(|m| m.origin_node_id)
+        .collect();
+    let active_origin = state.active_measurement().map(|m| m.origin_node_id);
+
+    // Optional coloring by a per-node metric, with its legend
+    let color_scale =
+        node_coloring::NodeColorScale::new(state.node_color_metric, &state.node_statistics);
+    if let Some(scale) = &color_scale {
+        node_coloring::draw_legend(painter, scale);
+    }
+
+    // Node bodies come from the cached mesh, rebuilt only when they changed
+    let active_measurement = state.active_measurement();
+    let key = NodeShapeKey {
+        rect,
+        world: state.world,
+        node_count: state.nodes.len(),
+        selected: state.selected,
+        metric: state.node_color_metric,
+        measurement_identifier: state.measurement_identifier,
+        reached_count: active_measurement.map_or(0, |m| m.reached_nodes.len()),
+        silent_hash: if silence_known {
+            silent_nodes.hash()
         } else {
-            Color32::from_rgb(40, 200, 255) // Cyan for others
-        };
+            0
+        },
+    };
+    if state.node_shapes.needs_rebuild(&key) {
+        let bodies = state.nodes.iter().enumerate().map(|(idx, p)| {
+            let is_selected = state.selected == Some(idx);
+            let mut color = if is_selected {
+                Color32::from_rgb(0, 255, 0) // Green for selected node
+            } else if let Some(scale) = &color_scale {
+                scale.color(state.node_statistics.get(&p.node_id))
+            } else {
+                Color32::from_rgb(40, 200, 255) // Cyan for others
+            };
 
-        if state.measurement_identifier != 0 && state.reached_nodes.contains(&p.node_id) {
-            color = Color32::from_rgb(255, 255, 0); // Yellow if reached in current measurement
-        }
+            if active_measurement.map_or(false, |m| m.reached_nodes.contains(&p.node_id)) {
+                color = Color32::from_rgb(255, 255, 0); // Yellow if reached in current measurement
+            }
+
+            if !is_selected && silence_known && silent_nodes.contains(p.node_id) {
+                color = Color32::from_rgb(110, 110, 110); // Grey if silent
+            }
+
+            // Nodes with a switched-off radio are drawn hollow, gateways as larger squares
+            let glyph = if p.disabled {
+                NodeGlyph::Ring
+            } else if p.is_gateway {
+                NodeGlyph::Gateway
+            } else {
+                NodeGlyph::Disc
+            };
+            (
+                transform.world_to_screen(p.position.x, p.position.y),
+                glyph,
+                color,
+            )
+        });
+        let mesh = node_shapes::build_mesh(bodies, radius);
+        state.node_shapes.store(key, mesh);
+    }
+    if let Some(shape) = state.node_shapes.shape() {
+        painter.add(shape);
+    }
 
-        painter.circle_filled(pos, radius, color);
+    let mut pulses = Vec::new();
+    for (idx, p) in state.nodes.iter().enumerate() {
+        let pos = transform.world_to_screen(p.position.x, p.position.y);
+        let is_selected = state.selected == Some(idx);
 
         // Optional ID label next to each node
         if state.show_node_ids {
@@ -489,12 +849,49 @@ fn draw_nodes(painter: &egui::Painter, rect: egui::Rect, state: &mut AppState, u
             );
         }
 
-        // Draw radio transfer indicator
-        draw_radio_indicator(painter, rect, state, &pos, p.node_id);
+        if measurement_origins.contains(&p.node_id) {
+            draw_origin_marker(painter, pos, active_origin == Some(p.node_id));
+        }
+
+        pulses.extend(radio_pulse(&transform, state, pos, p.node_id));
     }
+
+    // Radio transfer indicators, merged per screen cell during transmission storms
+    state.animation_lod.update(Instant::now());
+    if state.animation_lod.detail() == AnimationDetail::Aggregated {
+        pulses = animation_lod::aggregate(&pulses);
+    }
+    for pulse in &pulses {
+        let width = 1.0 + (pulse.count as f32).log2().min(3.0);
+        let color = color_for_message_type(pulse.message_type, pulse.alpha);
+        painter.circle_stroke(pulse.pos, pulse.radius, egui::Stroke::new(width, color));
+    }
+}
+
+/// Draw the diamond marker of a measurement origin around a node.
+///
+/// # Parameters
+///
+/// * `painter` - egui painter
+/// * `pos` - Screen position of the origin node
+/// * `active` - Whether the origin belongs to the displayed measurement (drawn brighter)
+fn draw_origin_marker(painter: &egui::Painter, pos: egui::Pos2, active: bool) {
+    let size = if active { 11.0 } else { 9.0 };
+    let stroke = if active {
+        egui::Stroke::new(2.5, Color32::from_rgb(255, 0, 200))
+    } else {
+        egui::Stroke::new(1.5, Color32::from_rgba_unmultiplied(255, 0, 200, 150))
+    };
+    let diamond = vec![
+        egui::pos2(pos.x, pos.y - size),
+        egui::pos2(pos.x + size, pos.y),
+        egui::pos2(pos.x, pos.y + size),
+        egui::pos2(pos.x - size, pos.y),
+    ];
+    painter.add(egui::Shape::closed_line(diamond, stroke));
 }
 
-/// Draw an animated radio transmission indicator for a node.
+/// Current state of a node's animated radio transmission indicator.
 ///
 /// The indicator shows as an expanding, fading circle representing the RF transmission.
 /// The animation lasts 1 second, growing from the node to its effective distance while
@@ -502,38 +899,37 @@ fn draw_nodes(painter: &egui::Painter, rect: egui::Rect, state: &mut AppState, u
 ///
 /// # Parameters
 ///
-/// * `painter` - egui painter
-/// * `rect` - Screen-space map rectangle
+/// * `transform` - World ↔ screen mapping of the map rectangle
 /// * `state` - Application state (for indicator data)
 /// * `pos` - Screen position of the transmitting node
 /// * `node_id` - ID of the node to check for active indicators
-fn draw_radio_indicator(
-    painter: &egui::Painter,
-    rect: egui::Rect,
+///
+/// # Returns
+///
+/// The pulse to draw, or `None` if the node has no visible indicator.
+fn radio_pulse(
+    transform: &MapTransform,
     state: &AppState,
-    pos: &egui::Pos2,
+    pos: egui::Pos2,
     node_id: u32,
-) {
-    if let Some((expiry, message_type, distance)) =
-        state.node_radio_transfer_indicators.get(&node_id)
-    {
-        let now = Instant::now();
-        if *expiry > now {
-            let remaining = *expiry - now;
-            if remaining > Duration::from_millis(0) {
-                let alpha = (remaining.as_millis() as f32
-                    / NODE_RADIO_TRANSFER_INDICATOR_TIMEOUT as f32)
-                    .clamp(0.0, 1.0);
-                // Distance is in meters, convert to pixels
-                let pixels_per_meter_x = rect.width() / state.width as f32;
-                let pixels_per_meter_y = rect.height() / state.height as f32;
-                let avg_pixels_per_meter = (pixels_per_meter_x + pixels_per_meter_y) / 2.0;
-                let radius = (*distance as f32 * avg_pixels_per_meter) * (1.0 - alpha);
-                let color = color_for_message_type(*message_type, alpha);
-                painter.circle_stroke(*pos, radius, egui::Stroke::new(1.0, color));
-            }
-        }
+) -> Option<Pulse> {
+    let (expiry, message_type, distance) = state.node_radio_transfer_indicators.get(&node_id)?;
+    let now = Instant::now();
+    if *expiry <= now || state.hidden_animation_types.contains(message_type) {
+        return None;
     }
+    let remaining = *expiry - now;
+    let alpha = (remaining.as_millis() as f32 / NODE_RADIO_TRANSFER_INDICATOR_TIMEOUT as f32)
+        .clamp(0.0, 1.0);
+    // Distance is in meters, convert to pixels
+    let radius = transform.meters_to_pixels(*distance as f32) * (1.0 - alpha);
+    Some(Pulse {
+        pos,
+        radius,
+        alpha,
+        message_type: *message_type,
+        count: 1,
+    })
 }
 
 /// Draw the effective radio range for the selected node.
@@ -552,29 +948,11 @@ fn draw_radio_range(
     selected_node: &crate::ui::NodeUIState,
     state: &AppState,
 ) {
-    let world_min_x = state.world_top_left.x;
-    let world_max_x = state.world_bottom_right.x;
-    let world_min_y = state.world_top_left.y;
-    let world_max_y = state.world_bottom_right.y;
-    let world_width = world_max_x - world_min_x;
-    let world_height = world_max_y - world_min_y;
-
-    let pos = egui::pos2(
-        egui::lerp(
-            rect.left()..=rect.right(),
-            ((selected_node.position.x - world_min_x) / world_width) as f32,
-        ),
-        egui::lerp(
-            rect.top()..=rect.bottom(),
-            ((selected_node.position.y - world_min_y) / world_height) as f32,
-        ),
-    );
+    let transform = MapTransform::new(state.world, rect);
+    let pos = transform.world_to_screen(selected_node.position.x, selected_node.position.y);
 
     // Radio strength is in meters, convert to pixels
-    let pixels_per_meter_x = rect.width() / state.width as f32;
-    let pixels_per_meter_y = rect.height() / state.height as f32;
-    let avg_pixels_per_meter = (pixels_per_meter_x + pixels_per_meter_y) / 2.0;
-    let radius = selected_node.radio_strength as f32 * avg_pixels_per_meter;
+    let radius = transform.meters_to_pixels(selected_node.radio_strength as f32);
     painter.circle_filled(pos, radius, Color32::from_rgba_unmultiplied(0, 255, 0, 50));
     painter.circle_stroke(
         pos,
@@ -587,7 +965,7 @@ fn draw_radio_range(
 ///
 /// Finds the nearest node to the click position using squared distance (to avoid sqrt).
 /// If a node is clicked again, it is deselected. Selecting a node sends a
-/// `RequestNodeInfo` command to populate the inspector panel.
+/// request for the node's details (`AppState::request_node_info`) to populate the inspector panel.
 ///
 /// # Parameters
 ///
@@ -597,25 +975,11 @@ fn draw_radio_range(
 fn handle_node_selection(response: &egui::Response, rect: egui::Rect, state: &mut AppState) {
     if response.clicked() {
         if let Some(click_pos) = response.interact_pointer_pos() {
-            let world_min_x = state.world_top_left.x;
-            let world_max_x = state.world_bottom_right.x;
-            let world_min_y = state.world_top_left.y;
-            let world_max_y = state.world_bottom_right.y;
-            let world_width = world_max_x - world_min_x;
-            let world_height = world_max_y - world_min_y;
+            let transform = MapTransform::new(state.world, rect);
 
             let mut best: Option<(usize, f32)> = None;
             for (i, p) in state.nodes.iter().enumerate() {
-                let pos = egui::pos2(
-                    egui::lerp(
-                        rect.left()..=rect.right(),
-                        ((p.position.x - world_min_x) / world_width) as f32,
-                    ),
-                    egui::lerp(
-                        rect.top()..=rect.bottom(),
-                        ((p.position.y - world_min_y) / world_height) as f32,
-                    ),
-                );
+                let pos = transform.world_to_screen(p.position.x, p.position.y);
                 let d2 = pos.distance_sq(click_pos);
                 if best.map_or(true, |(_, bd)| d2 < bd) {
                     best = Some((i, d2));
@@ -625,11 +989,7 @@ fn handle_node_selection(response: &egui::Response, rect: egui::Rect, state: &mu
             if new_selected != state.selected {
                 state.selected = new_selected;
                 if let Some(new_selected) = new_selected {
-                    let node_id = &state.nodes[new_selected].node_id;
-                    state
-                        .ui_command_tx
-                        .try_send(UICommand::RequestNodeInfo(node_id.clone()))
-                        .ok();
+                    state.request_node_info(state.nodes[new_selected].node_id);
                 }
             } else {
                 state.selected = None;
@@ -637,3 +997,187 @@ fn handle_node_selection(response: &egui::Response, rect: egui::Rect, state: &mu
         }
     }
 }
+
+/// Handle node drags on the map (with "Move nodes" enabled in Simulation mode).
+///
+/// A drag starting within a few pixels of a node moves that node; the node follows
+/// the pointer on the map and the finished move is applied to the simulation as
+/// one undoable edit.
+///
+/// # Parameters
+///
+/// * `response` - egui response from the map interaction area
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Mutable application state (nodes, drag state, edit history)
+fn handle_node_drag(response: &egui::Response, rect: egui::Rect, state: &mut AppState) {
+    const GRAB_DISTANCE: f32 = 10.0;
+    let transform = MapTransform::new(state.world, rect);
+
+    if response.drag_started() {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            state.node_drag = state
+                .nodes
+                .iter()
+                .map(|n| {
+                    let pos = transform.world_to_screen(n.position.x, n.position.y);
+                    (n, pos.distance(pointer))
+                })
+                .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(n, _)| (n.node_id, n.position.clone()));
+        }
+    }
+
+    let Some((node_id, from)) = state.node_drag.clone() else {
+        return;
+    };
+    if response.dragged() {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let (x, y) = transform.screen_to_world(pointer);
+            let (x, y) = state.world.clamp(x, y);
+            if let Some(node) = state.nodes.iter_mut().find(|n| n.node_id == node_id) {
+                node.position = Point { x, y };
+                state.node_shapes.invalidate();
+                state.neighbor_graph.invalidate();
+            }
+        }
+    }
+    if response.drag_stopped() {
+        state.node_drag = None;
+        let to = state
+            .nodes
+            .iter()
+            .find(|n| n.node_id == node_id)
+            .map(|n| n.position.clone());
+        if let Some(to) = to.filter(|to| to.x != from.x || to.y != from.y) {
+            edit_history::perform(state, Edit::MoveNode { node_id, from, to });
+        }
+    }
+}
+
+/// Handle a click on the map in measurement origin picking mode.
+///
+/// A click within a few pixels of a node selects it and either asks for
+/// confirmation or (if confirmation was turned off) starts the measurement at once.
+///
+/// # Parameters
+///
+/// * `response` - egui response from the map interaction area
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Mutable application state
+///
+/// # Returns
+///
+/// `true` if the click hit a node (and was consumed), `false` otherwise.
+fn handle_origin_pick(response: &egui::Response, rect: egui::Rect, state: &mut AppState) -> bool {
+    const PICK_DISTANCE: f32 = 12.0;
+    if !response.clicked() {
+        return false;
+    }
+    let Some(click_pos) = response.interact_pointer_pos() else {
+        return false;
+    };
+    let transform = MapTransform::new(state.world, rect);
+    let picked = state
+        .nodes
+        .iter()
+        .map(|n| {
+            let pos = transform.world_to_screen(n.position.x, n.position.y);
+            (n.node_id, pos.distance(click_pos))
+        })
+        .filter(|(_, distance)| *distance <= PICK_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(node_id, _)| node_id);
+    let Some(node_id) = picked else {
+        return false;
+    };
+
+    state.select_node(node_id);
+    if state.confirm_measurement_origin {
+        state.pending_measurement_origin = Some(node_id);
+    } else {
+        state.start_measurement(node_id, MeasurementKind::Block);
+    }
+    true
+}
+
+/// Render the confirmation dialog of a picked measurement origin (if any).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+fn render_origin_confirmation(ctx: &egui::Context, state: &mut AppState) {
+    let Some(node_id) = state.pending_measurement_origin else {
+        return;
+    };
+
+    let mut decision: Option<bool> = None;
+    // A modal dialog: the map and the panels behind it get no input while it is open,
+    // and Enter / Escape only answer the dialog
+    let modal =
+        egui::Modal::new(egui::Id::new("measurement_origin_confirmation")).show(ctx, |ui| {
+            ui.heading("Start measurement");
+            ui.label(format!("Start a measurement from node #{}?", node_id));
+            let mut dont_ask = !state.confirm_measurement_origin;
+            if ui
+                .checkbox(&mut dont_ask, "Don't ask again this session")
+                .changed()
+            {
+                state.confirm_measurement_origin = !dont_ask;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Start").clicked() {
+                    decision = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    decision = Some(false);
+                }
+            });
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)) {
+                decision = Some(true);
+            }
+        });
+    // Escape or a click outside the dialog
+    if decision.is_none() && modal.should_close() {
+        decision = Some(false);
+    }
+
+    match decision {
+        Some(true) => {
+            state.pending_measurement_origin = None;
+            state.start_measurement(node_id, MeasurementKind::Block);
+        }
+        Some(false) => {
+            state.pending_measurement_origin = None;
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_view_zoom_keeps_anchor_and_stays_inside_world() {
+        let rect = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(400.0, 200.0));
+        let mut view = MapView::default();
+        assert_eq!(view.view_rect(rect), rect);
+
+        // The world point under the anchor stays under the anchor
+        let anchor = egui::pos2(300.0, 50.0);
+        view.zoom_at(2.0, anchor, rect);
+        let zoomed = view.view_rect(rect);
+        assert_eq!(zoomed.size(), egui::vec2(800.0, 400.0));
+        assert!(((anchor.x - zoomed.min.x) / zoomed.width() - 0.75).abs() < 1e-5);
+        assert!(((anchor.y - zoomed.min.y) / zoomed.height() - 0.25).abs() < 1e-5);
+
+        // Centering near the edge is clamped so the map stays filled
+        view.center_on((1.0, 0.0));
+        assert_eq!(view.center, (0.75, 0.25));
+        view.zoom_at(0.1, anchor, rect);
+        assert_eq!(view.zoom, 1.0);
+        assert_eq!(view.view_rect(rect), rect);
+    }
+}
@@ -0,0 +1,219 @@
+//! # Map Style
+//!
+//! Colors of the map background, the coordinate grid and the obstacles, edited in the
+//! "Map style" window and persisted with the other application settings. The
+//! obstacle fill has its own opacity so a background image (e.g. imported map
+//! imagery the obstacles are aligned with) stays visible beneath the obstacles.
+//! The dark and light presets set all colors at once. The grid spacing is automatic
+//! (the longer side of the world in 10 cells) or a fixed number of meters per cell.
+
+use eframe::egui;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use super::AppState;
+
+/// Background of the map area.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MapBackground {
+    /// The theme's darkest background color.
+    #[default]
+    Dark,
+    /// A light grey background.
+    Light,
+}
+
+/// Grid lines drawn at most along the longer side of the world; finer spacings are coarsened.
+const MAX_GRID_CELLS: f64 = 200.0;
+
+/// Map colors and grid spacing; all colors are unmultiplied sRGBA.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapStyle {
+    pub background: MapBackground,
+    pub grid: [u8; 4],
+    pub obstacle_fill: [u8; 4],
+    pub obstacle_stroke: [u8; 4],
+    /// Grid cell size in meters (None = the longer side of the world in 10 cells).
+    pub grid_spacing_meters: Option<f64>,
+}
+
+impl Default for MapStyle {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl MapStyle {
+    /// Dark background, dark blue grid and white obstacles.
+    pub fn dark() -> Self {
+        Self {
+            background: MapBackground::Dark,
+            grid: [0, 0, 100, 255],
+            obstacle_fill: [255, 255, 255, 255],
+            obstacle_stroke: [255, 255, 255, 255],
+            grid_spacing_meters: None,
+        }
+    }
+
+    /// Light background, grey-blue grid and semi-transparent dark obstacles.
+    pub fn light() -> Self {
+        Self {
+            background: MapBackground::Light,
+            grid: [150, 150, 200, 255],
+            obstacle_fill: [70, 70, 70, 140],
+            obstacle_stroke: [40, 40, 40, 255],
+            grid_spacing_meters: None,
+        }
+    }
+
+    /// Color of the map background for the current egui visuals.
+    pub fn background_color(&self, visuals: &egui::Visuals) -> Color32 {
+        match self.background {
+            MapBackground::Dark => visuals.extreme_bg_color,
+            MapBackground::Light => Color32::from_rgb(235, 235, 235),
+        }
+    }
+
+    /// Color of the grid lines.
+    pub fn grid_color(&self) -> Color32 {
+        to_color(self.grid)
+    }
+
+    /// Grid cell size in meters for a world of the given size.
+    ///
+    /// # Returns
+    ///
+    /// The configured spacing, coarsened so the longer side has at most
+    /// `MAX_GRID_CELLS` cells, or a tenth of the longer side when automatic.
+    pub fn grid_spacing(&self, width_meters: f64, height_meters: f64) -> f64 {
+        let longer = width_meters.max(height_meters);
+        match self.grid_spacing_meters.filter(|spacing| *spacing > 0.0) {
+            Some(spacing) => spacing.max(longer / MAX_GRID_CELLS),
+            None => longer / 10.0,
+        }
+    }
+
+    /// Fill color of the obstacles (its alpha is the fill opacity).
+    pub fn obstacle_fill_color(&self) -> Color32 {
+        to_color(self.obstacle_fill)
+    }
+
+    /// Outline color of the obstacles.
+    pub fn obstacle_stroke_color(&self) -> Color32 {
+        to_color(self.obstacle_stroke)
+    }
+}
+
+fn to_color([r, g, b, a]: [u8; 4]) -> Color32 {
+    Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Render the map style window (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (map style)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    let mut open = state.show_map_style;
+    // Switching from automatic to a fixed spacing starts from the automatic one
+    let automatic_spacing =
+        MapStyle::default().grid_spacing(state.world.width_meters(), state.world.height_meters());
+    egui::Window::new("Map style")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let style = &mut state.map_style;
+            ui.horizontal(|ui| {
+                ui.label("Background:");
+                ui.radio_value(&mut style.background, MapBackground::Dark, "Dark");
+                ui.radio_value(&mut style.background, MapBackground::Light, "Light");
+            });
+            egui::Grid::new("map_style_colors").show(ui, |ui| {
+                ui.label("Grid:");
+                egui::color_picker::color_edit_button_srgba_unmultiplied(ui, &mut style.grid);
+                ui.end_row();
+                ui.label("Obstacle fill:");
+                egui::color_picker::color_edit_button_srgba_unmultiplied(
+                    ui,
+                    &mut style.obstacle_fill,
+                );
+                ui.end_row();
+                ui.label("Fill opacity:");
+                ui.add(egui::Slider::new(&mut style.obstacle_fill[3], 0..=255));
+                ui.end_row();
+                ui.label("Obstacle outline:");
+                egui::color_picker::color_edit_button_srgba_unmultiplied(
+                    ui,
+                    &mut style.obstacle_stroke,
+                );
+                ui.end_row();
+                ui.label("Grid spacing:");
+                ui.horizontal(|ui| {
+                    let mut automatic = style.grid_spacing_meters.is_none();
+                    if ui.checkbox(&mut automatic, "Automatic").changed() {
+                        style.grid_spacing_meters = if automatic {
+                            None
+                        } else {
+                            Some(automatic_spacing.round().max(1.0))
+                        };
+                    }
+                    if let Some(spacing) = &mut style.grid_spacing_meters {
+                        ui.add(
+                            egui::DragValue::new(spacing)
+                                .range(1.0..=f64::MAX)
+                                .speed(1.0)
+                                .suffix(" m"),
+                        )
+                        .on_hover_text("Meters per grid cell");
+                    }
+                });
+                ui.end_row();
+            });
+            ui.horizontal(|ui| {
+                // Presets set the colors and keep the grid spacing
+                if ui.button("Dark preset").clicked() {
+                    *style = MapStyle {
+                        grid_spacing_meters: style.grid_spacing_meters,
+                        ..MapStyle::dark()
+                    };
+                }
+                if ui.button("Light preset").clicked() {
+                    *style = MapStyle {
+                        grid_spacing_meters: style.grid_spacing_meters,
+                        ..MapStyle::light()
+                    };
+                }
+            });
+        });
+    state.show_map_style = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_settings_fall_back_to_the_dark_style() {
+        let style: MapStyle =
+            serde_json::from_str(r#"{ "obstacle_fill": [255, 255, 255, 64] }"#).unwrap();
+        assert_eq!(style.background, MapBackground::Dark);
+        assert_eq!(style.grid, MapStyle::dark().grid);
+        assert_eq!(
+            style.obstacle_fill_color(),
+            Color32::from_rgba_unmultiplied(255, 255, 255, 64)
+        );
+        assert_ne!(MapStyle::light().obstacle_fill[3], 255);
+
+        // Automatic spacing: the longer side in 10 cells; fixed spacings are capped in count
+        assert_eq!(style.grid_spacing(2000.0, 500.0), 200.0);
+        let fixed = MapStyle {
+            grid_spacing_meters: Some(50.0),
+            ..style
+        };
+        assert_eq!(fixed.grid_spacing(2000.0, 500.0), 50.0);
+        assert_eq!(fixed.grid_spacing(100_000.0, 500.0), 500.0);
+    }
+}
@@ -0,0 +1,586 @@
+//! # Measurement History
+//!
+//! Finished measurements are kept across sessions: when a measurement reaches all
+//! nodes or stalls, its record (scene, seed, origin, milestones and per-node reach
+//! times) is appended to `measurement_history.jsonl` next to the scene file, one
+//! JSON object per line. The history browser ("History" in the Measured data panel)
+//! lists the records of the scene's directory, compares the selected ones side by
+//! side, plots their reach curves (share of nodes reached over time) and re-opens a
+//! record as the displayed measurement, without rerunning the simulation.
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::app_state::{MeasurementMilestone, MeasurementProgress, MeasurementStall};
+use super::recent_files::RecentMode;
+use super::{AppState, MeasurementKind};
+use crate::common::provenance::{self, Provenance};
+
+/// File name of the history, in the directory of the scene file.
+const HISTORY_FILE_NAME: &str = "measurement_history.jsonl";
+
+/// Colors of the compared records' curves, in selection order.
+const CURVE_COLORS: [egui::Color32; 6] = [
+    egui::Color32::LIGHT_BLUE,
+    egui::Color32::YELLOW,
+    egui::Color32::LIGHT_GREEN,
+    egui::Color32::LIGHT_RED,
+    egui::Color32::GOLD,
+    egui::Color32::WHITE,
+];
+
+/// A finished measurement as stored in the history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeasurementRecord {
+    /// Local time the record was saved (RFC 3339).
+    pub recorded_at: String,
+    pub mode: RecentMode,
+    pub scene_path: Option<String>,
+    /// Hash of the resolved scene (see `provenance`).
+    pub scene_hash: Option<String>,
+    /// Random seed of the simulation (None in the analyzer modes).
+    pub seed: Option<u64>,
+    pub measurement_id: u32,
+    pub kind: MeasurementKind,
+    pub origin_node_id: u32,
+    /// Number of nodes in the scene.
+    pub node_count: usize,
+    /// Elapsed time at the last measurement packet (seconds).
+    pub total_time: u64,
+    pub total_message_count: u32,
+    /// Milestones matching `MEASUREMENT_MILESTONE_PERCENTAGES`.
+    pub milestones: [Option<MeasurementMilestone>; 3],
+    /// Nodes not reached when the measurement stalled, sorted by ID.
+    pub unreached_nodes: Vec<u32>,
+    /// Elapsed time (seconds) at which each node was first reached.
+    pub reach_times: BTreeMap<u32, f64>,
+    /// Hop depth of each reached node with a known relayer chain (see `hop_depth`).
+    #[serde(default)]
+    pub hops: BTreeMap<u32, u32>,
+}
+
+impl MeasurementRecord {
+    /// Build the record of a measurement.
+    ///
+    /// # Parameters
+    ///
+    /// * `measurement_id` - Identifier of the measurement
+    /// * `measurement` - The measurement's progress
+    /// * `node_count` - Number of nodes in the scene
+    /// * `mode` - Operating mode the measurement ran in
+    /// * `provenance` - Provenance of the running session
+    pub fn new(
+        measurement_id: u32,
+        measurement: &MeasurementProgress,
+        node_count: usize,
+        mode: RecentMode,
+        provenance: &Provenance,
+    ) -> Self {
+        Self {
+            recorded_at: provenance.exported_at.clone(),
+            mode,
+            scene_path: provenance.scene_path.clone(),
+            scene_hash: provenance.scene_hash.clone(),
+            seed: provenance.seed,
+            measurement_id,
+            kind: measurement.kind,
+            origin_node_id: measurement.origin_node_id,
+            node_count,
+            total_time: measurement.total_time,
+            total_message_count: measurement.total_message_count,
+            milestones: measurement.milestones,
+            unreached_nodes: measurement
+                .stall
+                .as_ref()
+                .map_or(Vec::new(), |stall| stall.unreached_nodes.clone()),
+            reach_times: measurement
+                .reach_times
+                .iter()
+                .map(|(node_id, time)| (*node_id, *time))
+                .collect(),
+            hops: measurement
+                .hops
+                .iter()
+                .map(|(node_id, hops)| (*node_id, *hops))
+                .collect(),
+        }
+    }
+
+    /// Rebuild the measurement progress, started at `start_time`, for display.
+    pub fn to_progress(&self, start_time: embassy_time::Instant) -> MeasurementProgress {
+        let mut measurement = MeasurementProgress::new(self.origin_node_id, start_time);
+        measurement.kind = self.kind;
+        measurement.reached_nodes = self.reach_times.keys().copied().collect();
+        measurement.reach_times = self
+            .reach_times
+            .iter()
+            .map(|(node_id, time)| (*node_id, *time))
+            .collect();
+        measurement.hops = self
+            .hops
+            .iter()
+            .map(|(node_id, hops)| (*node_id, *hops))
+            .collect();
+        measurement.total_time = self.total_time;
+        measurement.total_message_count = self.total_message_count;
+        measurement.milestones = self.milestones;
+        if !self.unreached_nodes.is_empty() {
+            measurement.stall = Some(MeasurementStall {
+                last_progress: self.reach_times.values().copied().fold(0.0, f64::max),
+                unreached_nodes: self.unreached_nodes.clone(),
+                diagnosis: Vec::new(),
+            });
+        }
+        measurement
+    }
+
+    /// Percentage of the scene's nodes reached by the measurement.
+    fn reached_percentage(&self) -> f64 {
+        if self.node_count == 0 {
+            return 0.0;
+        }
+        self.reach_times.len() as f64 / self.node_count as f64 * 100.0
+    }
+
+    /// Reach curve: (elapsed seconds, percentage of nodes reached) at every newly
+    /// reached node, in time order.
+    fn reach_curve(&self) -> Vec<(f64, f64)> {
+        let mut times: Vec<f64> = self.reach_times.values().copied().collect();
+        times.sort_by(f64::total_cmp);
+        let node_count = self.node_count.max(1) as f64;
+        times
+            .into_iter()
+            .enumerate()
+            .map(|(index, time)| (time, (index + 1) as f64 / node_count * 100.0))
+            .collect()
+    }
+
+    fn label(&self) -> String {
+        let recorded_at = chrono::DateTime::parse_from_rfc3339(&self.recorded_at)
+            .map_or(self.recorded_at.clone(), |time| {
+                time.format("%Y-%m-%d %H:%M").to_string()
+            });
+        let scene = self
+            .scene_path
+            .as_deref()
+            .and_then(|path| Path::new(path).file_name())
+            .map_or("-".into(), |name| name.to_string_lossy());
+        format!(
+            "{} {} #{} {} from node {} ({:.0}%)",
+            recorded_at,
+            scene,
+            self.measurement_id,
+            self.kind.label(),
+            self.origin_node_id,
+            self.reached_percentage()
+        )
+    }
+}
+
+/// History file of the scene at `scene_path`.
+fn history_path(scene_path: &str) -> PathBuf {
+    Path::new(scene_path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(HISTORY_FILE_NAME)
+}
+
+/// Append a record to the history file.
+fn append(path: &Path, record: &MeasurementRecord) -> Result<(), String> {
+    let mut line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Parse the records of a history file's text, newest first. Unreadable lines are
+/// skipped with a warning.
+fn parse(text: &str) -> Vec<MeasurementRecord> {
+    let mut records: Vec<MeasurementRecord> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| log::warn!("Skipping unreadable measurement history line: {}", e))
+                .ok()
+        })
+        .collect();
+    records.reverse();
+    records
+}
+
+/// Loaded history records, comparison selection and window visibility.
+#[derive(Debug, Clone, Default)]
+pub struct MeasurementHistoryState {
+    /// Whether the browser window is open.
+    pub open: bool,
+    /// History file the records were loaded from.
+    loaded_from: Option<PathBuf>,
+    /// Records of the loaded file, newest first.
+    records: Vec<MeasurementRecord>,
+    /// Indices of the records selected for comparison, in `records`.
+    selected: BTreeSet<usize>,
+    /// Measurements of this session already saved or re-opened from the history.
+    saved: HashSet<u32>,
+    error: Option<String>,
+}
+
+impl MeasurementHistoryState {
+    /// Forget which measurements were saved (their identifiers are cleared with the scene).
+    pub fn clear_session(&mut self) {
+        self.saved.clear();
+    }
+
+    fn load(&mut self, path: PathBuf) {
+        self.selected.clear();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                self.records = parse(&text);
+                self.error = None;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.records.clear();
+                self.error = None;
+            }
+            Err(e) => {
+                self.records.clear();
+                self.error = Some(format!("Failed to read {}: {}", path.display(), e));
+            }
+        }
+        self.loaded_from = Some(path);
+    }
+}
+
+/// Append the measurements that completed or stalled since the last call to the
+/// history of the running scene. Each measurement is saved once.
+pub fn save_finished(state: &mut AppState) {
+    let node_count = state.nodes.len();
+    let finished: Vec<u32> = state
+        .measurements
+        .iter()
+        .filter(|(id, measurement)| {
+            !state.measurement_history.saved.contains(id)
+                && (measurement.milestones[2].is_some() || measurement.stall.is_some())
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    if finished.is_empty() {
+        return;
+    }
+    let provenance = provenance::current().without_scene();
+    let Some(path) = provenance.scene_path.as_deref().map(history_path) else {
+        return;
+    };
+    for id in finished {
+        state.measurement_history.saved.insert(id);
+        let record = MeasurementRecord::new(
+            id,
+            &state.measurements[&id],
+            node_count,
+            state.operating_mode.into(),
+            &provenance,
+        );
+        match append(&path, &record) {
+            Ok(()) => {
+                log::info!("Measurement {} saved to {}", id, path.display());
+                if state.measurement_history.loaded_from.as_ref() == Some(&path) {
+                    state.measurement_history.load(path.clone());
+                }
+            }
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+}
+
+/// Show a history record as a tracked measurement (under a free identifier if its
+/// own is taken) and display it.
+fn reopen(state: &mut AppState, record: &MeasurementRecord) {
+    let mut id = record.measurement_id;
+    while state.measurements.contains_key(&id) {
+        id = id.wrapping_add(1).max(1);
+    }
+    let start_time = state.measurement_clock();
+    state
+        .measurements
+        .insert(id, record.to_progress(start_time));
+    state.measurement_identifier = id;
+    state.measurement_history.saved.insert(id);
+    log::info!(
+        "Re-opened measurement {} from {} as #{}",
+        record.measurement_id,
+        record.recorded_at,
+        id
+    );
+}
+
+/// Render the measurement history browser.
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.measurement_history.open {
+        return;
+    }
+    let session = provenance::current().without_scene();
+    let current_path = session.scene_path.as_deref().map(history_path);
+    if let Some(path) = current_path
+        .clone()
+        .filter(|path| state.measurement_history.loaded_from.as_ref() != Some(path))
+    {
+        state.measurement_history.load(path);
+    }
+    let current_hash = session.scene_hash;
+
+    let mut open = state.measurement_history.open;
+    let mut reload = false;
+    let mut to_reopen = None;
+    egui::Window::new("Measurement history")
+        .open(&mut open)
+        .default_width(640.0)
+        .show(ctx, |ui| {
+            let history = &mut state.measurement_history;
+            ui.horizontal(|ui| {
+                match &history.loaded_from {
+                    Some(path) => ui.weak(path.display().to_string()),
+                    None => ui.weak("No scene loaded"),
+                };
+                if ui.button("Reload").clicked() {
+                    reload = true;
+                }
+            });
+            if let Some(error) = &history.error {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
+            if history.records.is_empty() {
+                ui.label("No finished measurements recorded for this scene directory yet.");
+                return;
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for (index, record) in history.records.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let mut selected = history.selected.contains(&index);
+                            if ui.checkbox(&mut selected, record.label()).changed() {
+                                if selected {
+                                    history.selected.insert(index);
+                                } else {
+                                    history.selected.remove(&index);
+                                }
+                            }
+                            let other_scene = record.scene_hash.is_some()
+                                && record.scene_hash != current_hash;
+                            let response = ui.small_button("Open");
+                            let response = if other_scene {
+                                response.on_hover_text(
+                                    "Recorded with a different scene: node IDs may not match the map",
+                                )
+                            } else {
+                                response.on_hover_text("Show as the displayed measurement")
+                            };
+                            if response.clicked() {
+                                to_reopen = Some(index);
+                            }
+                        });
+                    }
+                });
+
+            let compared: Vec<&MeasurementRecord> = history
+                .selected
+                .iter()
+                .filter_map(|index| history.records.get(*index))
+                .collect();
+            if compared.is_empty() {
+                ui.weak("Select records to compare them.");
+                return;
+            }
+            ui.separator();
+            render_comparison(ui, &compared);
+            draw_curves(ui, &compared);
+        });
+    state.measurement_history.open = open;
+
+    if let Some(path) = current_path.filter(|_| reload) {
+        state.measurement_history.load(path);
+    }
+    if let Some(record) = to_reopen.and_then(|index| state.measurement_history.records.get(index)) {
+        let record = record.clone();
+        reopen(state, &record);
+    }
+}
+
+/// Table of the compared records' key figures.
+fn render_comparison(ui: &mut egui::Ui, compared: &[&MeasurementRecord]) {
+    let milestone_text = |milestone: Option<MeasurementMilestone>| {
+        milestone.map_or("-".to_string(), |milestone| {
+            format!("{} s / {} p", milestone.time, milestone.message_count)
+        })
+    };
+    egui::Grid::new("measurement_history_comparison")
+        .striped(true)
+        .show(ui, |ui| {
+            for header in [
+                "",
+                "Measurement",
+                "Seed",
+                "Nodes",
+                "50%",
+                "90%",
+                "100%",
+                "Packets",
+                "Reached",
+            ] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for (index, record) in compared.iter().enumerate() {
+                ui.colored_label(CURVE_COLORS[index % CURVE_COLORS.len()], "■");
+                ui.label(format!(
+                    "#{} {} from node {}",
+                    record.measurement_id,
+                    record.kind.label(),
+                    record.origin_node_id
+                ));
+                ui.label(record.seed.map_or("-".to_string(), |seed| seed.to_string()));
+                ui.label(record.node_count.to_string());
+                for milestone in record.milestones {
+                    ui.label(milestone_text(milestone));
+                }
+                ui.label(record.total_message_count.to_string());
+                ui.label(format!("{:.1}%", record.reached_percentage()));
+                ui.end_row();
+            }
+        });
+}
+
+/// Reach curves of the compared records over a shared time axis.
+fn draw_curves(ui: &mut egui::Ui, compared: &[&MeasurementRecord]) {
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width().max(200.0), 180.0),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let grid_stroke = egui::Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.3));
+    for percent in [50.0, 90.0] {
+        let y = egui::lerp(rect.bottom()..=rect.top(), percent / 100.0);
+        painter.hline(rect.x_range(), y, grid_stroke);
+    }
+
+    let curves: Vec<Vec<(f64, f64)>> = compared.iter().map(|record| record.reach_curve()).collect();
+    let end = curves
+        .iter()
+        .filter_map(|curve| curve.last())
+        .map(|(time, _)| *time)
+        .fold(0.0, f64::max);
+    if end <= 0.0 {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "No reach times recorded",
+            egui::FontId::default(),
+            visuals.weak_text_color(),
+        );
+        return;
+    }
+    let to_screen = |(time, percent): (f64, f64)| {
+        egui::pos2(
+            egui::lerp(rect.left()..=rect.right(), (time / end) as f32),
+            egui::lerp(rect.bottom()..=rect.top(), (percent / 100.0) as f32),
+        )
+    };
+    for (index, curve) in curves.iter().enumerate() {
+        // Step curve: the share rises at each reach time
+        let mut line = Vec::with_capacity(curve.len() * 2);
+        let mut previous = 0.0;
+        for &(time, percent) in curve {
+            line.push(to_screen((time, previous)));
+            line.push(to_screen((time, percent)));
+            previous = percent;
+        }
+        line.push(to_screen((end, previous)));
+        painter.add(egui::Shape::line(
+            line,
+            egui::Stroke::new(1.5, CURVE_COLORS[index % CURVE_COLORS.len()]),
+        ));
+    }
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        "100%",
+        egui::FontId::proportional(10.0),
+        visuals.weak_text_color(),
+    );
+    painter.text(
+        rect.right_bottom() - egui::vec2(4.0, 2.0),
+        egui::Align2::RIGHT_BOTTOM,
+        format!("{:.0} s", end),
+        egui::FontId::proportional(10.0),
+        visuals.weak_text_color(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_time::{Duration, Instant};
+
+    #[test]
+    fn test_records_round_trip_and_reopen_as_progress() {
+        let start = Instant::from_secs(10);
+        let mut measurement = MeasurementProgress::new(1, start);
+        measurement.record_sent_packet(start + Duration::from_secs(2));
+        measurement.record_reached(2, Some(1), 4, start + Duration::from_secs(3));
+        measurement.record_reached(3, Some(2), 4, start + Duration::from_secs(5));
+        assert!(measurement.check_stall(&[1, 2, 3, 4], 60, start + Duration::from_secs(70)));
+        let provenance = Provenance {
+            simulator_version: "1".into(),
+            radio_lib_version: "1".into(),
+            scene_path: Some("scenes/city.json".into()),
+            scene_hash: Some("abc".into()),
+            scene: None,
+            seed: Some(7),
+            speed_percent: 100,
+            event_driven: false,
+            speed_profile: None,
+            exported_at: "2026-10-15T12:00:00+02:00".into(),
+        };
+        let record =
+            MeasurementRecord::new(42, &measurement, 4, RecentMode::Simulation, &provenance);
+        assert_eq!(record.unreached_nodes, vec![4]);
+        assert_eq!(
+            record.reach_curve(),
+            vec![(0.0, 25.0), (3.0, 50.0), (5.0, 75.0)]
+        );
+
+        let older = MeasurementRecord {
+            measurement_id: 41,
+            ..record.clone()
+        };
+        let text = format!(
+            "{}\nnot json\n{}\n",
+            serde_json::to_string(&older).unwrap(),
+            serde_json::to_string(&record).unwrap()
+        );
+        let records = parse(&text);
+        assert_eq!(records, vec![record.clone(), older]);
+
+        let reopened = records[0].to_progress(Instant::from_secs(500));
+        assert_eq!(reopened.reached_nodes.len(), 3);
+        assert_eq!(reopened.reach_times[&3], 5.0);
+        assert_eq!(reopened.hops[&3], 2);
+        assert_eq!(reopened.milestones[0].map(|m| m.time), Some(3));
+        assert_eq!(reopened.stall.map(|s| s.unreached_nodes), Some(vec![4]));
+        assert_eq!(
+            history_path("scenes/city.json"),
+            Path::new("scenes").join(HISTORY_FILE_NAME)
+        );
+    }
+}
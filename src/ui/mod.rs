@@ -10,6 +10,44 @@
 //! - `top_panel`: Top metrics bar displaying system statistics, measurements, and simulation controls
 //! - `right_panel`: Node inspector showing detailed message streams and measurement controls
 //! - `map`: Central 2D map visualization with nodes, obstacles, and animated radio transmissions
+//! - `map_style`: Configurable map background, grid and obstacle colors (persisted)
+//! - `alerts`: User-defined alert rules and toast notifications
+//! - `animation_lod`: Adaptive detail of the map transmission animations under high transmission rates
+//! - `annotations`: Scene labels, arrows and regions on the map, with a drawing tool and JSON export
+//! - `collision_map`: Heat overlay of collision hotspots on the map
+//! - `command_palette`: Command palette (Ctrl+P) and keyboard shortcuts for node and tab navigation
+//! - `delivery_flow`: Map highlight of the nodes that recently decoded the selected node's packets
+//! - `backhaul`: Gateway backhaul delivery statistics window (Simulation mode)
+//! - `flood_efficiency`: Duplicate share of received flooded packets over time (Simulation mode)
+//! - `edit_history`: Undo/redo of obstacle edits, node moves and node radio switching
+//! - `event_search`: Search across all nodes' radio events by kind, type, node and time range
+//! - `hop_depth`: First-hop vs multi-hop breakdown and hop count distribution of measurements
+//! - `interval_marks`: Named session marks with throughput and collision rate per window between them
+//! - `launch_options`: Command-line options starting a session without the mode selector
+//! - `link_histograms`: Per-link RSSI and SINR histogram tab of the inspector (Simulation mode)
+//! - `liveness`: Silent node summary for the analyzer modes
+//! - `loop_profile`: Per-stage timing of the simulation network loop (Simulation mode)
+//! - `node_coloring`: Map node colors by a per-node metric with a continuous scale and legend
+//! - `node_shapes`: Cached mesh of the map's node bodies, rebuilt only when nodes or colors change
+//! - `node_table`: Sortable table of per-node radio statistics
+//! - `recent_files`: Recent session list and drag-and-drop of scene and log files on the mode selector
+//! - `reception_arrows`: Fading sender → receiver arrows of packet receptions seen by the analyzer
+//! - `region_stats`: Aggregated node statistics of rectangles drawn on the map, with CSV export
+//! - `refresh_queue`: Prioritized network → UI update queue shedding cosmetic updates under load
+//! - `refresh_diagnostics`: Saturation window with shed updates per message variant and remedies
+//! - `obstacle_editor`: Runtime obstacle editing with drag handles on the map
+//! - `packet_waterfall`: Per-packet copies, part requests and assembly of multi-packet messages (inspector tab)
+//! - `result_export`: Measurement export as a reach time choropleth image and CSV
+//! - `replay_export`: Measurement export as an animated standalone HTML replay
+//! - `ruler`: Map tool measuring the distance between two points in meters
+//! - `measurement_history`: On-disk history of finished measurements with a compare and re-open browser
+//! - `scene_load`: Scene load progress window with a Cancel button (Simulation mode)
+//! - `session_clock`: Virtual and wall-clock time of the running session and its average speed
+//! - `signal_probe`: Map tool showing a transmitter's RSSI, SNR margin and line of sight at a location
+//! - `scene_diff`: Side-by-side comparison of two scene files before loading
+//! - `sites_dashboard`: Summary dashboard of a multi-site real-time tracking session with drill-down
+//! - `stream_filter`: Type, direction, sender, time and text filters of the inspector stream tables
+//! - `time_travel`: Playback pause and past-time node inspection in Log Visualization
 //!
 //! ## Communication Protocol
 //!
@@ -23,18 +61,66 @@
 //! This simplifies state synchronization but requires the render loop to be efficient.
 //! The UI maintains minimal state and queries the latest data from channels each frame.
 
+pub mod alerts;
+pub mod animation_lod;
+pub mod annotations;
 pub mod app_state;
+pub mod backhaul;
+pub mod collision_map;
+pub mod command_palette;
+pub mod delivery_flow;
+pub mod edit_history;
+pub mod event_search;
+pub mod flood_efficiency;
+pub mod hop_depth;
+pub mod interval_marks;
+pub mod launch_options;
+pub mod liveness;
+pub mod loop_profile;
 pub mod map;
+pub mod map_style;
+pub mod measurement_history;
 pub mod mode_selector;
+pub mod neighbor_graph;
+pub mod node_coloring;
+pub mod node_shapes;
+pub mod node_table;
+pub mod obstacle_editor;
+pub mod packet_waterfall;
+pub mod recent_files;
+pub mod reception_arrows;
+pub mod refresh_diagnostics;
+pub mod refresh_queue;
+pub mod region_stats;
+pub mod replay_export;
+pub mod result_export;
 pub mod right_panel;
+pub mod ruler;
+pub mod scene_diff;
+pub mod scene_load;
+pub mod session_clock;
+pub mod signal_probe;
+pub mod sites_dashboard;
+pub mod stream_filter;
+pub mod time_travel;
 pub mod top_panel;
 
 use crate::common::connection_matrix::ConnectionMatrix;
-use crate::simulation::types::{FullMessage, LogLine};
-use crate::simulation::{NodeMessage, Point};
+use crate::common::node_statistics::NodeStatistics;
+use crate::common::units::WorldTransform;
+use crate::simulation::backhaul::BackhaulStatistics;
+use crate::simulation::event_store::{EventQuery, EventQueryResult};
+use crate::simulation::memory::MemoryUsage;
+use crate::simulation::signal_probe::SignalProbe;
+use crate::simulation::speed_target::{SpeedTarget, SpeedTargetStatus};
+use crate::simulation::stage_timing::StageTimingReport;
+use crate::simulation::types::{FullMessage, LogLine, NodeProtocolState};
+use crate::simulation::watchdog::NodeHealthReport;
+use crate::simulation::{NodeMessage, Obstacle, Point};
 
 pub use app_state::{AppState, color_for_message_type};
 use embassy_time::{Duration, Instant};
+use std::collections::HashMap;
 
 /// The three operational modes available in the application.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -47,6 +133,26 @@ pub enum OperatingMode {
     LogVisualization,
 }
 
+/// Message whose propagation a measurement tracks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum MeasurementKind {
+    /// An AddBlock originated by the origin node (block distribution).
+    #[default]
+    Block,
+    /// An AddTransaction originated by the origin node (mempool propagation, Simulation only).
+    Transaction,
+}
+
+impl MeasurementKind {
+    /// Name of the measured message type.
+    pub fn label(self) -> &'static str {
+        match self {
+            MeasurementKind::Block => "AddBlock",
+            MeasurementKind::Transaction => "AddTransaction",
+        }
+    }
+}
+
 /// Detailed information about a selected node, including its complete message history.
 ///
 /// This struct is sent from the network task to the UI when a node is selected,
@@ -67,6 +173,34 @@ pub struct NodeInfo {
     pub node_version: Option<u8>,
 }
 
+/// Reach status of a measurement for a node at an inspected time.
+#[derive(Debug, Clone)]
+pub struct MeasurementReachStatus {
+    /// Measurement sequence.
+    pub measurement_id: u32,
+    /// Node that started the measurement.
+    pub origin_node_id: u32,
+    /// Time from the measurement start until the node was reached (None = not reached yet).
+    pub reached_after: Option<Duration>,
+}
+
+/// Reconstructed state of a node at a past timestamp (log visualization time travel).
+#[derive(Debug, Clone)]
+pub struct NodeStateSnapshot {
+    /// Unique identifier of the node.
+    pub node_id: u32,
+    /// Inspected log timestamp.
+    pub time: Instant,
+    /// Packets transmitted up to the inspected time.
+    pub tx_packets: u64,
+    /// Packets received up to the inspected time.
+    pub rx_packets: u64,
+    /// Packets with CRC errors up to the inspected time.
+    pub crc_errors: u64,
+    /// Measurements started up to the inspected time, oldest first.
+    pub measurements: Vec<MeasurementReachStatus>,
+}
+
 /// Events pushed from the network task to update the UI state.
 ///
 /// These messages flow through the `UIRefreshChannel` and are processed by
@@ -76,30 +210,38 @@ pub struct NodeInfo {
 pub enum UIRefreshState {
     /// Display an alert dialog with the given message.
     Alert(String),
-    /// Update a single node's state (currently unused but kept for future use).
-    #[allow(dead_code)]
+    /// Replace a single node's state (e.g. its range after a TX power change).
     NodeUpdated(NodeUIState),
     /// Replace the entire node list with a new set (typically on scene load).
     NodesUpdated(Vec<NodeUIState>),
     /// Replace the obstacle list with a new set (typically on scene load).
     ObstaclesUpdated(Vec<crate::simulation::Obstacle>),
-    /// A node transmitted a radio message. Parameters: node ID, message type, effective distance.
-    NodeSentRadioMessage(u32, u8, u32),
+    /// Replace the scene's map annotations (on scene load).
+    AnnotationsUpdated(Vec<crate::common::annotations::Annotation>),
+    /// A node transmitted a radio message. Parameters: node ID, message type, effective distance,
+    /// packet length in bytes.
+    NodeSentRadioMessage(u32, u8, u32, usize),
     /// Detailed information about a selected node and its message history.
     NodeInfo(NodeInfo),
     /// Update global packet counters. Parameters: total sent, total received, total collisions.
     RadioMessagesCountUpdated(u64, u64, u64),
     /// Update the simulation delay warning. Parameter: delay.
     SimulationDelayWarningChanged(Duration),
-    /// A node was reached during a measurement. Parameters: node ID, measurement ID.
-    NodeReachedInMeasurement(u32, u32),
+    /// Current processing delay of the simulation behind its schedule, reported once
+    /// per second (sustained delay alert rule). Parameter: delay.
+    SimulationDelayUpdated(Duration),
+    /// A node was reached during a measurement. Parameters: node ID, measurement ID,
+    /// node it first received the measurement from (None if unknown), time (virtual
+    /// time, or log timestamp in the analyzer modes).
+    NodeReachedInMeasurement(u32, u32, Option<u32>, Instant),
     /// The simulation speed percentage changed (e.g., via auto-speed control).
     SimulationSpeedChanged(u32),
-    /// A message was sent during an active measurement. Parameter: sequence number.
-    SendMessageInMeasurement(u32),
+    /// A message was sent during an active measurement. Parameters: sequence number, time.
+    SendMessageInMeasurement(u32, Instant),
     /// Link quality thresholds from the scoring matrix. Parameters: poor limit, excellent limit.
     PoorAndExcellentLimits(u8, u8),
-    SceneDimensionsUpdated(Point, Point, f64, f64),
+    /// World bounds and world unit ↔ meter transform of the loaded scene.
+    SceneDimensionsUpdated(WorldTransform),
     BackgroundImageUpdated(Option<String>),
     /// Delay between real clock and last processed log timestamp (real-time tracking only).
     AnalyzerDelay(u64),
@@ -107,11 +249,62 @@ pub enum UIRefreshState {
     VisualizationEnded,
     /// Current operating mode changed.
     ModeChanged(OperatingMode),
+    /// The session task ended; the next `UICommand::StartMode` starts a new session.
+    SessionEnded,
     TimeUpdated(Instant),
     /// Indicates whether control commands are available (config loaded successfully).
     ControlAvailable(bool),
     /// Completed connection matrix for a requester node.
     ConnectionMatrixUpdated(ConnectionMatrix),
+    /// Radio manager state of a node answering `UICommand::QueryNodeState`. Parameters: node ID, state.
+    NodeProtocolStateUpdated(u32, NodeProtocolState),
+    /// Matches of a `UICommand::QueryEvents` search in the central event store.
+    EventQueryAnswered(EventQueryResult),
+    /// Signal histograms answering `UICommand::QueryLinkHistograms`. Parameters: node ID, incoming links.
+    LinkHistogramsUpdated(u32, Vec<crate::simulation::link_histograms::LinkHistogram>),
+    /// Diagnosis answering `UICommand::DiagnoseUnreached`. Parameters: measurement ID,
+    /// one diagnosis per unreached node.
+    UnreachedDiagnosed(
+        u32,
+        Vec<crate::simulation::unreached_diagnosis::UnreachedDiagnosis>,
+    ),
+    /// A measurement was started by the backend (e.g. scene-defined auto measurement,
+    /// or a start found in the log). Parameters: origin node ID, measurement ID, start time.
+    MeasurementStarted(u32, u32, Instant),
+    /// Link quality observed on a received echo packet. Parameters: sender ID, receiver ID, link quality.
+    LinkQualityObserved(u32, u32, u8),
+    /// A node decoded a packet of another node. Parameters: sender ID, receiver ID, message type.
+    PacketDelivered(u32, u32, u8),
+    /// Nodes produced log events since the last report (analyzer modes, one message
+    /// per activity tick). Parameter: node ID and latest log timestamp per node.
+    NodeActivity(Vec<(u32, Instant)>),
+    /// Snapshot of the cumulative per-node radio statistics (node ID -> statistics).
+    NodeStatisticsUpdated(HashMap<u32, NodeStatistics>),
+    /// Reconstructed node state at an inspected time (log visualization time travel).
+    NodeStateAt(NodeStateSnapshot),
+    /// Snapshot of the gateway backhaul statistics (Simulation mode, scenes with gateways).
+    BackhaulStatisticsUpdated(BackhaulStatistics),
+    /// Memory usage estimate of the running simulation (Simulation mode).
+    MemoryUsageUpdated(MemoryUsage),
+    /// Nodes whose input queue dropped messages or is full (Simulation mode, ordered
+    /// by node ID; empty when all node tasks are healthy).
+    NodeHealthUpdated(Vec<NodeHealthReport>),
+    /// Real time spent in each network loop stage over the last second (Simulation mode).
+    StageTimingsUpdated(StageTimingReport),
+    /// Progress of the active speed target (None = no target; Simulation mode).
+    SpeedTargetUpdated(Option<SpeedTargetStatus>),
+    /// A stop condition was met and the virtual clock is paused. Parameter: reason.
+    SimulationStopped(String),
+    /// Progress of the scene load (Simulation mode). Parameters: stage, completed
+    /// steps, total steps of the stage.
+    SceneLoadProgress(&'static str, usize, usize),
+    /// The scene load finished, failed or was cancelled.
+    SceneLoadEnded,
+    /// Link budget of a transmitter at a probed map location (Simulation mode).
+    SignalProbed(SignalProbe),
+    /// An alert rule evaluated by the simulation triggered (see `event_bus::AlertEngine`).
+    /// Parameter: notification text.
+    AlertRaised(String),
 }
 
 /// UI-specific representation of a node's state.
@@ -123,10 +316,16 @@ pub enum UIRefreshState {
 pub struct NodeUIState {
     /// Unique identifier of the node.
     pub node_id: u32,
-    /// 2D position in world coordinates (0..10000).
+    /// 2D position in world coordinates (within the world bounds).
     pub position: Point,
     /// Pre-calculated effective radio range in world units for rendering.
     pub radio_strength: u32,
+    /// TX power in dBm (Simulation mode; the analyzer modes only know the range).
+    pub tx_power: Option<f32>,
+    /// Whether the node has the gateway role (drawn as a square).
+    pub is_gateway: bool,
+    /// Whether the node's radio is switched off (runtime topology editing).
+    pub disabled: bool,
 }
 
 /// Commands sent from the UI to the network task.
@@ -139,10 +338,17 @@ pub enum UICommand {
     LoadFile(String),
     /// Request detailed information about a specific node.
     RequestNodeInfo(u32),
-    /// Start a measurement from a specific node. Parameters: node ID, measurement identifier.
-    StartMeasurement(u32, u32),
+    /// Start a measurement from a specific node. Parameters: node ID, measurement
+    /// identifier, measured message type.
+    StartMeasurement(u32, u32, MeasurementKind),
+    /// Stop tracking a measurement (its backend tracker is dropped). Parameter:
+    /// measurement identifier.
+    StopMeasurement(u32),
     /// Enable or disable automatic speed adjustment.
     SetAutoSpeed(bool),
+    /// Adjust the speed to finish a virtual duration within a wall-clock budget
+    /// (None = stop the target; Simulation mode only).
+    SetSpeedTarget(Option<SpeedTarget>),
     /// Start the application in a specific mode with file paths.
     StartMode {
         mode: OperatingMode,
@@ -153,4 +359,46 @@ pub enum UICommand {
     SendControlCommand(crate::control::ControlCommand),
     /// Request a connection matrix dump for the given node.
     RequestConnectionMatrix(u32),
+    /// Query the radio manager state of the given node (Simulation mode only).
+    QueryNodeState(u32),
+    /// Search the radio events of all nodes (Simulation mode only).
+    QueryEvents(EventQuery),
+    /// Query the signal histograms of the node's incoming links (Simulation mode only).
+    QueryLinkHistograms(u32),
+    /// Classify the nodes a stalled measurement did not reach (Simulation mode only).
+    /// Parameters: measurement ID, origin node ID, reached node IDs.
+    DiagnoseUnreached(u32, u32, Vec<u32>),
+    /// Replace the simulation's obstacles (runtime obstacle editing, Simulation mode only).
+    UpdateObstacles(Vec<Obstacle>),
+    /// Move a node to a new world position (runtime topology editing, Simulation mode only).
+    MoveNode(u32, Point),
+    /// Switch a node's radio on (true) or off (false) (runtime topology editing, Simulation mode only).
+    SetNodeEnabled(u32, bool),
+    /// Write the current connectivity graph to the given path (DOT, or GraphML for a
+    /// `.graphml` path; Simulation mode only).
+    ExportConnectivityGraph(String),
+    /// Cancel the scene load in progress (Simulation mode only).
+    CancelSceneLoad,
+    /// Set a node's TX power in dBm (runtime topology editing, Simulation mode only).
+    SetNodeTxPower(u32, f32),
+    /// Compute the link budget of a transmitter at a map location. Parameters:
+    /// transmitter node ID, location in world units (Simulation mode only).
+    ProbeSignal(u32, Point),
+    /// Set the collision rate limit in percent of the alert rules (None = rule
+    /// disabled; Simulation mode only).
+    SetCollisionRateAlert(Option<f64>),
+    /// Pause or resume log playback (Log Visualization mode only).
+    SetPlaybackPaused(bool),
+    /// Request a node's history and state as of a past log timestamp. Parameters: node ID, time.
+    RequestNodeStateAt(u32, Instant),
+    /// Switch the analyzer to another site's scene and live log (Real-time Tracking only).
+    OpenSite {
+        scene_path: String,
+        log_path: String,
+    },
+    /// Leave the running session and return to the mode selector (the session task
+    /// flushes its outputs and returns, see `session`).
+    EndSession,
+    /// The window was closed: flush outputs and stop (see `shutdown`).
+    Shutdown,
 }
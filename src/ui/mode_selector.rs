@@ -20,11 +20,17 @@
 //!
 //! The mode selector displays three panels with icons, descriptions, and action buttons.
 //! After selection, the application proceeds to file picker dialogs for the required files.
+//! Below the panels, "Compare scenes…" opens the scene diff window for two scene files
+//! and "Track sites…" starts multi-site real-time tracking from a sites file (see
+//! `sites_dashboard`), followed by the recent session list; scene and log files can also be dropped onto
+//! the window (see `recent_files`).
 
 use eframe::egui;
 use egui::Color32;
 use std::sync::Arc;
 
+use super::recent_files::{self, RecentEntry};
+
 /// Mode selector UI component managing the initial mode selection screen.
 ///
 /// Loads and displays icons for each mode and handles user interaction.
@@ -90,8 +96,8 @@ impl ModeSelector {
     }
 
     /// Render the mode selector screen
-    /// Returns the selected mode if any button was clicked
-    pub fn render(&mut self, ctx: &egui::Context) -> Option<ModeSelection> {
+    /// Returns the selected mode if any button or recent session was clicked
+    pub fn render(&mut self, ctx: &egui::Context, recent: &[RecentEntry]) -> Option<ModeSelection> {
         let mut selection = None;
         const PANEL_HEIGHT: f32 = 500.0;
         let button_size = egui::vec2(160.0, 32.0);
@@ -256,6 +262,33 @@ impl ModeSelector {
                 ui.spacing_mut().item_spacing.x = original_spacing;
             });
 
+            ui.add_space(20.0);
+            ui.vertical_centered(|ui| {
+                let button = egui::Button::new(egui::RichText::new("Compare scenes…").size(15.0).color(Color32::WHITE))
+                    .min_size(button_size);
+                if ui
+                    .add(button)
+                    .on_hover_text("Show the differences between two scene files before loading one")
+                    .clicked()
+                {
+                    selection = Some(ModeSelection::CompareScenes);
+                }
+                ui.add_space(button_spacing);
+                let button = egui::Button::new(egui::RichText::new("Track sites…").size(15.0).color(Color32::WHITE))
+                    .min_size(button_size);
+                if ui
+                    .add(button)
+                    .on_hover_text("Track several sites in real time from a sites file, with a dashboard of all sites")
+                    .clicked()
+                {
+                    selection = Some(ModeSelection::RealtimeSites);
+                }
+                ui.add_space(20.0);
+                if let Some(recent_selection) = recent_files::render_list(ui, recent) {
+                    selection = Some(recent_selection);
+                }
+            });
+
         });
 
         selection
@@ -289,6 +322,8 @@ impl ModeSelector {
 pub enum ModeSelection {
     /// Simulation mode - single scene file selection.
     Simulation,
+    /// Simulation mode - start with a known scene file (recent file or dropped file).
+    SimulationScene(String),
     /// Real-time tracking - scene file selection step.
     RealtimeSelectScene,
     /// Real-time tracking - log file selection step.
@@ -307,4 +342,8 @@ pub enum ModeSelection {
         scene_path: String,
         log_path: String,
     },
+    /// Compare two scene files (file selection and diff window).
+    CompareScenes,
+    /// Real-time tracking of several sites - sites file selection.
+    RealtimeSites,
 }
@@ -0,0 +1,151 @@
+//! # Cached Neighbor Graph
+//!
+//! The neighbor graph overlay links every pair of nodes in mutual radio range whose
+//! line of sight is clear in both directions. Finding the edges checks every pair
+//! of nodes against every obstacle, which is far too slow to repeat on each frame
+//! with thousands of nodes, so the edges are cached and only recomputed when:
+//! - a refresh message or an edit changed the nodes or the obstacles (`invalidate`:
+//!   node list and node updates, obstacle updates, topology edits, node and
+//!   obstacle drags)
+//! - the inputs of the frame differ from the cached ones (`NeighborGraphKey`:
+//!   world and node and obstacle counts)
+//!
+//! The edge colors follow the observed link qualities, which change with every
+//! echo packet, so they are still picked per frame from the cached edges.
+
+use super::NodeUIState;
+use crate::common::units::WorldTransform;
+use crate::simulation::Obstacle;
+use crate::simulation::geometry::is_intersect;
+
+/// Inputs of the neighbor graph that are not tracked by refresh messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeighborGraphKey {
+    pub world: WorldTransform,
+    pub node_count: usize,
+    pub obstacle_count: usize,
+}
+
+/// Cached edges of the neighbor graph.
+#[derive(Debug, Clone, Default)]
+pub struct NeighborGraphCache {
+    key: Option<NeighborGraphKey>,
+    edges: Vec<(usize, usize)>,
+    dirty: bool,
+}
+
+impl NeighborGraphCache {
+    /// Recompute the edges on the next frame (nodes or obstacles changed).
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether the edges must be recomputed for the frame's `key`.
+    pub fn needs_rebuild(&self, key: &NeighborGraphKey) -> bool {
+        self.dirty || self.key.as_ref() != Some(key)
+    }
+
+    /// Store the edges computed for `key`.
+    pub fn store(&mut self, key: NeighborGraphKey, edges: Vec<(usize, usize)>) {
+        self.key = Some(key);
+        self.edges = edges;
+        self.dirty = false;
+    }
+
+    /// The cached edges as pairs of node indices (into `AppState::nodes`).
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+}
+
+/// Find the edges of the neighbor graph.
+///
+/// Two nodes are neighbors when their distance (in meters) is within both nodes'
+/// effective radio range and the line between them is not blocked by an obstacle
+/// in either direction (one-way obstacles may block a single direction).
+///
+/// # Parameters
+///
+/// * `nodes` - All nodes
+/// * `obstacles` - All obstacles
+/// * `world` - World unit ↔ meter transform
+///
+/// # Returns
+///
+/// The neighbor pairs as node indices, lower index first.
+pub fn find_edges(
+    nodes: &[NodeUIState],
+    obstacles: &[Obstacle],
+    world: &WorldTransform,
+) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for (i, a) in nodes.iter().enumerate() {
+        for (j, b) in nodes.iter().enumerate().skip(i + 1) {
+            let d2 = world.distance2((a.position.x, a.position.y), (b.position.x, b.position.y));
+            let range = a.radio_strength.min(b.radio_strength) as f64;
+            if d2 > range * range {
+                continue;
+            }
+            if is_intersect(&a.position, &b.position, obstacles)
+                || is_intersect(&b.position, &a.position, obstacles)
+            {
+                continue;
+            }
+            edges.push((i, j));
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Point;
+    use crate::simulation::types::RectPos;
+
+    fn node(node_id: u32, x: f64) -> NodeUIState {
+        NodeUIState {
+            node_id,
+            position: Point { x, y: 50.0 },
+            radio_strength: 100,
+            tx_power: None,
+            is_gateway: false,
+            disabled: false,
+        }
+    }
+
+    #[test]
+    fn test_edges_and_cache_invalidation() {
+        let world = WorldTransform::from_meters_per_unit((0.0, 0.0), (1000.0, 100.0), 1.0);
+        let nodes = vec![node(1, 0.0), node(2, 80.0), node(3, 160.0), node(4, 500.0)];
+        assert_eq!(find_edges(&nodes, &[], &world), vec![(0, 1), (1, 2)]);
+
+        // A wall between the second and third node removes their edge
+        let wall = Obstacle::Rectangle {
+            position: RectPos {
+                top_left: Point { x: 110.0, y: 0.0 },
+                bottom_right: Point { x: 120.0, y: 100.0 },
+            },
+            one_way: None,
+        };
+        let edges = find_edges(&nodes, std::slice::from_ref(&wall), &world);
+        assert_eq!(edges, vec![(0, 1)]);
+
+        let key = NeighborGraphKey {
+            world,
+            node_count: nodes.len(),
+            obstacle_count: 1,
+        };
+        let mut cache = NeighborGraphCache::default();
+        assert!(cache.needs_rebuild(&key));
+        cache.store(key.clone(), edges);
+        assert!(!cache.needs_rebuild(&key));
+        assert_eq!(cache.edges(), &[(0, 1)]);
+        assert!(cache.needs_rebuild(&NeighborGraphKey {
+            obstacle_count: 0,
+            ..key.clone()
+        }));
+        cache.invalidate();
+        assert!(cache.needs_rebuild(&key));
+    }
+}
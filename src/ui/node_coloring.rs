@@ -0,0 +1,214 @@
+//! # Node Coloring by Metric
+//!
+//! Colors the map's nodes by a per-node metric from the node statistics (TX count,
+//! RX count, collision ratio, average link quality or neighbor count) instead of the
+//! uniform cyan. Values are mapped onto a continuous blue → green → yellow scale
+//! between the lowest and highest value of all nodes, and a legend with the range is
+//! drawn in the bottom-left corner of the map. Nodes without a value for the metric
+//! (e.g. no received packets for the average link quality) are drawn dark grey.
+//! Selection, silent-node and measurement colors still take precedence.
+//!
+//! Battery levels are not modeled by the simulator, so there is no battery metric.
+
+use eframe::egui;
+use egui::Color32;
+use std::collections::HashMap;
+
+use super::AppState;
+use crate::common::node_statistics::NodeStatistics;
+
+/// Color of nodes without a value for the selected metric.
+const NO_DATA_COLOR: Color32 = Color32::from_rgb(70, 70, 80);
+
+/// Stops of the color scale, from the lowest to the highest value.
+const SCALE_STOPS: [[u8; 3]; 5] = [
+    [68, 1, 84],
+    [59, 82, 139],
+    [33, 145, 140],
+    [94, 201, 98],
+    [253, 231, 37],
+];
+
+/// Metric the map's nodes are colored by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NodeColorMetric {
+    /// Uniform cyan (no metric).
+    #[default]
+    Uniform,
+    TxPackets,
+    RxPackets,
+    /// Share of the packets arriving at the node that were lost to collisions.
+    CollisionRatio,
+    AverageLinkQuality,
+    /// Distinct nodes heard from.
+    NeighborCount,
+}
+
+impl NodeColorMetric {
+    /// All metrics, in selector order.
+    pub const ALL: [NodeColorMetric; 6] = [
+        NodeColorMetric::Uniform,
+        NodeColorMetric::TxPackets,
+        NodeColorMetric::RxPackets,
+        NodeColorMetric::CollisionRatio,
+        NodeColorMetric::AverageLinkQuality,
+        NodeColorMetric::NeighborCount,
+    ];
+
+    /// Display name of the metric.
+    pub fn label(self) -> &'static str {
+        match self {
+            NodeColorMetric::Uniform => "Uniform",
+            NodeColorMetric::TxPackets => "TX packets",
+            NodeColorMetric::RxPackets => "RX packets",
+            NodeColorMetric::CollisionRatio => "Collision ratio (%)",
+            NodeColorMetric::AverageLinkQuality => "Average link quality",
+            NodeColorMetric::NeighborCount => "Neighbors",
+        }
+    }
+
+    /// Value of the metric for a node (None if the node has no value).
+    fn value(self, statistics: &NodeStatistics) -> Option<f64> {
+        match self {
+            NodeColorMetric::Uniform => None,
+            NodeColorMetric::TxPackets => Some(statistics.tx_packets as f64),
+            NodeColorMetric::RxPackets => Some(statistics.rx_packets as f64),
+            NodeColorMetric::CollisionRatio => {
+                let arrived = statistics.rx_packets + statistics.collisions;
+                (arrived > 0).then(|| statistics.collisions as f64 * 100.0 / arrived as f64)
+            }
+            NodeColorMetric::AverageLinkQuality => statistics.average_link_quality(),
+            NodeColorMetric::NeighborCount => Some(statistics.neighbors as f64),
+        }
+    }
+}
+
+/// Value range of the selected metric over all nodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeColorScale {
+    metric: NodeColorMetric,
+    min: f64,
+    max: f64,
+}
+
+impl NodeColorScale {
+    /// Scale of `metric` over the nodes' statistics (None for the uniform coloring or
+    /// when no node has a value).
+    pub fn new(metric: NodeColorMetric, statistics: &HashMap<u32, NodeStatistics>) -> Option<Self> {
+        let (min, max) = statistics
+            .values()
+            .filter_map(|statistics| metric.value(statistics))
+            .fold(None, |range: Option<(f64, f64)>, value| {
+                Some(range.map_or((value, value), |(min, max)| {
+                    (min.min(value), max.max(value))
+                }))
+            })?;
+        Some(Self { metric, min, max })
+    }
+
+    /// Color of a node with the given statistics.
+    pub fn color(&self, statistics: Option<&NodeStatistics>) -> Color32 {
+        match statistics.and_then(|statistics| self.metric.value(statistics)) {
+            Some(value) if self.max > self.min => {
+                scale_color(((value - self.min) / (self.max - self.min)) as f32)
+            }
+            Some(_) => scale_color(1.0),
+            None => NO_DATA_COLOR,
+        }
+    }
+}
+
+/// Color at position `t` (0..=1) of the scale.
+fn scale_color(t: f32) -> Color32 {
+    let position = t.clamp(0.0, 1.0) * (SCALE_STOPS.len() - 1) as f32;
+    let index = (position.floor() as usize).min(SCALE_STOPS.len() - 2);
+    let fraction = position - index as f32;
+    let [r, g, b] = std::array::from_fn(|channel| {
+        let from = SCALE_STOPS[index][channel] as f32;
+        let to = SCALE_STOPS[index + 1][channel] as f32;
+        (from + (to - from) * fraction).round() as u8
+    });
+    Color32::from_rgb(r, g, b)
+}
+
+/// Draw the legend of the scale in the bottom-left corner of the map.
+pub fn draw_legend(painter: &egui::Painter, scale: &NodeColorScale) {
+    let width = 160.0;
+    let origin = painter.clip_rect().left_bottom() + egui::vec2(8.0, -24.0);
+    let bar = egui::Rect::from_min_size(origin, egui::vec2(width, 10.0));
+    let steps = 32;
+    for step in 0..steps {
+        let x0 = bar.left() + width * step as f32 / steps as f32;
+        let x1 = bar.left() + width * (step + 1) as f32 / steps as f32;
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(x0..=x1, bar.y_range()),
+            0.0,
+            scale_color((step as f32 + 0.5) / steps as f32),
+        );
+    }
+    let font = egui::FontId::proportional(12.0);
+    painter.text(
+        bar.left_top() - egui::vec2(0.0, 2.0),
+        egui::Align2::LEFT_BOTTOM,
+        scale.metric.label(),
+        font.clone(),
+        Color32::WHITE,
+    );
+    painter.text(
+        bar.left_bottom() + egui::vec2(0.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        format!("{:.1}", scale.min),
+        font.clone(),
+        Color32::WHITE,
+    );
+    painter.text(
+        bar.right_bottom() + egui::vec2(0.0, 2.0),
+        egui::Align2::RIGHT_TOP,
+        format!("{:.1}", scale.max),
+        font,
+        Color32::WHITE,
+    );
+}
+
+/// Render the "Color by" metric selector.
+pub fn render_selector(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::ComboBox::from_id_source("node_color_metric")
+        .selected_text(format!("Color: {}", state.node_color_metric.label()))
+        .show_ui(ui, |ui| {
+            for metric in NodeColorMetric::ALL {
+                ui.selectable_value(&mut state.node_color_metric, metric, metric.label());
+            }
+        })
+        .response
+        .on_hover_text("Color the nodes on the map by a per-node statistic");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_spans_the_node_values() {
+        let node = |rx_packets, collisions| NodeStatistics {
+            rx_packets,
+            collisions,
+            ..NodeStatistics::default()
+        };
+        let statistics = HashMap::from([(1, node(90, 10)), (2, node(50, 50)), (3, node(0, 0))]);
+
+        assert_eq!(
+            NodeColorScale::new(NodeColorMetric::Uniform, &statistics),
+            None
+        );
+        let scale = NodeColorScale::new(NodeColorMetric::CollisionRatio, &statistics).unwrap();
+        assert_eq!((scale.min, scale.max), (10.0, 50.0));
+        assert_eq!(scale.color(statistics.get(&1)), scale_color(0.0));
+        assert_eq!(scale.color(statistics.get(&2)), scale_color(1.0));
+        // No packets arrived at node 3: no collision ratio
+        assert_eq!(scale.color(statistics.get(&3)), NO_DATA_COLOR);
+        assert_eq!(scale.color(None), NO_DATA_COLOR);
+
+        assert_eq!(scale_color(0.0), Color32::from_rgb(68, 1, 84));
+        assert_eq!(scale_color(1.0), Color32::from_rgb(253, 231, 37));
+    }
+}
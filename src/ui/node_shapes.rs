@@ -0,0 +1,252 @@
+//! # Cached Node Shapes
+//!
+//! With thousands of nodes, painting every node body as a separate circle or square
+//! shape and tessellating it on each frame limits the frame rate. The node bodies
+//! are therefore built into a single triangle mesh in screen coordinates, which is
+//! reused until it is out of date and handed to the painter as one shared shape.
+//!
+//! The mesh is rebuilt when:
+//! - a refresh message changed the nodes or their colors (`invalidate`: node list
+//!   and node updates, statistics snapshots, topology edits and node drags)
+//! - the inputs of the frame differ from the cached ones (`NodeShapeKey`: map
+//!   rectangle and world, selection, color metric, displayed measurement and its
+//!   reach count, and the silent node set, which changes with time)
+//!
+//! Labels, measurement origin markers and transmission indicators are few or
+//! transient and are still painted per frame on top of the mesh.
+
+use eframe::egui;
+use egui::{Color32, Mesh, Pos2};
+use std::sync::Arc;
+
+use super::node_coloring::NodeColorMetric;
+use crate::common::units::WorldTransform;
+
+/// Segments of a node circle (the nodes are a few pixels wide).
+const CIRCLE_SEGMENTS: usize = 12;
+
+/// Width of the anti-aliasing fringe of circles (pixels).
+const FEATHER: f32 = 1.0;
+
+/// Body of a node on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeGlyph {
+    /// Filled circle.
+    Disc,
+    /// Hollow circle (radio switched off).
+    Ring,
+    /// Larger square with a white outline.
+    Gateway,
+}
+
+/// Inputs of the node mesh that are not tracked by refresh messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeShapeKey {
+    pub rect: egui::Rect,
+    pub world: WorldTransform,
+    pub node_count: usize,
+    pub selected: Option<usize>,
+    pub metric: NodeColorMetric,
+    pub measurement_identifier: u32,
+    /// Reached nodes of the displayed measurement.
+    pub reached_count: usize,
+    /// Order-independent hash of the silent node IDs (see `silent_hash`).
+    pub silent_hash: u64,
+}
+
+/// Cached mesh of the node bodies.
+#[derive(Debug, Clone, Default)]
+pub struct NodeShapeCache {
+    key: Option<NodeShapeKey>,
+    mesh: Option<Arc<Mesh>>,
+    dirty: bool,
+}
+
+impl NodeShapeCache {
+    /// Rebuild the mesh on the next frame (nodes or their colors changed).
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether the mesh must be rebuilt for the frame's `key`.
+    pub fn needs_rebuild(&self, key: &NodeShapeKey) -> bool {
+        self.dirty || self.mesh.is_none() || self.key.as_ref() != Some(key)
+    }
+
+    /// Store the mesh built for `key`.
+    pub fn store(&mut self, key: NodeShapeKey, mesh: Mesh) {
+        self.key = Some(key);
+        self.mesh = Some(Arc::new(mesh));
+        self.dirty = false;
+    }
+
+    /// The cached mesh as a shape (None before the first build).
+    pub fn shape(&self) -> Option<egui::Shape> {
+        self.mesh.clone().map(egui::Shape::Mesh)
+    }
+}
+
+/// Order-independent hash of a set of node IDs.
+pub fn silent_hash<'a>(node_ids: impl IntoIterator<Item = &'a u32>) -> u64 {
+    node_ids.into_iter().fold(0, |hash, node_id| {
+        hash ^ (*node_id as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    })
+}
+
+/// Build the mesh of node bodies.
+///
+/// # Parameters
+///
+/// * `nodes` - Screen position, glyph and color of every node
+/// * `radius` - Radius of a node circle (pixels)
+pub fn build_mesh(
+    nodes: impl IntoIterator<Item = (Pos2, NodeGlyph, Color32)>,
+    radius: f32,
+) -> Mesh {
+    let mut mesh = Mesh::default();
+    for (center, glyph, color) in nodes {
+        match glyph {
+            NodeGlyph::Disc => add_disc(&mut mesh, center, radius, color),
+            NodeGlyph::Ring => add_ring(&mut mesh, center, radius, 1.5, color),
+            NodeGlyph::Gateway => add_gateway(&mut mesh, center, radius * 1.25, color),
+        }
+    }
+    mesh
+}
+
+/// Add a band between two concentric circles with a color at each edge.
+fn add_band(mesh: &mut Mesh, center: Pos2, inner: (f32, Color32), outer: (f32, Color32)) {
+    let first = mesh.vertices.len() as u32;
+    for segment in 0..CIRCLE_SEGMENTS {
+        let angle = std::f32::consts::TAU * segment as f32 / CIRCLE_SEGMENTS as f32;
+        let direction = egui::vec2(angle.cos(), angle.sin());
+        mesh.colored_vertex(center + direction * inner.0.max(0.0), inner.1);
+        mesh.colored_vertex(center + direction * outer.0, outer.1);
+    }
+    for segment in 0..CIRCLE_SEGMENTS as u32 {
+        let next = (segment + 1) % CIRCLE_SEGMENTS as u32;
+        let (inner_a, outer_a) = (first + segment * 2, first + segment * 2 + 1);
+        let (inner_b, outer_b) = (first + next * 2, first + next * 2 + 1);
+        mesh.add_triangle(inner_a, outer_a, outer_b);
+        mesh.add_triangle(inner_a, outer_b, inner_b);
+    }
+}
+
+/// Filled circle with a feathered edge.
+fn add_disc(mesh: &mut Mesh, center: Pos2, radius: f32, color: Color32) {
+    let solid = radius - FEATHER / 2.0;
+    let hub = mesh.vertices.len() as u32;
+    mesh.colored_vertex(center, color);
+    add_band(
+        mesh,
+        center,
+        (solid, color),
+        (radius + FEATHER / 2.0, Color32::TRANSPARENT),
+    );
+    let rim = hub + 1;
+    for segment in 0..CIRCLE_SEGMENTS as u32 {
+        let next = (segment + 1) % CIRCLE_SEGMENTS as u32;
+        mesh.add_triangle(hub, rim + segment * 2, rim + next * 2);
+    }
+}
+
+/// Circle outline of `width` pixels with feathered edges.
+fn add_ring(mesh: &mut Mesh, center: Pos2, radius: f32, width: f32, color: Color32) {
+    let inner = radius - width / 2.0;
+    let outer = radius + width / 2.0;
+    add_band(
+        mesh,
+        center,
+        (inner - FEATHER, Color32::TRANSPARENT),
+        (inner, color),
+    );
+    add_band(mesh, center, (inner, color), (outer, color));
+    add_band(
+        mesh,
+        center,
+        (outer, color),
+        (outer + FEATHER, Color32::TRANSPARENT),
+    );
+}
+
+/// Filled square with a one pixel white outline.
+fn add_gateway(mesh: &mut Mesh, center: Pos2, half: f32, color: Color32) {
+    let body = egui::Rect::from_center_size(center, egui::vec2(half * 2.0, half * 2.0));
+    let frame = body.expand(1.0);
+    for edge in [
+        egui::Rect::from_min_max(frame.min, egui::pos2(frame.max.x, body.min.y)),
+        egui::Rect::from_min_max(egui::pos2(frame.min.x, body.max.y), frame.max),
+        egui::Rect::from_min_max(
+            egui::pos2(frame.min.x, body.min.y),
+            egui::pos2(body.min.x, body.max.y),
+        ),
+        egui::Rect::from_min_max(
+            egui::pos2(body.max.x, body.min.y),
+            egui::pos2(frame.max.x, body.max.y),
+        ),
+    ] {
+        mesh.add_colored_rect(edge, Color32::WHITE);
+    }
+    mesh.add_colored_rect(body, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_glyphs_and_cache_invalidation() {
+        let color = Color32::from_rgb(40, 200, 255);
+        let disc = build_mesh([(Pos2::new(10.0, 10.0), NodeGlyph::Disc, color)], 4.0);
+        assert_eq!(disc.vertices.len(), 1 + CIRCLE_SEGMENTS * 2);
+        assert_eq!(disc.indices.len(), CIRCLE_SEGMENTS * 3 * 3);
+        assert_eq!(disc.vertices[0].color, color);
+        // The fringe fades out and stays within the radius plus half the feather
+        assert!(
+            disc.vertices
+                .iter()
+                .all(|v| (v.pos - Pos2::new(10.0, 10.0)).length() <= 4.5 + 1e-4)
+        );
+        assert!(
+            disc.vertices
+                .iter()
+                .any(|v| v.color == Color32::TRANSPARENT)
+        );
+
+        let mixed = build_mesh(
+            [
+                (Pos2::new(0.0, 0.0), NodeGlyph::Ring, color),
+                (Pos2::new(20.0, 0.0), NodeGlyph::Gateway, color),
+            ],
+            4.0,
+        );
+        assert_eq!(mixed.vertices.len(), 3 * CIRCLE_SEGMENTS * 2 + 5 * 4);
+        assert!(mixed.is_valid());
+
+        let key = NodeShapeKey {
+            rect: egui::Rect::from_min_size(Pos2::ZERO, egui::vec2(100.0, 100.0)),
+            world: WorldTransform::default(),
+            node_count: 2,
+            selected: None,
+            metric: NodeColorMetric::Uniform,
+            measurement_identifier: 0,
+            reached_count: 0,
+            silent_hash: silent_hash(&[1, 2]),
+        };
+        let mut cache = NodeShapeCache::default();
+        assert!(cache.needs_rebuild(&key));
+        cache.store(key.clone(), mixed);
+        assert!(!cache.needs_rebuild(&key));
+        assert!(cache.needs_rebuild(&NodeShapeKey {
+            selected: Some(1),
+            ..key.clone()
+        }));
+        // The silent set hash does not depend on the order
+        assert!(!cache.needs_rebuild(&NodeShapeKey {
+            silent_hash: silent_hash(&[2, 1]),
+            ..key.clone()
+        }));
+        cache.invalidate();
+        assert!(cache.needs_rebuild(&key));
+    }
+}
@@ -0,0 +1,460 @@
+//! # Node Table Window
+//!
+//! Sortable, filterable table of all nodes with their cumulative radio statistics
+//! (TX, RX, collisions, packets missed while transmitting, average link quality,
+//! neighbor count, relay delay, share of duplicate flooded packets, packets
+//! delivered with injected faults, channel activity detections and the share of
+//! them that found the channel busy). Statistics are published by the backend about once per second.
+//!
+//! Clicking a row selects the node on the map and in the inspector; the selected
+//! node's row is highlighted.
+
+use eframe::egui;
+
+use super::AppState;
+use crate::common::node_statistics::NodeStatistics;
+
+/// Column the node table is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeTableColumn {
+    #[default]
+    NodeId,
+    Tx,
+    Rx,
+    Collisions,
+    MissedWhileTransmitting,
+    MissedWhileSleeping,
+    AverageLinkQuality,
+    Neighbors,
+    RelayDelay,
+    Duplicates,
+    Faults,
+    Cad,
+    Memory,
+}
+
+impl NodeTableColumn {
+    /// Column header text.
+    fn title(self) -> &'static str {
+        match self {
+            NodeTableColumn::NodeId => "Node",
+            NodeTableColumn::Tx => "TX",
+            NodeTableColumn::Rx => "RX",
+            NodeTableColumn::Collisions => "Coll.",
+            NodeTableColumn::MissedWhileTransmitting => "Missed TX",
+            NodeTableColumn::MissedWhileSleeping => "Missed sleep",
+            NodeTableColumn::AverageLinkQuality => "Avg LQ",
+            NodeTableColumn::Neighbors => "Neighbors",
+            NodeTableColumn::RelayDelay => "Relay delay",
+            NodeTableColumn::Duplicates => "Dup.",
+            NodeTableColumn::Faults => "Faults",
+            NodeTableColumn::Cad => "CAD",
+            NodeTableColumn::Memory => "Memory",
+        }
+    }
+}
+
+/// View state of the node table window.
+#[derive(Debug, Clone, Default)]
+pub struct NodeTableState {
+    /// Whether the window is open.
+    pub open: bool,
+    /// Column the rows are sorted by.
+    pub sort_column: NodeTableColumn,
+    /// Sort in descending order.
+    pub descending: bool,
+    /// Node ID filter (substring match, empty = all nodes).
+    pub filter: String,
+}
+
+/// One row of the table.
+struct NodeRow {
+    node_id: u32,
+    tx: u64,
+    rx: u64,
+    collisions: u64,
+    missed_while_transmitting: u64,
+    missed_while_sleeping: u64,
+    average_link_quality: Option<f64>,
+    neighbors: usize,
+    /// Average and longest relay delay (ms), if any block was relayed.
+    relay_delay: Option<(f64, u64)>,
+    /// Percentage of received flooded packets that were duplicates.
+    duplicate_ratio: Option<f64>,
+    /// Packets delivered truncated and corrupted.
+    faults: (u64, u64),
+    /// CAD requests and the percentage of the answered ones that found the channel busy.
+    cad: (u64, Option<f64>),
+    /// Estimated memory of the node's histories and queues in bytes.
+    memory_bytes: u64,
+}
+
+impl NodeRow {
+    fn new(node_id: u32, statistics: Option<&NodeStatistics>) -> Self {
+        match statistics {
+            Some(s) => Self {
+                node_id,
+                tx: s.tx_packets,
+                rx: s.rx_packets,
+                collisions: s.collisions,
+                missed_while_transmitting: s.missed_while_transmitting,
+                missed_while_sleeping: s.missed_while_sleeping,
+                average_link_quality: s.average_link_quality(),
+                neighbors: s.neighbors,
+                relay_delay: s
+                    .average_relay_delay()
+                    .map(|average| (average, s.relay_delay_max_ms)),
+                duplicate_ratio: s.duplicate_ratio(),
+                faults: (s.truncated_packets, s.corrupted_packets),
+                cad: (s.cad_requests, s.cad_busy_ratio()),
+                memory_bytes: s.memory_bytes,
+            },
+            None => Self {
+                node_id,
+                tx: 0,
+                rx: 0,
+                collisions: 0,
+                missed_while_transmitting: 0,
+                missed_while_sleeping: 0,
+                average_link_quality: None,
+                neighbors: 0,
+                relay_delay: None,
+                duplicate_ratio: None,
+                faults: (0, 0),
+                cad: (0, None),
+                memory_bytes: 0,
+            },
+        }
+    }
+}
+
+/// Render the node table window (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (table view state, selection)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.node_table.open {
+        return;
+    }
+
+    let filter = state.node_table.filter.trim().to_string();
+    let mut rows: Vec<NodeRow> = state
+        .nodes
+        .iter()
+        .filter(|node| filter.is_empty() || node.node_id.to_string().contains(&filter))
+        .map(|node| NodeRow::new(node.node_id, state.node_statistics.get(&node.node_id)))
+        .collect();
+    sort_rows(
+        &mut rows,
+        state.node_table.sort_column,
+        state.node_table.descending,
+    );
+
+    let selected_node_id = state
+        .selected
+        .and_then(|i| state.nodes.get(i))
+        .map(|n| n.node_id);
+    let (poor_limit, excellent_limit) = (state.poor_limit, state.excellent_limit);
+    let mut clicked_node: Option<u32> = None;
+    let mut open = state.node_table.open;
+    let table_state = &mut state.node_table;
+
+    egui::Window::new("Nodes")
+        .open(&mut open)
+        .default_width(800.0)
+        .default_height(400.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Filter node ID:");
+                ui.text_edit_singleline(&mut table_state.filter);
+                ui.label(format!("{} nodes", rows.len()));
+            });
+            ui.separator();
+
+            use egui_extras::{Column, TableBuilder};
+            let row_height = ui.text_style_height(&egui::TextStyle::Body) * 1.3;
+            let columns = [
+                NodeTableColumn::NodeId,
+                NodeTableColumn::Tx,
+                NodeTableColumn::Rx,
+                NodeTableColumn::Collisions,
+                NodeTableColumn::MissedWhileTransmitting,
+                NodeTableColumn::MissedWhileSleeping,
+                NodeTableColumn::AverageLinkQuality,
+                NodeTableColumn::Neighbors,
+                NodeTableColumn::RelayDelay,
+                NodeTableColumn::Duplicates,
+                NodeTableColumn::Faults,
+                NodeTableColumn::Cad,
+                NodeTableColumn::Memory,
+            ];
+            TableBuilder::new(ui)
+                .striped(true)
+                .vscroll(true)
+                .sense(egui::Sense::click())
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .columns(Column::remainder().at_least(55.0), columns.len())
+                .header(row_height, |mut header| {
+                    for column in columns {
+                        header.col(|ui| {
+                            let mut title = column.title().to_string();
+                            if table_state.sort_column == column {
+                                title.push_str(if table_state.descending {
+                                    " ▼"
+                                } else {
+                                    " ▲"
+                                });
+                            }
+                            let mut response = ui.button(egui::RichText::new(title).strong());
+                            if column == NodeTableColumn::MissedWhileSleeping {
+                                response = response.on_hover_text(
+                                    "Packets lost because the node's receiver was asleep (duty cycling)",
+                                );
+                            }
+                            if column == NodeTableColumn::RelayDelay {
+                                response = response.on_hover_text(
+                                    "Average / longest time (ms) from receiving a measurement block to sending its first packet, including the random TX delay",
+                                );
+                            } else if column == NodeTableColumn::Duplicates {
+                                response = response.on_hover_text(
+                                    "Share of received AddBlock and AddTransaction packets the node already knew (Simulation mode)",
+                                );
+                            } else if column == NodeTableColumn::Faults {
+                                response = response.on_hover_text(
+                                    "Received packets delivered truncated / corrupted by the scene's packet_faults (Simulation mode)",
+                                );
+                            } else if column == NodeTableColumn::Cad {
+                                response = response.on_hover_text(
+                                    "Channel activity detections requested (share of the answers that found the channel busy; Simulation mode)",
+                                );
+                            } else if column == NodeTableColumn::Memory {
+                                response = response.on_hover_text(
+                                    "Estimated memory (KiB) of the node's packet, message and log histories and event queues (Simulation mode)",
+                                );
+                            }
+                            if response.clicked() {
+                                if table_state.sort_column == column {
+                                    table_state.descending = !table_state.descending;
+                                } else {
+                                    table_state.sort_column = column;
+                                    table_state.descending = column != NodeTableColumn::NodeId;
+                                }
+                            }
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(row_height, rows.len(), |mut row| {
+                        let data = &rows[row.index()];
+                        row.set_selected(selected_node_id == Some(data.node_id));
+                        row.col(|ui| {
+                            ui.label(format!("#{}", data.node_id));
+                        });
+                        row.col(|ui| {
+                            ui.label(data.tx.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(data.rx.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(data.collisions.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(data.missed_while_transmitting.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(data.missed_while_sleeping.to_string());
+                        });
+                        row.col(|ui| match data.average_link_quality {
+                            Some(lq) => {
+                                let color = if lq <= poor_limit as f64 {
+                                    egui::Color32::RED
+                                } else if lq >= excellent_limit as f64 {
+                                    egui::Color32::GREEN
+                                } else {
+                                    egui::Color32::YELLOW
+                                };
+                                ui.label(egui::RichText::new(format!("{:.1}", lq)).color(color));
+                            }
+                            None => {
+                                ui.label("-");
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(data.neighbors.to_string());
+                        });
+                        row.col(|ui| match data.relay_delay {
+                            Some((average, max)) => {
+                                ui.label(format!("{:.0} / {}", average, max));
+                            }
+                            None => {
+                                ui.label("-");
+                            }
+                        });
+                        row.col(|ui| match data.duplicate_ratio {
+                            Some(ratio) => {
+                                ui.label(format!("{:.0}%", ratio));
+                            }
+                            None => {
+                                ui.label("-");
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{} / {}", data.faults.0, data.faults.1));
+                        });
+                        row.col(|ui| match data.cad {
+                            (requests, Some(busy)) => {
+                                ui.label(format!("{} ({:.0}% busy)", requests, busy));
+                            }
+                            (requests, None) => {
+                                ui.label(requests.to_string());
+                            }
+                        });
+                        row.col(|ui| {
+                            if data.memory_bytes > 0 {
+                                ui.label(format!("{:.0} KiB", data.memory_bytes as f64 / 1024.0));
+                            } else {
+                                ui.label("-");
+                            }
+                        });
+                        if row.response().clicked() {
+                            clicked_node = Some(data.node_id);
+                        }
+                    });
+                });
+        });
+    state.node_table.open = open;
+
+    if let Some(node_id) = clicked_node {
+        state.select_node(node_id);
+    }
+}
+
+/// Sort rows by the given column; ties are broken by node ID.
+fn sort_rows(rows: &mut [NodeRow], column: NodeTableColumn, descending: bool) {
+    rows.sort_by(|a, b| {
+        let ordering = match column {
+            NodeTableColumn::NodeId => a.node_id.cmp(&b.node_id),
+            NodeTableColumn::Tx => a.tx.cmp(&b.tx),
+            NodeTableColumn::Rx => a.rx.cmp(&b.rx),
+            NodeTableColumn::Collisions => a.collisions.cmp(&b.collisions),
+            NodeTableColumn::MissedWhileTransmitting => a
+                .missed_while_transmitting
+                .cmp(&b.missed_while_transmitting),
+            NodeTableColumn::MissedWhileSleeping => {
+                a.missed_while_sleeping.cmp(&b.missed_while_sleeping)
+            }
+            NodeTableColumn::AverageLinkQuality => a
+                .average_link_quality
+                .unwrap_or(-1.0)
+                .total_cmp(&b.average_link_quality.unwrap_or(-1.0)),
+            NodeTableColumn::Neighbors => a.neighbors.cmp(&b.neighbors),
+            NodeTableColumn::RelayDelay => a
+                .relay_delay
+                .map_or(-1.0, |(average, _)| average)
+                .total_cmp(&b.relay_delay.map_or(-1.0, |(average, _)| average)),
+            NodeTableColumn::Duplicates => a
+                .duplicate_ratio
+                .unwrap_or(-1.0)
+                .total_cmp(&b.duplicate_ratio.unwrap_or(-1.0)),
+            NodeTableColumn::Faults => (a.faults.0 + a.faults.1).cmp(&(b.faults.0 + b.faults.1)),
+            NodeTableColumn::Cad => a
+                .cad
+                .1
+                .unwrap_or(-1.0)
+                .total_cmp(&b.cad.1.unwrap_or(-1.0))
+                .then(a.cad.0.cmp(&b.cad.0)),
+            NodeTableColumn::Memory => a.memory_bytes.cmp(&b.memory_bytes),
+        };
+        let ordering = if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then(a.node_id.cmp(&b.node_id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Node IDs of the rows sorted by `column`.
+    fn sorted(rows: &mut [NodeRow], column: NodeTableColumn, descending: bool) -> Vec<u32> {
+        sort_rows(rows, column, descending);
+        rows.iter().map(|row| row.node_id).collect()
+    }
+
+    #[test]
+    fn test_sort_rows_by_column_with_node_id_ties() {
+        // Node 1 scores low, node 2 high, nodes 3 and 4 tie in between; node 5 has no
+        // statistics (missing optional values sort first)
+        let statistics = |level: u64| NodeStatistics {
+            tx_packets: level,
+            rx_packets: level,
+            collisions: level,
+            missed_while_transmitting: level,
+            missed_while_sleeping: level,
+            link_quality_sum: level * level,
+            neighbors: level as usize,
+            relays: 1,
+            relay_delay_sum_ms: level,
+            memory_bytes: level,
+            flood_new_packets: 10 - level,
+            flood_duplicate_packets: level,
+            truncated_packets: level,
+            cad_requests: level,
+            cad_busy: level,
+            cad_free: 10 - level,
+            ..NodeStatistics::default()
+        };
+        let rows = || {
+            vec![
+                NodeRow::new(4, Some(&statistics(5))),
+                NodeRow::new(2, Some(&statistics(9))),
+                NodeRow::new(5, None),
+                NodeRow::new(3, Some(&statistics(5))),
+                NodeRow::new(1, Some(&statistics(1))),
+            ]
+        };
+
+        assert_eq!(
+            sorted(&mut rows(), NodeTableColumn::NodeId, false),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            sorted(&mut rows(), NodeTableColumn::NodeId, true),
+            vec![5, 4, 3, 2, 1]
+        );
+        for column in [
+            NodeTableColumn::Tx,
+            NodeTableColumn::Rx,
+            NodeTableColumn::Collisions,
+            NodeTableColumn::MissedWhileTransmitting,
+            NodeTableColumn::MissedWhileSleeping,
+            NodeTableColumn::AverageLinkQuality,
+            NodeTableColumn::Neighbors,
+            NodeTableColumn::RelayDelay,
+            NodeTableColumn::Duplicates,
+            NodeTableColumn::Faults,
+            NodeTableColumn::Cad,
+            NodeTableColumn::Memory,
+        ] {
+            // Ties stay in node ID order in both directions
+            assert_eq!(
+                sorted(&mut rows(), column, false),
+                vec![5, 1, 3, 4, 2],
+                "{:?}",
+                column
+            );
+            assert_eq!(
+                sorted(&mut rows(), column, true),
+                vec![2, 3, 4, 1, 5],
+                "{:?}",
+                column
+            );
+        }
+    }
+}
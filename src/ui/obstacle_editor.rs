@@ -0,0 +1,454 @@
+//! # Obstacle Editor
+//!
+//! Runtime editing of obstacles in Simulation mode. While editing is enabled, every
+//! obstacle gets two drag handles on the map:
+//! - a center handle that moves the obstacle
+//! - a resize handle (bottom-right corner of a rectangle, rim of a circle)
+//!
+//! Clicking a handle selects its obstacle; the editor window adds rectangles and
+//! circles and deletes the selected obstacle. Every finished edit is recorded in
+//! the edit history (undo/redo) and sent to the network task with
+//! `UICommand::UpdateObstacles`, which applies it to the line-of-sight checks and
+//! publishes the effective obstacles back with `UIRefreshState::ObstaclesUpdated`.
+
+use eframe::egui;
+use egui::Color32;
+
+use super::edit_history::Edit;
+use super::map::MapTransform;
+use super::{AppState, UICommand};
+use crate::simulation::types::{CirclePos, RectPos};
+use crate::simulation::{Obstacle, Point};
+
+/// Side length of a drag handle in pixels.
+const HANDLE_SIZE: f32 = 10.0;
+/// Minimum rectangle side (world units) and circle radius (meters).
+const MIN_OBSTACLE_SIZE: f64 = 1.0;
+
+/// View state of the obstacle editor.
+#[derive(Debug, Clone, Default)]
+pub struct ObstacleEditorState {
+    /// Whether obstacle editing is enabled (handles and editor window shown).
+    pub enabled: bool,
+    /// Index of the selected obstacle in `AppState::obstacles`.
+    pub selected: Option<usize>,
+    /// Obstacles before the drag in progress (a whole drag is one undoable edit).
+    drag_before: Option<Vec<Obstacle>>,
+}
+
+/// Kind of drag handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Handle {
+    Move,
+    Resize,
+}
+
+/// Record a finished edit for undo and send the current obstacle list to the network task.
+fn commit(state: &mut AppState, before: Vec<Obstacle>) {
+    state.edit_history.record(Edit::Obstacles {
+        before,
+        after: state.obstacles.clone(),
+    });
+    state
+        .ui_command_tx
+        .try_send(UICommand::UpdateObstacles(state.obstacles.clone()))
+        .ok();
+}
+
+/// Render the obstacle editor window (if editing is enabled).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (obstacles and editor state)
+pub fn render_window(ctx: &egui::Context, state: &mut AppState) {
+    if !state.obstacle_editor.enabled {
+        return;
+    }
+
+    let mut open = true;
+    let mut changed = false;
+    let before = state.obstacles.clone();
+    egui::Window::new("Obstacle editor")
+        .open(&mut open)
+        .resizable(false)
+        .default_width(240.0)
+        .show(ctx, |ui| {
+            ui.label(
+                "Drag the center handle to move an obstacle and the outer handle to resize it.",
+            );
+            ui.separator();
+
+            // New obstacles are placed in the middle of the world
+            let (left, top) = state.world.top_left();
+            let (right, bottom) = state.world.bottom_right();
+            let (center_x, center_y) = ((left + right) / 2.0, (top + bottom) / 2.0);
+            let (half_width, half_height) =
+                ((right - left).abs() / 20.0, (bottom - top).abs() / 20.0);
+
+            ui.horizontal(|ui| {
+                if ui.button("Add rectangle").clicked() {
+                    state.obstacles.push(Obstacle::Rectangle {
+                        position: RectPos {
+                            top_left: Point {
+                                x: center_x - half_width,
+                                y: center_y - half_height,
+                            },
+                            bottom_right: Point {
+                                x: center_x + half_width,
+                                y: center_y + half_height,
+                            },
+                        },
+                        one_way: None,
+                    });
+                    state.obstacle_editor.selected = Some(state.obstacles.len() - 1);
+                    changed = true;
+                }
+                if ui.button("Add circle").clicked() {
+                    state.obstacles.push(Obstacle::Circle {
+                        position: CirclePos {
+                            center: Point {
+                                x: center_x,
+                                y: center_y,
+                            },
+                            radius: (state.world.width_meters().min(state.world.height_meters())
+                                / 20.0)
+                                .max(MIN_OBSTACLE_SIZE),
+                        },
+                        one_way: None,
+                    });
+                    state.obstacle_editor.selected = Some(state.obstacles.len() - 1);
+                    changed = true;
+                }
+            });
+
+            let selected = state
+                .obstacle_editor
+                .selected
+                .filter(|i| *i < state.obstacles.len());
+            ui.add_enabled_ui(selected.is_some(), |ui| {
+                if ui.button("Delete selected").clicked() {
+                    if let Some(index) = selected {
+                        state.obstacles.remove(index);
+                        state.obstacle_editor.selected = None;
+                        changed = true;
+                    }
+                }
+            });
+            ui.label(format!("{} obstacles", state.obstacles.len()));
+        });
+
+    if changed {
+        commit(state, before);
+    }
+    if !open {
+        state.obstacle_editor.enabled = false;
+        state.obstacle_editor.selected = None;
+    }
+}
+
+/// Draw the drag handles of all obstacles on the map and apply drags.
+///
+/// Must be called after the map canvas was registered so the handles take
+/// precedence over node selection clicks.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI of the map panel
+/// * `painter` - Painter clipped to the map rectangle
+/// * `transform` - World ↔ screen mapping of the map rectangle
+/// * `state` - Mutable application state (obstacles and editor state)
+pub fn render_handles(
+    ui: &mut egui::Ui,
+    painter: &egui::Painter,
+    transform: &MapTransform,
+    state: &mut AppState,
+) {
+    let mut finished_edit = false;
+
+    for index in 0..state.obstacles.len() {
+        let is_selected = state.obstacle_editor.selected == Some(index);
+        for (handle, handle_rect) in handle_rects(&state.obstacles[index], transform) {
+            let response = ui.interact(
+                handle_rect,
+                egui::Id::new(("obstacle_handle", index, handle)),
+                egui::Sense::click_and_drag(),
+            );
+
+            let fill = if is_selected {
+                Color32::from_rgb(255, 140, 0)
+            } else {
+                Color32::from_rgb(200, 200, 200)
+            };
+            painter.rect_filled(handle_rect, 1.0, fill);
+            painter.rect_stroke(
+                handle_rect,
+                1.0,
+                egui::Stroke::new(1.0, Color32::BLACK),
+                egui::StrokeKind::Middle,
+            );
+
+            if response.clicked() || response.drag_started() {
+                state.obstacle_editor.selected = Some(index);
+            }
+            if response.drag_started() && state.obstacle_editor.drag_before.is_none() {
+                state.obstacle_editor.drag_before = Some(state.obstacles.clone());
+            }
+            if response.dragged() {
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    apply_drag(&mut state.obstacles[index], handle, pointer, transform);
+                    state.neighbor_graph.invalidate();
+                }
+            }
+            if response.drag_stopped() {
+                finished_edit = true;
+            }
+        }
+    }
+
+    if finished_edit {
+        if let Some(before) = state.obstacle_editor.drag_before.take() {
+            commit(state, before);
+        }
+    }
+}
+
+/// Screen rectangles of an obstacle's drag handles.
+fn handle_rects(obstacle: &Obstacle, transform: &MapTransform) -> [(Handle, egui::Rect); 2] {
+    let (center, rim) = match obstacle {
+        Obstacle::Rectangle { position, .. } => (
+            transform.world_to_screen(
+                (position.top_left.x + position.bottom_right.x) / 2.0,
+                (position.top_left.y + position.bottom_right.y) / 2.0,
+            ),
+            transform.world_to_screen(position.bottom_right.x, position.bottom_right.y),
+        ),
+        Obstacle::Circle { position, .. } => {
+            let center = transform.world_to_screen(position.center.x, position.center.y);
+            // The rim handle sits at the meter radius converted with the horizontal scale
+            let (radius_x, _) = transform.world().offset_to_units(position.radius, 0.0);
+            let rim = transform.world_to_screen(position.center.x + radius_x, position.center.y);
+            (center, rim)
+        }
+    };
+    let handle_size = egui::vec2(HANDLE_SIZE, HANDLE_SIZE);
+    [
+        (
+            Handle::Move,
+            egui::Rect::from_center_size(center, handle_size),
+        ),
+        (
+            Handle::Resize,
+            egui::Rect::from_center_size(rim, handle_size),
+        ),
+    ]
+}
+
+/// Apply a handle drag to an obstacle, keeping it inside the world bounds.
+fn apply_drag(
+    obstacle: &mut Obstacle,
+    handle: Handle,
+    pointer: egui::Pos2,
+    transform: &MapTransform,
+) {
+    let world = transform.world();
+    let (min_x, min_y, max_x, max_y) = world.bounds();
+    let (x, y) = transform.screen_to_world(pointer);
+    let (x, y) = world.clamp(x, y);
+
+    match (obstacle, handle) {
+        (Obstacle::Rectangle { position, .. }, Handle::Move) => {
+            // Corner offsets keep their sign, so inverted axes stay inverted
+            let width = position.bottom_right.x - position.top_left.x;
+            let height = position.bottom_right.y - position.top_left.y;
+            // max/min rather than clamp: an obstacle wider than the world must not panic
+            let center_x = x
+                .max(min_x + width.abs() / 2.0)
+                .min(max_x - width.abs() / 2.0);
+            let center_y = y
+                .max(min_y + height.abs() / 2.0)
+                .min(max_y - height.abs() / 2.0);
+            position.top_left = Point {
+                x: center_x - width / 2.0,
+                y: center_y - height / 2.0,
+            };
+            position.bottom_right = Point {
+                x: center_x + width / 2.0,
+                y: center_y + height / 2.0,
+            };
+        }
+        (Obstacle::Rectangle { position, .. }, Handle::Resize) => {
+            // Keep the corner on its side of the top-left corner, at least a minimum size away
+            let (top_left_x, top_left_y) = (position.top_left.x, position.top_left.y);
+            let (sign_x, sign_y) = (
+                (position.bottom_right.x - top_left_x).signum(),
+                (position.bottom_right.y - top_left_y).signum(),
+            );
+            position.bottom_right = Point {
+                x: top_left_x + sign_x * ((x - top_left_x) * sign_x).max(MIN_OBSTACLE_SIZE),
+                y: top_left_y + sign_y * ((y - top_left_y) * sign_y).max(MIN_OBSTACLE_SIZE),
+            };
+        }
+        (Obstacle::Circle { position, .. }, Handle::Move) => {
+            // The radius is in meters; keep the whole circle inside the world
+            let (radius_x, radius_y) = world.offset_to_units(position.radius, position.radius);
+            position.center = Point {
+                x: x.max(min_x + radius_x).min(max_x - radius_x),
+                y: y.max(min_y + radius_y).min(max_y - radius_y),
+            };
+        }
+        (Obstacle::Circle { position, .. }, Handle::Resize) => {
+            // Convert the pointer offset per axis: a world unit is not the same number
+            // of meters (or pixels) horizontally and vertically
+            let (pointer_x, pointer_y) = transform.screen_to_world(pointer);
+            let (dx, dy) = world
+                .offset_to_meters(pointer_x - position.center.x, pointer_y - position.center.y);
+            let radius = dx.hypot(dy);
+            let (room_x, room_y) = world.offset_to_meters(
+                (position.center.x - min_x).min(max_x - position.center.x),
+                (position.center.y - min_y).min(max_y - position.center.y),
+            );
+            position.radius = radius.min(room_x.min(room_y)).max(MIN_OBSTACLE_SIZE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::units::WorldTransform;
+
+    /// 1000 x 1000 world units of 2000 m x 1000 m on a 500 x 500 pixel map: a pixel is
+    /// two world units, a world unit 2 m horizontally and 1 m vertically.
+    fn transform() -> MapTransform {
+        let world = WorldTransform::from_dimensions((0.0, 0.0), (1000.0, 1000.0), 2000.0, 1000.0);
+        MapTransform::new(
+            world,
+            egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(500.0, 500.0)),
+        )
+    }
+
+    fn rectangle() -> Obstacle {
+        Obstacle::Rectangle {
+            position: RectPos {
+                top_left: Point { x: 100.0, y: 100.0 },
+                bottom_right: Point { x: 300.0, y: 200.0 },
+            },
+            one_way: None,
+        }
+    }
+
+    fn circle(radius: f64) -> Obstacle {
+        Obstacle::Circle {
+            position: CirclePos {
+                center: Point { x: 500.0, y: 500.0 },
+                radius,
+            },
+            one_way: None,
+        }
+    }
+
+    /// Handle of `obstacle` under the screen position, if any.
+    fn hit(obstacle: &Obstacle, x: f32, y: f32) -> Option<Handle> {
+        handle_rects(obstacle, &transform())
+            .into_iter()
+            .find(|(_, rect)| rect.contains(egui::pos2(x, y)))
+            .map(|(handle, _)| handle)
+    }
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_handles_sit_on_the_center_and_the_corner_or_rim() {
+        // Rectangle center (200, 150) and corner (300, 200) in world units
+        assert_eq!(hit(&rectangle(), 100.0, 75.0), Some(Handle::Move));
+        assert_eq!(hit(&rectangle(), 154.0, 96.0), Some(Handle::Resize));
+        assert_eq!(hit(&rectangle(), 106.0, 75.0), None);
+
+        // A 100 m radius is 50 units horizontally: the rim handle is 25 pixels right of
+        // the center, not 50 (as the vertical scale would give)
+        assert_eq!(hit(&circle(100.0), 250.0, 250.0), Some(Handle::Move));
+        assert_eq!(hit(&circle(100.0), 275.0, 250.0), Some(Handle::Resize));
+        assert_eq!(hit(&circle(100.0), 300.0, 250.0), None);
+    }
+
+    #[test]
+    fn test_drags_move_and_resize_within_the_world() {
+        let transform = transform();
+
+        // Moving into the corner keeps the whole rectangle inside the world
+        let mut obstacle = rectangle();
+        apply_drag(
+            &mut obstacle,
+            Handle::Move,
+            egui::pos2(0.0, 0.0),
+            &transform,
+        );
+        let Obstacle::Rectangle { position, .. } = &obstacle else {
+            unreachable!()
+        };
+        assert_near(position.top_left.x, 0.0);
+        assert_near(position.top_left.y, 0.0);
+        assert_near(position.bottom_right.x, 200.0);
+        assert_near(position.bottom_right.y, 100.0);
+
+        // The corner follows the pointer, but not past the top-left corner
+        let mut obstacle = rectangle();
+        apply_drag(
+            &mut obstacle,
+            Handle::Resize,
+            egui::pos2(200.0, 150.0),
+            &transform,
+        );
+        let Obstacle::Rectangle { position, .. } = &obstacle else {
+            unreachable!()
+        };
+        assert_near(position.bottom_right.x, 400.0);
+        assert_near(position.bottom_right.y, 300.0);
+        apply_drag(
+            &mut obstacle,
+            Handle::Resize,
+            egui::pos2(10.0, 10.0),
+            &transform,
+        );
+        let Obstacle::Rectangle { position, .. } = &obstacle else {
+            unreachable!()
+        };
+        assert_near(position.bottom_right.x, 100.0 + MIN_OBSTACLE_SIZE);
+        assert_near(position.bottom_right.y, 100.0 + MIN_OBSTACLE_SIZE);
+
+        // The radius is the pointer distance in meters: 30 units right (60 m) and 40
+        // units down (40 m) give 72.1 m
+        let mut obstacle = circle(100.0);
+        apply_drag(
+            &mut obstacle,
+            Handle::Resize,
+            egui::pos2(265.0, 270.0),
+            &transform,
+        );
+        let Obstacle::Circle { position, .. } = &obstacle else {
+            unreachable!()
+        };
+        assert_near(position.radius, 60f64.hypot(40.0));
+
+        // A moved circle keeps its radius inside the world on both axes
+        apply_drag(
+            &mut obstacle,
+            Handle::Move,
+            egui::pos2(0.0, 0.0),
+            &transform,
+        );
+        let Obstacle::Circle { position, .. } = &obstacle else {
+            unreachable!()
+        };
+        assert_near(position.center.x, 60f64.hypot(40.0) / 2.0);
+        assert_near(position.center.y, 60f64.hypot(40.0));
+    }
+}
@@ -0,0 +1,424 @@
+//! # Packet Latency Waterfall
+//!
+//! The inspector's "Waterfall" tab: for a message fragmented into several packets
+//! (e.g. an AddBlock), one lane per packet with every copy the selected node heard,
+//! over the time since the first copy. Copies are colored by their outcome (decoded,
+//! duplicate, lost to a collision, missed while transmitting or asleep); vertical
+//! markers show the RequestBlockPart messages the node sent for the sequence and
+//! the time the message was assembled. This makes the recovery of missing parts
+//! visible: which parts were lost, how long until they were requested and how long
+//! the repeated copies took.
+//!
+//! The waterfall is built from the node's radio and message streams, so it works in
+//! every mode and covers the packets still in the node's history.
+
+use std::collections::BTreeMap;
+
+use eframe::egui;
+use egui::Color32;
+use embassy_time::Instant;
+
+use super::NodeInfo;
+use super::app_state::message_type_name;
+use crate::simulation::NodeMessage;
+
+/// Message type of RequestBlockPart (`moonblokz_radio_lib::MessageType`).
+const REQUEST_BLOCK_PART: u8 = 5;
+
+const DECODED_COLOR: Color32 = Color32::from_rgb(90, 190, 90);
+const DUPLICATE_COLOR: Color32 = Color32::from_rgb(70, 110, 70);
+const LOST_COLOR: Color32 = Color32::from_rgb(220, 80, 80);
+const MISSED_COLOR: Color32 = Color32::from_rgb(230, 160, 60);
+const REQUEST_COLOR: Color32 = Color32::from_rgb(90, 150, 230);
+
+/// Outcome of a copy of a packet at the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyOutcome {
+    /// First decoded copy of the packet.
+    Decoded,
+    /// Decoded again after the packet was already known.
+    Duplicate,
+    Collision,
+    /// Missed while transmitting or asleep.
+    Missed,
+}
+
+/// A copy of a packet heard (or lost) by the node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PacketCopy {
+    time: Instant,
+    sender: u32,
+    outcome: CopyOutcome,
+}
+
+/// Copies, retransmission requests and assembly of one multi-packet message.
+#[derive(Debug, Clone, PartialEq)]
+struct Waterfall {
+    message_type: u8,
+    sequence: u32,
+    packet_count: u8,
+    /// Copies by packet number (1-based, as shown in the radio stream), in time order.
+    lanes: BTreeMap<u8, Vec<PacketCopy>>,
+    /// RequestBlockPart messages the node sent for the sequence.
+    requests: Vec<Instant>,
+    /// When the node assembled the message (None = incomplete).
+    completed: Option<Instant>,
+}
+
+impl Waterfall {
+    /// Time of the first copy.
+    fn start(&self) -> Option<Instant> {
+        self.lanes
+            .values()
+            .filter_map(|copies| copies.first())
+            .map(|copy| copy.time)
+            .min()
+    }
+
+    /// Time of the last event (copy, request or assembly).
+    fn end(&self) -> Option<Instant> {
+        self.lanes
+            .values()
+            .flatten()
+            .map(|copy| copy.time)
+            .chain(self.requests.iter().copied())
+            .chain(self.completed)
+            .max()
+    }
+}
+
+/// Group the multi-packet messages received by `node_info`'s node, newest first.
+fn build(node_info: &NodeInfo) -> Vec<Waterfall> {
+    let mut waterfalls: BTreeMap<(u8, u32), Waterfall> = BTreeMap::new();
+    let mut packets: Vec<&NodeMessage> = node_info.radio_packets.iter().collect();
+    packets.sort_by_key(|msg| msg.timestamp);
+    for msg in &packets {
+        let Some(sequence) = msg.sequence else {
+            continue;
+        };
+        if msg.sender_node == node_info.node_id || msg.packet_count <= 1 {
+            continue;
+        }
+        let waterfall = waterfalls
+            .entry((msg.message_type, sequence))
+            .or_insert_with(|| Waterfall {
+                message_type: msg.message_type,
+                sequence,
+                packet_count: msg.packet_count,
+                lanes: BTreeMap::new(),
+                requests: Vec::new(),
+                completed: None,
+            });
+        let lane = waterfall.lanes.entry(msg.packet_index).or_default();
+        let outcome = if msg.collision {
+            CopyOutcome::Collision
+        } else if msg.missed_while_transmitting || msg.missed_while_sleeping {
+            CopyOutcome::Missed
+        } else if lane.iter().any(|copy| copy.outcome == CopyOutcome::Decoded) {
+            CopyOutcome::Duplicate
+        } else {
+            CopyOutcome::Decoded
+        };
+        lane.push(PacketCopy {
+            time: msg.timestamp,
+            sender: msg.sender_node,
+            outcome,
+        });
+    }
+    // Requests for missing parts the node sent, and the assembled messages
+    for msg in &packets {
+        if msg.sender_node != node_info.node_id || msg.message_type != REQUEST_BLOCK_PART {
+            continue;
+        }
+        for waterfall in waterfalls.values_mut() {
+            if Some(waterfall.sequence) == msg.sequence {
+                waterfall.requests.push(msg.timestamp);
+            }
+        }
+    }
+    for message in node_info.messages.iter().filter(|m| !m.is_outgoing) {
+        if let Some(waterfall) = waterfalls.get_mut(&(message.message_type, message.sequence)) {
+            waterfall.completed = Some(
+                waterfall
+                    .completed
+                    .map_or(message.timestamp, |t| t.min(message.timestamp)),
+            );
+        }
+    }
+    let mut waterfalls: Vec<Waterfall> = waterfalls.into_values().collect();
+    waterfalls.sort_by_key(|waterfall| std::cmp::Reverse(waterfall.start()));
+    waterfalls
+}
+
+/// Seconds from `start` to `time`.
+fn offset_secs(start: Instant, time: Instant) -> f32 {
+    time.saturating_duration_since(start).as_millis() as f32 / 1000.0
+}
+
+/// Render the "Waterfall" tab of the inspector.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI of the tab area
+/// * `node_info` - Streams of the selected node (the receiver)
+pub fn render(ui: &mut egui::Ui, node_info: &NodeInfo) {
+    let waterfalls = build(node_info);
+    if waterfalls.is_empty() {
+        ui.label("No multi-packet messages received in the node's history.");
+        return;
+    }
+
+    // The shown message is kept per node; the newest one by default
+    let selected_id = egui::Id::new(("packet_waterfall_selected", node_info.node_id));
+    let mut selected: (u8, u32) = ui
+        .data(|d| d.get_temp(selected_id))
+        .unwrap_or((waterfalls[0].message_type, waterfalls[0].sequence));
+    let label = |waterfall: &Waterfall| {
+        format!(
+            "{} #{} ({} packets{})",
+            message_type_name(waterfall.message_type),
+            waterfall.sequence,
+            waterfall.packet_count,
+            if waterfall.completed.is_some() {
+                ""
+            } else {
+                ", incomplete"
+            }
+        )
+    };
+    let Some(waterfall) = waterfalls
+        .iter()
+        .find(|w| (w.message_type, w.sequence) == selected)
+        .or(waterfalls.first())
+    else {
+        return;
+    };
+    ui.horizontal(|ui| {
+        ui.label("Message:");
+        egui::ComboBox::from_id_salt("packet_waterfall_message")
+            .selected_text(label(waterfall))
+            .show_ui(ui, |ui| {
+                for w in &waterfalls {
+                    ui.selectable_value(&mut selected, (w.message_type, w.sequence), label(w));
+                }
+            });
+    });
+    ui.data_mut(|d| d.insert_temp(selected_id, selected));
+
+    let (Some(start), Some(end)) = (waterfall.start(), waterfall.end()) else {
+        return;
+    };
+    let span = offset_secs(start, end).max(0.001);
+    let lost = waterfall
+        .lanes
+        .values()
+        .flatten()
+        .filter(|copy| matches!(copy.outcome, CopyOutcome::Collision | CopyOutcome::Missed))
+        .count();
+    let missing = (1..=waterfall.packet_count)
+        .filter(|index| {
+            !waterfall.lanes.get(index).is_some_and(|copies| {
+                copies
+                    .iter()
+                    .any(|copy| copy.outcome == CopyOutcome::Decoded)
+            })
+        })
+        .count();
+    ui.label(format!(
+        "{}, {} copies lost, {} RequestBlockPart sent",
+        waterfall.completed.map_or(
+            format!("incomplete ({} parts missing)", missing),
+            |completed| format!("assembled after {:.3} s", offset_secs(start, completed))
+        ),
+        lost,
+        waterfall.requests.len()
+    ));
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        draw_waterfall(ui, waterfall, start, span);
+    });
+}
+
+/// Draw one lane per packet index with the copies as ticks over the time since
+/// `start`, the requests and the assembly as vertical markers.
+fn draw_waterfall(ui: &mut egui::Ui, waterfall: &Waterfall, start: Instant, span: f32) {
+    const LANE_HEIGHT: f32 = 14.0;
+    const LABEL_WIDTH: f32 = 40.0;
+    let lanes = waterfall.packet_count.max(1) as f32;
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width().max(200.0), lanes * LANE_HEIGHT + 16.0),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let font = egui::FontId::proportional(10.0);
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let plot_left = rect.left() + LABEL_WIDTH;
+    let plot_width = rect.width() - LABEL_WIDTH - 8.0;
+    let x_of = |time: Instant| plot_left + plot_width * offset_secs(start, time) / span;
+    let plot_bottom = rect.top() + lanes * LANE_HEIGHT;
+
+    for index in 1..=waterfall.packet_count {
+        let top = rect.top() + (index - 1) as f32 * LANE_HEIGHT;
+        painter.text(
+            egui::pos2(rect.left() + 4.0, top + LANE_HEIGHT / 2.0),
+            egui::Align2::LEFT_CENTER,
+            format!("#{}", index),
+            font.clone(),
+            visuals.weak_text_color(),
+        );
+        for copy in waterfall.lanes.get(&index).into_iter().flatten() {
+            let color = match copy.outcome {
+                CopyOutcome::Decoded => DECODED_COLOR,
+                CopyOutcome::Duplicate => DUPLICATE_COLOR,
+                CopyOutcome::Collision => LOST_COLOR,
+                CopyOutcome::Missed => MISSED_COLOR,
+            };
+            let x = x_of(copy.time);
+            painter.rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(x - 2.0, top + 2.0),
+                    egui::pos2(x + 2.0, top + LANE_HEIGHT - 2.0),
+                ),
+                1.0,
+                color,
+            );
+        }
+    }
+    for &request in &waterfall.requests {
+        let x = x_of(request);
+        painter.line_segment(
+            [egui::pos2(x, rect.top()), egui::pos2(x, plot_bottom)],
+            egui::Stroke::new(1.0, REQUEST_COLOR),
+        );
+    }
+    if let Some(completed) = waterfall.completed {
+        let x = x_of(completed);
+        painter.line_segment(
+            [egui::pos2(x, rect.top()), egui::pos2(x, plot_bottom)],
+            egui::Stroke::new(2.0, visuals.strong_text_color()),
+        );
+    }
+    painter.text(
+        egui::pos2(plot_left, rect.bottom() - 2.0),
+        egui::Align2::LEFT_BOTTOM,
+        "0 s",
+        font.clone(),
+        visuals.weak_text_color(),
+    );
+    painter.text(
+        egui::pos2(plot_left + plot_width, rect.bottom() - 2.0),
+        egui::Align2::RIGHT_BOTTOM,
+        format!("{:.3} s", span),
+        font,
+        visuals.weak_text_color(),
+    );
+
+    // Copies of the lane under the pointer
+    if let Some(pointer) = response.hover_pos() {
+        let row = ((pointer.y - rect.top()) / LANE_HEIGHT).floor();
+        if row >= 0.0 && row < lanes {
+            let index = row as u8 + 1;
+            let copies: Vec<String> = waterfall
+                .lanes
+                .get(&index)
+                .into_iter()
+                .flatten()
+                .map(|copy| {
+                    format!(
+                        "{:.3} s from #{}: {}",
+                        offset_secs(start, copy.time),
+                        copy.sender,
+                        match copy.outcome {
+                            CopyOutcome::Decoded => "decoded",
+                            CopyOutcome::Duplicate => "duplicate",
+                            CopyOutcome::Collision => "lost to a collision",
+                            CopyOutcome::Missed => "missed (transmitting or asleep)",
+                        }
+                    )
+                })
+                .collect();
+            response.on_hover_text(if copies.is_empty() {
+                format!("Packet #{}: never heard", index)
+            } else {
+                format!("Packet #{}\n{}", index, copies.join("\n"))
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::types::FullMessage;
+
+    #[test]
+    fn copies_requests_and_assembly_are_grouped_per_message() {
+        let packet = |millis, sender_node, message_type, packet_index, collision| NodeMessage {
+            timestamp: Instant::from_millis(millis),
+            message_type,
+            packet_size: 200,
+            packet_count: 3,
+            packet_index,
+            sender_node,
+            link_quality: 40,
+            collision,
+            missed_while_transmitting: false,
+            missed_while_sleeping: false,
+            sequence: Some(42),
+            packet: None,
+        };
+        let node_info = NodeInfo {
+            node_id: 1,
+            radio_packets: vec![
+                packet(1000, 2, 6, 1, false),
+                packet(1200, 2, 6, 2, true),
+                packet(1400, 2, 6, 3, false),
+                packet(1500, 3, 6, 1, false),
+                // The node requests the lost part and receives it from another relay
+                packet(2000, 1, REQUEST_BLOCK_PART, 1, false),
+                packet(2300, 3, 6, 2, false),
+                // Single-packet messages have no waterfall
+                NodeMessage {
+                    packet_count: 1,
+                    sequence: Some(7),
+                    ..packet(2400, 2, 6, 1, false)
+                },
+            ],
+            messages: vec![FullMessage {
+                timestamp: Instant::from_millis(2300),
+                message_type: 6,
+                sender_node: 3,
+                sequence: 42,
+                length: 600,
+                is_outgoing: false,
+            }],
+            log_lines: Vec::new(),
+            probe_version: None,
+            node_version: None,
+        };
+
+        let waterfalls = build(&node_info);
+        assert_eq!(waterfalls.len(), 1);
+        let waterfall = &waterfalls[0];
+        assert_eq!(waterfall.sequence, 42);
+        let outcomes = |index: u8| -> Vec<CopyOutcome> {
+            waterfall.lanes[&index].iter().map(|c| c.outcome).collect()
+        };
+        assert_eq!(
+            outcomes(1),
+            vec![CopyOutcome::Decoded, CopyOutcome::Duplicate]
+        );
+        assert_eq!(
+            outcomes(2),
+            vec![CopyOutcome::Collision, CopyOutcome::Decoded]
+        );
+        assert_eq!(waterfall.requests, vec![Instant::from_millis(2000)]);
+        assert_eq!(waterfall.completed, Some(Instant::from_millis(2300)));
+        assert_eq!(waterfall.start(), Some(Instant::from_millis(1000)));
+        assert_eq!(
+            offset_secs(waterfall.start().unwrap(), waterfall.end().unwrap()),
+            1.3
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! # Recent Files and Drag-and-Drop
+//!
+//! File-dialog-free ways to start a session from the mode selector screen (useful
+//! where the native file dialog is clunky, e.g. over remote desktop):
+//!
+//! - **Recent files**: Every started session (mode, scene and log file) is remembered,
+//!   newest first, and persisted with the settings. Clicking an entry starts it again.
+//! - **Drag-and-drop**: Scene JSON and log files dropped onto the window fill the
+//!   missing file of the mode being set up, or start a session directly (see
+//!   `resolve_drop`).
+
+use eframe::egui;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::OperatingMode;
+use super::mode_selector::{ModeSelection, ModeSelector};
+
+/// Maximum number of remembered sessions.
+const MAX_RECENT: usize = 10;
+
+/// Mode of a remembered session (persisted form of `OperatingMode`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecentMode {
+    Simulation,
+    RealtimeTracking,
+    LogVisualization,
+}
+
+impl From<OperatingMode> for RecentMode {
+    fn from(mode: OperatingMode) -> Self {
+        match mode {
+            OperatingMode::Simulation => RecentMode::Simulation,
+            OperatingMode::RealtimeTracking => RecentMode::RealtimeTracking,
+            OperatingMode::LogVisualization => RecentMode::LogVisualization,
+        }
+    }
+}
+
+/// A previously started session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RecentEntry {
+    pub mode: RecentMode,
+    pub scene_path: String,
+    /// Log file (analyzer modes).
+    pub log_path: Option<String>,
+}
+
+impl RecentEntry {
+    /// Whether all files of the session still exist.
+    fn files_exist(&self) -> bool {
+        Path::new(&self.scene_path).exists()
+            && self
+                .log_path
+                .as_ref()
+                .is_none_or(|log| Path::new(log).exists())
+    }
+
+    /// Mode selection starting this session.
+    fn selection(&self) -> ModeSelection {
+        match (self.mode, self.log_path.clone()) {
+            (RecentMode::RealtimeTracking, Some(log_path)) => ModeSelection::RealtimeTracking {
+                scene_path: self.scene_path.clone(),
+                log_path,
+            },
+            (RecentMode::LogVisualization, Some(log_path)) => ModeSelection::LogVisualization {
+                scene_path: self.scene_path.clone(),
+                log_path,
+            },
+            _ => ModeSelection::SimulationScene(self.scene_path.clone()),
+        }
+    }
+
+    fn label(&self) -> String {
+        let file_name = |path: &str| {
+            Path::new(path)
+                .file_name()
+                .map_or(path.to_string(), |name| name.to_string_lossy().to_string())
+        };
+        let mode = match self.mode {
+            RecentMode::Simulation => "Simulation",
+            RecentMode::RealtimeTracking => "Real-time",
+            RecentMode::LogVisualization => "Log",
+        };
+        match &self.log_path {
+            Some(log) => format!(
+                "{}: {} + {}",
+                mode,
+                file_name(&self.scene_path),
+                file_name(log)
+            ),
+            None => format!("{}: {}", mode, file_name(&self.scene_path)),
+        }
+    }
+}
+
+/// Remember a started session (moved to the front, at most `MAX_RECENT` kept).
+pub fn remember(recent: &mut Vec<RecentEntry>, entry: RecentEntry) {
+    recent.retain(|existing| *existing != entry);
+    recent.insert(0, entry);
+    recent.truncate(MAX_RECENT);
+}
+
+/// Whether a dropped file is a scene (JSON) rather than a log file.
+fn is_scene_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Decide what dropped files do on the mode selector screen.
+///
+/// A scene and a log file together start Log Visualization. A single file fills the
+/// missing file of a half set-up analyzer mode (a log prefers Real-time Tracking if
+/// its scene is already chosen, otherwise Log Visualization). A scene alone with no
+/// analyzer mode in progress starts a simulation.
+///
+/// # Returns
+///
+/// The selection to start, or `None` if only a file path was stored in `selector`.
+pub fn resolve_drop(selector: &mut ModeSelector, paths: &[String]) -> Option<ModeSelection> {
+    let scene = paths.iter().find(|path| is_scene_file(path)).cloned();
+    let log = paths.iter().find(|path| !is_scene_file(path)).cloned();
+    match (scene, log) {
+        (Some(scene_path), Some(log_path)) => Some(ModeSelection::LogVisualization {
+            scene_path,
+            log_path,
+        }),
+        (Some(scene_path), None) => {
+            if let Some(log_path) = selector.realtime_log_path.take() {
+                Some(ModeSelection::RealtimeTracking {
+                    scene_path,
+                    log_path,
+                })
+            } else if let Some(log_path) = selector.logvis_log_path.take() {
+                Some(ModeSelection::LogVisualization {
+                    scene_path,
+                    log_path,
+                })
+            } else {
+                Some(ModeSelection::SimulationScene(scene_path))
+            }
+        }
+        (None, Some(log_path)) => {
+            if let Some(scene_path) = selector.realtime_scene_path.take() {
+                Some(ModeSelection::RealtimeTracking {
+                    scene_path,
+                    log_path,
+                })
+            } else if let Some(scene_path) = selector.logvis_scene_path.take() {
+                Some(ModeSelection::LogVisualization {
+                    scene_path,
+                    log_path,
+                })
+            } else {
+                selector.logvis_log_path = Some(log_path);
+                None
+            }
+        }
+        (None, None) => None,
+    }
+}
+
+/// Paths of the files dropped onto the window this frame.
+pub fn dropped_paths(ctx: &egui::Context) -> Vec<String> {
+    ctx.input(|input| {
+        input
+            .raw
+            .dropped_files
+            .iter()
+            .filter_map(|file| file.path.as_ref())
+            .map(|path| path.to_string_lossy().to_string())
+            .collect()
+    })
+}
+
+/// Dim the window while files are dragged over it.
+pub fn render_drop_overlay(ctx: &egui::Context) {
+    if ctx.input(|input| input.raw.hovered_files.is_empty()) {
+        return;
+    }
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("file_drop_overlay"),
+    ));
+    let screen = ctx.screen_rect();
+    painter.rect_filled(screen, 0.0, Color32::from_black_alpha(180));
+    painter.text(
+        screen.center(),
+        egui::Align2::CENTER_CENTER,
+        "Drop a scene (.json) and/or log file",
+        egui::FontId::proportional(24.0),
+        Color32::WHITE,
+    );
+}
+
+/// Render the recent session list.
+///
+/// # Returns
+///
+/// The selection of the clicked entry, if any.
+pub fn render_list(ui: &mut egui::Ui, recent: &[RecentEntry]) -> Option<ModeSelection> {
+    let mut selection = None;
+    ui.label(egui::RichText::new("Recent").size(16.0));
+    if recent.is_empty() {
+        ui.label("No recent files. Tip: drop a scene or log file onto the window.");
+        return None;
+    }
+    for entry in recent {
+        let available = entry.files_exist();
+        let hover = match &entry.log_path {
+            Some(log) => format!("{}\n{}", entry.scene_path, log),
+            None => entry.scene_path.clone(),
+        };
+        let response = ui
+            .add_enabled(available, egui::Button::new(entry.label()).frame(false))
+            .on_hover_text(hover)
+            .on_disabled_hover_text("File no longer exists");
+        if response.clicked() {
+            selection = Some(entry.selection());
+        }
+    }
+    selection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_and_recent_list() {
+        let mut selector = ModeSelector::new();
+        assert_eq!(
+            resolve_drop(&mut selector, &["a/scene.JSON".to_string()]),
+            Some(ModeSelection::SimulationScene("a/scene.JSON".to_string()))
+        );
+        assert_eq!(
+            resolve_drop(&mut selector, &["run.log.gz".to_string()]),
+            None
+        );
+        assert_eq!(
+            resolve_drop(&mut selector, &["scene.json".to_string()]),
+            Some(ModeSelection::LogVisualization {
+                scene_path: "scene.json".to_string(),
+                log_path: "run.log.gz".to_string(),
+            })
+        );
+
+        let mut recent = Vec::new();
+        for i in 0..12 {
+            remember(
+                &mut recent,
+                RecentEntry {
+                    mode: RecentMode::Simulation,
+                    scene_path: format!("scene{}.json", i % 11),
+                    log_path: None,
+                },
+            );
+        }
+        assert_eq!(recent.len(), MAX_RECENT);
+        assert_eq!(recent[0].scene_path, "scene0.json");
+        assert_eq!(recent[1].scene_path, "scene10.json");
+    }
+}
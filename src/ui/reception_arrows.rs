@@ -0,0 +1,145 @@
+//! # Reception Arrows
+//!
+//! In the analyzer modes every logged packet reception that names its sender
+//! (`*TM2*` and its JSON equivalent) draws a short-lived arrow from the sender to
+//! the receiver in the message type's color, next to the sender's transmit pulse,
+//! so the reception topology observed in the field is visible on the map. Arrows
+//! fade out over `NODE_RADIO_TRANSFER_INDICATOR_DURATION` like the pulses and
+//! follow the animation type filter. At most `MAX_ARROWS` are kept; the oldest
+//! are dropped first under heavy traffic. In Simulation mode only the links shown
+//! from the event search (see `event_search`) are drawn.
+
+use eframe::egui;
+use embassy_time::Instant;
+use std::collections::{HashMap, VecDeque};
+
+use super::AppState;
+use super::app_state::{NODE_RADIO_TRANSFER_INDICATOR_DURATION, color_for_message_type};
+use super::map::MapTransform;
+
+/// Maximum number of arrows kept at once.
+const MAX_ARROWS: usize = 500;
+
+/// Pixels left free around the receiver so the arrow head stays visible next to the node.
+const RECEIVER_GAP: f32 = 8.0;
+
+/// A reception from `sender` at `receiver`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ReceptionArrow {
+    sender: u32,
+    receiver: u32,
+    message_type: u8,
+    expiry: Instant,
+}
+
+/// Receptions whose arrows are still shown.
+#[derive(Debug, Clone, Default)]
+pub struct ReceptionArrows {
+    arrows: VecDeque<ReceptionArrow>,
+}
+
+impl ReceptionArrows {
+    /// Record a reception at `now`.
+    pub fn record(&mut self, sender: u32, receiver: u32, message_type: u8, now: Instant) {
+        self.expire(now);
+        // A repeated reception of the same link and type restarts its arrow
+        self.arrows.retain(|arrow| {
+            (arrow.sender, arrow.receiver, arrow.message_type) != (sender, receiver, message_type)
+        });
+        if self.arrows.len() >= MAX_ARROWS {
+            self.arrows.pop_front();
+        }
+        self.arrows.push_back(ReceptionArrow {
+            sender,
+            receiver,
+            message_type,
+            expiry: now + NODE_RADIO_TRANSFER_INDICATOR_DURATION,
+        });
+    }
+
+    /// Drop the arrows that faded out.
+    fn expire(&mut self, now: Instant) {
+        self.arrows.retain(|arrow| arrow.expiry > now);
+    }
+
+    /// Forget all arrows (scene change).
+    pub fn clear(&mut self) {
+        self.arrows.clear();
+    }
+}
+
+/// Draw the reception arrows.
+///
+/// # Parameters
+///
+/// * `painter` - Painter of the map
+/// * `rect` - Screen rectangle of the map
+/// * `state` - Application state (arrows, nodes, hidden animation types)
+pub fn draw(painter: &egui::Painter, rect: egui::Rect, state: &AppState) {
+    if state.reception_arrows.arrows.is_empty() {
+        return;
+    }
+    let now = Instant::now();
+    let transform = MapTransform::new(state.world, rect);
+    let positions: HashMap<u32, egui::Pos2> = state
+        .nodes
+        .iter()
+        .map(|node| {
+            (
+                node.node_id,
+                transform.world_to_screen(node.position.x, node.position.y),
+            )
+        })
+        .collect();
+
+    for arrow in &state.reception_arrows.arrows {
+        if arrow.expiry <= now || state.hidden_animation_types.contains(&arrow.message_type) {
+            continue;
+        }
+        let (Some(start), Some(end)) = (
+            positions.get(&arrow.sender).copied(),
+            positions.get(&arrow.receiver).copied(),
+        ) else {
+            continue;
+        };
+        let vector = end - start;
+        let length = vector.length();
+        if length <= RECEIVER_GAP {
+            continue;
+        }
+        let remaining = arrow.expiry - now;
+        let alpha = (remaining.as_millis() as f32
+            / NODE_RADIO_TRANSFER_INDICATOR_DURATION.as_millis() as f32)
+            .clamp(0.0, 1.0);
+        let color = color_for_message_type(arrow.message_type, alpha);
+        painter.arrow(
+            start,
+            vector * ((length - RECEIVER_GAP) / length),
+            egui::Stroke::new(1.5, color),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_receptions_restart_their_arrow_and_old_ones_expire() {
+        let mut arrows = ReceptionArrows::default();
+        arrows.record(1, 2, 6, Instant::from_millis(0));
+        arrows.record(1, 3, 6, Instant::from_millis(100));
+        arrows.record(1, 2, 6, Instant::from_millis(500));
+        assert_eq!(arrows.arrows.len(), 2);
+        assert_eq!(arrows.arrows.back().map(|a| a.receiver), Some(2));
+
+        // The 1 -> 3 arrow faded out, the restarted 1 -> 2 arrow is still shown
+        arrows.record(4, 5, 6, Instant::from_millis(1200));
+        let links: Vec<(u32, u32)> = arrows
+            .arrows
+            .iter()
+            .map(|a| (a.sender, a.receiver))
+            .collect();
+        assert_eq!(links, vec![(1, 2), (4, 5)]);
+    }
+}
@@ -0,0 +1,145 @@
+//! # UI Refresh Saturation Diagnostics
+//!
+//! Window explaining why animations are missing or counters stale: it opens by
+//! itself once `SATURATION_THRESHOLD` more updates were shed by the refresh queue
+//! since it was last closed, and can be opened from the "Shed UI updates" counter in
+//! the System Metrics panel. It lists the shed updates per message variant and the
+//! pending queue length, and suggests lowering the simulation speed, enabling
+//! Auto speed or coalescing pending snapshots (see `refresh_queue`).
+
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+
+use super::{AppState, OperatingMode, UICommand};
+
+/// Shed updates since the window was last closed that open it automatically.
+const SATURATION_THRESHOLD: u64 = 500;
+
+/// Visibility of the diagnostics window.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshDiagnosticsState {
+    /// Whether the window is open.
+    pub open: bool,
+    /// Shed count when the window was last closed.
+    acknowledged: u64,
+}
+
+impl RefreshDiagnosticsState {
+    /// Whether enough updates were shed since the last close to open the window.
+    fn saturated(&self, shed_count: u64) -> bool {
+        shed_count.saturating_sub(self.acknowledged) >= SATURATION_THRESHOLD
+    }
+}
+
+/// Open the window when the queue is saturated and render it (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (diagnostics window, speed controls)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    let shed_count = state.ui_refresh_rx.shed_count();
+    if !state.refresh_diagnostics.open && state.refresh_diagnostics.saturated(shed_count) {
+        log::warn!(
+            "UI refresh queue saturated: {} updates shed, opening diagnostics",
+            shed_count
+        );
+        state.refresh_diagnostics.open = true;
+    }
+    if !state.refresh_diagnostics.open {
+        return;
+    }
+
+    let rx = state.ui_refresh_rx;
+    let is_simulation = state.operating_mode == OperatingMode::Simulation;
+    let mut open = true;
+    let mut lower_speed = false;
+    let mut enable_auto_speed = false;
+    egui::Window::new("UI updates shed")
+        .open(&mut open)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "The UI could not keep up with the backend: {} updates were dropped ({} pending). \
+                 Missing transmission animations and stale counters are caused by these drops.",
+                shed_count,
+                rx.pending()
+            ));
+            ui.separator();
+            TableBuilder::new(ui)
+                .striped(true)
+                .column(Column::auto().at_least(200.0))
+                .column(Column::remainder())
+                .max_scroll_height(200.0)
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        ui.strong("Message");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Shed");
+                    });
+                })
+                .body(|mut body| {
+                    for (name, count) in rx.shed_by_variant() {
+                        body.row(18.0, |mut row| {
+                            row.col(|ui| {
+                                ui.label(name);
+                            });
+                            row.col(|ui| {
+                                ui.label(count.to_string());
+                            });
+                        });
+                    }
+                });
+            ui.separator();
+            ui.label("Suggestions:");
+            if is_simulation {
+                ui.horizontal(|ui| {
+                    lower_speed = ui.button("Halve the speed").clicked();
+                    enable_auto_speed = ui
+                        .button("Enable Auto speed")
+                        .on_hover_text("Let the simulation speed follow the available CPU")
+                        .clicked();
+                });
+            }
+            let mut coalescing = rx.coalescing();
+            if ui
+                .checkbox(&mut coalescing, "Coalesce snapshots")
+                .on_hover_text(
+                    "A counter or statistics snapshot replaces a pending one of the same kind instead of queueing behind it",
+                )
+                .changed()
+            {
+                rx.set_coalescing(coalescing);
+            }
+        });
+
+    if lower_speed {
+        state.speed_percent = (state.speed_percent / 2).max(20);
+        crate::time_driver::set_simulation_speed_percent(state.speed_percent);
+    }
+    if enable_auto_speed {
+        state.auto_speed_enabled = true;
+        let _ = state.ui_command_tx.try_send(UICommand::SetAutoSpeed(true));
+    }
+    if !open {
+        state.refresh_diagnostics.open = false;
+        state.refresh_diagnostics.acknowledged = shed_count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_reopens_only_after_new_drops() {
+        let mut diagnostics = RefreshDiagnosticsState::default();
+        assert!(!diagnostics.saturated(SATURATION_THRESHOLD - 1));
+        assert!(diagnostics.saturated(SATURATION_THRESHOLD));
+
+        diagnostics.acknowledged = SATURATION_THRESHOLD;
+        assert!(!diagnostics.saturated(SATURATION_THRESHOLD + 10));
+        assert!(diagnostics.saturated(2 * SATURATION_THRESHOLD));
+    }
+}
@@ -0,0 +1,459 @@
+//! # Prioritized UI Refresh Queue
+//!
+//! Network → UI channel that sheds load by priority instead of silently dropping
+//! whatever does not fit. Every `UIRefreshState` has a priority:
+//!
+//! - **Critical** (alerts, scene setup, mode changes, measurement milestones, replies
+//!   to UI requests) is queued above the capacity, up to `CRITICAL_LIMIT`; only a UI
+//!   that stopped reading entirely makes it shed them (an error is logged).
+//! - **Normal** (counter and statistics snapshots, time and delay updates, node
+//!   activity) is dropped only when the queue is full; the next snapshot supersedes it.
+//! - **Cosmetic** (transmission animations, link quality and delivery observations)
+//!   is shed once the queue is filled above `COSMETIC_SHED_THRESHOLD`.
+//!
+//! Messages keep their order. Senders never block: `send` queues (or sheds) and
+//! returns immediately, so a slow UI frame cannot stall the simulation. The number of
+//! shed messages is shown in the top panel and a warning is logged when shedding starts.
+//!
+//! Shed messages are counted per variant for the saturation diagnostics window. With
+//! coalescing enabled, a normal-priority snapshot replaces a pending snapshot of the
+//! same variant instead of being queued behind it, so the UI catches up faster.
+//!
+//! Node activity feeds the liveness tracking, so it must not be lost: a
+//! `NodeActivity` batch is always merged into a pending one (keeping the latest
+//! timestamp per node ID), even when the queue is full. Batches are never merged
+//! across a critical message, so activity stays on its side of a session change.
+
+use embassy_time::Instant;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+
+use super::UIRefreshState;
+use crate::UI_REFRESH_CHANNEL_SIZE;
+
+/// Queue fill level above which cosmetic updates are shed.
+const COSMETIC_SHED_THRESHOLD: usize = UI_REFRESH_CHANNEL_SIZE * 3 / 4;
+/// Queue fill level above which even critical messages are shed, so a stalled UI
+/// cannot grow the queue without bound.
+const CRITICAL_LIMIT: usize = UI_REFRESH_CHANNEL_SIZE * 20;
+
+/// Delivery priority of a UI refresh message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RefreshPriority {
+    Critical,
+    Normal,
+    Cosmetic,
+}
+
+/// Priority of a refresh message.
+fn priority(state: &UIRefreshState) -> RefreshPriority {
+    match state {
+        UIRefreshState::NodeSentRadioMessage(..)
+        | UIRefreshState::LinkQualityObserved(..)
+        | UIRefreshState::PacketDelivered(..) => RefreshPriority::Cosmetic,
+        UIRefreshState::NodeActivity(_)
+        | UIRefreshState::RadioMessagesCountUpdated(..)
+        | UIRefreshState::SimulationDelayWarningChanged(_)
+        | UIRefreshState::SimulationDelayUpdated(_)
+        | UIRefreshState::SimulationSpeedChanged(_)
+        | UIRefreshState::AnalyzerDelay(_)
+        | UIRefreshState::TimeUpdated(_)
+        | UIRefreshState::NodeStatisticsUpdated(_)
+        | UIRefreshState::BackhaulStatisticsUpdated(_)
+        | UIRefreshState::MemoryUsageUpdated(_)
+        | UIRefreshState::NodeHealthUpdated(_)
+        | UIRefreshState::StageTimingsUpdated(_)
+        | UIRefreshState::SpeedTargetUpdated(_)
+        | UIRefreshState::SceneLoadProgress(..) => RefreshPriority::Normal,
+        UIRefreshState::Alert(_)
+        | UIRefreshState::NodeUpdated(_)
+        | UIRefreshState::NodesUpdated(_)
+        | UIRefreshState::ObstaclesUpdated(_)
+        | UIRefreshState::AnnotationsUpdated(_)
+        | UIRefreshState::NodeInfo(_)
+        | UIRefreshState::NodeReachedInMeasurement(..)
+        | UIRefreshState::SendMessageInMeasurement(..)
+        | UIRefreshState::PoorAndExcellentLimits(..)
+        | UIRefreshState::SceneDimensionsUpdated(_)
+        | UIRefreshState::BackgroundImageUpdated(_)
+        | UIRefreshState::VisualizationEnded
+        | UIRefreshState::ModeChanged(_)
+        | UIRefreshState::SessionEnded
+        | UIRefreshState::ControlAvailable(_)
+        | UIRefreshState::ConnectionMatrixUpdated(_)
+        | UIRefreshState::NodeProtocolStateUpdated(..)
+        | UIRefreshState::EventQueryAnswered(_)
+        | UIRefreshState::LinkHistogramsUpdated(..)
+        | UIRefreshState::UnreachedDiagnosed(..)
+        | UIRefreshState::MeasurementStarted(..)
+        | UIRefreshState::NodeStateAt(_)
+        | UIRefreshState::SimulationStopped(_)
+        | UIRefreshState::SceneLoadEnded
+        | UIRefreshState::SignalProbed(_)
+        | UIRefreshState::AlertRaised(_) => RefreshPriority::Critical,
+    }
+}
+
+/// Name of a message variant for the shedding diagnostics.
+fn variant_name(state: &UIRefreshState) -> &'static str {
+    match state {
+        UIRefreshState::Alert(_) => "Alert",
+        UIRefreshState::NodeUpdated(_) => "NodeUpdated",
+        UIRefreshState::NodesUpdated(_) => "NodesUpdated",
+        UIRefreshState::ObstaclesUpdated(_) => "ObstaclesUpdated",
+        UIRefreshState::AnnotationsUpdated(_) => "AnnotationsUpdated",
+        UIRefreshState::NodeInfo(_) => "NodeInfo",
+        UIRefreshState::NodeReachedInMeasurement(..) => "NodeReachedInMeasurement",
+        UIRefreshState::SendMessageInMeasurement(..) => "SendMessageInMeasurement",
+        UIRefreshState::PoorAndExcellentLimits(..) => "PoorAndExcellentLimits",
+        UIRefreshState::SceneDimensionsUpdated(_) => "SceneDimensionsUpdated",
+        UIRefreshState::BackgroundImageUpdated(_) => "BackgroundImageUpdated",
+        UIRefreshState::VisualizationEnded => "VisualizationEnded",
+        UIRefreshState::ModeChanged(_) => "ModeChanged",
+        UIRefreshState::SessionEnded => "SessionEnded",
+        UIRefreshState::ControlAvailable(_) => "ControlAvailable",
+        UIRefreshState::ConnectionMatrixUpdated(_) => "ConnectionMatrixUpdated",
+        UIRefreshState::NodeProtocolStateUpdated(..) => "NodeProtocolStateUpdated",
+        UIRefreshState::EventQueryAnswered(_) => "EventQueryAnswered",
+        UIRefreshState::LinkHistogramsUpdated(..) => "LinkHistogramsUpdated",
+        UIRefreshState::UnreachedDiagnosed(..) => "UnreachedDiagnosed",
+        UIRefreshState::MeasurementStarted(..) => "MeasurementStarted",
+        UIRefreshState::NodeStateAt(_) => "NodeStateAt",
+        UIRefreshState::SimulationStopped(_) => "SimulationStopped",
+        UIRefreshState::SceneLoadEnded => "SceneLoadEnded",
+        UIRefreshState::SignalProbed(_) => "SignalProbed",
+        UIRefreshState::AlertRaised(_) => "AlertRaised",
+        UIRefreshState::NodeSentRadioMessage(..) => "NodeSentRadioMessage",
+        UIRefreshState::LinkQualityObserved(..) => "LinkQualityObserved",
+        UIRefreshState::PacketDelivered(..) => "PacketDelivered",
+        UIRefreshState::NodeActivity(_) => "NodeActivity",
+        UIRefreshState::RadioMessagesCountUpdated(..) => "RadioMessagesCountUpdated",
+        UIRefreshState::SimulationDelayWarningChanged(_) => "SimulationDelayWarningChanged",
+        UIRefreshState::SimulationDelayUpdated(_) => "SimulationDelayUpdated",
+        UIRefreshState::SimulationSpeedChanged(_) => "SimulationSpeedChanged",
+        UIRefreshState::AnalyzerDelay(_) => "AnalyzerDelay",
+        UIRefreshState::TimeUpdated(_) => "TimeUpdated",
+        UIRefreshState::NodeStatisticsUpdated(_) => "NodeStatisticsUpdated",
+        UIRefreshState::BackhaulStatisticsUpdated(_) => "BackhaulStatisticsUpdated",
+        UIRefreshState::MemoryUsageUpdated(_) => "MemoryUsageUpdated",
+        UIRefreshState::NodeHealthUpdated(_) => "NodeHealthUpdated",
+        UIRefreshState::StageTimingsUpdated(_) => "StageTimingsUpdated",
+        UIRefreshState::SpeedTargetUpdated(_) => "SpeedTargetUpdated",
+        UIRefreshState::SceneLoadProgress(..) => "SceneLoadProgress",
+    }
+}
+
+/// Merge a node activity batch into a pending one, keeping the latest timestamp per node.
+fn merge_activity(pending: &mut Vec<(u32, Instant)>, activity: Vec<(u32, Instant)>) {
+    let mut index: HashMap<u32, usize> = pending
+        .iter()
+        .enumerate()
+        .map(|(i, (node_id, _))| (*node_id, i))
+        .collect();
+    for (node_id, timestamp) in activity {
+        match index.get(&node_id) {
+            Some(&i) => pending[i].1 = pending[i].1.max(timestamp),
+            None => {
+                index.insert(node_id, pending.len());
+                pending.push((node_id, timestamp));
+            }
+        }
+    }
+}
+
+/// Error returned by `try_send` when a message was shed.
+#[derive(Debug)]
+pub struct Shed;
+
+/// Error returned by `try_receive` when the queue is empty.
+#[derive(Debug)]
+pub struct Empty;
+
+struct QueueState {
+    messages: VecDeque<UIRefreshState>,
+    shed_count: u64,
+    /// Shed messages per variant name.
+    shed_by_variant: BTreeMap<&'static str, u64>,
+    shedding: bool,
+    /// Replace pending normal-priority snapshots of the same variant.
+    coalescing: bool,
+}
+
+/// Prioritized queue for sending UI state updates from the network task to the UI.
+pub struct UIRefreshQueue {
+    state: Mutex<QueueState>,
+}
+
+impl UIRefreshQueue {
+    /// Create an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                messages: VecDeque::new(),
+                shed_count: 0,
+                shed_by_variant: BTreeMap::new(),
+                shedding: false,
+                coalescing: false,
+            }),
+        }
+    }
+
+    /// Sender side of the queue.
+    pub fn sender(&'static self) -> UIRefreshQueueSender {
+        UIRefreshQueueSender { queue: self }
+    }
+
+    /// Receiver side of the queue.
+    pub fn receiver(&'static self) -> UIRefreshQueueReceiver {
+        UIRefreshQueueReceiver { queue: self }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue a message according to its priority.
+    fn push(&self, mut message: UIRefreshState) -> Result<(), Shed> {
+        let mut state = self.lock();
+        if let UIRefreshState::NodeActivity(activity) = &mut message {
+            let pending = state
+                .messages
+                .iter_mut()
+                .rev()
+                .take_while(|pending| priority(pending) != RefreshPriority::Critical)
+                .find_map(|pending| match pending {
+                    UIRefreshState::NodeActivity(pending) => Some(pending),
+                    _ => None,
+                });
+            if let Some(pending) = pending {
+                merge_activity(pending, std::mem::take(activity));
+                return Ok(());
+            }
+        }
+        let priority = priority(&message);
+        if state.coalescing && priority == RefreshPriority::Normal {
+            let variant = std::mem::discriminant(&message);
+            if let Some(pending) = state
+                .messages
+                .iter_mut()
+                .find(|pending| std::mem::discriminant(*pending) == variant)
+            {
+                *pending = message;
+                return Ok(());
+            }
+        }
+        let limit = match priority {
+            RefreshPriority::Critical => CRITICAL_LIMIT,
+            RefreshPriority::Normal => UI_REFRESH_CHANNEL_SIZE,
+            RefreshPriority::Cosmetic => COSMETIC_SHED_THRESHOLD,
+        };
+        if state.messages.len() >= limit {
+            state.shed_count += 1;
+            *state
+                .shed_by_variant
+                .entry(variant_name(&message))
+                .or_default() += 1;
+            if priority == RefreshPriority::Critical {
+                log::error!(
+                    "UI refresh queue is full ({} pending), dropping {}",
+                    state.messages.len(),
+                    variant_name(&message)
+                );
+            } else if !state.shedding {
+                state.shedding = true;
+                log::warn!(
+                    "UI refresh queue is behind ({} pending), shedding low-priority updates",
+                    state.messages.len()
+                );
+            }
+            return Err(Shed);
+        }
+        if state.messages.len() < COSMETIC_SHED_THRESHOLD {
+            state.shedding = false;
+        }
+        state.messages.push_back(message);
+        Ok(())
+    }
+}
+
+impl Default for UIRefreshQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sender side of the UI refresh queue.
+#[derive(Clone, Copy)]
+pub struct UIRefreshQueueSender {
+    queue: &'static UIRefreshQueue,
+}
+
+impl UIRefreshQueueSender {
+    /// Queue a message, ignoring whether it was shed; never blocks.
+    pub fn send(&self, message: UIRefreshState) {
+        let _ = self.queue.push(message);
+    }
+
+    /// Queue a message, returning `Err(Shed)` if it was dropped under load.
+    pub fn try_send(&self, message: UIRefreshState) -> Result<(), Shed> {
+        self.queue.push(message)
+    }
+}
+
+/// Receiver side of the UI refresh queue.
+#[derive(Clone, Copy)]
+pub struct UIRefreshQueueReceiver {
+    queue: &'static UIRefreshQueue,
+}
+
+impl UIRefreshQueueReceiver {
+    /// Take the oldest queued message.
+    pub fn try_receive(&self) -> Result<UIRefreshState, Empty> {
+        self.queue.lock().messages.pop_front().ok_or(Empty)
+    }
+
+    /// Number of messages shed since the start of the application.
+    pub fn shed_count(&self) -> u64 {
+        self.queue.lock().shed_count
+    }
+
+    /// Shed messages per variant name since the start of the application.
+    pub fn shed_by_variant(&self) -> Vec<(&'static str, u64)> {
+        let state = self.queue.lock();
+        state
+            .shed_by_variant
+            .iter()
+            .map(|(name, count)| (*name, *count))
+            .collect()
+    }
+
+    /// Number of messages waiting for the UI.
+    pub fn pending(&self) -> usize {
+        self.queue.lock().messages.len()
+    }
+
+    /// Whether pending snapshots are replaced by newer ones of the same variant.
+    pub fn coalescing(&self) -> bool {
+        self.queue.lock().coalescing
+    }
+
+    /// Enable or disable coalescing of normal-priority snapshots.
+    pub fn set_coalescing(&self, enabled: bool) {
+        self.queue.lock().coalescing = enabled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosmetic_updates_are_shed_before_critical_ones() {
+        let queue: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+        let tx = queue.sender();
+        let rx = queue.receiver();
+
+        for _ in 0..COSMETIC_SHED_THRESHOLD {
+            assert!(
+                tx.try_send(UIRefreshState::NodeSentRadioMessage(1, 6, 100, 20))
+                    .is_ok()
+            );
+        }
+        assert!(
+            tx.try_send(UIRefreshState::NodeSentRadioMessage(1, 6, 100, 20))
+                .is_err()
+        );
+        for _ in COSMETIC_SHED_THRESHOLD..UI_REFRESH_CHANNEL_SIZE {
+            assert!(
+                tx.try_send(UIRefreshState::SimulationSpeedChanged(100))
+                    .is_ok()
+            );
+        }
+        assert!(
+            tx.try_send(UIRefreshState::SimulationSpeedChanged(100))
+                .is_err()
+        );
+        assert!(
+            tx.try_send(UIRefreshState::Alert("full".to_string()))
+                .is_ok()
+        );
+        assert_eq!(rx.shed_count(), 2);
+
+        let mut last = None;
+        while let Ok(message) = rx.try_receive() {
+            last = Some(message);
+        }
+        assert!(matches!(last, Some(UIRefreshState::Alert(_))));
+        assert_eq!(
+            rx.shed_by_variant(),
+            vec![("NodeSentRadioMessage", 1), ("SimulationSpeedChanged", 1)]
+        );
+    }
+
+    #[test]
+    fn test_critical_messages_are_bounded() {
+        let queue: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+        let tx = queue.sender();
+        let rx = queue.receiver();
+
+        for _ in 0..CRITICAL_LIMIT {
+            tx.send(UIRefreshState::SessionEnded);
+        }
+        assert_eq!(rx.pending(), CRITICAL_LIMIT);
+        assert!(
+            tx.try_send(UIRefreshState::Alert("lost".to_string()))
+                .is_err()
+        );
+        assert_eq!(rx.shed_by_variant(), vec![("Alert", 1)]);
+    }
+
+    #[test]
+    fn test_coalescing_replaces_pending_snapshots_of_the_same_variant() {
+        let queue: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+        let tx = queue.sender();
+        let rx = queue.receiver();
+        rx.set_coalescing(true);
+
+        tx.try_send(UIRefreshState::SimulationSpeedChanged(100))
+            .unwrap();
+        tx.try_send(UIRefreshState::NodeSentRadioMessage(1, 6, 100, 20))
+            .unwrap();
+        tx.try_send(UIRefreshState::NodeSentRadioMessage(1, 6, 100, 20))
+            .unwrap();
+        tx.try_send(UIRefreshState::SimulationSpeedChanged(150))
+            .unwrap();
+        assert_eq!(rx.pending(), 3);
+        assert!(matches!(
+            rx.try_receive(),
+            Ok(UIRefreshState::SimulationSpeedChanged(150))
+        ));
+    }
+
+    #[test]
+    fn test_saturated_queue_keeps_the_latest_activity_per_node() {
+        let queue: &'static UIRefreshQueue = Box::leak(Box::new(UIRefreshQueue::new()));
+        let tx = queue.sender();
+        let rx = queue.receiver();
+        let at = Instant::from_secs;
+
+        tx.try_send(UIRefreshState::NodeActivity(vec![(1, at(10)), (2, at(10))]))
+            .unwrap();
+        for _ in 1..UI_REFRESH_CHANNEL_SIZE {
+            tx.try_send(UIRefreshState::TimeUpdated(at(10))).unwrap();
+        }
+        assert!(tx.try_send(UIRefreshState::TimeUpdated(at(11))).is_err());
+
+        // The full queue still takes activity: it is merged into the pending batch
+        tx.try_send(UIRefreshState::NodeActivity(vec![(1, at(12)), (3, at(12))]))
+            .unwrap();
+        tx.try_send(UIRefreshState::NodeActivity(vec![(2, at(5))]))
+            .unwrap();
+        assert_eq!(rx.pending(), UI_REFRESH_CHANNEL_SIZE);
+        let Ok(UIRefreshState::NodeActivity(mut activity)) = rx.try_receive() else {
+            panic!("the activity batch must be first");
+        };
+        activity.sort_by_key(|(node_id, _)| *node_id);
+        assert_eq!(activity, vec![(1, at(12)), (2, at(10)), (3, at(12))]);
+        assert_eq!(rx.shed_by_variant(), vec![("TimeUpdated", 1)]);
+    }
+}
@@ -0,0 +1,388 @@
+//! # Region-of-Interest Statistics
+//!
+//! Aggregated statistics of the nodes inside rectangles drawn on the map, to
+//! compare districts of a scene. With "Draw region" enabled a primary-button drag
+//! on the map adds a rectangle; the "Regions" window lists every region side by
+//! side: node count, summed TX/RX/collision counts, average link quality and the
+//! reach of the displayed measurement (reached nodes, mean and longest reach time).
+//! Regions can be renamed and removed, and the table exported as CSV (with a
+//! provenance sidecar).
+
+use eframe::egui;
+use egui::Color32;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use super::app_state::MeasurementProgress;
+use super::map::MapTransform;
+use super::{AppState, NodeUIState};
+use crate::common::node_statistics::NodeStatistics;
+use crate::common::provenance;
+
+/// Drags shorter than this many pixels on either axis do not add a region.
+const MIN_REGION_PIXELS: f32 = 5.0;
+
+/// A named rectangle in world units.
+#[derive(Debug, Clone, PartialEq)]
+struct Region {
+    name: String,
+    min: (f64, f64),
+    max: (f64, f64),
+}
+
+impl Region {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+}
+
+/// Aggregated statistics of the nodes in a region.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct RegionSummary {
+    nodes: usize,
+    tx: u64,
+    rx: u64,
+    collisions: u64,
+    /// Average link quality over all received packets.
+    average_link_quality: Option<f64>,
+    /// Nodes reached by the displayed measurement.
+    reached: usize,
+    /// Mean and longest reach time (seconds) of the reached nodes.
+    reach_time: Option<(f64, f64)>,
+}
+
+/// Drawn regions and window visibility.
+#[derive(Debug, Clone, Default)]
+pub struct RegionStatsState {
+    /// Whether primary-button drags on the map draw a region.
+    pub drawing: bool,
+    /// Whether the window is open.
+    pub open: bool,
+    regions: Vec<Region>,
+    /// Screen position where the region drag in progress started.
+    drag_start: Option<egui::Pos2>,
+    /// Number given to the next region's default name.
+    next_number: usize,
+}
+
+/// Aggregate the statistics of the nodes inside `region`.
+fn summarize(
+    region: &Region,
+    nodes: &[NodeUIState],
+    statistics: &HashMap<u32, NodeStatistics>,
+    measurement: Option<&MeasurementProgress>,
+) -> RegionSummary {
+    let mut summary = RegionSummary::default();
+    let mut link_quality_sum = 0;
+    let mut reach_times = Vec::new();
+    for node in nodes
+        .iter()
+        .filter(|node| region.contains(node.position.x, node.position.y))
+    {
+        summary.nodes += 1;
+        if let Some(s) = statistics.get(&node.node_id) {
+            summary.tx += s.tx_packets;
+            summary.rx += s.rx_packets;
+            summary.collisions += s.collisions;
+            link_quality_sum += s.link_quality_sum;
+        }
+        if let Some(time) = measurement.and_then(|m| m.reach_times.get(&node.node_id)) {
+            reach_times.push(*time);
+        }
+    }
+    if summary.rx > 0 {
+        summary.average_link_quality = Some(link_quality_sum as f64 / summary.rx as f64);
+    }
+    summary.reached = reach_times.len();
+    if !reach_times.is_empty() {
+        let mean = reach_times.iter().sum::<f64>() / reach_times.len() as f64;
+        let max = reach_times.iter().copied().fold(0.0, f64::max);
+        summary.reach_time = Some((mean, max));
+    }
+    summary
+}
+
+/// CSV with one row per region.
+fn regions_csv(rows: &[(&Region, RegionSummary)]) -> String {
+    let mut csv = String::from(
+        "region,min_x,min_y,max_x,max_y,nodes,tx,rx,collisions,average_link_quality,reached,mean_reach_time_s,max_reach_time_s\n",
+    );
+    for (region, summary) in rows {
+        let _ = writeln!(
+            csv,
+            "\"{}\",{},{},{},{},{},{},{},{},{},{},{},{}",
+            region.name.replace('"', "\"\""),
+            region.min.0,
+            region.min.1,
+            region.max.0,
+            region.max.1,
+            summary.nodes,
+            summary.tx,
+            summary.rx,
+            summary.collisions,
+            summary
+                .average_link_quality
+                .map_or(String::new(), |lq| format!("{:.2}", lq)),
+            summary.reached,
+            summary
+                .reach_time
+                .map_or(String::new(), |(mean, _)| format!("{:.3}", mean)),
+            summary
+                .reach_time
+                .map_or(String::new(), |(_, max)| format!("{:.3}", max)),
+        );
+    }
+    csv
+}
+
+/// Add a region from a finished drag and draw the regions (and the one being drawn).
+///
+/// # Parameters
+///
+/// * `response` - egui response from the map interaction area
+/// * `painter` - Map painter
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Mutable application state (regions)
+pub fn handle_map(
+    response: &egui::Response,
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    state: &mut AppState,
+) {
+    let transform = MapTransform::new(state.world, rect);
+    let regions = &mut state.region_stats;
+
+    if regions.drawing {
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            regions.drag_start = response.ctx.input(|i| i.pointer.press_origin());
+        }
+        if let (Some(start), Some(current)) = (regions.drag_start, response.hover_pos()) {
+            let drawn = egui::Rect::from_two_pos(start, current);
+            painter.rect_stroke(
+                drawn,
+                0.0,
+                egui::Stroke::new(1.5, Color32::LIGHT_BLUE),
+                egui::StrokeKind::Middle,
+            );
+            if response.drag_stopped() {
+                regions.drag_start = None;
+                if drawn.width() >= MIN_REGION_PIXELS && drawn.height() >= MIN_REGION_PIXELS {
+                    let (x1, y1) = transform.screen_to_world(drawn.min);
+                    let (x2, y2) = transform.screen_to_world(drawn.max);
+                    regions.next_number += 1;
+                    regions.regions.push(Region {
+                        name: format!("Region {}", regions.next_number),
+                        min: (x1.min(x2), y1.min(y2)),
+                        max: (x1.max(x2), y1.max(y2)),
+                    });
+                    regions.open = true;
+                }
+            }
+        }
+    } else {
+        regions.drag_start = None;
+    }
+
+    for region in &regions.regions {
+        let screen = egui::Rect::from_two_pos(
+            transform.world_to_screen(region.min.0, region.min.1),
+            transform.world_to_screen(region.max.0, region.max.1),
+        );
+        painter.rect(
+            screen,
+            0.0,
+            Color32::from_rgba_unmultiplied(100, 160, 255, 20),
+            egui::Stroke::new(1.0, Color32::LIGHT_BLUE),
+            egui::StrokeKind::Middle,
+        );
+        painter.text(
+            screen.left_top() + egui::vec2(3.0, 2.0),
+            egui::Align2::LEFT_TOP,
+            &region.name,
+            egui::FontId::proportional(11.0),
+            Color32::LIGHT_BLUE,
+        );
+    }
+}
+
+/// Render the region statistics window (if open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (regions, statistics, measurement)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.region_stats.open {
+        return;
+    }
+    let measurement = state.active_measurement().cloned();
+    let summaries: Vec<RegionSummary> = state
+        .region_stats
+        .regions
+        .iter()
+        .map(|region| {
+            summarize(
+                region,
+                &state.nodes,
+                &state.node_statistics,
+                measurement.as_ref(),
+            )
+        })
+        .collect();
+
+    let mut open = true;
+    let mut remove: Option<usize> = None;
+    let mut export = false;
+    let regions = &mut state.region_stats;
+    egui::Window::new("Regions")
+        .open(&mut open)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            if regions.regions.is_empty() {
+                ui.label("Enable \"Draw region\" and drag a rectangle on the map.");
+                return;
+            }
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                egui::Grid::new("region_stats_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        for (index, region) in regions.regions.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut region.name)
+                                        .desired_width(90.0),
+                                );
+                                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                        ui.end_row();
+
+                        let mut row = |label: &str, value: &dyn Fn(&RegionSummary) -> String| {
+                            ui.strong(label);
+                            for summary in &summaries {
+                                ui.label(value(summary));
+                            }
+                            ui.end_row();
+                        };
+                        row("Nodes", &|s| s.nodes.to_string());
+                        row("TX", &|s| s.tx.to_string());
+                        row("RX", &|s| s.rx.to_string());
+                        row("Collisions", &|s| s.collisions.to_string());
+                        row("Avg LQ", &|s| {
+                            s.average_link_quality
+                                .map_or("-".to_string(), |lq| format!("{:.1}", lq))
+                        });
+                        row("Reached", &|s| format!("{}/{}", s.reached, s.nodes));
+                        row("Reach time", &|s| {
+                            s.reach_time.map_or("-".to_string(), |(mean, max)| {
+                                format!("{:.1} / {:.1} s", mean, max)
+                            })
+                        });
+                    });
+            });
+            if measurement.is_none() {
+                ui.weak("Reach times appear once a measurement is displayed.");
+            }
+            ui.separator();
+            export = ui.button("Export CSV").clicked();
+        });
+
+    if let Some(index) = remove {
+        regions.regions.remove(index);
+    }
+    if !open {
+        regions.open = false;
+    }
+    if export {
+        let rows: Vec<(&Region, RegionSummary)> = regions.regions.iter().zip(summaries).collect();
+        export_csv(&regions_csv(&rows), &mut state.alert);
+    }
+}
+
+/// Ask for a path and write the region CSV with its provenance sidecar.
+fn export_csv(csv: &str, alert: &mut Option<String>) {
+    let file_name = format!(
+        "regions_{}.csv",
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("CSV", &["csv"])
+        .set_file_name(file_name)
+        .save_file()
+    else {
+        return;
+    };
+    let result = std::fs::write(&path, csv)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        .and_then(|_| provenance::write_sidecar(&path));
+    match result {
+        Ok(_) => log::info!("Exported region statistics to {}", path.display()),
+        Err(e) => {
+            log::error!("{}", e);
+            *alert = Some(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Point;
+
+    fn node(node_id: u32, x: f64, y: f64) -> NodeUIState {
+        NodeUIState {
+            node_id,
+            position: Point { x, y },
+            radio_strength: 100,
+            tx_power: None,
+            is_gateway: false,
+            disabled: false,
+        }
+    }
+
+    #[test]
+    fn test_summary_aggregates_only_nodes_inside_the_region() {
+        let region = Region {
+            name: "Old town".to_string(),
+            min: (0.0, 0.0),
+            max: (100.0, 100.0),
+        };
+        let nodes = vec![
+            node(1, 10.0, 10.0),
+            node(2, 90.0, 50.0),
+            node(3, 150.0, 50.0),
+        ];
+        let statistics = |tx, rx, link_quality_sum| NodeStatistics {
+            tx_packets: tx,
+            rx_packets: rx,
+            link_quality_sum,
+            ..Default::default()
+        };
+        let statistics = HashMap::from([
+            (1, statistics(5, 10, 100)),
+            (2, statistics(3, 30, 600)),
+            (3, statistics(50, 50, 50)),
+        ]);
+        let mut measurement = MeasurementProgress::new(1, embassy_time::Instant::from_secs(0));
+        measurement.reach_times.insert(2, 3.0);
+        measurement.reach_times.insert(3, 8.0);
+
+        let summary = summarize(&region, &nodes, &statistics, Some(&measurement));
+        assert_eq!(summary.nodes, 2);
+        assert_eq!((summary.tx, summary.rx), (8, 40));
+        assert_eq!(summary.average_link_quality, Some(17.5));
+        assert_eq!(summary.reached, 2);
+        assert_eq!(summary.reach_time, Some((1.5, 3.0)));
+
+        let csv = regions_csv(&[(&region, summary)]);
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("\"Old town\",0,0,100,100,2,8,40,0,17.50,2,1.500,3.000")
+        );
+    }
+}
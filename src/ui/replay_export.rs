@@ -0,0 +1,434 @@
+//! # Measurement Replay Export
+//!
+//! Shares a measurement with people who will not install the simulator: "Export
+//! replay" writes a single self-contained HTML file (no external scripts or
+//! styles) that replays the measurement in any browser. The page draws the
+//! obstacles and nodes, animates every captured transmission as a fading range
+//! ring in its message type's color and turns the nodes from grey to the reach
+//! time color (green = reached first, red = reached last) as the measurement
+//! reaches them. Play/pause, a playback speed and a time slider are provided.
+//!
+//! Transmissions come from a bounded capture of the session's transmit events
+//! (`TransmissionCapture`, the same events that drive the map's pulses), taken on
+//! the measurement clock; the replay covers the measurement start up to one second
+//! after its last reach or packet. Under heavy load the UI may shed transmit
+//! events (see `refresh_queue`), so the replay can miss some pulses; reach times
+//! are always complete.
+
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::path::Path;
+
+use super::app_state::{MeasurementProgress, color_for_message_type};
+use super::{AppState, NodeUIState};
+use crate::common::provenance;
+use crate::common::units::WorldTransform;
+use crate::simulation::Obstacle;
+use embassy_time::{Duration, Instant};
+
+/// Maximum number of captured transmissions; the oldest are dropped first.
+const MAX_CAPTURED_TRANSMISSIONS: usize = 200_000;
+
+/// Time replayed after the last reach or packet of the measurement.
+const REPLAY_TAIL: Duration = Duration::from_secs(1);
+
+/// Placeholder of the replay data in `REPLAY_TEMPLATE`.
+const DATA_PLACEHOLDER: &str = "__REPLAY_DATA__";
+
+/// A transmission seen by the UI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CapturedTransmission {
+    /// Measurement clock time of the transmission.
+    time: Instant,
+    node_id: u32,
+    message_type: u8,
+    /// Effective distance of the transmitter in meters.
+    distance: u32,
+}
+
+/// Bounded capture of the session's transmissions for the replay export.
+#[derive(Debug, Clone, Default)]
+pub struct TransmissionCapture {
+    entries: VecDeque<CapturedTransmission>,
+}
+
+impl TransmissionCapture {
+    /// Record a transmission at `time` (measurement clock).
+    pub fn record(&mut self, node_id: u32, message_type: u8, distance: u32, time: Instant) {
+        if self.entries.len() >= MAX_CAPTURED_TRANSMISSIONS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(CapturedTransmission {
+            time,
+            node_id,
+            message_type,
+            distance,
+        });
+    }
+
+    /// Forget the captured transmissions (scene change).
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Seconds from `start` to `time`.
+fn seconds_since(start: Instant, time: Instant) -> f64 {
+    time.saturating_duration_since(start).as_millis() as f64 / 1000.0
+}
+
+/// CSS color of a message type.
+fn css_color(message_type: u8) -> String {
+    let color = color_for_message_type(message_type, 1.0);
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Replay data embedded in the page.
+///
+/// Positions are normalized to the world bounds (0..1, top-left origin); distances
+/// are in meters, times in seconds since the measurement start.
+///
+/// # Parameters
+///
+/// * `world` - World bounds and scale of the scene
+/// * `obstacles` - Obstacles of the scene
+/// * `nodes` - Nodes of the scene
+/// * `measurement` - The replayed measurement
+/// * `capture` - Captured transmissions of the session
+fn replay_data(
+    world: WorldTransform,
+    obstacles: &[Obstacle],
+    nodes: &[NodeUIState],
+    measurement: &MeasurementProgress,
+    capture: &TransmissionCapture,
+) -> Value {
+    let start = measurement.start_time;
+    let last_reach = measurement
+        .reach_times
+        .values()
+        .copied()
+        .fold(0.0_f64, f64::max);
+    let end = start
+        + Duration::from_secs(measurement.total_time)
+            .max(Duration::from_millis((last_reach * 1000.0).round() as u64))
+        + REPLAY_TAIL;
+
+    let obstacles: Vec<Value> = obstacles
+        .iter()
+        .map(|obstacle| match obstacle {
+            Obstacle::Rectangle { position, .. } => {
+                let (x0, y0) = world.normalize(position.top_left.x, position.top_left.y);
+                let (x1, y1) = world.normalize(position.bottom_right.x, position.bottom_right.y);
+                json!({ "kind": "rect", "x0": x0, "y0": y0, "x1": x1, "y1": y1 })
+            }
+            Obstacle::Circle { position, .. } => {
+                let (x, y) = world.normalize(position.center.x, position.center.y);
+                json!({ "kind": "circle", "x": x, "y": y, "r": position.radius })
+            }
+        })
+        .collect();
+
+    let mut sorted_nodes: Vec<&NodeUIState> = nodes.iter().collect();
+    sorted_nodes.sort_by_key(|node| node.node_id);
+    let nodes: Vec<Value> = sorted_nodes
+        .into_iter()
+        .map(|node| {
+            let (x, y) = world.normalize(node.position.x, node.position.y);
+            json!({
+                "id": node.node_id,
+                "x": x,
+                "y": y,
+                "reach": measurement.reach_times.get(&node.node_id),
+            })
+        })
+        .collect();
+
+    // The page expects the transmissions in time order
+    let mut window: Vec<&CapturedTransmission> = capture
+        .entries
+        .iter()
+        .filter(|entry| entry.time >= start && entry.time <= end)
+        .collect();
+    window.sort_by_key(|entry| entry.time);
+    let mut message_types: Vec<u8> = Vec::new();
+    let transmissions: Vec<Value> = window
+        .into_iter()
+        .map(|entry| {
+            if !message_types.contains(&entry.message_type) {
+                message_types.push(entry.message_type);
+            }
+            json!([
+                seconds_since(start, entry.time),
+                entry.node_id,
+                entry.message_type,
+                entry.distance
+            ])
+        })
+        .collect();
+    let colors: serde_json::Map<String, Value> = message_types
+        .into_iter()
+        .map(|message_type| (message_type.to_string(), json!(css_color(message_type))))
+        .collect();
+
+    json!({
+        "title": format!(
+            "{} measurement from node #{}",
+            measurement.kind.label(),
+            measurement.origin_node_id
+        ),
+        "world": { "width_m": world.width_meters(), "height_m": world.height_meters() },
+        "duration": seconds_since(start, end),
+        "obstacles": obstacles,
+        "nodes": nodes,
+        "transmissions": transmissions,
+        "colors": colors,
+    })
+}
+
+/// The HTML page of the replay data.
+fn replay_html(data: &Value) -> String {
+    // "</" inside the inline script would end it early
+    let data = data.to_string().replace("</", "<\\/");
+    REPLAY_TEMPLATE.replace(DATA_PLACEHOLDER, &data)
+}
+
+/// Export the displayed measurement as a standalone HTML replay.
+///
+/// Opens a save dialog; failures are shown in the alert dialog.
+///
+/// # Parameters
+///
+/// * `state` - Mutable application state (measurement data, capture, alert)
+pub fn export_replay(state: &mut AppState) {
+    let Some(measurement) = state.active_measurement().cloned() else {
+        return;
+    };
+
+    let file_name = format!(
+        "measurement_{}_{}.html",
+        state.measurement_identifier,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("HTML page", &["html"])
+        .set_file_name(file_name)
+        .save_file()
+    else {
+        return;
+    };
+
+    let data = replay_data(
+        state.world,
+        &state.obstacles,
+        &state.nodes,
+        &measurement,
+        &state.transmission_capture,
+    );
+    match write_replay(&path, &data) {
+        Ok(()) => log::info!(
+            "Exported measurement {} replay to {}",
+            state.measurement_identifier,
+            path.display()
+        ),
+        Err(e) => {
+            log::error!("{}", e);
+            state.alert = Some(e);
+        }
+    }
+}
+
+/// Write the replay page and its provenance sidecar.
+fn write_replay(path: &Path, data: &Value) -> Result<(), String> {
+    std::fs::write(path, replay_html(data))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    provenance::write_sidecar(path)?;
+    Ok(())
+}
+
+/// Standalone replay page; `__REPLAY_DATA__` is replaced by the replay data.
+const REPLAY_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>MoonBlokz measurement replay</title>
+<style>
+  body { margin: 0; background: #14141e; color: #ddd; font: 14px sans-serif; }
+  header { padding: 8px 12px; display: flex; gap: 12px; align-items: center; flex-wrap: wrap; }
+  h1 { font-size: 16px; margin: 0 12px 0 0; }
+  #time { font-variant-numeric: tabular-nums; min-width: 9em; }
+  #slider { flex: 1; min-width: 200px; }
+  canvas { display: block; margin: 0 auto; }
+</style>
+</head>
+<body>
+<header>
+  <h1 id="title"></h1>
+  <button id="play">Pause</button>
+  <label>Speed <select id="speed">
+    <option value="0.25">0.25×</option><option value="0.5">0.5×</option>
+    <option value="1" selected>1×</option><option value="2">2×</option>
+    <option value="5">5×</option><option value="10">10×</option>
+  </select></label>
+  <input id="slider" type="range" min="0" max="1000" value="0">
+  <span id="time"></span>
+  <span id="reached"></span>
+</header>
+<canvas id="map"></canvas>
+<script>
+const DATA = __REPLAY_DATA__;
+const PULSE_SECONDS = 1.0;
+const canvas = document.getElementById("map");
+const ctx = canvas.getContext("2d");
+const slider = document.getElementById("slider");
+const playButton = document.getElementById("play");
+const speedSelect = document.getElementById("speed");
+document.getElementById("title").textContent = DATA.title;
+
+let time = 0;
+let playing = true;
+let lastFrame = null;
+
+function resize() {
+  const maxWidth = window.innerWidth - 24;
+  const maxHeight = window.innerHeight - document.querySelector("header").offsetHeight - 24;
+  const aspect = DATA.world.height_m / Math.max(DATA.world.width_m, 1e-9);
+  let width = maxWidth;
+  let height = width * aspect;
+  if (height > maxHeight) { height = maxHeight; width = height / aspect; }
+  canvas.width = Math.max(1, Math.floor(width));
+  canvas.height = Math.max(1, Math.floor(height));
+}
+
+function reachColor(fraction) {
+  fraction = Math.min(1, Math.max(0, isFinite(fraction) ? fraction : 0));
+  if (fraction < 0.5) return `rgb(${Math.round(fraction * 2 * 255)},200,0)`;
+  return `rgb(255,${Math.round((1 - fraction) * 2 * 200)},0)`;
+}
+
+const maxReach = Math.max(0, ...DATA.nodes.map(n => n.reach ?? 0));
+const nodesById = new Map(DATA.nodes.map(n => [n.id, n]));
+
+function draw() {
+  const w = canvas.width, h = canvas.height;
+  ctx.fillStyle = "#14141e";
+  ctx.fillRect(0, 0, w, h);
+
+  ctx.fillStyle = "#ebebeb";
+  for (const o of DATA.obstacles) {
+    if (o.kind === "rect") {
+      ctx.fillRect(Math.min(o.x0, o.x1) * w, Math.min(o.y0, o.y1) * h,
+                   Math.abs(o.x1 - o.x0) * w, Math.abs(o.y1 - o.y0) * h);
+    } else {
+      ctx.beginPath();
+      ctx.ellipse(o.x * w, o.y * h, o.r / DATA.world.width_m * w, o.r / DATA.world.height_m * h, 0, 0, 2 * Math.PI);
+      ctx.fill();
+    }
+  }
+
+  ctx.lineWidth = 2;
+  for (const [t, nodeId, type, distance] of DATA.transmissions) {
+    if (t > time) break;
+    const age = time - t;
+    const node = nodesById.get(nodeId);
+    if (age > PULSE_SECONDS || !node) continue;
+    const progress = age / PULSE_SECONDS;
+    ctx.globalAlpha = 1 - progress;
+    ctx.strokeStyle = DATA.colors[type] ?? "#ffffff";
+    ctx.beginPath();
+    ctx.ellipse(node.x * w, node.y * h,
+                Math.max(1, distance * progress / DATA.world.width_m * w),
+                Math.max(1, distance * progress / DATA.world.height_m * h), 0, 0, 2 * Math.PI);
+    ctx.stroke();
+  }
+  ctx.globalAlpha = 1;
+
+  let reached = 0;
+  for (const node of DATA.nodes) {
+    const isReached = node.reach !== null && node.reach <= time;
+    if (isReached) reached++;
+    ctx.beginPath();
+    ctx.arc(node.x * w, node.y * h, 6, 0, 2 * Math.PI);
+    ctx.fillStyle = isReached ? reachColor(maxReach > 0 ? node.reach / maxReach : 0) : "#6e6e6e";
+    ctx.fill();
+    ctx.strokeStyle = "#000";
+    ctx.lineWidth = 1;
+    ctx.stroke();
+  }
+
+  document.getElementById("time").textContent = `${time.toFixed(2)} s / ${DATA.duration.toFixed(2)} s`;
+  document.getElementById("reached").textContent = `Reached ${reached}/${DATA.nodes.length}`;
+  slider.value = DATA.duration > 0 ? Math.round(time / DATA.duration * 1000) : 0;
+}
+
+function frame(timestamp) {
+  if (playing && lastFrame !== null) {
+    time += (timestamp - lastFrame) / 1000 * parseFloat(speedSelect.value);
+    if (time >= DATA.duration) { time = DATA.duration; setPlaying(false); }
+  }
+  lastFrame = timestamp;
+  draw();
+  requestAnimationFrame(frame);
+}
+
+function setPlaying(value) {
+  playing = value;
+  playButton.textContent = playing ? "Pause" : "Play";
+}
+
+playButton.addEventListener("click", () => {
+  if (!playing && time >= DATA.duration) time = 0;
+  setPlaying(!playing);
+});
+slider.addEventListener("input", () => {
+  time = slider.value / 1000 * DATA.duration;
+});
+window.addEventListener("resize", resize);
+resize();
+requestAnimationFrame(frame);
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Point;
+
+    #[test]
+    fn replay_keeps_the_measurement_window_and_escapes_the_script() {
+        let world = WorldTransform::from_meters_per_unit((0.0, 0.0), (100.0, 50.0), 10.0);
+        let node = |node_id, x| NodeUIState {
+            node_id,
+            position: Point { x, y: 25.0 },
+            radio_strength: 0,
+            tx_power: None,
+            is_gateway: false,
+            disabled: false,
+        };
+        let nodes = vec![node(2, 75.0), node(1, 25.0)];
+        let mut measurement = MeasurementProgress::new(1, Instant::from_secs(100));
+        measurement.reach_times.insert(2, 3.5);
+
+        let mut capture = TransmissionCapture::default();
+        capture.record(1, 6, 400, Instant::from_secs(90));
+        capture.record(1, 6, 400, Instant::from_millis(100_250));
+        capture.record(2, 6, 400, Instant::from_millis(104_000));
+        capture.record(2, 6, 400, Instant::from_secs(120));
+
+        let data = replay_data(world, &[], &nodes, &measurement, &capture);
+        assert_eq!(data["duration"], json!(4.5));
+        assert_eq!(data["nodes"][0]["id"], json!(1));
+        assert_eq!(data["nodes"][1]["x"], json!(0.75));
+        assert_eq!(data["nodes"][1]["reach"], json!(3.5));
+        assert_eq!(
+            data["transmissions"],
+            json!([[0.25, 1, 6, 400], [4.0, 2, 6, 400]])
+        );
+        assert_eq!(data["colors"]["6"], json!("#ffa500"));
+
+        let html = replay_html(&json!({ "title": "</script>" }));
+        assert!(html.contains(r#"const DATA = {"title":"<\/script>"};"#));
+        assert!(!html.contains(DATA_PLACEHOLDER));
+    }
+}
@@ -0,0 +1,397 @@
+//! # Measurement Result Export
+//!
+//! Exports the displayed measurement with a single "Export results" action:
+//! - a PNG choropleth of the map where every node is colored by its reach time
+//!   (green = reached first, red = reached last, grey = not reached), with
+//!   obstacles and a color legend
+//! - a CSV file with the underlying per-node data, written next to the image
+//!   with the same file name and a `.csv` extension
+//! - for a stalled measurement, a `.stall.csv` file listing the unreached nodes
+//!   with their positions and the time of the last progress
+//! - a `.png.provenance.json` sidecar with the run provenance (versions, scene,
+//!   seed and speed)
+//!
+//! The image is rasterized in software so the export does not depend on the
+//! window size or the current map zoom.
+
+use eframe::egui;
+use image::{Rgba, RgbaImage};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use super::app_state::{MeasurementProgress, MeasurementStall};
+use super::map::MapTransform;
+use super::{AppState, NodeUIState};
+use crate::common::provenance;
+use crate::common::units::WorldTransform;
+use crate::simulation::Obstacle;
+
+/// Width of the exported image in pixels (height follows the world aspect ratio).
+const EXPORT_IMAGE_WIDTH: u32 = 1600;
+/// Height of the legend band below the map in pixels.
+const LEGEND_HEIGHT: u32 = 60;
+/// Radius of a node disc in pixels.
+const NODE_RADIUS: f32 = 7.0;
+/// Scale factor of the legend font (pixels per glyph dot).
+const FONT_SCALE: u32 = 3;
+
+const BACKGROUND_COLOR: Rgba<u8> = Rgba([20, 20, 30, 255]);
+const OBSTACLE_COLOR: Rgba<u8> = Rgba([235, 235, 235, 255]);
+const UNREACHED_COLOR: Rgba<u8> = Rgba([110, 110, 110, 255]);
+const OUTLINE_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// Build the stall report CSV: one row per unreached node, sorted by node ID.
+///
+/// Columns: `node_id,x,y,last_progress_s`.
+fn stall_csv(nodes: &[NodeUIState], stall: &MeasurementStall) -> String {
+    let mut csv = String::from("node_id,x,y,last_progress_s\n");
+    for node_id in &stall.unreached_nodes {
+        let Some(node) = nodes.iter().find(|n| n.node_id == *node_id) else {
+            continue;
+        };
+        let _ = writeln!(
+            csv,
+            "{},{},{},{:.3}",
+            node_id, node.position.x, node.position.y, stall.last_progress
+        );
+    }
+    csv
+}
+
+/// Color of a reach time on the green → yellow → red scale.
+///
+/// # Parameters
+///
+/// * `fraction` - Reach time relative to the slowest reached node (0.0..=1.0)
+fn reach_time_color(fraction: f64) -> Rgba<u8> {
+    let fraction = if fraction.is_finite() {
+        fraction.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    if fraction < 0.5 {
+        Rgba([(fraction * 2.0 * 255.0).round() as u8, 200, 0, 255])
+    } else {
+        Rgba([255, ((1.0 - fraction) * 2.0 * 200.0).round() as u8, 0, 255])
+    }
+}
+
+/// Build the CSV with one row per node, sorted by node ID.
+///
+/// Columns: `node_id,x,y,reached,reach_time_s,first_relayer,hops` (the reach time
+/// is empty for nodes that were not reached, the relayer and hop count where
+/// unknown; see `hop_depth`).
+fn reach_times_csv(nodes: &[NodeUIState], measurement: &MeasurementProgress) -> String {
+    let mut sorted: Vec<&NodeUIState> = nodes.iter().collect();
+    sorted.sort_by_key(|node| node.node_id);
+
+    let mut csv = String::from("node_id,x,y,reached,reach_time_s,first_relayer,hops\n");
+    for node in sorted {
+        let reach_time = measurement.reach_times.get(&node.node_id);
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{}",
+            node.node_id,
+            node.position.x,
+            node.position.y,
+            reach_time.is_some(),
+            reach_time.map_or(String::new(), |t| format!("{:.3}", t)),
+            measurement
+                .first_relayers
+                .get(&node.node_id)
+                .map_or(String::new(), |relayer| relayer.to_string()),
+            measurement
+                .hops
+                .get(&node.node_id)
+                .map_or(String::new(), |hops| hops.to_string())
+        );
+    }
+    csv
+}
+
+/// 3x5 dot patterns of the glyphs used by the legend (one row per `u8`, MSB left).
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        's' => [0b000, 0b111, 0b100, 0b011, 0b110],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        _ => return None,
+    })
+}
+
+/// Pixel width of `text` drawn with `draw_text`.
+fn text_width(text: &str) -> u32 {
+    text.chars().count() as u32 * 4 * FONT_SCALE
+}
+
+/// Draw `text` with its top-left corner at (`x`, `y`); unknown glyphs are skipped.
+fn draw_text(image: &mut RgbaImage, x: u32, y: u32, text: &str) {
+    for (index, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else { continue };
+        let glyph_x = x + index as u32 * 4 * FONT_SCALE;
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    fill_rect(
+                        image,
+                        (glyph_x + column * FONT_SCALE) as f32,
+                        (y + row as u32 * FONT_SCALE) as f32,
+                        FONT_SCALE as f32,
+                        FONT_SCALE as f32,
+                        TEXT_COLOR,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Fill an axis-aligned rectangle, clipped to the image.
+fn fill_rect(image: &mut RgbaImage, left: f32, top: f32, width: f32, height: f32, color: Rgba<u8>) {
+    let x0 = left.max(0.0).round() as u32;
+    let y0 = top.max(0.0).round() as u32;
+    let x1 = ((left + width).round().max(0.0) as u32).min(image.width());
+    let y1 = ((top + height).round().max(0.0) as u32).min(image.height());
+    for y in y0..y1 {
+        for x in x0..x1 {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+/// Fill a disc, clipped to the image.
+fn fill_circle(image: &mut RgbaImage, center: egui::Pos2, radius: f32, color: Rgba<u8>) {
+    let x0 = (center.x - radius).floor().max(0.0) as u32;
+    let y0 = (center.y - radius).floor().max(0.0) as u32;
+    let x1 = ((center.x + radius).ceil().max(0.0) as u32).min(image.width());
+    let y1 = ((center.y + radius).ceil().max(0.0) as u32).min(image.height());
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (dx, dy) = (x as f32 + 0.5 - center.x, y as f32 + 0.5 - center.y);
+            if dx * dx + dy * dy <= radius * radius {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// Rasterize the reach time choropleth of a measurement.
+///
+/// # Parameters
+///
+/// * `world` - World bounds and unit scale of the scene
+/// * `obstacles` - Obstacles drawn below the nodes
+/// * `nodes` - Nodes to color
+/// * `measurement` - Measurement providing the reach times
+///
+/// # Returns
+///
+/// The image: the map (keeping the world aspect ratio in meters) above a legend band.
+fn render_choropleth(
+    world: WorldTransform,
+    obstacles: &[Obstacle],
+    nodes: &[NodeUIState],
+    measurement: &MeasurementProgress,
+) -> RgbaImage {
+    let aspect_ratio = if world.width_meters() > 0.0 {
+        world.height_meters() / world.width_meters()
+    } else {
+        1.0
+    };
+    let map_height = ((EXPORT_IMAGE_WIDTH as f64 * aspect_ratio).round() as u32).clamp(1, 10000);
+    let mut image = RgbaImage::from_pixel(
+        EXPORT_IMAGE_WIDTH,
+        map_height + LEGEND_HEIGHT,
+        BACKGROUND_COLOR,
+    );
+    let transform = MapTransform::new(
+        world,
+        egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(EXPORT_IMAGE_WIDTH as f32, map_height as f32),
+        ),
+    );
+
+    for obstacle in obstacles {
+        match obstacle {
+            Obstacle::Rectangle { position, .. } => {
+                let corners = egui::Rect::from_two_pos(
+                    transform.world_to_screen(position.top_left.x, position.top_left.y),
+                    transform.world_to_screen(position.bottom_right.x, position.bottom_right.y),
+                );
+                fill_rect(
+                    &mut image,
+                    corners.left(),
+                    corners.top(),
+                    corners.width(),
+                    corners.height(),
+                    OBSTACLE_COLOR,
+                );
+            }
+            Obstacle::Circle { position, .. } => {
+                let center = transform.world_to_screen(position.center.x, position.center.y);
+                let radius = transform.meters_to_pixels(position.radius as f32);
+                fill_circle(&mut image, center, radius, OBSTACLE_COLOR);
+            }
+        }
+    }
+
+    let max_reach_time = measurement
+        .reach_times
+        .values()
+        .copied()
+        .fold(0.0_f64, f64::max);
+    for node in nodes {
+        let center = transform.world_to_screen(node.position.x, node.position.y);
+        let color = match measurement.reach_times.get(&node.node_id) {
+            Some(t) if max_reach_time > 0.0 => reach_time_color(t / max_reach_time),
+            Some(_) => reach_time_color(0.0),
+            None => UNREACHED_COLOR,
+        };
+        fill_circle(&mut image, center, NODE_RADIUS + 1.5, OUTLINE_COLOR);
+        fill_circle(&mut image, center, NODE_RADIUS, color);
+    }
+
+    // Legend: "0s" [gradient bar] "<max>s"   [grey swatch] "-" (not reached)
+    let label_y = map_height + (LEGEND_HEIGHT - 5 * FONT_SCALE) / 2;
+    let bar_top = (map_height + LEGEND_HEIGHT / 2 - 10) as f32;
+    let min_label = "0s";
+    let max_label = format!("{:.1}s", max_reach_time);
+    draw_text(&mut image, 20, label_y, min_label);
+    let bar_left = 20 + text_width(min_label) + 10;
+    let bar_width = EXPORT_IMAGE_WIDTH / 2;
+    for x in 0..bar_width {
+        let color = reach_time_color(x as f64 / (bar_width - 1) as f64);
+        fill_rect(&mut image, (bar_left + x) as f32, bar_top, 1.0, 20.0, color);
+    }
+    let max_label_x = bar_left + bar_width + 10;
+    draw_text(&mut image, max_label_x, label_y, &max_label);
+    let swatch_left = max_label_x + text_width(&max_label) + 40;
+    fill_rect(
+        &mut image,
+        swatch_left as f32,
+        bar_top,
+        20.0,
+        20.0,
+        UNREACHED_COLOR,
+    );
+    draw_text(&mut image, swatch_left + 30, label_y, "-");
+
+    image
+}
+
+/// Write the choropleth image to `image_path` and the CSV (plus the stall report
+/// of a stalled measurement) next to it.
+///
+/// # Returns
+///
+/// The path of the written CSV file, or an error message.
+fn write_results(
+    image_path: &Path,
+    state: &AppState,
+    measurement: &MeasurementProgress,
+) -> Result<PathBuf, String> {
+    let image = render_choropleth(state.world, &state.obstacles, &state.nodes, measurement);
+    image
+        .save_with_format(image_path, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to write {}: {}", image_path.display(), e))?;
+
+    let csv_path = image_path.with_extension("csv");
+    std::fs::write(&csv_path, reach_times_csv(&state.nodes, measurement))
+        .map_err(|e| format!("Failed to write {}: {}", csv_path.display(), e))?;
+
+    if let Some(stall) = &measurement.stall {
+        let stall_path = image_path.with_extension("stall.csv");
+        std::fs::write(&stall_path, stall_csv(&state.nodes, stall))
+            .map_err(|e| format!("Failed to write {}: {}", stall_path.display(), e))?;
+    }
+    provenance::write_sidecar(image_path)?;
+    Ok(csv_path)
+}
+
+/// Export the displayed measurement as a choropleth PNG and a CSV file.
+///
+/// Opens a save dialog for the image; the CSV is written next to it. Failures are
+/// shown in the alert dialog.
+///
+/// # Parameters
+///
+/// * `state` - Mutable application state (measurement data, alert)
+pub fn export_results(state: &mut AppState) {
+    let Some(measurement) = state.active_measurement().cloned() else {
+        return;
+    };
+
+    let file_name = format!(
+        "measurement_{}_{}.png",
+        state.measurement_identifier,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let Some(image_path) = rfd::FileDialog::new()
+        .add_filter("PNG image", &["png"])
+        .set_file_name(file_name)
+        .save_file()
+    else {
+        return;
+    };
+
+    match write_results(&image_path, state, &measurement) {
+        Ok(csv_path) => log::info!(
+            "Exported measurement {} results to {} and {}",
+            state.measurement_identifier,
+            image_path.display(),
+            csv_path.display()
+        ),
+        Err(e) => {
+            log::error!("{}", e);
+            state.alert = Some(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Point;
+
+    #[test]
+    fn test_csv_lists_nodes_with_reach_times() {
+        let node = |node_id, x| NodeUIState {
+            node_id,
+            position: Point { x, y: 5.0 },
+            radio_strength: 0,
+            tx_power: None,
+            is_gateway: false,
+            disabled: false,
+        };
+        let nodes = vec![node(2, 20.0), node(1, 10.0)];
+        let measurement = MeasurementProgress::new(1, embassy_time::Instant::from_secs(0));
+
+        let csv = reach_times_csv(&nodes, &measurement);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "node_id,x,y,reached,reach_time_s,first_relayer,hops"
+        );
+        assert_eq!(lines[1], "1,10,5,true,0.000,,0");
+        assert_eq!(lines[2], "2,20,5,false,,,");
+    }
+
+    #[test]
+    fn test_reach_time_color_scale_endpoints() {
+        assert_eq!(reach_time_color(0.0), Rgba([0, 200, 0, 255]));
+        assert_eq!(reach_time_color(1.0), Rgba([255, 0, 0, 255]));
+        assert_eq!(reach_time_color(f64::NAN), reach_time_color(0.0));
+    }
+}
@@ -5,7 +5,8 @@
 //! - Node metadata (ID, position, radio strength)
 //! - Message statistics (sent/received counts)
 //! - Complete message history in a scrollable, virtualized table
-//! - Measurement control button (Start/Reset)
+//! - Measurement control button (Start/Reset) and radio on/off switch (Simulation)
+//! - Live TX power and position editing (Simulation), applied when a value is committed
 //!
 //! ## Message Table
 //!
@@ -16,6 +17,10 @@
 //! - Green rows: Messages received from other nodes
 //! - Red rows: Collision detected (packet lost)
 //!
+//! Clicking a radio stream row shows the packet's decoded protocol fields. Filter
+//! controls above the radio and message stream tables narrow the rows by type,
+//! direction, collisions, sender, time range and text (see `stream_filter`).
+//!
 //! ## Link Quality Visualization
 //!
 //! Link quality values are color-coded based on the scoring matrix thresholds:
@@ -23,13 +28,20 @@
 //! - Yellow: Medium quality
 //! - Green: Excellent quality (≥ excellent_limit)
 
+use crate::common::connection_matrix::link_matrices_to_json;
+use crate::common::units::WorldTransform;
+use crate::simulation::Point;
+use crate::simulation::message_decoder::RecordedPacket;
 use crate::simulation::types::LogLevel;
-use crate::ui::app_state::InspectorTab;
-use crate::ui::{AppState, OperatingMode, UICommand, color_for_message_type};
+use crate::ui::app_state::{InspectorTab, message_type_name};
+use crate::ui::edit_history::{self, Edit};
+use crate::ui::stream_filter::{self, StreamFilter, StreamRow, row_time};
+use crate::ui::{
+    AppState, MeasurementKind, NodeUIState, OperatingMode, UICommand, color_for_message_type,
+};
 use chrono::{Local, TimeZone};
 use eframe::egui;
 use egui::Color32;
-use std::cmp::max;
 
 /// Render the right inspector panel.
 ///
@@ -92,15 +104,26 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                 ui.add_space(10.0);
                 ui.label("Radio strength:");
                 ui.label(egui::RichText::new(format!("{}", p.radio_strength)).strong());
+                if p.disabled {
+                    ui.add_space(10.0);
+                    ui.label(egui::RichText::new("Radio disabled").color(Color32::GRAY));
+                }
             });
+            let live_edits = render_live_edit(ui, p, &state.world);
 
             let mut sent_messages_count = 0;
             let mut received_messages_count = 0;
+            let mut missed_while_transmitting_count = 0;
+            let mut missed_while_sleeping_count = 0;
 
             if let Some(node_info) = &state.node_info {
                 for msg in &node_info.radio_packets {
                     if msg.sender_node == p.node_id {
                         sent_messages_count += 1;
+                    } else if msg.missed_while_transmitting {
+                        missed_while_transmitting_count += 1;
+                    } else if msg.missed_while_sleeping {
+                        missed_while_sleeping_count += 1;
                     } else {
                         received_messages_count += 1;
                     }
@@ -113,7 +136,38 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                 ui.add_space(10.0);
                 ui.label("Received packets:");
                 ui.label(egui::RichText::new(format!("{}", received_messages_count)).strong());
+                if state.operating_mode == OperatingMode::Simulation {
+                    ui.add_space(10.0);
+                    ui.label("Missed while TX:")
+                        .on_hover_text("Packets lost because the node was transmitting (half-duplex)");
+                    ui.label(egui::RichText::new(format!("{}", missed_while_transmitting_count)).strong());
+                    if missed_while_sleeping_count > 0 {
+                        ui.add_space(10.0);
+                        ui.label("Missed while asleep:")
+                            .on_hover_text("Packets lost because the node's receiver was asleep (duty cycling)");
+                        ui.label(egui::RichText::new(format!("{}", missed_while_sleeping_count)).strong());
+                    }
+                }
             });
+            if state.operating_mode == OperatingMode::Simulation {
+                if let Some(statistics) = state.node_statistics.get(&p.node_id) {
+                    ui.horizontal(|ui| {
+                        ui.label("CAD requests:")
+                            .on_hover_text("Channel activity detections before transmitting, and their answers");
+                        ui.label(egui::RichText::new(format!("{}", statistics.cad_requests)).strong());
+                        ui.add_space(10.0);
+                        ui.label("Busy:");
+                        ui.label(egui::RichText::new(format!("{}", statistics.cad_busy)).strong());
+                        ui.add_space(10.0);
+                        ui.label("Free:");
+                        ui.label(egui::RichText::new(format!("{}", statistics.cad_free)).strong());
+                        if let Some(ratio) = statistics.cad_busy_ratio() {
+                            ui.label(format!("({:.0}% busy)", ratio));
+                        }
+                    });
+                }
+            }
+            super::time_travel::render_snapshot(ui, state, p.node_id);
 
             // Tab bar header (outside of bottom-up so it doesn't steal table space)
             if let Some(_node_info) = &state.node_info {
@@ -125,6 +179,11 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                     if state.operating_mode != OperatingMode::LogVisualization {
                         ui.selectable_value(&mut state.inspector_tab, InspectorTab::ConnectionMatrix, "Connection Matrix");
                     }
+                    if state.operating_mode == OperatingMode::Simulation {
+                        ui.selectable_value(&mut state.inspector_tab, InspectorTab::ProtocolState, "Protocol State");
+                        ui.selectable_value(&mut state.inspector_tab, InspectorTab::LinkStats, "Link Stats");
+                    }
+                    ui.selectable_value(&mut state.inspector_tab, InspectorTab::Waterfall, "Waterfall");
                 });
                 ui.add_space(4.0);
             }
@@ -134,9 +193,16 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
             let avail_h = ui.available_height();
             let button_h = ui.spacing().interact_size.y;
             let node_id = p.node_id; // Capture node_id before moving into closure
+            let radio_disabled = p.disabled;
+            for edit in live_edits {
+                edit_history::perform(state, edit);
+            }
             let show_measurement_button = state.operating_mode != OperatingMode::LogVisualization;
             let show_control_buttons = state.operating_mode == OperatingMode::RealtimeTracking;
             let control_available = state.control_available;
+            // The button resets the displayed measurement if this node originated it,
+            // otherwise it starts a new (possibly concurrent) measurement from this node.
+            let is_measurement_origin = state.active_measurement().map_or(false, |m| m.origin_node_id == node_id);
 
             ui.allocate_ui_with_layout(
                 egui::vec2(avail_w, avail_h),
@@ -165,30 +231,16 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                                 let pad = (ui.available_width() - button_w).max(0.0) / 2.0;
                                 ui.add_space(pad);
 
-                                let measurement_button_title: String = if state.measurement_identifier > 0 {
+                                let measurement_button_title: String = if is_measurement_origin {
                                     "Reset Measurement".into()
                                 } else {
                                     "Start Measurement".into()
                                 };
                                 if ui.add_sized([half_button_w, button_h], egui::Button::new(measurement_button_title)).clicked() {
-                                    if state.measurement_identifier == 0 {
-                                        state.measurement_identifier = max(rand::random::<u32>() % 100000, 1);
-                                        state.measurement_start_time = embassy_time::Instant::now();
-                                        let _ = state.ui_command_tx.try_send(UICommand::StartMeasurement(node_id, state.measurement_identifier));
-                                        log::info!("Started measurement {} on node {}", state.measurement_identifier, node_id);
-                                        state.reached_nodes.clear();
-                                        state.reached_nodes.insert(node_id);
+                                    if !is_measurement_origin {
+                                        state.start_measurement(node_id, MeasurementKind::Block);
                                     } else {
-                                        state.reached_nodes.clear();
-                                        state.measurement_identifier = 0;
-                                        state.measurement_50_time = 0;
-                                        state.measurement_90_time = 0;
-                                        state.measurement_100_time = 0;
-                                        state.measurement_total_time = 0;
-                                        state.measurement_total_message_count = 0;
-                                        state.measurement_50_message_count = 0;
-                                        state.measurement_90_message_count = 0;
-                                        state.measurement_100_message_count = 0;
+                                        state.reset_measurement();
                                     }
                                 }
 
@@ -238,36 +290,54 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                                 });
                             });
                         } else if show_measurement_button {
-                            // For Simulation mode (no control buttons), just show measurement button
+                            // For Simulation mode (no control buttons): measurement and radio on/off buttons
                             ui.horizontal(|ui| {
                                 let pad = (ui.available_width() - button_w).max(0.0) / 2.0;
                                 ui.add_space(pad);
-                                let measurement_button_title: String = if state.measurement_identifier > 0 {
+                                let measurement_button_title: String = if is_measurement_origin {
                                     "Reset Measurement".into()
                                 } else {
                                     "Start Measurement".into()
                                 };
-                                if ui.add_sized([button_w, button_h], egui::Button::new(measurement_button_title)).clicked() {
-                                    if state.measurement_identifier == 0 {
-                                        state.measurement_identifier = max(rand::random::<u32>() % 100000, 1);
-                                        state.measurement_start_time = embassy_time::Instant::now();
-                                        let _ = state.ui_command_tx.try_send(UICommand::StartMeasurement(node_id, state.measurement_identifier));
-                                        log::info!("Started measurement {} on node {}", state.measurement_identifier, node_id);
-                                        state.reached_nodes.clear();
-                                        state.reached_nodes.insert(node_id);
+                                if ui.add_sized([half_button_w, button_h], egui::Button::new(measurement_button_title)).clicked() {
+                                    if !is_measurement_origin {
+                                        state.start_measurement(node_id, MeasurementKind::Block);
                                     } else {
-                                        state.reached_nodes.clear();
-                                        state.measurement_identifier = 0;
-                                        state.measurement_50_time = 0;
-                                        state.measurement_90_time = 0;
-                                        state.measurement_100_time = 0;
-                                        state.measurement_total_time = 0;
-                                        state.measurement_total_message_count = 0;
-                                        state.measurement_50_message_count = 0;
-                                        state.measurement_90_message_count = 0;
-                                        state.measurement_100_message_count = 0;
+                                        state.reset_measurement();
                                     }
                                 }
+
+                                ui.add_space(6.0);
+
+                                let radio_button_title = if radio_disabled { "Enable Radio" } else { "Disable Radio" };
+                                if ui
+                                    .add_sized([half_button_w, button_h], egui::Button::new(radio_button_title))
+                                    .on_hover_text("Switch the node's radio on or off (undoable)")
+                                    .clicked()
+                                {
+                                    edit_history::perform(
+                                        state,
+                                        Edit::SetNodeEnabled {
+                                            node_id,
+                                            enabled: radio_disabled,
+                                        },
+                                    );
+                                }
+                            });
+
+                            ui.add_space(6.0);
+
+                            // Row above: mempool propagation measurement
+                            ui.horizontal(|ui| {
+                                let pad = (ui.available_width() - button_w).max(0.0) / 2.0;
+                                ui.add_space(pad);
+                                if ui
+                                    .add_sized([button_w, button_h], egui::Button::new("Measure Transaction"))
+                                    .on_hover_text("Originate an AddTransaction from this node and track its propagation")
+                                    .clicked()
+                                {
+                                    state.start_measurement(node_id, MeasurementKind::Transaction);
+                                }
                             });
                         }
 
@@ -289,15 +359,23 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                                     InspectorTab::ConnectionMatrix => {
                                         render_connection_matrix(ui, state, node_id);
                                     }
+                                    InspectorTab::ProtocolState => {
+                                        render_protocol_state(ui, state, node_id);
+                                    }
+                                    InspectorTab::LinkStats => {
+                                        super::link_histograms::render(ui, state, node_id);
+                                    }
                                     _ => {
                                         if has_matching_node_info {
                                             match current_tab {
                                                 InspectorTab::RadioStream => {
+                                                    stream_filter::render_controls(ui, &mut state.stream_filter, state.operating_mode, true);
                                                     if let Some(node_info) = &state.node_info {
                                                         render_radio_stream_table(ui, state, node_info);
                                                     }
                                                 }
                                                 InspectorTab::MessageStream => {
+                                                    stream_filter::render_controls(ui, &mut state.stream_filter, state.operating_mode, false);
                                                     if let Some(node_info) = &state.node_info {
                                                         render_message_stream_table(ui, state, node_info);
                                                     }
@@ -308,7 +386,12 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                                                         render_log_stream(ui, state, &log_lines);
                                                     }
                                                 }
-                                                InspectorTab::ConnectionMatrix => {}
+                                                InspectorTab::Waterfall => {
+                                                    if let Some(node_info) = &state.node_info {
+                                                        super::packet_waterfall::render(ui, node_info);
+                                                    }
+                                                }
+                                                InspectorTab::ConnectionMatrix | InspectorTab::ProtocolState | InspectorTab::LinkStats => {}
                                             }
                                         }
                                     }
@@ -342,7 +425,14 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
 /// - Size: Packet size in bytes
 /// - LQ: Link quality (0-63), color-coded by threshold
 ///
-/// Collision rows are highlighted in red with white text.
+/// Collision rows are highlighted in red and packets missed while transmitting in
+/// orange, both with white text.
+///
+/// Only packets passing the inspector stream filter are listed.
+///
+/// Clicking a row expands it: its decoded protocol fields (echo targets, requested
+/// block parts, transaction IDs, ...) are shown above the table until the row is
+/// clicked again. The expanded row is kept in egui memory per node.
 ///
 /// # Parameters
 ///
@@ -352,6 +442,44 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
 fn render_radio_stream_table(ui: &mut egui::Ui, state: &AppState, node_info: &crate::ui::NodeInfo) {
     use egui_extras::{Column, TableBuilder};
 
+    let expanded_id = egui::Id::new(("radio_stream_expanded", node_info.node_id));
+    let expanded: Option<RadioRowKey> = ui.data(|d| d.get_temp(expanded_id));
+    if let Some(msg) = expanded.and_then(|key| {
+        node_info
+            .radio_packets
+            .iter()
+            .find(|m| RadioRowKey::of(m) == key)
+    }) {
+        render_packet_details(ui, msg);
+    }
+    let mut clicked_row: Option<RadioRowKey> = None;
+
+    // Indices of the packets passing the stream filter
+    let filtered_indices: Vec<usize> = node_info
+        .radio_packets
+        .iter()
+        .enumerate()
+        .filter(|(_, msg)| {
+            state.stream_filter.matches(&StreamRow {
+                message_type: msg.message_type,
+                outgoing: msg.sender_node == node_info.node_id,
+                collision: msg.collision
+                    || msg.missed_while_transmitting
+                    || msg.missed_while_sleeping,
+                sender: msg.sender_node,
+                sequence: msg.sequence,
+                time: row_time(state.operating_mode, state.start_time, msg.timestamp),
+                details: &|| {
+                    msg.packet
+                        .as_ref()
+                        .map(RecordedPacket::details)
+                        .unwrap_or_default()
+                },
+            })
+        })
+        .map(|(i, _)| i)
+        .collect();
+
     let row_height = ui.text_style_height(&egui::TextStyle::Body) * 1.3;
     TableBuilder::new(ui)
         .striped(true)
@@ -391,12 +519,15 @@ fn render_radio_stream_table(ui: &mut egui::Ui, state: &AppState, node_info: &cr
         })
         .body(|body| {
             // Virtualized rows: only build visible rows; keep newest-first order
-            let row_count = node_info.radio_packets.len();
+            let row_count = filtered_indices.len();
             body.rows(row_height, row_count, |mut row| {
                 // Map visible row index to reversed (newest-first) index
                 let row_index = row.index();
-                let msg_idx = row_count - 1 - row_index;
+                let msg_idx = filtered_indices[row_count - 1 - row_index];
                 let msg = &node_info.radio_packets[msg_idx];
+                let row_key = RadioRowKey::of(msg);
+                let is_expanded = expanded == Some(row_key);
+                row.set_selected(is_expanded);
 
                 // Color rows red if from this node, else green
                 let is_self = node_info.node_id == msg.sender_node;
@@ -410,20 +541,16 @@ fn render_radio_stream_table(ui: &mut egui::Ui, state: &AppState, node_info: &cr
                     // Paint whole row red for collisions and use white text for contrast
                     collision_fill = Some(Color32::from_rgb(255, 0, 0));
                     row_color = Color32::WHITE;
+                } else if msg.missed_while_transmitting {
+                    // Half-duplex losses are orange to tell them apart from collisions
+                    collision_fill = Some(Color32::from_rgb(255, 140, 0));
+                    row_color = Color32::WHITE;
+                } else if msg.missed_while_sleeping {
+                    // Sleep losses are slate blue
+                    collision_fill = Some(Color32::from_rgb(90, 90, 170));
+                    row_color = Color32::WHITE;
                 }
-                let type_string = match msg.message_type {
-                    1 => "Req echo",
-                    2 => "Echo",
-                    3 => "Echo result",
-                    4 => "Req block",
-                    5 => "Req blk prt",
-                    6 => "Add block",
-                    7 => "Add trans",
-                    8 => "Req mempool",
-                    9 => "Support",
-                    255 => "Packet CRC Error",
-                    _ => "Unknown",
-                };
+                let type_string = message_type_name(msg.message_type);
                 let from_string = if msg.message_type == 255 {
                     // For CRC errors, sender is unknown
                     "?".to_string()
@@ -478,7 +605,8 @@ fn render_radio_stream_table(ui: &mut egui::Ui, state: &AppState, node_info: &cr
                         let rect = ui.available_rect_before_wrap();
                         ui.painter().rect_filled(rect, 0.0, fill);
                     }
-                    ui.colored_label(message_type_color, type_string);
+                    let marker = if is_expanded { "▾" } else { "▸" };
+                    ui.colored_label(message_type_color, format!("{} {}", marker, type_string));
                 });
                 row.col(|ui| {
                     if let Some(fill) = collision_fill {
@@ -530,8 +658,162 @@ fn render_radio_stream_table(ui: &mut egui::Ui, state: &AppState, node_info: &cr
 
                     ui.colored_label(link_quality_color, link_quality_string);
                 });
+
+                if row.response().clicked() {
+                    clicked_row = Some(row_key);
+                }
             });
         });
+
+    // Clicking the expanded row collapses it, clicking another row expands that one
+    if let Some(key) = clicked_row {
+        let next = if expanded == Some(key) {
+            None
+        } else {
+            Some(key)
+        };
+        ui.data_mut(|d| d.insert_temp(expanded_id, next));
+    }
+}
+
+/// Values of the live node editor while one of them is being dragged or typed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct NodeDraft {
+    node_id: u32,
+    tx_power: f32,
+    x: f64,
+    y: f64,
+}
+
+/// Render the TX power and position editors of a node (Simulation mode).
+///
+/// The values are kept as a draft while dragging or typing and become undoable
+/// edits when the drag ends or the field loses focus.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context
+/// * `node` - The selected node (no editor without a known TX power)
+/// * `world` - World transform (bounds of the position fields)
+///
+/// # Returns
+///
+/// The edits to perform (TX power and/or move), empty while nothing was committed.
+fn render_live_edit(ui: &mut egui::Ui, node: &NodeUIState, world: &WorldTransform) -> Vec<Edit> {
+    let (min_x, min_y, max_x, max_y) = world.bounds();
+    let Some(tx_power) = node.tx_power else {
+        return Vec::new();
+    };
+    let draft_id = egui::Id::new("inspector_node_draft");
+    let current = NodeDraft {
+        node_id: node.node_id,
+        tx_power,
+        x: node.position.x,
+        y: node.position.y,
+    };
+    let mut draft = ui
+        .data(|d| d.get_temp::<NodeDraft>(draft_id))
+        .filter(|draft| draft.node_id == node.node_id)
+        .unwrap_or(current);
+
+    let responses = ui
+        .horizontal(|ui| {
+            ui.label("TX power:");
+            let power = ui
+                .add(
+                    egui::DragValue::new(&mut draft.tx_power)
+                        .range(-50.0..=50.0)
+                        .speed(0.1)
+                        .fixed_decimals(1)
+                        .suffix(" dBm"),
+                )
+                .on_hover_text("Drag or type a new TX power; the range is recomputed (undoable)");
+            ui.add_space(10.0);
+            ui.label("X:");
+            let x = ui.add(egui::DragValue::new(&mut draft.x).range(min_x..=max_x));
+            ui.label("Y:");
+            let y = ui.add(egui::DragValue::new(&mut draft.y).range(min_y..=max_y));
+            [power, x, y]
+        })
+        .inner;
+
+    let editing = responses.iter().any(|r| r.dragged() || r.has_focus());
+    let committed = responses.iter().any(|r| r.drag_stopped() || r.lost_focus());
+    let mut edits = Vec::new();
+    if committed {
+        if draft.tx_power != current.tx_power {
+            edits.push(Edit::SetTxPower {
+                node_id: node.node_id,
+                from: current.tx_power,
+                to: draft.tx_power,
+            });
+        }
+        if draft.x != current.x || draft.y != current.y {
+            edits.push(Edit::MoveNode {
+                node_id: node.node_id,
+                from: node.position.clone(),
+                to: Point {
+                    x: draft.x,
+                    y: draft.y,
+                },
+            });
+        }
+    }
+    ui.data_mut(|d| {
+        if editing && !committed {
+            d.insert_temp(draft_id, draft);
+        } else {
+            d.remove::<NodeDraft>(draft_id);
+        }
+    });
+    edits
+}
+
+/// Identity of a radio stream row, stable while newer packets are appended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RadioRowKey {
+    timestamp_ticks: u64,
+    sender_node: u32,
+    message_type: u8,
+    packet_index: u8,
+}
+
+impl RadioRowKey {
+    fn of(msg: &crate::simulation::NodeMessage) -> Self {
+        Self {
+            timestamp_ticks: msg.timestamp.as_ticks(),
+            sender_node: msg.sender_node,
+            message_type: msg.message_type,
+            packet_index: msg.packet_index,
+        }
+    }
+}
+
+/// Render the decoded protocol fields of an expanded radio stream row.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context
+/// * `msg` - The expanded packet
+fn render_packet_details(ui: &mut egui::Ui, msg: &crate::simulation::NodeMessage) {
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        ui.strong("Packet details");
+        let Some(packet) = &msg.packet else {
+            ui.label("Payload not available (packet payloads are not logged in analyzer modes).");
+            return;
+        };
+        egui::Grid::new("radio_packet_details")
+            .num_columns(2)
+            .spacing([12.0, 2.0])
+            .show(ui, |ui| {
+                for (label, value) in &packet.details() {
+                    ui.label(*label);
+                    ui.label(egui::RichText::new(value).monospace());
+                    ui.end_row();
+                }
+            });
+    });
 }
 
 /// Render the message stream table showing complete messages (e.g., AddBlock).
@@ -542,6 +824,8 @@ fn render_radio_stream_table(ui: &mut egui::Ui, state: &AppState, node_info: &cr
 /// - Type: Message type name
 /// - Sequence: Message sequence number
 ///
+/// Only messages passing the inspector stream filter are listed.
+///
 /// # Parameters
 ///
 /// * `ui` - egui UI context
@@ -554,6 +838,29 @@ fn render_message_stream_table(
 ) {
     use egui_extras::{Column, TableBuilder};
 
+    // Indices of the messages passing the stream filter (full messages have no collisions)
+    let filter = StreamFilter {
+        collisions_only: false,
+        ..state.stream_filter.clone()
+    };
+    let filtered_indices: Vec<usize> = node_info
+        .messages
+        .iter()
+        .enumerate()
+        .filter(|(_, msg)| {
+            filter.matches(&StreamRow {
+                message_type: msg.message_type,
+                outgoing: msg.is_outgoing,
+                collision: false,
+                sender: msg.sender_node,
+                sequence: Some(msg.sequence),
+                time: row_time(state.operating_mode, state.start_time, msg.timestamp),
+                details: &|| Vec::new(),
+            })
+        })
+        .map(|(i, _)| i)
+        .collect();
+
     let row_height = ui.text_style_height(&egui::TextStyle::Body) * 1.3;
 
     TableBuilder::new(ui)
@@ -581,10 +888,10 @@ fn render_message_stream_table(
             });
         })
         .body(|body| {
-            let row_count = node_info.messages.len();
+            let row_count = filtered_indices.len();
             body.rows(row_height, row_count, |mut row| {
                 let row_index = row.index();
-                let msg_idx = row_count - 1 - row_index; // Newest first
+                let msg_idx = filtered_indices[row_count - 1 - row_index]; // Newest first
                 let msg = &node_info.messages[msg_idx];
 
                 let is_outgoing = msg.is_outgoing;
@@ -600,6 +907,7 @@ fn render_message_stream_table(
                 };
                 let type_string = match msg.message_type {
                     6 => "AddBlock",
+                    7 => "AddTransaction",
                     _ => "Unknown",
                 };
 
@@ -794,6 +1102,20 @@ fn render_connection_matrix(ui: &mut egui::Ui, state: &mut AppState, node_id: u3
             let time_string = format_inspector_timestamp(state, timestamp);
             ui.label(format!("Matrix: {}", time_string));
         }
+
+        let collected = state.connection_matrices.len();
+        if ui
+            .add_enabled(
+                collected > 0,
+                egui::Button::new(format!("Export all ({})", collected)),
+            )
+            .on_hover_text(
+                "Save the matrices of all nodes as a link matrix file (scene link_matrix.initial)",
+            )
+            .clicked()
+        {
+            export_link_matrices(state);
+        }
     });
 
     ui.add_space(8.0);
@@ -811,6 +1133,39 @@ fn render_connection_matrix(ui: &mut egui::Ui, state: &mut AppState, node_id: u3
     }
 }
 
+/// Save the collected connection matrices of all nodes into a link matrix file.
+fn export_link_matrices(state: &mut AppState) {
+    let file_name = format!(
+        "link_matrices_{}.json",
+        Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("Link matrix file", &["json"])
+        .set_file_name(file_name)
+        .save_file()
+    else {
+        return;
+    };
+
+    let result = link_matrices_to_json(
+        state.connection_matrices.values(),
+        Some(crate::common::provenance::current()),
+    )
+    .and_then(|json| std::fs::write(&path, json).map_err(Into::into));
+    match result {
+        Ok(()) => log::info!(
+            "Exported {} link matrices to {}",
+            state.connection_matrices.len(),
+            path.display()
+        ),
+        Err(e) => {
+            let message = format!("Cannot export link matrices to {}: {}", path.display(), e);
+            log::error!("{}", message);
+            state.alert = Some(message);
+        }
+    }
+}
+
 fn format_inspector_timestamp(state: &AppState, timestamp: embassy_time::Instant) -> String {
     match state.operating_mode {
         OperatingMode::Simulation => {
@@ -828,6 +1183,108 @@ fn format_inspector_timestamp(state: &AppState, timestamp: embassy_time::Instant
     }
 }
 
+/// Render the protocol state tab: the node's radio manager internals, queried on demand.
+fn render_protocol_state(ui: &mut egui::Ui, state: &mut AppState, node_id: u32) {
+    use egui_extras::{Column, TableBuilder};
+
+    if state.operating_mode != OperatingMode::Simulation {
+        ui.label("Protocol state inspection is available in Simulation mode only.");
+        return;
+    }
+
+    // Query once when the tab is first shown for a node; later refreshes are explicit
+    if !state.protocol_states.contains_key(&node_id)
+        && !state.protocol_state_pending.contains(&node_id)
+    {
+        state.protocol_state_pending.insert(node_id);
+        let _ = state
+            .ui_command_tx
+            .try_send(UICommand::QueryNodeState(node_id));
+    }
+
+    ui.horizontal(|ui| {
+        if ui
+            .button("Refresh")
+            .on_hover_text("Query the node's radio manager state again")
+            .clicked()
+        {
+            state.protocol_state_pending.insert(node_id);
+            let _ = state
+                .ui_command_tx
+                .try_send(UICommand::QueryNodeState(node_id));
+        }
+        if state.protocol_state_pending.contains(&node_id) {
+            ui.add(egui::Spinner::new());
+        }
+        if let Some((timestamp, _)) = state.protocol_states.get(&node_id) {
+            ui.label(format!(
+                "State: {}",
+                format_inspector_timestamp(state, *timestamp)
+            ));
+        }
+    });
+
+    let Some((_, protocol_state)) = state.protocol_states.get(&node_id) else {
+        return;
+    };
+    let timer = |remaining: Option<u64>| {
+        remaining.map_or("not running".to_string(), |ms| {
+            format!("in {:.1} s", ms as f64 / 1000.0)
+        })
+    };
+    egui::Grid::new("protocol_state_summary")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("TX queue");
+            ui.label(format!("{} packets", protocol_state.tx_queue_len));
+            ui.end_row();
+            ui.label("Next echo request");
+            ui.label(timer(protocol_state.next_echo_request_ms));
+            ui.end_row();
+            ui.label("Echo gathering ends");
+            ui.label(timer(protocol_state.echo_gathering_end_ms));
+            ui.end_row();
+        });
+    ui.add_space(4.0);
+
+    if protocol_state.neighbors.is_empty() {
+        ui.label("No scored neighbors.");
+        return;
+    }
+    TableBuilder::new(ui)
+        .striped(true)
+        .vscroll(true)
+        .min_scrolled_height(100.0)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::exact(110.0))
+        .column(Column::remainder().clip(true))
+        .header(20.0, |mut header| {
+            header.col(|ui| {
+                ui.label(egui::RichText::new("Neighbor").strong());
+            });
+            header.col(|ui| {
+                ui.label(egui::RichText::new("Score").strong());
+            });
+        })
+        .body(|body| {
+            body.rows(18.0, protocol_state.neighbors.len(), |mut row| {
+                let neighbor = protocol_state.neighbors[row.index()];
+                row.col(|ui| {
+                    ui.label(format!("#{}", neighbor.node_id));
+                });
+                row.col(|ui| {
+                    let color =
+                        link_quality_color(neighbor.score, state.poor_limit, state.excellent_limit);
+                    let mut text = egui::RichText::new(format!("{}", neighbor.score));
+                    if let Some(color) = color {
+                        text = text.color(color);
+                    }
+                    ui.label(text);
+                });
+            });
+        });
+}
+
 fn render_connection_matrix_table(
     ui: &mut egui::Ui,
     state: &AppState,
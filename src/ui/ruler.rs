@@ -0,0 +1,165 @@
+//! # Ruler Tool
+//!
+//! Map tool measuring the distance between two points in meters, using the scene's
+//! scaling (`meters_per_unit` or the world size, separately per axis). With "Ruler"
+//! enabled the first click sets the start point, the end follows the pointer until
+//! the second click fixes it, and a third click starts a new measurement. Clicks
+//! near a node snap to its position, so node-to-node distances are exact. While the
+//! ruler is enabled, clicks measure instead of selecting nodes.
+
+use eframe::egui;
+use egui::Color32;
+
+use super::AppState;
+use super::map::MapTransform;
+use crate::common::units::WorldTransform;
+
+/// Clicks within this many pixels of a node snap to the node's position.
+const NODE_SNAP_DISTANCE: f32 = 12.0;
+
+/// Color of the ruler line and its end markers.
+const RULER_COLOR: Color32 = Color32::from_rgb(255, 210, 60);
+
+/// Ruler tool state.
+#[derive(Debug, Clone, Default)]
+pub struct RulerState {
+    /// Whether the ruler tool is active.
+    pub enabled: bool,
+    /// Start point in world units.
+    start: Option<(f64, f64)>,
+    /// Fixed end point in world units (None = follow the pointer).
+    end: Option<(f64, f64)>,
+}
+
+impl RulerState {
+    /// Record a click at `point`: start a measurement, or end the running one.
+    fn click(&mut self, point: (f64, f64)) {
+        match (self.start, self.end) {
+            (Some(_), None) => self.end = Some(point),
+            _ => {
+                self.start = Some(point);
+                self.end = None;
+            }
+        }
+    }
+
+    /// Forget the measurement (tool disabled or a new scene loaded).
+    pub fn clear(&mut self) {
+        self.start = None;
+        self.end = None;
+    }
+}
+
+/// Distance in meters between two points given in world units.
+fn distance_meters(world: &WorldTransform, a: (f64, f64), b: (f64, f64)) -> f64 {
+    world.distance2(a, b).sqrt()
+}
+
+/// Format a distance as meters below 1 km and as kilometers above.
+fn format_distance(meters: f64) -> String {
+    if meters < 1000.0 {
+        format!("{:.1} m", meters)
+    } else {
+        format!("{:.3} km", meters / 1000.0)
+    }
+}
+
+/// Handle ruler clicks and draw the measurement (if the tool is enabled).
+///
+/// # Parameters
+///
+/// * `response` - egui response from the map interaction area
+/// * `painter` - Map painter
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Mutable application state (ruler state, nodes, world scaling)
+///
+/// # Returns
+///
+/// `true` if a click was consumed by the ruler (it must not select a node).
+pub fn handle(
+    response: &egui::Response,
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    state: &mut AppState,
+) -> bool {
+    let transform = MapTransform::new(state.world, rect);
+    // World position of a screen point, snapped to the nearest node within reach
+    let snap = |pos: egui::Pos2| {
+        state
+            .nodes
+            .iter()
+            .map(|node| {
+                let screen = transform.world_to_screen(node.position.x, node.position.y);
+                (screen.distance(pos), (node.position.x, node.position.y))
+            })
+            .filter(|(distance, _)| *distance <= NODE_SNAP_DISTANCE)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or_else(|| transform.screen_to_world(pos), |(_, point)| point)
+    };
+
+    let mut consumed = false;
+    if let Some(click) = response
+        .interact_pointer_pos()
+        .filter(|_| response.clicked())
+    {
+        let point = snap(click);
+        state.ruler.click(point);
+        consumed = true;
+    }
+
+    let Some(start) = state.ruler.start else {
+        return consumed;
+    };
+    let Some(end) = state.ruler.end.or_else(|| response.hover_pos().map(&snap)) else {
+        return consumed;
+    };
+
+    let from = transform.world_to_screen(start.0, start.1);
+    let to = transform.world_to_screen(end.0, end.1);
+    let stroke = egui::Stroke::new(2.0, RULER_COLOR);
+    painter.line_segment([from, to], stroke);
+    for point in [from, to] {
+        painter.circle_stroke(point, 4.0, stroke);
+    }
+    let (dx, dy) = state
+        .world
+        .offset_to_meters(end.0 - start.0, end.1 - start.1);
+    let label = format!(
+        "{}\nΔx {:.1} m, Δy {:.1} m",
+        format_distance(distance_meters(&state.world, start, end)),
+        dx.abs(),
+        dy.abs()
+    );
+    let anchor = from + (to - from) / 2.0 + egui::vec2(8.0, -8.0);
+    let galley = painter.layout_no_wrap(label, egui::FontId::proportional(12.0), RULER_COLOR);
+    let background =
+        egui::Rect::from_min_size(anchor - egui::vec2(0.0, galley.size().y), galley.size())
+            .expand(3.0);
+    painter.rect_filled(background, 3.0, Color32::from_black_alpha(180));
+    painter.galley(background.min + egui::vec2(3.0, 3.0), galley, RULER_COLOR);
+    consumed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_measure_in_scene_meters() {
+        let world = WorldTransform::from_dimensions((0.0, 0.0), (1000.0, 1000.0), 2000.0, 500.0);
+        let mut ruler = RulerState::default();
+        ruler.click((0.0, 0.0));
+        assert_eq!(ruler.end, None);
+        ruler.click((150.0, 800.0));
+        let (start, end) = (ruler.start.unwrap(), ruler.end.unwrap());
+        // 300 m along X and 400 m along Y with non-uniform scaling
+        assert!((distance_meters(&world, start, end) - 500.0).abs() < 1e-9);
+        assert_eq!(format_distance(500.0), "500.0 m");
+        assert_eq!(format_distance(12_345.0), "12.345 km");
+
+        // A third click starts a new measurement
+        ruler.click((10.0, 10.0));
+        assert_eq!(ruler.start, Some((10.0, 10.0)));
+        assert_eq!(ruler.end, None);
+    }
+}
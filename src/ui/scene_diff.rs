@@ -0,0 +1,232 @@
+//! # Scene Diff Window
+//!
+//! Compares two scene files from the mode selector screen before either is loaded,
+//! to confirm what actually differs between experiment variants. The window shows
+//! a mini map of the node layout (unchanged nodes grey, added green, removed red,
+//! moved nodes yellow with a line from the old position) next to the textual list
+//! of node, obstacle and parameter changes (see `common::scene_diff`). The second
+//! scene can be loaded in Simulation mode directly from the window.
+
+use eframe::egui;
+use egui::{Color32, Pos2, Stroke};
+
+use super::{AppState, OperatingMode};
+use crate::common::scene_diff::{NodeChange, Position, SceneDiff, diff_scene_files};
+
+const UNCHANGED_COLOR: Color32 = Color32::from_rgb(140, 140, 140);
+const ADDED_COLOR: Color32 = Color32::from_rgb(80, 200, 90);
+const REMOVED_COLOR: Color32 = Color32::from_rgb(220, 70, 70);
+const MOVED_COLOR: Color32 = Color32::from_rgb(230, 200, 60);
+
+/// Size of the mini map in pixels.
+const MINI_MAP_SIZE: f32 = 320.0;
+
+/// Two compared scene files and their differences.
+pub struct SceneComparison {
+    before_path: String,
+    after_path: String,
+    diff: Result<SceneDiff, String>,
+}
+
+/// Ask for the two scene files and compare them.
+///
+/// Cancelling either file picker leaves the previous comparison untouched.
+///
+/// # Parameters
+///
+/// * `state` - Mutable application state (last simulation scene directory, comparison)
+pub fn pick_and_compare(state: &mut AppState) {
+    let Some(before_path) = pick_scene_file(state, "Select the first (reference) scene") else {
+        return;
+    };
+    let Some(after_path) = pick_scene_file(state, "Select the second scene") else {
+        return;
+    };
+    let diff = diff_scene_files(&before_path, &after_path).map_err(|e| e.to_string());
+    state.scene_comparison = Some(SceneComparison {
+        before_path,
+        after_path,
+        diff,
+    });
+}
+
+/// Open a scene file picker starting in the last simulation scene directory.
+fn pick_scene_file(state: &mut AppState, title: &str) -> Option<String> {
+    let mut dialog = rfd::FileDialog::new()
+        .set_title(title)
+        .add_filter("Scene files", &["json"]);
+    if let Some(dir) = &state.last_open_dir_sim_scene {
+        dialog = dialog.set_directory(dir);
+    }
+    let file = dialog.pick_file()?;
+    if let Some(parent) = file.parent() {
+        state.last_open_dir_sim_scene = Some(parent.to_string_lossy().to_string());
+    }
+    Some(file.to_string_lossy().to_string())
+}
+
+/// Render the scene diff window (if a comparison is open).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    let Some(comparison) = &state.scene_comparison else {
+        return;
+    };
+
+    let mut open = true;
+    let mut load_after = false;
+    let mut compare_again = false;
+    egui::Window::new("Scene Diff")
+        .open(&mut open)
+        .default_width(720.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("scene_diff_files").show(ui, |ui| {
+                ui.label("First:");
+                ui.monospace(&comparison.before_path);
+                ui.end_row();
+                ui.label("Second:");
+                ui.monospace(&comparison.after_path);
+                ui.end_row();
+            });
+            ui.separator();
+
+            match &comparison.diff {
+                Err(err) => {
+                    ui.colored_label(REMOVED_COLOR, err);
+                }
+                Ok(diff) if diff.is_empty() => {
+                    ui.label("The scenes are identical.");
+                }
+                Ok(diff) => {
+                    ui.horizontal_top(|ui| {
+                        render_mini_map(ui, diff);
+                        ui.vertical(|ui| {
+                            ui.label(summary(diff));
+                            egui::ScrollArea::vertical()
+                                .max_height(MINI_MAP_SIZE)
+                                .show(ui, |ui| {
+                                    for line in diff.to_lines() {
+                                        let color = match line.chars().next() {
+                                            Some('+') => ADDED_COLOR,
+                                            Some('-') => REMOVED_COLOR,
+                                            _ => MOVED_COLOR,
+                                        };
+                                        ui.label(
+                                            egui::RichText::new(line).monospace().color(color),
+                                        );
+                                    }
+                                });
+                        });
+                    });
+                }
+            }
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        comparison.diff.is_ok(),
+                        egui::Button::new("Load second scene in Simulation"),
+                    )
+                    .clicked()
+                {
+                    load_after = true;
+                }
+                if ui.button("Compare other scenes…").clicked() {
+                    compare_again = true;
+                }
+            });
+        });
+
+    if load_after {
+        if let Some(comparison) = state.scene_comparison.take() {
+            state.start_session(OperatingMode::Simulation, comparison.after_path, None);
+        }
+    } else if compare_again {
+        pick_and_compare(state);
+    } else if !open {
+        state.scene_comparison = None;
+    }
+}
+
+/// One-line count of the changes.
+fn summary(diff: &SceneDiff) -> String {
+    let count = |f: fn(&NodeChange) -> bool| diff.nodes.iter().filter(|c| f(c)).count();
+    format!(
+        "Nodes: {} added, {} removed, {} moved, {} changed · Obstacles: {} added, {} removed · Parameters: {} changed",
+        count(|c| matches!(c, NodeChange::Added { .. })),
+        count(|c| matches!(c, NodeChange::Removed { .. })),
+        count(|c| matches!(c, NodeChange::Moved { .. })),
+        count(|c| matches!(c, NodeChange::FieldChanged { .. })),
+        diff.obstacles_added.len(),
+        diff.obstacles_removed.len(),
+        diff.parameters.len()
+    )
+}
+
+/// Draw the node layout of both scenes scaled to the mini map.
+fn render_mini_map(ui: &mut egui::Ui, diff: &SceneDiff) {
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(MINI_MAP_SIZE, MINI_MAP_SIZE),
+        egui::Sense::hover(),
+    );
+    let rect = response.rect.shrink(8.0);
+    painter.rect_filled(response.rect, 4.0, Color32::from_gray(25));
+
+    // Fit the bounding box of all positions (both scenes) into the map
+    let positions: Vec<Position> = diff
+        .unchanged_nodes
+        .iter()
+        .map(|(_, p)| *p)
+        .chain(diff.nodes.iter().flat_map(|change| match change {
+            NodeChange::Added { position, .. } | NodeChange::Removed { position, .. } => {
+                vec![*position]
+            }
+            NodeChange::Moved { from, to, .. } => vec![*from, *to],
+            NodeChange::FieldChanged { .. } => Vec::new(),
+        }))
+        .collect();
+    if positions.is_empty() {
+        return;
+    }
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for (x, y) in &positions {
+        min_x = min_x.min(*x);
+        min_y = min_y.min(*y);
+        max_x = max_x.max(*x);
+        max_y = max_y.max(*y);
+    }
+    let scale = (rect.width() as f64 / (max_x - min_x).max(1.0))
+        .min(rect.height() as f64 / (max_y - min_y).max(1.0));
+    let to_screen = |(x, y): Position| {
+        Pos2::new(
+            rect.left() + ((x - min_x) * scale) as f32,
+            rect.top() + ((y - min_y) * scale) as f32,
+        )
+    };
+
+    for (_, position) in &diff.unchanged_nodes {
+        painter.circle_filled(to_screen(*position), 2.5, UNCHANGED_COLOR);
+    }
+    for change in &diff.nodes {
+        match change {
+            NodeChange::Added { position, .. } => {
+                painter.circle_filled(to_screen(*position), 4.0, ADDED_COLOR);
+            }
+            NodeChange::Removed { position, .. } => {
+                painter.circle_stroke(to_screen(*position), 4.0, Stroke::new(1.5, REMOVED_COLOR));
+            }
+            NodeChange::Moved { from, to, .. } => {
+                painter.line_segment(
+                    [to_screen(*from), to_screen(*to)],
+                    Stroke::new(1.0, MOVED_COLOR),
+                );
+                painter.circle_filled(to_screen(*to), 4.0, MOVED_COLOR);
+            }
+            NodeChange::FieldChanged { .. } => {}
+        }
+    }
+}
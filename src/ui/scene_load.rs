@@ -0,0 +1,98 @@
+//! # Scene Load Progress
+//!
+//! Progress window shown while the network task loads a scene (Simulation mode):
+//! reading, validating and spawning the nodes in batches. The Cancel button asks
+//! the network task to abandon the load; the spawned node processes are stopped.
+
+use eframe::egui;
+
+use super::{AppState, UICommand};
+
+/// Progress of the scene load in progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneLoad {
+    /// Current load stage.
+    pub stage: &'static str,
+    /// Completed steps of the stage.
+    pub done: usize,
+    /// Total steps of the stage.
+    pub total: usize,
+    /// Whether the user asked to cancel the load.
+    pub cancel_requested: bool,
+}
+
+impl SceneLoad {
+    /// Completed fraction of the current stage (0..=1).
+    fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.done as f32 / self.total as f32).min(1.0)
+    }
+}
+
+/// Render the scene load progress window (if a load is in progress).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state (scene load progress)
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    let Some(load) = state.scene_load.as_mut() else {
+        return;
+    };
+    let mut cancel = false;
+    egui::Window::new("Loading scene")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(ctx, |ui| {
+            let text = if load.total > 1 {
+                format!("{} ({}/{})", load.stage, load.done, load.total)
+            } else {
+                format!("{}…", load.stage)
+            };
+            ui.label(text);
+            ui.add(
+                egui::ProgressBar::new(load.fraction())
+                    .desired_width(300.0)
+                    .animate(true),
+            );
+            ui.vertical_centered(|ui| {
+                let button = if load.cancel_requested {
+                    "Cancelling…"
+                } else {
+                    "Cancel"
+                };
+                cancel = ui
+                    .add_enabled(!load.cancel_requested, egui::Button::new(button))
+                    .clicked();
+            });
+        });
+    if cancel {
+        load.cancel_requested = true;
+        state
+            .ui_command_tx
+            .try_send(UICommand::CancelSceneLoad)
+            .ok();
+    }
+    ctx.request_repaint();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fraction_is_clamped_and_handles_empty_stages() {
+        let load = |done, total| SceneLoad {
+            stage: "Spawning nodes",
+            done,
+            total,
+            cancel_requested: false,
+        };
+        assert_eq!(load(0, 0).fraction(), 0.0);
+        assert_eq!(load(250, 1000).fraction(), 0.25);
+        assert_eq!(load(5, 4).fraction(), 1.0);
+    }
+}
@@ -0,0 +1,99 @@
+//! # Session Clock
+//!
+//! The simulation runs on the scaled virtual clock of `time_driver`, so its time
+//! drifts away from the wall clock with every speed change, pause and slow frame.
+//! The session clock keeps both starts of the running session: the virtual time
+//! (the clock of every simulation timestamp, measurement and stop condition) and
+//! the wall-clock time. The top panel shows the virtual time since the session
+//! start, the elapsed wall time and the effective average speed, their ratio.
+//!
+//! The clock starts when the mode's task starts running and restarts when a scene
+//! finished loading, so the time spent on the mode selector or building the scene
+//! is not counted.
+
+use embassy_time::{Duration, Instant};
+
+/// Virtual and wall-clock start of the running session.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionClock {
+    virtual_start: Instant,
+    wall_start: std::time::Instant,
+}
+
+impl SessionClock {
+    /// Start the clock now.
+    pub fn start() -> Self {
+        Self {
+            virtual_start: Instant::now(),
+            wall_start: std::time::Instant::now(),
+        }
+    }
+
+    /// Virtual time elapsed since the session start.
+    pub fn virtual_elapsed(&self) -> Duration {
+        Instant::now().saturating_duration_since(self.virtual_start)
+    }
+
+    /// Wall-clock time elapsed since the session start.
+    pub fn wall_elapsed(&self) -> std::time::Duration {
+        self.wall_start.elapsed()
+    }
+
+    /// Effective average speed of the session in percent.
+    pub fn average_speed_percent(&self) -> Option<f64> {
+        average_speed_percent(self.virtual_elapsed(), self.wall_elapsed())
+    }
+}
+
+/// Virtual time covered per wall-clock time in percent (None during the first
+/// second, when the ratio is mostly noise).
+fn average_speed_percent(
+    virtual_elapsed: Duration,
+    wall_elapsed: std::time::Duration,
+) -> Option<f64> {
+    if wall_elapsed < std::time::Duration::from_secs(1) {
+        return None;
+    }
+    Some(virtual_elapsed.as_micros() as f64 / wall_elapsed.as_micros() as f64 * 100.0)
+}
+
+/// Format a duration as `m:ss`, or `h:mm:ss` from one hour on.
+pub fn format_clock(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_speed_and_clock_format() {
+        assert_eq!(
+            average_speed_percent(Duration::from_secs(50), std::time::Duration::from_secs(10)),
+            Some(500.0)
+        );
+        assert_eq!(
+            average_speed_percent(
+                Duration::from_millis(1500),
+                std::time::Duration::from_millis(3000)
+            ),
+            Some(50.0)
+        );
+        assert_eq!(
+            average_speed_percent(
+                Duration::from_secs(5),
+                std::time::Duration::from_millis(500)
+            ),
+            None
+        );
+
+        assert_eq!(format_clock(0), "0:00");
+        assert_eq!(format_clock(125), "2:05");
+        assert_eq!(format_clock(3 * 3600 + 61), "3:01:01");
+    }
+}
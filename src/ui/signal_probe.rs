@@ -0,0 +1,208 @@
+//! # Signal Probe Tool
+//!
+//! Map tool showing the link budget of the selected node at any map location
+//! (Simulation mode): with "Probe signal" enabled the probe follows the pointer,
+//! and a click away from the nodes pins it (a second click releases it); clicking a
+//! node still selects it as the transmitter. The network task computes the
+//! RSSI, SNR margin and line of sight with the scene's propagation model (see
+//! `simulation::signal_probe`); one request is in flight at a time, so moving the
+//! pointer does not flood the command queue. The result is drawn next to the probe
+//! marker, with a line to the transmitter that is red when an obstacle blocks it.
+
+use eframe::egui;
+use egui::Color32;
+
+use super::map::MapTransform;
+use super::{AppState, UICommand};
+use crate::simulation::Point;
+use crate::simulation::signal_probe::SignalProbe;
+
+/// Clicks within this many pixels of a node select the node instead of pinning.
+const NODE_CLICK_DISTANCE: f32 = 12.0;
+
+/// Probe tool state.
+#[derive(Debug, Clone, Default)]
+pub struct SignalProbeState {
+    /// Whether the probe tool is active.
+    pub enabled: bool,
+    /// Pinned probe location in world units (None = follow the pointer).
+    pinned: Option<Point>,
+    /// Transmitter and location of the last request.
+    last_request: Option<(u32, f64, f64)>,
+    /// A request is waiting for its result.
+    in_flight: bool,
+    /// Latest result.
+    result: Option<SignalProbe>,
+}
+
+impl SignalProbeState {
+    /// Command probing `position` for `transmitter_id`, unless a request is in flight
+    /// or the same location was already probed.
+    fn next_request(&mut self, transmitter_id: u32, position: &Point) -> Option<UICommand> {
+        let request = (transmitter_id, position.x.round(), position.y.round());
+        if self.in_flight || self.last_request == Some(request) {
+            return None;
+        }
+        self.in_flight = true;
+        self.last_request = Some(request);
+        Some(UICommand::ProbeSignal(transmitter_id, position.clone()))
+    }
+
+    /// Store the result of a probe request.
+    pub fn receive(&mut self, probe: SignalProbe) {
+        self.in_flight = false;
+        self.result = Some(probe);
+    }
+
+    /// Probe again with the current nodes and obstacles (after an edit or a new scene);
+    /// a request lost while the scene was loading is not waited for.
+    pub fn invalidate(&mut self) {
+        self.last_request = None;
+        self.in_flight = false;
+    }
+}
+
+/// Move or pin the probe, request its link budget and draw it (if the tool is enabled).
+///
+/// # Parameters
+///
+/// * `response` - egui response from the map interaction area
+/// * `painter` - Map painter
+/// * `rect` - Screen-space map rectangle
+/// * `state` - Mutable application state (probe state, selected transmitter)
+///
+/// # Returns
+///
+/// `true` if a click was consumed by pinning or releasing the probe (clicks on a
+/// node are left to the node selection).
+pub fn handle(
+    response: &egui::Response,
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    state: &mut AppState,
+) -> bool {
+    let transform = MapTransform::new(state.world, rect);
+    let to_world = |pos: egui::Pos2| {
+        let (x, y) = transform.screen_to_world(pos);
+        Point { x, y }
+    };
+
+    let mut consumed = false;
+    if let Some(click) = response
+        .interact_pointer_pos()
+        .filter(|_| response.clicked())
+    {
+        let on_node = state.nodes.iter().any(|node| {
+            transform
+                .world_to_screen(node.position.x, node.position.y)
+                .distance(click)
+                <= NODE_CLICK_DISTANCE
+        });
+        if !on_node {
+            let probe = &mut state.signal_probe;
+            probe.pinned = match probe.pinned {
+                Some(_) => None,
+                None => Some(to_world(click)),
+            };
+            consumed = true;
+        }
+    }
+    let Some(position) = state
+        .signal_probe
+        .pinned
+        .clone()
+        .or_else(|| response.hover_pos().map(to_world))
+    else {
+        return consumed;
+    };
+    let marker = transform.world_to_screen(position.x, position.y);
+    let Some(transmitter) = state.selected.and_then(|i| state.nodes.get(i)) else {
+        painter.text(
+            marker + egui::vec2(10.0, -10.0),
+            egui::Align2::LEFT_BOTTOM,
+            "Select a transmitter",
+            egui::FontId::proportional(12.0),
+            Color32::LIGHT_GRAY,
+        );
+        return consumed;
+    };
+
+    let transmitter_id = transmitter.node_id;
+    let source = transform.world_to_screen(transmitter.position.x, transmitter.position.y);
+    if let Some(command) = state.signal_probe.next_request(transmitter_id, &position) {
+        if state.ui_command_tx.try_send(command).is_err() {
+            // Command queue full: retry on the next frame
+            state.signal_probe.invalidate();
+        }
+    }
+
+    let result = state
+        .signal_probe
+        .result
+        .as_ref()
+        .filter(|probe| probe.transmitter_id == transmitter_id);
+    let color = match result {
+        Some(probe) if probe.decodable() => Color32::GREEN,
+        Some(probe) if probe.los_blocked => Color32::RED,
+        Some(_) => Color32::YELLOW,
+        None => Color32::GRAY,
+    };
+    painter.line_segment([source, marker], egui::Stroke::new(1.0, color));
+    painter.circle_stroke(marker, 6.0, egui::Stroke::new(2.0, color));
+    if state.signal_probe.pinned.is_some() {
+        painter.circle_filled(marker, 2.5, color);
+    }
+    if let Some(probe) = result {
+        let text = format!(
+            "#{} → {:.0} m\nRSSI {:.1} dBm\nSNR {:.1} dB (margin {:+.1} dB)\n{}{}",
+            transmitter_id,
+            probe.distance,
+            probe.rssi,
+            probe.snr,
+            probe.snr_margin,
+            if probe.los_blocked {
+                "LOS blocked"
+            } else {
+                "LOS clear"
+            },
+            if probe.out_of_range {
+                ", out of range"
+            } else {
+                ""
+            }
+        );
+        let galley = painter.layout_no_wrap(text, egui::FontId::proportional(12.0), color);
+        let text_rect = egui::Rect::from_min_size(
+            marker + egui::vec2(10.0, 10.0),
+            galley.size() + egui::vec2(8.0, 6.0),
+        );
+        painter.rect_filled(text_rect, 3.0, Color32::from_black_alpha(180));
+        painter.galley(text_rect.min + egui::vec2(4.0, 3.0), galley, color);
+    }
+    consumed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_request_in_flight_and_no_repeats() {
+        let mut state = SignalProbeState::default();
+        let here = Point { x: 100.2, y: 50.0 };
+        assert!(state.next_request(1, &here).is_some());
+        // Still waiting for the result
+        assert!(
+            state
+                .next_request(1, &Point { x: 300.0, y: 50.0 })
+                .is_none()
+        );
+
+        state.in_flight = false;
+        assert!(state.next_request(1, &Point { x: 99.8, y: 50.0 }).is_none());
+        assert!(state.next_request(2, &here).is_some());
+
+        state.invalidate();
+        assert!(state.next_request(2, &here).is_some());
+    }
+}
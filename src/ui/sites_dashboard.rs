@@ -0,0 +1,161 @@
+//! # Sites Dashboard
+//!
+//! Real-time tracking of several deployments at once. The user opens a sites file
+//! (see `analyzer::sites`) from the mode selector; the first site is shown on the
+//! map while a background monitor tails every site's log. The dashboard window
+//! lists each site's nodes alive, traffic rate, alerts and last log line, and
+//! "Open" drills down into a site: the current map view is cleared and the
+//! analyzer restarts on that site's scene and log.
+
+use chrono::Utc;
+use eframe::egui;
+use egui::Color32;
+
+use super::{AppState, OperatingMode, UICommand};
+use crate::analyzer::sites::{SiteConfig, SiteMonitor, load_sites};
+
+const OK_COLOR: Color32 = Color32::from_rgb(80, 200, 90);
+const ALERT_COLOR: Color32 = Color32::from_rgb(230, 140, 60);
+
+/// Sites of a multi-site tracking session and their monitor.
+pub struct SitesDashboard {
+    monitor: SiteMonitor,
+    sites: Vec<SiteConfig>,
+    /// Index of the site shown on the map.
+    active: usize,
+    /// Whether the dashboard window is visible.
+    pub open: bool,
+}
+
+impl SitesDashboard {
+    /// Name of the site shown on the map.
+    pub fn active_name(&self) -> &str {
+        &self.sites[self.active].name
+    }
+}
+
+/// Ask for a sites file, start monitoring its sites and track the first one.
+///
+/// # Parameters
+///
+/// * `state` - Mutable application state (last real-time scene directory, session)
+pub fn pick_and_start(state: &mut AppState) {
+    let mut dialog = rfd::FileDialog::new()
+        .set_title("Select the sites file")
+        .add_filter("Sites files", &["json"]);
+    if let Some(dir) = &state.last_open_dir_rt_scene {
+        dialog = dialog.set_directory(dir);
+    }
+    let Some(file) = dialog.pick_file() else {
+        return;
+    };
+    if let Some(parent) = file.parent() {
+        state.last_open_dir_rt_scene = Some(parent.to_string_lossy().to_string());
+    }
+    let path = file.to_string_lossy().to_string();
+    let sites = match load_sites(&path) {
+        Ok(sites) => sites,
+        Err(e) => {
+            log::error!("Failed to load sites file {}: {}", path, e);
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Sites file")
+                .set_description(format!("Failed to load {}: {}", path, e))
+                .show();
+            return;
+        }
+    };
+
+    let first = sites[0].clone();
+    state.sites = Some(SitesDashboard {
+        monitor: SiteMonitor::start(sites.clone()),
+        sites,
+        active: 0,
+        open: true,
+    });
+    state.start_session(
+        OperatingMode::RealtimeTracking,
+        first.scene,
+        Some(first.log),
+    );
+}
+
+/// Render the sites dashboard window (multi-site sessions only).
+///
+/// # Parameters
+///
+/// * `ctx` - egui context
+/// * `state` - Mutable application state
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    let Some(dashboard) = &mut state.sites else {
+        return;
+    };
+    if !dashboard.open {
+        return;
+    }
+
+    let summaries = dashboard.monitor.summaries();
+    let now = Utc::now();
+    let mut open_site = None;
+    egui::Window::new("Sites")
+        .open(&mut dashboard.open)
+        .default_width(640.0)
+        .show(ctx, |ui| {
+            if summaries.is_empty() {
+                ui.label("Reading the site logs…");
+                return;
+            }
+            egui::Grid::new("sites_dashboard")
+                .striped(true)
+                .num_columns(6)
+                .show(ui, |ui| {
+                    ui.strong("Site");
+                    ui.strong("Alive");
+                    ui.strong("Packets/min");
+                    ui.strong("Last line");
+                    ui.strong("Alerts");
+                    ui.label("");
+                    ui.end_row();
+
+                    for (index, summary) in summaries.iter().enumerate() {
+                        let name = egui::RichText::new(&summary.name);
+                        if index == dashboard.active {
+                            ui.label(name.strong()).on_hover_text("Shown on the map");
+                        } else {
+                            ui.label(name);
+                        }
+                        ui.label(format!("{}/{}", summary.nodes_alive, summary.node_count));
+                        ui.label(summary.packets_per_minute.to_string());
+                        match summary.last_line {
+                            Some(t) => {
+                                ui.label(format!("{} s ago", (now - t).num_seconds().max(0)))
+                            }
+                            None => ui.label("–"),
+                        };
+                        if summary.alerts.is_empty() {
+                            ui.colored_label(OK_COLOR, "OK");
+                        } else {
+                            ui.colored_label(ALERT_COLOR, summary.alerts.join("\n"));
+                        }
+                        if ui
+                            .add_enabled(index != dashboard.active, egui::Button::new("Open"))
+                            .on_hover_text("Show this site on the map")
+                            .clicked()
+                        {
+                            open_site = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if let Some(index) = open_site {
+        dashboard.active = index;
+        let site = dashboard.sites[index].clone();
+        state.clear_session_view();
+        let _ = state.ui_command_tx.try_send(UICommand::OpenSite {
+            scene_path: site.scene,
+            log_path: site.log,
+        });
+    }
+}
@@ -0,0 +1,275 @@
+//! # Inspector Stream Filter
+//!
+//! Filter controls shown above the radio and message stream tables of the node
+//! inspector: message type, direction (sent/received), collisions only, sender ID,
+//! time range and a free text search over the row (type, sender, sequence and the
+//! decoded packet fields). Time bounds use the unit of the table's Time column:
+//! seconds since start in Simulation mode, local `HH:MM[:SS]` in the analyzer modes.
+
+use chrono::{Local, TimeZone, Timelike};
+use eframe::egui;
+
+use super::OperatingMode;
+use super::app_state::message_type_name;
+
+/// Direction filter of the stream tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    All,
+    Sent,
+    Received,
+}
+
+/// Filter settings of the inspector stream tables (shared by both tabs).
+#[derive(Debug, Clone, Default)]
+pub struct StreamFilter {
+    /// Only show this message type (None = all types).
+    pub message_type: Option<u8>,
+    pub direction: Direction,
+    /// Only show collisions and packets missed while transmitting or sleeping (radio stream).
+    pub collisions_only: bool,
+    /// Sender node ID text (empty = any sender).
+    pub sender: String,
+    /// Lower time bound text (empty = open).
+    pub time_from: String,
+    /// Upper time bound text (empty = open).
+    pub time_to: String,
+    /// Case-insensitive text search.
+    pub text: String,
+}
+
+/// One stream table row as seen by the filter.
+pub struct StreamRow<'a> {
+    pub message_type: u8,
+    pub outgoing: bool,
+    pub collision: bool,
+    pub sender: u32,
+    pub sequence: Option<u32>,
+    /// Time in the unit of the Time column (see `row_time`).
+    pub time: u64,
+    /// Decoded packet fields, only evaluated when the text search needs them.
+    pub details: &'a dyn Fn() -> Vec<(&'static str, String)>,
+}
+
+impl StreamFilter {
+    /// Whether any filter is set.
+    pub fn is_active(&self) -> bool {
+        self.message_type.is_some()
+            || self.direction != Direction::All
+            || self.collisions_only
+            || !self.sender.trim().is_empty()
+            || !self.time_from.trim().is_empty()
+            || !self.time_to.trim().is_empty()
+            || !self.text.trim().is_empty()
+    }
+
+    /// Whether a row passes all filters. Unparsable sender or time fields are ignored.
+    pub fn matches(&self, row: &StreamRow) -> bool {
+        if self.message_type.is_some_and(|t| t != row.message_type) {
+            return false;
+        }
+        match self.direction {
+            Direction::Sent if !row.outgoing => return false,
+            Direction::Received if row.outgoing => return false,
+            _ => {}
+        }
+        if self.collisions_only && !row.collision {
+            return false;
+        }
+        let sender = self.sender.trim().trim_start_matches('#').parse::<u32>();
+        if sender.is_ok_and(|sender| sender != row.sender) {
+            return false;
+        }
+        if parse_time_bound(&self.time_from).is_some_and(|from| row.time < from)
+            || parse_time_bound(&self.time_to).is_some_and(|to| row.time > to)
+        {
+            return false;
+        }
+
+        let text = self.text.trim().to_lowercase();
+        if text.is_empty() {
+            return true;
+        }
+        let sequence = row.sequence.map(|s| format!("#{}", s)).unwrap_or_default();
+        let sender = format!("#{}", row.sender);
+        if [message_type_name(row.message_type), &sender, &sequence]
+            .into_iter()
+            .any(|field| field.to_lowercase().contains(&text))
+        {
+            return true;
+        }
+        (row.details)()
+            .iter()
+            .any(|(_, value)| value.to_lowercase().contains(&text))
+    }
+}
+
+/// Parse a time bound: whole seconds (`120`) or a local time of day (`14:05`, `14:05:30`).
+///
+/// # Returns
+///
+/// Seconds (since start, or since local midnight for times of day), or `None` if
+/// the text is empty or invalid.
+fn parse_time_bound(text: &str) -> Option<u64> {
+    let text = text.trim().trim_end_matches('s').trim();
+    if !text.contains(':') {
+        return text.parse().ok();
+    }
+    let parts: Vec<u64> = text
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [h, m] if h < 24 && m < 60 => Some(h * 3600 + m * 60),
+        [h, m, s] if h < 24 && m < 60 && s < 60 => Some(h * 3600 + m * 60 + s),
+        _ => None,
+    }
+}
+
+/// Time of a row in the unit of the Time column.
+///
+/// # Parameters
+///
+/// * `mode` - Current operating mode
+/// * `start_time` - Simulation start (Simulation mode)
+/// * `timestamp` - Row timestamp (virtual time, or Unix epoch in the analyzer modes)
+pub fn row_time(
+    mode: OperatingMode,
+    start_time: embassy_time::Instant,
+    timestamp: embassy_time::Instant,
+) -> u64 {
+    match mode {
+        OperatingMode::Simulation => timestamp.duration_since(start_time).as_secs(),
+        OperatingMode::RealtimeTracking | OperatingMode::LogVisualization => Local
+            .timestamp_opt(timestamp.as_secs() as i64, 0)
+            .single()
+            .map_or(0, |dt| dt.num_seconds_from_midnight() as u64),
+    }
+}
+
+/// Render the filter controls.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context
+/// * `filter` - Filter settings to edit
+/// * `mode` - Current operating mode (selects the time bound hint)
+/// * `with_collisions` - Whether to offer the collisions only checkbox (radio stream)
+pub fn render_controls(
+    ui: &mut egui::Ui,
+    filter: &mut StreamFilter,
+    mode: OperatingMode,
+    with_collisions: bool,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Type:");
+        egui::ComboBox::from_id_source("stream_filter_type")
+            .width(100.0)
+            .selected_text(filter.message_type.map_or("All", message_type_name))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.message_type, None, "All");
+                for message_type in 1..=9 {
+                    ui.selectable_value(
+                        &mut filter.message_type,
+                        Some(message_type),
+                        message_type_name(message_type),
+                    );
+                }
+            });
+        ui.selectable_value(&mut filter.direction, Direction::All, "All");
+        ui.selectable_value(&mut filter.direction, Direction::Sent, "Sent");
+        ui.selectable_value(&mut filter.direction, Direction::Received, "Received");
+        if with_collisions {
+            ui.checkbox(&mut filter.collisions_only, "Collisions only")
+                .on_hover_text("Collisions and packets missed while transmitting");
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("From node:");
+        ui.add(
+            egui::TextEdit::singleline(&mut filter.sender)
+                .desired_width(50.0)
+                .hint_text("ID"),
+        );
+        let hint = match mode {
+            OperatingMode::Simulation => "s",
+            OperatingMode::RealtimeTracking | OperatingMode::LogVisualization => "HH:MM",
+        };
+        ui.label("Time:");
+        ui.add(
+            egui::TextEdit::singleline(&mut filter.time_from)
+                .desired_width(55.0)
+                .hint_text(hint),
+        );
+        ui.label("–");
+        ui.add(
+            egui::TextEdit::singleline(&mut filter.time_to)
+                .desired_width(55.0)
+                .hint_text(hint),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Search:");
+        ui.add(
+            egui::TextEdit::singleline(&mut filter.text)
+                .hint_text("Type, sender, sequence or packet field..."),
+        );
+        if ui
+            .add_enabled(filter.is_active(), egui::Button::new("Clear"))
+            .clicked()
+        {
+            *filter = StreamFilter::default();
+        }
+    });
+    ui.add_space(4.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_matches_rows() {
+        let details = vec![("Block", "seq 42".to_string())];
+        let row = StreamRow {
+            message_type: 6,
+            outgoing: false,
+            collision: false,
+            sender: 17,
+            sequence: Some(42),
+            time: 125,
+            details: &|| details.clone(),
+        };
+
+        assert!(StreamFilter::default().matches(&row));
+        let filter = StreamFilter {
+            message_type: Some(6),
+            direction: Direction::Received,
+            sender: "#17".to_string(),
+            time_from: "120".to_string(),
+            time_to: "130 s".to_string(),
+            text: "SEQ 42".to_string(),
+            ..Default::default()
+        };
+        assert!(filter.matches(&row));
+        assert!(
+            !StreamFilter {
+                collisions_only: true,
+                ..Default::default()
+            }
+            .matches(&row)
+        );
+        assert!(
+            !StreamFilter {
+                time_to: "100".to_string(),
+                ..Default::default()
+            }
+            .matches(&row)
+        );
+
+        assert_eq!(parse_time_bound("14:05:30"), Some(50730));
+        assert_eq!(parse_time_bound("25:00"), None);
+        assert_eq!(parse_time_bound(""), None);
+    }
+}
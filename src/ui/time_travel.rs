@@ -0,0 +1,150 @@
+//! # Time Travel Inspection (Log Visualization)
+//!
+//! Log playback can be paused at any point. While paused, a time slider selects a
+//! past log timestamp between the first log event and the playback position, and
+//! the inspector shows the selected node as of that time:
+//! - radio, message and log streams cut off at the inspected time
+//! - packet counters reconstructed from the analyzer's time-indexed timeline
+//! - reach status of every measurement started up to the inspected time
+//!
+//! The analyzer answers `UICommand::RequestNodeStateAt` with a filtered `NodeInfo`
+//! and a `NodeStateSnapshot`. Resuming playback returns the inspector to live data.
+
+use eframe::egui;
+use embassy_time::{Duration, Instant};
+
+use super::{AppState, NodeStateSnapshot, UICommand};
+
+/// View state of the time travel inspection.
+#[derive(Debug, Clone, Default)]
+pub struct TimeTravelState {
+    /// Whether log playback is paused.
+    pub paused: bool,
+    /// Inspected log timestamp (None = live data).
+    pub inspect_time: Option<Instant>,
+    /// Latest reconstructed state of the selected node at `inspect_time`.
+    pub snapshot: Option<NodeStateSnapshot>,
+}
+
+/// Render the pause/resume button and the inspection time slider.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context (controls column of the top panel)
+/// * `state` - Mutable application state
+pub fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        if state.time_travel.paused {
+            if ui.button("▶ Resume").clicked() {
+                state.time_travel = TimeTravelState::default();
+                state
+                    .ui_command_tx
+                    .try_send(UICommand::SetPlaybackPaused(false))
+                    .ok();
+                state.refresh_selected_node_info();
+            }
+        } else if ui
+            .add_enabled(!state.visualization_ended, egui::Button::new("⏸ Pause"))
+            .on_hover_text("Pause playback to inspect node states at past timestamps")
+            .clicked()
+        {
+            state.time_travel.paused = true;
+            state.time_travel.inspect_time = state.last_simulation_time;
+            state
+                .ui_command_tx
+                .try_send(UICommand::SetPlaybackPaused(true))
+                .ok();
+            state.refresh_selected_node_info();
+        }
+
+        // The slider is available while paused (or after the log ended)
+        let (Some(start), Some(end)) = (state.first_activity_time, state.last_simulation_time)
+        else {
+            return;
+        };
+        if end <= start || !(state.time_travel.paused || state.visualization_ended) {
+            return;
+        }
+
+        let span_secs = (end - start).as_millis() as f64 / 1000.0;
+        let current = state.time_travel.inspect_time.unwrap_or(end);
+        let mut offset_secs = current.saturating_duration_since(start).as_millis() as f64 / 1000.0;
+        let slider = egui::Slider::new(&mut offset_secs, 0.0..=span_secs)
+            .suffix(" s")
+            .text("Inspect at");
+        if ui.add(slider).changed() {
+            state.time_travel.inspect_time =
+                Some(start + Duration::from_millis((offset_secs * 1000.0).round() as u64));
+            state.refresh_selected_node_info();
+        }
+        if state.time_travel.inspect_time.is_some() && ui.button("Live").clicked() {
+            state.time_travel.inspect_time = None;
+            state.time_travel.snapshot = None;
+            state.refresh_selected_node_info();
+        }
+    });
+}
+
+/// Render the reconstructed state of the selected node (when inspecting a past time).
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context (inspector panel)
+/// * `state` - Application state
+/// * `node_id` - Selected node
+pub fn render_snapshot(ui: &mut egui::Ui, state: &AppState, node_id: u32) {
+    let Some(inspect_time) = state.time_travel.inspect_time else {
+        return;
+    };
+    let Some(snapshot) = state
+        .time_travel
+        .snapshot
+        .as_ref()
+        .filter(|s| s.node_id == node_id && s.time == inspect_time)
+    else {
+        ui.label("Reconstructing node state…");
+        return;
+    };
+
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        let time_string = chrono::DateTime::from_timestamp_millis(snapshot.time.as_millis() as i64)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%H:%M:%S%.3f")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "-".to_string());
+        ui.label(egui::RichText::new(format!("State as of {}", time_string)).strong());
+        ui.horizontal(|ui| {
+            ui.label("TX:");
+            ui.label(egui::RichText::new(snapshot.tx_packets.to_string()).strong());
+            ui.add_space(10.0);
+            ui.label("RX:");
+            ui.label(egui::RichText::new(snapshot.rx_packets.to_string()).strong());
+            ui.add_space(10.0);
+            ui.label("CRC errors:");
+            ui.label(egui::RichText::new(snapshot.crc_errors.to_string()).strong());
+        });
+        if snapshot.measurements.is_empty() {
+            ui.label("No measurement started yet.");
+        }
+        for measurement in &snapshot.measurements {
+            let status = match measurement.reached_after {
+                Some(after) => egui::RichText::new(format!(
+                    "reached after {:.1} s",
+                    after.as_millis() as f64 / 1000.0
+                ))
+                .color(egui::Color32::YELLOW),
+                None => egui::RichText::new("not reached").color(egui::Color32::GRAY),
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Measurement #{} from node {}:",
+                    measurement.measurement_id, measurement.origin_node_id
+                ));
+                ui.label(status);
+            });
+        }
+    });
+}
@@ -8,6 +8,12 @@
 //! The panel uses a 3-column layout to organize information clearly and provides
 //! real-time feedback on simulation performance and network behavior.
 
+use crate::simulation::speed_target::SpeedTarget;
+use crate::simulation::unreached_diagnosis::UnreachedDiagnosis;
+use crate::ui::animation_lod::AnimationDetail;
+use crate::ui::app_state::MEASUREMENT_MILESTONE_PERCENTAGES;
+use crate::ui::map::MapView;
+use crate::ui::session_clock::format_clock;
 use crate::ui::{AppState, OperatingMode, UICommand};
 use chrono::{Local, TimeZone};
 use eframe::egui;
@@ -16,7 +22,7 @@ use embassy_time::Duration;
 /// Render the top panel with metrics and controls.
 ///
 /// Creates a fixed-height (150px) top panel with three columns:
-/// 1. System metrics showing simulation time, node count, and packet statistics
+/// 1. System metrics showing simulation and wall time, node count, and packet statistics
 /// 2. Measurement data showing distribution progress and milestone times
 /// 3. Control widgets for adjusting simulation speed and display options
 ///
@@ -28,17 +34,16 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
     egui::TopBottomPanel::top("top_metrics")
         .exact_height(150.0)
         .show(ctx, |ui| {
-            let throughput_tx = if state.start_time.elapsed().as_secs() > 0 {
-                ((state.total_sent_packets as f64 / state.start_time.elapsed().as_secs() as f64)
-                    * 60.0) as u64
+            // Per virtual minute of the session, the time the packets were sent in
+            let session_secs = state.session_clock.virtual_elapsed().as_secs();
+            let throughput_tx = if session_secs > 0 {
+                ((state.total_sent_packets as f64 / session_secs as f64) * 60.0) as u64
             } else {
                 0
             };
 
-            let throughput_rx = if state.start_time.elapsed().as_secs() > 0 {
-                ((state.total_received_packets as f64
-                    / state.start_time.elapsed().as_secs() as f64)
-                    * 60.0) as u64
+            let throughput_rx = if session_secs > 0 {
+                ((state.total_received_packets as f64 / session_secs as f64) * 60.0) as u64
             } else {
                 0
             };
@@ -61,9 +66,8 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                         // Format time based on operating mode
                         let sim_time_str = match state.operating_mode {
                             OperatingMode::Simulation => {
-                                // In simulation mode, show seconds since start
-                                let sim_secs = embassy_time::Instant::now().as_secs();
-                                format!("{:<6}", format!("{}s", sim_secs))
+                                // In simulation mode, show the virtual time since the scene start
+                                format!("{:<7}", format_clock(session_secs))
                             }
                             OperatingMode::RealtimeTracking | OperatingMode::LogVisualization => {
                                 // In analyzer modes, use last_simulation_time with local timezone
@@ -86,6 +90,26 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                             egui::RichText::new(state.total_sent_packets.to_string()).strong(),
                         );
                     });
+                    if state.operating_mode == OperatingMode::Simulation {
+                        let wall_secs = state.session_clock.wall_elapsed().as_secs();
+                        let average_speed = state
+                            .session_clock
+                            .average_speed_percent()
+                            .map_or("-".to_string(), |percent| format!("{:.0}%", percent));
+                        ui.horizontal(|ui| {
+                            ui.label("Wall time:");
+                            ui.label(
+                                egui::RichText::new(format!("{:<7}", format_clock(wall_secs)))
+                                    .monospace()
+                                    .strong(),
+                            );
+                            ui.label("Avg speed:");
+                            ui.label(egui::RichText::new(average_speed).strong())
+                                .on_hover_text(
+                                    "Virtual time covered per wall-clock time since the scene start, including pauses and the time the simulation fell behind",
+                                );
+                        });
+                    }
 
                     let nodes_count_str = format!("{:<7}", state.nodes.len()); // fixed 7 chars, left-aligned (e.g., "42    ")
 
@@ -116,6 +140,144 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                             ui.label("%");
                         });
                     }
+
+                    // Memory estimate of the node histories and queues (Simulation mode)
+                    if let Some(memory_usage) = state.memory_usage {
+                        const MIB: f64 = 1024.0 * 1024.0;
+                        let color = if memory_usage.exceeds_warning() {
+                            egui::Color32::RED
+                        } else {
+                            ui.visuals().text_color()
+                        };
+                        let resident = memory_usage
+                            .resident_bytes
+                            .map_or("-".to_string(), |bytes| {
+                                format!("{:.0}", bytes as f64 / MIB)
+                            });
+                        ui.horizontal(|ui| {
+                            ui.label("Memory (est.):");
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{:.1} MiB",
+                                    memory_usage.estimated_bytes as f64 / MIB
+                                ))
+                                .strong()
+                                .color(color),
+                            )
+                            .on_hover_text(format!(
+                                "Node histories and queues; process RSS: {} MiB, warning above {} MiB",
+                                resident,
+                                memory_usage.warning_bytes as f64 / MIB
+                            ));
+                            ui.label("  Airtime queue:");
+                            ui.label(
+                                egui::RichText::new(memory_usage.airtime_packets.to_string())
+                                    .strong(),
+                            );
+                        });
+                    }
+
+                    // Real-time load of the network loop and its dominant stage
+                    if let Some(report) = &state.loop_profile.report {
+                        let busy = report.busy_percent();
+                        let color = if busy >= super::loop_profile::BUSY_WARNING_PERCENT {
+                            egui::Color32::YELLOW
+                        } else {
+                            ui.visuals().text_color()
+                        };
+                        let dominant = report
+                            .dominant_stage()
+                            .map_or("-", |stage| stage.stage.label());
+                        ui.horizontal(|ui| {
+                            ui.label("Loop busy:");
+                            ui.label(
+                                egui::RichText::new(format!("{:.0}%", busy))
+                                    .strong()
+                                    .color(color),
+                            )
+                            .on_hover_text(format!("Most time spent in: {}", dominant));
+                            if ui.small_button("Profile").clicked() {
+                                state.loop_profile.open = true;
+                            }
+                        });
+                    }
+
+                    // Node tasks losing deliveries or no longer consuming their queue
+                    if !state.node_health.is_empty() {
+                        let stalled = state
+                            .node_health
+                            .iter()
+                            .filter(|report| report.stalled)
+                            .count();
+                        let color = if stalled > 0 {
+                            egui::Color32::RED
+                        } else {
+                            egui::Color32::YELLOW
+                        };
+                        let details: Vec<String> = state
+                            .node_health
+                            .iter()
+                            .map(|report| {
+                                let mut policy = String::new();
+                                if report.blocked_deliveries > 0 {
+                                    policy.push_str(&format!(
+                                        ", clock frozen {} times ({} ms)",
+                                        report.blocked_deliveries, report.blocked_ms
+                                    ));
+                                }
+                                if report.overflowed_messages > 0 {
+                                    policy.push_str(&format!(
+                                        ", {} buffered (now {}, peak {})",
+                                        report.overflowed_messages,
+                                        report.overflow_depth,
+                                        report.peak_overflow
+                                    ));
+                                }
+                                format!(
+                                    "Node {}: {} CAD responses, {} radio packets dropped{}{}",
+                                    report.node_id,
+                                    report.dropped_cad_responses,
+                                    report.dropped_radio_transfers,
+                                    policy,
+                                    match report.full_for_secs {
+                                        Some(secs) if report.stalled => {
+                                            format!(", stalled (queue full for {}s)", secs)
+                                        }
+                                        Some(secs) => format!(", queue full for {}s", secs),
+                                        None => String::new(),
+                                    }
+                                )
+                            })
+                            .collect();
+                        ui.horizontal(|ui| {
+                            ui.label("Unhealthy nodes:");
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} ({} stalled)",
+                                    state.node_health.len(),
+                                    stalled
+                                ))
+                                .strong()
+                                .color(color),
+                            )
+                            .on_hover_text(details.join("\n"));
+                        });
+                    }
+
+                    // Low-priority UI updates shed while the UI was behind
+                    let shed_count = state.ui_refresh_rx.shed_count();
+                    if shed_count > 0 {
+                        ui.horizontal(|ui| {
+                            ui.label("Shed UI updates:");
+                            ui.label(egui::RichText::new(format!("{}", shed_count)).strong())
+                                .on_hover_text(
+                                    "Transmission animations and other cosmetic updates dropped while the UI could not keep up",
+                                );
+                            if ui.small_button("Details").clicked() {
+                                state.refresh_diagnostics.open = true;
+                            }
+                        });
+                    }
                 });
 
                 // Column 2: Measured distribution
@@ -133,108 +295,116 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
 
 /// Render the measurement data column.
 ///
-/// Displays current measurement progress including:
+/// Displays the progress of the displayed measurement including:
 /// - Total measurement time and packet count
 /// - Distribution percentage (what % of nodes have been reached)
 /// - Milestone times (50%, 90%, 100% distribution reached)
 /// - Packets per node ratio for each milestone
 ///
-/// Automatically detects and records milestone times as they are reached.
+/// When several measurements run concurrently, a selector next to the heading
+/// switches which one is displayed (and highlighted on the map). Milestones are
+/// recorded by `MeasurementProgress` as refresh messages arrive. The "Export results"
+/// button saves the displayed measurement (see `result_export`); "History" opens the
+/// browser of finished measurements (see `measurement_history`).
 ///
 /// # Parameters
 ///
 /// * `ui` - egui UI context
-/// * `state` - Mutable state for updating milestone times
+/// * `state` - Mutable state for switching the displayed measurement
 fn render_measured_data(ui: &mut egui::Ui, state: &mut AppState) {
-    let measurement_duration_string = if state.measurement_identifier > 0 {
-        let measurement_total_time_with_s = format!("{}s", state.measurement_total_time);
-        format!("{:<7}", measurement_total_time_with_s)
-    } else {
-        "-".into()
-    };
+    let node_count = state.nodes.len();
 
-    let distribution_percentage = if state.nodes.len() > 0 && state.measurement_identifier > 0 {
-        (state.reached_nodes.len() as f64 / state.nodes.len() as f64) * 100.0
-    } else {
-        0.0
-    };
-
-    let distribution_percentage_string =
-        if state.nodes.len() > 0 && state.measurement_identifier > 0 {
-            format!("{:.0}", distribution_percentage)
-        } else {
-            "-".into()
-        };
-
-    if distribution_percentage >= 50.0 && state.measurement_50_time == 0 {
-        state.measurement_50_time = state.measurement_start_time.elapsed().as_secs();
-        state.measurement_50_message_count = state.measurement_total_message_count;
-    }
-
-    if distribution_percentage >= 90.0 && state.measurement_90_time == 0 {
-        state.measurement_90_time = state.measurement_start_time.elapsed().as_secs();
-        state.measurement_90_message_count = state.measurement_total_message_count;
-    }
+    ui.horizontal(|ui| {
+        ui.heading("Measured data");
+        if state.measurements.len() > 1 {
+            let selected_text = match state.active_measurement() {
+                Some(m) => format!(
+                    "#{} {} from node {}",
+                    state.measurement_identifier,
+                    m.kind.label(),
+                    m.origin_node_id
+                ),
+                None => "-".to_string(),
+            };
+            egui::ComboBox::from_id_source("measurement_selector")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for (id, m) in &state.measurements {
+                        let label = format!(
+                            "#{} {} from node {} ({:.0}%)",
+                            id,
+                            m.kind.label(),
+                            m.origin_node_id,
+                            m.distribution_percentage(node_count)
+                        );
+                        ui.selectable_value(&mut state.measurement_identifier, *id, label);
+                    }
+                });
+        } else if let Some(m) = state.active_measurement() {
+            ui.weak(m.kind.label());
+        }
+        let has_measurement = state.active_measurement().is_some();
+        if ui
+            .add_enabled(has_measurement, egui::Button::new("Export results"))
+            .on_hover_text("Save a reach time map (PNG) and the per-node data (CSV)")
+            .clicked()
+        {
+            super::result_export::export_results(state);
+        }
+        if ui
+            .add_enabled(has_measurement, egui::Button::new("Export replay"))
+            .on_hover_text("Save an animated replay of the measurement as a standalone HTML page")
+            .clicked()
+        {
+            super::replay_export::export_replay(state);
+        }
+        if ui
+            .button("History")
+            .on_hover_text(
+                "Browse, compare and re-open finished measurements of this scene directory",
+            )
+            .clicked()
+        {
+            state.measurement_history.open = true;
+        }
+    });
+    ui.separator();
 
-    if distribution_percentage >= 99.9 && state.measurement_100_time == 0 {
-        state.measurement_100_time = state.measurement_start_time.elapsed().as_secs();
-        state.measurement_100_message_count = state.measurement_total_message_count;
-    }
+    let measurement = state.active_measurement();
 
-    let measurement_50_time_string = if state.measurement_50_time > 0 {
-        format!("{}s", state.measurement_50_time)
-    } else {
-        "-".into()
-    };
-    let measurement_90_time_string = if state.measurement_90_time > 0 {
-        format!("{}s", state.measurement_90_time)
-    } else {
-        "-".into()
-    };
-    let measurement_100_time_string = if state.measurement_100_time > 0 {
-        format!("{}s", state.measurement_100_time)
-    } else {
-        "-".into()
+    let measurement_duration_string = match measurement {
+        Some(m) => format!("{:<7}", format!("{}s", m.total_time)),
+        None => "-".into(),
     };
 
-    let p_per_n_string = if state.measurement_total_message_count > 0 && state.nodes.len() > 0 {
-        format!(
-            "{}",
-            (state.measurement_total_message_count * 100) / state.nodes.len() as u32
-        )
-    } else {
-        "-".into()
+    let distribution_percentage_string = match measurement {
+        Some(m) if node_count > 0 => format!("{:.0}", m.distribution_percentage(node_count)),
+        _ => "-".into(),
     };
 
-    let p_per_n_50_string = if state.measurement_50_message_count > 0 && state.nodes.len() > 0 {
-        format!(
-            "{}",
-            (state.measurement_50_message_count * 100) / state.nodes.len() as u32
-        )
-    } else {
-        "-".into()
-    };
+    let total_message_count = measurement.map_or(0, |m| m.total_message_count);
 
-    let p_per_n_90_string = if state.measurement_90_message_count > 0 && state.nodes.len() > 0 {
-        format!(
-            "{}",
-            (state.measurement_90_message_count * 100) / state.nodes.len() as u32
-        )
+    let p_per_n_string = if total_message_count > 0 && node_count > 0 {
+        format!("{}", (total_message_count * 100) / node_count as u32)
     } else {
         "-".into()
     };
 
-    let p_per_n_100_string = if state.measurement_100_message_count > 0 && state.nodes.len() > 0 {
-        format!(
-            "{}",
-            (state.measurement_100_message_count * 100) / state.nodes.len() as u32
-        )
-    } else {
-        "-".into()
-    };
+    // Milestone time and packets-per-node strings for 50%, 90% and 100%
+    let milestone_strings: Vec<(String, String)> = (0..MEASUREMENT_MILESTONE_PERCENTAGES.len())
+        .map(|i| match measurement.and_then(|m| m.milestones[i]) {
+            Some(milestone) => {
+                let p_per_n = if milestone.message_count > 0 && node_count > 0 {
+                    format!("{}", (milestone.message_count * 100) / node_count as u32)
+                } else {
+                    "-".into()
+                };
+                (format!("{}s", milestone.time), p_per_n)
+            }
+            None => ("-".into(), "-".into()),
+        })
+        .collect();
 
-    ui.heading("Measured data");
-    ui.separator();
     ui.horizontal(|ui| {
         ui.label("Total time: ");
         ui.label(
@@ -243,14 +413,12 @@ fn render_measured_data(ui: &mut egui::Ui, state: &mut AppState) {
                 .monospace(),
         );
         ui.label("packets: ");
-        ui.label(
-            egui::RichText::new(format!("{}", state.measurement_total_message_count)).strong(),
-        );
+        ui.label(egui::RichText::new(format!("{}", total_message_count)).strong());
     });
     ui.horizontal(|ui| {
         ui.label("Distribution: ");
         ui.label(
-            egui::RichText::new(format!("{}", distribution_percentage_string))
+            egui::RichText::new(distribution_percentage_string)
                 .strong()
                 .monospace(),
         );
@@ -259,27 +427,150 @@ fn render_measured_data(ui: &mut egui::Ui, state: &mut AppState) {
         ui.label(egui::RichText::new(p_per_n_string).strong());
         ui.label("%");
     });
+    for (label, (time_string, p_per_n)) in ["50% time: ", "90% time: ", "100% time: "]
+        .into_iter()
+        .zip(milestone_strings)
+    {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.label(egui::RichText::new(time_string).strong());
+            ui.label("   P/N:");
+            ui.label(egui::RichText::new(p_per_n).strong());
+            ui.label("%");
+        });
+    }
+    super::hop_depth::render_summary(ui, state);
+
+    // Stalled measurement: no newly reached node for the configured duration
+    if let Some(stall) = state.active_measurement().and_then(|m| m.stall.as_ref()) {
+        let listed: Vec<String> = stall
+            .unreached_nodes
+            .iter()
+            .take(50)
+            .map(|id| format!("#{}", id))
+            .collect();
+        let more = stall.unreached_nodes.len().saturating_sub(listed.len());
+        let mut unreached_text = format!("Unreached: {}", listed.join(", "));
+        if more > 0 {
+            unreached_text.push_str(&format!(" and {} more", more));
+        }
+        ui.label(
+            egui::RichText::new(format!(
+                "Stalled since {:.0}s: {} nodes unreached",
+                stall.last_progress,
+                stall.unreached_nodes.len()
+            ))
+            .color(egui::Color32::RED),
+        )
+        .on_hover_text(format!(
+            "{}\nThe list is included in \"Export results\" (.stall.csv)",
+            unreached_text
+        ));
+        render_unreached_diagnosis(ui, &stall.diagnosis);
+    }
+}
+
+/// Render the per-node explanation of a stalled measurement's unreached nodes, with
+/// the number of nodes per cause in the header (see `unreached_diagnosis`).
+fn render_unreached_diagnosis(ui: &mut egui::Ui, diagnosis: &[UnreachedDiagnosis]) {
+    if diagnosis.is_empty() {
+        return;
+    }
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for entry in diagnosis {
+        let label = entry.cause.label();
+        match counts.iter_mut().find(|(name, _)| *name == label) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((label, 1)),
+        }
+    }
+    let summary: Vec<String> = counts
+        .iter()
+        .map(|(label, count)| format!("{} {}", count, label))
+        .collect();
+    egui::CollapsingHeader::new(format!("Diagnosis: {}", summary.join(", ")))
+        .id_salt("unreached_diagnosis")
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    for entry in diagnosis {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(egui::RichText::new(format!("#{}", entry.node_id)).strong());
+                            ui.label(format!(
+                                "{}: {}",
+                                entry.cause.label(),
+                                entry.cause.explanation()
+                            ));
+                        });
+                    }
+                });
+        });
+}
+
+/// Render the speed target row: "finish N virtual minutes within M minutes", with the
+/// required speed and the projected finish of the active target (see `speed_target`).
+fn render_speed_target(ui: &mut egui::Ui, state: &mut AppState) {
     ui.horizontal(|ui| {
-        ui.label("50% time: ");
-        ui.label(egui::RichText::new(format!("{}", measurement_50_time_string)).strong());
-        ui.label("   P/N:");
-        ui.label(egui::RichText::new(p_per_n_50_string).strong());
-        ui.label("%");
-    });
-    ui.horizontal(|ui| {
-        ui.label("90% time: ");
-        ui.label(egui::RichText::new(format!("{}", measurement_90_time_string)).strong());
-        ui.label("   P/N:");
-        ui.label(egui::RichText::new(p_per_n_90_string).strong());
-        ui.label("%");
-    });
-    ui.horizontal(|ui| {
-        ui.label("100% time: ");
-        ui.label(egui::RichText::new(format!("{}", measurement_100_time_string)).strong());
-        ui.label("   P/N:");
-        ui.label(egui::RichText::new(p_per_n_100_string).strong());
-        ui.label("%");
+        ui.label("Finish");
+        ui.add(
+            egui::DragValue::new(&mut state.speed_target_virtual_minutes)
+                .range(1..=100_000)
+                .suffix(" virtual min"),
+        );
+        ui.label("within");
+        ui.add(
+            egui::DragValue::new(&mut state.speed_target_wall_minutes)
+                .range(1..=10_000)
+                .suffix(" min"),
+        );
+        if state.speed_target.is_some() {
+            if ui.button("Stop target").clicked() {
+                state.speed_target = None;
+                let _ = state.ui_command_tx.try_send(UICommand::SetSpeedTarget(None));
+            }
+        } else if ui
+            .button("Start target")
+            .on_hover_text(
+                "Adjust the speed to cover the virtual duration within the wall-clock budget, without falling behind",
+            )
+            .clicked()
+        {
+            let _ = state
+                .ui_command_tx
+                .try_send(UICommand::SetSpeedTarget(Some(SpeedTarget {
+                    virtual_secs: u64::from(state.speed_target_virtual_minutes) * 60,
+                    wall_clock_secs: u64::from(state.speed_target_wall_minutes) * 60,
+                })));
+        }
     });
+    let Some(status) = &state.speed_target else {
+        return;
+    };
+    let required = status
+        .required_percent
+        .map_or("budget used up".to_string(), |required| {
+            format!("needs {}%", required)
+        });
+    let text = if status.finished {
+        format!(
+            "Target reached after {:.1} min",
+            status.wall_elapsed_secs / 60.0
+        )
+    } else {
+        format!(
+            "{} (now {}%), done at ~{:.1} of {} min",
+            required,
+            status.current_percent,
+            status.projected_wall_secs / 60.0,
+            status.target.wall_clock_secs / 60
+        )
+    };
+    if status.feasible {
+        ui.label(text);
+    } else {
+        ui.colored_label(egui::Color32::RED, format!("Infeasible: {}", text));
+    }
 }
 
 /// Render the controls column.
@@ -287,8 +578,21 @@ fn render_measured_data(ui: &mut egui::Ui, state: &mut AppState) {
 /// Provides interactive widgets for:
 /// - Speed slider (20% - 1000%): Adjust simulation time scaling
 /// - Auto speed checkbox: Enable automatic speed adjustment based on CPU load
+/// - Speed target: Finish a virtual duration within a wall-clock budget (Simulation)
 /// - Reset button: Return speed to 100% (real-time)
+/// - Stop condition notice and Resume button: Continue a run paused by a stop condition
 /// - Show node IDs checkbox: Toggle node ID labels on the map
+/// - Neighbor graph checkbox: Toggle the neighbor graph overlay on the map
+/// - Edit obstacles checkbox: Toggle runtime obstacle editing (Simulation)
+/// - Move nodes checkbox: Toggle dragging nodes on the map (Simulation)
+/// - Undo/Redo buttons: Revert and re-apply obstacle and topology edits (Simulation)
+/// - Alerts button: Open the alert rule configuration window
+/// - Silent nodes checkbox: Toggle the silent node list (analyzer modes)
+/// - Node table checkbox: Toggle the sortable per-node statistics table
+/// - Pick origin checkbox: Start measurements by clicking nodes on the map
+/// - Node search, Follow checkbox and Reset view button: Locate nodes on the zoomable map
+/// - Animations menu: Show or hide map transmission animations per message type
+/// - Pause/Resume and "Inspect at" slider: Time travel inspection (Log Visualization)
 /// - Delay warning: Display if simulation is running behind schedule
 ///
 /// Controls adapt based on operating mode:
@@ -301,7 +605,16 @@ fn render_measured_data(ui: &mut egui::Ui, state: &mut AppState) {
 /// * `ui` - egui UI context
 /// * `state` - Mutable state for updating control values
 fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.heading("Controls");
+    ui.horizontal(|ui| {
+        ui.heading("Controls");
+        if ui
+            .button("Change mode")
+            .on_hover_text("End this session and return to the mode selector")
+            .clicked()
+        {
+            state.end_session();
+        }
+    });
     ui.separator();
 
     match state.operating_mode {
@@ -315,6 +628,25 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
                 if ui.checkbox(&mut show_ids, "Show node IDs").changed() {
                     state.show_node_ids = show_ids;
                 }
+                ui.checkbox(&mut state.show_neighbor_graph, "Neighbor graph");
+                ui.checkbox(&mut state.show_delivery_flow, "Deliveries")
+                    .on_hover_text(
+                        "Highlight the nodes that recently decoded the selected node's packets",
+                    );
+                ui.checkbox(&mut state.show_collision_map, "Collision map")
+                    .on_hover_text("Heat overlay of collisions at the receiving nodes");
+                super::node_coloring::render_selector(ui, state);
+                if ui.button("Map style").clicked() {
+                    state.show_map_style = true;
+                }
+                ui.checkbox(&mut state.obstacle_editor.enabled, "Edit obstacles");
+                ui.checkbox(&mut state.move_nodes, "Move nodes")
+                    .on_hover_text("Drag nodes on the map to a new position");
+                ui.checkbox(&mut state.signal_probe.enabled, "Probe signal")
+                    .on_hover_text(
+                        "Show the selected node's RSSI, SNR margin and line of sight at the pointer; click the map to pin the probe",
+                    );
+                super::edit_history::render_buttons(ui, state);
             });
             ui.horizontal(|ui| {
                 ui.label("Speed:");
@@ -327,6 +659,18 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
                     crate::time_driver::set_simulation_speed_percent(state.speed_percent);
                 }
             });
+            render_speed_target(ui, state);
+            if let Some(reason) = &state.simulation_stopped {
+                let mut resume = false;
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::YELLOW, format!("Stopped: {}", reason));
+                    resume = ui.button("Resume").clicked();
+                });
+                if resume {
+                    crate::time_driver::set_simulation_paused(false);
+                    state.simulation_stopped = None;
+                }
+            }
             ui.horizontal(|ui| {
                 let mut auto = state.auto_speed_enabled;
                 if ui.checkbox(&mut auto, "Auto speed").changed() {
@@ -339,6 +683,46 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
                     state.speed_percent = 100;
                     crate::time_driver::set_simulation_speed_percent(state.speed_percent);
                 }
+                if ui.button("Alerts").clicked() {
+                    state.alert_monitor.show_rules_window = true;
+                }
+                ui.checkbox(&mut state.node_table.open, "Node table");
+                ui.checkbox(&mut state.region_stats.open, "Regions");
+                ui.checkbox(&mut state.annotations.open, "Annotations")
+                    .on_hover_text("Show, draw and export the scene's map annotations");
+                if ui
+                    .checkbox(&mut state.ruler.enabled, "Ruler")
+                    .on_hover_text("Click two points on the map to measure their distance")
+                    .changed()
+                {
+                    state.ruler.clear();
+                }
+                ui.checkbox(&mut state.interval_marks.open, "Marks")
+                    .on_hover_text(
+                        "Split the session into named windows and compare their statistics",
+                    );
+                ui.checkbox(&mut state.region_stats.drawing, "Draw region")
+                    .on_hover_text("Drag a rectangle on the map to add a statistics region");
+                ui.checkbox(&mut state.event_search.open, "Event search")
+                    .on_hover_text(
+                        "Search the radio events of all nodes by kind, type, node and time",
+                    );
+                ui.checkbox(&mut state.flood_efficiency.open, "Duplicates")
+                    .on_hover_text(
+                        "Share of received flooded packets that were duplicates, over time",
+                    );
+                ui.checkbox(&mut state.pick_measurement_origin, "Pick origin")
+                    .on_hover_text("Click a node on the map to start a measurement from it");
+                if state.backhaul_statistics.is_some() {
+                    ui.checkbox(&mut state.show_backhaul, "Backhaul");
+                }
+                if ui
+                    .button("Export graph")
+                    .on_hover_text("Save the current reachability graph as Graphviz DOT or GraphML")
+                    .clicked()
+                {
+                    export_connectivity_graph(state);
+                }
             });
             ui.horizontal(|ui| {
                 ui.label("Log level:");
@@ -371,11 +755,25 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
             ui.horizontal(|ui| {
                 ui.label("Mode:");
                 ui.label(egui::RichText::new("Real-time Tracking").strong());
+                if let Some(sites) = &state.sites {
+                    ui.label(format!("– {}", sites.active_name()));
+                }
                 ui.add_space(20.0);
                 let mut show_ids = state.show_node_ids;
                 if ui.checkbox(&mut show_ids, "Show node IDs").changed() {
                     state.show_node_ids = show_ids;
                 }
+                ui.checkbox(&mut state.show_neighbor_graph, "Neighbor graph");
+                ui.checkbox(&mut state.show_delivery_flow, "Deliveries")
+                    .on_hover_text(
+                        "Highlight the nodes that recently decoded the selected node's packets",
+                    );
+                ui.checkbox(&mut state.show_collision_map, "Collision map")
+                    .on_hover_text("Heat overlay of collisions at the receiving nodes");
+                super::node_coloring::render_selector(ui, state);
+                if ui.button("Map style").clicked() {
+                    state.show_map_style = true;
+                }
             });
             ui.horizontal(|ui| {
                 ui.label("Delay:");
@@ -383,6 +781,34 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
                 let mins = total_secs / 60;
                 let secs = total_secs % 60;
                 ui.label(egui::RichText::new(format!("{:02}:{:02}", mins, secs)).strong());
+                ui.add_space(20.0);
+                if ui.button("Alerts").clicked() {
+                    state.alert_monitor.show_rules_window = true;
+                }
+                ui.checkbox(&mut state.show_silent_nodes, "Silent nodes");
+                ui.checkbox(&mut state.node_table.open, "Node table");
+                ui.checkbox(&mut state.region_stats.open, "Regions");
+                ui.checkbox(&mut state.annotations.open, "Annotations")
+                    .on_hover_text("Show, draw and export the scene's map annotations");
+                if ui
+                    .checkbox(&mut state.ruler.enabled, "Ruler")
+                    .on_hover_text("Click two points on the map to measure their distance")
+                    .changed()
+                {
+                    state.ruler.clear();
+                }
+                ui.checkbox(&mut state.interval_marks.open, "Marks")
+                    .on_hover_text(
+                        "Split the session into named windows and compare their statistics",
+                    );
+                ui.checkbox(&mut state.region_stats.drawing, "Draw region")
+                    .on_hover_text("Drag a rectangle on the map to add a statistics region");
+                ui.checkbox(&mut state.pick_measurement_origin, "Pick origin")
+                    .on_hover_text("Click a node on the map to start a measurement from it");
+                if let Some(sites) = &mut state.sites {
+                    ui.checkbox(&mut sites.open, "Sites")
+                        .on_hover_text("Dashboard of all sites of the sites file");
+                }
             });
 
             // Network Commands section
@@ -437,7 +863,7 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
             });
         }
         OperatingMode::LogVisualization => {
-            // Log visualization: speed controls but no auto-speed
+            // Log visualization: speed controls drive the playback through the virtual clock
             ui.horizontal(|ui| {
                 ui.label("Mode:");
                 ui.label(egui::RichText::new("Log Visualization").strong());
@@ -446,6 +872,17 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
                 if ui.checkbox(&mut show_ids, "Show node IDs").changed() {
                     state.show_node_ids = show_ids;
                 }
+                ui.checkbox(&mut state.show_neighbor_graph, "Neighbor graph");
+                ui.checkbox(&mut state.show_delivery_flow, "Deliveries")
+                    .on_hover_text(
+                        "Highlight the nodes that recently decoded the selected node's packets",
+                    );
+                ui.checkbox(&mut state.show_collision_map, "Collision map")
+                    .on_hover_text("Heat overlay of collisions at the receiving nodes");
+                super::node_coloring::render_selector(ui, state);
+                if ui.button("Map style").clicked() {
+                    state.show_map_style = true;
+                }
             });
             ui.horizontal(|ui| {
                 ui.label("Speed:");
@@ -459,14 +896,47 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
                 }
             });
             ui.horizontal(|ui| {
+                let mut auto = state.auto_speed_enabled;
+                if ui
+                    .checkbox(&mut auto, "Auto speed")
+                    .on_hover_text("Play as fast as the log can be processed")
+                    .changed()
+                {
+                    state.auto_speed_enabled = auto;
+                    let _ = state
+                        .ui_command_tx
+                        .try_send(UICommand::SetAutoSpeed(state.auto_speed_enabled));
+                }
                 if ui.button("Reset").clicked() {
                     state.speed_percent = 100;
                     crate::time_driver::set_simulation_speed_percent(state.speed_percent);
                 }
+                ui.checkbox(&mut state.show_silent_nodes, "Silent nodes");
+                ui.checkbox(&mut state.node_table.open, "Node table");
+                ui.checkbox(&mut state.region_stats.open, "Regions");
+                ui.checkbox(&mut state.annotations.open, "Annotations")
+                    .on_hover_text("Show, draw and export the scene's map annotations");
+                if ui
+                    .checkbox(&mut state.ruler.enabled, "Ruler")
+                    .on_hover_text("Click two points on the map to measure their distance")
+                    .changed()
+                {
+                    state.ruler.clear();
+                }
+                ui.checkbox(&mut state.interval_marks.open, "Marks")
+                    .on_hover_text(
+                        "Split the session into named windows and compare their statistics",
+                    );
+                ui.checkbox(&mut state.region_stats.drawing, "Draw region")
+                    .on_hover_text("Drag a rectangle on the map to add a statistics region");
             });
+            super::time_travel::render_controls(ui, state);
         }
     }
 
+    render_node_search(ui, state);
+    render_animation_filter(ui, state);
+
     // Show delay warning for simulation mode
     if state.operating_mode == OperatingMode::Simulation
         && state.simulation_delay > Duration::from_millis(10)
@@ -485,3 +955,163 @@ fn render_controls(ui: &mut egui::Ui, state: &mut AppState) {
         ui.label(egui::RichText::new("✅ Log visualization complete").color(egui::Color32::GREEN));
     }
 }
+
+/// Message types with transmission animations on the map, with their names.
+const ANIMATION_MESSAGE_TYPES: [(u8, &str); 9] = [
+    (1, "Echo request"),
+    (2, "Echo"),
+    (3, "Echo result"),
+    (4, "Request block"),
+    (5, "Request block part"),
+    (6, "Add block"),
+    (7, "Add transaction"),
+    (8, "Request mempool"),
+    (9, "Support"),
+];
+
+/// Render the map animation filter: one checkbox per message type.
+///
+/// Hiding e.g. the echo floods keeps measurement propagation (Add block) visible
+/// in chatty networks. Counters and the inspector are not affected.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context
+/// * `state` - Mutable application state (hidden animation types)
+/// Ask for a file name and let the network task write the connectivity graph
+/// (GraphML for a `.graphml` name, Graphviz DOT otherwise).
+fn export_connectivity_graph(state: &mut AppState) {
+    let file_name = format!("connectivity_{}.dot", Local::now().format("%Y%m%d_%H%M%S"));
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("Graphviz DOT", &["dot", "gv"])
+        .add_filter("GraphML", &["graphml"])
+        .set_file_name(file_name)
+        .save_file()
+    else {
+        return;
+    };
+    let _ = state
+        .ui_command_tx
+        .try_send(UICommand::ExportConnectivityGraph(
+            path.to_string_lossy().to_string(),
+        ));
+}
+
+fn render_animation_filter(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        let mut title = if state.hidden_animation_types.is_empty() {
+            "Animations".to_string()
+        } else {
+            format!("Animations ({} hidden)", state.hidden_animation_types.len())
+        };
+        let detail = state.animation_lod.detail();
+        if detail != AnimationDetail::Full {
+            title.push_str(" – reduced detail");
+        }
+        ui.menu_button(title, |ui| {
+            for (message_type, name) in ANIMATION_MESSAGE_TYPES {
+                let mut shown = !state.hidden_animation_types.contains(&message_type);
+                let label = egui::RichText::new(name)
+                    .color(super::app_state::color_for_message_type(message_type, 1.0));
+                if ui.checkbox(&mut shown, label).changed() {
+                    if shown {
+                        state.hidden_animation_types.remove(&message_type);
+                    } else {
+                        state.hidden_animation_types.insert(message_type);
+                    }
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Show all").clicked() {
+                    state.hidden_animation_types.clear();
+                }
+                if ui.button("Only Add block").clicked() {
+                    state.hidden_animation_types = ANIMATION_MESSAGE_TYPES
+                        .iter()
+                        .map(|(message_type, _)| *message_type)
+                        .filter(|message_type| *message_type != 6)
+                        .collect();
+                }
+            });
+            ui.separator();
+            let settings = &mut state.animation_lod.settings;
+            ui.checkbox(&mut settings.enabled, "Adaptive detail")
+                .on_hover_text(
+                    "Skip the pulses of short packets and merge nearby pulses while many transmissions are active",
+                );
+            ui.add_enabled_ui(settings.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Above");
+                    ui.add(egui::DragValue::new(&mut settings.rate_threshold).range(1..=10_000));
+                    ui.label("transmissions/s");
+                });
+            });
+            let status = match detail {
+                AnimationDetail::Full => "full",
+                AnimationDetail::Reduced => "short packets skipped",
+                AnimationDetail::Aggregated => "short packets skipped, pulses merged",
+            };
+            ui.weak(format!(
+                "{} transmissions/s, detail: {}",
+                state.animation_lod.rate(),
+                status
+            ));
+        })
+        .response
+        .on_hover_text("Show or hide map transmission animations by message type");
+    });
+}
+
+/// Render the node search row: find a node by ID, follow the selected node, reset the map view.
+///
+/// Pressing Enter or "Find" selects the node and centers the map on it, zooming in
+/// to at least `SEARCH_ZOOM`.
+///
+/// # Parameters
+///
+/// * `ui` - egui UI context
+/// * `state` - Mutable application state (search text, selection, map view)
+fn render_node_search(ui: &mut egui::Ui, state: &mut AppState) {
+    const SEARCH_ZOOM: f32 = 4.0;
+
+    ui.horizontal(|ui| {
+        ui.label("Find node:");
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut state.node_search)
+                .desired_width(80.0)
+                .hint_text("ID"),
+        );
+        let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let node_id = state
+            .node_search
+            .trim()
+            .trim_start_matches('#')
+            .parse::<u32>()
+            .ok()
+            .filter(|id| state.nodes.iter().any(|n| n.node_id == *id));
+
+        let find_clicked = ui.button("Find").clicked();
+        if let Some(node_id) = node_id.filter(|_| find_clicked || submitted) {
+            if let Some(node) = state.nodes.iter().find(|n| n.node_id == node_id) {
+                let (tx, ty) = state.world.normalize(node.position.x, node.position.y);
+                state.map_view.zoom = state.map_view.zoom.max(SEARCH_ZOOM);
+                state.map_view.center_on((tx as f32, ty as f32));
+            }
+            state.select_node(node_id);
+        }
+        if !state.node_search.trim().is_empty() && node_id.is_none() {
+            ui.label(egui::RichText::new("no such node").color(egui::Color32::GRAY));
+        }
+
+        ui.checkbox(&mut state.follow_selected, "Follow")
+            .on_hover_text("Keep the selected node centered on the map");
+        if ui
+            .add_enabled(state.map_view.zoom > 1.0, egui::Button::new("Reset view"))
+            .on_hover_text("Zoom: mouse wheel, pan: right mouse button drag")
+            .clicked()
+        {
+            state.map_view = MapView::default();
+        }
+    });
+}